@@ -1,25 +1,300 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, BufRead, IsTerminal, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use std::sync::{atomic::{AtomicU64, Ordering}, Arc, Condvar, Mutex};
-use std::time::Duration;
+use std::process::{Command, Stdio};
+use std::net::IpAddr;
+use std::sync::{atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering}, Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
 use tiny_http::{Header, Method, Response, Server, StatusCode};
-use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use time::{format_description::well_known::Rfc3339, Date, Month, OffsetDateTime, UtcOffset};
+use unicode_segmentation::UnicodeSegmentation;
 
-const DEFAULT_FOLDERS: [(&str, &str); 4] = [
-    ("backlog", "Backlog"),
-    ("planned", "Planned"),
-    ("in_progress", "In Progress"),
-    ("done", "Done"),
+type TemplateColumns = &'static [(&'static str, Option<u32>)];
+/// Tasks by column id, as the loaders return them.
+type Folders = HashMap<String, Vec<Task>>;
+
+const DEFAULT_TEMPLATE: &str = "default";
+const DEFAULT_PORT: u16 = 8787;
+const DEFAULT_LANG: &str = "en";
+/// Board templates for a fresh `.workspace-kanban`: column ids and WIP limits.
+/// Titles come from `COLUMN_TITLES` in the chosen language.
+const BOARD_TEMPLATES: [(&str, TemplateColumns); 4] = [
+    (
+        "default",
+        &[("backlog", None), ("planned", None), ("in_progress", None), ("done", None)],
+    ),
+    ("simple", &[("todo", None), ("in_progress", None), ("done", None)]),
+    (
+        "scrum",
+        &[
+            ("backlog", None),
+            ("sprint", None),
+            ("in_progress", Some(3)),
+            ("review", Some(2)),
+            ("done", None),
+        ],
+    ),
+    (
+        "gtd",
+        &[("inbox", None), ("next", Some(5)), ("waiting", None), ("someday", None), ("done", None)],
+    ),
+];
+/// Column titles per language; English is the fallback for missing entries.
+const COLUMN_TITLES: [(&str, &[(&str, &str)]); 4] = [
+    (
+        "en",
+        &[
+            ("backlog", "Backlog"),
+            ("planned", "Planned"),
+            ("in_progress", "In Progress"),
+            ("done", "Done"),
+            ("todo", "To Do"),
+            ("sprint", "Sprint"),
+            ("review", "Review"),
+            ("inbox", "Inbox"),
+            ("next", "Next Actions"),
+            ("waiting", "Waiting For"),
+            ("someday", "Someday"),
+        ],
+    ),
+    (
+        "de",
+        &[
+            ("backlog", "Backlog"),
+            ("planned", "Geplant"),
+            ("in_progress", "In Arbeit"),
+            ("done", "Fertig"),
+            ("todo", "Zu erledigen"),
+            ("sprint", "Sprint"),
+            ("review", "Review"),
+            ("inbox", "Eingang"),
+            ("next", "Nächste Schritte"),
+            ("waiting", "Wartet"),
+            ("someday", "Irgendwann"),
+        ],
+    ),
+    (
+        "fr",
+        &[
+            ("backlog", "Backlog"),
+            ("planned", "Planifié"),
+            ("in_progress", "En cours"),
+            ("done", "Terminé"),
+            ("todo", "À faire"),
+            ("sprint", "Sprint"),
+            ("review", "Revue"),
+            ("inbox", "Boîte de réception"),
+            ("next", "Prochaines actions"),
+            ("waiting", "En attente"),
+            ("someday", "Un jour"),
+        ],
+    ),
+    (
+        "es",
+        &[
+            ("backlog", "Pendientes"),
+            ("planned", "Planificado"),
+            ("in_progress", "En curso"),
+            ("done", "Hecho"),
+            ("todo", "Por hacer"),
+            ("sprint", "Sprint"),
+            ("review", "Revisión"),
+            ("inbox", "Bandeja de entrada"),
+            ("next", "Próximas acciones"),
+            ("waiting", "En espera"),
+            ("someday", "Algún día"),
+        ],
+    ),
+];
+/// Codes of the errors answered through `respond_error`; each needs an
+/// English entry in `MESSAGES`.
+const ERROR_CODES: [&str; 20] = [
+    "not_found",
+    "task_not_found",
+    "unknown_action",
+    "method_not_allowed",
+    "invalid_id",
+    "root_missing",
+    "config_missing",
+    "board_full",
+    "column_frozen",
+    "column_locked",
+    "snapshot_not_found",
+    "share_not_found",
+    "share_expired",
+    "admin_required",
+    "subscription_not_found",
+    "id_conflict",
+    "view_not_found",
+    "rule_broken",
+    "readme_not_found",
+    "delivery_not_found",
+];
+/// Server-generated text per language, keyed by error code or report
+/// string; `{name}` placeholders are filled by `message`. English is the
+/// fallback for missing entries.
+const MESSAGES: [(&str, &[(&str, &str)]); 2] = [
+    (
+        "en",
+        &[
+            ("not_found", "not found"),
+            ("task_not_found", "Task not found"),
+            ("unknown_action", "Unknown task action"),
+            ("method_not_allowed", "Method not allowed here; use {allowed}"),
+            ("invalid_id", "invalid id: {id} (ids use a-z, 0-9, '-' and '.')"),
+            ("root_missing", ROOT_MISSING),
+            ("config_missing", CONFIG_MISSING),
+            ("board_full", "Board is full: {current} of {max} tasks (max_tasks); delete or archive tasks first"),
+            ("column_frozen", "Column {column} is frozen (wip=0) and accepts no new tasks"),
+            (
+                "column_locked",
+                "Column {column} is locked; tasks cannot be added to or removed from it without the admin token",
+            ),
+            ("snapshot_not_found", "snapshot not found"),
+            ("share_not_found", "Share link not found"),
+            ("share_expired", "Share link has expired"),
+            ("admin_required", "admin token required"),
+            ("subscription_not_found", "Subscription not found"),
+            ("id_conflict", "Task id already exists: {id}"),
+            ("view_not_found", "View not found"),
+            ("rule_broken", "The task breaks the column rule \"{rule}\""),
+            ("readme_not_found", "This board has no read-me"),
+            ("delivery_not_found", "Webhook delivery not found"),
+            ("report.column", "Column `{column}`"),
+            ("report.column_wip", "Column `{column}`, WIP limit {limit}"),
+            ("report.column_frozen", "Column `{column}`, frozen (WIP limit 0)"),
+            ("report.no_tasks", "_No tasks._"),
+            ("report.since", "Since {name} ({taken_at})"),
+            ("report.moved", "Moved"),
+            ("report.added", "Added"),
+            ("report.edited", "Edited"),
+            ("report.removed", "Removed"),
+            ("report.no_changes", "No changes."),
+        ],
+    ),
+    (
+        "de",
+        &[
+            ("not_found", "nicht gefunden"),
+            ("task_not_found", "Aufgabe nicht gefunden"),
+            ("unknown_action", "Unbekannte Aufgabenaktion"),
+            ("method_not_allowed", "Methode hier nicht erlaubt; erlaubt: {allowed}"),
+            ("invalid_id", "Ungültige ID: {id} (IDs bestehen aus a-z, 0-9, '-' und '.')"),
+            ("root_missing", "Board-Verzeichnis fehlt"),
+            (
+                "config_missing",
+                "Board-Konfiguration fehlt; Server neu starten oder .workspace-kanban wiederherstellen",
+            ),
+            (
+                "board_full",
+                "Board ist voll: {current} von {max} Aufgaben (max_tasks); erst Aufgaben löschen oder archivieren",
+            ),
+            ("column_frozen", "Spalte {column} ist eingefroren (wip=0) und nimmt keine neuen Aufgaben an"),
+            (
+                "column_locked",
+                "Spalte {column} ist gesperrt; ohne Admin-Token können keine Aufgaben hinzugefügt oder entfernt werden",
+            ),
+            ("snapshot_not_found", "Snapshot nicht gefunden"),
+            ("share_not_found", "Freigabelink nicht gefunden"),
+            ("share_expired", "Freigabelink ist abgelaufen"),
+            ("admin_required", "Admin-Token erforderlich"),
+            ("subscription_not_found", "Abonnement nicht gefunden"),
+            ("id_conflict", "Aufgaben-ID ist bereits vergeben: {id}"),
+            ("view_not_found", "Ansicht nicht gefunden"),
+            ("rule_broken", "Die Aufgabe verstößt gegen die Spaltenregel \"{rule}\""),
+            ("readme_not_found", "Dieses Board hat keine Liesmich-Datei"),
+            ("delivery_not_found", "Webhook-Zustellung nicht gefunden"),
+            ("report.column", "Spalte `{column}`"),
+            ("report.column_wip", "Spalte `{column}`, WIP-Limit {limit}"),
+            ("report.column_frozen", "Spalte `{column}`, eingefroren (WIP-Limit 0)"),
+            ("report.no_tasks", "_Keine Aufgaben._"),
+            ("report.since", "Seit {name} ({taken_at})"),
+            ("report.moved", "Verschoben"),
+            ("report.added", "Hinzugefügt"),
+            ("report.edited", "Bearbeitet"),
+            ("report.removed", "Entfernt"),
+            ("report.no_changes", "Keine Änderungen."),
+        ],
+    ),
 ];
 const CONFIG_FILE: &str = ".workspace-kanban";
+const ROOT_MISSING: &str = "root missing";
 const THEME_FILE: &str = ".kanban-theme.conf";
+/// JSON alternative to `THEME_FILE`; its settings win where both set one.
+const THEME_JSON_FILE: &str = ".kanban-theme.json";
+const HISTORY_DIR: &str = ".history";
+/// Named board snapshots, one JSON file each; a dot-directory, so reconcile
+/// never treats it as a column.
+const SNAPSHOTS_DIR: &str = ".kanban-snapshots";
+/// Snapshots kept; taking another one prunes the oldest.
+const SNAPSHOT_LIMIT: usize = 30;
+const ATTACHMENTS_DIR: &str = "_attachments";
+/// Where `doctor --relocate-foreign` puts files found in column folders,
+/// under `ATTACHMENTS_DIR`, one subfolder per column. Not a valid task id.
+const LOOSE_FILES_DIR: &str = "_loose";
+/// Optional `favicon.svg`, `favicon.png` and `manifest.json` for this board,
+/// looked up here before the root. A dot-directory, so reconcile and task
+/// scans skip it, as they skip root files.
+const ASSETS_DIR: &str = ".kanban-assets";
+/// Larger board assets are ignored in favour of the bundled default.
+const BOARD_ASSET_MAX_BYTES: u64 = 1024 * 1024;
+/// Seconds a browser may reuse a favicon or manifest before revalidating.
+const BOARD_ASSET_MAX_AGE: u32 = 300;
+/// Extra directory names (one per line) that the board never treats as
+/// columns, on top of `INTERNAL_DIRS` and every dot-directory.
+const IGNORE_FILE: &str = ".kanbanignore";
+/// Team members, one name per line; lets reports list people without tasks.
+const USERS_FILE: &str = ".kanban-users";
+/// `old-id -> new-id` lines appended when a rename changes a task id, so old
+/// links keep resolving. A root file, so reconcile and task scans (which only
+/// look at folders) never pick it up.
+const ALIASES_FILE: &str = ".kanban-aliases";
+/// Directories that belong to the server or to tooling, never to the board.
+const INTERNAL_DIRS: [&str; 4] = [ATTACHMENTS_DIR, "_archive", "node_modules", "target"];
+/// Append-only log of board activity, one JSON object per line.
+const ACTIVITY_FILE: &str = ".kanban-activity.jsonl";
+const ATTACHMENT_MAX_BYTES: usize = 10 * 1024 * 1024;
+/// Extensions accepted for uploads, with the MIME type they are served as.
+/// Anything that a browser could execute (html, svg, js) is left out.
+const ATTACHMENT_TYPES: [(&str, &str); 14] = [
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("pdf", "application/pdf"),
+    ("txt", "text/plain; charset=utf-8"),
+    ("log", "text/plain; charset=utf-8"),
+    ("md", "text/markdown; charset=utf-8"),
+    ("csv", "text/csv; charset=utf-8"),
+    ("json", "application/json"),
+    ("zip", "application/zip"),
+    ("gz", "application/gzip"),
+    ("mp4", "video/mp4"),
+];
+const DEFAULT_HISTORY_LIMIT: usize = 20;
+const DEFAULT_MAX_TASKS_PER_RESPONSE: usize = 5000;
+const DEFAULT_MAX_COLUMNS: usize = 50;
+/// `lint_columns` warns when more columns than this have no WIP limit.
+const LINT_UNLIMITED_COLUMNS: usize = 10;
+/// Whether the board's file system ignores case in file names (macOS and
+/// Windows defaults). Probed once at startup; unset counts as case-sensitive.
+static CASE_INSENSITIVE_FS: OnceLock<bool> = OnceLock::new();
+/// Hook processes currently running; new events are dropped at `HOOK_MAX_RUNNING`.
+static HOOKS_RUNNING: AtomicUsize = AtomicUsize::new(0);
+/// Bumped when an existing API response changes incompatibly.
+const API_VERSION: u32 = 1;
+/// Versioned route prefixes under `/api/`, oldest first.
+const API_VERSIONS: [&str; 1] = ["v1"];
+/// When the unprefixed `/api/...` routes go away; sent as their `Sunset`.
+const LEGACY_API_SUNSET: &str = "Thu, 01 Apr 2027 00:00:00 GMT";
 const INDEX_HTML: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/../web/index.html"));
 const APP_JS: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/../web/app.js"));
 const STYLES_CSS: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/../web/styles.css"));
+/// Served when the board brings no favicon of its own.
+const FAVICON_SVG: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/../web/favicon.svg"));
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct Task {
     id: String,
@@ -27,29 +302,441 @@ struct Task {
     description: String,
     creator: String,
     assigned_to: String,
-    created_at: String,
-    updated_at: String,
+    created_at: Timestamp,
+    updated_at: Timestamp,
     status: String,
     tags: Vec<String>,
     folder: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    icon: Option<String>,
+    #[serde(default)]
+    mentions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    /// Effort in the team's own unit (points, hours).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimate: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completed_at: Option<Timestamp>,
+    /// When the task entered its current column.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    column_since: Option<Timestamp>,
+    /// Whole days since `column_since`; computed per listing, never stored.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    days_in_column: Option<i64>,
+    /// Holder and expiry of an editing lock; set per listing, never stored.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    locked_by: Option<String>,
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    locked_until: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<Attachment>,
+    /// Headers the server does not know, in file order. Fields declared on
+    /// the board get typed values (see `type_fields`); others stay strings.
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    extra: serde_json::Map<String, serde_json::Value>,
+    /// Where the task lives on disk; filled in when the file is read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file: Option<TaskFile>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Header keys `parse_task` maps to `Task` fields; anything else is kept in
+/// `extra` and written back unchanged.
+const TASK_HEADERS: [&str; 14] = [
+    "creator",
+    "assigned_to",
+    "created_at",
+    "updated_at",
+    "status",
+    "tags",
+    "color",
+    "icon",
+    "due",
+    "estimate",
+    "completed_at",
+    "column_since",
+    "attachments",
+    "title",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct TaskFile {
+    /// Relative to the board root, always with forward slashes.
+    path: String,
+    size: u64,
+    modified: Option<String>,
+}
+
+fn task_file(path: &Path, folder: &str) -> Option<TaskFile> {
+    let meta = fs::metadata(path).ok()?;
+    let name = path.file_name()?.to_string_lossy();
+    Some(TaskFile {
+        path: format!("{}/{}", folder, name),
+        size: meta.len(),
+        modified: meta.modified().ok().map(|at| format_timestamp(OffsetDateTime::from(at))),
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct Attachment {
+    name: String,
+    size: u64,
+    url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 struct BoardColumn {
     id: String,
     title: String,
+    /// `Some(0)` freezes the column: it keeps its tasks but accepts no new
+    /// ones.
     wip_limit: Option<u32>,
+    #[serde(default)]
+    default_tags: Vec<String>,
+    #[serde(default)]
+    default_assignee: Option<String>,
+    #[serde(default)]
+    done: bool,
+    /// Task template (`.kanban-templates/<id>.md`) for tasks created here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+    /// Default task order (`sort=`, one of `COLUMN_SORT_KEYS`); listings
+    /// that pass `?sort=` ignore it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sort: Option<String>,
+    /// `asc` (default) or `desc`, from the `dir=` attribute.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sort_dir: Option<String>,
+    /// People who take turns getting unassigned tasks that enter the column.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    auto_assign: Vec<String>,
+    /// `locked=true`: tasks cannot be created in, moved into or moved out of
+    /// the column without the admin token. Edits in place stay allowed.
+    #[serde(default)]
+    locked: bool,
+    /// `max_age=5d` (or `36h`): tasks in the column for longer breach its
+    /// policy. Kept as written; `validate_columns` checks it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_age: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Attribute keys accepted as trailing `key=value` tokens on a column line.
+const COLUMN_ATTRIBUTES: [&str; 10] =
+    ["wip", "default_tags", "default_assignee", "done", "template", "sort", "dir", "auto_assign", "locked", "max_age"];
+/// Longest `max_age=` accepted, in days.
+const MAX_AGE_LIMIT_DAYS: i64 = 3650;
+
+/// Next `auto_assign=` position per column, so restarts keep the rotation.
+const ROTATION_FILE: &str = ".kanban-rotation.json";
+
+/// Manual task order per column, written by moves that give a `position`.
+const ORDER_FILE: &str = ".kanban-order.json";
+
+/// Column sort policies; `manual` keeps the listing's usual newest-update
+/// order, after the tasks placed in `ORDER_FILE`. `priority` reads a custom
+/// field named `priority`.
+const COLUMN_SORT_KEYS: [&str; 5] = ["priority", "created", "updated", "due", "manual"];
+
+/// Task templates referenced by a column's `template=` attribute.
+const TASK_TEMPLATES_DIR: &str = ".kanban-templates";
+
+/// Board-level settings written as `key = value` lines in the board file.
+const BOARD_SETTINGS: [&str; 13] = [
+    "status_mode",
+    "statuses",
+    "default_column",
+    "max_tasks",
+    "unique_titles",
+    "collision",
+    "slug_stopwords",
+    "slug_stopwords_extra",
+    "private_fields",
+    "sprint_start",
+    "sprint_end",
+    "modified_at",
+    "modified_by",
+];
+
+/// Words dropped from generated ids when `slug_stopwords = true`, per
+/// language; languages without a list use English.
+const SLUG_STOPWORDS: [(&str, &[&str]); 2] = [
+    ("en", &["a", "an", "and", "at", "by", "for", "from", "in", "into", "of", "on", "or", "the", "to", "with"]),
+    (
+        "de",
+        &[
+            "am", "an", "auf", "aus", "das", "dem", "den", "der", "des", "die", "ein", "eine", "einem", "einen", "einer",
+            "eines", "im", "in", "mit", "oder", "und", "von", "zu", "zum", "zur",
+        ],
+    ),
+];
+
+/// Value type of a custom task field.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum FieldKind {
+    Text,
+    Number,
+    Enum,
+}
+
+/// A custom task field declared by a `field <name>: <type>` line in the
+/// board file, e.g. `field severity: enum(low,med,high)`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct FieldDef {
+    name: String,
+    #[serde(rename = "type")]
+    kind: FieldKind,
+    /// Allowed values of an `enum` field, in sort order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    values: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+enum RuleKind {
+    RequiresTag,
+    DeniesTag,
+}
+
+/// A `rule <column> requires-tag <tag>` or `rule <column> denies-tag <tag>`
+/// line in the board file: which tasks may enter a column.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct RoutingRule {
+    column: String,
+    kind: RuleKind,
+    tag: String,
+}
+
+impl RoutingRule {
+    /// The rule as written after `rule `, e.g. `doing requires-tag ready`.
+    fn spec(&self) -> String {
+        let kind = match self.kind {
+            RuleKind::RequiresTag => "requires-tag",
+            RuleKind::DeniesTag => "denies-tag",
+        };
+        format!("{} {} {}", self.column, kind, self.tag)
+    }
+
+    fn allows(&self, task: &Task) -> bool {
+        let tagged = task.tags.iter().any(|t| t.eq_ignore_ascii_case(&self.tag));
+        match self.kind {
+            RuleKind::RequiresTag => tagged,
+            RuleKind::DeniesTag => !tagged,
+        }
+    }
+}
+
+/// What creating a task whose title matches an existing one does.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum UniqueTitles {
+    #[default]
+    Off,
+    /// Create anyway and list the matches as `similar_tasks`.
+    Warn,
+    /// Refuse exact matches with 409 unless `?force=true`.
+    Enforce,
+}
+
+/// What creating a task whose id is already taken does.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Collision {
+    /// Append `-2`, `-3`, ...; the default for ids made from the title.
+    Suffix,
+    /// Refuse with 409; the default for explicit ids.
+    Error,
+    /// Replace the existing task, keeping its content in history.
+    Overwrite,
+    /// Keep the existing task and report the item as skipped. Only batch
+    /// creates and imports skip; a single create refuses like `error`.
+    Skip,
+}
+
+impl Collision {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "suffix" => Ok(Collision::Suffix),
+            "error" => Ok(Collision::Error),
+            "overwrite" => Ok(Collision::Overwrite),
+            "skip" => Ok(Collision::Skip),
+            other => Err(format!("Invalid collision: {} (expected suffix, error, overwrite or skip)", other)),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Collision::Suffix => "suffix",
+            Collision::Error => "error",
+            Collision::Overwrite => "overwrite",
+            Collision::Skip => "skip",
+        }
+    }
+}
+
+/// How a create request's id was settled.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum CollisionOutcome {
+    /// The id was free.
+    Created,
+    Suffixed,
+    Overwritten,
+    Skipped,
+}
+
+/// How a task's `status` relates to its folder. In `column` mode status
+/// always equals the folder id; in `free` mode it is an independent,
+/// finer-grained value ("blocked", "waiting-review") kept across moves.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum StatusMode {
+    #[default]
+    Column,
+    Free,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 struct BoardConfig {
     columns: Vec<BoardColumn>,
+    status_mode: StatusMode,
+    /// Allowed `status` values in free mode; empty allows any value.
+    statuses: Vec<String>,
+    /// Column for new tasks that name none; the first column when unset.
+    default_column: Option<String>,
+    /// Cap on the number of tasks; creates are refused once it is reached.
+    max_tasks: Option<usize>,
+    unique_titles: UniqueTitles,
+    /// Default id collision strategy of creates and imports; unset keeps
+    /// the per-request defaults of `Collision`.
+    collision: Option<Collision>,
+    /// Drop stop-words from ids generated for new or renamed tasks.
+    slug_stopwords: bool,
+    /// Stop-words added to the built-in list for the server's language.
+    slug_stopwords_extra: Vec<String>,
+    /// Custom task fields, in declaration order.
+    #[serde(default)]
+    fields: Vec<FieldDef>,
+    /// Task keys (such as `creator`) left out of shared views.
+    #[serde(default)]
+    private_fields: Vec<String>,
+    /// Which tasks may enter which columns, in file order.
+    #[serde(default)]
+    rules: Vec<RoutingRule>,
+    /// First and last day of the current sprint, `YYYY-MM-DD`; set together.
+    sprint_start: Option<String>,
+    sprint_end: Option<String>,
+    /// Stamped by `write_config` on every change made through the server.
+    modified_at: Option<String>,
+    modified_by: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+/// The served theme: both theme files merged over the defaults.
+#[derive(Debug, Serialize, Clone, PartialEq)]
 struct ThemeSettings {
     headline: Option<String>,
-    colors: HashMap<String, String>,
+    colors: BTreeMap<String, String>,
+    palette: Vec<String>,
+    font_family: String,
+    /// CSS lengths such as `15px` or `1.1rem`.
+    font_size: String,
+    card_radius: String,
+    density: Density,
+    /// Accent color per column id.
+    column_colors: BTreeMap<String, String>,
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        ThemeSettings {
+            headline: None,
+            colors: BTreeMap::new(),
+            palette: Vec::new(),
+            font_family: "\"Space Grotesk\", system-ui, sans-serif".to_string(),
+            font_size: "16px".to_string(),
+            card_radius: "16px".to_string(),
+            density: Density::Comfortable,
+            column_colors: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Density {
+    Compact,
+    Comfortable,
+}
+
+impl Density {
+    fn parse(value: &str) -> Result<Density, String> {
+        match value {
+            "compact" => Ok(Density::Compact),
+            "comfortable" => Ok(Density::Comfortable),
+            other => Err(format!("Invalid density: {} (expected compact or comfortable)", other)),
+        }
+    }
+}
+
+/// `.kanban-theme.json` and the body of `PUT /api/theme`. Every setting is
+/// optional; `colors` and `column_colors` add to what the conf file sets.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    headline: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    colors: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    palette: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_family: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    font_size: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    card_radius: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    density: Option<Density>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    column_colors: BTreeMap<String, String>,
+}
+
+/// Parsed theme plus the stamps of the files it was read from; `etag`
+/// changes only when the served theme does.
+struct ThemeCache {
+    state: Mutex<ThemeState>,
+}
+
+#[derive(Default)]
+struct ThemeState {
+    loaded: bool,
+    stamp: Vec<Option<(SystemTime, u64)>>,
+    theme: serde_json::Value,
+    etag: String,
+}
+
+const EVENT_BACKLOG: usize = 256;
+const EVENT_KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// Recent server-sent events, kept briefly so reconnecting clients can
+/// resume from `Last-Event-ID`.
+struct EventBus {
+    log: Mutex<EventLog>,
+    cvar: Condvar,
+}
+
+#[derive(Default)]
+struct EventLog {
+    last_id: u64,
+    events: VecDeque<ServerEvent>,
+}
+
+struct ServerEvent {
+    id: u64,
+    kind: String,
+    data: String,
 }
 
 struct UpdateState {
@@ -58,14 +745,30 @@ struct UpdateState {
     cvar: Condvar,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct NewTask {
+    /// Explicit id; derived from the title when absent.
+    id: Option<String>,
     title: String,
     description: Option<String>,
     creator: Option<String>,
     assigned_to: Option<String>,
     tags: Option<Vec<String>>,
     status: Option<String>,
+    color: Option<String>,
+    icon: Option<String>,
+    due: Option<String>,
+    estimate: Option<f64>,
+    /// Creation time for tasks brought over from elsewhere; now when absent.
+    /// Any of `TIMESTAMP_FORMATS`, stored as RFC 3339 UTC.
+    created_at: Option<String>,
+    /// Target column; defaults to `status` when that names a column, else the
+    /// board's `default_column`.
+    folder: Option<String>,
+    /// Custom field values, checked against the board's `fields`.
+    extra: Option<serde_json::Map<String, serde_json::Value>>,
+    /// What to do when the id is taken; see `settle_id`.
+    collision: Option<Collision>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -75,20 +778,181 @@ struct UpdateTask {
     creator: Option<String>,
     assigned_to: Option<String>,
     tags: Option<Vec<String>>,
+    color: Option<String>,
+    icon: Option<String>,
+    due: Option<String>,
+    /// 0 clears the estimate.
+    estimate: Option<f64>,
+    /// Only applied in free status mode.
+    status: Option<String>,
+    /// Merged into the task's fields; `null` or `""` removes one.
+    extra: Option<serde_json::Map<String, serde_json::Value>>,
 }
 
 #[derive(Debug, Deserialize)]
 struct MoveTask {
     folder: String,
+    /// Only used in free status mode; column mode sets status to the folder.
+    status: Option<String>,
+    /// Index in the target column's manual order; past the end appends.
+    position: Option<usize>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 struct BoardUpdate {
     columns: Vec<BoardColumn>,
+    /// Board settings left out of the update keep their current values.
+    status_mode: Option<StatusMode>,
+    statuses: Option<Vec<String>>,
+    /// An empty string clears the setting.
+    default_column: Option<String>,
+    /// 0 clears the cap.
+    max_tasks: Option<usize>,
+    unique_titles: Option<UniqueTitles>,
+    collision: Option<Collision>,
+    slug_stopwords: Option<bool>,
+    slug_stopwords_extra: Option<Vec<String>>,
+    fields: Option<Vec<FieldDef>>,
+    private_fields: Option<Vec<String>>,
+    rules: Option<Vec<RoutingRule>>,
+    /// An empty string clears the date.
+    sprint_start: Option<String>,
+    sprint_end: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ColumnReorder {
+    columns: Vec<String>,
+}
+
+/// `PATCH /api/columns/{id}`: changes one column's attributes.
+#[derive(Debug, Deserialize)]
+struct ColumnPatch {
+    locked: Option<bool>,
 }
 
 fn now_iso() -> String {
-    OffsetDateTime::now_utc().format(&Rfc3339).unwrap_or_default()
+    format_timestamp(OffsetDateTime::now_utc())
+}
+
+fn format_timestamp(at: OffsetDateTime) -> String {
+    // RFC 3339 only fails for years outside 0..=9999 or sub-minute offsets,
+    // neither of which a UTC clock reading produces.
+    at.format(&Rfc3339).expect("UTC timestamps always format as RFC 3339")
+}
+
+/// What a task timestamp may be written as, for error messages.
+const TIMESTAMP_FORMATS: [&str; 4] = [
+    "RFC 3339 (2024-06-01T14:00:00Z)",
+    "YYYY-MM-DD",
+    "YYYY-MM-DD HH:MM[:SS] (UTC)",
+    "Unix epoch seconds",
+];
+
+/// A task header timestamp (`created_at`, `updated_at`, `completed_at`,
+/// `column_since`). Values in one of `TIMESTAMP_FORMATS` are held, written
+/// and served as RFC 3339 UTC. Reading files stays lenient: anything else is
+/// kept as written with `at` unset, for `doctor` to report.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+struct Timestamp {
+    text: String,
+    at: Option<OffsetDateTime>,
+}
+
+impl Timestamp {
+    fn now() -> Timestamp {
+        Timestamp::from(OffsetDateTime::now_utc())
+    }
+
+    /// Lenient: normalizes what parses and keeps the rest as is.
+    fn read(raw: &str) -> Timestamp {
+        let raw = raw.trim();
+        match parse_any_timestamp(raw) {
+            Some(at) => Timestamp::from(at),
+            None => Timestamp { text: raw.to_string(), at: None },
+        }
+    }
+
+    /// Strict, for values coming through the API: the error names `field`
+    /// and lists the accepted formats.
+    fn parse(field: &str, raw: &str) -> Result<Timestamp, String> {
+        let stamp = Timestamp::read(raw);
+        match stamp.at {
+            Some(_) => Ok(stamp),
+            None => Err(format!("invalid {} {:?}; accepted formats: {}", field, raw.trim(), TIMESTAMP_FORMATS.join(", "))),
+        }
+    }
+
+    fn at(&self) -> Option<OffsetDateTime> {
+        self.at
+    }
+}
+
+impl From<OffsetDateTime> for Timestamp {
+    fn from(at: OffsetDateTime) -> Timestamp {
+        let at = at.to_offset(UtcOffset::UTC);
+        Timestamp { text: format_timestamp(at), at: Some(at) }
+    }
+}
+
+impl From<String> for Timestamp {
+    fn from(raw: String) -> Timestamp {
+        Timestamp::read(&raw)
+    }
+}
+
+impl From<Timestamp> for String {
+    fn from(stamp: Timestamp) -> String {
+        stamp.text
+    }
+}
+
+impl std::ops::Deref for Timestamp {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Parses any of `TIMESTAMP_FORMATS`; dates without an offset are UTC.
+fn parse_any_timestamp(raw: &str) -> Option<OffsetDateTime> {
+    if let Some(at) = parse_timestamp(raw) {
+        return Some(at);
+    }
+    if !raw.is_empty() && raw.len() <= 12 && raw.bytes().all(|b| b.is_ascii_digit()) {
+        return OffsetDateTime::from_unix_timestamp(raw.parse().ok()?).ok();
+    }
+    let (date, clock) = raw.split_once(' ').unwrap_or((raw, ""));
+    let date = parse_plain_date(date)?;
+    let parts: Vec<&str> = clock.split(':').collect();
+    if clock.is_empty() {
+        return Some(date.midnight().assume_utc());
+    }
+    if !(2..=3).contains(&parts.len()) || parts.iter().any(|p| p.len() != 2 || !p.bytes().all(|b| b.is_ascii_digit())) {
+        return None;
+    }
+    let number = |i: usize| parts.get(i).map_or(Some(0), |p| p.parse::<u8>().ok());
+    let time = time::Time::from_hms(number(0)?, number(1)?, number(2)?).ok()?;
+    Some(date.with_time(time).assume_utc())
+}
+
+/// Sets `updated_at` to now, or one second past the previous value when the
+/// clock reads earlier (VM resumed, clock stepped back), so a task's
+/// `updated_at` never goes backwards.
+fn touch_task(task: &mut Task) {
+    let now = OffsetDateTime::now_utc();
+    task.updated_at = match task.updated_at.at() {
+        Some(previous) if previous >= now => Timestamp::from(previous + time::Duration::seconds(1)),
+        _ => Timestamp::from(now),
+    };
 }
 
 fn ensure_folders(root: &Path, config: &BoardConfig) -> io::Result<()> {
@@ -106,12 +970,104 @@ fn theme_path(root: &Path) -> PathBuf {
     root.join(THEME_FILE)
 }
 
-fn write_default_config(path: &Path) -> io::Result<()> {
-    let mut contents = String::new();
-    for (id, title) in DEFAULT_FOLDERS {
-        contents.push_str(&format!("{}: {}\n", id, title));
+fn theme_json_path(root: &Path) -> PathBuf {
+    root.join(THEME_JSON_FILE)
+}
+
+fn is_known_lang(lang: &str) -> bool {
+    COLUMN_TITLES.iter().any(|(code, _)| *code == lang)
+}
+
+fn column_title(lang: &str, id: &str) -> String {
+    let lookup = |code: &str| {
+        COLUMN_TITLES
+            .iter()
+            .find(|(c, _)| *c == code)
+            .and_then(|(_, titles)| titles.iter().find(|(column, _)| *column == id))
+            .map(|(_, title)| title.to_string())
+    };
+    lookup(lang)
+        .or_else(|| lookup(DEFAULT_LANG))
+        .unwrap_or_else(|| id.to_string())
+}
+
+thread_local! {
+    /// Language of the request this thread is answering, else the server's
+    /// `--lang`; set by `handle_request` and, for CLI commands, by `main`.
+    /// Unset threads use English.
+    static REQUEST_LANG: std::cell::Cell<Option<&'static str>> = const { std::cell::Cell::new(None) };
+}
+
+/// The catalog language for `lang` (`de-AT` → `de`), if there is one.
+fn message_lang(lang: &str) -> Option<&'static str> {
+    let primary = lang.trim().split(['-', '_']).next().unwrap_or("").to_lowercase();
+    MESSAGES.iter().map(|(code, _)| *code).find(|code| *code == primary)
+}
+
+/// The language a request is answered in: `?lang=`, else the first catalog
+/// language in `Accept-Language` by weight, else `--lang`.
+fn resolve_request_lang(query: Option<&str>, accept_language: Option<&str>) -> Option<&'static str> {
+    if let Some(lang) = query.and_then(message_lang) {
+        return Some(lang);
     }
-    fs::write(path, contents)
+    let mut ranges: Vec<(f32, &str)> = accept_language
+        .unwrap_or("")
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let weight = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some((weight, tag)).filter(|(weight, _)| *weight > 0.0)
+        })
+        .collect();
+    ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+    ranges.into_iter().find_map(|(_, tag)| message_lang(tag))
+}
+
+/// The catalog text for `key` in the current language, English when it has
+/// no translation, with `{name}` placeholders replaced from `params`.
+fn message(key: &str, params: &[(&str, &str)]) -> String {
+    message_in(REQUEST_LANG.with(std::cell::Cell::get).unwrap_or(DEFAULT_LANG), key, params)
+}
+
+/// `message` in `lang`, whatever the request asked for.
+fn message_in(lang: &str, key: &str, params: &[(&str, &str)]) -> String {
+    let lookup = |lang: &str| {
+        MESSAGES
+            .iter()
+            .find(|(code, _)| *code == lang)
+            .and_then(|(_, texts)| texts.iter().find(|(k, _)| *k == key))
+            .map(|(_, text)| *text)
+    };
+    let mut text = lookup(lang).or_else(|| lookup(DEFAULT_LANG)).unwrap_or(key).to_string();
+    for (name, value) in params {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+/// Columns of the named template with titles in `lang`; `None` for an unknown
+/// template.
+fn template_columns(template: &str, lang: &str) -> Option<Vec<BoardColumn>> {
+    let (_, columns) = BOARD_TEMPLATES.iter().find(|(name, _)| *name == template)?;
+    Some(
+        columns
+            .iter()
+            .map(|(id, wip_limit)| BoardColumn {
+                id: id.to_string(),
+                title: column_title(lang, id),
+                wip_limit: *wip_limit,
+                ..Default::default()
+            })
+            .collect(),
+    )
+}
+
+fn write_default_config(path: &Path, defaults: &BoardConfig) -> io::Result<()> {
+    fs::write(path, render_config(defaults))
 }
 
 fn parse_config_line(line: &str) -> Option<BoardColumn> {
@@ -133,65 +1089,347 @@ fn parse_config_line(line: &str) -> Option<BoardColumn> {
     {
         return None;
     }
-    let mut title = title_part;
-    let mut wip_limit: Option<u32> = None;
-    if let Some((base_title, tail)) = title_part.split_once("wip=") {
-        title = base_title.trim();
-        let raw = tail.trim().split_whitespace().next().unwrap_or("");
-        if let Ok(val) = raw.parse::<u32>() {
-            if val > 0 {
-                wip_limit = Some(val);
-            }
-        }
-    }
+    // Only trailing tokens are attributes, so a `wip=` inside the title stays.
+    let (title, attributes) = take_trailing_attributes(title_part, &COLUMN_ATTRIBUTES);
+    let wip_limit = attributes.get("wip").and_then(|v| v.parse::<u32>().ok());
     let title = if title.is_empty() {
         id_part
     } else {
         title
     };
+    let default_tags = attributes
+        .get("default_tags")
+        .map(|v| split_list(v))
+        .unwrap_or_default();
+    let default_assignee = attributes
+        .get("default_assignee")
+        .map(|v| v.to_string())
+        .filter(|v| !v.is_empty());
+    let flag = |key: &str| {
+        attributes
+            .get(key)
+            .is_some_and(|v| matches!(v.to_ascii_lowercase().as_str(), "true" | "yes" | "1"))
+    };
+    let done = flag("done");
+    let locked = flag("locked");
+    let template = attributes.get("template").map(|v| v.to_string()).filter(|v| is_valid_id(v));
+    let sort = attributes.get("sort").map(|v| v.to_ascii_lowercase());
+    let sort_dir = attributes.get("dir").map(|v| v.to_ascii_lowercase());
+    let auto_assign = attributes
+        .get("auto_assign")
+        .map(|v| split_list(v))
+        .unwrap_or_default();
+    let max_age = attributes.get("max_age").map(|v| v.to_ascii_lowercase());
     Some(BoardColumn {
         id: id_part.to_string(),
         title: title.to_string(),
         wip_limit,
+        default_tags,
+        default_assignee,
+        done,
+        template,
+        sort,
+        sort_dir,
+        auto_assign,
+        locked,
+        max_age,
     })
 }
 
-fn load_theme(root: &Path) -> ThemeSettings {
-    let path = theme_path(root);
-    let mut colors = HashMap::new();
-    let mut headline = None;
-    if !path.exists() {
-        return ThemeSettings { headline, colors };
+/// Splits trailing whitespace-separated `key=value` tokens off `text` as long
+/// as the key is one of `keys`. Returns the remaining text and the attributes.
+fn take_trailing_attributes<'a>(text: &'a str, keys: &[&str]) -> (&'a str, HashMap<String, String>) {
+    let mut attributes = HashMap::new();
+    let mut rest = text.trim_end();
+    loop {
+        let (head, token) = match rest.rsplit_once(char::is_whitespace) {
+            Some((head, token)) => (head, token),
+            None => ("", rest),
+        };
+        let Some((key, value)) = token.split_once('=') else {
+            break;
+        };
+        if !keys.contains(&key) || attributes.contains_key(key) {
+            break;
+        }
+        attributes.insert(key.to_string(), value.to_string());
+        rest = head.trim_end();
+        if rest.is_empty() {
+            break;
+        }
     }
-    if let Ok(contents) = fs::read_to_string(&path) {
-        for line in contents.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                continue;
+    (rest, attributes)
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Parses `.kanban-theme.conf`. Lines without `=` and invalid palette colors
+/// are errors so a half-saved edit is not mistaken for a new theme.
+fn parse_theme(contents: &str) -> Result<ThemeSettings, String> {
+    let mut theme = ThemeSettings::default();
+    for (index, line) in contents.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let (key, value) = match trimmed.split_once('=') {
+            Some((k, v)) => (k.trim(), v.trim()),
+            None => return Err(format!("line {}: expected key=value", index + 1)),
+        };
+        if key.eq_ignore_ascii_case("headline") {
+            if !value.is_empty() {
+                theme.headline = Some(value.to_string());
             }
-            let (key, value) = match trimmed.split_once('=') {
-                Some((k, v)) => (k.trim(), v.trim()),
-                None => continue,
-            };
-            if key.eq_ignore_ascii_case("headline") {
-                if !value.is_empty() {
-                    headline = Some(value.to_string());
+            continue;
+        }
+        let setting = match key {
+            "font_family" => Some(ThemeFile { font_family: Some(value.to_string()), ..Default::default() }),
+            "font_size" => Some(ThemeFile { font_size: Some(value.to_string()), ..Default::default() }),
+            "card_radius" => Some(ThemeFile { card_radius: Some(value.to_string()), ..Default::default() }),
+            "density" => {
+                let density = Density::parse(value).map_err(|msg| format!("line {}: {}", index + 1, msg))?;
+                Some(ThemeFile { density: Some(density), ..Default::default() })
+            }
+            _ => key.strip_prefix("column_color.").map(|id| ThemeFile {
+                column_colors: BTreeMap::from([(id.to_string(), value.to_string())]),
+                ..Default::default()
+            }),
+        };
+        if let Some(file) = setting {
+            apply_theme_file(&mut theme, file).map_err(|msg| format!("line {}: {}", index + 1, msg))?;
+            continue;
+        }
+        if key.starts_with("color.palette.") {
+            match normalize_color(value) {
+                Ok(Some(hex)) => {
+                    if !theme.palette.contains(&hex) {
+                        theme.palette.push(hex);
+                    }
                 }
-                continue;
+                Ok(None) => {}
+                Err(msg) => return Err(format!("line {}: {}", index + 1, msg)),
+            }
+            continue;
+        }
+        if key.starts_with("color.") && !value.is_empty() {
+            theme
+                .colors
+                .insert(key.trim_start_matches("color.").to_string(), value.to_string());
+        }
+    }
+    Ok(theme)
+}
+
+/// A CSS length: a number followed by `px`, `rem`, `em`, `pt` or `%`.
+fn css_length(key: &str, value: &str) -> Result<String, String> {
+    let value = value.trim();
+    let number = value.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%');
+    let unit = &value[number.len()..];
+    if ["px", "rem", "em", "pt", "%"].contains(&unit) && number.parse::<f64>().is_ok_and(|n| n.is_finite() && n >= 0.0) {
+        Ok(value.to_string())
+    } else {
+        Err(format!("Invalid {}: {:?} (expected a number with a unit: px, rem, em, pt or %)", key, value))
+    }
+}
+
+/// A CSS font-family list; characters that could end the declaration are
+/// refused.
+fn css_font_family(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    if value.is_empty() || value.contains([';', '{', '}', '<', '>']) {
+        return Err(format!("Invalid font_family: {:?}", value));
+    }
+    Ok(value.to_string())
+}
+
+fn column_color(id: &str, value: &str) -> Result<String, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-') {
+        return Err(format!("Invalid column id in column_colors: {}", id));
+    }
+    normalize_color(value)?.ok_or_else(|| format!("Missing color for column {}", id))
+}
+
+/// Applies `.kanban-theme.json` (or a `PUT /api/theme` body) on top of
+/// `theme`, validating every value.
+fn apply_theme_file(theme: &mut ThemeSettings, file: ThemeFile) -> Result<(), String> {
+    if let Some(headline) = file.headline.filter(|h| !h.trim().is_empty()) {
+        theme.headline = Some(headline.trim().to_string());
+    }
+    for (key, value) in file.colors {
+        if value.trim().is_empty() || value.contains([';', '{', '}']) {
+            return Err(format!("Invalid color {}: {:?}", key, value));
+        }
+        theme.colors.insert(key, value.trim().to_string());
+    }
+    if let Some(palette) = file.palette {
+        theme.palette.clear();
+        for color in palette {
+            if let Some(hex) = normalize_color(&color)?.filter(|hex| !theme.palette.contains(hex)) {
+                theme.palette.push(hex);
             }
-            if key.starts_with("color.") && !value.is_empty() {
-                colors.insert(key.trim_start_matches("color.").to_string(), value.to_string());
+        }
+    }
+    if let Some(family) = file.font_family {
+        theme.font_family = css_font_family(&family)?;
+    }
+    if let Some(size) = file.font_size {
+        theme.font_size = css_length("font_size", &size)?;
+    }
+    if let Some(radius) = file.card_radius {
+        theme.card_radius = css_length("card_radius", &radius)?;
+    }
+    if let Some(density) = file.density {
+        theme.density = density;
+    }
+    for (id, color) in file.column_colors {
+        let color = column_color(&id, &color)?;
+        theme.column_colors.insert(id, color);
+    }
+    Ok(())
+}
+
+/// Column ids in `column_colors` that are not columns of the board.
+fn unknown_theme_columns(theme: &ThemeSettings, config: &BoardConfig) -> Vec<String> {
+    theme
+        .column_colors
+        .keys()
+        .filter(|id| !config.columns.iter().any(|c| c.id == **id))
+        .cloned()
+        .collect()
+}
+
+/// Reads the conf and JSON theme files, each optional, over the defaults.
+/// Errors name the malformed file.
+fn read_theme(root: &Path) -> Result<ThemeSettings, String> {
+    let mut theme = match fs::read_to_string(theme_path(root)) {
+        Ok(contents) => parse_theme(&contents).map_err(|msg| format!("{}: {}", THEME_FILE, msg))?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => ThemeSettings::default(),
+        Err(err) => return Err(format!("{}: {}", THEME_FILE, err)),
+    };
+    match fs::read_to_string(theme_json_path(root)) {
+        Ok(contents) => serde_json::from_str::<ThemeFile>(&contents)
+            .map_err(|err| err.to_string())
+            .and_then(|file| apply_theme_file(&mut theme, file))
+            .map_err(|msg| format!("{}: {}", THEME_JSON_FILE, msg))?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(format!("{}: {}", THEME_JSON_FILE, err)),
+    }
+    Ok(theme)
+}
+
+/// Validates a `PUT /api/theme` body against the conf theme and the board,
+/// then replaces `.kanban-theme.json` with it.
+fn save_theme_file(root: &Path, config: &BoardConfig, body: &str) -> Result<(), (u16, String)> {
+    let file: ThemeFile = serde_json::from_str(body).map_err(|err| (400, err.to_string()))?;
+    let mut theme = match fs::read_to_string(theme_path(root)) {
+        Ok(contents) => parse_theme(&contents).unwrap_or_default(),
+        Err(_) => ThemeSettings::default(),
+    };
+    let contents = serde_json::to_string_pretty(&file).map_err(|err| (500, err.to_string()))?;
+    apply_theme_file(&mut theme, file).map_err(|msg| (400, msg))?;
+    let unknown = unknown_theme_columns(&theme, config);
+    if !unknown.is_empty() {
+        return Err((400, format!("column_colors names unknown columns: {}", unknown.join(", "))));
+    }
+    fs::write(theme_json_path(root), contents + "\n").map_err(|err| (500, err.to_string()))
+}
+
+/// The theme files that exist, for the startup summary.
+fn theme_files(root: &Path) -> Vec<&'static str> {
+    [(THEME_FILE, theme_path(root)), (THEME_JSON_FILE, theme_json_path(root))]
+        .into_iter()
+        .filter(|(_, path)| path.exists())
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Returns the current theme and its ETag, re-reading the file only when its
+/// mtime or size changed. A malformed file keeps the previous theme; a real
+/// change is announced as a `theme_changed` event.
+fn current_theme(root: &Path, cache: &ThemeCache, bus: &EventBus) -> (serde_json::Value, String) {
+    let stamp: Vec<Option<(SystemTime, u64)>> = [theme_path(root), theme_json_path(root)]
+        .iter()
+        .map(|path| {
+            fs::metadata(path)
+                .ok()
+                .map(|meta| (meta.modified().unwrap_or(SystemTime::UNIX_EPOCH), meta.len()))
+        })
+        .collect();
+    let mut state = cache.state.lock().unwrap();
+    if state.loaded && state.stamp == stamp {
+        return (state.theme.clone(), state.etag.clone());
+    }
+    state.stamp = stamp;
+    let theme = match read_theme(root) {
+        Ok(theme) => theme,
+        Err(msg) => {
+            eprintln!("Warning: ignoring malformed theme ({}); keeping the previous theme", msg);
+            if state.loaded {
+                return (state.theme.clone(), state.etag.clone());
             }
+            ThemeSettings::default()
+        }
+    };
+    let value = serde_json::json!(theme);
+    let etag = format!("\"{:016x}\"", content_hash(&value.to_string()));
+    if etag != state.etag {
+        if state.loaded {
+            publish_event(bus, "theme_changed", serde_json::json!({ "theme": value }));
         }
+        state.theme = value;
+        state.etag = etag;
     }
-    ThemeSettings { headline, colors }
+    state.loaded = true;
+    (state.theme.clone(), state.etag.clone())
+}
+
+fn content_hash<T: std::hash::Hash + ?Sized>(value: &T) -> u64 {
+    use std::hash::Hasher;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Which file `--write-default-theme` creates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ThemeFormat {
+    Conf,
+    Json,
 }
 
-fn write_default_theme(root: &Path) -> io::Result<bool> {
-    let path = theme_path(root);
+/// Writes a starter theme file in `format` unless that file exists; returns
+/// whether it was written.
+fn write_default_theme(root: &Path, format: ThemeFormat) -> io::Result<bool> {
+    let path = match format {
+        ThemeFormat::Conf => theme_path(root),
+        ThemeFormat::Json => theme_json_path(root),
+    };
     if path.exists() {
         return Ok(false);
     }
+    if format == ThemeFormat::Json {
+        let defaults = ThemeSettings::default();
+        let file = ThemeFile {
+            headline: Some("Kanban Task Files".to_string()),
+            colors: DEFAULT_THEME_COLORS.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            palette: Some(DEFAULT_THEME_PALETTE.iter().map(|c| c.to_string()).collect()),
+            font_family: Some(defaults.font_family),
+            font_size: Some(defaults.font_size),
+            card_radius: Some(defaults.card_radius),
+            density: Some(defaults.density),
+            column_colors: BTreeMap::new(),
+        };
+        let mut contents = serde_json::to_string_pretty(&file).map_err(io::Error::other)?;
+        contents.push('\n');
+        fs::write(path, contents)?;
+        return Ok(true);
+    }
     let contents = "\
 # Headline text shown in the app header\n\
 headline=Kanban Task Files\n\
@@ -209,19 +1447,147 @@ color.card=#ffffff\n\
 # Background gradient start/middle/end\n\
 color.bg_start=#fff4e6\n\
 color.bg_mid=#f7efe2\n\
-color.bg_end=#ece4d7\n";
+color.bg_end=#ece4d7\n\
+\n\
+# Suggested card colors offered by the UI\n\
+color.palette.1=#ff7a18\n\
+color.palette.2=#2f80ed\n\
+color.palette.3=#27ae60\n\
+color.palette.4=#9b51e0\n\
+\n\
+# Typography and spacing: font_family, font_size, card_radius (a number\n\
+# with px, rem, em, pt or %), density (compact or comfortable) and\n\
+# column_color.<column id>=#rrggbb\n\
+font_size=16px\n\
+card_radius=16px\n\
+density=comfortable\n";
     fs::write(path, contents)?;
     Ok(true)
 }
 
-fn validate_columns(columns: &[BoardColumn]) -> Result<(), String> {
-    if columns.is_empty() {
-        return Err("Board must have at least one column".to_string());
+/// Colors of the starter theme, as in the conf starter file.
+const DEFAULT_THEME_COLORS: [(&str, &str); 8] = [
+    ("accent", "#ff7a18"),
+    ("accent_deep", "#c24800"),
+    ("ink", "#141414"),
+    ("muted", "#4e4c48"),
+    ("card", "#ffffff"),
+    ("bg_start", "#fff4e6"),
+    ("bg_mid", "#f7efe2"),
+    ("bg_end", "#ece4d7"),
+];
+const DEFAULT_THEME_PALETTE: [&str; 4] = ["#ff7a18", "#2f80ed", "#27ae60", "#9b51e0"];
+
+/// `validate_columns` plus the board settings that refer to columns.
+fn validate_board(config: &BoardConfig, max_columns: usize) -> Result<(), String> {
+    validate_columns(&config.columns, max_columns)?;
+    validate_fields(&config.fields)?;
+    validate_sprint(config)?;
+    validate_rules(config)?;
+    if let Some(status) = config.statuses.iter().find(|s| !is_list_item(s)) {
+        return Err(format!("Invalid status {:?}: statuses cannot be empty or contain commas or line breaks", status));
     }
-    let mut seen = HashMap::new();
-    for column in columns {
-        if column.id.is_empty() {
-            return Err("Column id cannot be empty".to_string());
+    if let Some(word) = config.slug_stopwords_extra.iter().find(|w| !is_list_item(w) || w.contains(char::is_whitespace)) {
+        return Err(format!("Invalid stop-word {:?}: stop-words are single words without commas", word));
+    }
+    for key in &config.private_fields {
+        let known = TASK_HEADERS.contains(&key.as_str())
+            || ["id", "description", "mentions"].contains(&key.as_str())
+            || config.fields.iter().any(|field| field.name == *key);
+        if !is_list_item(key) || !known {
+            return Err(format!("Invalid private field {:?}: expected a task key or a custom field", key));
+        }
+    }
+    if let Some(default) = &config.default_column {
+        if !config.columns.iter().any(|c| c.id == *default) {
+            return Err(format!(
+                "default_column {} is not a column; point it at another column or clear it",
+                default
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `value` can be one entry of a comma-separated board setting.
+fn is_list_item(value: &str) -> bool {
+    !value.trim().is_empty() && !value.contains([',', '\r', '\n'])
+}
+
+/// The board after `update`: its columns, plus the settings it includes;
+/// settings it leaves out keep their values from `old`.
+fn updated_config(old: &BoardConfig, update: BoardUpdate) -> BoardConfig {
+    BoardConfig {
+        columns: update.columns,
+        status_mode: update.status_mode.unwrap_or(old.status_mode),
+        statuses: update.statuses.unwrap_or_else(|| old.statuses.clone()),
+        default_column: match update.default_column {
+            Some(column) => Some(column).filter(|c| !c.is_empty()),
+            None => old.default_column.clone(),
+        },
+        max_tasks: match update.max_tasks {
+            Some(max) => Some(max).filter(|m| *m > 0),
+            None => old.max_tasks,
+        },
+        unique_titles: update.unique_titles.unwrap_or(old.unique_titles),
+        collision: update.collision.or(old.collision),
+        slug_stopwords: update.slug_stopwords.unwrap_or(old.slug_stopwords),
+        slug_stopwords_extra: update
+            .slug_stopwords_extra
+            .map(|words| words.iter().map(|w| w.trim().to_lowercase()).filter(|w| !w.is_empty()).collect())
+            .unwrap_or_else(|| old.slug_stopwords_extra.clone()),
+        fields: update.fields.unwrap_or_else(|| old.fields.clone()),
+        rules: update.rules.unwrap_or_else(|| old.rules.clone()),
+        private_fields: update
+            .private_fields
+            .map(|keys| keys.iter().map(|k| k.trim().to_string()).filter(|k| !k.is_empty()).collect())
+            .unwrap_or_else(|| old.private_fields.clone()),
+        sprint_start: match update.sprint_start {
+            Some(date) => sprint_date(&date),
+            None => old.sprint_start.clone(),
+        },
+        sprint_end: match update.sprint_end {
+            Some(date) => sprint_date(&date),
+            None => old.sprint_end.clone(),
+        },
+        ..Default::default()
+    }
+}
+
+/// A `sprint_start`/`sprint_end` value from a board update: empty clears
+/// it, a date is normalized and anything else is kept for `validate_board`
+/// to reject.
+fn sprint_date(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    Some(parse_plain_date(raw).map_or_else(|| raw.to_string(), |date| date.to_string())).filter(|v| !v.is_empty())
+}
+
+/// Where new tasks go when the request names no column.
+fn default_column(config: &BoardConfig) -> &str {
+    config
+        .default_column
+        .as_deref()
+        .filter(|id| config.columns.iter().any(|c| c.id == *id))
+        .unwrap_or(&config.columns[0].id)
+}
+
+/// Checks the column list; more than `max_columns` columns (unless 0) are
+/// refused.
+fn validate_columns(columns: &[BoardColumn], max_columns: usize) -> Result<(), String> {
+    if columns.is_empty() {
+        return Err("Board must have at least one column".to_string());
+    }
+    if max_columns > 0 && columns.len() > max_columns {
+        return Err(format!(
+            "Board has {} columns, more than the maximum of {} (raise it with --max-columns)",
+            columns.len(),
+            max_columns
+        ));
+    }
+    let mut seen = HashMap::new();
+    for column in columns {
+        if column.id.is_empty() {
+            return Err("Column id cannot be empty".to_string());
         }
         if !column
             .id
@@ -233,884 +1599,18009 @@ fn validate_columns(columns: &[BoardColumn]) -> Result<(), String> {
         if seen.contains_key(&column.id) {
             return Err(format!("Duplicate column id: {}", column.id));
         }
+        // The title shares its line with the attributes, so it must read
+        // back as the same title.
+        if column.title.contains(['\r', '\n']) {
+            return Err(format!("Title of column {} cannot contain line breaks", column.id));
+        }
+        if let Some(key) = take_trailing_attributes(&column.title, &COLUMN_ATTRIBUTES).1.keys().next() {
+            return Err(format!(
+                "Title of column {} cannot end with {}=...: it would be read as an attribute",
+                column.id, key
+            ));
+        }
+        for tag in &column.default_tags {
+            if tag.is_empty() || tag.contains(',') || tag.contains(char::is_whitespace) {
+                return Err(format!("Invalid default tag for column {}: {:?}", column.id, tag));
+            }
+        }
+        if let Some(assignee) = &column.default_assignee {
+            if assignee.contains(char::is_whitespace) {
+                return Err(format!(
+                    "Default assignee for column {} cannot contain whitespace",
+                    column.id
+                ));
+            }
+        }
+        for name in &column.auto_assign {
+            if name.is_empty() || name.contains(',') || name.contains(char::is_whitespace) {
+                return Err(format!("Invalid auto_assign name for column {}: {:?}", column.id, name));
+            }
+        }
+        if !column.auto_assign.is_empty() && column.default_assignee.is_some() {
+            return Err(format!(
+                "Column {} cannot have both default_assignee and auto_assign",
+                column.id
+            ));
+        }
+        if let Some(template) = column.template.as_deref().filter(|t| !is_valid_id(t)) {
+            return Err(format!("Invalid template for column {}: {:?}", column.id, template));
+        }
+        if let Some(sort) = column.sort.as_deref().filter(|s| !COLUMN_SORT_KEYS.contains(s)) {
+            return Err(format!(
+                "Invalid sort for column {}: {} (expected one of {})",
+                column.id,
+                sort,
+                COLUMN_SORT_KEYS.join(", ")
+            ));
+        }
+        if let Some(dir) = column.sort_dir.as_deref().filter(|d| !matches!(*d, "asc" | "desc")) {
+            return Err(format!("Invalid dir for column {}: {} (expected asc or desc)", column.id, dir));
+        }
+        if let Some(max_age) = &column.max_age {
+            parse_max_age(max_age).map_err(|msg| format!("Invalid max_age for column {}: {}", column.id, msg))?;
+        }
         seen.insert(column.id.clone(), true);
     }
     Ok(())
 }
 
-fn write_config(root: &Path, config: &BoardConfig) -> io::Result<()> {
-    let mut contents = String::new();
-    for column in &config.columns {
-        if let Some(limit) = column.wip_limit {
-            if limit > 0 {
-                contents.push_str(&format!("{}: {} wip={}\n", column.id, column.title, limit));
-                continue;
-            }
-        }
-        contents.push_str(&format!("{}: {}\n", column.id, column.title));
-    }
-    fs::write(config_path(root), contents)
+/// A `max_age=` value: a whole number of days (`5d`) or hours (`36h`), from
+/// one hour up to `MAX_AGE_LIMIT_DAYS`.
+fn parse_max_age(value: &str) -> Result<time::Duration, String> {
+    let (number, hours) = match (value.strip_suffix('d'), value.strip_suffix('h')) {
+        (Some(number), _) => (number, 24),
+        (_, Some(number)) => (number, 1),
+        _ => return Err(format!("{:?} needs a d or h suffix, e.g. 5d or 36h", value)),
+    };
+    number
+        .parse::<i64>()
+        .ok()
+        .filter(|_| number.chars().all(|c| c.is_ascii_digit()))
+        .and_then(|n| n.checked_mul(hours))
+        .filter(|total| (1..=MAX_AGE_LIMIT_DAYS * 24).contains(total))
+        .map(time::Duration::hours)
+        .ok_or_else(|| format!("{:?} is not a whole number of days or hours from 1h to {}d", value, MAX_AGE_LIMIT_DAYS))
 }
 
-fn load_config(root: &Path, yes: bool) -> io::Result<BoardConfig> {
-    let path = config_path(root);
-    if !path.exists() {
-        if yes {
-            write_default_config(&path)?;
-        } else {
-            println!(
-                "Missing {} in {}.",
-                CONFIG_FILE,
-                root.display()
-            );
-            print!("Create default board file? [y/N] ");
-            io::stdout().flush()?;
-            let mut input = String::new();
-            io::stdin().read_line(&mut input)?;
-            let answer = input.trim().to_lowercase();
-            if answer == "y" || answer == "yes" {
-                write_default_config(&path)?;
-            } else {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    "Missing .workspace-kanban",
-                ));
-            }
+/// Columns in file order; `BoardConfig.columns` order is the board's display
+/// order everywhere (API, UI, folder listings).
+/// Soft problems with a valid column list: repeated titles, ids that only
+/// differ in `-` versus `_` (ids are always lowercase), and many columns
+/// without a WIP limit.
+fn lint_columns(columns: &[BoardColumn]) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    for (i, column) in columns.iter().enumerate() {
+        let earlier = &columns[..i];
+        if let Some(other) = earlier.iter().find(|c| c.title.trim().eq_ignore_ascii_case(column.title.trim())) {
+            let message = format!("columns {} and {} have the same title {:?}", other.id, column.id, column.title.trim());
+            warnings.push(Warning::new("duplicate_column_title", message).column(&column.id));
         }
-    }
-    let contents = fs::read_to_string(&path)?;
-    let mut columns = Vec::new();
-    for line in contents.lines() {
-        if let Some(column) = parse_config_line(line) {
-            columns.push(column);
+        let lookalike = |id: &str| id.replace('-', "_");
+        if let Some(other) = earlier.iter().find(|c| lookalike(&c.id) == lookalike(&column.id)) {
+            let message = format!("column ids {} and {} differ only in '-' and '_'", other.id, column.id);
+            warnings.push(Warning::new("lookalike_column_ids", message).column(&column.id));
         }
     }
-    if columns.is_empty() {
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            "No valid columns in .workspace-kanban",
+    let unlimited = columns.iter().filter(|c| c.wip_limit.is_none()).count();
+    if unlimited > LINT_UNLIMITED_COLUMNS {
+        warnings.push(Warning::new(
+            "unlimited_columns",
+            format!(
+                "{} columns have no WIP limit; consider limits or fewer columns (warned above {})",
+                unlimited, LINT_UNLIMITED_COLUMNS
+            ),
         ));
     }
-    Ok(BoardConfig { columns })
+    warnings
 }
 
-fn prompt_handle_removed_folder(root: &Path, folder: &str, config: &BoardConfig) -> io::Result<()> {
-    let folder_path = root.join(folder);
-    let mut tasks = Vec::new();
-    if folder_path.exists() {
-        for entry in fs::read_dir(&folder_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().and_then(|e| e.to_str()) == Some("md") {
-                tasks.push(path);
-            }
-        }
-    }
-    if tasks.is_empty() {
-        let _ = fs::remove_dir_all(&folder_path);
-        return Ok(());
-    }
+fn parse_config_contents(contents: &str) -> Vec<BoardColumn> {
+    contents.lines().filter_map(parse_config_line).collect()
+}
 
-    println!(
-        "Folder '{}' is not in {} but contains {} task(s).",
-        folder,
-        CONFIG_FILE,
-        tasks.len()
+/// Saves a board change made by `actor`: stamps `modified_at`/`modified_by`
+/// and appends a `board_changed` entry with the column diff to the activity
+/// log.
+fn write_config(root: &Path, config: &mut BoardConfig, actor: &str) -> io::Result<()> {
+    let before = read_config(root).map(|old| old.columns).unwrap_or_default();
+    config.modified_at = Some(now_iso());
+    config.modified_by = Some(actor.to_string());
+    // Written beside the board file and renamed over it, so readers never
+    // see a half-written config.
+    let staged = root.join(format!("{}.tmp", CONFIG_FILE));
+    let rendered = render_config(config);
+    expect_change(&config_path(root), Some(rendered.as_bytes()));
+    fs::write(&staged, rendered)?;
+    if let Err(err) = fs::rename(&staged, config_path(root)) {
+        let _ = fs::remove_file(&staged);
+        return Err(err);
+    }
+    append_activity(
+        root,
+        serde_json::json!({
+            "at": config.modified_at,
+            "actor": actor,
+            "action": "board_changed",
+            "columns": column_changes(&before, &config.columns),
+        }),
     );
-    println!("Choose action: [d]elete tasks, [m]ove tasks, [a]bort");
-    print!("> ");
-    io::stdout().flush()?;
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    let answer = input.trim().to_lowercase();
-    match answer.as_str() {
-        "d" | "delete" => {
-            for path in tasks {
-                let _ = fs::remove_file(path);
-            }
-            let _ = fs::remove_dir_all(&folder_path);
-            Ok(())
+    Ok(())
+}
+
+/// Serializes board file updates so two edits cannot interleave their
+/// folder creation and write.
+static CONFIG_LOCK: Mutex<()> = Mutex::new(());
+
+/// Creates the folders of columns that have none yet, returning what it
+/// created. On failure the folders created so far are removed again and the
+/// error names the failing path.
+fn create_column_folders(root: &Path, config: &BoardConfig) -> Result<Vec<PathBuf>, String> {
+    let mut created = Vec::new();
+    for column in &config.columns {
+        let dir = root.join(&column.id);
+        if dir.is_dir() {
+            continue;
         }
-        "m" | "move" => {
-            println!("Move to which folder?");
-            for (index, column) in config.columns.iter().enumerate() {
-                println!("  {}) {} ({})", index + 1, column.title, column.id);
-            }
-            print!("> ");
-            io::stdout().flush()?;
-            let mut choice = String::new();
-            io::stdin().read_line(&mut choice)?;
-            let idx: usize = choice.trim().parse().unwrap_or(0);
-            if idx == 0 || idx > config.columns.len() {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    "Invalid move target",
-                ));
-            }
-            let target = &config.columns[idx - 1].id;
-            fs::create_dir_all(root.join(target))?;
-            for path in tasks {
-                if let Some(filename) = path.file_name() {
-                    let dest = root.join(target).join(filename);
-                    fs::rename(&path, &dest)?;
-                    if let Ok(mut task) = parse_task(&dest, target) {
-                        task.folder = target.to_string();
-                        task.status = target.to_string();
-                        task.updated_at = now_iso();
-                        let _ = write_task(&dest, &task);
-                    }
-                }
-            }
-            let _ = fs::remove_dir_all(&folder_path);
-            Ok(())
+        if let Err(err) = fs::create_dir(&dir) {
+            remove_created_folders(&created);
+            return Err(format!("{}: {}", dir.display(), err));
         }
-        _ => Err(io::Error::new(io::ErrorKind::Other, "Aborted")),
+        created.push(dir);
     }
+    Ok(created)
 }
 
-fn reconcile_folders(root: &Path, config: &BoardConfig, yes: bool) -> io::Result<()> {
-    ensure_folders(root, config)?;
-    if !root.exists() {
-        return Ok(());
+fn remove_created_folders(created: &[PathBuf]) {
+    for dir in created.iter().rev() {
+        if let Err(err) = fs::remove_dir(dir) {
+            eprintln!("Failed to remove {}: {}", dir.display(), err);
+        }
     }
-    let mut allowed: HashMap<String, bool> = HashMap::new();
-    for column in &config.columns {
-        allowed.insert(column.id.clone(), true);
+}
+
+/// Saves a validated board edit: the new columns' folders first, then the
+/// board file. If either step fails, the folders it created are removed and
+/// the previous board file stays in place.
+fn apply_board_config(root: &Path, config: &mut BoardConfig, actor: &str) -> Result<(), String> {
+    let _guard = CONFIG_LOCK.lock().unwrap();
+    let created = create_column_folders(root, config)?;
+    if let Err(err) = write_config(root, config, actor) {
+        remove_created_folders(&created);
+        return Err(format!("{}: {}", config_path(root).display(), err));
     }
-    for entry in fs::read_dir(root)? {
-        let entry = entry?;
-        let path = entry.path();
-        if !path.is_dir() {
-            continue;
+    Ok(())
+}
+
+/// One entry of the column diff recorded with `board_changed`.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(tag = "change", rename_all = "snake_case")]
+enum ColumnChange {
+    Added { column: String },
+    Removed { column: String },
+    Renamed { column: String, from: String, to: String },
+    LimitChanged { column: String, from: Option<u32>, to: Option<u32> },
+    LockChanged { column: String, locked: bool },
+}
+
+fn column_changes(old: &[BoardColumn], new: &[BoardColumn]) -> Vec<ColumnChange> {
+    let limit = |column: &BoardColumn| column.wip_limit;
+    let mut changes = Vec::new();
+    for column in old {
+        if !new.iter().any(|c| c.id == column.id) {
+            changes.push(ColumnChange::Removed { column: column.id.clone() });
         }
-        let folder_name = entry.file_name().to_string_lossy().to_string();
-        if folder_name == ".git" {
+    }
+    for column in new {
+        let Some(previous) = old.iter().find(|c| c.id == column.id) else {
+            changes.push(ColumnChange::Added { column: column.id.clone() });
             continue;
+        };
+        if previous.title != column.title {
+            changes.push(ColumnChange::Renamed {
+                column: column.id.clone(),
+                from: previous.title.clone(),
+                to: column.title.clone(),
+            });
         }
-        if !allowed.contains_key(&folder_name) {
-            if yes {
-                let has_tasks = fs::read_dir(&path)?
-                    .filter_map(|e| e.ok())
-                    .any(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("md"));
-                if has_tasks {
-                    return Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        format!(
-                            "Folder '{}' has tasks but is not in {}; run without -y to resolve",
-                            folder_name, CONFIG_FILE
-                        ),
-                    ));
-                } else {
-                    let _ = fs::remove_dir_all(&path);
-                }
-            } else {
-                prompt_handle_removed_folder(root, &folder_name, config)?;
-            }
+        if limit(previous) != limit(column) {
+            changes.push(ColumnChange::LimitChanged {
+                column: column.id.clone(),
+                from: limit(previous),
+                to: limit(column),
+            });
+        }
+        if previous.locked != column.locked {
+            changes.push(ColumnChange::LockChanged { column: column.id.clone(), locked: column.locked });
         }
     }
-    Ok(())
+    changes
 }
 
-fn refresh_config(root: &Path, yes: bool) -> Result<BoardConfig, String> {
-    let config = load_config(root, yes).map_err(|err| err.to_string())?;
-    reconcile_folders(root, &config, yes).map_err(|err| err.to_string())?;
-    Ok(config)
+/// Appends one line to the activity log. Logging is best effort: a board
+/// whose log cannot be written keeps working.
+fn append_activity(root: &Path, entry: serde_json::Value) {
+    let appended = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(root.join(ACTIVITY_FILE))
+        .and_then(|mut file| writeln!(file, "{}", entry));
+    if let Err(err) = appended {
+        eprintln!("Could not write {}: {}", ACTIVITY_FILE, err);
+    }
 }
 
-fn print_help() {
-    println!(r#"Kanban Task Files server
+/// Appends a task change to the activity log; `extra` (an object such as
+/// `{"from": .., "to": ..}`) is merged into the entry.
+fn record_task_activity(root: &Path, hooks: &Hooks, actor: &str, action: &str, task: &str, extra: serde_json::Value) {
+    let mut entry = serde_json::json!({ "at": now_iso(), "actor": actor, "action": action, "task": task });
+    if let (Some(entry), serde_json::Value::Object(extra)) = (entry.as_object_mut(), extra) {
+        entry.extend(extra);
+    }
+    notify_hooks(root, hooks, &entry);
+    append_activity(root, entry);
+}
 
-Usage:
-  kanban-server [options]
+/// Logs a `task_mentioned` entry for each user `task` mentions that were not
+/// in `before`, so hooks, webhooks and subscriptions can tell them.
+fn record_new_mentions(root: &Path, hooks: &Hooks, actor: &str, task: &Task, before: &[String]) {
+    for user in task.mentions.iter().filter(|user| !before.contains(user)) {
+        record_task_activity(root, hooks, actor, "task_mentioned", &task.id, serde_json::json!({ "user": user }));
+    }
+}
 
-Options:
-  -t, --target <dir>             Base directory for task folders (default: ./kanban_data or KANBAN_ROOT)
-  -y, --yes                      Create missing folders without prompting
-  -h, --help                     Show this help message
-      --show-task-editor=<bool>  Show task editor on load (default: true)
-      --show-board-editor=<bool> Show board editor on load (default: false)
-      --write-default-theme      Create .kanban-theme.conf with default values
-      --open-browser=<bool>      Open default system browser on start (default: false)
-      --open-browser-once=<bool> Open browser only once per target (default: true)
+/// Names `--hook-events` accepts, one per task activity, plus `violation` for
+/// a task newly past its column's `max_age`.
+const HOOK_EVENTS: [&str; 8] = ["create", "move", "edit", "delete", "transfer", "handover", "mention", "violation"];
+/// Activity actions a subscription can ask for; the ones hooks run for.
+const SUBSCRIPTION_EVENTS: [&str; 7] =
+    ["task_created", "task_moved", "task_edited", "task_deleted", "task_transferred", "tasks_handed_over", "task_mentioned"];
+/// A hook still running after this long is killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+const HOOK_MAX_RUNNING: usize = 4;
 
-Environment:
-  KANBAN_ROOT   Default base directory if --target is not provided
-  KANBAN_PORT   Port to bind (default: 8787)
+#[derive(Debug, Clone)]
+struct Hook {
+    command: PathBuf,
+    /// Events to run for; empty runs for all.
+    events: Vec<String>,
+    /// The served board; activity on transfer targets does not run the hook.
+    root: PathBuf,
+}
 
-The server reads .workspace-kanban for board structure and ensures folders exist.
-"#);
+/// Where a server sends its board's task activity besides the activity log:
+/// `--hook` and `--webhook`. CLI commands use the empty default, so they
+/// never run either.
+#[derive(Default)]
+struct Hooks {
+    hook: Option<Hook>,
+    webhook: Option<Arc<Webhook>>,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct UiOptions {
-    show_task_editor: bool,
-    show_board_editor: bool,
+/// The `--hook-events` name of an activity-log action.
+fn hook_event(action: &str) -> Option<&'static str> {
+    match action {
+        "task_created" => Some("create"),
+        "task_moved" => Some("move"),
+        "task_edited" => Some("edit"),
+        "task_deleted" => Some("delete"),
+        "task_transferred" => Some("transfer"),
+        "tasks_handed_over" => Some("handover"),
+        "task_mentioned" => Some("mention"),
+        "policy_violation" => Some("violation"),
+        _ => None,
+    }
 }
 
-fn parse_args() -> Result<(Option<String>, bool, UiOptions, bool, bool, bool), String> {
-    let mut args = std::env::args().skip(1);
-    let mut target: Option<String> = None;
-    let mut yes = false;
-    let mut write_default_settings = false;
-    let mut open_browser = false;
-    let mut open_browser_once = true;
-    let mut ui = UiOptions {
-        show_task_editor: true,
-        show_board_editor: false,
+/// Environment for a hook run: the event, the task id and the columns it
+/// left and entered (empty when they do not apply).
+fn hook_env(event: &str, entry: &serde_json::Value) -> Vec<(&'static str, String)> {
+    let text = |key: &str| entry[key].as_str().unwrap_or("").to_string();
+    let (from, to) = match event {
+        "create" => (String::new(), text("column")),
+        "delete" => (text("column"), String::new()),
+        _ => (text("from"), text("to")),
     };
-    while let Some(arg) = args.next() {
-        match arg.as_str() {
-            "-t" | "--target" => {
-                let value = args.next().ok_or("Missing value for --target")?;
-                target = Some(value);
-            }
-            "-y" | "--yes" => {
-                yes = true;
-            }
-            "--write-default-theme" => {
-                write_default_settings = true;
-            }
-            "-h" | "--help" => {
-                print_help();
-                std::process::exit(0);
-            }
-            _ if arg.starts_with("--show-task-editor=") => {
-                ui.show_task_editor = parse_bool_flag(&arg, "--show-task-editor")?;
-            }
-            _ if arg.starts_with("--show-board-editor=") => {
-                ui.show_board_editor = parse_bool_flag(&arg, "--show-board-editor")?;
-            }
-            _ if arg.starts_with("--open-browser=") => {
-                open_browser = parse_bool_flag(&arg, "--open-browser")?;
-            }
-            _ if arg.starts_with("--open-browser-once=") => {
-                open_browser_once = parse_bool_flag(&arg, "--open-browser-once")?;
-            }
-            "--show-task-editor" | "--show-board-editor" | "--open-browser" | "--open-browser-once" => {
-                return Err("Use --show-task-editor=<true|false>, --show-board-editor=<true|false>, --open-browser=<true|false>, or --open-browser-once=<true|false>".to_string());
-            }
-            _ => return Err(format!("Unknown argument: {}", arg)),
-        }
-    }
-    Ok((target, yes, ui, write_default_settings, open_browser, open_browser_once))
+    vec![
+        ("KANBAN_EVENT", event.to_string()),
+        ("KANBAN_TASK_ID", text("task")),
+        ("KANBAN_FROM", from),
+        ("KANBAN_TO", to),
+    ]
 }
-fn parse_bool_flag(arg: &str, name: &str) -> Result<bool, String> {
-    let value = arg
-        .split_once('=')
-        .map(|(_, v)| v)
-        .ok_or_else(|| format!("Missing value for {}", name))?;
-    match value.to_lowercase().as_str() {
-        "true" | "1" | "yes" | "on" => Ok(true),
-        "false" | "0" | "no" | "off" => Ok(false),
-        _ => Err(format!("Invalid boolean for {}: {}", name, value)),
+
+/// Hands an activity entry to the `--hook` and the `--webhook`. Both get the
+/// same event: the entry with its `event` name and the `subscriptions` it
+/// matched.
+fn notify_hooks(root: &Path, hooks: &Hooks, entry: &serde_json::Value) {
+    let Some(event) = entry["action"].as_str().and_then(hook_event) else {
+        return;
+    };
+    let hook = hooks.hook.as_ref().filter(|hook| hook.root == root);
+    let webhook = hooks.webhook.as_deref().filter(|webhook| webhook.root == root);
+    if hook.is_none() && webhook.is_none() {
+        return;
+    }
+    let mut input = entry.clone();
+    input["event"] = serde_json::json!(event);
+    input["subscriptions"] = serde_json::json!(matching_subscriptions(root, entry));
+    if let Some(hook) = hook {
+        run_hook(hook, event, &input);
+    }
+    if let Some(webhook) = webhook {
+        queue_webhook(webhook, input);
     }
 }
 
-fn open_browser_url(url: &str) -> io::Result<()> {
-    #[cfg(target_os = "windows")]
-    {
-        Command::new("cmd").args(["/C", "start", "", url]).spawn()?;
-        return Ok(());
+/// Starts the `--hook` command for an event in the background. Never waits
+/// for it: failures are only logged.
+fn run_hook(hook: &Hook, event: &'static str, input: &serde_json::Value) {
+    if !hook.events.is_empty() && !hook.events.iter().any(|e| e == event) {
+        return;
     }
-    #[cfg(target_os = "macos")]
-    {
-        Command::new("open").arg(url).spawn()?;
-        return Ok(());
+    if HOOKS_RUNNING.fetch_add(1, Ordering::SeqCst) >= HOOK_MAX_RUNNING {
+        HOOKS_RUNNING.fetch_sub(1, Ordering::SeqCst);
+        eprintln!("Hook skipped for {} {}: {} hooks already running", event, input["task"], HOOK_MAX_RUNNING);
+        return;
     }
-    #[cfg(target_os = "linux")]
-    {
-        Command::new("xdg-open").arg(url).spawn()?;
-        return Ok(());
+    let command = hook.command.clone();
+    let input = input.clone();
+    std::thread::spawn(move || {
+        let result = run_hook_command(&command, &hook_env(event, &input), &input.to_string(), HOOK_TIMEOUT);
+        HOOKS_RUNNING.fetch_sub(1, Ordering::SeqCst);
+        if let Err(msg) = result {
+            eprintln!("Hook {} failed for {} {}: {}", command.display(), event, input["task"], msg);
+        }
+    });
+}
+
+/// Runs `command` with `input` on stdin, killing it after `timeout`. A
+/// non-zero exit is an error carrying the captured stderr.
+fn run_hook_command(command: &Path, env: &[(&str, String)], input: &str, timeout: Duration) -> Result<(), String> {
+    let mut child = Command::new(command)
+        .envs(env.iter().map(|(key, value)| (*key, value)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| err.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores its input may exit before reading it.
+        let _ = stdin.write_all(input.as_bytes());
     }
-    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-    {
-        Err(io::Error::new(
-            io::ErrorKind::Other,
-            "open browser not supported on this platform",
-        ))
+    let stderr = child.stderr.take().map(|mut pipe| {
+        std::thread::spawn(move || {
+            let mut text = String::new();
+            let _ = pipe.read_to_string(&mut text);
+            text
+        })
+    });
+    let started = SystemTime::now();
+    let status = loop {
+        match child.try_wait().map_err(|err| err.to_string())? {
+            Some(status) => break status,
+            None if started.elapsed().unwrap_or_default() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {:?}", timeout));
+            }
+            None => std::thread::sleep(Duration::from_millis(20)),
+        }
+    };
+    if status.success() {
+        return Ok(());
     }
+    let stderr = stderr.and_then(|reader| reader.join().ok()).unwrap_or_default();
+    Err(format!("{}: {}", status, stderr.trim()))
 }
 
-fn browser_marker_path(root: &Path) -> PathBuf {
-    root.join(".kanban-browser-opened")
+/// Outgoing `--webhook` deliveries with their status, oldest first, one JSON
+/// object per line in the board root.
+const WEBHOOK_LOG_FILE: &str = ".kanban-webhook-log.jsonl";
+/// Deliveries kept in `WEBHOOK_LOG_FILE`; storing another drops the oldest.
+const WEBHOOK_LOG_LIMIT: usize = 500;
+/// A webhook receiver that has not answered after this long failed.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Wait before the first retry; it doubles with every attempt up to
+/// `WEBHOOK_MAX_BACKOFF`.
+const WEBHOOK_FIRST_BACKOFF: Duration = Duration::from_secs(30);
+const WEBHOOK_MAX_BACKOFF: Duration = Duration::from_secs(3600);
+/// How often the delivery thread looks for failed deliveries that are due.
+const WEBHOOK_RETRY_SCAN: Duration = Duration::from_secs(5);
+const DEFAULT_WEBHOOK_RETRY_HOURS: u64 = 24;
+
+/// Serializes read-modify-write cycles of `WEBHOOK_LOG_FILE`.
+static WEBHOOK_LOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// A `--webhook` receiver. Only plain `http://` is spoken; `https://`
+/// receivers need a TLS-terminating proxy in front.
+#[derive(Debug, Clone, PartialEq)]
+struct WebhookUrl {
+    /// `host` or `host:port`, as sent in the `Host` header.
+    authority: String,
+    host: String,
+    port: u16,
+    path: String,
 }
 
-fn notify_update(state: &Arc<UpdateState>) {
-    state.version.fetch_add(1, Ordering::SeqCst);
-    state.cvar.notify_all();
+impl WebhookUrl {
+    fn parse(raw: &str) -> Result<WebhookUrl, String> {
+        let invalid = || format!("Invalid value for --webhook: {} (use http://host[:port]/path)", raw);
+        let rest = raw.trim().strip_prefix("http://").ok_or_else(invalid)?;
+        let (authority, path) = rest.find('/').map_or((rest, "/"), |at| rest.split_at(at));
+        let (host, port) = match authority.rsplit_once(':').filter(|(_, port)| !port.contains(']')) {
+            Some((host, port)) => (host, port.parse().map_err(|_| invalid())?),
+            None => (authority, 80),
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if host.is_empty() || path.chars().any(char::is_whitespace) {
+            return Err(invalid());
+        }
+        Ok(WebhookUrl {
+            authority: authority.to_string(),
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
 }
 
-fn parse_since(url: &str) -> u64 {
-    if let Some(query) = url.split('?').nth(1) {
-        for pair in query.split('&') {
-            if let Some((key, value)) = pair.split_once('=') {
-                if key == "since" {
-                    if let Ok(parsed) = value.parse::<u64>() {
-                        return parsed;
-                    }
-                }
-            }
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum DeliveryStatus {
+    /// Stored, not sent yet.
+    Pending,
+    Delivered,
+    /// The last attempt failed; retried at `next_attempt_at` when set.
+    Failed,
+}
+
+impl DeliveryStatus {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "pending" => Ok(DeliveryStatus::Pending),
+            "delivered" => Ok(DeliveryStatus::Delivered),
+            "failed" => Ok(DeliveryStatus::Failed),
+            other => Err(format!("Invalid status: {} (expected pending, delivered or failed)", other)),
         }
     }
-    0
 }
 
-fn slugify(input: &str) -> String {
-    let mut out = String::new();
-    let mut last_dash = false;
-    for ch in input.to_lowercase().chars() {
-        if ch.is_ascii_alphanumeric() {
-            out.push(ch);
-            last_dash = false;
-        } else if ch.is_whitespace() || ch == '-' || ch == '_' {
-            if !last_dash {
-                out.push('-');
-                last_dash = true;
+/// One POST to the webhook: the events it carries and how sending went.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct Delivery {
+    id: String,
+    created_at: String,
+    status: DeliveryStatus,
+    attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_attempt_at: Option<String>,
+    /// Unset once the retry window is over; only a redelivery sends it then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    next_attempt_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    events: Vec<serde_json::Value>,
+}
+
+/// The `--webhook` of the served board and the events waiting for their
+/// batch window to pass.
+struct Webhook {
+    url: WebhookUrl,
+    root: PathBuf,
+    batch_window: Duration,
+    retry_for: Duration,
+    queue: Mutex<WebhookQueue>,
+    cvar: Condvar,
+}
+
+#[derive(Default)]
+struct WebhookQueue {
+    events: Vec<serde_json::Value>,
+    /// When the oldest queued event arrived.
+    since: Option<Instant>,
+}
+
+/// Queues an event for the `--webhook`. The delivery thread sends it.
+fn queue_webhook(webhook: &Webhook, entry: serde_json::Value) {
+    let mut queue = webhook.queue.lock().unwrap();
+    queue.since.get_or_insert_with(Instant::now);
+    queue.events.push(entry);
+    webhook.cvar.notify_all();
+}
+
+/// Takes the queued events once `window` has passed since the oldest: all
+/// of them as one batch, or one batch per event when there is no window.
+fn take_batches(queue: &mut WebhookQueue, window: Duration, now: Instant) -> Vec<Vec<serde_json::Value>> {
+    match queue.since {
+        Some(since) if now.saturating_duration_since(since) >= window => {
+            queue.since = None;
+            let events = std::mem::take(&mut queue.events);
+            if window.is_zero() {
+                events.into_iter().map(|event| vec![event]).collect()
+            } else {
+                vec![events]
             }
         }
-    }
-    let trimmed = out.trim_matches('-').to_string();
-    if trimmed.is_empty() {
-        "task".to_string()
-    } else {
-        trimmed
+        _ => Vec::new(),
     }
 }
 
-fn is_valid_id(id: &str) -> bool {
-    !id.is_empty() && id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+/// The delivery thread: stores and sends every batch as it becomes due and
+/// retries failed deliveries, including those left by an earlier run.
+fn run_webhook(webhook: &Webhook, stopped: &AtomicBool) {
+    let mut last_scan: Option<Instant> = None;
+    while !stopped.load(Ordering::SeqCst) {
+        let batches = {
+            let queue = webhook.queue.lock().unwrap();
+            let wait = queue.since.map_or(WEBHOOK_RETRY_SCAN, |since| webhook.batch_window.saturating_sub(since.elapsed()));
+            let mut queue = if wait.is_zero() { queue } else { webhook.cvar.wait_timeout(queue, wait).unwrap().0 };
+            take_batches(&mut queue, webhook.batch_window, Instant::now())
+        };
+        for events in batches {
+            let delivery = Delivery {
+                id: unique_id(),
+                created_at: now_iso(),
+                status: DeliveryStatus::Pending,
+                attempts: 0,
+                last_attempt_at: None,
+                next_attempt_at: None,
+                error: None,
+                events,
+            };
+            if let Err(err) = store_delivery(&webhook.root, &delivery) {
+                eprintln!("Could not write {}: {}", WEBHOOK_LOG_FILE, err);
+            }
+            attempt_delivery(&webhook.root, &webhook.url, delivery, webhook.retry_for);
+        }
+        if last_scan.is_none_or(|at| at.elapsed() >= WEBHOOK_RETRY_SCAN) {
+            last_scan = Some(Instant::now());
+            let now = OffsetDateTime::now_utc();
+            for delivery in read_deliveries(&webhook.root).into_iter().filter(|d| delivery_due(d, now)) {
+                attempt_delivery(&webhook.root, &webhook.url, delivery, webhook.retry_for);
+            }
+        }
+    }
 }
 
-fn unique_slug(root: &Path, base: &str, config: &BoardConfig) -> String {
-    if !exists_anywhere(root, base, config) {
-        return base.to_string();
+/// Whether the delivery thread should send a stored delivery now.
+fn delivery_due(delivery: &Delivery, now: OffsetDateTime) -> bool {
+    match delivery.status {
+        DeliveryStatus::Pending => true,
+        DeliveryStatus::Delivered => false,
+        DeliveryStatus::Failed => {
+            delivery.next_attempt_at.as_deref().and_then(parse_timestamp).is_some_and(|at| at <= now)
+        }
     }
-    let mut n = 2;
-    loop {
-        let candidate = format!("{}-{}", base, n);
-        if !exists_anywhere(root, &candidate, config) {
-            return candidate;
+}
+
+/// When a delivery that failed `attempts` times is retried: after
+/// `WEBHOOK_FIRST_BACKOFF`, doubled per attempt up to `WEBHOOK_MAX_BACKOFF`.
+/// None once that is more than `retry_for` after the delivery was created.
+fn next_attempt(created_at: &str, attempts: u32, now: OffsetDateTime, retry_for: Duration) -> Option<String> {
+    let created = parse_timestamp(created_at).unwrap_or(now);
+    let backoff = WEBHOOK_FIRST_BACKOFF
+        .saturating_mul(1 << attempts.saturating_sub(1).min(16))
+        .min(WEBHOOK_MAX_BACKOFF);
+    let at = now + backoff;
+    (at <= created + retry_for).then(|| format_timestamp(at))
+}
+
+/// Sends a delivery once and stores how it went.
+fn attempt_delivery(root: &Path, url: &WebhookUrl, mut delivery: Delivery, retry_for: Duration) -> Delivery {
+    let now = OffsetDateTime::now_utc();
+    delivery.attempts += 1;
+    delivery.last_attempt_at = Some(format_timestamp(now));
+    let body = serde_json::json!({ "delivery": delivery.id, "events": delivery.events });
+    match post_webhook(url, &delivery.id, &body.to_string()) {
+        Ok(()) => {
+            delivery.status = DeliveryStatus::Delivered;
+            delivery.next_attempt_at = None;
+            delivery.error = None;
         }
-        n += 1;
+        Err(msg) => {
+            delivery.status = DeliveryStatus::Failed;
+            delivery.next_attempt_at = next_attempt(&delivery.created_at, delivery.attempts, now, retry_for);
+            delivery.error = Some(msg);
+        }
+    }
+    if let Err(err) = store_delivery(root, &delivery) {
+        eprintln!("Could not write {}: {}", WEBHOOK_LOG_FILE, err);
     }
+    delivery
 }
 
-fn exists_anywhere(root: &Path, id: &str, config: &BoardConfig) -> bool {
-    config
-        .columns
-        .iter()
-        .any(|column| root.join(&column.id).join(format!("{}.md", id)).exists())
+/// POSTs `body` to the webhook; any 2xx answer is a success.
+fn post_webhook(url: &WebhookUrl, delivery: &str, body: &str) -> Result<(), String> {
+    use std::net::{TcpStream, ToSocketAddrs};
+    let addr = (url.host.as_str(), url.port)
+        .to_socket_addrs()
+        .map_err(|err| err.to_string())?
+        .next()
+        .ok_or_else(|| format!("cannot resolve {}", url.host))?;
+    let mut stream = TcpStream::connect_timeout(&addr, WEBHOOK_TIMEOUT).map_err(|err| err.to_string())?;
+    stream.set_read_timeout(Some(WEBHOOK_TIMEOUT)).map_err(|err| err.to_string())?;
+    stream.set_write_timeout(Some(WEBHOOK_TIMEOUT)).map_err(|err| err.to_string())?;
+    let head = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: kanban-server\r\nContent-Type: application/json\r\nContent-Length: {}\r\nX-Kanban-Delivery: {}\r\nConnection: close\r\n\r\n",
+        url.path,
+        url.authority,
+        body.len(),
+        delivery
+    );
+    stream
+        .write_all(head.as_bytes())
+        .and_then(|_| stream.write_all(body.as_bytes()))
+        .map_err(|err| err.to_string())?;
+    let mut status_line = String::new();
+    io::BufReader::new(&stream).read_line(&mut status_line).map_err(|err| err.to_string())?;
+    match status_line.split_whitespace().nth(1).and_then(|code| code.parse::<u16>().ok()) {
+        Some(code) if (200..300).contains(&code) => Ok(()),
+        Some(code) => Err(format!("HTTP {}", code)),
+        None => Err("no HTTP response".to_string()),
+    }
 }
 
-fn task_path(root: &Path, folder: &str, id: &str) -> PathBuf {
-    root.join(folder).join(format!("{}.md", id))
+/// Lines that do not parse are dropped.
+fn read_deliveries(root: &Path) -> Vec<Delivery> {
+    fs::read_to_string(root.join(WEBHOOK_LOG_FILE))
+        .map(|text| text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Adds a delivery to the log or replaces the one with its id, dropping the
+/// oldest beyond `WEBHOOK_LOG_LIMIT`.
+fn store_delivery(root: &Path, delivery: &Delivery) -> io::Result<()> {
+    let _guard = WEBHOOK_LOG_LOCK.lock().unwrap();
+    let mut deliveries = read_deliveries(root);
+    match deliveries.iter_mut().find(|stored| stored.id == delivery.id) {
+        Some(stored) => *stored = delivery.clone(),
+        None => deliveries.push(delivery.clone()),
+    }
+    let excess = deliveries.len().saturating_sub(WEBHOOK_LOG_LIMIT);
+    deliveries.drain(..excess);
+    let text: String = deliveries.iter().map(|d| format!("{}\n", serde_json::json!(d))).collect();
+    let staged = root.join(format!("{}.tmp", WEBHOOK_LOG_FILE));
+    fs::write(&staged, text)?;
+    fs::rename(&staged, root.join(WEBHOOK_LOG_FILE))
+}
+
+/// Who a request acts for: the `X-Kanban-User` header, or "server".
+fn request_actor(request: &tiny_http::Request) -> String {
+    header_value(request, "X-Kanban-User")
+        .map(|user| user.trim().chars().take(64).collect::<String>())
+        .filter(|user| !user.is_empty())
+        .unwrap_or_else(|| "server".to_string())
 }
 
-fn find_task_path(root: &Path, id: &str, config: &BoardConfig) -> Option<(PathBuf, String)> {
+/// Whether the request carries `Authorization: Bearer <--admin-token>`.
+fn is_admin(request: &tiny_http::Request, settings: &Settings) -> bool {
+    let Some(token) = settings.admin_token.as_deref() else {
+        return false;
+    };
+    header_value(request, "Authorization")
+        .and_then(|value| value.strip_prefix("Bearer ").map(|v| v.trim() == token))
+        .unwrap_or(false)
+}
+
+fn render_config(config: &BoardConfig) -> String {
+    let mut contents = String::new();
+    if let Some(at) = &config.modified_at {
+        contents.push_str(&format!("modified_at = {}\n", at));
+    }
+    if let Some(by) = &config.modified_by {
+        contents.push_str(&format!("modified_by = {}\n", by));
+    }
+    if config.status_mode == StatusMode::Free {
+        contents.push_str("status_mode = free\n");
+    }
+    if !config.statuses.is_empty() {
+        contents.push_str(&format!("statuses = {}\n", config.statuses.join(", ")));
+    }
+    if let Some(column) = &config.default_column {
+        contents.push_str(&format!("default_column = {}\n", column));
+    }
+    if let Some(max) = config.max_tasks {
+        contents.push_str(&format!("max_tasks = {}\n", max));
+    }
+    match config.unique_titles {
+        UniqueTitles::Off => {}
+        UniqueTitles::Warn => contents.push_str("unique_titles = warn\n"),
+        UniqueTitles::Enforce => contents.push_str("unique_titles = enforce\n"),
+    }
+    if let Some(collision) = config.collision {
+        contents.push_str(&format!("collision = {}\n", collision.as_str()));
+    }
+    if config.slug_stopwords {
+        contents.push_str("slug_stopwords = true\n");
+    }
+    if !config.slug_stopwords_extra.is_empty() {
+        contents.push_str(&format!("slug_stopwords_extra = {}\n", config.slug_stopwords_extra.join(", ")));
+    }
+    if !config.private_fields.is_empty() {
+        contents.push_str(&format!("private_fields = {}\n", config.private_fields.join(", ")));
+    }
+    if let (Some(start), Some(end)) = (&config.sprint_start, &config.sprint_end) {
+        contents.push_str(&format!("sprint_start = {}\nsprint_end = {}\n", start, end));
+    }
+    for field in &config.fields {
+        match field.kind {
+            FieldKind::Text => contents.push_str(&format!("field {}: text\n", field.name)),
+            FieldKind::Number => contents.push_str(&format!("field {}: number\n", field.name)),
+            FieldKind::Enum => contents.push_str(&format!("field {}: enum({})\n", field.name, field.values.join(","))),
+        }
+    }
+    for rule in &config.rules {
+        contents.push_str(&format!("rule {}\n", rule.spec()));
+    }
     for column in &config.columns {
-        let path = task_path(root, &column.id, id);
-        if path.exists() {
-            return Some((path, column.id.clone()));
+        contents.push_str(&format!("{}: {}", column.id, column.title));
+        if let Some(limit) = column.wip_limit {
+            contents.push_str(&format!(" wip={}", limit));
+        }
+        if !column.default_tags.is_empty() {
+            contents.push_str(&format!(" default_tags={}", column.default_tags.join(",")));
+        }
+        if let Some(assignee) = column.default_assignee.as_deref().filter(|a| !a.is_empty()) {
+            contents.push_str(&format!(" default_assignee={}", assignee));
+        }
+        if column.done {
+            contents.push_str(" done=true");
+        }
+        if let Some(template) = &column.template {
+            contents.push_str(&format!(" template={}", template));
+        }
+        if let Some(sort) = &column.sort {
+            contents.push_str(&format!(" sort={}", sort));
+        }
+        if let Some(dir) = &column.sort_dir {
+            contents.push_str(&format!(" dir={}", dir));
         }
+        if !column.auto_assign.is_empty() {
+            contents.push_str(&format!(" auto_assign={}", column.auto_assign.join(",")));
+        }
+        if column.locked {
+            contents.push_str(" locked=true");
+        }
+        if let Some(max_age) = &column.max_age {
+            contents.push_str(&format!(" max_age={}", max_age));
+        }
+        contents.push('\n');
     }
-    None
+    contents
 }
 
-fn parse_task(path: &Path, folder: &str) -> io::Result<Task> {
-    let content = fs::read_to_string(path)?;
-    let mut lines = content.lines();
-    let mut header: HashMap<String, String> = HashMap::new();
-    let mut description_lines: Vec<String> = Vec::new();
-    let mut in_body = false;
-    while let Some(line) = lines.next() {
-        if !in_body {
-            if line.trim().is_empty() {
-                in_body = true;
-                continue;
+/// Reorders `columns` to follow `order`, which must list every existing
+/// column id exactly once. Column fields are carried over untouched.
+fn reorder_columns(columns: &[BoardColumn], order: &[String]) -> Result<Vec<BoardColumn>, String> {
+    if order.len() != columns.len() {
+        return Err(format!(
+            "Expected {} column ids, got {}",
+            columns.len(),
+            order.len()
+        ));
+    }
+    let mut reordered: Vec<BoardColumn> = Vec::with_capacity(columns.len());
+    for id in order {
+        if reordered.iter().any(|c| &c.id == id) {
+            return Err(format!("Duplicate column id: {}", id));
+        }
+        match columns.iter().find(|c| &c.id == id) {
+            Some(column) => reordered.push(column.clone()),
+            None => return Err(format!("Unknown column id: {}", id)),
+        }
+    }
+    Ok(reordered)
+}
+
+fn absolute_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Expands a leading `~` (alone or followed by a separator) to the home
+/// directory.
+fn expand_home(raw: &str) -> PathBuf {
+    match (raw.strip_prefix('~'), home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            home.join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(raw),
+    }
+}
+
+/// The board root as one unambiguous absolute path: `~` expanded, symlinks
+/// resolved, no `.`, `..` or trailing separator. Parts that do not exist yet
+/// (a root created with `--create-root`) are kept as given.
+fn canonical_root(raw: &str) -> io::Result<PathBuf> {
+    let mut resolved = PathBuf::new();
+    for component in std::path::absolute(expand_home(raw))?.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                resolved.pop();
             }
-            if let Some((key, value)) = line.split_once(':') {
-                header.insert(key.trim().to_string(), value.trim().to_string());
+            std::path::Component::Normal(name) => {
+                resolved.push(name);
+                if let Ok(real) = fs::canonicalize(&resolved) {
+                    resolved = real;
+                }
             }
-        } else {
-            description_lines.push(line.to_string());
+            other => resolved.push(other),
         }
     }
-    let file_stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("task");
-    let tags = header
-        .get("tags")
-        .map(|v| {
-            v.split(',')
-                .map(|t| t.trim().to_string())
-                .filter(|t| !t.is_empty())
-                .collect::<Vec<_>>()
-        })
-        .unwrap_or_default();
-    Ok(Task {
-        id: file_stem.to_string(),
-        title: header.get("title").cloned().unwrap_or_default(),
-        description: description_lines.join("\n"),
-        creator: header.get("creator").cloned().unwrap_or_default(),
-        assigned_to: header.get("assigned_to").cloned().unwrap_or_default(),
-        created_at: header.get("created_at").cloned().unwrap_or_default(),
-        updated_at: header.get("updated_at").cloned().unwrap_or_default(),
-        status: header.get("status").cloned().unwrap_or_else(|| folder.to_string()),
-        tags,
-        folder: folder.to_string(),
-    })
+    Ok(resolved)
 }
 
-fn write_task(path: &Path, task: &Task) -> io::Result<()> {
-    let tags = if task.tags.is_empty() {
-        String::new()
+/// Why a canonical `root` is refused without `--allow-unusual-root`: boards
+/// write folders and dot-files into their root, which is never wanted in
+/// the file system root, the home directory itself or next to the binary.
+fn unusual_root_reason(root: &Path) -> Option<&'static str> {
+    let is_root = |dir: Option<PathBuf>| dir.and_then(|dir| fs::canonicalize(dir).ok()).is_some_and(|dir| dir == root);
+    if root.parent().is_none() {
+        Some("the file system root")
+    } else if is_root(home_dir()) {
+        Some("the home directory")
+    } else if is_root(std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf))) {
+        Some("the directory of the server binary")
     } else {
-        task.tags.join(", ")
-    };
-    let body = format!(
-        "creator: {}\nassigned_to: {}\ncreated_at: {}\nupdated_at: {}\nstatus: {}\ntags: {}\ntitle: {}\n\n{}\n",
-        task.creator,
-        task.assigned_to,
-        task.created_at,
-        task.updated_at,
-        task.status,
-        tags,
-        task.title,
-        task.description
-    );
-    fs::write(path, body)
+        None
+    }
 }
 
-fn load_all_tasks(root: &Path, config: &BoardConfig) -> io::Result<HashMap<String, Vec<Task>>> {
-    let mut out: HashMap<String, Vec<Task>> = HashMap::new();
-    for column in &config.columns {
-        let mut tasks = Vec::new();
-        let dir = root.join(&column.id);
-        if dir.exists() {
-            for entry in fs::read_dir(dir)? {
-                let entry = entry?;
-                let path = entry.path();
-                if path.extension().and_then(|e| e.to_str()) == Some("md") {
-                    if let Ok(task) = parse_task(&path, &column.id) {
-                        tasks.push(task);
-                    }
-                }
+/// Makes sure the board root is a directory before anything is written into
+/// it. A missing root is only created with `--create-root` or after a yes at
+/// an interactive prompt, so a mistyped `--target` does not grow a new board.
+/// Returns whether the root was created.
+fn ensure_root(root: &Path, create: bool) -> Result<bool, String> {
+    let shown = absolute_path(root);
+    if root.is_dir() {
+        return Ok(false);
+    }
+    if root.exists() {
+        return Err(format!("Board root {} is a file, not a directory", shown.display()));
+    }
+    let confirmed = create
+        || (io::stdin().is_terminal() && {
+            print!("Board root {} does not exist. Create it? [y/N] ", shown.display());
+            let _ = io::stdout().flush();
+            let mut input = String::new();
+            let _ = io::stdin().read_line(&mut input);
+            matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+        });
+    if !confirmed {
+        return Err(format!(
+            "Board root {} does not exist (pass --create-root to create it)",
+            shown.display()
+        ));
+    }
+    fs::create_dir_all(root).map_err(|err| format!("Failed to create {}: {}", shown.display(), err))?;
+    Ok(true)
+}
+
+/// Creates a missing `.workspace-kanban` from `defaults`, asking first unless
+/// `yes` is set.
+fn ensure_config(root: &Path, yes: bool, defaults: &BoardConfig) -> io::Result<()> {
+    let path = config_path(root);
+    if !path.exists() {
+        if yes {
+            write_default_config(&path, defaults)?;
+        } else {
+            println!(
+                "Missing {} in {}.",
+                CONFIG_FILE,
+                root.display()
+            );
+            print!("Create default board file? [y/N] ");
+            io::stdout().flush()?;
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            let answer = input.trim().to_lowercase();
+            if answer == "y" || answer == "yes" {
+                write_default_config(&path, defaults)?;
+            } else {
+                return Err(io::Error::other("Missing .workspace-kanban"));
             }
         }
-        out.insert(column.id.clone(), tasks);
     }
-    Ok(out)
+    Ok(())
 }
 
-fn respond_asset(path: &str) -> Option<Response<std::io::Cursor<Vec<u8>>>> {
-    let (body, content_type) = match path {
-        "/" | "/index.html" => (INDEX_HTML, "text/html"),
-        "/app.js" => (APP_JS, "application/javascript"),
-        "/styles.css" => (STYLES_CSS, "text/css"),
-        _ => return None,
+fn load_config(root: &Path, yes: bool) -> io::Result<BoardConfig> {
+    let defaults = BoardConfig {
+        columns: template_columns(DEFAULT_TEMPLATE, DEFAULT_LANG).unwrap_or_default(),
+        ..Default::default()
     };
-    Some(
-        Response::from_string(body)
-            .with_header(Header::from_bytes("Content-Type", content_type).unwrap()),
-    )
+    ensure_config(root, yes, &defaults)?;
+    read_config(root)
 }
 
-fn respond_json(status: StatusCode, body: &str) -> Response<std::io::Cursor<Vec<u8>>> {
-    Response::from_string(body)
-        .with_status_code(status)
-        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap())
+fn read_config(root: &Path) -> io::Result<BoardConfig> {
+    let contents = fs::read_to_string(config_path(root))?;
+    let columns = parse_config_contents(&contents);
+    if columns.is_empty() {
+        return Err(io::Error::other("No valid columns in .workspace-kanban"));
+    }
+    let mut config = BoardConfig { columns, ..Default::default() };
+    apply_board_settings(&mut config, &contents).map_err(io::Error::other)?;
+    Ok(config)
 }
 
-fn respond_text(status: StatusCode, body: &str) -> Response<std::io::Cursor<Vec<u8>>> {
-    Response::from_string(body).with_status_code(status)
+/// Reads `key = value` lines naming a board setting. Column lines never
+/// match: an `=` is not allowed in a column id.
+fn apply_board_settings(config: &mut BoardConfig, contents: &str) -> Result<(), String> {
+    for line in contents.lines() {
+        if let Some(spec) = line.trim().strip_prefix("field ") {
+            config.fields.push(parse_field_line(spec)?);
+            continue;
+        }
+        if let Some(spec) = line.trim().strip_prefix("rule ") {
+            config.rules.push(parse_rule_line(spec)?);
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if !BOARD_SETTINGS.contains(&key) {
+            continue;
+        }
+        match key {
+            "status_mode" => {
+                config.status_mode = match value {
+                    "column" => StatusMode::Column,
+                    "free" => StatusMode::Free,
+                    other => return Err(format!("Invalid status_mode: {} (expected column or free)", other)),
+                }
+            }
+            "default_column" => config.default_column = Some(value.to_string()).filter(|v| !v.is_empty()),
+            "max_tasks" => {
+                config.max_tasks = match value.parse::<usize>() {
+                    Ok(0) => None,
+                    Ok(max) => Some(max),
+                    Err(_) => return Err(format!("Invalid max_tasks: {} (expected a number)", value)),
+                }
+            }
+            "unique_titles" => {
+                config.unique_titles = match value {
+                    "off" => UniqueTitles::Off,
+                    "warn" => UniqueTitles::Warn,
+                    "enforce" => UniqueTitles::Enforce,
+                    other => return Err(format!("Invalid unique_titles: {} (expected warn, enforce or off)", other)),
+                }
+            }
+            "collision" => {
+                config.collision = Some(Collision::parse(value)?);
+            }
+            "slug_stopwords" => {
+                config.slug_stopwords = match value {
+                    "true" => true,
+                    "false" => false,
+                    other => return Err(format!("Invalid slug_stopwords: {} (expected true or false)", other)),
+                }
+            }
+            "slug_stopwords_extra" => config.slug_stopwords_extra = split_list(&value.to_lowercase()),
+            "private_fields" => config.private_fields = split_list(value),
+            "sprint_start" => config.sprint_start = Some(value.to_string()).filter(|v| !v.is_empty()),
+            "sprint_end" => config.sprint_end = Some(value.to_string()).filter(|v| !v.is_empty()),
+            "modified_at" => config.modified_at = Some(value.to_string()).filter(|v| !v.is_empty()),
+            "modified_by" => config.modified_by = Some(value.to_string()).filter(|v| !v.is_empty()),
+            _ => {
+                config.statuses = value
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            }
+        }
+    }
+    validate_fields(&config.fields)?;
+    validate_sprint(config)
 }
 
-fn main() -> io::Result<()> {
-    let (target_arg, yes, ui, write_default_settings_flag, open_browser, open_browser_once) = match parse_args() {
-        Ok(v) => v,
-        Err(msg) => {
-            eprintln!("{}\n", msg);
-            print_help();
-            std::process::exit(1);
+/// Most days `sprint_start` to `sprint_end` may span.
+const SPRINT_MAX_DAYS: i64 = 366;
+
+/// The sprint window, or None when the board sets no sprint.
+fn sprint_window(config: &BoardConfig) -> Result<Option<(Date, Date)>, String> {
+    let date = |key: &str, value: &Option<String>| -> Result<Option<Date>, String> {
+        match value {
+            None => Ok(None),
+            Some(raw) => parse_plain_date(raw).map(Some).ok_or_else(|| format!("Invalid {}: {} (expected YYYY-MM-DD)", key, raw)),
         }
     };
-    let port: u16 = std::env::var("KANBAN_PORT")
-        .ok()
-        .and_then(|v| v.parse().ok())
-        .unwrap_or(8787);
-    let root = target_arg
-        .or_else(|| std::env::var("KANBAN_ROOT").ok())
-        .unwrap_or_else(|| "./kanban_data".to_string());
-    let root_path = PathBuf::from(root);
-    if write_default_settings_flag {
-        match write_default_theme(&root_path) {
-            Ok(true) => println!(
-                "Created default theme file at {}",
-                theme_path(&root_path).display()
-            ),
-            Ok(false) => println!(
-                "Theme file already exists at {}",
-                theme_path(&root_path).display()
-            ),
-            Err(err) => {
-                eprintln!("Failed to write theme: {}", err);
-                std::process::exit(1);
-            }
+    match (date("sprint_start", &config.sprint_start)?, date("sprint_end", &config.sprint_end)?) {
+        (None, None) => Ok(None),
+        (Some(start), Some(end)) if end < start => Err(format!("sprint_end {} is before sprint_start {}", end, start)),
+        (Some(start), Some(end)) if (end - start).whole_days() >= SPRINT_MAX_DAYS => {
+            Err(format!("A sprint may span at most {} days", SPRINT_MAX_DAYS))
         }
+        (Some(start), Some(end)) => Ok(Some((start, end))),
+        _ => Err("sprint_start and sprint_end are set together".to_string()),
     }
-    if let Err(msg) = refresh_config(&root_path, yes) {
-        eprintln!("{}", msg);
-        std::process::exit(1);
-    }
+}
 
-    let server = Server::http(("0.0.0.0", port))
-        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
-    let url = format!("http://localhost:{}", port);
-    println!("Kanban server running on {}", url);
-    let update_state = Arc::new(UpdateState {
-        version: AtomicU64::new(1),
-        lock: Mutex::new(()),
-        cvar: Condvar::new(),
-    });
-    if open_browser {
-        let marker = browser_marker_path(&root_path);
-        let already_opened = open_browser_once && marker.exists();
-        if !already_opened {
-            if let Err(err) = open_browser_url(&url) {
-                eprintln!("Failed to open browser: {}", err);
-            } else if open_browser_once {
-                let _ = fs::write(marker, url.as_bytes());
+fn validate_sprint(config: &BoardConfig) -> Result<(), String> {
+    sprint_window(config).map(|_| ())
+}
+
+/// Parses the part of a `field` line after the keyword: `<name>: text`,
+/// `<name>: number` or `<name>: enum(a,b,...)`.
+fn parse_field_line(spec: &str) -> Result<FieldDef, String> {
+    let Some((name, kind)) = spec.split_once(':') else {
+        return Err(format!("Invalid field line: field {} (expected field <name>: <type>)", spec.trim()));
+    };
+    let (name, kind) = (name.trim().to_string(), kind.trim());
+    let (kind, values) = match kind {
+        "text" => (FieldKind::Text, Vec::new()),
+        "number" => (FieldKind::Number, Vec::new()),
+        _ => match kind.strip_prefix("enum(").and_then(|v| v.strip_suffix(')')) {
+            Some(values) => (FieldKind::Enum, split_list(values)),
+            None => {
+                return Err(format!("Invalid type for field {}: {} (expected text, number or enum(a,b,...))", name, kind))
             }
+        },
+    };
+    Ok(FieldDef { name, kind, values })
+}
+
+/// Parses the part of a `rule` line after the keyword:
+/// `<column> requires-tag <tag>` or `<column> denies-tag <tag>`.
+fn parse_rule_line(spec: &str) -> Result<RoutingRule, String> {
+    let invalid = || format!("Invalid rule line: rule {} (expected rule <column> requires-tag|denies-tag <tag>)", spec.trim());
+    let [column, kind, tag] = spec.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Err(invalid());
+    };
+    let kind = match kind {
+        "requires-tag" => RuleKind::RequiresTag,
+        "denies-tag" => RuleKind::DeniesTag,
+        _ => return Err(invalid()),
+    };
+    Ok(RoutingRule { column: column.to_string(), kind, tag: tag.to_string() })
+}
+
+fn validate_rules(config: &BoardConfig) -> Result<(), String> {
+    for rule in &config.rules {
+        if !config.columns.iter().any(|c| c.id == rule.column) {
+            return Err(format!("Rule \"{}\" names unknown column {}; remove the rule first", rule.spec(), rule.column));
+        }
+        if rule.tag.is_empty() || rule.tag.contains(',') || rule.tag.contains(char::is_whitespace) {
+            return Err(format!("Invalid tag in rule \"{}\": {:?}", rule.spec(), rule.tag));
         }
     }
+    Ok(())
+}
 
-    for request in server.incoming_requests() {
-        let root_path = root_path.clone();
-        let update_state = update_state.clone();
-        let ui = ui;
-        std::thread::spawn(move || {
-            let mut request = request;
-            let method = request.method().clone();
-            let url = request.url().to_string();
-            let path_only = url.split('?').next().unwrap_or(url.as_str());
-
-            if path_only.starts_with("/api/") {
-                let mut body = String::new();
-                let _ = request.as_reader().read_to_string(&mut body);
-
-                let response = match (&method, path_only) {
-                    (Method::Get, "/api/updates") => {
-                        let since = parse_since(&url);
-                        let guard = update_state.lock.lock().unwrap();
-                        let current = update_state.version.load(Ordering::SeqCst);
-                        let mut changed = current > since;
-                        if !changed {
-                            let _ = update_state
-                                .cvar
-                                .wait_timeout(guard, Duration::from_secs(25))
-                                .unwrap();
-                            let latest = update_state.version.load(Ordering::SeqCst);
-                            changed = latest > since;
-                        }
-                        let latest = update_state.version.load(Ordering::SeqCst);
-                        respond_json(
-                            StatusCode(200),
-                            &serde_json::json!({ "version": latest, "changed": changed }).to_string(),
-                        )
-                    }
-                    (Method::Get, "/api/board") => match refresh_config(&root_path, yes) {
-                        Ok(cfg) => {
-                            let payload = serde_json::json!({ "board": cfg });
-                            respond_json(StatusCode(200), &payload.to_string())
-                        }
-                    Err(msg) => respond_json(
-                        StatusCode(500),
-                        &serde_json::json!({"error": msg}).to_string(),
-                    ),
-                },
-                (Method::Put, "/api/board") => match refresh_config(&root_path, yes) {
-                    Ok(_cfg) => {
-                        let parsed: Result<BoardUpdate, _> = serde_json::from_str(&body);
-                        match parsed {
-                            Ok(update) => {
-                                if let Err(msg) = validate_columns(&update.columns) {
-                                    respond_json(
-                                        StatusCode(400),
-                                        &serde_json::json!({ "error": msg }).to_string(),
-                                    )
-                                } else {
-                                    let new_config = BoardConfig {
-                                        columns: update.columns,
-                                    };
-                                    match write_config(&root_path, &new_config) {
-                                        Ok(_) => match refresh_config(&root_path, yes) {
-                                            Ok(cfg) => {
-                                                notify_update(&update_state);
-                                                let payload = serde_json::json!({ "board": cfg });
-                                                respond_json(StatusCode(200), &payload.to_string())
-                                            }
-                                            Err(msg) => respond_json(
-                                                StatusCode(500),
-                                                &serde_json::json!({"error": msg}).to_string(),
-                                            ),
-                                        },
-                                        Err(err) => respond_json(
-                                            StatusCode(500),
-                                            &serde_json::json!({ "error": err.to_string() }).to_string(),
-                                        ),
-                                    }
-                                }
-                            }
-                            Err(err) => respond_json(
-                                StatusCode(400),
-                                &serde_json::json!({ "error": err.to_string() }).to_string(),
-                            ),
-                        }
-                    }
-                    Err(msg) => respond_json(
-                        StatusCode(500),
-                        &serde_json::json!({"error": msg}).to_string(),
-                    ),
-                },
-                (Method::Get, "/api/ui") => {
-                    let payload = serde_json::json!({
-                        "show_task_editor": ui.show_task_editor,
-                        "show_board_editor": ui.show_board_editor
-                    });
-                    respond_json(StatusCode(200), &payload.to_string())
-                }
-                (Method::Get, "/api/theme") => {
-                    let theme = load_theme(&root_path);
-                    respond_json(StatusCode(200), &serde_json::json!({ "theme": theme }).to_string())
+/// Custom field names double as task headers: lowercase ASCII, digits, `_`
+/// and `-`, starting with a letter, and not a header the server owns.
+fn is_field_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        && !TASK_HEADERS.contains(&name)
+}
+
+fn validate_fields(fields: &[FieldDef]) -> Result<(), String> {
+    for (i, field) in fields.iter().enumerate() {
+        if !is_field_name(&field.name) {
+            return Err(format!(
+                "Invalid field name: {} (use a-z, 0-9, '_' and '-', not a built-in header)",
+                field.name
+            ));
+        }
+        if fields[..i].iter().any(|f| f.name == field.name) {
+            return Err(format!("Duplicate field: {}", field.name));
+        }
+        match field.kind {
+            FieldKind::Enum if field.values.is_empty() => {
+                return Err(format!("Field {} is an enum without values", field.name))
+            }
+            FieldKind::Enum => {
+                if let Some(bad) = field.values.iter().find(|v| v.is_empty() || v.contains([',', ')', '\n'])) {
+                    return Err(format!("Invalid value for enum field {}: {:?}", field.name, bad));
                 }
-                (Method::Get, "/api/tasks") => match refresh_config(&root_path, yes) {
-                    Ok(cfg) => match load_all_tasks(&root_path, &cfg) {
-                            Ok(folders) => {
-                                let payload = serde_json::json!({ "folders": folders, "board": cfg });
-                                respond_json(StatusCode(200), &payload.to_string())
-                            }
-                            Err(err) => respond_json(
-                                StatusCode(500),
-                                &serde_json::json!({"error": err.to_string()}).to_string(),
-                            ),
-                        },
-                    Err(msg) => respond_json(
-                        StatusCode(500),
-                        &serde_json::json!({"error": msg}).to_string(),
-                    ),
-                },
-                (Method::Post, "/api/tasks") => {
-                    match refresh_config(&root_path, yes) {
-                        Ok(cfg) => {
-                            let parsed: Result<NewTask, _> = serde_json::from_str(&body);
-                            match parsed {
-                                Ok(new_task) => {
-                                    let folder = new_task
-                                        .status
-                                        .clone()
-                                        .filter(|s| cfg.columns.iter().any(|c| c.id == *s))
-                                        .unwrap_or_else(|| cfg.columns[0].id.clone());
-                                    let base_slug = slugify(&new_task.title);
-                                    let id = unique_slug(&root_path, &base_slug, &cfg);
-                                    let now = now_iso();
-                                    let task = Task {
-                                        id: id.clone(),
-                                        title: new_task.title,
-                                        description: new_task.description.unwrap_or_default(),
-                                        creator: new_task.creator.unwrap_or_default(),
-                                        assigned_to: new_task.assigned_to.unwrap_or_default(),
-                                        created_at: now.clone(),
-                                        updated_at: now,
-                                        status: folder.clone(),
-                                        tags: new_task.tags.unwrap_or_default(),
-                                        folder: folder.clone(),
-                                    };
-                                    let path = task_path(&root_path, &folder, &id);
-                                    match write_task(&path, &task) {
-                                        Ok(_) => {
-                                            notify_update(&update_state);
-                                            respond_json(
-                                            StatusCode(201),
-                                            &serde_json::json!(task).to_string(),
-                                        )}
-                                        Err(err) => respond_json(
-                                            StatusCode(500),
-                                            &serde_json::json!({ "error": err.to_string() }).to_string(),
-                                        ),
-                                    }
-                                }
-                                Err(err) => respond_json(
-                                    StatusCode(400),
-                                    &serde_json::json!({ "error": err.to_string() }).to_string(),
-                                ),
-                            }
-                        }
-                        Err(msg) => respond_json(
-                            StatusCode(500),
-                            &serde_json::json!({ "error": msg }).to_string(),
-                        ),
-                    }
+            }
+            _ if !field.values.is_empty() => {
+                return Err(format!("Field {} has values but is not an enum", field.name))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Checks a client-supplied status against the board's allowed list.
+fn validate_status(config: &BoardConfig, status: &str) -> Result<(), String> {
+    if status.is_empty() || config.statuses.is_empty() || config.statuses.iter().any(|s| s == status) {
+        Ok(())
+    } else {
+        Err(format!("Unknown status: {} (allowed: {})", status, config.statuses.join(", ")))
+    }
+}
+
+/// A file in a column folder that is not a task: an image, a note, anything
+/// without the `.md` extension. Never parsed; only listed and moved.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+struct ForeignFile {
+    name: String,
+    bytes: u64,
+}
+
+/// The foreign files directly inside `dir`, by name. Subdirectories are
+/// not listed.
+fn foreign_files(dir: &Path) -> Vec<ForeignFile> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<ForeignFile> = entries
+        .flatten()
+        .filter(|entry| !entry.path().is_dir() && entry.path().extension().and_then(|e| e.to_str()) != Some("md"))
+        .map(|entry| ForeignFile {
+            name: entry.file_name().to_string_lossy().to_string(),
+            bytes: entry.metadata().map(|m| m.len()).unwrap_or(0),
+        })
+        .collect();
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    files
+}
+
+/// `GET /api/board/foreign-files`: the foreign files of every column that
+/// has some, with sizes.
+fn foreign_files_report(root: &Path, config: &BoardConfig) -> serde_json::Value {
+    let columns: Vec<serde_json::Value> = config
+        .columns
+        .iter()
+        .filter_map(|column| {
+            let files = foreign_files(&root.join(&column.id));
+            (!files.is_empty()).then(|| {
+                let bytes: u64 = files.iter().map(|f| f.bytes).sum();
+                serde_json::json!({ "column": column.id, "files": files, "bytes": bytes })
+            })
+        })
+        .collect();
+    let count: usize = columns.iter().map(|c| c["files"].as_array().map_or(0, Vec::len)).sum();
+    let bytes: u64 = columns.iter().map(|c| c["bytes"].as_u64().unwrap_or(0)).sum();
+    serde_json::json!({ "columns": columns, "count": count, "bytes": bytes })
+}
+
+/// Moves `column/name` to `_attachments/_loose/<column>/name`, keeping the
+/// name; refuses to overwrite a file already there.
+fn relocate_foreign_file(root: &Path, column: &str, name: &str) -> io::Result<String> {
+    let dir = root.join(ATTACHMENTS_DIR).join(LOOSE_FILES_DIR).join(column);
+    let dest = dir.join(name);
+    if dest.exists() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} exists", dest.display())));
+    }
+    fs::create_dir_all(&dir)?;
+    fs::rename(root.join(column).join(name), &dest)?;
+    Ok(format!("{}/{}/{}/{}", ATTACHMENTS_DIR, LOOSE_FILES_DIR, column, name))
+}
+
+/// Asks whether the foreign files of a folder that is about to go may be
+/// deleted with it; no answer means no.
+fn confirm_foreign_delete(folder: &str, foreign: &[ForeignFile]) -> io::Result<bool> {
+    let names: Vec<&str> = foreign.iter().map(|f| f.name.as_str()).collect();
+    println!("'{}' also holds {} file(s) that are not tasks: {}", folder, foreign.len(), names.join(", "));
+    print!("Delete them too? [y/N] ");
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn prompt_handle_removed_folder(root: &Path, folder: &str, config: &BoardConfig, opts: &BoardOptions) -> io::Result<()> {
+    let folder_path = root.join(folder);
+    let mut tasks = Vec::new();
+    if folder_path.exists() {
+        for entry in fs::read_dir(&folder_path)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("md") {
+                tasks.push(path);
+            }
+        }
+    }
+    let foreign = foreign_files(&folder_path);
+    if tasks.is_empty() && foreign.is_empty() {
+        let _ = remove_folder(&folder_path);
+        return Ok(());
+    }
+    let linked = fs::symlink_metadata(&folder_path).is_ok_and(|m| m.file_type().is_symlink());
+    // Foreign files stay unless someone confirms; the link of a linked
+    // folder may always go, since that deletes nothing behind it.
+    let keep_foreign = |deleting: bool| -> io::Result<bool> {
+        if linked || foreign.is_empty() {
+            return Ok(false);
+        }
+        Ok(!deleting || !confirm_foreign_delete(folder, &foreign)?)
+    };
+
+    println!(
+        "Folder '{}' is not in {} but contains {} task(s).",
+        folder,
+        CONFIG_FILE,
+        tasks.len()
+    );
+    println!("Choose action: [d]elete tasks, [m]ove tasks, [a]bort");
+    print!("> ");
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let answer = input.trim().to_lowercase();
+    match answer.as_str() {
+        "d" | "delete" => {
+            if linked {
+                println!("'{}' is a symlink; removing the link and keeping the files it points to.", folder);
+            } else {
+                for path in tasks {
+                    let _ = fs::remove_file(path);
                 }
-                _ => {
-                    if let Some(id) = url.strip_prefix("/api/tasks/") {
-                        let parts: Vec<&str> = id.split('/').collect();
-                        let id_part = parts.first().copied().unwrap_or("");
-                        if !is_valid_id(id_part) {
-                            respond_json(StatusCode(400), &serde_json::json!({"error": "invalid id"}).to_string())
-                        } else if parts.len() == 2 && parts[1] == "move" && method == Method::Post {
-                            match refresh_config(&root_path, yes) {
-                                Ok(cfg) => {
-                                    let parsed: Result<MoveTask, _> = serde_json::from_str(&body);
-                                    match parsed {
-                                        Ok(move_req) => {
-                                            if !cfg.columns.iter().any(|c| c.id == move_req.folder) {
-                                                respond_json(StatusCode(400), &serde_json::json!({"error": "invalid folder"}).to_string())
-                                            } else if let Some((path, current_folder)) =
-                                                find_task_path(&root_path, id_part, &cfg)
-                                            {
-                                                match parse_task(&path, &current_folder) {
-                                                    Ok(mut task) => {
-                                                        let target_path = task_path(&root_path, &move_req.folder, id_part);
-                                                        if target_path.exists() {
-                                                            respond_json(StatusCode(409), &serde_json::json!({"error": "target file exists"}).to_string())
-                                                        } else {
-                                                            task.folder = move_req.folder.clone();
-                                                            task.status = move_req.folder.clone();
-                                                            task.updated_at = now_iso();
-                                                            if let Err(err) = fs::rename(&path, &target_path) {
-                                                                respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string())
-                                                            } else if let Err(err) = write_task(&target_path, &task) {
-                                                                respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string())
-                                                            } else {
-                                                                notify_update(&update_state);
-                                                                respond_json(StatusCode(200), &serde_json::json!(task).to_string())
-                                                            }
-                                                        }
-                                                    }
-                                                    Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
-                                                }
-                                            } else {
-                                                respond_json(StatusCode(404), &serde_json::json!({"error": "task not found"}).to_string())
-                                            }
-                                        }
-                                        Err(err) => respond_json(StatusCode(400), &serde_json::json!({"error": err.to_string()}).to_string()),
-                                    }
-                                }
-                                Err(msg) => respond_json(
-                                    StatusCode(500),
-                                    &serde_json::json!({ "error": msg }).to_string(),
-                                ),
-                            }
-                        } else if parts.len() == 1 && method == Method::Put {
-                            match refresh_config(&root_path, yes) {
-                                Ok(cfg) => {
-                                    let parsed: Result<UpdateTask, _> = serde_json::from_str(&body);
-                                    match parsed {
-                                        Ok(update) => {
-                                            if let Some((path, folder)) =
-                                                find_task_path(&root_path, id_part, &cfg)
-                                            {
-                                                match parse_task(&path, &folder) {
-                                                    Ok(mut task) => {
-                                                        let mut rename_error: Option<Response<std::io::Cursor<Vec<u8>>>> = None;
-                                                        if let Some(title) = update.title {
-                                                            let new_slug = slugify(&title);
-                                                            if new_slug != task.id {
-                                                                let final_slug =
-                                                                    unique_slug(&root_path, &new_slug, &cfg);
-                                                                let new_path = task_path(&root_path, &folder, &final_slug);
-                                                                if let Err(err) = fs::rename(&path, &new_path) {
-                                                                    rename_error = Some(respond_json(
-                                                                        StatusCode(500),
-                                                                        &serde_json::json!({"error": err.to_string()}).to_string(),
-                                                                    ));
-                                                                } else {
-                                                                    task.id = final_slug;
-                                                                }
-                                                            }
-                                                            task.title = title;
-                                                        }
-                                                        if let Some(resp) = rename_error {
-                                                            resp
-                                                        } else {
-                                                            if let Some(desc) = update.description {
-                                                                task.description = desc;
-                                                            }
-                                                            if let Some(creator) = update.creator {
-                                                                task.creator = creator;
-                                                            }
-                                                            if let Some(assigned_to) = update.assigned_to {
-                                                                task.assigned_to = assigned_to;
-                                                            }
-                                                            if let Some(tags) = update.tags {
-                                                                task.tags = tags;
-                                                            }
-                                                            task.updated_at = now_iso();
-                                                            let final_path = task_path(&root_path, &folder, &task.id);
-                                                            match write_task(&final_path, &task) {
-                                                                Ok(_) => {
-                                                                    notify_update(&update_state);
-                                                                    respond_json(StatusCode(200), &serde_json::json!(task).to_string())
-                                                                }
-                                                                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
-                                                            }
-                                                        }
-                                                    }
-                                                    Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
-                                                }
-                                            } else {
-                                                respond_json(StatusCode(404), &serde_json::json!({"error": "task not found"}).to_string())
-                                            }
-                                        }
-                                        Err(err) => respond_json(StatusCode(400), &serde_json::json!({"error": err.to_string()}).to_string()),
-                                    }
-                                }
-                                Err(msg) => respond_json(
-                                    StatusCode(500),
-                                    &serde_json::json!({ "error": msg }).to_string(),
-                                ),
-                            }
-                        } else if parts.len() == 1 && method == Method::Delete {
-                            match refresh_config(&root_path, yes) {
-                                Ok(cfg) => {
-                                    if let Some((path, _folder)) =
-                                        find_task_path(&root_path, id_part, &cfg)
-                                    {
-                                        match fs::remove_file(&path) {
-                                            Ok(_) => {
-                                                notify_update(&update_state);
-                                                respond_json(StatusCode(204), "")
-                                            }
-                                            Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
-                                        }
-                                    } else {
-                                        respond_json(StatusCode(404), &serde_json::json!({"error": "task not found"}).to_string())
-                                    }
-                                }
-                                Err(msg) => respond_json(
-                                    StatusCode(500),
-                                    &serde_json::json!({ "error": msg }).to_string(),
-                                ),
-                            }
-                        } else {
-                            respond_json(StatusCode(404), &serde_json::json!({"error": "not found"}).to_string())
+            }
+            if keep_foreign(true)? {
+                println!("Kept '{}' with its {} file(s) that are not tasks.", folder, foreign.len());
+            } else {
+                let _ = remove_folder(&folder_path);
+            }
+            Ok(())
+        }
+        "m" | "move" => {
+            println!("Move to which folder?");
+            for (index, column) in config.columns.iter().enumerate() {
+                println!("  {}) {} ({})", index + 1, column.title, column.id);
+            }
+            print!("> ");
+            io::stdout().flush()?;
+            let mut choice = String::new();
+            io::stdin().read_line(&mut choice)?;
+            let idx: usize = choice.trim().parse().unwrap_or(0);
+            if idx == 0 || idx > config.columns.len() {
+                return Err(io::Error::other("Invalid move target"));
+            }
+            let target = &config.columns[idx - 1].id;
+            fs::create_dir_all(root.join(target))?;
+            for path in tasks {
+                if let Some(filename) = path.file_name() {
+                    let dest = root.join(target).join(filename);
+                    fs::rename(&path, &dest)?;
+                    if let Ok(mut task) = parse_task(&dest, target, config.status_mode, &opts.base_path) {
+                        task.folder = target.to_string();
+                        if config.status_mode == StatusMode::Column {
+                            task.status = target.to_string();
                         }
-                    } else {
-                        respond_json(StatusCode(404), &serde_json::json!({"error": "not found"}).to_string())
+                        touch_task(&mut task);
+                        task.column_since = Some(task.updated_at.clone());
+                        update_completion(config, &mut task);
+                        let _ = write_task(&dest, &mut task);
                     }
                 }
-            };
-
-                let _ = request.respond(response);
-                return;
             }
-
-            if let Some(response) = respond_asset(path_only) {
-                let _ = request.respond(response);
+            if keep_foreign(false)? {
+                println!("Kept '{}' with its {} file(s) that are not tasks.", folder, foreign.len());
             } else {
-                let response = respond_text(StatusCode(404), "Not Found");
-                let _ = request.respond(response);
+                let _ = remove_folder(&folder_path);
             }
-        });
+            Ok(())
+        }
+        _ => Err(io::Error::other("Aborted")),
+    }
+}
+
+/// What `reconcile_folders` would do for `config`, without touching anything.
+#[derive(Debug, Serialize, Default)]
+struct ReconcilePlan {
+    /// Contents of a board file that would be created (only when missing).
+    config_file: Option<String>,
+    create_folders: Vec<String>,
+    remove_empty_folders: Vec<String>,
+    /// Folders with tasks that are not columns; these need a decision
+    /// (interactive prompt, or an error with `-y`).
+    orphan_folders: Vec<OrphanFolder>,
+    /// Folders that are not columns and hold no tasks but foreign files;
+    /// never removed automatically.
+    kept_folders: Vec<OrphanFolder>,
+}
+
+#[derive(Debug, Serialize)]
+struct OrphanFolder {
+    folder: String,
+    tasks: usize,
+    foreign_files: usize,
+}
+
+impl ReconcilePlan {
+    fn is_empty(&self) -> bool {
+        self.config_file.is_none()
+            && self.create_folders.is_empty()
+            && self.remove_empty_folders.is_empty()
+            && self.orphan_folders.is_empty()
+    }
+}
+
+/// Whether a directory in the board root is left alone by reconcile, the
+/// task loader and `doctor`: any dot-directory (`.git`, `.hg`, `.history`,
+/// `.trash`, ...), `INTERNAL_DIRS`, and the names listed in `.kanbanignore`.
+fn is_internal_dir(name: &str, ignored: &[String]) -> bool {
+    name.starts_with('.') || INTERNAL_DIRS.contains(&name) || ignored.iter().any(|n| n == name)
+}
+
+/// Names from `.kanbanignore`: one per line, `#` comments, a trailing `/`
+/// allowed. A missing file means no extra names.
+fn read_ignore_file(root: &Path) -> Vec<String> {
+    fs::read_to_string(root.join(IGNORE_FILE))
+        .unwrap_or_default()
+        .lines()
+        .map(|line| line.trim().trim_end_matches('/'))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+fn plan_reconcile(root: &Path, config: &BoardConfig) -> io::Result<ReconcilePlan> {
+    let mut plan = ReconcilePlan::default();
+    for column in &config.columns {
+        if !root.join(&column.id).is_dir() {
+            plan.create_folders.push(column.id.clone());
+        }
+    }
+    if !root.exists() {
+        return Ok(plan);
+    }
+    let ignored = read_ignore_file(root);
+    let mut entries = fs::read_dir(root)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let folder_name = entry.file_name().to_string_lossy().to_string();
+        if is_internal_dir(&folder_name, &ignored) {
+            continue;
+        }
+        if config.columns.iter().any(|c| c.id == folder_name) {
+            continue;
+        }
+        let tasks = fs::read_dir(&path)?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().and_then(|ext| ext.to_str()) == Some("md"))
+            .count();
+        let foreign = foreign_files(&path).len();
+        let orphan = OrphanFolder { folder: folder_name, tasks, foreign_files: foreign };
+        match (tasks, foreign) {
+            (0, 0) => plan.remove_empty_folders.push(orphan.folder),
+            (0, _) => plan.kept_folders.push(orphan),
+            _ => plan.orphan_folders.push(orphan),
+        }
+    }
+    Ok(plan)
+}
+
+/// Folder upkeep that needs no decision: creates missing column folders and
+/// removes empty ones that are no longer columns. Returns the plan so
+/// startup can deal with orphan folders.
+fn sync_folders(root: &Path, config: &BoardConfig) -> io::Result<ReconcilePlan> {
+    let plan = plan_reconcile(root, config)?;
+    ensure_folders(root, config)?;
+    for folder in &plan.remove_empty_folders {
+        let _ = remove_folder(&root.join(folder));
+    }
+    Ok(plan)
+}
+
+fn reconcile_folders(root: &Path, config: &BoardConfig, opts: &BoardOptions, yes: bool) -> io::Result<()> {
+    let plan = sync_folders(root, config)?;
+    for orphan in &plan.orphan_folders {
+        if yes {
+            return Err(io::Error::other(format!(
+                "Folder '{}' has tasks but is not in {}; run without -y to resolve",
+                orphan.folder, CONFIG_FILE
+            )));
+        }
+        prompt_handle_removed_folder(root, &orphan.folder, config, opts)?;
+    }
+    Ok(())
+}
+
+fn print_plan(root: &Path, plan: &ReconcilePlan) {
+    println!("Dry run for {}:", root.display());
+    if plan.is_empty() {
+        println!("  nothing to change");
+        return;
+    }
+    if let Some(contents) = &plan.config_file {
+        println!("  would write {}:", CONFIG_FILE);
+        for line in contents.lines() {
+            println!("    {}", line);
+        }
+    }
+    for folder in &plan.create_folders {
+        println!("  would create folder {}/", folder);
+    }
+    for folder in &plan.remove_empty_folders {
+        println!("  would remove empty folder {}/", folder);
+    }
+    for orphan in &plan.orphan_folders {
+        println!(
+            "  folder {}/ is not in {} and has {} task(s): would ask to delete or move them (error with -y)",
+            orphan.folder, CONFIG_FILE, orphan.tasks
+        );
     }
+    for kept in &plan.kept_folders {
+        println!(
+            "  folder {}/ is not in {} and holds {} file(s) that are not tasks: would keep it",
+            kept.folder, CONFIG_FILE, kept.foreign_files
+        );
+    }
+}
 
+/// `--dry-run`: reports what startup would change and touches nothing.
+fn dry_run(root: &Path, defaults: &BoardConfig, opts: &BoardOptions) -> io::Result<()> {
+    let (config, config_file) = if config_path(root).exists() {
+        (read_config(root)?, None)
+    } else {
+        (defaults.clone(), Some(render_config(defaults)))
+    };
+    validate_board(&config, opts.max_columns).map_err(io::Error::other)?;
+    let mut plan = plan_reconcile(root, &config)?;
+    plan.config_file = config_file;
+    print_plan(root, &plan);
+    for line in run_migrations(root, &config, opts, true).map_err(io::Error::other)? {
+        println!("  would run {}", line);
+    }
     Ok(())
 }
+
+/// Board setup at startup, before the listener exists: may create the board
+/// file (after a prompt unless `yes`) and ask what to do with orphan folders.
+fn prepare_board(root: &Path, yes: bool, opts: &BoardOptions) -> Result<BoardConfig, String> {
+    let config = load_config(root, yes).map_err(|err| err.to_string())?;
+    validate_board(&config, opts.max_columns)?;
+    reconcile_folders(root, &config, opts, yes).map_err(|err| err.to_string())?;
+    remember_config(root, &config);
+    Ok(config)
+}
+
+const FORMAT_VERSION_FILE: &str = ".kanban-version";
+/// The on-disk format this binary writes; the version of the last migration.
+const FORMAT_VERSION: u32 = 1;
+
+/// One step of the on-disk format: `run` brings a board from `version - 1`
+/// to `version`. Runs must be idempotent; with `dry_run` they change nothing.
+/// They return the files they changed (or would change).
+struct Migration {
+    version: u32,
+    description: &'static str,
+    run: fn(&Path, &BoardConfig, &BoardOptions, bool) -> io::Result<Vec<String>>,
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "set column_since from updated_at",
+    run: migrate_column_since,
+}];
+
+/// The board's format version; a board without `.kanban-version` predates
+/// versioning and is version 0. Fails for a version newer than this binary.
+fn check_format_version(root: &Path) -> Result<u32, String> {
+    let found = match fs::read_to_string(root.join(FORMAT_VERSION_FILE)) {
+        Ok(text) => text
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| format!("{}: not a version number: {:?}", FORMAT_VERSION_FILE, text.trim()))?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => 0,
+        Err(err) => return Err(format!("{}: {}", FORMAT_VERSION_FILE, err)),
+    };
+    if found > FORMAT_VERSION {
+        return Err(format!(
+            "board format version {} is newer than this server supports ({}); upgrade kanban-server",
+            found, FORMAT_VERSION
+        ));
+    }
+    Ok(found)
+}
+
+/// Runs the migrations newer than the board's format version, in order,
+/// recording each finished one in `.kanban-version`. Returns a log line per
+/// migration; with `dry_run` nothing is written.
+fn run_migrations(root: &Path, config: &BoardConfig, opts: &BoardOptions, dry_run: bool) -> Result<Vec<String>, String> {
+    let found = check_format_version(root)?;
+    let mut log = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.version > found) {
+        let changed = (migration.run)(root, config, opts, dry_run)
+            .map_err(|err| format!("migration {} ({}) failed: {}", migration.version, migration.description, err))?;
+        log.push(format!(
+            "migration {}: {} ({} file(s){})",
+            migration.version,
+            migration.description,
+            changed.len(),
+            if dry_run { " would change" } else { " changed" }
+        ));
+        if !dry_run {
+            fs::write(root.join(FORMAT_VERSION_FILE), format!("{}\n", migration.version)).map_err(|err| err.to_string())?;
+        }
+    }
+    Ok(log)
+}
+
+/// Version 1: tasks written before `column_since` existed get it from `updated_at`.
+fn migrate_column_since(root: &Path, config: &BoardConfig, opts: &BoardOptions, dry_run: bool) -> io::Result<Vec<String>> {
+    let ignored = read_ignore_file(root);
+    let mut changed = Vec::new();
+    for column in config.columns.iter().filter(|c| !is_internal_dir(&c.id, &ignored)) {
+        let Ok(entries) = fs::read_dir(root.join(&column.id)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if task_file_id(&path, opts.follow_symlinks).is_none() {
+                continue;
+            }
+            let mut task = parse_task(&path, &column.id, config.status_mode, &opts.base_path)?;
+            if task.column_since.is_some() || task.updated_at.is_empty() {
+                continue;
+            }
+            if !dry_run {
+                task.column_since = Some(task.updated_at.clone());
+                write_task(&path, &mut task)?;
+            }
+            changed.push(format!("{}/{}.md", column.id, task.id));
+        }
+    }
+    Ok(changed)
+}
+
+const CONFIG_MISSING: &str = "board config missing; restart server or restore .workspace-kanban";
+
+/// The last board config that parsed, per root, so reads keep working while
+/// the file is missing or broken.
+static LAST_GOOD_CONFIG: Mutex<Vec<(PathBuf, BoardConfig)>> = Mutex::new(Vec::new());
+
+fn remember_config(root: &Path, config: &BoardConfig) {
+    let mut known = LAST_GOOD_CONFIG.lock().unwrap();
+    known.retain(|(path, _)| path != root);
+    known.push((root.to_path_buf(), config.clone()));
+}
+
+fn last_good_config(root: &Path) -> Option<BoardConfig> {
+    let known = LAST_GOOD_CONFIG.lock().unwrap();
+    known.iter().find(|(path, _)| path == root).map(|(_, config)| config.clone())
+}
+
+/// The config for a request. Never prompts or writes a board file: when the
+/// file is missing or unparsable the last good config is returned (the
+/// router only lets reads through in that state), or `CONFIG_MISSING`.
+fn refresh_config(root: &Path) -> Result<BoardConfig, String> {
+    if !root.is_dir() {
+        return Err(ROOT_MISSING.to_string());
+    }
+    match read_config(root) {
+        Ok(config) => {
+            remember_config(root, &config);
+            sync_folders(root, &config).map_err(|err| err.to_string())?;
+            Ok(config)
+        }
+        Err(_) => last_good_config(root).ok_or_else(|| CONFIG_MISSING.to_string()),
+    }
+}
+
+/// Read-only health scan of a board directory, shared by the startup
+/// summary, `--validate-only` and `doctor`.
+#[derive(Debug, Default)]
+struct BoardScan {
+    root: PathBuf,
+    config: Option<BoardConfig>,
+    columns: Vec<ColumnScan>,
+    theme: Option<Result<(), String>>,
+    /// Theme files found, conf file first.
+    theme_files: Vec<&'static str>,
+    warnings: Vec<String>,
+    errors: Vec<String>,
+}
+
+#[derive(Debug)]
+struct ColumnScan {
+    id: String,
+    tasks: usize,
+    missing: bool,
+}
+
+impl BoardScan {
+    fn servable(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+fn scan_board(root: &Path, yes: bool, opts: &BoardOptions) -> BoardScan {
+    let mut scan = BoardScan {
+        root: absolute_path(root),
+        ..Default::default()
+    };
+    if root.is_file() {
+        scan.errors.push(format!("{} is a file, not a directory", scan.root.display()));
+        return scan;
+    }
+    if !root.is_dir() {
+        scan.errors.push(format!("{} does not exist (start with --create-root to create it)", scan.root.display()));
+        return scan;
+    }
+    if !config_path(root).exists() {
+        scan.errors.push(format!("{} is missing (start with -y to create it)", CONFIG_FILE));
+    } else {
+        match read_config(root) {
+            Ok(config) => {
+                if let Err(msg) = validate_board(&config, opts.max_columns) {
+                    scan.errors.push(format!("{}: {}", CONFIG_FILE, msg));
+                }
+                scan.config = Some(config);
+            }
+            Err(err) => scan.errors.push(format!("{}: {}", CONFIG_FILE, err)),
+        }
+    }
+    if let Err(msg) = check_format_version(root) {
+        scan.errors.push(msg);
+    }
+    let mut newest: Option<OffsetDateTime> = None;
+    if let Some(config) = &scan.config {
+        for column in &config.columns {
+            let (tasks, skipped, column_newest) = scan_column_files(root, &column.id, case_insensitive_fs(), opts);
+            scan.warnings.extend(skipped);
+            newest = newest.max(column_newest);
+            scan.columns.push(ColumnScan {
+                id: column.id.clone(),
+                tasks,
+                missing: !root.join(&column.id).is_dir(),
+            });
+        }
+        let total: usize = scan.columns.iter().map(|c| c.tasks).sum();
+        if let Some(max) = config.max_tasks.filter(|max| total * 5 >= max * 4) {
+            scan.warnings.push(format!(
+                "{} of {} tasks used (max_tasks); creates are refused at the cap",
+                total, max
+            ));
+        }
+        let now = OffsetDateTime::now_utc();
+        if let Some(newest) = newest.filter(|newest| *newest > now) {
+            scan.warnings.push(format!(
+                "system clock ({}) is behind the newest task timestamp ({}); edits will be stamped after it",
+                format_timestamp(now),
+                format_timestamp(newest)
+            ));
+        }
+        match plan_reconcile(root, config) {
+            Ok(plan) => {
+                for orphan in plan.orphan_folders {
+                    let message = format!(
+                        "folder {}/ has {} task(s) but is not in {}",
+                        orphan.folder, orphan.tasks, CONFIG_FILE
+                    );
+                    if yes {
+                        scan.errors.push(format!("{}; startup with -y refuses to continue", message));
+                    } else {
+                        scan.warnings.push(format!("{}; startup will ask to delete or move them", message));
+                    }
+                }
+                for kept in plan.kept_folders {
+                    scan.warnings.push(format!(
+                        "folder {}/ is not in {} but holds {} file(s) that are not tasks; it is kept until you remove it",
+                        kept.folder, CONFIG_FILE, kept.foreign_files
+                    ));
+                }
+            }
+            Err(err) => scan.warnings.push(format!("could not list {}: {}", root.display(), err)),
+        }
+    }
+    scan.theme_files = theme_files(root);
+    if !scan.theme_files.is_empty() {
+        let parsed = read_theme(root);
+        match &parsed {
+            Err(msg) => scan.warnings.push(format!("theme is malformed ({}); defaults are used", msg)),
+            Ok(theme) => {
+                for id in scan.config.as_ref().map(|config| unknown_theme_columns(theme, config)).unwrap_or_default() {
+                    scan.warnings.push(format!("theme sets a color for {}, which is not a column", id));
+                }
+            }
+        }
+        scan.theme = Some(parsed.map(|_| ()));
+    }
+    scan
+}
+
+/// Counts the task files of one column; returns a warning for every `.md`
+/// file the board skips (unreadable, or named so no API call can reach it,
+/// with a free name to rename it to), for names that differ only in case,
+/// and the newest `updated_at` found.
+fn scan_column_files(
+    root: &Path,
+    folder: &str,
+    insensitive: bool,
+    opts: &BoardOptions,
+) -> (usize, Vec<String>, Option<OffsetDateTime>) {
+    let mut count = 0;
+    let mut skipped = Vec::new();
+    let mut newest = None;
+    let Ok(entries) = fs::read_dir(root.join(folder)) else {
+        return (0, skipped, newest);
+    };
+    let entries: Vec<fs::DirEntry> = entries.flatten().collect();
+    let names: Vec<String> = entries
+        .iter()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".md"))
+        .collect();
+    let renames = rename_proposals(&names);
+    for clash in case_clashes(&names) {
+        let files: Vec<String> = clash.iter().map(|name| format!("{}/{}", folder, name)).collect();
+        skipped.push(if insensitive {
+            format!("{} differ only in case and are one file on this file system", files.join(" and "))
+        } else {
+            format!(
+                "{} differ only in case; they collide on case-insensitive file systems (macOS, Windows)",
+                files.join(" and ")
+            )
+        });
+    }
+    for entry in entries {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("md") {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if let Some(reason) = link_skip_reason(&path, opts.follow_symlinks) {
+            skipped.push(format!("skipped {}/{}: {}", folder, name, reason));
+            continue;
+        }
+        if task_file_id(&path, opts.follow_symlinks).is_none() {
+            let target = renames.get(&name).cloned().unwrap_or_default();
+            skipped.push(format!("skipped {}/{}: not a valid task id (rename it to {})", folder, name, target));
+            continue;
+        }
+        match parse_task(&path, folder, StatusMode::Column, &opts.base_path) {
+            Ok(task) => {
+                count += 1;
+                newest = newest.max(task.updated_at.at());
+            }
+            Err(_) => skipped.push(format!("skipped unreadable file {}/{}", folder, name)),
+        }
+    }
+    skipped.sort();
+    (count, skipped, newest)
+}
+
+/// Groups of file names that differ only in case, each sorted. On a
+/// case-insensitive file system every group is one file.
+fn case_clashes(names: &[String]) -> Vec<Vec<String>> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for name in names {
+        groups.entry(name.to_lowercase()).or_default().push(name.clone());
+    }
+    groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort();
+            group
+        })
+        .collect()
+}
+
+/// A free task file name for every `.md` name in one folder whose stem is
+/// not a valid id. Valid names and earlier proposals are taken, so
+/// `Readme.md` next to `readme.md` becomes `readme-2.md`.
+fn rename_proposals(names: &[String]) -> HashMap<String, String> {
+    let stem = |name: &str| name.trim_end_matches(".md").to_string();
+    let mut taken: HashSet<String> = names.iter().map(|name| stem(name)).filter(|stem| is_valid_id(stem)).collect();
+    let mut sorted: Vec<&String> = names.iter().filter(|name| !is_valid_id(&stem(name))).collect();
+    sorted.sort();
+    let mut proposals = HashMap::new();
+    for name in sorted {
+        let id = unique_slug(&slugify(&stem(name)), &taken);
+        taken.insert(id.clone());
+        proposals.insert(name.clone(), format!("{}.md", id));
+    }
+    proposals
+}
+
+/// Whether `path` exists under exactly this name. On a case-insensitive
+/// file system `fix.md` also opens `Fix.md`, which listings skip, so the
+/// folder is listed to compare the real name.
+fn exact_file_exists(path: &Path, insensitive: bool) -> bool {
+    if !insensitive || !path.exists() {
+        return path.exists();
+    }
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return false;
+    };
+    fs::read_dir(dir).is_ok_and(|entries| entries.flatten().any(|entry| entry.file_name() == name))
+}
+
+fn print_summary(scan: &BoardScan, bind: &str) {
+    println!("Board root:  {}", scan.root.display());
+    if scan.config.is_some() {
+        let columns: Vec<String> = scan
+            .columns
+            .iter()
+            .map(|c| {
+                if c.missing {
+                    format!("{} (missing)", c.id)
+                } else {
+                    format!("{} ({})", c.id, c.tasks)
+                }
+            })
+            .collect();
+        println!("Columns:     {} — {}", scan.columns.len(), columns.join(", "));
+        println!("Tasks:       {}", scan.columns.iter().map(|c| c.tasks).sum::<usize>());
+    }
+    let theme = match &scan.theme {
+        None => "not found, using defaults",
+        Some(Ok(())) => "found",
+        Some(Err(_)) => "malformed, using defaults",
+    };
+    let files = if scan.theme_files.is_empty() { THEME_FILE.to_string() } else { scan.theme_files.join(" + ") };
+    println!("Theme:       {} ({})", files, theme);
+    println!("Web assets:  embedded");
+    println!("Bind:        {}", bind);
+    for warning in &scan.warnings {
+        println!("Warning:     {}", warning);
+    }
+    for error in &scan.errors {
+        println!("Error:       {}", error);
+    }
+}
+
+/// `doctor`: the startup scan plus checks of the server's side folders.
+/// With `backfill`, tasks without `column_since` get it from `updated_at`;
+/// with `reindex`, the manifest is rebuilt instead of checked; with
+/// `relocate`, foreign files move out of the column folders.
+fn doctor(root: &Path, yes: bool, backfill: bool, reindex: bool, relocate: bool, opts: &BoardOptions) -> BoardScan {
+    let mut scan = scan_board(root, yes, opts);
+    let Some(config) = scan.config.clone() else {
+        return scan;
+    };
+    let known = |id: &str| find_task_path(root, id, &config, opts).is_some();
+    let ignored = read_ignore_file(root);
+    for column in config.columns.iter().filter(|c| is_internal_dir(&c.id, &ignored)) {
+        scan.warnings.push(format!(
+            "column {} names an internal directory (see {}); its tasks are not loaded",
+            column.id, IGNORE_FILE
+        ));
+    }
+    scan.warnings.extend(lint_columns(&config.columns).into_iter().map(|w| w.message));
+    let mut without_since = 0;
+    if let Ok(entries) = fs::read_dir(root.join(ATTACHMENTS_DIR)) {
+        for entry in entries.flatten() {
+            let id = entry.file_name().to_string_lossy().to_string();
+            if id != LOOSE_FILES_DIR && !known(&id) {
+                scan.warnings.push(format!("{}/{}/ belongs to no task", ATTACHMENTS_DIR, id));
+            }
+        }
+    }
+    for column in config.columns.iter().filter(|c| !is_internal_dir(&c.id, &ignored)) {
+        let Ok(entries) = fs::read_dir(root.join(&column.id)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if task_file_id(&path, opts.follow_symlinks).is_none() {
+                continue;
+            }
+            let Ok(mut task) = parse_task(&path, &column.id, config.status_mode, &opts.base_path) else {
+                continue;
+            };
+            if task.column_since.is_none() && !task.updated_at.is_empty() {
+                without_since += 1;
+                if backfill {
+                    task.column_since = Some(task.updated_at.clone());
+                    if let Err(err) = fs::write(&path, render_task(&task)) {
+                        scan.warnings.push(format!("could not backfill {}/{}.md: {}", column.id, task.id, err));
+                    }
+                }
+            }
+            for attachment in &task.attachments {
+                if !attachments_dir(root, &task.id).join(&attachment.name).is_file() {
+                    scan.warnings.push(format!(
+                        "{}/{}.md lists attachment {} but the file is missing",
+                        column.id, task.id, attachment.name
+                    ));
+                }
+            }
+            if config.status_mode == StatusMode::Column && task.status != column.id {
+                scan.warnings.push(format!(
+                    "{}/{}.md has status '{}' but lives in {}/",
+                    column.id, task.id, task.status, column.id
+                ));
+            }
+            let stamps = [
+                ("created_at", Some(&task.created_at)),
+                ("updated_at", Some(&task.updated_at)),
+                ("completed_at", task.completed_at.as_ref()),
+                ("column_since", task.column_since.as_ref()),
+            ];
+            for (field, stamp) in stamps {
+                if let Some(stamp) = stamp.filter(|s| s.at().is_none() && !s.is_empty()) {
+                    scan.warnings.push(format!(
+                        "{}/{}.md has an unreadable {} {:?} (accepted formats: {})",
+                        column.id, task.id, field, &**stamp, TIMESTAMP_FORMATS.join(", ")
+                    ));
+                }
+            }
+        }
+    }
+    scan.warnings.extend(stale_order_entries(root, &config));
+    let mut foreign = 0;
+    for column in config.columns.iter().filter(|c| !is_internal_dir(&c.id, &ignored)) {
+        for file in foreign_files(&root.join(&column.id)) {
+            if !relocate {
+                foreign += 1;
+                continue;
+            }
+            match relocate_foreign_file(root, &column.id, &file.name) {
+                Ok(dest) => scan.warnings.push(format!("moved {}/{} to {}", column.id, file.name, dest)),
+                Err(err) => scan.warnings.push(format!("could not move {}/{}: {}", column.id, file.name, err)),
+            }
+        }
+    }
+    if foreign > 0 {
+        scan.warnings.push(format!(
+            "{} file(s) in column folders are not tasks (run doctor --relocate-foreign to move them to {}/{}/)",
+            foreign, ATTACHMENTS_DIR, LOOSE_FILES_DIR
+        ));
+    }
+    if without_since > 0 {
+        scan.warnings.push(if backfill {
+            format!("set column_since from updated_at on {} task(s)", without_since)
+        } else {
+            format!("{} task(s) have no column_since (run doctor --backfill to set it from updated_at)", without_since)
+        });
+    }
+    if reindex {
+        match rebuild_manifest(root, &config, opts) {
+            Ok(count) => scan.warnings.push(format!("rebuilt {} with {} task(s)", MANIFEST_FILE, count)),
+            Err(err) => scan.errors.push(format!("could not rebuild {}: {}", MANIFEST_FILE, err)),
+        }
+    } else if let Some(drift) = manifest_drift(root, &config, opts).filter(|drift| *drift > 0) {
+        scan.warnings.push(format!("{} is out of date for {} task(s) (run doctor --reindex to rebuild it)", MANIFEST_FILE, drift));
+    }
+    scan
+}
+
+fn print_help() {
+    println!(r#"Kanban Task Files server
+
+Usage:
+  kanban-server [options]
+  kanban-server doctor [options]   Check the board directory and report problems
+  kanban-server doctor --backfill  Also set missing column_since headers from updated_at
+  kanban-server doctor --reindex   Rebuild the task manifest instead of checking it
+  kanban-server doctor --relocate-foreign
+                                   Move files that are not tasks out of column folders
+                                   into _attachments/_loose/<column>/
+  kanban-server export [--format md] [-o <file>]
+                                   Print the whole board as one Markdown document
+  kanban-server import <file.csv> --map <spec> [--headers] [--strict] [--collision <strategy>]
+                                   Create one task per CSV row (- reads stdin)
+  kanban-server publish --out <dir> [--include-archive] [--include-trash]
+                                   Write a read-only static copy of the board
+  kanban-server recover [--apply]  List changes a crash left unfinished; --apply finishes
+                                   or rolls back each one (stop the server first)
+  kanban-server init --from-pack <file>
+                                   Apply a board pack (columns, theme, templates, users,
+                                   views) to a new or existing board
+  kanban-server merge --from <dir> --into <dir> [--column-map <from=to>] [--prefix-a <prefix>]
+                                   Copy one board's columns, tasks and users into another
+  kanban-server status [--pidfile <path>]
+                                   Report whether the server of a pidfile runs, and on which port
+  kanban-server service install|uninstall
+                                   Register a Windows service that serves the board with the
+                                   other options given whenever the machine runs, or remove it
+  kanban-server journal grep --task <id> --debug-journal <file>
+                                   Print the debug journal's lines about one task, across rotations
+
+Options:
+  -t, --target <dir>             Base directory for task folders (default: ./kanban_data)
+  -y, --yes                      Create missing folders without prompting
+      --create-root              Create the target directory if it does not exist
+      --allow-unusual-root       Allow /, the home directory or the binary's directory as the target
+      --follow-symlinks          Load task files that are symlinks (skipped by default)
+      --strict-api               Reject request bodies with unknown fields (ignored by default)
+      --manifest                 Serve listings from .kanban-manifest.json (for boards on network shares)
+      --reindex                  Rebuild the manifest before serving
+  -h, --help                     Show this help message
+      --show-task-editor=<bool>  Show task editor on load (default: true)
+      --show-board-editor=<bool> Show board editor on load (default: false)
+      --write-default-theme[=json] Create .kanban-theme.conf (or .kanban-theme.json) with default values
+      --dry-run                  Print what startup would create, move or delete, then exit
+      --validate-only            Print the startup summary and exit 0 if the board can be served, 1 otherwise
+      --base-path <path>         URL prefix when served behind a reverse proxy, e.g. /kanban
+      --trusted-proxy <cidr>     Honor X-Forwarded-For/-Proto/-Host from this range (repeatable)
+      --board <name>=<dir>       Another board tasks may be transferred to (repeatable)
+      --hook <path>              Run this executable after every task change (event JSON on stdin)
+      --hook-events <list>       Only run the hook for these events: create, move, edit, delete, transfer, handover, mention, violation
+      --webhook <url>            POST every hook event to this http:// URL, retrying failed deliveries
+      --watch                    Report edits made to the board's files from outside the server
+      --watch-exclude <dir>      Directory name --watch skips, besides _attachments, .history and the journal (repeatable)
+      --admin-token <token>      Bearer token that may add or remove tasks in locked columns
+      --unix-socket <path>       Listen on a unix domain socket (mode 0600) instead of TCP
+      --tcp                      With --unix-socket, listen on TCP as well
+  -q, --quiet                    Print only errors while starting to serve
+      --startup-json             Print one JSON line (port, bind, root, pid, url) once listening;
+                                 startup errors become one JSON object on stderr
+      --daemonize                Serve in the background, detached from the terminal
+      --pidfile <path>           Write the server's pid and port here once listening
+                                 (default with --daemonize: <target>/.kanban-server.pid)
+      --log-file <path>          Where --daemonize appends the server's output, and the Windows
+                                 service its startup errors
+                                 (default: <target>/.kanban-server.log)
+      --debug-journal <file>     Record every request that changes the board, body included,
+                                 for debugging (off by default)
+      --open-browser=<bool>      Open default system browser on start (default: false)
+      --open-browser-once=<bool> Open browser only once per target (default: true)
+      --timezone=<offset>        UTC offset for date-only filter values, e.g. +02:00 (default: UTC)
+      --history-limit=<n>        Versions kept per task in .history (default: 20, 0 disables)
+      --max-tasks-per-response=<n> Cap on tasks in one listing (default: 5000, 0 = unlimited)
+      --max-columns=<n>          Reject boards with more columns (default: 50, 0 = unlimited)
+      --lang=<code>              Language of column titles in a new board file and default language of error messages: en, de, fr, es (default: en)
+      --template=<name>          Columns of a new board file: default, simple, scrum, gtd (default: default)
+      --webhook-batch-window-ms=<n> Send events arriving within n ms as one delivery (default: 0)
+      --webhook-retry-hours=<n>  Retry failed webhook deliveries for n hours (default: 24)
+      --watch-interval-ms=<n>    How often --watch scans the board (default: 2000)
+
+{}
+The server reads .workspace-kanban for board structure and ensures folders exist.
+"#, env_help());
+}
+
+/// The `Environment:` part of `print_help`, listing every option's variable.
+fn env_help() -> String {
+    let mut out = String::from("Environment (an option given on the command line wins; empty values count as unset):\n");
+    out.push_str(&format!("  {:<32} Port to bind (default: {})\n", "KANBAN_PORT", DEFAULT_PORT));
+    for (flag, var, form) in ENV_OPTIONS {
+        let usage = match form {
+            EnvForm::Switch => format!("{} when true", flag),
+            EnvForm::List => format!("{}, comma-separated", flag),
+            EnvForm::Value | EnvForm::Assign => flag.to_string(),
+        };
+        out.push_str(&format!("  {:<32} {}\n", var, usage));
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+struct UiOptions {
+    show_task_editor: bool,
+    show_board_editor: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Settings {
+    target: Option<String>,
+    yes: bool,
+    ui: UiOptions,
+    write_default_theme: Option<ThemeFormat>,
+    open_browser: bool,
+    open_browser_once: bool,
+    timezone: UtcOffset,
+    history_limit: usize,
+    /// Cap on tasks in one listing response; 0 means unlimited.
+    max_tasks_per_response: usize,
+    board: BoardOptions,
+    template: String,
+    dry_run: bool,
+    validate_only: bool,
+    doctor: bool,
+    create_root: bool,
+    allow_unusual_root: bool,
+    strict_api: bool,
+    backfill: bool,
+    /// `doctor --relocate-foreign`.
+    relocate_foreign: bool,
+    /// `export` subcommand: print the board in `export_format`, to `output`
+    /// or stdout.
+    export: bool,
+    export_format: Option<String>,
+    output: Option<PathBuf>,
+    /// `import` subcommand: the CSV file (`-` for stdin) and its options.
+    import: Option<PathBuf>,
+    csv: CsvImportOptions,
+    /// `publish` subcommand: write a static read-only site.
+    publish: bool,
+    publish_options: PublishOptions,
+    /// `recover` subcommand: end journal entries a crash left pending,
+    /// only reporting them unless `apply`.
+    recover: bool,
+    apply: bool,
+    /// `init --from-pack`: apply a board pack file to the target.
+    init: bool,
+    from_pack: Option<PathBuf>,
+    /// `merge` subcommand: copy one board into another.
+    merge: bool,
+    merge_options: MergeOptions,
+    trusted_proxies: Vec<Cidr>,
+    /// Other boards this server may transfer tasks to (`--board name=dir`).
+    boards: Vec<PeerBoard>,
+    /// Executable run after task changes, limited to `hook_events` when set.
+    hook: Option<PathBuf>,
+    hook_events: Vec<String>,
+    /// Receiver of task activity; events arriving within
+    /// `webhook_batch_window_ms` of the first go out as one delivery, and
+    /// failed deliveries are retried for `webhook_retry_hours`.
+    webhook: Option<WebhookUrl>,
+    webhook_batch_window_ms: u64,
+    webhook_retry_hours: u64,
+    /// `--watch`: report outside edits to the board's files, scanning every
+    /// `watch_interval_ms` and skipping `WATCH_DEFAULT_EXCLUDES` and
+    /// `watch_excludes`.
+    watch: bool,
+    watch_excludes: Vec<String>,
+    watch_interval_ms: u64,
+    /// Bearer token that may change tasks in `locked=true` columns.
+    admin_token: Option<String>,
+    /// Serve listings from `MANIFEST_FILE`; `reindex` rebuilds it first (or,
+    /// for `doctor`, instead of reporting drift).
+    manifest: bool,
+    reindex: bool,
+    /// Listen on this unix domain socket instead of TCP, or besides it
+    /// with `tcp`.
+    unix_socket: Option<PathBuf>,
+    tcp: bool,
+    /// `--quiet`: serving prints nothing but errors.
+    quiet: bool,
+    /// `--startup-json`: serving prints one JSON line once it listens, and
+    /// startup errors as one JSON object on stderr.
+    startup_json: bool,
+    /// `--daemonize`: serve from a detached background process whose
+    /// output goes to `log_file`.
+    daemonize: bool,
+    log_file: Option<PathBuf>,
+    /// Where the serving process records its pid and port; required with
+    /// `daemonize`, which defaults it into the board root.
+    pidfile: Option<PathBuf>,
+    /// `status` subcommand: report on the server `pidfile` names.
+    status: bool,
+    /// `service install|uninstall|run` subcommand, for the Windows service.
+    service: Option<ServiceCommand>,
+    /// Record every request that may change the board in this file (see
+    /// `DebugJournal`); also the journal `journal grep` reads.
+    debug_journal: Option<PathBuf>,
+    /// `journal grep` subcommand: print the journal lines about
+    /// `journal_task`.
+    journal_grep: bool,
+    journal_task: Option<String>,
+    /// TCP port, from `KANBAN_PORT`.
+    port: u16,
+}
+
+/// Flags that change how the board's files are read and checked, for the
+/// server and the CLI commands alike.
+#[derive(Debug, Clone)]
+struct BoardOptions {
+    /// `--follow-symlinks`: load task files that are symlinks.
+    follow_symlinks: bool,
+    /// `--max-columns`: boards with more columns are rejected; 0 = unlimited.
+    max_columns: usize,
+    /// `--lang`: picks the built-in slug stop-word list and the default
+    /// language of `MESSAGES`.
+    lang: String,
+    /// `--base-path`, the URL prefix the server is mounted under, without a
+    /// trailing slash; empty when served at the root. Attachment URLs start
+    /// with it.
+    base_path: String,
+}
+
+impl Default for BoardOptions {
+    fn default() -> Self {
+        BoardOptions {
+            follow_symlinks: false,
+            max_columns: DEFAULT_MAX_COLUMNS,
+            lang: DEFAULT_LANG.to_string(),
+            base_path: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PeerBoard {
+    name: String,
+    root: PathBuf,
+}
+
+/// Parses a `--board` value, `name=dir`.
+fn parse_peer_board(value: &str) -> Result<PeerBoard, String> {
+    let (name, dir) = value
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid value for --board: {} (use name=dir)", value))?;
+    if !is_valid_id(name) || dir.is_empty() {
+        return Err(format!("Invalid value for --board: {} (use name=dir, name in a-z, 0-9, '-')", value));
+    }
+    Ok(PeerBoard { name: name.to_string(), root: PathBuf::from(dir) })
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            target: None,
+            yes: false,
+            ui: UiOptions {
+                show_task_editor: true,
+                show_board_editor: false,
+            },
+            write_default_theme: None,
+            open_browser: false,
+            open_browser_once: true,
+            timezone: UtcOffset::UTC,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            max_tasks_per_response: DEFAULT_MAX_TASKS_PER_RESPONSE,
+            board: BoardOptions::default(),
+            template: DEFAULT_TEMPLATE.to_string(),
+            dry_run: false,
+            validate_only: false,
+            doctor: false,
+            create_root: false,
+            allow_unusual_root: false,
+            strict_api: false,
+            backfill: false,
+            relocate_foreign: false,
+            export: false,
+            export_format: None,
+            output: None,
+            import: None,
+            csv: CsvImportOptions::default(),
+            publish: false,
+            publish_options: PublishOptions::default(),
+            recover: false,
+            apply: false,
+            init: false,
+            from_pack: None,
+            merge: false,
+            merge_options: MergeOptions::default(),
+            trusted_proxies: Vec::new(),
+            boards: Vec::new(),
+            hook: None,
+            hook_events: Vec::new(),
+            webhook: None,
+            webhook_batch_window_ms: 0,
+            webhook_retry_hours: DEFAULT_WEBHOOK_RETRY_HOURS,
+            watch: false,
+            watch_excludes: Vec::new(),
+            watch_interval_ms: DEFAULT_WATCH_INTERVAL_MS,
+            admin_token: None,
+            manifest: false,
+            reindex: false,
+            unix_socket: None,
+            tcp: false,
+            quiet: false,
+            startup_json: false,
+            daemonize: false,
+            log_file: None,
+            pidfile: None,
+            status: false,
+            service: None,
+            debug_journal: None,
+            journal_grep: false,
+            journal_task: None,
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+fn parse_args() -> Result<Settings, String> {
+    parse_arg_list(std::env::args().skip(1))
+}
+
+/// How a `KANBAN_*` variable turns into its command-line option.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EnvForm {
+    /// A flag without a value, given when the variable is a true boolean.
+    Switch,
+    /// `--flag <value>`.
+    Value,
+    /// `--flag <value>` once per comma-separated item.
+    List,
+    /// `--flag=<value>`.
+    Assign,
+}
+
+/// The environment variable of every server option. Each is parsed like the
+/// option itself, so an invalid value fails startup.
+const ENV_OPTIONS: [(&str, &str, EnvForm); 41] = [
+    ("--target", "KANBAN_ROOT", EnvForm::Value),
+    ("--yes", "KANBAN_YES", EnvForm::Switch),
+    ("--create-root", "KANBAN_CREATE_ROOT", EnvForm::Switch),
+    ("--allow-unusual-root", "KANBAN_ALLOW_UNUSUAL_ROOT", EnvForm::Switch),
+    ("--follow-symlinks", "KANBAN_FOLLOW_SYMLINKS", EnvForm::Switch),
+    ("--strict-api", "KANBAN_STRICT_API", EnvForm::Switch),
+    ("--manifest", "KANBAN_MANIFEST", EnvForm::Switch),
+    ("--reindex", "KANBAN_REINDEX", EnvForm::Switch),
+    ("--show-task-editor", "KANBAN_SHOW_TASK_EDITOR", EnvForm::Assign),
+    ("--show-board-editor", "KANBAN_SHOW_BOARD_EDITOR", EnvForm::Assign),
+    ("--write-default-theme", "KANBAN_WRITE_DEFAULT_THEME", EnvForm::Assign),
+    ("--dry-run", "KANBAN_DRY_RUN", EnvForm::Switch),
+    ("--validate-only", "KANBAN_VALIDATE_ONLY", EnvForm::Switch),
+    ("--base-path", "KANBAN_BASE_PATH", EnvForm::Value),
+    ("--trusted-proxy", "KANBAN_TRUSTED_PROXY", EnvForm::List),
+    ("--board", "KANBAN_BOARD", EnvForm::List),
+    ("--hook", "KANBAN_HOOK", EnvForm::Value),
+    ("--hook-events", "KANBAN_HOOK_EVENTS", EnvForm::Value),
+    ("--webhook", "KANBAN_WEBHOOK", EnvForm::Value),
+    ("--watch", "KANBAN_WATCH", EnvForm::Switch),
+    ("--watch-exclude", "KANBAN_WATCH_EXCLUDE", EnvForm::List),
+    ("--admin-token", "KANBAN_ADMIN_TOKEN", EnvForm::Value),
+    ("--unix-socket", "KANBAN_UNIX_SOCKET", EnvForm::Value),
+    ("--tcp", "KANBAN_TCP", EnvForm::Switch),
+    ("--quiet", "KANBAN_QUIET", EnvForm::Switch),
+    ("--startup-json", "KANBAN_STARTUP_JSON", EnvForm::Switch),
+    ("--daemonize", "KANBAN_DAEMONIZE", EnvForm::Switch),
+    ("--pidfile", "KANBAN_PIDFILE", EnvForm::Value),
+    ("--log-file", "KANBAN_LOG_FILE", EnvForm::Value),
+    ("--debug-journal", "KANBAN_DEBUG_JOURNAL", EnvForm::Value),
+    ("--open-browser", "KANBAN_OPEN_BROWSER", EnvForm::Assign),
+    ("--open-browser-once", "KANBAN_OPEN_BROWSER_ONCE", EnvForm::Assign),
+    ("--timezone", "KANBAN_TIMEZONE", EnvForm::Assign),
+    ("--history-limit", "KANBAN_HISTORY_LIMIT", EnvForm::Assign),
+    ("--max-tasks-per-response", "KANBAN_MAX_TASKS_PER_RESPONSE", EnvForm::Assign),
+    ("--max-columns", "KANBAN_MAX_COLUMNS", EnvForm::Assign),
+    ("--lang", "KANBAN_LANG", EnvForm::Assign),
+    ("--template", "KANBAN_TEMPLATE", EnvForm::Assign),
+    ("--webhook-batch-window-ms", "KANBAN_WEBHOOK_BATCH_WINDOW_MS", EnvForm::Assign),
+    ("--webhook-retry-hours", "KANBAN_WEBHOOK_RETRY_HOURS", EnvForm::Assign),
+    ("--watch-interval-ms", "KANBAN_WATCH_INTERVAL_MS", EnvForm::Assign),
+];
+
+/// The long option an argument sets: `-t` is `--target`, and
+/// `--lang=de` is `--lang`.
+fn option_name(arg: &str) -> &str {
+    match arg {
+        "-t" => "--target",
+        "-y" => "--yes",
+        _ => arg.split_once('=').map_or(arg, |(flag, _)| flag),
+    }
+}
+
+/// Applies the variable of every option not in `given` as if it had been
+/// passed on the command line. Empty variables count as unset; errors name
+/// the variable.
+fn apply_env(settings: &mut Settings, given: &[String], env: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+    for (flag, var, form) in ENV_OPTIONS {
+        if given.iter().any(|g| g == flag) {
+            continue;
+        }
+        let Some(value) = env(var).filter(|v| !v.trim().is_empty()) else {
+            continue;
+        };
+        let tokens = match form {
+            EnvForm::Switch => match parse_bool_flag(&format!("{}={}", var, value), var)? {
+                true => vec![flag.to_string()],
+                false => Vec::new(),
+            },
+            EnvForm::Value => vec![flag.to_string(), value],
+            EnvForm::List => split_list(&value).into_iter().flat_map(|item| [flag.to_string(), item]).collect(),
+            EnvForm::Assign => vec![format!("{}={}", flag, value)],
+        };
+        let mut tokens = tokens.into_iter();
+        while let Some(arg) = tokens.next() {
+            apply_arg(settings, &arg, &mut tokens).map_err(|err| format!("{}: {}", var, err))?;
+        }
+    }
+    Ok(())
+}
+
+/// Applies one command-line argument, taking its value from `args` where the
+/// option has a separate one.
+fn apply_arg(settings: &mut Settings, arg: &str, args: &mut impl Iterator<Item = String>) -> Result<(), String> {
+    match arg {
+        "-t" | "--target" => {
+            let value = args.next().ok_or("Missing value for --target")?;
+            settings.target = Some(value);
+        }
+        "-y" | "--yes" => {
+            settings.yes = true;
+        }
+        "--write-default-theme" => {
+            settings.write_default_theme = Some(ThemeFormat::Conf);
+        }
+        "--dry-run" => {
+            settings.dry_run = true;
+        }
+        "--validate-only" => {
+            settings.validate_only = true;
+        }
+        "--create-root" => {
+            settings.create_root = true;
+        }
+        "--allow-unusual-root" => {
+            settings.allow_unusual_root = true;
+        }
+        "--follow-symlinks" => {
+            settings.board.follow_symlinks = true;
+        }
+        "--strict-api" => {
+            settings.strict_api = true;
+        }
+        "--backfill" => {
+            settings.backfill = true;
+        }
+        "--relocate-foreign" => {
+            settings.relocate_foreign = true;
+        }
+        "--manifest" => {
+            settings.manifest = true;
+        }
+        "--reindex" => {
+            settings.reindex = true;
+        }
+        "doctor" if !settings.doctor => {
+            settings.doctor = true;
+        }
+        "export" if !settings.export => {
+            settings.export = true;
+        }
+        "publish" if !settings.publish => {
+            settings.publish = true;
+        }
+        "--out" => {
+            let value = args.next().ok_or("Missing value for --out")?;
+            settings.publish_options.out = Some(PathBuf::from(value));
+        }
+        "--include-archive" => {
+            settings.publish_options.include_archive = true;
+        }
+        "--include-trash" => {
+            settings.publish_options.include_trash = true;
+        }
+        "recover" if !settings.recover => {
+            settings.recover = true;
+        }
+        "--apply" => {
+            settings.apply = true;
+        }
+        "init" if !settings.init => {
+            settings.init = true;
+        }
+        "--from-pack" => {
+            let value = args.next().ok_or("Missing value for --from-pack")?;
+            settings.from_pack = Some(PathBuf::from(value));
+        }
+        "merge" if !settings.merge => {
+            settings.merge = true;
+        }
+        "--from" => {
+            let value = args.next().ok_or("Missing value for --from")?;
+            settings.merge_options.from = Some(PathBuf::from(value));
+        }
+        "--into" => {
+            let value = args.next().ok_or("Missing value for --into")?;
+            settings.merge_options.into = Some(PathBuf::from(value));
+        }
+        "--column-map" => {
+            let value = args.next().ok_or("Missing value for --column-map")?;
+            settings.merge_options.column_map.extend(parse_column_map(&value)?);
+        }
+        "--prefix-a" => {
+            let value = args.next().ok_or("Missing value for --prefix-a")?;
+            if !is_valid_id(&format!("{}x", value)) {
+                return Err(format!("Invalid value for --prefix-a: {} (use a-z, 0-9, '-')", value));
+            }
+            settings.merge_options.prefix = Some(value);
+        }
+        "import" if settings.import.is_none() => {
+            let value = args.next().ok_or("Missing CSV file for import (use - for stdin)")?;
+            settings.import = Some(PathBuf::from(value));
+        }
+        "--map" => {
+            settings.csv.map = args.next().ok_or("Missing value for --map")?;
+        }
+        "--headers" => {
+            settings.csv.headers = true;
+        }
+        "--strict" => {
+            settings.csv.strict = true;
+        }
+        "--collision" => {
+            let value = args.next().ok_or("Missing value for --collision")?;
+            settings.csv.collision = Some(Collision::parse(&value)?);
+        }
+        "--format" => {
+            let value = args.next().ok_or("Missing value for --format")?;
+            if value != "md" {
+                return Err(format!("Unsupported export format: {} (supported: md)", value));
+            }
+            settings.export_format = Some(value);
+        }
+        "-o" | "--output" => {
+            let value = args.next().ok_or("Missing value for --output")?;
+            settings.output = Some(PathBuf::from(value));
+        }
+        "--trusted-proxy" => {
+            let value = args.next().ok_or("Missing value for --trusted-proxy")?;
+            let cidr = parse_cidr(&value)
+                .ok_or_else(|| format!("Invalid value for --trusted-proxy: {} (use an IP or CIDR)", value))?;
+            settings.trusted_proxies.push(cidr);
+        }
+        "--board" => {
+            let value = args.next().ok_or("Missing value for --board")?;
+            settings.boards.push(parse_peer_board(&value)?);
+        }
+        "--unix-socket" => {
+            let value = args.next().ok_or("Missing value for --unix-socket")?;
+            settings.unix_socket = Some(PathBuf::from(value));
+        }
+        "--tcp" => {
+            settings.tcp = true;
+        }
+        "-q" | "--quiet" => {
+            settings.quiet = true;
+        }
+        "--startup-json" => {
+            settings.startup_json = true;
+        }
+        "--daemonize" => {
+            settings.daemonize = true;
+        }
+        "--pidfile" => {
+            let value = args.next().ok_or("Missing value for --pidfile")?;
+            settings.pidfile = Some(PathBuf::from(value));
+        }
+        "--log-file" => {
+            let value = args.next().ok_or("Missing value for --log-file")?;
+            settings.log_file = Some(PathBuf::from(value));
+        }
+        "status" if !settings.status => {
+            settings.status = true;
+        }
+        "service" if settings.service.is_none() => {
+            let command = args.next().ok_or("Missing service command (available: install, uninstall, run)")?;
+            settings.service = Some(match command.as_str() {
+                "install" => ServiceCommand::Install,
+                "uninstall" => ServiceCommand::Uninstall,
+                "run" => ServiceCommand::Run,
+                _ => return Err(format!("Unknown service command: {} (available: install, uninstall, run)", command)),
+            });
+        }
+        "--debug-journal" => {
+            let value = args.next().ok_or("Missing value for --debug-journal")?;
+            settings.debug_journal = Some(PathBuf::from(value));
+        }
+        "journal" if !settings.journal_grep => {
+            let command = args.next().ok_or("Missing journal command (available: grep)")?;
+            if command != "grep" {
+                return Err(format!("Unknown journal command: {} (available: grep)", command));
+            }
+            settings.journal_grep = true;
+        }
+        "--task" => {
+            let value = args.next().ok_or("Missing value for --task")?;
+            settings.journal_task = Some(value);
+        }
+        "--admin-token" => {
+            let value = args.next().ok_or("Missing value for --admin-token")?;
+            if value.trim().is_empty() {
+                return Err("--admin-token must not be empty".to_string());
+            }
+            settings.admin_token = Some(value.trim().to_string());
+        }
+        "--hook" => {
+            let value = args.next().ok_or("Missing value for --hook")?;
+            settings.hook = Some(PathBuf::from(value));
+        }
+        "--hook-events" => {
+            let value = args.next().ok_or("Missing value for --hook-events")?;
+            for event in split_list(&value) {
+                if !HOOK_EVENTS.contains(&event.as_str()) {
+                    return Err(format!("Unknown hook event: {} (allowed: {})", event, HOOK_EVENTS.join(", ")));
+                }
+                settings.hook_events.push(event);
+            }
+        }
+        "--webhook" => {
+            let value = args.next().ok_or("Missing value for --webhook")?;
+            settings.webhook = Some(WebhookUrl::parse(&value)?);
+        }
+        "--watch" => {
+            settings.watch = true;
+        }
+        "--watch-exclude" => {
+            let value = args.next().ok_or("Missing value for --watch-exclude")?;
+            let name = value.trim().trim_matches('/');
+            if name.is_empty() || name.contains('/') {
+                return Err(format!("Invalid value for --watch-exclude: {} (use a directory name)", value));
+            }
+            settings.watch_excludes.push(name.to_string());
+        }
+        "--base-path" => {
+            let value = args.next().ok_or("Missing value for --base-path")?;
+            settings.board.base_path = normalize_base_path(&value)?;
+        }
+        "-h" | "--help" => {
+            print_help();
+            std::process::exit(0);
+        }
+        _ if arg.starts_with("--show-task-editor=") => {
+            settings.ui.show_task_editor = parse_bool_flag(arg, "--show-task-editor")?;
+        }
+        _ if arg.starts_with("--show-board-editor=") => {
+            settings.ui.show_board_editor = parse_bool_flag(arg, "--show-board-editor")?;
+        }
+        _ if arg.starts_with("--open-browser=") => {
+            settings.open_browser = parse_bool_flag(arg, "--open-browser")?;
+        }
+        _ if arg.starts_with("--open-browser-once=") => {
+            settings.open_browser_once = parse_bool_flag(arg, "--open-browser-once")?;
+        }
+        _ if arg.starts_with("--history-limit=") => {
+            let value = arg.split_once('=').map(|(_, v)| v).unwrap_or("");
+            settings.history_limit = value
+                .parse()
+                .map_err(|_| format!("Invalid value for --history-limit: {}", value))?;
+        }
+        _ if arg.starts_with("--max-tasks-per-response=") => {
+            let value = arg.split_once('=').map(|(_, v)| v).unwrap_or("");
+            settings.max_tasks_per_response = value
+                .parse()
+                .map_err(|_| format!("Invalid value for --max-tasks-per-response: {}", value))?;
+        }
+        _ if arg.starts_with("--write-default-theme=") => {
+            let value = arg.split_once('=').map(|(_, v)| v).unwrap_or("");
+            settings.write_default_theme = Some(match value {
+                "conf" => ThemeFormat::Conf,
+                "json" => ThemeFormat::Json,
+                _ => return Err(format!("Invalid value for --write-default-theme: {} (expected conf or json)", value)),
+            });
+        }
+        _ if arg.starts_with("--max-columns=") => {
+            let value = arg.split_once('=').map(|(_, v)| v).unwrap_or("");
+            settings.board.max_columns = value
+                .parse()
+                .map_err(|_| format!("Invalid value for --max-columns: {}", value))?;
+        }
+        _ if arg.starts_with("--webhook-batch-window-ms=") => {
+            let value = arg.split_once('=').map(|(_, v)| v).unwrap_or("");
+            settings.webhook_batch_window_ms = value
+                .parse()
+                .map_err(|_| format!("Invalid value for --webhook-batch-window-ms: {}", value))?;
+        }
+        _ if arg.starts_with("--webhook-retry-hours=") => {
+            let value = arg.split_once('=').map(|(_, v)| v).unwrap_or("");
+            settings.webhook_retry_hours = value
+                .parse()
+                .map_err(|_| format!("Invalid value for --webhook-retry-hours: {}", value))?;
+        }
+        _ if arg.starts_with("--watch-interval-ms=") => {
+            let value = arg.split_once('=').map(|(_, v)| v).unwrap_or("");
+            settings.watch_interval_ms = value
+                .parse()
+                .ok()
+                .filter(|ms| *ms > 0)
+                .ok_or_else(|| format!("Invalid value for --watch-interval-ms: {}", value))?;
+        }
+        _ if arg.starts_with("--lang=") => {
+            settings.board.lang = arg.split_once('=').map(|(_, v)| v).unwrap_or("").to_string();
+        }
+        _ if arg.starts_with("--template=") => {
+            settings.template = arg.split_once('=').map(|(_, v)| v).unwrap_or("").to_string();
+        }
+        _ if arg.starts_with("--timezone=") => {
+            let value = arg.split_once('=').map(|(_, v)| v).unwrap_or("");
+            settings.timezone = parse_utc_offset(value)
+                .ok_or_else(|| format!("Invalid value for --timezone: {} (use UTC or +HH:MM)", value))?;
+        }
+        "--show-task-editor" | "--show-board-editor" | "--open-browser" | "--open-browser-once" => {
+            return Err("Use --show-task-editor=<true|false>, --show-board-editor=<true|false>, --open-browser=<true|false>, or --open-browser-once=<true|false>".to_string());
+        }
+        _ => return Err(format!("Unknown argument: {}", arg)),
+    }
+    Ok(())
+}
+
+/// Settings from command-line arguments (without the program name) and the
+/// `KANBAN_*` environment variables.
+fn parse_arg_list(args: impl IntoIterator<Item = String>) -> Result<Settings, String> {
+    parse_settings(args, |name| std::env::var(name).ok())
+}
+
+/// `parse_arg_list` with the environment looked up through `env`. An option
+/// given on the command line wins over its variable.
+fn parse_settings(args: impl IntoIterator<Item = String>, env: impl Fn(&str) -> Option<String>) -> Result<Settings, String> {
+    let mut args = args.into_iter();
+    let mut settings = Settings::default();
+    let mut given = Vec::new();
+    while let Some(arg) = args.next() {
+        given.push(option_name(&arg).to_string());
+        apply_arg(&mut settings, &arg, &mut args)?;
+    }
+    apply_env(&mut settings, &given, &env)?;
+    if let Some(port) = env("KANBAN_PORT").filter(|v| !v.trim().is_empty()) {
+        settings.port = port.trim().parse().map_err(|_| format!("KANBAN_PORT: Invalid port: {}", port))?;
+    }
+    settings.board.lang = settings.board.lang.trim().to_lowercase();
+    if template_columns(&settings.template, DEFAULT_LANG).is_none() {
+        let names: Vec<&str> = BOARD_TEMPLATES.iter().map(|(name, _)| *name).collect();
+        return Err(format!(
+            "Unknown board template: {} (available: {})",
+            settings.template,
+            names.join(", ")
+        ));
+    }
+    if settings.backfill && !settings.doctor {
+        return Err("--backfill only applies to the doctor command".to_string());
+    }
+    if settings.relocate_foreign && !settings.doctor {
+        return Err("--relocate-foreign only applies to the doctor command".to_string());
+    }
+    if settings.tcp && settings.unix_socket.is_none() {
+        return Err("--tcp only applies with --unix-socket".to_string());
+    }
+    if cfg!(not(unix)) && settings.unix_socket.is_some() {
+        return Err("--unix-socket is only supported on Unix-like systems".to_string());
+    }
+    if settings.reindex && !settings.manifest && !settings.doctor {
+        return Err("--reindex needs --manifest or the doctor command".to_string());
+    }
+    if (settings.export_format.is_some() || settings.output.is_some()) && !settings.export {
+        return Err("--format and --output only apply to the export command".to_string());
+    }
+    let csv = &settings.csv;
+    if (!csv.map.is_empty() || csv.headers || csv.strict || csv.collision.is_some()) && settings.import.is_none() {
+        return Err("--map, --headers, --strict and --collision only apply to the import command".to_string());
+    }
+    let publish = &settings.publish_options;
+    if (publish.out.is_some() || publish.include_archive || publish.include_trash) && !settings.publish {
+        return Err("--out, --include-archive and --include-trash only apply to the publish command".to_string());
+    }
+    if settings.apply && !settings.recover {
+        return Err("--apply only applies to the recover command".to_string());
+    }
+    if settings.publish && publish.out.is_none() {
+        return Err("publish needs --out <dir>".to_string());
+    }
+    let merge = &settings.merge_options;
+    let merge_tuned = merge.from.is_some() || merge.into.is_some() || !merge.column_map.is_empty() || merge.prefix.is_some();
+    if merge_tuned && !settings.merge {
+        return Err("--from, --into, --column-map and --prefix-a only apply to the merge command".to_string());
+    }
+    if settings.merge && (merge.from.is_none() || merge.into.is_none()) {
+        return Err("merge needs --from <dir> and --into <dir>".to_string());
+    }
+    if settings.init != settings.from_pack.is_some() {
+        return Err("--from-pack only applies to the init command, which needs it".to_string());
+    }
+    if !settings.hook_events.is_empty() && settings.hook.is_none() {
+        return Err("--hook-events needs --hook".to_string());
+    }
+    let webhook_tuned = settings.webhook_batch_window_ms != 0 || settings.webhook_retry_hours != DEFAULT_WEBHOOK_RETRY_HOURS;
+    if webhook_tuned && settings.webhook.is_none() {
+        return Err("--webhook-batch-window-ms and --webhook-retry-hours need --webhook".to_string());
+    }
+    if (!settings.watch_excludes.is_empty() || settings.watch_interval_ms != DEFAULT_WATCH_INTERVAL_MS) && !settings.watch {
+        return Err("--watch-exclude and --watch-interval-ms need --watch".to_string());
+    }
+    let command = settings.doctor
+        || settings.export
+        || settings.import.is_some()
+        || settings.publish
+        || settings.recover
+        || settings.init
+        || settings.merge
+        || settings.status
+        || settings.journal_grep
+        || settings.validate_only
+        || settings.dry_run;
+    if settings.service.is_some() && command {
+        return Err("The service commands cannot be combined with another command".to_string());
+    }
+    if settings.daemonize && command {
+        return Err("--daemonize only applies when serving".to_string());
+    }
+    if settings.daemonize && settings.service.is_some() {
+        return Err("--daemonize does not apply to the service commands: the service manager runs the server in the background".to_string());
+    }
+    if settings.daemonize && settings.open_browser {
+        return Err("--daemonize cannot be combined with --open-browser: a background server has no desktop to open it on".to_string());
+    }
+    if settings.service.is_some() && settings.open_browser {
+        return Err("The service commands cannot be combined with --open-browser: a service has no desktop to open it on".to_string());
+    }
+    let serves_in_background = settings.daemonize || matches!(settings.service, Some(ServiceCommand::Install | ServiceCommand::Run));
+    if settings.log_file.is_some() && !serves_in_background {
+        return Err("--log-file needs --daemonize or service install".to_string());
+    }
+    if settings.pidfile.is_some() && ((command && !settings.status) || settings.service == Some(ServiceCommand::Uninstall)) {
+        return Err("--pidfile only applies when serving and to the status command".to_string());
+    }
+    if settings.journal_grep != settings.journal_task.is_some() {
+        return Err("--task only applies to the journal grep command, which needs it".to_string());
+    }
+    if settings.debug_journal.is_some() && command && !settings.journal_grep {
+        return Err("--debug-journal only applies when serving and to the journal grep command".to_string());
+    }
+    if settings.journal_grep && settings.debug_journal.is_none() {
+        return Err("journal grep needs --debug-journal <file>".to_string());
+    }
+    Ok(settings)
+}
+fn case_insensitive_fs() -> bool {
+    CASE_INSENSITIVE_FS.get().copied().unwrap_or(false)
+}
+
+/// Whether file names in `root` ignore case: writes a probe file and looks
+/// it up in upper case. A root that cannot be written counts as sensitive.
+fn probe_case_insensitive(root: &Path) -> bool {
+    let probe = root.join(".kanban-case-probe");
+    if fs::write(&probe, "").is_err() {
+        return false;
+    }
+    let insensitive = root.join(".KANBAN-CASE-PROBE").exists();
+    let _ = fs::remove_file(&probe);
+    insensitive
+}
+
+fn normalize_base_path(raw: &str) -> Result<String, String> {
+    let trimmed = raw.trim().trim_end_matches('/');
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+    if trimmed.chars().any(|c| c.is_whitespace() || matches!(c, '?' | '#' | '%' | '\\')) {
+        return Err(format!("Invalid value for --base-path: {}", raw));
+    }
+    if trimmed.starts_with('/') {
+        Ok(trimmed.to_string())
+    } else {
+        Ok(format!("/{}", trimmed))
+    }
+}
+
+/// Maps a request URL onto the router's root-relative URL. `Err(Some(url))`
+/// asks for a redirect (the bare prefix without its slash), `Err(None)` means
+/// the URL is outside the base path.
+fn route_url(raw: &str, base: &str) -> Result<String, Option<String>> {
+    if base.is_empty() {
+        return Ok(raw.to_string());
+    }
+    let Some(rest) = raw.strip_prefix(base) else {
+        return Err(None);
+    };
+    if rest.is_empty() || rest.starts_with('?') {
+        return Err(Some(format!("{}/{}", base, rest)));
+    }
+    if rest.starts_with('/') {
+        Ok(rest.to_string())
+    } else {
+        Err(None)
+    }
+}
+
+/// Which response shapes a request gets: the unprefixed `/api/...` routes
+/// keep the legacy payloads, `/api/v1/...` the current ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ApiShape {
+    Legacy,
+    V1,
+}
+
+/// Maps `/api/v1/...` onto the `/api/...` route it shares handlers with.
+/// Every other URL, including non-API ones, is returned as is.
+fn api_shape(url: &str) -> (ApiShape, String) {
+    match url.strip_prefix("/api/v1") {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '?', '#']) => (ApiShape::V1, format!("/api{}", rest)),
+        _ => (ApiShape::Legacy, url.to_string()),
+    }
+}
+
+/// The error code of a v1 error body: legacy messages that already are a
+/// code (`task_not_found`) keep it, the rest get one from the status.
+fn error_code(status: u16, message: &str) -> String {
+    if !message.is_empty() && message.chars().all(|c| c.is_ascii_lowercase() || c == '_') {
+        return message.to_string();
+    }
+    let code = match status {
+        400 => "bad_request",
+        401 => "unauthorized",
+        403 => "forbidden",
+        404 => "not_found",
+        405 => "method_not_allowed",
+        409 => "conflict",
+        423 => "locked",
+        413 => "payload_too_large",
+        429 => "rate_limited",
+        503 => "unavailable",
+        507 => "board_full",
+        500.. => "internal_error",
+        _ => "error",
+    };
+    code.to_string()
+}
+
+/// Rewrites a legacy JSON payload into its v1 shape: errors become
+/// `{"error": {"code", "message", ...details}}`, keeping a catalog `code`
+/// and `message` where the legacy payload has them, and task listings put each
+/// column's tasks into its `columns` entry instead of a `folders` map and
+/// number them with a `position`.
+fn v1_payload(status: u16, mut payload: serde_json::Value) -> serde_json::Value {
+    let Some(object) = payload.as_object_mut() else {
+        return payload;
+    };
+    if let Some(error_text) = object.get("error").and_then(|e| e.as_str()).map(str::to_string) {
+        let text = |key: &str| object.get(key).and_then(|v| v.as_str()).map(str::to_string);
+        let code = text("code").unwrap_or_else(|| error_code(status, &error_text));
+        let message = text("message").unwrap_or(error_text);
+        let mut error = serde_json::Map::new();
+        error.insert("code".to_string(), serde_json::json!(code));
+        error.insert("message".to_string(), serde_json::json!(message));
+        error.extend(std::mem::take(object).into_iter().filter(|(key, _)| !["error", "code", "message"].contains(&key.as_str())));
+        return serde_json::json!({ "error": error });
+    }
+    if let (Some(serde_json::Value::Object(mut folders)), true) = (object.remove("folders"), object.contains_key("columns")) {
+        for column in object["columns"].as_array_mut().into_iter().flatten() {
+            let tasks = column["id"].as_str().and_then(|id| folders.remove(id)).unwrap_or_default();
+            column["tasks"] = tasks;
+        }
+    }
+    // Listings number each column's tasks from the page's `offset`.
+    for column in object.get_mut("columns").and_then(|c| c.as_array_mut()).into_iter().flatten() {
+        if let Some(offset) = column["offset"].as_u64() {
+            number_tasks(&mut column["tasks"], offset);
+        }
+    }
+    if let (Some(offset), Some(tasks)) = (object.get("column").and_then(|c| c["offset"].as_u64()), object.get_mut("tasks")) {
+        number_tasks(tasks, offset);
+    }
+    payload
+}
+
+/// Gives each listed task its zero-based `position` in the column's order,
+/// filters applied and before paging.
+fn number_tasks(tasks: &mut serde_json::Value, offset: u64) {
+    for (index, task) in tasks.as_array_mut().into_iter().flatten().enumerate() {
+        if let Some(task) = task.as_object_mut() {
+            task.insert("position".to_string(), serde_json::json!(offset + index as u64));
+        }
+    }
+}
+
+/// What marks a response to an unversioned `/api/...` URL as deprecated.
+fn legacy_headers(url: &str, base_path: &str) -> [Header; 3] {
+    let successor = format!("<{}/api/v1{}>; rel=\"successor-version\"", base_path, url.trim_start_matches("/api"));
+    [
+        Header::from_bytes("Deprecation", "true").unwrap(),
+        Header::from_bytes("Sunset", LEGACY_API_SUNSET).unwrap(),
+        Header::from_bytes("Link", successor.as_bytes()).unwrap(),
+    ]
+}
+
+/// Applies the response shape of the route set a request came in on. Legacy
+/// responses keep their payload, minus the catalog fields of errors, and
+/// announce their end with `Deprecation`, `Sunset` and a `Link` to the v1
+/// route; v1 JSON bodies are reshaped.
+fn shape_response(
+    response: Response<io::Cursor<Vec<u8>>>,
+    shape: ApiShape,
+    url: &str,
+    base_path: &str,
+) -> Response<io::Cursor<Vec<u8>>> {
+    let status = response.status_code();
+    if shape == ApiShape::Legacy && status.0 < 400 {
+        let mut response = response;
+        for header in legacy_headers(url, base_path) {
+            response.add_header(header);
+        }
+        return response;
+    }
+    let headers = response.headers().to_vec();
+    let mut body = Vec::new();
+    let _ = response.into_reader().read_to_end(&mut body);
+    let json = headers.iter().any(|h| h.field.equiv("Content-Type") && h.value.as_str().starts_with("application/json"));
+    if let Some(payload) = serde_json::from_slice(&body).ok().filter(|_| json) {
+        let shaped = match shape {
+            ApiShape::Legacy => legacy_error(payload),
+            ApiShape::V1 => Some(v1_payload(status.0, payload)),
+        };
+        if let Some(payload) = shaped {
+            body = payload.to_string().into_bytes();
+        }
+    }
+    let mut shaped = Response::from_data(body).with_status_code(status);
+    for header in headers {
+        shaped.add_header(header);
+    }
+    if shape == ApiShape::Legacy {
+        for header in legacy_headers(url, base_path) {
+            shaped.add_header(header);
+        }
+    }
+    shaped
+}
+
+/// Drops the `code` and `message` a catalog error carries for v1 clients,
+/// so legacy error bodies stay as they were; None leaves the body alone.
+fn legacy_error(payload: serde_json::Value) -> Option<serde_json::Value> {
+    let serde_json::Value::Object(object) = payload else {
+        return None;
+    };
+    let code = object.get("code").and_then(|c| c.as_str());
+    if !code.is_some_and(|code| ERROR_CODES.contains(&code)) || !object.get("error").is_some_and(|e| e.is_string()) {
+        return None;
+    }
+    Some(serde_json::Value::Object(object.into_iter().filter(|(key, _)| key != "code" && key != "message").collect()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    fn contains(&self, ip: IpAddr) -> bool {
+        fn masked(bits: u128, prefix: u8, width: u8) -> u128 {
+            if prefix == 0 {
+                0
+            } else {
+                bits >> (width - prefix)
+            }
+        }
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                masked(u32::from(net) as u128, self.prefix, 32) == masked(u32::from(ip) as u128, self.prefix, 32)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                masked(u128::from(net), self.prefix, 128) == masked(u128::from(ip), self.prefix, 128)
+            }
+            (IpAddr::V4(_), IpAddr::V6(ip)) => ip.to_ipv4_mapped().is_some_and(|ip| self.contains(IpAddr::V4(ip))),
+            _ => false,
+        }
+    }
+}
+
+fn parse_cidr(value: &str) -> Option<Cidr> {
+    let (addr, prefix) = match value.trim().split_once('/') {
+        Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, Some(prefix.parse::<u8>().ok()?)),
+        None => (value.trim().parse::<IpAddr>().ok()?, None),
+    };
+    let width = if addr.is_ipv4() { 32 } else { 128 };
+    let prefix = prefix.unwrap_or(width);
+    if prefix > width {
+        return None;
+    }
+    Some(Cidr { addr, prefix })
+}
+
+/// The requesting client as far as we can tell: the socket peer, or, when
+/// that peer is a trusted proxy, what the proxy reports.
+#[derive(Debug, Clone, PartialEq)]
+struct Peer {
+    ip: Option<IpAddr>,
+    proto: String,
+    host: Option<String>,
+}
+
+/// Walks `X-Forwarded-For` from the right, skipping trusted proxies, so a
+/// client cannot spoof its address by sending the header itself.
+fn forwarded_client(remote: IpAddr, forwarded_for: Option<&str>, trusted: &[Cidr]) -> IpAddr {
+    let is_trusted = |ip: IpAddr| trusted.iter().any(|cidr| cidr.contains(ip));
+    if !is_trusted(remote) {
+        return remote;
+    }
+    let mut client = remote;
+    for hop in forwarded_for.unwrap_or("").rsplit(',') {
+        let Ok(ip) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !is_trusted(ip) {
+            break;
+        }
+    }
+    client
+}
+
+fn resolve_peer(request: &tiny_http::Request, trusted: &[Cidr]) -> Peer {
+    let remote = request.remote_addr().map(|addr| addr.ip());
+    let via_proxy = remote.is_some_and(|ip| trusted.iter().any(|cidr| cidr.contains(ip)));
+    let forwarded = |name: &str| {
+        header_value(request, name)
+            .filter(|_| via_proxy)
+            .and_then(|v| v.split(',').next().map(|v| v.trim().to_string()))
+            .filter(|v| !v.is_empty())
+    };
+    Peer {
+        ip: remote.map(|ip| forwarded_client(ip, header_value(request, "X-Forwarded-For").as_deref(), trusted)),
+        proto: forwarded("X-Forwarded-Proto")
+            .filter(|proto| proto == "http" || proto == "https")
+            .unwrap_or_else(|| "http".to_string()),
+        host: forwarded("X-Forwarded-Host").or_else(|| header_value(request, "Host")),
+    }
+}
+
+fn format_utc_offset(offset: UtcOffset) -> String {
+    if offset.is_utc() {
+        return "UTC".to_string();
+    }
+    let (hours, minutes, _) = offset.as_hms();
+    let sign = if offset.is_negative() { '-' } else { '+' };
+    format!("{}{:02}:{:02}", sign, hours.unsigned_abs(), minutes.unsigned_abs())
+}
+
+fn parse_utc_offset(value: &str) -> Option<UtcOffset> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("utc") || value == "Z" {
+        return Some(UtcOffset::UTC);
+    }
+    let (sign, rest) = match value.chars().next()? {
+        '+' => (1, &value[1..]),
+        '-' => (-1, &value[1..]),
+        _ => return None,
+    };
+    let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+    let hours: i8 = hours.parse().ok()?;
+    let minutes: i8 = minutes.parse().ok()?;
+    UtcOffset::from_hms(sign * hours, sign * minutes, 0).ok()
+}
+
+fn parse_bool_flag(arg: &str, name: &str) -> Result<bool, String> {
+    let value = arg
+        .split_once('=')
+        .map(|(_, v)| v)
+        .ok_or_else(|| format!("Missing value for {}", name))?;
+    match value.to_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Ok(true),
+        "false" | "0" | "no" | "off" => Ok(false),
+        _ => Err(format!("Invalid boolean for {}: {}", name, value)),
+    }
+}
+
+fn open_browser_url(url: &str) -> io::Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        Command::new("cmd").args(["/C", "start", "", url]).spawn()?;
+        Ok(())
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Command::new("open").arg(url).spawn()?;
+        Ok(())
+    }
+    #[cfg(target_os = "linux")]
+    {
+        Command::new("xdg-open").arg(url).spawn()?;
+        Ok(())
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        Err(io::Error::other("open browser not supported on this platform"))
+    }
+}
+
+fn browser_marker_path(root: &Path) -> PathBuf {
+    root.join(".kanban-browser-opened")
+}
+
+fn notify_update(state: &Arc<UpdateState>) {
+    state.version.fetch_add(1, Ordering::SeqCst);
+    state.cvar.notify_all();
+}
+
+fn publish_event(bus: &EventBus, kind: &str, data: serde_json::Value) {
+    let mut log = bus.log.lock().unwrap();
+    log.last_id += 1;
+    let id = log.last_id;
+    log.events.push_back(ServerEvent {
+        id,
+        kind: kind.to_string(),
+        data: data.to_string(),
+    });
+    while log.events.len() > EVENT_BACKLOG {
+        log.events.pop_front();
+    }
+    bus.cvar.notify_all();
+}
+
+/// Directories `--watch` never looks into, besides those given with
+/// `--watch-exclude`: the server's own bookkeeping and version control.
+const WATCH_DEFAULT_EXCLUDES: [&str; 5] = [ATTACHMENTS_DIR, HISTORY_DIR, JOURNAL_DIR, SNAPSHOTS_DIR, ".git"];
+const DEFAULT_WATCH_INTERVAL_MS: u64 = 2000;
+/// How long a write the server announced in `EXPECTED_CHANGES` may take to
+/// show up. A file that still differs after this is reported as changed
+/// from outside.
+const WATCH_EXPECT_TTL: Duration = Duration::from_secs(30);
+
+/// Writes the server is making, so `--watch` does not report them back:
+/// the fingerprint of the content written (None for a removal) and when it
+/// was announced. Only filled while a watcher runs.
+static EXPECTED_CHANGES: Mutex<BTreeMap<PathBuf, (Option<u64>, Instant)>> = Mutex::new(BTreeMap::new());
+/// Set once a `--watch` thread runs.
+static WATCHING: AtomicBool = AtomicBool::new(false);
+
+/// Announces a write to `path` (`None` removes it) before it happens.
+fn expect_change(path: &Path, content: Option<&[u8]>) {
+    if WATCHING.load(Ordering::SeqCst) {
+        let fingerprint = content.map(content_hash);
+        EXPECTED_CHANGES.lock().unwrap().insert(path.to_path_buf(), (fingerprint, Instant::now()));
+    }
+}
+
+/// Size and modification time of every file `--watch` looks at.
+type FileStamps = BTreeMap<PathBuf, (u64, Option<SystemTime>)>;
+
+/// Files the server keeps for itself (`.kanban-*`) and staged writes are
+/// never reported; they change with every request.
+fn watch_skips_file(name: &str) -> bool {
+    name.starts_with(".kanban-") || name.ends_with(".tmp") || name.ends_with(".transfer")
+}
+
+/// Stamps every file under `root` outside the `excludes` directories.
+/// Symlinked folders are followed at the top level only, like columns.
+fn stamp_files(root: &Path, excludes: &[String]) -> FileStamps {
+    let mut stamps = FileStamps::new();
+    let mut dirs = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = entry.path();
+            let Ok(kind) = entry.file_type() else {
+                continue;
+            };
+            if kind.is_dir() || (depth == 0 && kind.is_symlink() && path.is_dir()) {
+                if !excludes.contains(&name) {
+                    dirs.push((path, depth + 1));
+                }
+            } else if !watch_skips_file(&name) {
+                if let Ok(meta) = fs::metadata(&path) {
+                    stamps.insert(path, (meta.len(), meta.modified().ok()));
+                }
+            }
+        }
+    }
+    stamps
+}
+
+/// Paths added, removed or restamped between two scans.
+fn changed_files(before: &FileStamps, after: &FileStamps) -> Vec<PathBuf> {
+    let removed = before.keys().filter(|path| !after.contains_key(*path));
+    let changed = after.iter().filter(|(path, stamp)| before.get(*path) != Some(stamp)).map(|(path, _)| path);
+    let mut paths: Vec<PathBuf> = removed.chain(changed).cloned().collect();
+    paths.sort();
+    paths
+}
+
+/// Splits changed paths into outside edits and those still awaiting a
+/// write the server announced; paths whose content is what the server
+/// wrote are dropped. Announcements older than `WATCH_EXPECT_TTL` expire,
+/// so a file the server meant to write but someone else changed surfaces.
+fn sort_changes(
+    expected: &mut BTreeMap<PathBuf, (Option<u64>, Instant)>,
+    changed: Vec<PathBuf>,
+    now: Instant,
+) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    expected.retain(|_, (_, at)| now.saturating_duration_since(*at) < WATCH_EXPECT_TTL);
+    let mut external = Vec::new();
+    let mut pending = Vec::new();
+    for path in changed {
+        let Some((fingerprint, _)) = expected.get(&path) else {
+            external.push(path);
+            continue;
+        };
+        if fs::read(&path).ok().map(|bytes| content_hash(&bytes[..])) != *fingerprint {
+            pending.push(path);
+        }
+    }
+    (external, pending)
+}
+
+/// Stamps outside edits had on the scan that found them (`None` for a
+/// removal), waiting for the next scan to confirm them.
+type Settling = BTreeMap<PathBuf, Option<(u64, Option<SystemTime>)>>;
+
+/// One `--watch` scan: compares `current` with `known`, which it then
+/// replaces, and returns the outside edits to report. An outside edit is
+/// only reported once the next scan finds the same stamp, so a file caught
+/// halfway through a write (truncated but not yet filled) shows up once.
+fn watch_scan(
+    known: &mut FileStamps,
+    mut current: FileStamps,
+    settling: &mut Settling,
+    expected: &mut BTreeMap<PathBuf, (Option<u64>, Instant)>,
+    now: Instant,
+) -> Vec<PathBuf> {
+    let changed = changed_files(known, &current);
+    settling.retain(|path, _| changed.contains(path));
+    let (external, pending) = sort_changes(expected, changed, now);
+    let mut settled = Vec::new();
+    let mut unsettled = pending;
+    for path in external {
+        let stamp = current.get(&path).copied();
+        if settling.get(&path) == Some(&stamp) {
+            settling.remove(&path);
+            settled.push(path);
+        } else {
+            settling.insert(path.clone(), stamp);
+            unsettled.push(path);
+        }
+    }
+    // A file still being written, by the server or from outside, is
+    // compared against its old stamp again on the next scan.
+    for path in unsettled {
+        match known.get(&path) {
+            Some(stamp) => current.insert(path, *stamp),
+            None => current.remove(&path),
+        };
+    }
+    *known = current;
+    settled
+}
+
+/// The `--watch` thread: every `interval`, compares the board's files with
+/// the last scan. Outside edits found in one scan bump the update version
+/// and go out as one `files_changed` event with their root-relative paths.
+fn run_watcher(root: &Path, excludes: &[String], interval: Duration, updates: &Arc<UpdateState>, bus: &EventBus, stopped: &AtomicBool) {
+    let mut known = stamp_files(root, excludes);
+    let mut settling = Settling::new();
+    while !stopped.load(Ordering::SeqCst) {
+        std::thread::sleep(interval);
+        let current = stamp_files(root, excludes);
+        let external = watch_scan(&mut known, current, &mut settling, &mut EXPECTED_CHANGES.lock().unwrap(), Instant::now());
+        if external.is_empty() {
+            continue;
+        }
+        let paths: Vec<String> = external
+            .iter()
+            .map(|path| path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/"))
+            .collect();
+        notify_update(updates);
+        publish_event(bus, "files_changed", serde_json::json!({ "paths": paths }));
+    }
+}
+
+fn header_value(request: &tiny_http::Request, name: &str) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str().to_string())
+}
+
+/// Serves `GET /api/events` as a Server-Sent Events stream on the raw
+/// connection (tiny_http would buffer a chunked body). Without
+/// `Last-Event-ID` a client only sees events published after it connected.
+fn stream_events(request: tiny_http::Request, bus: &EventBus) {
+    let resume = header_value(&request, "Last-Event-ID").and_then(|v| v.trim().parse::<u64>().ok());
+    let mut last_seen = resume.unwrap_or_else(|| bus.log.lock().unwrap().last_id);
+    let mut writer = request.into_writer();
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\nretry: 2000\n\n";
+    if writer.write_all(head.as_bytes()).and_then(|_| writer.flush()).is_err() {
+        return;
+    }
+    loop {
+        let chunk = {
+            let mut log = bus.log.lock().unwrap();
+            if log.last_id <= last_seen {
+                log = bus.cvar.wait_timeout(log, EVENT_KEEPALIVE).unwrap().0;
+            }
+            let mut chunk = String::new();
+            for event in log.events.iter().filter(|e| e.id > last_seen) {
+                chunk.push_str(&format!("id: {}\nevent: {}\ndata: {}\n\n", event.id, event.kind, event.data));
+            }
+            last_seen = last_seen.max(log.last_id);
+            chunk
+        };
+        let chunk = if chunk.is_empty() { ": keepalive\n\n".to_string() } else { chunk };
+        if writer.write_all(chunk.as_bytes()).and_then(|_| writer.flush()).is_err() {
+            return;
+        }
+    }
+}
+
+fn parse_since(query: &HashMap<String, String>) -> u64 {
+    query.get("since").and_then(|v| v.parse().ok()).unwrap_or(0)
+}
+
+/// A request target parsed once at the top of the router: the path without
+/// query or fragment, split into percent-decoded segments (empty ones, as
+/// left by a trailing or doubled slash, are dropped), and the query.
+#[derive(Debug, PartialEq)]
+struct RequestTarget {
+    /// `/` plus the segments joined with `/`; what routes match on.
+    path: String,
+    segments: Vec<String>,
+    query: HashMap<String, String>,
+}
+
+/// Fails for segments that decode to a path separator or a dot segment,
+/// which would let one segment address something else.
+fn parse_target(url: &str) -> Result<RequestTarget, String> {
+    let url = url.split('#').next().unwrap_or("");
+    let (raw_path, _) = url.split_once('?').unwrap_or((url, ""));
+    let mut segments = Vec::new();
+    for raw in raw_path.split('/').filter(|s| !s.is_empty()) {
+        // `+` is only a space in query strings.
+        let segment = percent_decode(&raw.replace('+', "%2B"));
+        if segment.contains(['/', '\\', '\0']) || segment == "." || segment == ".." {
+            return Err(format!("invalid path segment: {}", raw));
+        }
+        segments.push(segment);
+    }
+    Ok(RequestTarget {
+        path: format!("/{}", segments.join("/")),
+        segments,
+        query: parse_query(url),
+    })
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(value) => {
+                        out.push(value);
+                        i += 2;
+                    }
+                    Err(_) => out.push(b'%'),
+                }
+            }
+            other => out.push(other),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// RFC 3986 percent-encoding of everything but unreserved characters.
+fn percent_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// `attachment` disposition with an ASCII fallback `filename` and the exact
+/// name as RFC 5987 `filename*`, so non-ASCII names survive the download.
+fn content_disposition(filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| if c == ' ' || (c.is_ascii_graphic() && c != '"' && c != '\\') { c } else { '_' })
+        .collect();
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback,
+        percent_encode(filename)
+    )
+}
+
+fn parse_query(url: &str) -> HashMap<String, String> {
+    let mut out = HashMap::new();
+    if let Some(query) = url.split_once('?').map(|(_, q)| q) {
+        let query = query.split('#').next().unwrap_or("");
+        for pair in query.split('&') {
+            if pair.is_empty() {
+                continue;
+            }
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            out.insert(percent_decode(key), percent_decode(value));
+        }
+    }
+    out
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+struct TaskFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    assigned_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    creator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    q: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "serialize_bound")]
+    created_after: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "serialize_bound")]
+    created_before: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "serialize_bound")]
+    updated_after: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "serialize_bound")]
+    updated_before: Option<OffsetDateTime>,
+    /// `field.<name>=<value>` filters on custom fields.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    fields: BTreeMap<String, String>,
+}
+
+fn serialize_bound<S: serde::Serializer>(
+    value: &Option<OffsetDateTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(at) => serializer.serialize_str(&at.format(&Rfc3339).unwrap_or_default()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Result of checking one task against a filter. Tasks lacking a timestamp a
+/// date bound needs are reported separately so the gap stays visible.
+#[derive(Debug, PartialEq)]
+enum FilterOutcome {
+    Match,
+    Reject,
+    MissingDate,
+}
+
+fn parse_task_filter(query: &HashMap<String, String>, tz: UtcOffset) -> Result<TaskFilter, String> {
+    let get = |key: &str| {
+        query
+            .get(key)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let bound = |key: &str, end_of_day: bool| -> Result<Option<OffsetDateTime>, String> {
+        match get(key) {
+            Some(raw) => parse_date_bound(&raw, tz, end_of_day)
+                .map(Some)
+                .ok_or_else(|| format!("Invalid {}: {} (expected RFC3339 or YYYY-MM-DD)", key, raw)),
+            None => Ok(None),
+        }
+    };
+    Ok(TaskFilter {
+        tag: get("tag"),
+        assigned_to: get("assigned_to"),
+        creator: get("creator"),
+        q: get("q"),
+        created_after: bound("created_after", false)?,
+        created_before: bound("created_before", true)?,
+        updated_after: bound("updated_after", false)?,
+        updated_before: bound("updated_before", true)?,
+        fields: query
+            .iter()
+            .filter_map(|(key, value)| Some((key.strip_prefix("field.")?.to_string(), value.trim().to_string())))
+            .filter(|(name, value)| !name.is_empty() && !value.is_empty())
+            .collect(),
+    })
+}
+
+/// Parses an RFC3339 timestamp or a plain `YYYY-MM-DD` date. Dates are taken in
+/// `tz` and expand to the first (or, for upper bounds, last) instant of the day.
+fn parse_date_bound(raw: &str, tz: UtcOffset, end_of_day: bool) -> Option<OffsetDateTime> {
+    if let Some(at) = parse_timestamp(raw) {
+        return Some(at);
+    }
+    let date = parse_plain_date(raw)?;
+    let start = date.midnight().assume_offset(tz);
+    if end_of_day {
+        Some(start + time::Duration::days(1) - time::Duration::nanoseconds(1))
+    } else {
+        Some(start)
+    }
+}
+
+fn parse_plain_date(raw: &str) -> Option<Date> {
+    let mut parts = raw.trim().splitn(3, '-');
+    let year: i32 = parts.next()?.parse().ok()?;
+    let month: u8 = parts.next()?.parse().ok()?;
+    let day: u8 = parts.next()?.parse().ok()?;
+    Date::from_calendar_date(year, Month::try_from(month).ok()?, day).ok()
+}
+
+/// Numbers compare by value (`3` matches `3.0`), everything else
+/// case-insensitively.
+fn field_matches(value: &serde_json::Value, wanted: &str) -> bool {
+    let text = field_text(value);
+    match (text.parse::<f64>(), wanted.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => text.eq_ignore_ascii_case(wanted),
+    }
+}
+
+fn filter_is_active(filter: &TaskFilter) -> bool {
+    filter.tag.is_some()
+        || filter.assigned_to.is_some()
+        || filter.creator.is_some()
+        || filter.q.is_some()
+        || !filter.fields.is_empty()
+        || filter_has_dates(filter)
+}
+
+fn filter_has_dates(filter: &TaskFilter) -> bool {
+    filter.created_after.is_some()
+        || filter.created_before.is_some()
+        || filter.updated_after.is_some()
+        || filter.updated_before.is_some()
+}
+
+fn filter_task(filter: &TaskFilter, task: &Task) -> FilterOutcome {
+    if let Some(tag) = &filter.tag {
+        if !task.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            return FilterOutcome::Reject;
+        }
+    }
+    if let Some(assigned_to) = &filter.assigned_to {
+        if !task.assigned_to.eq_ignore_ascii_case(assigned_to) {
+            return FilterOutcome::Reject;
+        }
+    }
+    if let Some(creator) = &filter.creator {
+        if !task.creator.eq_ignore_ascii_case(creator) {
+            return FilterOutcome::Reject;
+        }
+    }
+    if let Some(q) = &filter.q {
+        let needle = q.to_lowercase();
+        if !task.title.to_lowercase().contains(&needle)
+            && !task.description.to_lowercase().contains(&needle)
+        {
+            return FilterOutcome::Reject;
+        }
+    }
+    for (name, wanted) in &filter.fields {
+        if !task.extra.get(name).is_some_and(|value| field_matches(value, wanted)) {
+            return FilterOutcome::Reject;
+        }
+    }
+    let ranges = [
+        (&task.created_at, filter.created_after, filter.created_before),
+        (&task.updated_at, filter.updated_after, filter.updated_before),
+    ];
+    for (value, after, before) in ranges {
+        if after.is_none() && before.is_none() {
+            continue;
+        }
+        let Some(at) = parse_timestamp(value) else {
+            return FilterOutcome::MissingDate;
+        };
+        if after.is_some_and(|after| at < after) || before.is_some_and(|before| at > before) {
+            return FilterOutcome::Reject;
+        }
+    }
+    FilterOutcome::Match
+}
+
+/// Per-column summary sent next to the task lists so clients never recount.
+/// `count` reflects active filters, `total` and `over_limit` always use the
+/// full column because that is what WIP limits apply to.
+#[derive(Debug, Serialize)]
+struct ColumnMeta {
+    id: String,
+    title: String,
+    count: usize,
+    total: usize,
+    hidden: usize,
+    wip_limit: Option<u32>,
+    /// `wip_limit` is 0: no new tasks can be created in or moved into it.
+    frozen: bool,
+    /// `locked=true`: only the admin token may add or remove tasks.
+    locked: bool,
+    over_limit: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_age: Option<String>,
+    /// Tasks in the column for longer than `max_age`, filters aside.
+    over_age: Vec<String>,
+    /// Position of the first returned task among the `count` matches.
+    offset: usize,
+    /// Tasks actually included in this response.
+    returned: usize,
+    /// Set when `--max-tasks-per-response` cut this column short.
+    truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct BoardTotals {
+    count: usize,
+    total: usize,
+    hidden: usize,
+    over_limit_columns: usize,
+}
+
+fn is_frozen(column: &BoardColumn) -> bool {
+    column.wip_limit == Some(0)
+}
+
+fn frozen_column_error(column: &BoardColumn) -> String {
+    message_in(DEFAULT_LANG, "column_frozen", &[("column", &column.id)])
+}
+
+/// The first of `folders` that is a `locked=true` column, unless the request
+/// is allowed to change locked columns.
+fn locked_column<'a>(cfg: &'a BoardConfig, folders: &[&str], admin: bool) -> Option<&'a BoardColumn> {
+    cfg.columns.iter().filter(|_| !admin).find(|c| c.locked && folders.contains(&c.id.as_str()))
+}
+
+fn locked_column_error(column: &BoardColumn) -> String {
+    message_in(DEFAULT_LANG, "column_locked", &[("column", &column.id)])
+}
+
+/// The first rule of `column` that keeps `task` out, unless `force` skips
+/// the rules.
+fn broken_rule<'a>(cfg: &'a BoardConfig, column: &str, task: &Task, force: bool) -> Option<&'a RoutingRule> {
+    cfg.rules.iter().filter(|_| !force).find(|rule| rule.column == column && !rule.allows(task))
+}
+
+/// Whether a request's `?force=true` skips routing rules: with
+/// `--admin-token`, only for requests that send it.
+fn rules_forced(query: &HashMap<String, String>, settings: &Settings, admin: bool) -> bool {
+    query.get("force").is_some_and(|v| v == "true") && (settings.admin_token.is_none() || admin)
+}
+
+/// The 422 answer for a task a routing rule keeps out; `spec` is the
+/// rule as written in the board file.
+fn rule_broken(spec: &str) -> Response<io::Cursor<Vec<u8>>> {
+    let mut payload = error_payload("rule_broken", &[("rule", spec)]);
+    payload["rule"] = serde_json::json!(spec);
+    respond_json(StatusCode(422), &payload.to_string())
+}
+
+fn column_locked(column: &BoardColumn) -> Response<io::Cursor<Vec<u8>>> {
+    let mut payload = error_payload("column_locked", &[("column", &column.id)]);
+    payload["column"] = serde_json::json!(column.id);
+    respond_json(StatusCode(423), &payload.to_string())
+}
+
+/// Frozen columns are never over their limit; their tasks may stay.
+fn wip_over_limit(column: &BoardColumn, total: usize) -> bool {
+    match column.wip_limit {
+        Some(limit) if limit > 0 => total > limit as usize,
+        _ => false,
+    }
+}
+
+/// A `wip_limit_exceeded` warning of `PUT /api/board`: the common warning
+/// fields plus the limit and the count that exceeds it.
+#[derive(Debug, Serialize, PartialEq)]
+struct WipWarning {
+    code: &'static str,
+    message: String,
+    column: String,
+    wip_limit: u32,
+    count: usize,
+}
+
+/// Columns whose limit changed in a board update and is now exceeded by the
+/// tasks already in them. Columns that were over an unchanged limit are not
+/// reported again.
+fn wip_limit_warnings(old: &BoardConfig, new: &BoardConfig, counts: &HashMap<String, usize>) -> Vec<WipWarning> {
+    new.columns
+        .iter()
+        .filter_map(|column| {
+            let count = counts.get(&column.id).copied().unwrap_or(0);
+            let previous = old.columns.iter().find(|c| c.id == column.id).and_then(|c| c.wip_limit);
+            if previous == column.wip_limit || !wip_over_limit(column, count) {
+                return None;
+            }
+            let limit = column.wip_limit?;
+            Some(WipWarning {
+                code: "wip_limit_exceeded",
+                message: format!("{} holds {} tasks, over its new WIP limit of {}", column.title, count, limit),
+                column: column.id.clone(),
+                wip_limit: limit,
+                count,
+            })
+        })
+        .collect()
+}
+
+/// What the activity log knows about column policies: the column each task
+/// last entered and when, and since when each column has been locked.
+#[derive(Debug, Default)]
+struct PolicyLog {
+    entered: HashMap<String, (String, OffsetDateTime)>,
+    locked_at: HashMap<String, OffsetDateTime>,
+}
+
+fn read_policy_log(root: &Path) -> PolicyLog {
+    let mut policy = PolicyLog::default();
+    let log = fs::File::open(root.join(ACTIVITY_FILE)).ok().map(io::BufReader::new);
+    for line in log.into_iter().flat_map(|log| log.lines().map_while(Result::ok)) {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let Some(at) = entry["at"].as_str().and_then(parse_timestamp) else {
+            continue;
+        };
+        match entry["action"].as_str() {
+            Some("task_created" | "task_moved" | "task_transferred") => {
+                if let (Some(task), Some(column)) = (entry["task"].as_str(), activity_column(&entry)) {
+                    policy.entered.insert(task.to_string(), (column.to_string(), at));
+                }
+            }
+            Some("board_changed") => {
+                let changes = entry["columns"].as_array().into_iter().flatten();
+                for change in changes.filter(|change| change["change"] == "lock_changed") {
+                    let column = change["column"].as_str().unwrap_or_default().to_string();
+                    if change["locked"] == true {
+                        policy.locked_at.insert(column, at);
+                    } else {
+                        policy.locked_at.remove(&column);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    policy
+}
+
+/// When `task` entered `column`: its `column_since`, else the latest
+/// activity entry that put it there.
+fn entered_column(task: &Task, column: &str, policy: &PolicyLog) -> Option<OffsetDateTime> {
+    task.column_since.as_ref().and_then(Timestamp::at).or_else(|| {
+        policy.entered.get(&task.id).filter(|(entered, _)| entered == column).map(|(_, at)| *at)
+    })
+}
+
+/// Ids of `tasks` that have been in `column` longer than its `max_age`. The
+/// activity log is only read when a task has no `column_since`.
+fn over_age_tasks(root: &Path, column: &BoardColumn, tasks: &[Task], now: OffsetDateTime) -> Vec<String> {
+    let Some(max_age) = column.max_age.as_deref().and_then(|v| parse_max_age(v).ok()) else {
+        return Vec::new();
+    };
+    let policy = if tasks.iter().any(|t| t.column_since.is_none()) { read_policy_log(root) } else { PolicyLog::default() };
+    tasks
+        .iter()
+        .filter(|task| entered_column(task, &column.id, &policy).is_some_and(|since| now - since > max_age))
+        .map(|task| task.id.clone())
+        .collect()
+}
+
+/// One breached column policy, as listed by `GET /api/violations`.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+enum Violation {
+    /// More tasks than `wip=` allows.
+    Wip { column: String, wip_limit: u32, count: usize, tasks: Vec<String> },
+    /// A task in the column for longer than `max_age=`.
+    MaxAge { column: String, max_age: String, task: String, since: String, age_hours: i64 },
+    /// A task that entered a `locked=true` column after it was locked.
+    Locked { column: String, task: String, since: String, locked_at: String },
+}
+
+/// Every breached policy on the board, in column order. Lock anomalies need
+/// the `board_changed` entry that locked the column; a column locked by
+/// editing the board file reports none.
+fn board_violations(root: &Path, cfg: &BoardConfig, folders: &HashMap<String, Vec<Task>>, now: OffsetDateTime) -> Vec<Violation> {
+    let policy = if cfg.columns.iter().any(|c| c.max_age.is_some() || c.locked) {
+        read_policy_log(root)
+    } else {
+        PolicyLog::default()
+    };
+    let mut violations = Vec::new();
+    for column in &cfg.columns {
+        let tasks = folders.get(&column.id).map(Vec::as_slice).unwrap_or_default();
+        if let Some(wip_limit) = column.wip_limit.filter(|_| wip_over_limit(column, tasks.len())) {
+            violations.push(Violation::Wip {
+                column: column.id.clone(),
+                wip_limit,
+                count: tasks.len(),
+                tasks: tasks.iter().map(|t| t.id.clone()).collect(),
+            });
+        }
+        let max_age = column.max_age.as_deref().and_then(|v| parse_max_age(v).ok());
+        let locked_at = policy.locked_at.get(&column.id).filter(|_| column.locked);
+        for task in tasks {
+            let Some(since) = entered_column(task, &column.id, &policy) else {
+                continue;
+            };
+            if max_age.is_some_and(|max_age| now - since > max_age) {
+                violations.push(Violation::MaxAge {
+                    column: column.id.clone(),
+                    max_age: column.max_age.clone().unwrap_or_default(),
+                    task: task.id.clone(),
+                    since: format_timestamp(since),
+                    age_hours: (now - since).whole_hours(),
+                });
+            }
+            if let Some(locked_at) = locked_at.filter(|locked_at| since > **locked_at) {
+                violations.push(Violation::Locked {
+                    column: column.id.clone(),
+                    task: task.id.clone(),
+                    since: format_timestamp(since),
+                    locked_at: format_timestamp(*locked_at),
+                });
+            }
+        }
+    }
+    violations
+}
+
+/// How often the server looks for tasks that went past their column's
+/// `max_age` since the last look.
+const VIOLATION_SCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The `max_age` breaches among `violations` that are not in `seen`, which
+/// becomes the current set; a task that leaves the column and comes back
+/// over age is reported again.
+fn new_age_violations(violations: Vec<Violation>, seen: &mut HashSet<(String, String)>) -> Vec<Violation> {
+    let mut current = HashSet::new();
+    let mut fresh = Vec::new();
+    for violation in violations {
+        let Violation::MaxAge { column, task, .. } = &violation else {
+            continue;
+        };
+        let key = (column.clone(), task.clone());
+        if !seen.contains(&key) {
+            fresh.push(violation);
+        }
+        current.insert(key);
+    }
+    *seen = current;
+    fresh
+}
+
+/// Announces a task newly past its column's `max_age`: a `policy_violation`
+/// event for live clients and a `violation` run of the hook.
+fn announce_violation(root: &Path, hooks: &Hooks, bus: &EventBus, violation: &Violation) {
+    let mut entry = serde_json::json!({ "at": now_iso(), "actor": "server", "action": "policy_violation" });
+    if let (Some(entry), serde_json::Value::Object(fields)) = (entry.as_object_mut(), serde_json::json!(violation)) {
+        entry.extend(fields);
+    }
+    publish_event(bus, "policy_violation", entry.clone());
+    notify_hooks(root, hooks, &entry);
+}
+
+fn column_meta(column: &BoardColumn, total: usize, count: usize) -> ColumnMeta {
+    ColumnMeta {
+        id: column.id.clone(),
+        title: column.title.clone(),
+        count,
+        total,
+        hidden: total.saturating_sub(count),
+        wip_limit: column.wip_limit,
+        frozen: is_frozen(column),
+        locked: column.locked,
+        over_limit: wip_over_limit(column, total),
+        max_age: column.max_age.clone(),
+        over_age: Vec::new(),
+        offset: 0,
+        returned: count,
+        truncated: false,
+    }
+}
+
+/// `limit` / `offset` query parameters, applied per column, and the
+/// sparse `fields` list applied to every task.
+#[derive(Debug, Default)]
+struct Page {
+    limit: Option<usize>,
+    offset: usize,
+    fields: Option<Vec<String>>,
+    /// `sort=field.<name>` (or `-field.<name>` for descending).
+    sort: Option<FieldSort>,
+}
+
+#[derive(Debug, PartialEq)]
+struct FieldSort {
+    name: String,
+    descending: bool,
+}
+
+fn parse_page(query: &HashMap<String, String>) -> Result<Page, String> {
+    let number = |key: &str| -> Result<Option<usize>, String> {
+        match query.get(key).map(|v| v.trim()).filter(|v| !v.is_empty()) {
+            None => Ok(None),
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("Invalid {}: {} (expected a non-negative integer)", key, value)),
+        }
+    };
+    let fields = query.get("fields").map(|list| {
+        list.split(',')
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect()
+    });
+    let sort = match query.get("sort").map(|v| v.trim()).filter(|v| !v.is_empty()) {
+        None => None,
+        Some(raw) => {
+            let (descending, key) = match raw.strip_prefix('-') {
+                Some(key) => (true, key),
+                None => (false, raw),
+            };
+            match key.strip_prefix("field.").filter(|name| !name.is_empty()) {
+                Some(name) => Some(FieldSort { name: name.to_string(), descending }),
+                None => return Err(format!("Invalid sort: {} (expected field.<name>, with a leading - for descending)", raw)),
+            }
+        }
+    };
+    Ok(Page {
+        limit: number("limit")?,
+        offset: number("offset")?.unwrap_or(0),
+        fields,
+        sort,
+    })
+}
+
+/// Serializes tasks, keeping only the requested fields (`id` always stays).
+fn project_tasks(tasks: &[Task], fields: Option<&[String]>) -> serde_json::Value {
+    let Some(fields) = fields else {
+        return serde_json::json!(tasks);
+    };
+    tasks
+        .iter()
+        .map(|task| match serde_json::json!(task) {
+            serde_json::Value::Object(map) => serde_json::Value::Object(
+                map.into_iter()
+                    .filter(|(key, _)| key == "id" || fields.iter().any(|f| f == key))
+                    .collect(),
+            ),
+            other => other,
+        })
+        .collect()
+}
+
+/// Orders two custom field values: `enum` values in declaration order,
+/// numbers by value, anything else case-insensitively.
+fn compare_field_values(field: Option<&FieldDef>, a: &serde_json::Value, b: &serde_json::Value) -> std::cmp::Ordering {
+    let rank = |value: &serde_json::Value| {
+        field
+            .filter(|f| f.kind == FieldKind::Enum)
+            .and_then(|f| f.values.iter().position(|v| Some(v.as_str()) == value.as_str()))
+    };
+    if let (Some(a), Some(b)) = (rank(a), rank(b)) {
+        return a.cmp(&b);
+    }
+    if let (Some(a), Some(b)) = (a.as_f64(), b.as_f64()) {
+        return a.total_cmp(&b);
+    }
+    field_text(a).to_lowercase().cmp(&field_text(b).to_lowercase())
+}
+
+/// Orders tasks by a column's `sort=` policy. Tasks without the sort value
+/// (no due date, no `priority` field) go last in either direction; ties keep
+/// their current order.
+fn sort_by_policy(tasks: &mut [Task], key: &str, descending: bool, fields: &[FieldDef]) {
+    let priority = fields.iter().find(|f| f.name == "priority");
+    let missing = |task: &Task| match key {
+        "priority" => !task.extra.contains_key("priority"),
+        "created" => task.created_at.at().is_none(),
+        "updated" => task.updated_at.at().is_none(),
+        "due" => task.due.as_deref().is_none_or(str::is_empty),
+        _ => false,
+    };
+    tasks.sort_by(|a, b| {
+        if missing(a) || missing(b) {
+            return missing(a).cmp(&missing(b));
+        }
+        let order = match key {
+            "priority" => compare_field_values(priority, &a.extra["priority"], &b.extra["priority"]),
+            "created" => a.created_at.at().cmp(&b.created_at.at()),
+            "updated" => a.updated_at.at().cmp(&b.updated_at.at()),
+            "due" => a.due.cmp(&b.due),
+            _ => std::cmp::Ordering::Equal,
+        };
+        if descending { order.reverse() } else { order }
+    });
+}
+
+/// Custom field holding the ids of the tasks a task waits for, separated by
+/// commas or spaces, e.g. `blocked_by: api-auth, db-migration`.
+const BLOCKED_BY_FIELD: &str = "blocked_by";
+
+/// The ids in a task's `blocked_by` field, normalized like URL ids. The task
+/// itself is never its own blocker.
+fn task_blockers(task: &Task) -> Vec<String> {
+    let Some(value) = task.extra.get(BLOCKED_BY_FIELD) else {
+        return Vec::new();
+    };
+    let mut ids: Vec<String> = Vec::new();
+    for id in field_text(value).split(|c: char| c == ',' || c.is_whitespace()).map(normalize_id) {
+        if !id.is_empty() && id != task.id && !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    ids
+}
+
+/// An unfinished task another one waits for.
+#[derive(Debug, Serialize)]
+struct Blocker {
+    id: String,
+    title: String,
+    column: String,
+}
+
+#[derive(Debug, Serialize)]
+struct BlockedTask {
+    #[serde(flatten)]
+    task: Task,
+    /// The blockers that are not done yet, in `blocked_by` order.
+    waiting_on: Vec<Blocker>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReadyCounts {
+    ready: usize,
+    blocked: usize,
+}
+
+/// `GET /api/tasks/ready`: a column split into tasks whose blockers are all
+/// finished and tasks still waiting.
+#[derive(Debug, Serialize)]
+struct ReadyListing {
+    column: String,
+    counts: ReadyCounts,
+    ready: Vec<Task>,
+    blocked: Vec<BlockedTask>,
+}
+
+/// Splits `column` of the board (`folders`, all columns) by its tasks'
+/// `blocked_by` field. A blocker is finished when it sits in a done column
+/// or in `archived`; an id no task has (a deleted task) counts as finished
+/// too, with a `dangling_blocker` warning. Both lists are ordered by the
+/// `priority` field as the column's `sort=priority` would order them (its
+/// `dir=` included), then oldest first.
+fn ready_listing(
+    cfg: &BoardConfig,
+    column: &str,
+    folders: &HashMap<String, Vec<Task>>,
+    archived: &HashSet<String>,
+) -> (ReadyListing, Vec<Warning>) {
+    let placed: HashMap<&str, &Task> = folders.values().flatten().map(|task| (task.id.as_str(), task)).collect();
+    let mut tasks = folders.get(column).cloned().unwrap_or_default();
+    let descending = cfg.columns.iter().any(|c| c.id == column && c.sort_dir.as_deref() == Some("desc"));
+    sort_by_policy(&mut tasks, "created", false, &cfg.fields);
+    sort_by_policy(&mut tasks, "priority", descending, &cfg.fields);
+    let mut listing = ReadyListing {
+        column: column.to_string(),
+        counts: ReadyCounts { ready: 0, blocked: 0 },
+        ready: Vec::new(),
+        blocked: Vec::new(),
+    };
+    let mut warnings = Vec::new();
+    for task in tasks {
+        let mut waiting_on = Vec::new();
+        for id in task_blockers(&task) {
+            match placed.get(id.as_str()) {
+                Some(blocker) if !is_done_column(cfg, &blocker.folder) => waiting_on.push(Blocker {
+                    id: blocker.id.clone(),
+                    title: blocker.title.clone(),
+                    column: blocker.folder.clone(),
+                }),
+                Some(_) => {}
+                None if archived.contains(&id) => {}
+                None => warnings.push(
+                    Warning::new("dangling_blocker", format!("{} is blocked by {}, which no longer exists", task.id, id))
+                        .task(&task.id)
+                        .column(column),
+                ),
+            }
+        }
+        if waiting_on.is_empty() {
+            listing.ready.push(task);
+        } else {
+            listing.blocked.push(BlockedTask { task, waiting_on });
+        }
+    }
+    listing.counts = ReadyCounts { ready: listing.ready.len(), blocked: listing.blocked.len() };
+    (listing, warnings)
+}
+
+/// Orders a column newest update first (id breaks ties), then by a custom
+/// field when the page asks for it or else by the column's `sort=` policy
+/// (`order` being its manual order), and keeps the requested page, spending
+/// at most `budget` tasks (None = no cap). Updates `meta` with what was
+/// returned.
+fn paginate(
+    tasks: &mut Vec<Task>,
+    column: &BoardColumn,
+    page: &Page,
+    fields: &[FieldDef],
+    order: &[String],
+    budget: &mut Option<usize>,
+    meta: &mut ColumnMeta,
+) {
+    tasks.sort_by_cached_key(|task| (std::cmp::Reverse(task.updated_at.at()), task.id.clone()));
+    if page.sort.is_none() {
+        match column.sort.as_deref().filter(|k| *k != "manual") {
+            Some(key) => sort_by_policy(tasks, key, column.sort_dir.as_deref() == Some("desc"), fields),
+            None => apply_manual_order(tasks, order),
+        }
+    }
+    if let Some(sort) = &page.sort {
+        let field = fields.iter().find(|f| f.name == sort.name);
+        // Tasks without the field go last in either direction.
+        tasks.sort_by(|a, b| match (a.extra.get(&sort.name), b.extra.get(&sort.name)) {
+            (Some(a), Some(b)) if sort.descending => compare_field_values(field, b, a),
+            (Some(a), Some(b)) => compare_field_values(field, a, b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+    }
+    let requested = tasks.len().saturating_sub(page.offset).min(page.limit.unwrap_or(usize::MAX));
+    let allowed = budget.map_or(requested, |left| requested.min(left));
+    tasks.drain(..page.offset.min(tasks.len()));
+    tasks.truncate(allowed);
+    if let Some(left) = budget {
+        *left -= allowed;
+    }
+    meta.offset = page.offset;
+    meta.returned = allowed;
+    meta.truncated = allowed < requested;
+}
+
+/// Filters and pagination shared by the task listings.
+fn parse_listing_query(query: &HashMap<String, String>, timezone: UtcOffset) -> Result<(TaskFilter, Page), String> {
+    Ok((parse_task_filter(query, timezone)?, parse_page(query)?))
+}
+
+fn response_budget(max_tasks: usize) -> Option<usize> {
+    Some(max_tasks).filter(|max| *max > 0)
+}
+
+fn board_totals(columns: &[ColumnMeta]) -> BoardTotals {
+    BoardTotals {
+        count: columns.iter().map(|c| c.count).sum(),
+        total: columns.iter().map(|c| c.total).sum(),
+        hidden: columns.iter().map(|c| c.hidden).sum(),
+        over_limit_columns: columns.iter().filter(|c| c.over_limit).count(),
+    }
+}
+
+/// Drops tasks rejected by `filter`; returns how many were dropped only
+/// because they lack a timestamp a date bound needs.
+fn retain_matching(tasks: &mut Vec<Task>, filter: &TaskFilter) -> usize {
+    let mut missing_dates = 0;
+    tasks.retain(|task| match filter_task(filter, task) {
+        FilterOutcome::Match => true,
+        FilterOutcome::Reject => false,
+        FilterOutcome::MissingDate => {
+            missing_dates += 1;
+            false
+        }
+    });
+    missing_dates
+}
+
+/// What a listing reports beside its tasks: columns over their WIP limit
+/// and tasks a date filter could not judge.
+fn listing_warnings(columns: &[ColumnMeta], missing_dates: usize) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = columns
+        .iter()
+        .filter(|meta| meta.over_limit)
+        .map(|meta| {
+            let limit = meta.wip_limit.unwrap_or_default();
+            let message = format!("{} holds {} tasks, over its WIP limit of {}", meta.title, meta.total, limit);
+            Warning::new("wip_limit_exceeded", message).column(&meta.id)
+        })
+        .collect();
+    if missing_dates > 0 {
+        let message = format!("{} tasks without the timestamp a date filter needs were left out", missing_dates);
+        warnings.push(Warning::new("missing_dates", message));
+    }
+    warnings
+}
+
+/// `GET /api/columns/{id}/tasks`: one column, scanning only its folder.
+fn column_tasks_payload(
+    root: &Path,
+    column: &BoardColumn,
+    fields: &[FieldDef],
+    mut tasks: Vec<Task>,
+    filter: &TaskFilter,
+    page: &Page,
+    max_tasks: usize,
+) -> serde_json::Value {
+    let total = tasks.len();
+    let over_age = over_age_tasks(root, column, &tasks, OffsetDateTime::now_utc());
+    let missing_dates = retain_matching(&mut tasks, filter);
+    let mut meta = column_meta(column, total, tasks.len());
+    meta.over_age = over_age;
+    let order = read_order(root).remove(&column.id).unwrap_or_default();
+    paginate(&mut tasks, column, page, fields, &order, &mut response_budget(max_tasks), &mut meta);
+    set_days_in_column(&mut tasks, OffsetDateTime::now_utc());
+    let warnings = listing_warnings(std::slice::from_ref(&meta), missing_dates);
+    let payload = serde_json::json!({
+        "truncated": meta.truncated,
+        "column": meta,
+        "tasks": project_tasks(&tasks, page.fields.as_deref()),
+        "filter": filter,
+        "filtered": filter_is_active(filter),
+        "excluded_missing_dates": missing_dates,
+    });
+    with_warnings(payload, warnings)
+}
+
+fn set_days_in_column(tasks: &mut [Task], now: OffsetDateTime) {
+    for task in tasks {
+        task.days_in_column = task
+            .column_since
+            .as_deref()
+            .and_then(parse_timestamp)
+            .map(|since| (now - since).whole_days().max(0));
+    }
+}
+
+/// Lists `folders` filtered and paged. With a `cache`, descriptions that
+/// `load_listing_tasks` left out are read for the returned tasks.
+#[allow(clippy::too_many_arguments)]
+fn tasks_payload(
+    root: &Path,
+    config: &BoardConfig,
+    opts: &BoardOptions,
+    mut folders: HashMap<String, Vec<Task>>,
+    filter: &TaskFilter,
+    page: &Page,
+    max_tasks: usize,
+    cache: Option<&TaskCache>,
+) -> serde_json::Value {
+    let mut columns = Vec::new();
+    let mut missing_dates = 0;
+    let mut budget = response_budget(max_tasks);
+    let order = read_order(root);
+    for column in &config.columns {
+        let tasks = folders.entry(column.id.clone()).or_default();
+        let total = tasks.len();
+        let over_age = over_age_tasks(root, column, tasks, OffsetDateTime::now_utc());
+        missing_dates += retain_matching(tasks, filter);
+        let mut meta = column_meta(column, total, tasks.len());
+        meta.over_age = over_age;
+        let column_order = order.get(&column.id).map(Vec::as_slice).unwrap_or_default();
+        paginate(tasks, column, page, &config.fields, column_order, &mut budget, &mut meta);
+        if let Some(cache) = cache.filter(|_| page.fields.as_deref().is_none_or(|f| f.iter().any(|f| f == "description"))) {
+            fill_descriptions(root, config.status_mode, opts, cache, tasks);
+        }
+        set_days_in_column(tasks, OffsetDateTime::now_utc());
+        columns.push(meta);
+    }
+    let truncated = columns.iter().any(|c| c.truncated);
+    let totals = board_totals(&columns);
+    // Emit folders in column order rather than HashMap order.
+    let mut ordered = serde_json::Map::new();
+    for column in &config.columns {
+        let tasks = folders.remove(&column.id).unwrap_or_default();
+        ordered.insert(column.id.clone(), project_tasks(&tasks, page.fields.as_deref()));
+    }
+    let warnings = listing_warnings(&columns, missing_dates);
+    let payload = serde_json::json!({
+        "folders": ordered,
+        "board": config,
+        "columns": columns,
+        "totals": totals,
+        "truncated": truncated,
+        "filter": filter,
+        "filtered": filter_is_active(filter),
+        "excluded_missing_dates": missing_dates,
+    });
+    with_warnings(payload, warnings)
+}
+
+/// Derives an id from a title. Dots survive only between two letters or
+/// digits ("v1.2 release" becomes `v1.2-release`), so the result always
+/// passes `is_valid_id`.
+fn slugify(input: &str) -> String {
+    slugify_without(input, &[])
+}
+
+/// The language whose built-in stop-words apply: `lang`, or English when
+/// there is no list for it.
+fn stopword_lang(lang: &str) -> &str {
+    if SLUG_STOPWORDS.iter().any(|(code, _)| *code == lang) {
+        lang
+    } else {
+        DEFAULT_LANG
+    }
+}
+
+/// The stop-words `task_slug` drops for this board: empty unless
+/// `slug_stopwords` is on, else the built-in list for `lang` plus the
+/// board's `slug_stopwords_extra`.
+fn slug_stopwords(config: &BoardConfig, lang: &str) -> Vec<String> {
+    if !config.slug_stopwords {
+        return Vec::new();
+    }
+    let lang = stopword_lang(lang);
+    let builtin = SLUG_STOPWORDS.iter().find(|(code, _)| *code == lang).map(|(_, words)| *words).unwrap_or_default();
+    let mut words: Vec<String> = builtin.iter().map(|w| w.to_string()).collect();
+    for extra in &config.slug_stopwords_extra {
+        if !words.contains(extra) {
+            words.push(extra.clone());
+        }
+    }
+    words
+}
+
+/// The id a new or renamed task gets from its title under the board's rules.
+fn task_slug(config: &BoardConfig, lang: &str, title: &str) -> String {
+    slugify_without(title, &slug_stopwords(config, lang))
+}
+
+/// `slugify`, dropping whole words found in `stopwords`. The last word is
+/// kept when every word is a stop-word, so "The" still becomes `the`.
+fn slugify_without(input: &str, stopwords: &[String]) -> String {
+    let mut out = String::new();
+    let mut last_dash = false;
+    let chars: Vec<char> = input.to_lowercase().chars().collect();
+    for (i, &ch) in chars.iter().enumerate() {
+        let inner_dot = ch == '.'
+            && i > 0
+            && chars[i - 1].is_ascii_alphanumeric()
+            && chars.get(i + 1).is_some_and(|c| c.is_ascii_alphanumeric());
+        if ch.is_ascii_alphanumeric() || inner_dot {
+            out.push(ch);
+            last_dash = false;
+        } else if (ch.is_whitespace() || ch == '-' || ch == '_') && !last_dash {
+            out.push('-');
+            last_dash = true;
+        }
+    }
+    let mut words: Vec<&str> = out.split('-').filter(|w| !w.is_empty()).collect();
+    if !stopwords.is_empty() {
+        let last = words.last().copied();
+        words.retain(|w| !stopwords.iter().any(|s| s == w));
+        if words.is_empty() {
+            words.extend(last);
+        }
+    }
+    let trimmed = words.join("-");
+    if trimmed.is_empty() {
+        "task".to_string()
+    } else {
+        portable_stem(&trimmed)
+    }
+}
+
+/// Device names Windows reserves in any folder, with or without extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8", "com9", "lpt1",
+    "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Windows' MAX_PATH. Checked on every platform so a board stays portable.
+const MAX_PATH_LEN: usize = 260;
+
+fn is_reserved_name(stem: &str) -> bool {
+    let base = stem.split('.').next().unwrap_or(stem);
+    RESERVED_NAMES.iter().any(|name| base.eq_ignore_ascii_case(name))
+}
+
+/// Device names only trip up Windows; elsewhere `con` is an ordinary id.
+fn reserved_here(stem: &str) -> bool {
+    cfg!(windows) && is_reserved_name(stem)
+}
+
+/// `con` becomes `con-task` and `aux.log` becomes `aux-task.log`; other stems
+/// are returned unchanged.
+fn avoid_reserved_name(stem: &str) -> String {
+    if !is_reserved_name(stem) {
+        return stem.to_string();
+    }
+    match stem.split_once('.') {
+        Some((base, rest)) => format!("{}-task.{}", base, rest),
+        None => format!("{}-task", stem),
+    }
+}
+
+/// A file stem that names the same file on every platform: NTFS drops
+/// trailing dots and spaces, and on Windows device names get a suffix.
+fn portable_stem(stem: &str) -> String {
+    let trimmed = stem.trim_end_matches(['.', ' ']);
+    if reserved_here(trimmed) {
+        avoid_reserved_name(trimmed)
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// `task_path` for a file about to be written, refusing paths longer than
+/// `MAX_PATH_LEN` characters.
+fn checked_task_path(root: &Path, folder: &str, id: &str) -> Result<PathBuf, String> {
+    let path = task_path(root, folder, id);
+    let len = path.to_string_lossy().chars().count();
+    if len > MAX_PATH_LEN {
+        return Err(format!(
+            "Task path is {} characters, over the {} character limit: {}",
+            len,
+            MAX_PATH_LEN,
+            path.display()
+        ));
+    }
+    Ok(path)
+}
+
+/// Accepts `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` and returns the value in
+/// lowercase. An empty value means "no color".
+fn normalize_color(value: &str) -> Result<Option<String>, String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    let hex = trimmed
+        .strip_prefix('#')
+        .ok_or_else(|| format!("Invalid color: {} (expected #rrggbb)", trimmed))?;
+    if ![3, 4, 6, 8].contains(&hex.len()) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Invalid color: {} (expected #rrggbb)", trimmed));
+    }
+    Ok(Some(format!("#{}", hex.to_ascii_lowercase())))
+}
+
+/// Keeps only the first grapheme cluster so an icon is always one symbol.
+fn normalize_icon(value: &str) -> Option<String> {
+    value
+        .trim()
+        .graphemes(true)
+        .next()
+        .map(|g| g.to_string())
+}
+
+/// Ids are file stems: lowercase ASCII letters, digits, `-` and `.`, never
+/// starting with a dot or containing `..`, so an id cannot leave its folder.
+/// A trailing dot (dropped by NTFS) and, on Windows, device names such as
+/// `con` are rejected as well.
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && !id.starts_with('.')
+        && !id.ends_with('.')
+        && !id.contains("..")
+        && !reserved_here(id)
+        && id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')
+}
+
+/// Canonical form of an id taken from a URL: trimmed, lowercase and without
+/// a trailing `.md`, since people paste file names.
+fn normalize_id(raw: &str) -> String {
+    let lower = raw.trim().to_ascii_lowercase();
+    match lower.strip_suffix(".md") {
+        Some(stem) => stem.to_string(),
+        None => lower,
+    }
+}
+
+/// The id of a task file, or None when `path` is not a `.md` file whose stem
+/// is a valid id. Listings skip such files so every listed task is addressable.
+fn task_file_id(path: &Path, follow_symlinks: bool) -> Option<&str> {
+    if path.extension().and_then(|e| e.to_str()) != Some("md") || link_skip_reason(path, follow_symlinks).is_some() {
+        return None;
+    }
+    path.file_stem().and_then(|s| s.to_str()).filter(|stem| is_valid_id(stem))
+}
+
+/// Why a task file that is a symlink is not loaded, or None for a regular
+/// file and for a link that `follow` allows. Dangling links are always
+/// skipped. Symlinked column folders are followed regardless.
+fn link_skip_reason(path: &Path, follow: bool) -> Option<&'static str> {
+    let meta = fs::symlink_metadata(path).ok()?;
+    if !meta.file_type().is_symlink() {
+        return None;
+    }
+    match fs::metadata(path) {
+        Err(_) => Some("dangling symlink"),
+        Ok(target) if !target.is_file() => Some("symlink to a non-file"),
+        Ok(_) if !follow => Some("symlink (start with --follow-symlinks to load it)"),
+        Ok(_) => None,
+    }
+}
+
+/// True when `path`, with every symlink resolved, lies inside `dir` (also
+/// resolved). Paths that do not exist are never inside.
+fn resolves_within(path: &Path, dir: &Path) -> bool {
+    match (fs::canonicalize(path), fs::canonicalize(dir)) {
+        (Ok(path), Ok(dir)) => path.starts_with(dir),
+        _ => false,
+    }
+}
+
+/// Removes a folder, or only the link when it is a symlink, so cleaning up
+/// a linked column never deletes anything on the other side.
+fn remove_folder(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.file_type().is_symlink() {
+        // Windows directory links are removed like directories.
+        fs::remove_file(path).or_else(|_| fs::remove_dir(path))
+    } else {
+        fs::remove_dir_all(path)
+    }
+}
+
+/// Error code sent with 507 when a create would pass `max_tasks`.
+const BOARD_FULL: &str = "board_full";
+
+/// Task count and bytes on disk, from directory listings only (no file is
+/// parsed), so it stays cheap on very large boards.
+#[derive(Debug, Serialize, Default, PartialEq)]
+struct BoardUsage {
+    tasks: usize,
+    bytes: u64,
+    max_tasks: Option<usize>,
+    /// Share of `max_tasks` in use, rounded to one decimal.
+    percent_of_cap: Option<f64>,
+}
+
+fn board_usage(root: &Path, config: &BoardConfig, opts: &BoardOptions) -> BoardUsage {
+    let mut usage = BoardUsage { max_tasks: config.max_tasks, ..Default::default() };
+    for column in &config.columns {
+        let Ok(entries) = fs::read_dir(root.join(&column.id)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if task_file_id(&path, opts.follow_symlinks).is_some() {
+                usage.tasks += 1;
+                usage.bytes += fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            }
+        }
+    }
+    usage.percent_of_cap = config
+        .max_tasks
+        .map(|max| (usage.tasks as f64 * 1000.0 / max as f64).round() / 10.0);
+    usage
+}
+
+/// Refuses adding `adding` tasks to a board that already holds `current`.
+fn check_capacity(config: &BoardConfig, current: usize, adding: usize) -> Result<(), String> {
+    match config.max_tasks {
+        Some(max) if current + adding > max => {
+            Err(message_in(DEFAULT_LANG, "board_full", &[("current", &current.to_string()), ("max", &max.to_string())]))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn board_full(msg: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    respond_json(StatusCode(507), &serde_json::json!({ "error": msg, "code": BOARD_FULL }).to_string())
+}
+
+/// Held from picking a new id until its file exists, so two concurrent
+/// creates (or a create and a rename) never settle on the same id.
+static CREATE_LOCK: Mutex<()> = Mutex::new(());
+
+#[cfg(test)]
+thread_local! {
+    static ID_LISTINGS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Ids of every task on the board, from one listing per column folder.
+fn existing_ids(root: &Path, config: &BoardConfig) -> HashSet<String> {
+    let mut ids = HashSet::new();
+    for column in &config.columns {
+        #[cfg(test)]
+        ID_LISTINGS.with(|n| n.set(n.get() + 1));
+        let Ok(entries) = fs::read_dir(root.join(&column.id)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("md") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    // Lowercased: on case-insensitive file systems `Fix.md`
+                    // would clash with a new `fix.md`.
+                    ids.insert(stem.to_ascii_lowercase());
+                }
+            }
+        }
+    }
+    ids
+}
+
+/// Picks `base`, or `base-N` with the smallest free N >= 2.
+fn unique_slug(base: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("unbounded suffix range")
+}
+
+/// The id a create request gets and how a clash was handled.
+#[derive(Debug, Clone)]
+struct SettledId {
+    id: String,
+    strategy: Collision,
+    outcome: CollisionOutcome,
+}
+
+/// Settles the id of a create request against `taken`. The strategy is the
+/// request's own `collision`, else `fallback` (a query parameter), else the
+/// board's, else `suffix` for ids made from the title and `error` for
+/// explicit ones. `error` fails with 409 carrying the taken id.
+fn settle_id(
+    cfg: &BoardConfig,
+    opts: &BoardOptions,
+    new_task: &NewTask,
+    fallback: Option<Collision>,
+    taken: &HashSet<String>,
+) -> Result<SettledId, (u16, String)> {
+    let explicit = new_task.id.as_deref().map(str::trim).filter(|id| !id.is_empty());
+    let base = match explicit {
+        Some(id) if !is_valid_id(id) => return Err((400, format!("Invalid task id: {}", id))),
+        Some(id) => id.to_string(),
+        None => task_slug(cfg, &opts.lang, &new_task.title),
+    };
+    let strategy = new_task
+        .collision
+        .or(fallback)
+        .or(cfg.collision)
+        .unwrap_or(if explicit.is_some() { Collision::Error } else { Collision::Suffix });
+    let settled = |id: String, outcome| Ok(SettledId { id, strategy, outcome });
+    if !taken.contains(&base) {
+        return settled(base, CollisionOutcome::Created);
+    }
+    match strategy {
+        Collision::Suffix => settled(unique_slug(&base, taken), CollisionOutcome::Suffixed),
+        Collision::Overwrite => settled(base, CollisionOutcome::Overwritten),
+        Collision::Skip => settled(base, CollisionOutcome::Skipped),
+        Collision::Error => Err((409, base)),
+    }
+}
+
+fn id_conflict(id: &str) -> Response<io::Cursor<Vec<u8>>> {
+    let mut payload = error_payload("id_conflict", &[("id", id)]);
+    payload["id"] = serde_json::json!(id);
+    respond_json(StatusCode(409), &payload.to_string())
+}
+
+/// Sets aside the task an `overwrite` create replaces: its content goes
+/// into history, and its file is removed when the new task lands in another
+/// column. Returns the replaced file for the journal and for rollbacks.
+fn replace_existing(
+    root: &Path,
+    cfg: &BoardConfig,
+    opts: &BoardOptions,
+    id: &str,
+    folder: &str,
+    admin: bool,
+    history_limit: usize,
+) -> Result<FileState, (u16, String)> {
+    // An id that is taken but not a task file here (a skipped symlink) is
+    // a plain conflict.
+    let (path, column) = find_task_path(root, id, cfg, opts).ok_or_else(|| (409, id.to_string()))?;
+    if let Some(locked) = locked_column(cfg, &[&column], admin) {
+        return Err((423, locked_column_error(locked)));
+    }
+    let content = fs::read_to_string(&path).map_err(|err| (500, err.to_string()))?;
+    record_history(root, id, &path, history_limit);
+    if column != folder {
+        expect_change(&path, None);
+        fs::remove_file(&path).map_err(|err| (500, err.to_string()))?;
+    }
+    Ok(file_state(&column, id, content))
+}
+
+/// Puts a task replaced by `replace_existing` back after a failed write.
+fn restore_replaced(root: &Path, replaced: &FileState) {
+    if let Err(err) = fs::write(task_path(root, &replaced.folder, &replaced.id), &replaced.content) {
+        eprintln!("Could not restore {}: {}", replaced.id, err);
+    }
+}
+
+/// Activity fields of a create: its column, and the column of the task it
+/// overwrote.
+fn created_activity(task: &Task, applied: &AppliedDefaults, replaced: Option<&FileState>) -> serde_json::Value {
+    let mut extra = serde_json::json!({ "column": task.folder });
+    if let Some(replaced) = replaced {
+        extra["overwritten"] = serde_json::json!(replaced.folder);
+    }
+    entry_activity(extra, applied)
+}
+
+/// Turns a create request into a task ready to be written. Unknown or missing
+/// columns fall back to the board's default column; `taken` holds the ids already in use.
+fn build_new_task(
+    root: &Path,
+    cfg: &BoardConfig,
+    opts: &BoardOptions,
+    new_task: NewTask,
+    taken: &HashSet<String>,
+) -> Result<(Task, AppliedDefaults), String> {
+    let color = normalize_color(new_task.color.as_deref().unwrap_or(""))?;
+    let due = normalize_due(new_task.due.as_deref().unwrap_or(""))?;
+    let estimate = normalize_estimate(new_task.estimate.unwrap_or(0.0))?;
+    let created_at = match new_task.created_at.as_deref().filter(|raw| !raw.trim().is_empty()) {
+        Some(raw) => Timestamp::parse("created_at", raw)?,
+        None => Timestamp::now(),
+    };
+    let extra = normalize_extra(&cfg.fields, new_task.extra.clone().unwrap_or_default())?;
+    let is_column = |s: &String| cfg.columns.iter().any(|c| c.id == *s);
+    let folder = new_task
+        .folder
+        .clone()
+        .filter(is_column)
+        .or_else(|| new_task.status.clone().filter(is_column))
+        .unwrap_or_else(|| default_column(cfg).to_string());
+    if let Some(column) = cfg.columns.iter().find(|c| c.id == folder).filter(|c| is_frozen(c)) {
+        return Err(frozen_column_error(column));
+    }
+    let status = match cfg.status_mode {
+        StatusMode::Column => folder.clone(),
+        StatusMode::Free => {
+            let status = new_task.status.clone().unwrap_or_default();
+            validate_status(cfg, &status)?;
+            status
+        }
+    };
+    let id = match new_task.id.as_deref().map(str::trim).filter(|id| !id.is_empty()) {
+        Some(id) if !is_valid_id(id) => return Err(format!("Invalid task id: {}", id)),
+        Some(id) if taken.contains(id) => {
+            return Err(format!("Task id already exists: {}", id))
+        }
+        Some(id) => id.to_string(),
+        None => unique_slug(&task_slug(cfg, &opts.lang, &new_task.title), taken),
+    };
+    let now = Timestamp::now();
+    let mut task = Task {
+        id,
+        title: new_task.title,
+        description: new_task.description.unwrap_or_default(),
+        creator: new_task.creator.unwrap_or_default(),
+        assigned_to: new_task.assigned_to.unwrap_or_default(),
+        created_at,
+        updated_at: now.clone(),
+        status,
+        tags: new_task.tags.unwrap_or_default(),
+        folder: folder.clone(),
+        color,
+        icon: new_task.icon.as_deref().and_then(normalize_icon),
+        mentions: Vec::new(),
+        due,
+        estimate,
+        completed_at: None,
+        column_since: Some(now),
+        days_in_column: None,
+        locked_by: None,
+        locked_until: None,
+        attachments: Vec::new(),
+        extra: serde_json::Map::new(),
+        file: None,
+    };
+    merge_extra(&mut task, extra);
+    task.mentions = extract_mentions(&task.description);
+    update_completion(cfg, &mut task);
+    let applied = match cfg.columns.iter().find(|c| c.id == folder) {
+        Some(column) => {
+            let mut applied = apply_column_defaults(column, &mut task);
+            apply_column_template(root, cfg, opts, column, &mut task, &mut applied);
+            apply_auto_assign(root, column, &mut task, &mut applied);
+            applied
+        }
+        None => AppliedDefaults::default(),
+    };
+    Ok((task, applied))
+}
+
+/// Creates one task file. Errors carry the HTTP status to answer with.
+/// Lowercased with whitespace runs collapsed, for duplicate checks.
+fn normalize_title(title: &str) -> String {
+    title.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Shortest title (in words) that counts as a near duplicate by prefix.
+const TITLE_PREFIX_MIN_WORDS: usize = 2;
+
+#[derive(Debug, Serialize)]
+struct SimilarTask {
+    id: String,
+    title: String,
+    column: String,
+    /// Same normalized title; otherwise one title starts with the other.
+    exact: bool,
+}
+
+fn similar_title_warning(similar: &SimilarTask) -> Warning {
+    let kind = if similar.exact { "the same title" } else { "a similar title" };
+    let message = format!("{} in {} has {}: {:?}", similar.id, similar.column, kind, similar.title);
+    Warning::new("similar_title", message).task(&similar.id).column(&similar.column)
+}
+
+/// Existing tasks whose title matches `title` after normalization, or
+/// whose title starts with it (or is a start of it) at a word boundary.
+/// Exact matches come first.
+fn similar_tasks(folders: &HashMap<String, Vec<Task>>, title: &str) -> Vec<SimilarTask> {
+    let wanted = normalize_title(title);
+    if wanted.is_empty() {
+        return Vec::new();
+    }
+    let is_prefix = |short: &str, long: &str| {
+        short.split(' ').count() >= TITLE_PREFIX_MIN_WORDS && long.strip_prefix(short).is_some_and(|rest| rest.starts_with(' '))
+    };
+    let mut similar: Vec<SimilarTask> = folders
+        .values()
+        .flatten()
+        .filter_map(|task| {
+            let existing = normalize_title(&task.title);
+            let exact = existing == wanted;
+            (exact || is_prefix(&wanted, &existing) || is_prefix(&existing, &wanted)).then(|| SimilarTask {
+                id: task.id.clone(),
+                title: task.title.clone(),
+                column: task.folder.clone(),
+                exact,
+            })
+        })
+        .collect();
+    similar.sort_by(|a, b| b.exact.cmp(&a.exact).then_with(|| a.id.cmp(&b.id)));
+    similar
+}
+
+/// Creates one task file. Errors carry the HTTP status to answer with; a
+/// 409 carries the taken id (`skip` refuses like `error` here). An
+/// overwritten task is returned for the journal.
+/// Creates one task. Errors carry the id for 409 and the broken rule's
+/// spec for 422.
+fn create_task(
+    root: &Path,
+    cfg: &BoardConfig,
+    opts: &BoardOptions,
+    new_task: NewTask,
+    admin: bool,
+    force_rules: bool,
+    history_limit: usize,
+) -> Result<(Task, AppliedDefaults, Option<FileState>), (u16, String)> {
+    let _guard = CREATE_LOCK.lock().unwrap();
+    let mut taken = existing_ids(root, cfg);
+    let settled = settle_id(cfg, opts, &new_task, None, &taken)?;
+    let overwrite = match settled.outcome {
+        CollisionOutcome::Skipped => return Err((409, settled.id)),
+        CollisionOutcome::Overwritten => taken.remove(&settled.id),
+        _ => false,
+    };
+    if !overwrite {
+        check_capacity(cfg, taken.len(), 1).map_err(|msg| (507, msg))?;
+    }
+    let new_task = NewTask { id: Some(settled.id), ..new_task };
+    let (mut task, applied) = build_new_task(root, cfg, opts, new_task, &taken).map_err(|msg| (400, msg))?;
+    if let Some(column) = locked_column(cfg, &[&task.folder], admin) {
+        return Err((423, locked_column_error(column)));
+    }
+    if let Some(rule) = broken_rule(cfg, &task.folder, &task, force_rules) {
+        return Err((422, rule.spec()));
+    }
+    let path = checked_task_path(root, &task.folder, &task.id).map_err(|msg| (400, msg))?;
+    let replaced = match overwrite {
+        true => Some(replace_existing(root, cfg, opts, &task.id, &task.folder, admin, history_limit)?),
+        false => None,
+    };
+    if let Err(err) = write_task(&path, &mut task) {
+        replaced.iter().for_each(|replaced| restore_replaced(root, replaced));
+        return Err((500, err.to_string()));
+    }
+    Ok((task, applied, replaced))
+}
+
+#[derive(Debug, Deserialize)]
+struct TransferTask {
+    to_board: String,
+    folder: Option<String>,
+}
+
+/// Copies the files of a flat directory (an attachments folder).
+fn copy_files(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves task `id` into `folder` (default: the default column) of another
+/// board, renaming it on an id clash. Works across file systems: the copy is
+/// staged under a hidden name next to its target, the source is set aside
+/// under a hidden name, and only then does the copy go live. Any failure puts
+/// the source back, so there is never a second live copy. Returns the task
+/// as written and the column it left.
+#[allow(clippy::too_many_arguments)]
+fn transfer_task(
+    root: &Path,
+    cfg: &BoardConfig,
+    opts: &BoardOptions,
+    id: &str,
+    dest_root: &Path,
+    dest_cfg: &BoardConfig,
+    folder: Option<&str>,
+    history_limit: usize,
+) -> Result<(Task, String), (u16, String)> {
+    let folder = folder.unwrap_or_else(|| default_column(dest_cfg)).to_string();
+    if !dest_cfg.columns.iter().any(|c| c.id == folder) {
+        return Err((400, "invalid folder".to_string()));
+    }
+    let (path, current_folder) = find_task_path(root, id, cfg, opts).ok_or((404, "task not found".to_string()))?;
+    let mut task = parse_task(&path, &current_folder, cfg.status_mode, &opts.base_path).map_err(|err| (500, err.to_string()))?;
+
+    let _guard = CREATE_LOCK.lock().unwrap();
+    let taken = existing_ids(dest_root, dest_cfg);
+    check_capacity(dest_cfg, taken.len(), 1).map_err(|msg| (507, msg))?;
+    let new_id = unique_slug(id, &taken);
+    let dest_path = checked_task_path(dest_root, &folder, &new_id).map_err(|msg| (400, msg))?;
+
+    task.id = new_id.clone();
+    task.folder = folder.clone();
+    match dest_cfg.status_mode {
+        StatusMode::Column => task.status = folder.clone(),
+        StatusMode::Free => {
+            if validate_status(dest_cfg, &task.status).is_err() {
+                task.status = String::new();
+            }
+        }
+    }
+    touch_task(&mut task);
+    task.column_since = Some(task.updated_at.clone());
+    update_completion(dest_cfg, &mut task);
+    record_history(root, id, &path, history_limit);
+
+    let fail = |err: io::Error| (500, err.to_string());
+    let staged = dest_path.with_file_name(format!(".{}.md.transfer", new_id));
+    let set_aside = path.with_file_name(format!(".{}.md.transfer", id));
+    let source_attachments = attachments_dir(root, id);
+    let dest_attachments = attachments_dir(dest_root, &new_id);
+    let copied_attachments = source_attachments.is_dir();
+    let undo_copy = || {
+        let _ = fs::remove_file(&staged);
+        if copied_attachments {
+            let _ = fs::remove_dir_all(&dest_attachments);
+        }
+    };
+    if let Err(err) = write_task(&staged, &mut task) {
+        undo_copy();
+        return Err(fail(err));
+    }
+    if copied_attachments {
+        if let Err(err) = copy_files(&source_attachments, &dest_attachments) {
+            undo_copy();
+            return Err(fail(err));
+        }
+    }
+    expect_change(&path, None);
+    if let Err(err) = fs::rename(&path, &set_aside) {
+        undo_copy();
+        return Err(fail(err));
+    }
+    if let Err(err) = fs::rename(&staged, &dest_path) {
+        let _ = fs::rename(&set_aside, &path);
+        undo_copy();
+        return Err(fail(err));
+    }
+    task.file = task_file(&dest_path, &task.folder);
+    if let Err(err) = fs::remove_file(&set_aside) {
+        eprintln!("Failed to remove {}: {}", set_aside.display(), err);
+    }
+    if copied_attachments {
+        remove_attachments(root, id);
+    }
+    Ok((task, current_folder))
+}
+
+/// `POST /api/boards/{src}/tasks/{id}/transfer`: `src` must be the served
+/// board; `to_board` one registered with `--board`.
+#[allow(clippy::too_many_arguments)]
+fn handle_transfer(
+    root: &Path,
+    cfg: &BoardConfig,
+    src: &str,
+    id: &str,
+    body: &str,
+    settings: &Settings,
+    hooks: &Hooks,
+    actor: &str,
+    admin: bool,
+    update_state: &Arc<UpdateState>,
+) -> Response<io::Cursor<Vec<u8>>> {
+    if src != board_name(root) {
+        return respond_json(StatusCode(404), &serde_json::json!({ "error": "board not found" }).to_string());
+    }
+    let (request, ignored) = match parse_body::<TransferTask>(body, settings.strict_api) {
+        Ok(parsed) => parsed,
+        Err(error) => return respond_json(StatusCode(400), &error.to_string()),
+    };
+    let Some(dest) = settings.boards.iter().find(|board| board.name == request.to_board) else {
+        return respond_json(
+            StatusCode(404),
+            &serde_json::json!({ "error": format!("unknown board: {} (register it with --board)", request.to_board) }).to_string(),
+        );
+    };
+    let current = find_task_path(root, id, cfg, &settings.board).map(|(_, folder)| folder).unwrap_or_default();
+    if let Some(column) = locked_column(cfg, &[&current], admin) {
+        return column_locked(column);
+    }
+    let dest_cfg = match read_config(&dest.root) {
+        Ok(dest_cfg) => dest_cfg,
+        Err(err) => {
+            return respond_json(
+                StatusCode(503),
+                &serde_json::json!({ "error": format!("board {} is unavailable: {}", dest.name, err) }).to_string(),
+            )
+        }
+    };
+    match transfer_task(root, cfg, &settings.board, id, &dest.root, &dest_cfg, request.folder.as_deref(), settings.history_limit) {
+        Ok((task, from_column)) => {
+            record_task_activity(
+                root,
+                hooks,
+                actor,
+                "task_transferred",
+                id,
+                serde_json::json!({ "from": from_column, "to_board": dest.name, "to_id": task.id, "to": task.folder }),
+            );
+            record_task_activity(
+                &dest.root,
+                hooks,
+                actor,
+                "task_transferred",
+                &task.id,
+                serde_json::json!({ "from_board": src, "from_id": id, "from": from_column, "to": task.folder }),
+            );
+            notify_update(update_state);
+            let payload = serde_json::json!({ "board": dest.name, "task": task });
+            respond_json(StatusCode(200), &with_ignored_fields(payload, &ignored).to_string())
+        }
+        Err((507, msg)) => board_full(&msg),
+        Err((status, msg)) => respond_json(StatusCode(status), &serde_json::json!({ "error": msg }).to_string()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct HandoverRequest {
+    to: String,
+    /// Columns to take tasks from; every column when absent.
+    columns: Option<Vec<String>>,
+    /// Only list the tasks that would be handed over.
+    #[serde(default)]
+    dry_run: bool,
+    /// Also take tasks from done columns that `columns` does not name.
+    #[serde(default)]
+    include_done: bool,
+    /// Also take tasks from `_archive/`.
+    #[serde(default)]
+    include_archive: bool,
+}
+
+/// The tasks a handover takes from `from`: those assigned to them (ignoring
+/// case) in the requested columns. Done columns count only when `columns`
+/// names them or with `include_done`, `_archive/` only with `include_archive`.
+fn handover_tasks(root: &Path, cfg: &BoardConfig, opts: &BoardOptions, from: &str, request: &HandoverRequest) -> Result<Vec<Task>, (u16, String)> {
+    if let Some(unknown) = request.columns.iter().flatten().find(|id| !cfg.columns.iter().any(|c| &c.id == *id)) {
+        return Err((400, format!("Unknown column: {}", unknown)));
+    }
+    let mut folders = load_all_tasks(root, cfg, opts, None).map_err(|err| (500, err.to_string()))?;
+    let mut tasks = Vec::new();
+    for column in &cfg.columns {
+        let wanted = match &request.columns {
+            Some(columns) => columns.contains(&column.id),
+            None => request.include_done || !is_done_column(cfg, &column.id),
+        };
+        if wanted {
+            tasks.extend(folders.remove(&column.id).unwrap_or_default());
+        }
+    }
+    if request.include_archive {
+        tasks.extend(scan_column(root, "_archive", cfg.status_mode, opts, None).map_err(|err| (500, err.to_string()))?);
+    }
+    tasks.retain(|task| task.assigned_to.eq_ignore_ascii_case(from));
+    Ok(tasks)
+}
+
+/// `POST /api/users/{from}/handover`: reassigns `from`'s tasks to `to`. Each
+/// task is journaled and logged as `task_reassigned`; the hook and the event
+/// stream get a single `tasks_handed_over` event listing all of them.
+#[allow(clippy::too_many_arguments)]
+fn handle_handover(
+    root: &Path,
+    cfg: &BoardConfig,
+    from: &str,
+    body: &str,
+    settings: &Settings,
+    hooks: &Hooks,
+    actor: &str,
+    journal: &Journal,
+    update_state: &Arc<UpdateState>,
+    events: &EventBus,
+) -> Response<io::Cursor<Vec<u8>>> {
+    let (request, ignored) = match parse_body::<HandoverRequest>(body, settings.strict_api) {
+        Ok(parsed) => parsed,
+        Err(error) => return respond_json(StatusCode(400), &error.to_string()),
+    };
+    let to = request.to.trim();
+    let users = read_users_file(root);
+    let invalid = if to.is_empty() {
+        Some("to is required".to_string())
+    } else if to.eq_ignore_ascii_case(from) {
+        Some(format!("{} already owns these tasks", to))
+    } else if !users.is_empty() && !users.iter().any(|user| user.eq_ignore_ascii_case(to)) {
+        Some(format!("Unknown user: {} (not listed in {})", to, USERS_FILE))
+    } else {
+        None
+    };
+    if let Some(msg) = invalid {
+        return respond_json(StatusCode(400), &serde_json::json!({ "error": msg }).to_string());
+    }
+    let tasks = match handover_tasks(root, cfg, &settings.board, from, &request) {
+        Ok(tasks) => tasks,
+        Err((status, msg)) => return respond_json(StatusCode(status), &serde_json::json!({ "error": msg }).to_string()),
+    };
+    if request.dry_run {
+        let ids: Vec<&str> = tasks.iter().map(|task| task.id.as_str()).collect();
+        let payload = serde_json::json!({ "from": from, "to": to, "dry_run": true, "tasks": ids, "failed": [] });
+        return respond_json(StatusCode(200), &with_ignored_fields(payload, &ignored).to_string());
+    }
+
+    let mut handed = Vec::new();
+    let mut failed = Vec::new();
+    for mut task in tasks {
+        let path = task_path(root, &task.folder, &task.id);
+        let before = fs::read_to_string(&path).unwrap_or_default();
+        record_history(root, &task.id, &path, settings.history_limit);
+        task.assigned_to = to.to_string();
+        touch_task(&mut task);
+        let (before, after) = (file_state(&task.folder, &task.id, before), file_state(&task.folder, &task.id, render_task(&task)));
+        match journaled(journal, "update", Some(before), Some(after), || write_task(&path, &mut task)) {
+            Ok(_) => {
+                append_activity(
+                    root,
+                    serde_json::json!({
+                        "at": now_iso(),
+                        "actor": actor,
+                        "action": "task_reassigned",
+                        "task": task.id,
+                        "column": task.folder,
+                        "from_user": from,
+                        "to_user": to,
+                    }),
+                );
+                handed.push(task.id);
+            }
+            Err(err) => failed.push(serde_json::json!({ "task": task.id, "error": err.to_string() })),
+        }
+    }
+    if !handed.is_empty() {
+        let summary = serde_json::json!({ "from_user": from, "to_user": to, "tasks": handed });
+        let mut entry = serde_json::json!({ "at": now_iso(), "actor": actor, "action": "tasks_handed_over" });
+        if let (Some(entry), serde_json::Value::Object(summary)) = (entry.as_object_mut(), summary.clone()) {
+            entry.extend(summary);
+        }
+        notify_hooks(root, hooks, &entry);
+        publish_event(events, "tasks_handed_over", summary);
+        notify_update(update_state);
+    }
+    let status = if failed.is_empty() { 200 } else { 207 };
+    let payload = serde_json::json!({ "from": from, "to": to, "dry_run": false, "tasks": handed, "failed": failed });
+    respond_json(StatusCode(status), &with_ignored_fields(payload, &ignored).to_string())
+}
+
+/// Share links, one JSON array in the board root.
+const SHARES_FILE: &str = ".kanban-shares";
+const SHARE_DEFAULT_DAYS: u32 = 30;
+const SHARE_MAX_DAYS: u32 = 365;
+/// Listing filters a share or saved view may pin, besides `field.<name>`.
+const LISTING_FILTER_KEYS: [&str; 8] =
+    ["tag", "assigned_to", "creator", "q", "created_after", "created_before", "updated_after", "updated_before"];
+
+/// Serializes read-modify-write cycles of `SHARES_FILE`.
+static SHARES_LOCK: Mutex<()> = Mutex::new(());
+
+/// A read-only view of the tasks matching `filter`, reachable with `token`
+/// until `expires_at`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Share {
+    token: String,
+    /// Listing query parameters every shared response is limited to.
+    filter: BTreeMap<String, String>,
+    created_at: String,
+    created_by: String,
+    expires_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewShare {
+    filter: BTreeMap<String, String>,
+    /// 1 to `SHARE_MAX_DAYS`; `SHARE_DEFAULT_DAYS` when absent.
+    expires_in_days: Option<u32>,
+}
+
+fn read_shares(root: &Path) -> Vec<Share> {
+    fs::read_to_string(root.join(SHARES_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn write_shares(root: &Path, shares: &[Share]) -> io::Result<()> {
+    let staged = root.join(format!("{}.tmp", SHARES_FILE));
+    fs::write(&staged, serde_json::to_string_pretty(shares).unwrap_or_default())?;
+    fs::rename(&staged, root.join(SHARES_FILE))
+}
+
+fn share_expired(share: &Share, now: OffsetDateTime) -> bool {
+    parse_timestamp(&share.expires_at).is_none_or(|at| at <= now)
+}
+
+/// Bytes in a share token; it is sent as twice as many hex digits.
+const SHARE_TOKEN_BYTES: usize = 32;
+
+/// 256 unguessable bits from the OS random source, as hex.
+fn share_token() -> io::Result<String> {
+    let mut bytes = [0u8; SHARE_TOKEN_BYTES];
+    getrandom::fill(&mut bytes)?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// A short id that is unique, not secret, for subscriptions and webhook
+/// deliveries. Each `RandomState` is keyed from the OS random source.
+fn unique_id() -> String {
+    use std::hash::{BuildHasher, Hasher};
+    let nanos = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(nanos);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Validates a share request and stores it; expired shares are dropped on
+/// the way.
+fn create_share(root: &Path, request: NewShare, actor: &str, tz: UtcOffset) -> Result<Share, (u16, String)> {
+    let filter: BTreeMap<String, String> = request
+        .filter
+        .into_iter()
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(_, value)| !value.is_empty())
+        .collect();
+    if let Some(key) = filter.keys().find(|key| !LISTING_FILTER_KEYS.contains(&key.as_str()) && !key.starts_with("field.")) {
+        return Err((400, format!("Unknown filter: {} (allowed: {}, field.<name>)", key, LISTING_FILTER_KEYS.join(", "))));
+    }
+    if filter.is_empty() {
+        return Err((400, "A share needs at least one filter".to_string()));
+    }
+    parse_task_filter(&filter.clone().into_iter().collect(), tz).map_err(|msg| (400, msg))?;
+    let days = request.expires_in_days.unwrap_or(SHARE_DEFAULT_DAYS);
+    if !(1..=SHARE_MAX_DAYS).contains(&days) {
+        return Err((400, format!("expires_in_days must be between 1 and {}", SHARE_MAX_DAYS)));
+    }
+    let now = OffsetDateTime::now_utc();
+    let share = Share {
+        token: share_token().map_err(|err| (500, err.to_string()))?,
+        filter,
+        created_at: format_timestamp(now),
+        created_by: actor.to_string(),
+        expires_at: format_timestamp(now + time::Duration::days(days.into())),
+    };
+    let _guard = SHARES_LOCK.lock().unwrap();
+    let mut shares = read_shares(root);
+    shares.retain(|s| !share_expired(s, now));
+    shares.push(share.clone());
+    write_shares(root, &shares).map_err(|err| (500, err.to_string()))?;
+    Ok(share)
+}
+
+/// Removes a share; false when no share has that token.
+fn revoke_share(root: &Path, token: &str) -> io::Result<bool> {
+    let _guard = SHARES_LOCK.lock().unwrap();
+    let mut shares = read_shares(root);
+    let before = shares.len();
+    shares.retain(|s| s.token != token);
+    if shares.len() == before {
+        return Ok(false);
+    }
+    write_shares(root, &shares)?;
+    Ok(true)
+}
+
+/// The board as a share shows it: column layout and custom fields, without
+/// defaults, rotations or who changed the board.
+fn shared_board(cfg: &BoardConfig) -> BoardConfig {
+    BoardConfig {
+        columns: cfg
+            .columns
+            .iter()
+            .map(|c| BoardColumn {
+                id: c.id.clone(),
+                title: c.title.clone(),
+                wip_limit: c.wip_limit,
+                done: c.done,
+                sort: c.sort.clone(),
+                sort_dir: c.sort_dir.clone(),
+                locked: c.locked,
+                max_age: c.max_age.clone(),
+                ..Default::default()
+            })
+            .collect(),
+        status_mode: cfg.status_mode,
+        statuses: cfg.statuses.clone(),
+        fields: cfg.fields.clone(),
+        ..Default::default()
+    }
+}
+
+/// Drops the board's `private_fields` (task keys or custom field names) and
+/// the attachments (their URLs are outside the share) from a task object.
+fn redact_task(task: &mut serde_json::Value, private_fields: &[String]) {
+    if let Some(object) = task.as_object_mut() {
+        object.remove("attachments");
+        for key in private_fields {
+            object.remove(key);
+            if let Some(extra) = object.get_mut("extra").and_then(|extra| extra.as_object_mut()) {
+                extra.remove(key);
+            }
+        }
+    }
+}
+
+/// `GET /api/shared/{token}/...`: the read-only view of a share. Tasks
+/// outside the share's filter do not exist here, on any route, and totals
+/// only count shared tasks.
+fn handle_shared(
+    root: &Path,
+    cfg: &BoardConfig,
+    segments: &[String],
+    query: &HashMap<String, String>,
+    settings: &Settings,
+    theme: impl FnOnce() -> serde_json::Value,
+) -> Response<io::Cursor<Vec<u8>>> {
+    let error = |status: u16, msg: &str| respond_json(StatusCode(status), &serde_json::json!({ "error": msg }).to_string());
+    let [_, _, token, rest @ ..] = segments else {
+        return respond_error(404, "not_found", &[]);
+    };
+    let Some(share) = read_shares(root).into_iter().find(|s| &s.token == token) else {
+        return respond_json(StatusCode(404), &code_error("share_not_found", &[]).to_string());
+    };
+    if share_expired(&share, OffsetDateTime::now_utc()) {
+        return respond_json(StatusCode(410), &code_error("share_expired", &[]).to_string());
+    }
+    let share_filter = match parse_task_filter(&share.filter.clone().into_iter().collect(), settings.timezone) {
+        Ok(filter) => filter,
+        Err(msg) => return error(500, &msg),
+    };
+    let mut folders = match load_all_tasks(root, cfg, &settings.board, None) {
+        Ok(folders) => folders,
+        Err(err) => return error(500, &err.to_string()),
+    };
+    for tasks in folders.values_mut() {
+        retain_matching(tasks, &share_filter);
+    }
+    let board = shared_board(cfg);
+    let private = &cfg.private_fields;
+    match rest.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["tasks"] => match parse_listing_query(query, settings.timezone) {
+            Ok((filter, page)) => {
+                let mut payload = tasks_payload(root, &board, &settings.board, folders, &filter, &page, settings.max_tasks_per_response, None);
+                let listed = payload["folders"].as_object_mut().into_iter().flat_map(|folders| folders.values_mut());
+                for task in listed.flat_map(|tasks| tasks.as_array_mut().into_iter().flatten()) {
+                    redact_task(task, private);
+                }
+                respond_json(StatusCode(200), &payload.to_string())
+            }
+            Err(msg) => error(400, &msg),
+        },
+        ["tasks", id] => match folders.into_values().flatten().find(|task| task.id == *id) {
+            Some(task) => {
+                let mut task = serde_json::json!(task);
+                redact_task(&mut task, private);
+                respond_json(StatusCode(200), &task.to_string())
+            }
+            None => task_not_found(),
+        },
+        ["search"] => {
+            let q = query.get("q").map(|v| v.trim().to_string()).unwrap_or_default();
+            if q.is_empty() {
+                return error(400, "query parameter q is required");
+            }
+            let filter = match parse_task_filter(query, settings.timezone) {
+                Ok(filter) => TaskFilter { q: None, ..filter },
+                Err(msg) => return error(400, &msg),
+            };
+            let limit = query.get("limit").and_then(|v| v.parse::<usize>().ok()).filter(|n| *n > 0);
+            let results = search_tasks(&board, folders, &q, &filter, limit.unwrap_or(SEARCH_DEFAULT_LIMIT).min(SEARCH_MAX_LIMIT));
+            let mut hits = serde_json::json!(results.hits);
+            for hit in hits.as_array_mut().into_iter().flatten() {
+                redact_task(&mut hit["task"], private);
+                if hit["snippet"]["field"].as_str().is_some_and(|field| private.iter().any(|key| key == field)) {
+                    hit["snippet"] = serde_json::Value::Null;
+                }
+            }
+            let payload = serde_json::json!({ "query": q, "total": results.total, "results": hits });
+            respond_json(StatusCode(200), &payload.to_string())
+        }
+        ["board"] => respond_json(StatusCode(200), &serde_json::json!({ "board": board }).to_string()),
+        ["theme"] => respond_json(StatusCode(200), &serde_json::json!({ "theme": theme() }).to_string()),
+        _ => respond_error(404, "not_found", &[]),
+    }
+}
+/// Notification subscriptions, one JSON array in the board root.
+const SUBSCRIPTIONS_FILE: &str = ".kanban-subscriptions";
+/// Newest matching activity entries a user's Atom feed holds.
+const FEED_ENTRIES: usize = 50;
+
+/// Serializes read-modify-write cycles of `SUBSCRIPTIONS_FILE`.
+static SUBSCRIPTIONS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Which task activity `user` wants to hear about: `events` are activity
+/// actions and `columns` the columns a task lands in; empty lists match all.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Subscription {
+    id: String,
+    user: String,
+    columns: Vec<String>,
+    events: Vec<String>,
+    created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewSubscription {
+    user: String,
+    #[serde(default)]
+    columns: Vec<String>,
+    #[serde(default)]
+    events: Vec<String>,
+}
+
+/// Read on every match, so changes apply without a restart.
+fn read_subscriptions(root: &Path) -> Vec<Subscription> {
+    fs::read_to_string(root.join(SUBSCRIPTIONS_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn write_subscriptions(root: &Path, subscriptions: &[Subscription]) -> io::Result<()> {
+    let staged = root.join(format!("{}.tmp", SUBSCRIPTIONS_FILE));
+    fs::write(&staged, serde_json::to_string_pretty(subscriptions).unwrap_or_default())?;
+    fs::rename(&staged, root.join(SUBSCRIPTIONS_FILE))
+}
+
+/// The column an activity entry's task ended up in on this board: `to` for
+/// moves and transfers in, `column` for the rest; none for edits and for
+/// transfers to another board.
+fn activity_column(entry: &serde_json::Value) -> Option<&str> {
+    match entry["action"].as_str()? {
+        "task_moved" => entry["to"].as_str(),
+        "task_transferred" if entry.get("to_board").is_none() => entry["to"].as_str(),
+        "task_transferred" | "task_edited" | "tasks_handed_over" | "task_mentioned" => None,
+        _ => entry["column"].as_str(),
+    }
+}
+
+fn subscription_matches(subscription: &Subscription, entry: &serde_json::Value) -> bool {
+    let Some(action) = entry["action"].as_str() else {
+        return false;
+    };
+    (subscription.events.is_empty() || subscription.events.iter().any(|e| e == action))
+        && (subscription.columns.is_empty()
+            || activity_column(entry).is_some_and(|column| subscription.columns.iter().any(|c| c == column)))
+}
+
+/// The subscriptions an activity entry notifies. Hooks and feeds both go
+/// through here.
+fn matching_subscriptions(root: &Path, entry: &serde_json::Value) -> Vec<Subscription> {
+    read_subscriptions(root).into_iter().filter(|s| subscription_matches(s, entry)).collect()
+}
+
+/// Validates a subscription request against the board and stores it.
+fn create_subscription(root: &Path, cfg: &BoardConfig, request: NewSubscription) -> Result<Subscription, String> {
+    let user = request.user.trim();
+    if user.is_empty() {
+        return Err("user must not be empty".to_string());
+    }
+    if let Some(column) = request.columns.iter().find(|c| !cfg.columns.iter().any(|col| &col.id == *c)) {
+        return Err(format!("Unknown column: {}", column));
+    }
+    if let Some(event) = request.events.iter().find(|e| !SUBSCRIPTION_EVENTS.contains(&e.as_str())) {
+        return Err(format!("Unknown event: {} (allowed: {})", event, SUBSCRIPTION_EVENTS.join(", ")));
+    }
+    let subscription = Subscription {
+        id: unique_id(),
+        user: user.to_string(),
+        columns: request.columns,
+        events: request.events,
+        created_at: now_iso(),
+    };
+    let _guard = SUBSCRIPTIONS_LOCK.lock().unwrap();
+    let mut subscriptions = read_subscriptions(root);
+    subscriptions.push(subscription.clone());
+    write_subscriptions(root, &subscriptions).map_err(|err| err.to_string())?;
+    Ok(subscription)
+}
+
+/// Removes a subscription; false when none has that id.
+fn delete_subscription(root: &Path, id: &str) -> io::Result<bool> {
+    let _guard = SUBSCRIPTIONS_LOCK.lock().unwrap();
+    let mut subscriptions = read_subscriptions(root);
+    let before = subscriptions.len();
+    subscriptions.retain(|s| s.id != id);
+    if subscriptions.len() == before {
+        return Ok(false);
+    }
+    write_subscriptions(root, &subscriptions)?;
+    Ok(true)
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// One line describing an activity entry, with column titles.
+fn activity_summary(entry: &serde_json::Value, cfg: &BoardConfig) -> String {
+    let text = |key: &str| entry[key].as_str().unwrap_or("").to_string();
+    let title = |key: &str| {
+        let id = text(key);
+        cfg.columns.iter().find(|c| c.id == id).map(|c| c.title.clone()).unwrap_or(id)
+    };
+    match text("action").as_str() {
+        "task_created" => format!("{} created in {}", text("task"), title("column")),
+        "task_moved" => format!("{} moved from {} to {}", text("task"), title("from"), title("to")),
+        "task_edited" => format!("{} edited", text("task")),
+        "task_deleted" => format!("{} deleted from {}", text("task"), title("column")),
+        "task_transferred" => format!("{} transferred", text("task")),
+        "tasks_handed_over" => format!("Tasks handed over from {} to {}", text("from_user"), text("to_user")),
+        "task_mentioned" => format!("@{} mentioned in {}", text("user"), text("task")),
+        other => format!("{} {}", text("task"), other),
+    }
+}
+
+/// `GET /api/users/{name}/feed.atom`: the newest activity entries matching
+/// any of `user`'s subscriptions, newest first.
+fn user_feed(root: &Path, cfg: &BoardConfig, user: &str) -> String {
+    let subscriptions: Vec<Subscription> =
+        read_subscriptions(root).into_iter().filter(|s| s.user.eq_ignore_ascii_case(user)).collect();
+    let mut entries: Vec<serde_json::Value> = Vec::new();
+    if !subscriptions.is_empty() {
+        let log = fs::File::open(root.join(ACTIVITY_FILE)).ok().map(io::BufReader::new);
+        for line in log.into_iter().flat_map(|log| log.lines().map_while(Result::ok)) {
+            let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+                continue;
+            };
+            if subscriptions.iter().any(|s| subscription_matches(s, &entry)) {
+                entries.push(entry);
+            }
+        }
+    }
+    entries.reverse();
+    entries.truncate(FEED_ENTRIES);
+    let updated = entries.first().and_then(|e| e["at"].as_str()).map(str::to_string).unwrap_or_else(now_iso);
+    let board = board_name(root);
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    out.push_str(&format!("  <id>urn:kanban:{}:feed:{}</id>\n", xml_escape(&board), xml_escape(user)));
+    out.push_str(&format!("  <title>{} – {}</title>\n", xml_escape(&board_title(root)), xml_escape(user)));
+    out.push_str(&format!("  <updated>{}</updated>\n", xml_escape(&updated)));
+    for entry in &entries {
+        let at = entry["at"].as_str().unwrap_or("");
+        let action = entry["action"].as_str().unwrap_or("");
+        let task = entry["task"].as_str().unwrap_or("");
+        out.push_str("  <entry>\n");
+        out.push_str(&format!("    <id>urn:kanban:{}:{}:{}:{}</id>\n", xml_escape(&board), xml_escape(at), xml_escape(action), xml_escape(task)));
+        out.push_str(&format!("    <title>{}</title>\n", xml_escape(&activity_summary(entry, cfg))));
+        out.push_str(&format!("    <updated>{}</updated>\n", xml_escape(at)));
+        out.push_str(&format!("    <author><name>{}</name></author>\n", xml_escape(entry["actor"].as_str().unwrap_or(""))));
+        out.push_str(&format!("    <category term=\"{}\"/>\n", xml_escape(action)));
+        out.push_str("  </entry>\n");
+    }
+    out.push_str("</feed>\n");
+    out
+}
+
+/// Saved views, one JSON array; a root file, so never a column.
+const VIEWS_FILE: &str = ".kanban-views";
+
+/// Serializes read-modify-write cycles of `VIEWS_FILE`.
+static VIEWS_LOCK: Mutex<()> = Mutex::new(());
+
+/// A named, stored task listing: `filter` holds listing query parameters
+/// plus the `columns` to show (all when empty), `sort` a column sort key or
+/// `field.<name>`, with a leading `-` for descending.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedView {
+    name: String,
+    #[serde(default)]
+    owner: String,
+    filter: ViewFilter,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sort: Option<String>,
+    created_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ViewFilter {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    columns: Vec<String>,
+    #[serde(flatten)]
+    params: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewView {
+    name: String,
+    #[serde(default)]
+    owner: String,
+    #[serde(default)]
+    filter: ViewFilter,
+    sort: Option<String>,
+}
+
+fn read_views(root: &Path) -> Vec<SavedView> {
+    fs::read_to_string(root.join(VIEWS_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn write_views(root: &Path, views: &[SavedView]) -> io::Result<()> {
+    let staged = root.join(format!("{}.tmp", VIEWS_FILE));
+    fs::write(&staged, serde_json::to_string_pretty(views).unwrap_or_default())?;
+    fs::rename(&staged, root.join(VIEWS_FILE))
+}
+
+/// View names are used in URLs: lowercase letters, digits, `-` and `_`.
+fn is_view_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= 64 && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// The board's read-me, first name found wins. Root files, so reconcile
+/// and task scans never pick them up.
+const README_FILES: [&str; 2] = ["BOARD.md", ".kanban-readme.md"];
+
+fn readme_path(root: &Path) -> Option<PathBuf> {
+    README_FILES.iter().map(|name| root.join(name)).find(|path| path.is_file())
+}
+
+/// Replaces the read-me, keeping whichever of `README_FILES` exists; an
+/// empty text removes it.
+fn write_readme(root: &Path, text: &str) -> io::Result<()> {
+    let existing = readme_path(root);
+    if text.trim().is_empty() {
+        return existing.map_or(Ok(()), |path| {
+            expect_change(&path, None);
+            fs::remove_file(path)
+        });
+    }
+    let path = existing.unwrap_or_else(|| root.join(README_FILES[0]));
+    let staged = path.with_extension("md.tmp");
+    expect_change(&path, Some(text.as_bytes()));
+    fs::write(&staged, text)?;
+    fs::rename(&staged, path)
+}
+
+/// Renders the Markdown a read-me needs as an HTML fragment: headings,
+/// paragraphs, `-`/`*`/`1.` lists, fenced code, `code`, **bold**, *italic*
+/// and links. Everything else is escaped, so HTML in the file shows as
+/// text, and links other than http(s), mailto and relative ones lose
+/// their target.
+fn render_markdown_html(markdown: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph = Vec::new();
+    let mut list = None;
+    let mut in_code = false;
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            if in_code {
+                html.push_str("</code></pre>\n");
+            } else {
+                close_blocks(&mut html, &mut paragraph, &mut list);
+                html.push_str("<pre><code>");
+            }
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            html.push_str(&xml_escape(line));
+            html.push('\n');
+            continue;
+        }
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+        let item = match trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+            Some(text) => Some(("ul", text)),
+            None => trimmed[digits..].strip_prefix(". ").filter(|_| digits > 0).map(|text| ("ol", text)),
+        };
+        if trimmed.is_empty() {
+            close_blocks(&mut html, &mut paragraph, &mut list);
+        } else if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            close_blocks(&mut html, &mut paragraph, &mut list);
+            html.push_str(&format!("<h{0}>{1}</h{0}>\n", level, markdown_inline(trimmed[level..].trim())));
+        } else if let Some((tag, text)) = item {
+            if !paragraph.is_empty() || list != Some(tag) {
+                close_blocks(&mut html, &mut paragraph, &mut list);
+                html.push_str(&format!("<{}>\n", tag));
+                list = Some(tag);
+            }
+            html.push_str(&format!("<li>{}</li>\n", markdown_inline(text)));
+        } else {
+            if list.is_some() {
+                close_blocks(&mut html, &mut paragraph, &mut list);
+            }
+            paragraph.push(trimmed);
+        }
+    }
+    if in_code {
+        html.push_str("</code></pre>\n");
+    }
+    close_blocks(&mut html, &mut paragraph, &mut list);
+    html
+}
+
+/// Ends the open paragraph or list of `render_markdown_html`.
+fn close_blocks(html: &mut String, paragraph: &mut Vec<&str>, list: &mut Option<&str>) {
+    if !paragraph.is_empty() {
+        html.push_str(&format!("<p>{}</p>\n", markdown_inline(&paragraph.join(" "))));
+        paragraph.clear();
+    }
+    if let Some(tag) = list.take() {
+        html.push_str(&format!("</{}>\n", tag));
+    }
+}
+
+fn markdown_inline(text: &str) -> String {
+    wrap_pairs(&xml_escape(text), "`", ("<code>", "</code>"), str::to_string, markdown_links)
+}
+
+fn markdown_links(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('[') {
+        let link = rest[start + 1..].split_once("](").and_then(|(label, tail)| {
+            let (url, after) = tail.split_once(')')?;
+            (!label.contains(['[', ']'])).then_some((label, url, after))
+        });
+        let Some((label, url, after)) = link else {
+            out.push_str(&markdown_emphasis(&rest[..=start]));
+            rest = &rest[start + 1..];
+            continue;
+        };
+        out.push_str(&markdown_emphasis(&rest[..start]));
+        if is_safe_link(url) {
+            out.push_str(&format!("<a href=\"{}\">{}</a>", url, markdown_emphasis(label)));
+        } else {
+            out.push_str(&markdown_emphasis(label));
+        }
+        rest = after;
+    }
+    out.push_str(&markdown_emphasis(rest));
+    out
+}
+
+fn markdown_emphasis(text: &str) -> String {
+    let italic = |text: &str| wrap_pairs(text, "*", ("<em>", "</em>"), str::to_string, str::to_string);
+    wrap_pairs(text, "**", ("<strong>", "</strong>"), italic, italic)
+}
+
+/// Wraps the text between pairs of `marker` in `tags`, rendering it with
+/// `inner` and the rest with `outer`. An unpaired last marker stays as is.
+fn wrap_pairs(
+    text: &str,
+    marker: &str,
+    tags: (&str, &str),
+    inner: impl Fn(&str) -> String,
+    outer: impl Fn(&str) -> String,
+) -> String {
+    let parts: Vec<&str> = text.split(marker).collect();
+    let paired = parts.len() - (parts.len() + 1) % 2;
+    let mut out = String::new();
+    for (index, part) in parts.iter().enumerate() {
+        if index >= paired {
+            out.push_str(marker);
+            out.push_str(&outer(part));
+        } else if index % 2 == 1 {
+            out.push_str(&format!("{}{}{}", tags.0, inner(part), tags.1));
+        } else {
+            out.push_str(&outer(part));
+        }
+    }
+    out
+}
+
+/// Link targets a read-me may use: relative ones and http(s) or mailto.
+fn is_safe_link(url: &str) -> bool {
+    if url.is_empty() || url.contains(char::is_whitespace) {
+        return false;
+    }
+    match url.split_once(':') {
+        None => true,
+        Some((scheme, _)) if scheme.contains(['/', '?', '#']) => true,
+        Some((scheme, _)) => ["http", "https", "mailto"].contains(&scheme.to_ascii_lowercase().as_str()),
+    }
+}
+
+/// The board, filter and page a view lists, through the same engine as
+/// `GET /api/tasks`. The view's columns narrow the board; a column sort key
+/// becomes every column's sort policy. `limit`, `offset` and `fields` come
+/// from the request.
+fn view_listing(
+    cfg: &BoardConfig,
+    view: &SavedView,
+    request: &HashMap<String, String>,
+    tz: UtcOffset,
+) -> Result<(BoardConfig, TaskFilter, Page), String> {
+    let mut query: HashMap<String, String> = view.filter.params.clone().into_iter().collect();
+    for key in ["limit", "offset", "fields"] {
+        if let Some(value) = request.get(key) {
+            query.insert(key.to_string(), value.clone());
+        }
+    }
+    let mut board = cfg.clone();
+    if !view.filter.columns.is_empty() {
+        board.columns.retain(|c| view.filter.columns.contains(&c.id));
+    }
+    if let Some(sort) = &view.sort {
+        let (descending, key) = match sort.strip_prefix('-') {
+            Some(key) => (true, key),
+            None => (false, sort.as_str()),
+        };
+        if COLUMN_SORT_KEYS.contains(&key) {
+            for column in &mut board.columns {
+                column.sort = Some(key.to_string());
+                column.sort_dir = Some(if descending { "desc" } else { "asc" }.to_string());
+            }
+        } else {
+            query.insert("sort".to_string(), sort.clone());
+        }
+    }
+    let (filter, page) = parse_listing_query(&query, tz)?;
+    Ok((board, filter, page))
+}
+
+/// Validates a view against the board and stores it; 409 when the name is
+/// taken.
+fn create_view(root: &Path, cfg: &BoardConfig, request: NewView, tz: UtcOffset) -> Result<SavedView, (u16, String)> {
+    let name = request.name.trim();
+    if !is_view_name(name) {
+        return Err((400, format!("Invalid view name: {:?} (lowercase letters, digits, - and _)", name)));
+    }
+    let params: BTreeMap<String, String> = request
+        .filter
+        .params
+        .into_iter()
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(_, value)| !value.is_empty())
+        .collect();
+    if let Some(key) = params.keys().find(|key| !LISTING_FILTER_KEYS.contains(&key.as_str()) && !key.starts_with("field.")) {
+        return Err((400, format!("Unknown filter: {} (allowed: columns, {}, field.<name>)", key, LISTING_FILTER_KEYS.join(", "))));
+    }
+    if let Some(column) = request.filter.columns.iter().find(|c| !cfg.columns.iter().any(|col| &col.id == *c)) {
+        return Err((400, format!("Unknown column: {}", column)));
+    }
+    let view = SavedView {
+        name: name.to_string(),
+        owner: request.owner.trim().to_string(),
+        filter: ViewFilter { columns: request.filter.columns, params },
+        sort: request.sort.map(|s| s.trim().to_string()).filter(|s| !s.is_empty()),
+        created_at: now_iso(),
+    };
+    view_listing(cfg, &view, &HashMap::new(), tz).map_err(|msg| (400, msg))?;
+    let _guard = VIEWS_LOCK.lock().unwrap();
+    let mut views = read_views(root);
+    if views.iter().any(|v| v.name == view.name) {
+        return Err((409, format!("A view named {} already exists", view.name)));
+    }
+    views.push(view.clone());
+    write_views(root, &views).map_err(|err| (500, err.to_string()))?;
+    Ok(view)
+}
+
+/// Removes a view; false when none has that name.
+fn delete_view(root: &Path, name: &str) -> io::Result<bool> {
+    let _guard = VIEWS_LOCK.lock().unwrap();
+    let mut views = read_views(root);
+    let before = views.len();
+    views.retain(|v| v.name != name);
+    if views.len() == before {
+        return Ok(false);
+    }
+    write_views(root, &views)?;
+    Ok(true)
+}
+
+const BATCH_MAX_TASKS: usize = 100;
+const CSV_MAX_ROWS: usize = 1000;
+/// Task fields a CSV column can be mapped to.
+const CSV_FIELDS: [&str; 8] = ["title", "description", "tags", "assigned_to", "creator", "column", "due", "created_at"];
+
+#[derive(Debug, Default, Clone)]
+struct CsvImportOptions {
+    /// `field:column,...`, columns as 1-based indexes or, with `headers`,
+    /// header names.
+    map: String,
+    /// The first row holds column names and is not imported.
+    headers: bool,
+    /// Rows naming an unknown board column fail instead of using the default column.
+    strict: bool,
+    /// Rows may target `locked=true` columns: the CLI, or a request with the
+    /// admin token.
+    admin: bool,
+    /// Rows may break routing rules: a request whose `?force=true` counts
+    /// (see `rules_forced`).
+    force_rules: bool,
+    /// What a row whose id is taken does; see `settle_id`.
+    collision: Option<Collision>,
+    /// History versions kept for tasks replaced by `overwrite`.
+    history_limit: usize,
+}
+
+impl CsvImportOptions {
+    fn from_query(query: &HashMap<String, String>) -> Result<Self, String> {
+        Ok(CsvImportOptions {
+            map: query.get("map").cloned().unwrap_or_default(),
+            headers: query.get("headers").is_some_and(|v| v == "true"),
+            strict: query.get("strict").is_some_and(|v| v == "true"),
+            admin: false,
+            force_rules: false,
+            collision: query.get("collision").map(|v| Collision::parse(v)).transpose()?,
+            history_limit: 0,
+        })
+    }
+}
+
+/// Splits CSV text (RFC 4180: `,` separators, `"` quoting with `""`
+/// escapes, LF or CRLF line ends) into rows. Blank lines are dropped.
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|f| !f.is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(format!("unterminated quoted field in row {}", rows.len() + 1));
+    }
+    row.push(field);
+    if row.iter().any(|f| !f.is_empty()) {
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Resolves `--map`/`?map=` into task field -> 0-based CSV column.
+fn parse_csv_mapping(spec: &str, header: Option<&[String]>) -> Result<HashMap<&'static str, usize>, String> {
+    let mut mapping = HashMap::new();
+    for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (field, source) = pair
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid mapping {:?} (use field:column)", pair))?;
+        let field = CSV_FIELDS
+            .iter()
+            .copied()
+            .find(|f| *f == field.trim())
+            .ok_or_else(|| format!("Unknown field {:?} (allowed: {})", field.trim(), CSV_FIELDS.join(", ")))?;
+        let source = source.trim();
+        let index = match (source.parse::<usize>(), header) {
+            (Ok(n), _) if n >= 1 => n - 1,
+            (_, Some(names)) => names
+                .iter()
+                .position(|name| name.trim().eq_ignore_ascii_case(source))
+                .ok_or_else(|| format!("No CSV column named {:?}", source))?,
+            _ => return Err(format!("Invalid CSV column {:?} for {} (1-based index, or a name with headers=true)", source, field)),
+        };
+        mapping.insert(field, index);
+    }
+    if !mapping.contains_key("title") {
+        return Err("The mapping must include title".to_string());
+    }
+    Ok(mapping)
+}
+
+/// Turns one CSV row into a create request. `Ok(None)` means the row has no
+/// title and is skipped.
+fn csv_row_task(row: &[String], mapping: &HashMap<&'static str, usize>, cfg: &BoardConfig, strict: bool) -> Result<Option<NewTask>, String> {
+    // Every field but the description ends up in a one-line header, so line
+    // breaks inside a quoted cell become spaces.
+    let get = |field: &str| {
+        mapping
+            .get(field)
+            .and_then(|index| row.get(*index))
+            .map(|value| match field {
+                "description" => value.trim().to_string(),
+                _ => value.split(['\r', '\n']).map(str::trim).filter(|line| !line.is_empty()).collect::<Vec<_>>().join(" "),
+            })
+            .filter(|value| !value.is_empty())
+    };
+    let Some(title) = get("title") else {
+        return Ok(None);
+    };
+    let folder = match get("column") {
+        None => None,
+        Some(name) => match cfg.columns.iter().find(|c| c.id == name || c.title.eq_ignore_ascii_case(&name)) {
+            Some(column) => Some(column.id.clone()),
+            None if strict => return Err(format!("Unknown column: {}", name)),
+            None => None,
+        },
+    };
+    Ok(Some(NewTask {
+        title,
+        description: get("description"),
+        creator: get("creator"),
+        assigned_to: get("assigned_to"),
+        tags: get("tags").map(|tags| tags.split(';').map(str::trim).filter(|t| !t.is_empty()).map(str::to_string).collect()),
+        due: get("due"),
+        created_at: get("created_at"),
+        folder,
+        ..Default::default()
+    }))
+}
+
+#[derive(Debug, Serialize)]
+struct CsvImportReport {
+    created: usize,
+    skipped: usize,
+    failed: usize,
+    /// One entry per data row (1-based, not counting the header row).
+    results: Vec<serde_json::Value>,
+    #[serde(skip)]
+    tasks: Vec<Task>,
+    /// What column defaults did to each of `tasks`, in the same order.
+    #[serde(skip)]
+    applied: Vec<AppliedDefaults>,
+    /// The task each of `tasks` overwrote, if any.
+    #[serde(skip)]
+    replaced: Vec<Option<FileState>>,
+}
+
+/// `POST /api/import/csv` and `import`: creates one task per row. Rows
+/// without a title are skipped, invalid rows fail on their own; the rest
+/// are written.
+fn import_csv(root: &Path, cfg: &BoardConfig, opts: &BoardOptions, text: &str, options: &CsvImportOptions) -> Result<CsvImportReport, (u16, String)> {
+    let mut rows = parse_csv(text).map_err(|msg| (400, msg))?;
+    let header = if options.headers && !rows.is_empty() { Some(rows.remove(0)) } else { None };
+    if rows.len() > CSV_MAX_ROWS {
+        return Err((400, format!("CSV import is limited to {} rows", CSV_MAX_ROWS)));
+    }
+    if options.map.trim().is_empty() {
+        return Err((400, "A column mapping is required, e.g. map=title:1,tags:2".to_string()));
+    }
+    let mapping = parse_csv_mapping(&options.map, header.as_deref()).map_err(|msg| (400, msg))?;
+    let requests: Vec<Result<Option<NewTask>, String>> =
+        rows.iter().map(|row| csv_row_task(row, &mapping, cfg, options.strict)).collect();
+
+    let _guard = CREATE_LOCK.lock().unwrap();
+    let mut taken = existing_ids(root, cfg);
+    // Tasks from before the import that no earlier row has claimed yet.
+    let mut replaceable = taken.clone();
+    let adding = requests.iter().filter(|r| matches!(r, Ok(Some(_)))).count();
+    check_capacity(cfg, taken.len(), adding).map_err(|msg| (507, msg))?;
+    let mut report = CsvImportReport {
+        created: 0,
+        skipped: 0,
+        failed: 0,
+        results: Vec::new(),
+        tasks: Vec::new(),
+        applied: Vec::new(),
+        replaced: Vec::new(),
+    };
+    for (index, request) in requests.into_iter().enumerate() {
+        let row = index + 1;
+        let new_task = match request {
+            Ok(None) => {
+                report.skipped += 1;
+                report.results.push(serde_json::json!({ "row": row, "skipped": "empty title" }));
+                continue;
+            }
+            Ok(Some(new_task)) => new_task,
+            Err(msg) => {
+                report.failed += 1;
+                report.results.push(serde_json::json!({ "row": row, "error": msg }));
+                continue;
+            }
+        };
+        let settled = match settle_id(cfg, opts, &new_task, options.collision, &taken) {
+            Ok(settled) if settled.outcome == CollisionOutcome::Overwritten && !replaceable.contains(&settled.id) => {
+                Err(collision_error(&settled.id, settled.strategy))
+            }
+            Ok(settled) => Ok(settled),
+            Err((409, id)) => Err(collision_error(&id, Collision::Error)),
+            Err((_, msg)) => Err(serde_json::json!({ "error": msg })),
+        };
+        let settled = match settled {
+            Ok(settled) if settled.outcome == CollisionOutcome::Skipped => {
+                report.skipped += 1;
+                report.results.push(serde_json::json!({
+                    "row": row,
+                    "skipped": "id exists",
+                    "id": settled.id,
+                    "collision": settled.strategy,
+                    "outcome": settled.outcome,
+                }));
+                continue;
+            }
+            Ok(settled) => settled,
+            Err(detail) => {
+                report.failed += 1;
+                let mut result = serde_json::json!({ "row": row });
+                if let (Some(result), serde_json::Value::Object(detail)) = (result.as_object_mut(), detail) {
+                    result.extend(detail);
+                }
+                report.results.push(result);
+                continue;
+            }
+        };
+        let mut free = taken.clone();
+        free.remove(&settled.id);
+        let new_task = NewTask { id: Some(settled.id.clone()), ..new_task };
+        let written = build_new_task(root, cfg, opts, new_task, &free).and_then(|(mut task, applied)| {
+            if let Some(column) = locked_column(cfg, &[&task.folder], options.admin) {
+                return Err(locked_column_error(column));
+            }
+            if let Some(rule) = broken_rule(cfg, &task.folder, &task, options.force_rules) {
+                return Err(message_in(DEFAULT_LANG, "rule_broken", &[("rule", &rule.spec())]));
+            }
+            let path = checked_task_path(root, &task.folder, &task.id)?;
+            let replaced = match settled.outcome {
+                CollisionOutcome::Overwritten => Some(
+                    replace_existing(root, cfg, opts, &task.id, &task.folder, options.admin, options.history_limit)
+                        .map_err(|(_, msg)| msg)?,
+                ),
+                _ => None,
+            };
+            if let Err(err) = write_task(&path, &mut task) {
+                replaced.iter().for_each(|replaced| restore_replaced(root, replaced));
+                return Err(err.to_string());
+            }
+            Ok((task, applied, replaced))
+        });
+        match written {
+            Ok((task, applied, replaced)) => {
+                report.created += 1;
+                taken.insert(task.id.clone());
+                replaceable.remove(&task.id);
+                report.results.push(serde_json::json!({
+                    "row": row,
+                    "id": task.id,
+                    "column": task.folder,
+                    "collision": settled.strategy,
+                    "outcome": settled.outcome,
+                }));
+                report.tasks.push(task);
+                report.applied.push(applied);
+                report.replaced.push(replaced);
+            }
+            Err(msg) => {
+                report.failed += 1;
+                report.results.push(serde_json::json!({ "row": row, "error": msg }));
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// The `import` subcommand; returns the exit code.
+fn import_csv_file(root: &Path, file: &Path, opts: &BoardOptions, options: &CsvImportOptions) -> i32 {
+    let text = if file == Path::new("-") {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).map(|_| text)
+    } else {
+        fs::read_to_string(file)
+    };
+    let result = text
+        .map_err(|err| format!("{}: {}", file.display(), err))
+        .and_then(|text| {
+            let cfg = read_config(root).map_err(|err| err.to_string())?;
+            import_csv(root, &cfg, opts, &text, &CsvImportOptions { admin: true, ..options.clone() }).map_err(|(_, msg)| msg)
+        });
+    match result {
+        Ok(report) => {
+            for ((task, applied), replaced) in report.tasks.iter().zip(&report.applied).zip(&report.replaced) {
+                record_task_activity(root, &Hooks::default(), "cli", "task_created", &task.id, created_activity(task, applied, replaced.as_ref()));
+                record_new_mentions(root, &Hooks::default(), "cli", task, &[]);
+            }
+            for result in report.results.iter().filter(|r| r.get("error").is_some()) {
+                eprintln!("row {}: {}", result["row"], result["error"].as_str().unwrap_or_default());
+            }
+            println!("Created {} task(s), skipped {}, failed {}", report.created, report.skipped, report.failed);
+            if report.failed > 0 { 1 } else { 0 }
+        }
+        Err(msg) => {
+            eprintln!("{}", msg);
+            1
+        }
+    }
+}
+
+/// Either a bare array of tasks or `{"tasks": [...], "atomic": true}`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum BatchRequest {
+    Tasks(Vec<NewTask>),
+    Options {
+        tasks: Vec<NewTask>,
+        #[serde(default)]
+        atomic: bool,
+    },
+}
+
+/// A batch item that passed validation.
+enum BatchItem {
+    Ready(Box<Task>, AppliedDefaults, SettledId),
+    /// Its id is taken and its strategy is `skip`.
+    Skipped(SettledId),
+}
+
+/// The result fields of an item whose id clash could not be resolved.
+fn collision_error(id: &str, strategy: Collision) -> serde_json::Value {
+    let msg = match strategy {
+        Collision::Overwrite => format!("Task {} was already written earlier in this request", id),
+        _ => message_in(DEFAULT_LANG, "id_conflict", &[("id", id)]),
+    };
+    serde_json::json!({ "error": msg, "id": id, "collision": strategy, "outcome": "conflict" })
+}
+
+/// `POST /api/tasks/batch`: validates every item (stricter than a single
+/// create: titles are required and statuses must name a column) before
+/// writing anything. Atomic batches are all-or-nothing; otherwise each item
+/// reports its own task or error, in input order.
+#[allow(clippy::too_many_arguments)]
+fn handle_batch_create(
+    root: &Path,
+    cfg: &BoardConfig,
+    opts: &BoardOptions,
+    body: &str,
+    journal: &Journal,
+    update_state: &Arc<UpdateState>,
+    hooks: &Hooks,
+    actor: &str,
+    admin: bool,
+    force_rules: bool,
+    collision: Option<Collision>,
+    history_limit: usize,
+    strict_api: bool,
+) -> Response<io::Cursor<Vec<u8>>> {
+    let value: serde_json::Value = match serde_json::from_str(body) {
+        Ok(value) => value,
+        Err(err) => return respond_json(StatusCode(400), &serde_json::json!({ "error": err.to_string() }).to_string()),
+    };
+    let mut accepted = accepted_fields::<NewTask>().to_vec();
+    let (items, prefix) = match &value {
+        serde_json::Value::Object(object) => {
+            accepted.extend(["tasks", "atomic"]);
+            (object.get("tasks").and_then(|tasks| tasks.as_array()), "tasks")
+        }
+        other => (other.as_array(), ""),
+    };
+    let mut ignored = unknown_fields(&value, &["tasks", "atomic"], "");
+    for (index, item) in items.into_iter().flatten().enumerate() {
+        ignored.extend(unknown_fields(item, accepted_fields::<NewTask>(), &format!("{}[{}].", prefix, index)));
+    }
+    if let Err(error) = check_unknown_fields(&ignored, &accepted, strict_api) {
+        return respond_json(StatusCode(400), &error.to_string());
+    }
+    let (items, atomic) = match serde_json::from_value::<BatchRequest>(value) {
+        Ok(BatchRequest::Tasks(tasks)) => (tasks, false),
+        Ok(BatchRequest::Options { tasks, atomic }) => (tasks, atomic),
+        Err(err) => return respond_json(StatusCode(400), &serde_json::json!({ "error": err.to_string() }).to_string()),
+    };
+    if items.is_empty() {
+        return respond_json(StatusCode(400), &serde_json::json!({ "error": "Batch is empty" }).to_string());
+    }
+    if items.len() > BATCH_MAX_TASKS {
+        return respond_json(
+            StatusCode(400),
+            &serde_json::json!({ "error": format!("Batch is limited to {} tasks", BATCH_MAX_TASKS) }).to_string(),
+        );
+    }
+
+    let _guard = CREATE_LOCK.lock().unwrap();
+    let mut taken = existing_ids(root, cfg);
+    // Tasks from before the batch that no earlier item has claimed yet.
+    let mut replaceable = taken.clone();
+    let current = taken.len();
+    let mut prepared: Vec<Result<BatchItem, serde_json::Value>> = items
+        .into_iter()
+        .map(|item| {
+            let error = |msg: String| serde_json::json!({ "error": msg });
+            if item.title.trim().is_empty() {
+                return Err(error("Title is required".to_string()));
+            }
+            let column_field = match cfg.status_mode {
+                StatusMode::Column => item.folder.as_deref().or(item.status.as_deref()),
+                StatusMode::Free => item.folder.as_deref(),
+            };
+            if let Some(column) = column_field {
+                if !cfg.columns.iter().any(|c| c.id == column) {
+                    return Err(error(format!("Unknown column: {}", column)));
+                }
+            }
+            let settled = match settle_id(cfg, opts, &item, collision, &taken) {
+                Ok(settled) => settled,
+                Err((409, id)) => return Err(collision_error(&id, Collision::Error)),
+                Err((_, msg)) => return Err(error(msg)),
+            };
+            match settled.outcome {
+                CollisionOutcome::Skipped => return Ok(BatchItem::Skipped(settled)),
+                CollisionOutcome::Overwritten if !replaceable.contains(&settled.id) => {
+                    return Err(collision_error(&settled.id, settled.strategy));
+                }
+                _ => {}
+            }
+            let mut free = taken.clone();
+            free.remove(&settled.id);
+            let (task, applied) = build_new_task(root, cfg, opts, NewTask { id: Some(settled.id.clone()), ..item }, &free).map_err(error)?;
+            if let Some(column) = locked_column(cfg, &[&task.folder], admin) {
+                return Err(error(locked_column_error(column)));
+            }
+            if let Some(rule) = broken_rule(cfg, &task.folder, &task, force_rules) {
+                return Err(error(message_in(DEFAULT_LANG, "rule_broken", &[("rule", &rule.spec())])));
+            }
+            taken.insert(task.id.clone());
+            replaceable.remove(&task.id);
+            Ok(BatchItem::Ready(Box::new(task), applied, settled))
+        })
+        .collect();
+    let item_error = |index: usize, detail: &serde_json::Value| {
+        let mut result = serde_json::json!({ "index": index });
+        if let (Some(result), Some(detail)) = (result.as_object_mut(), detail.as_object()) {
+            result.extend(detail.clone());
+        }
+        result
+    };
+
+    if atomic && prepared.iter().any(|p| p.is_err()) {
+        let errors: Vec<serde_json::Value> = prepared
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| p.as_ref().err().map(|detail| item_error(i, detail)))
+            .collect();
+        return respond_json(
+            StatusCode(400),
+            &serde_json::json!({ "error": "Batch validation failed", "errors": errors }).to_string(),
+        );
+    }
+
+    let adding = prepared
+        .iter()
+        .filter(|p| matches!(p, Ok(BatchItem::Ready(_, _, settled)) if settled.outcome != CollisionOutcome::Overwritten))
+        .count();
+    if let Err(msg) = check_capacity(cfg, current, adding) {
+        return board_full(&msg);
+    }
+
+    let mut written: Vec<(&Task, &AppliedDefaults, Option<FileState>)> = Vec::new();
+    let rollback = |written: &[(&Task, &AppliedDefaults, Option<FileState>)]| {
+        for (done, _, replaced) in written {
+            let path = task_path(root, &done.folder, &done.id);
+            expect_change(&path, None);
+            let _ = fs::remove_file(path);
+            replaced.iter().for_each(|replaced| restore_replaced(root, replaced));
+        }
+    };
+    let mut results = Vec::new();
+    let mut skipped = 0;
+    for (index, item) in prepared.iter_mut().enumerate() {
+        let (task, applied, settled) = match item {
+            Ok(BatchItem::Ready(task, applied, settled)) => (&mut **task, &*applied, &*settled),
+            Ok(BatchItem::Skipped(settled)) => {
+                skipped += 1;
+                results.push(serde_json::json!({
+                    "index": index,
+                    "id": settled.id,
+                    "collision": settled.strategy,
+                    "outcome": settled.outcome,
+                }));
+                continue;
+            }
+            Err(detail) => {
+                results.push(item_error(index, detail));
+                continue;
+            }
+        };
+        let path = match checked_task_path(root, &task.folder, &task.id) {
+            Ok(path) => path,
+            Err(msg) if atomic => {
+                rollback(&written);
+                return respond_json(
+                    StatusCode(400),
+                    &serde_json::json!({ "error": msg, "index": index }).to_string(),
+                );
+            }
+            Err(msg) => {
+                results.push(item_error(index, &serde_json::json!({ "error": msg })));
+                continue;
+            }
+        };
+        let replaced = match settled.outcome {
+            CollisionOutcome::Overwritten => match replace_existing(root, cfg, opts, &task.id, &task.folder, admin, history_limit) {
+                Ok(replaced) => Some(replaced),
+                Err((status, msg)) if atomic => {
+                    rollback(&written);
+                    return respond_json(StatusCode(status), &serde_json::json!({ "error": msg, "index": index }).to_string());
+                }
+                Err((_, msg)) => {
+                    results.push(item_error(index, &serde_json::json!({ "error": msg })));
+                    continue;
+                }
+            },
+            _ => None,
+        };
+        match write_task(&path, task) {
+            Ok(_) => {
+                results.push(serde_json::json!({
+                    "index": index,
+                    "task": task_with_defaults(task, applied),
+                    "collision": settled.strategy,
+                    "outcome": settled.outcome,
+                }));
+                written.push((task, applied, replaced));
+            }
+            Err(err) => {
+                replaced.iter().for_each(|replaced| restore_replaced(root, replaced));
+                if atomic {
+                    rollback(&written);
+                    return respond_json(
+                        StatusCode(500),
+                        &serde_json::json!({
+                            "error": format!("Writing {} failed, batch rolled back: {}", task.id, err),
+                            "index": index,
+                        })
+                        .to_string(),
+                    );
+                }
+                results.push(item_error(index, &serde_json::json!({ "error": err.to_string() })));
+            }
+        }
+    }
+
+    for (task, applied, replaced) in &written {
+        record_task_activity(root, hooks, actor, "task_created", &task.id, created_activity(task, applied, replaced.as_ref()));
+        record_new_mentions(root, hooks, actor, task, &[]);
+        journal_record(journal, "create", replaced.clone(), Some(file_state(&task.folder, &task.id, render_task(task))));
+    }
+    if !written.is_empty() {
+        notify_update(update_state);
+    }
+    let failed = results.len() - written.len() - skipped;
+    let status = if failed == 0 { 201 } else { 207 };
+    let payload = serde_json::json!({ "created": written.len(), "skipped": skipped, "failed": failed, "results": results });
+    respond_json(StatusCode(status), &with_ignored_fields(payload, &ignored).to_string())
+}
+
+fn task_path(root: &Path, folder: &str, id: &str) -> PathBuf {
+    root.join(folder).join(format!("{}.md", id))
+}
+
+fn find_task_path(root: &Path, id: &str, config: &BoardConfig, opts: &BoardOptions) -> Option<(PathBuf, String)> {
+    for column in &config.columns {
+        let path = task_path(root, &column.id, id);
+        if exact_file_exists(&path, case_insensitive_fs()) && link_skip_reason(&path, opts.follow_symlinks).is_none() {
+            return Some((path, column.id.clone()));
+        }
+    }
+    None
+}
+
+/// Collects `@name` mentions from markdown text, lowercased and deduplicated in
+/// order of appearance. Fenced code blocks, inline code spans and e-mail
+/// addresses (an `@` directly after a word character) are ignored.
+fn extract_mentions(text: &str) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let chars: Vec<char> = line.chars().collect();
+        let mut in_code = false;
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if c == '`' {
+                in_code = !in_code;
+                i += 1;
+                continue;
+            }
+            let boundary = i == 0 || !is_mention_char(chars[i - 1]);
+            if c == '@' && !in_code && boundary {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && is_mention_char(chars[end]) {
+                    end += 1;
+                }
+                let name: String = chars[start..end].iter().collect();
+                let name = name.trim_end_matches(['.', '-']).to_lowercase();
+                if !name.is_empty() && !out.contains(&name) {
+                    out.push(name);
+                }
+                i = end.max(start);
+                continue;
+            }
+            i += 1;
+        }
+    }
+    out
+}
+
+fn is_mention_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/// What a column's defaults changed on a task entering it.
+#[derive(Debug, Default, Serialize)]
+struct AppliedDefaults {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    assigned_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+    /// Set when `assigned_to` came from the column's `auto_assign=` rotation.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    auto_assigned: bool,
+}
+
+/// Adds the column's default tags (without duplicates) and fills the assignee
+/// only when the task has none.
+/// Columns flagged `done=true` count as finished; boards without any flag
+/// fall back to a column with the id `done`.
+fn is_done_column(config: &BoardConfig, id: &str) -> bool {
+    if config.columns.iter().any(|c| c.done) {
+        config.columns.iter().any(|c| c.id == id && c.done)
+    } else {
+        id == "done"
+    }
+}
+
+/// Sets `completed_at` when a task lands in a done column and clears it when
+/// it leaves one.
+fn update_completion(config: &BoardConfig, task: &mut Task) {
+    if is_done_column(config, &task.folder) {
+        if task.completed_at.is_none() {
+            task.completed_at = Some(Timestamp::now());
+        }
+    } else {
+        task.completed_at = None;
+    }
+}
+
+/// Validates a due date (`YYYY-MM-DD`); an empty value clears it.
+fn normalize_due(raw: &str) -> Result<Option<String>, String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(None);
+    }
+    match parse_plain_date(raw) {
+        Some(date) => Ok(Some(date.to_string())),
+        None => Err(format!("invalid due date {:?}, expected YYYY-MM-DD", raw)),
+    }
+}
+
+/// Validates an estimate; 0 clears it.
+fn normalize_estimate(value: f64) -> Result<Option<f64>, String> {
+    if !value.is_finite() || value < 0.0 {
+        return Err(format!("invalid estimate {}, expected a number >= 0", value));
+    }
+    Ok(Some(value).filter(|v| *v > 0.0))
+}
+
+fn apply_column_defaults(column: &BoardColumn, task: &mut Task) -> AppliedDefaults {
+    let mut applied = AppliedDefaults::default();
+    for tag in &column.default_tags {
+        if !task.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            task.tags.push(tag.clone());
+            applied.tags.push(tag.clone());
+        }
+    }
+    if task.assigned_to.trim().is_empty() {
+        if let Some(assignee) = column.default_assignee.as_ref().filter(|a| !a.is_empty()) {
+            task.assigned_to = assignee.clone();
+            applied.assigned_to = Some(assignee.clone());
+        }
+    }
+    applied
+}
+
+/// Serializes read-modify-write cycles of `ROTATION_FILE`.
+static ROTATION_LOCK: Mutex<()> = Mutex::new(());
+
+/// Assigns a task that has no assignee to the next person in the column's
+/// `auto_assign=` list and advances the column's position in
+/// `ROTATION_FILE`. Tasks that already have an assignee leave the rotation
+/// untouched.
+fn apply_auto_assign(root: &Path, column: &BoardColumn, task: &mut Task, applied: &mut AppliedDefaults) {
+    if column.auto_assign.is_empty() || !task.assigned_to.trim().is_empty() {
+        return;
+    }
+    let _guard = ROTATION_LOCK.lock().unwrap();
+    let path = root.join(ROTATION_FILE);
+    let mut cursors: BTreeMap<String, usize> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default();
+    let cursor = cursors.get(&column.id).copied().unwrap_or(0) % column.auto_assign.len();
+    let name = column.auto_assign[cursor].clone();
+    cursors.insert(column.id.clone(), (cursor + 1) % column.auto_assign.len());
+    if let Err(err) = fs::write(&path, serde_json::json!(cursors).to_string()) {
+        eprintln!("Failed to save auto_assign rotation for {}: {}", column.id, err);
+    }
+    task.assigned_to = name.clone();
+    applied.assigned_to = Some(name);
+    applied.auto_assigned = true;
+}
+
+/// Serializes read-modify-write cycles of `ORDER_FILE`.
+static ORDER_LOCK: Mutex<()> = Mutex::new(());
+
+/// Task ids per column id, in manual order. Columns nobody ordered have no
+/// entry.
+fn read_order(root: &Path) -> BTreeMap<String, Vec<String>> {
+    fs::read_to_string(root.join(ORDER_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn write_order(root: &Path, order: &BTreeMap<String, Vec<String>>) -> io::Result<()> {
+    let staged = root.join(format!("{}.tmp", ORDER_FILE));
+    fs::write(&staged, serde_json::to_string_pretty(order).unwrap_or_default())?;
+    fs::rename(&staged, root.join(ORDER_FILE))
+}
+
+/// Updates the manual order after `id` moved from `from` to `to` (its file
+/// is already in `to`): takes it out of `from`'s order and inserts it into
+/// `to`'s at `position`, or appends it when `to` has an order but no position
+/// was given. Ids whose file is no longer in the column are dropped from both
+/// lists. Returns the task's old and new index, None where it was not
+/// ordered.
+fn move_in_order(root: &Path, id: &str, from: &str, to: &str, position: Option<usize>) -> io::Result<(Option<usize>, Option<usize>)> {
+    let _guard = ORDER_LOCK.lock().unwrap();
+    let mut order = read_order(root);
+    if position.is_none() && !order.contains_key(from) && !order.contains_key(to) {
+        return Ok((None, None));
+    }
+    let present = |column: &str, other: &str| root.join(column).join(format!("{}.md", other)).is_file();
+    let mut old = None;
+    if let Some(list) = order.get_mut(from) {
+        list.retain(|other| other == id || present(from, other));
+        old = list.iter().position(|other| other == id);
+        list.retain(|other| other != id);
+    }
+    let mut new = None;
+    if position.is_some() || order.contains_key(to) {
+        let list = order.entry(to.to_string()).or_default();
+        list.retain(|other| other != id && present(to, other));
+        let index = position.unwrap_or(list.len()).min(list.len());
+        list.insert(index, id.to_string());
+        new = Some(index);
+    }
+    order.retain(|_, list| !list.is_empty());
+    write_order(root, &order)?;
+    Ok((old, new))
+}
+
+/// Puts the tasks listed in a column's manual order first, in that order;
+/// the rest keep their current order after them.
+fn apply_manual_order(tasks: &mut [Task], order: &[String]) {
+    if order.is_empty() {
+        return;
+    }
+    let rank: HashMap<&str, usize> = order.iter().enumerate().map(|(i, id)| (id.as_str(), i)).collect();
+    tasks.sort_by_key(|task| rank.get(task.id.as_str()).copied().unwrap_or(usize::MAX));
+}
+
+/// Ordered ids, per column, whose task file is not in that column, plus
+/// columns in `ORDER_FILE` that are not on the board.
+fn stale_order_entries(root: &Path, config: &BoardConfig) -> Vec<String> {
+    let mut stale = Vec::new();
+    for (column, ids) in read_order(root) {
+        if !config.columns.iter().any(|c| c.id == column) {
+            stale.push(format!("{} orders column {} which is not on the board", ORDER_FILE, column));
+            continue;
+        }
+        let missing: Vec<&str> =
+            ids.iter().filter(|id| !root.join(&column).join(format!("{}.md", id)).is_file()).map(String::as_str).collect();
+        if !missing.is_empty() {
+            stale.push(format!("{} lists {} for {} but they are not in {}/", ORDER_FILE, missing.join(", "), column, column));
+        }
+    }
+    stale
+}
+
+/// Activity fields for a task that entered a column, plus `auto_assigned`
+/// when the column's rotation picked its assignee.
+fn entry_activity(mut extra: serde_json::Value, applied: &AppliedDefaults) -> serde_json::Value {
+    if applied.auto_assigned {
+        extra["auto_assigned"] = serde_json::json!(applied.assigned_to);
+    }
+    extra
+}
+
+/// Reads `.kanban-templates/<id>.md`, a task file whose tags and
+/// description seed new tasks.
+fn read_task_template(root: &Path, id: &str, mode: StatusMode, opts: &BoardOptions) -> io::Result<Task> {
+    parse_task(&root.join(TASK_TEMPLATES_DIR).join(format!("{}.md", id)), "", mode, &opts.base_path)
+}
+
+/// Gives a task created without a description its column's template: the
+/// template's description and its tags (without duplicates). A missing
+/// template is logged and the task is created as is.
+fn apply_column_template(root: &Path, cfg: &BoardConfig, opts: &BoardOptions, column: &BoardColumn, task: &mut Task, applied: &mut AppliedDefaults) {
+    let Some(id) = column.template.as_deref() else {
+        return;
+    };
+    if !task.description.trim().is_empty() {
+        return;
+    }
+    let template = match read_task_template(root, id, cfg.status_mode, opts) {
+        Ok(template) => template,
+        Err(err) => {
+            eprintln!("Template {} of column {} not applied: {}", id, column.id, err);
+            return;
+        }
+    };
+    task.description = template.description;
+    task.mentions = extract_mentions(&task.description);
+    for tag in template.tags {
+        if !task.tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            task.tags.push(tag.clone());
+            applied.tags.push(tag);
+        }
+    }
+    applied.template = Some(id.to_string());
+}
+
+fn task_with_defaults(task: &Task, applied: &AppliedDefaults) -> serde_json::Value {
+    let mut payload = serde_json::json!(task);
+    if !applied.tags.is_empty() || applied.assigned_to.is_some() || applied.template.is_some() {
+        payload["applied_defaults"] = serde_json::json!(applied);
+    }
+    payload
+}
+
+/// Reads a task file. Without a `status:` header the status defaults to the
+/// folder in column mode and stays empty in free mode.
+/// A field value as written in the task header.
+fn field_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// The JSON value of a declared field's header text: numbers for `number`
+/// fields, the declared spelling for `enum` fields. `None` when the text is
+/// not valid for the type.
+fn typed_field_value(field: &FieldDef, text: &str) -> Option<serde_json::Value> {
+    match field.kind {
+        FieldKind::Text => Some(serde_json::Value::String(text.to_string())),
+        FieldKind::Number => match text.parse::<i64>() {
+            Ok(n) => Some(n.into()),
+            Err(_) => text.parse::<f64>().ok().and_then(serde_json::Number::from_f64).map(serde_json::Value::Number),
+        },
+        FieldKind::Enum => field
+            .values
+            .iter()
+            .find(|v| v.eq_ignore_ascii_case(text))
+            .map(|v| serde_json::Value::String(v.clone())),
+    }
+}
+
+/// Gives the task's declared fields their typed values. Values that do not
+/// fit the declaration, and undeclared headers, stay strings.
+fn type_fields(fields: &[FieldDef], task: &mut Task) {
+    for field in fields {
+        if let Some(value) = task.extra.get_mut(&field.name) {
+            if let Some(typed) = value.as_str().and_then(|text| typed_field_value(field, text)) {
+                *value = typed;
+            }
+        }
+    }
+}
+
+/// Checks custom field values sent by a client against the board's
+/// declarations and converts them to what gets stored. `null` and `""` come
+/// back as `null`, meaning "remove the field".
+fn normalize_extra(
+    fields: &[FieldDef],
+    extra: serde_json::Map<String, serde_json::Value>,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let mut out = serde_json::Map::new();
+    for (name, value) in extra {
+        if !is_field_name(&name) {
+            return Err(format!("Invalid field name: {} (use a-z, 0-9, '_' and '-', not a built-in header)", name));
+        }
+        let text = match &value {
+            serde_json::Value::Null => String::new(),
+            serde_json::Value::String(text) => text.trim().to_string(),
+            serde_json::Value::Number(_) | serde_json::Value::Bool(_) => value.to_string(),
+            _ => return Err(format!("Invalid {}: expected a string or number", name)),
+        };
+        if text.is_empty() {
+            out.insert(name, serde_json::Value::Null);
+            continue;
+        }
+        if text.contains('\n') {
+            return Err(format!("Invalid {}: values must be a single line", name));
+        }
+        let typed = match fields.iter().find(|f| f.name == name) {
+            None => serde_json::Value::String(text),
+            Some(field) => typed_field_value(field, &text).ok_or_else(|| match field.kind {
+                FieldKind::Enum => format!("Invalid {}: {} (allowed: {})", name, text, field.values.join(", ")),
+                _ => format!("Invalid {}: {} (expected a number)", name, text),
+            })?,
+        };
+        out.insert(name, typed);
+    }
+    Ok(out)
+}
+
+/// Applies values from `normalize_extra`: `null` removes a field, anything
+/// else sets it (new fields go last).
+fn merge_extra(task: &mut Task, extra: serde_json::Map<String, serde_json::Value>) {
+    for (name, value) in extra {
+        if value.is_null() {
+            task.extra.shift_remove(&name);
+        } else {
+            task.extra.insert(name, value);
+        }
+    }
+}
+
+/// Reads a task file; its attachment URLs start with `base_path`.
+fn parse_task(path: &Path, folder: &str, mode: StatusMode, base_path: &str) -> io::Result<Task> {
+    let content = fs::read_to_string(path)?;
+    let mut header: HashMap<String, String> = HashMap::new();
+    let mut extra_keys: Vec<String> = Vec::new();
+    let mut description_lines: Vec<String> = Vec::new();
+    let mut in_body = false;
+    for line in content.lines() {
+        if !in_body {
+            if line.trim().is_empty() {
+                in_body = true;
+                continue;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                let key = key.trim().to_string();
+                if !TASK_HEADERS.contains(&key.as_str()) && !extra_keys.contains(&key) {
+                    extra_keys.push(key.clone());
+                }
+                header.insert(key, value.trim().to_string());
+            }
+        } else {
+            description_lines.push(line.to_string());
+        }
+    }
+    let file_stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("task");
+    let description = description_lines.join("\n");
+    let mentions = extract_mentions(&description);
+    let tags = header
+        .get("tags")
+        .map(|v| {
+            v.split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let attachments = header
+        .get("attachments")
+        .map(|v| parse_attachments(file_stem, v, base_path))
+        .unwrap_or_default();
+    Ok(Task {
+        id: file_stem.to_string(),
+        title: header.get("title").cloned().unwrap_or_default(),
+        description,
+        creator: header.get("creator").cloned().unwrap_or_default(),
+        assigned_to: header.get("assigned_to").cloned().unwrap_or_default(),
+        created_at: Timestamp::read(header.get("created_at").map_or("", |v| v.as_str())),
+        updated_at: Timestamp::read(header.get("updated_at").map_or("", |v| v.as_str())),
+        status: header.get("status").cloned().unwrap_or_else(|| match mode {
+            StatusMode::Column => folder.to_string(),
+            StatusMode::Free => String::new(),
+        }),
+        tags,
+        folder: folder.to_string(),
+        color: header.get("color").cloned().filter(|v| !v.is_empty()),
+        icon: header.get("icon").cloned().filter(|v| !v.is_empty()),
+        mentions,
+        due: header.get("due").cloned().filter(|v| !v.is_empty()),
+        estimate: header
+            .get("estimate")
+            .and_then(|v| v.parse::<f64>().ok())
+            .and_then(|v| normalize_estimate(v).ok().flatten()),
+        completed_at: header.get("completed_at").filter(|v| !v.is_empty()).map(|v| Timestamp::read(v)),
+        column_since: header.get("column_since").filter(|v| !v.is_empty()).map(|v| Timestamp::read(v)),
+        days_in_column: None,
+        locked_by: None,
+        locked_until: None,
+        attachments,
+        extra: extra_keys
+            .into_iter()
+            .map(|key| {
+                let value = header.remove(&key).unwrap_or_default();
+                (key, serde_json::Value::String(value))
+            })
+            .collect(),
+        file: task_file(path, folder),
+    })
+}
+
+/// Writes the task file and refreshes `task.file` to describe it.
+fn write_task(path: &Path, task: &mut Task) -> io::Result<()> {
+    let rendered = render_task(task);
+    expect_change(path, Some(rendered.as_bytes()));
+    fs::write(path, rendered)?;
+    task.file = task_file(path, &task.folder);
+    Ok(())
+}
+
+fn render_task(task: &Task) -> String {
+    let tags = if task.tags.is_empty() {
+        String::new()
+    } else {
+        task.tags.join(", ")
+    };
+    let mut optional = String::new();
+    if let Some(color) = &task.color {
+        optional.push_str(&format!("color: {}\n", color));
+    }
+    if let Some(icon) = &task.icon {
+        optional.push_str(&format!("icon: {}\n", icon));
+    }
+    if let Some(due) = &task.due {
+        optional.push_str(&format!("due: {}\n", due));
+    }
+    if let Some(estimate) = task.estimate {
+        optional.push_str(&format!("estimate: {}\n", estimate));
+    }
+    if let Some(completed_at) = &task.completed_at {
+        optional.push_str(&format!("completed_at: {}\n", completed_at));
+    }
+    if let Some(since) = &task.column_since {
+        optional.push_str(&format!("column_since: {}\n", since));
+    }
+    if !task.attachments.is_empty() {
+        let list: Vec<String> = task
+            .attachments
+            .iter()
+            .map(|a| format!("{}:{}", a.name, a.size))
+            .collect();
+        optional.push_str(&format!("attachments: {}\n", list.join(", ")));
+    }
+    for (key, value) in &task.extra {
+        optional.push_str(&format!("{}: {}\n", key, field_text(value)));
+    }
+    format!(
+        "creator: {}\nassigned_to: {}\ncreated_at: {}\nupdated_at: {}\nstatus: {}\ntags: {}\n{}title: {}\n\n{}\n",
+        task.creator,
+        task.assigned_to,
+        task.created_at,
+        task.updated_at,
+        task.status,
+        tags,
+        optional,
+        task.title,
+        task.description
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct VersionInfo {
+    timestamp: String,
+    saved_at: String,
+    size: u64,
+}
+
+fn history_dir(root: &Path, id: &str) -> PathBuf {
+    root.join(HISTORY_DIR).join(id)
+}
+
+/// Version names are compact UTC stamps (`20260130T120000123Z`) so they sort
+/// lexically and are valid file names on every platform.
+fn history_stamp(at: OffsetDateTime) -> String {
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}{:03}Z",
+        at.year(),
+        at.month() as u8,
+        at.day(),
+        at.hour(),
+        at.minute(),
+        at.second(),
+        at.millisecond()
+    )
+}
+
+fn stamp_to_rfc3339(stamp: &str) -> Option<String> {
+    let digits: String = stamp.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 17 {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}T{}:{}:{}.{}Z",
+        &digits[0..4],
+        &digits[4..6],
+        &digits[6..8],
+        &digits[8..10],
+        &digits[10..12],
+        &digits[12..14],
+        &digits[14..17]
+    ))
+}
+
+fn is_valid_stamp(stamp: &str) -> bool {
+    !stamp.is_empty()
+        && stamp
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == 'T' || c == 'Z' || c == '-')
+}
+
+/// Copies the current task file into `.history/<id>/` before it is overwritten
+/// or removed, then prunes the oldest versions beyond `limit`. A `limit` of 0
+/// disables history entirely.
+fn save_history(root: &Path, id: &str, path: &Path, limit: usize) -> io::Result<()> {
+    if limit == 0 || !path.exists() {
+        return Ok(());
+    }
+    let dir = history_dir(root, id);
+    fs::create_dir_all(&dir)?;
+    let stamp = history_stamp(OffsetDateTime::now_utc());
+    let mut target = dir.join(format!("{}.md", stamp));
+    let mut n = 2;
+    while target.exists() {
+        target = dir.join(format!("{}-{}.md", stamp, n));
+        n += 1;
+    }
+    fs::copy(path, &target)?;
+    let versions = list_versions(root, id)?;
+    for version in versions.iter().skip(limit) {
+        let _ = fs::remove_file(dir.join(format!("{}.md", version.timestamp)));
+    }
+    Ok(())
+}
+
+fn record_history(root: &Path, id: &str, path: &Path, limit: usize) {
+    if let Err(err) = save_history(root, id, path, limit) {
+        eprintln!("Failed to save history for {}: {}", id, err);
+    }
+}
+
+/// Moves a task's history along with a rename so versions stay reachable.
+/// Renames recorded in `ALIASES_FILE`; a later line for the same old id wins.
+fn read_aliases(root: &Path) -> HashMap<String, String> {
+    fs::read_to_string(root.join(ALIASES_FILE))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once("->"))
+        .map(|(old, new)| (old.trim().to_string(), new.trim().to_string()))
+        .filter(|(old, new)| is_valid_id(old) && is_valid_id(new))
+        .collect()
+}
+
+fn record_alias(root: &Path, old_id: &str, new_id: &str) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(root.join(ALIASES_FILE))?;
+    writeln!(file, "{} -> {}", old_id, new_id)
+}
+
+/// The task an old id was renamed to, following chains of renames. None
+/// when `id` is a task itself (a live task always wins over an alias) or
+/// when no alias leads to an existing task.
+fn resolve_alias(root: &Path, id: &str, config: &BoardConfig, opts: &BoardOptions) -> Option<String> {
+    if !is_valid_id(id) || find_task_path(root, id, config, opts).is_some() {
+        return None;
+    }
+    let aliases = read_aliases(root);
+    let mut current = id;
+    for _ in 0..aliases.len() {
+        current = aliases.get(current)?;
+        if find_task_path(root, current, config, opts).is_some() {
+            return Some(current.to_string());
+        }
+    }
+    None
+}
+
+/// Rewrites `ALIASES_FILE` without aliases that no longer lead to a task,
+/// pointing the rest straight at their current id. Runs after deletes.
+fn prune_aliases(root: &Path, config: &BoardConfig, opts: &BoardOptions) -> io::Result<()> {
+    let path = root.join(ALIASES_FILE);
+    if !path.exists() {
+        return Ok(());
+    }
+    let kept: BTreeMap<String, String> = read_aliases(root)
+        .into_keys()
+        .filter_map(|old| resolve_alias(root, &old, config, opts).map(|new| (old, new)))
+        .collect();
+    if kept.is_empty() {
+        return fs::remove_file(path);
+    }
+    let contents: String = kept.iter().map(|(old, new)| format!("{} -> {}\n", old, new)).collect();
+    fs::write(path, contents)
+}
+
+fn rename_history(root: &Path, old_id: &str, new_id: &str) {
+    if old_id != new_id {
+        move_dir(&history_dir(root, old_id), &history_dir(root, new_id));
+    }
+}
+
+/// Carries a task's history, attachments and alias over to its new id
+/// after a title change. Safe to repeat, so `recover` can finish a rename
+/// that a crash cut short.
+fn finish_rename(root: &Path, old_id: &str, new_id: &str) -> io::Result<()> {
+    rename_history(root, old_id, new_id);
+    move_dir(&attachments_dir(root, old_id), &attachments_dir(root, new_id));
+    if read_aliases(root).get(old_id).map(String::as_str) != Some(new_id) {
+        record_alias(root, old_id, new_id)?;
+    }
+    Ok(())
+}
+
+/// Renames `from` to `to`, merging file by file when `to` already exists.
+fn move_dir(from: &Path, to: &Path) {
+    if !from.exists() {
+        return;
+    }
+    if to.exists() {
+        if let Ok(entries) = fs::read_dir(from) {
+            for entry in entries.flatten() {
+                let _ = fs::rename(entry.path(), to.join(entry.file_name()));
+            }
+        }
+        let _ = fs::remove_dir(from);
+    } else {
+        let _ = fs::rename(from, to);
+    }
+}
+
+/// Lists saved versions of a task, newest first.
+fn list_versions(root: &Path, id: &str) -> io::Result<Vec<VersionInfo>> {
+    let dir = history_dir(root, id);
+    let mut out = Vec::new();
+    if !dir.exists() {
+        return Ok(out);
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("md") {
+            continue;
+        }
+        let Some(stamp) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        out.push(VersionInfo {
+            timestamp: stamp.to_string(),
+            saved_at: stamp_to_rfc3339(stamp).unwrap_or_default(),
+            size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+        });
+    }
+    out.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(out)
+}
+
+fn version_path(root: &Path, id: &str, stamp: &str) -> Option<PathBuf> {
+    if !is_valid_stamp(stamp) {
+        return None;
+    }
+    let path = history_dir(root, id).join(format!("{}.md", stamp));
+    if path.exists() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Sub-resources of `/api/tasks/{id}`, listed in `unknown_action` errors.
+const TASK_ACTIONS: [&str; 6] = ["attachments", "diff", "export", "lock", "move", "versions"];
+
+/// Methods accepted by a `/api/tasks/{id}/...` path (split on `/`, id
+/// first), or `None` when no such sub-resource exists.
+fn task_route_methods(parts: &[&str]) -> Option<&'static [&'static str]> {
+    match parts {
+        [_] => Some(&["GET", "PUT", "DELETE"]),
+        [_, "move"] => Some(&["POST"]),
+        [_, "lock"] => Some(&["POST", "DELETE"]),
+        [_, "export"] | [_, "diff"] | [_, "versions"] | [_, "versions", _] => Some(&["GET"]),
+        [_, "versions", _, "restore"] => Some(&["POST"]),
+        [_, "attachments"] => Some(&["GET", "POST"]),
+        [_, "attachments", _] => Some(&["GET", "DELETE"]),
+        _ => None,
+    }
+}
+
+/// Errors whose legacy `error` already is the code keep it there and carry
+/// the catalog text in `message`.
+fn code_error(code: &str, params: &[(&str, &str)]) -> serde_json::Value {
+    debug_assert!(ERROR_CODES.contains(&code), "{} is not in ERROR_CODES", code);
+    serde_json::json!({ "error": code, "code": code, "message": message(code, params) })
+}
+
+fn task_not_found() -> Response<std::io::Cursor<Vec<u8>>> {
+    respond_json(StatusCode(404), &code_error("task_not_found", &[]).to_string())
+}
+
+/// Resolves a `/api/tasks/{id}/...` request before it is dispatched, in a
+/// fixed order: an invalid id is 400, an unknown sub-resource 404
+/// `unknown_action`, a wrong method 405 with `Allow`, and a task that does
+/// not exist 404 `task_not_found`. History reads also work for deleted
+/// tasks that still have versions.
+fn check_task_route(
+    root: &Path,
+    cfg: &BoardConfig,
+    opts: &BoardOptions,
+    parts: &[&str],
+    method: &Method,
+) -> Result<(), Response<std::io::Cursor<Vec<u8>>>> {
+    let id = parts[0];
+    if !is_valid_id(id) {
+        return Err(respond_error(400, "invalid_id", &[("id", &format!("{:?}", id))]));
+    }
+    let Some(allowed) = task_route_methods(parts) else {
+        let mut payload = code_error("unknown_action", &[]);
+        payload["actions"] = serde_json::json!(TASK_ACTIONS);
+        return Err(respond_json(StatusCode(404), &payload.to_string()));
+    };
+    if !allowed.contains(&method.as_str()) {
+        let allow = allowed.join(", ");
+        let mut payload = code_error("method_not_allowed", &[("allowed", &allow)]);
+        payload["allowed"] = serde_json::json!(allowed);
+        return Err(respond_json(StatusCode(405), &payload.to_string())
+        .with_header(Header::from_bytes("Allow", allow.as_bytes()).unwrap()));
+    }
+    let history_read = *method == Method::Get && matches!(parts[1..].first(), Some(&"versions") | Some(&"diff"));
+    if find_task_path(root, id, cfg, opts).is_none() && !(history_read && history_dir(root, id).exists()) {
+        return Err(task_not_found());
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_versions(
+    root: &Path,
+    cfg: &BoardConfig,
+    opts: &BoardOptions,
+    id: &str,
+    rest: &[&str],
+    method: &Method,
+    history_limit: usize,
+    journal: &Journal,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let current = find_task_path(root, id, cfg, opts);
+    match (method, rest) {
+        (Method::Get, []) => {
+            if current.is_none() && !history_dir(root, id).exists() {
+                return task_not_found();
+            }
+            match list_versions(root, id) {
+                Ok(versions) => respond_json(
+                    StatusCode(200),
+                    &serde_json::json!({ "id": id, "versions": versions }).to_string(),
+                ),
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            }
+        }
+        (Method::Get, [stamp]) => match version_path(root, id, stamp) {
+            Some(path) => match fs::read_to_string(&path) {
+                Ok(content) => Response::from_string(content).with_header(
+                    Header::from_bytes("Content-Type", "text/markdown; charset=utf-8").unwrap(),
+                ),
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            },
+            None => respond_json(StatusCode(404), &serde_json::json!({"error": "version not found"}).to_string()),
+        },
+        (Method::Post, [stamp, "restore"]) => {
+            let Some((path, folder)) = current else {
+                return task_not_found();
+            };
+            let Some(source) = version_path(root, id, stamp) else {
+                return respond_json(StatusCode(404), &serde_json::json!({"error": "version not found"}).to_string());
+            };
+            let content = match fs::read_to_string(&source) {
+                Ok(content) => content,
+                Err(err) => {
+                    return respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string())
+                }
+            };
+            let before = fs::read_to_string(&path).unwrap_or_default();
+            record_history(root, id, &path, history_limit);
+            let (before, after) = (file_state(&folder, id, before), file_state(&folder, id, content.clone()));
+            if let Err(err) = journaled(journal, "restore", Some(before), Some(after), || fs::write(&path, &content)) {
+                return respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string());
+            }
+            match parse_task(&path, &folder, cfg.status_mode, &opts.base_path) {
+                Ok(task) => respond_json(StatusCode(200), &serde_json::json!(task).to_string()),
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            }
+        }
+        _ => respond_error(404, "not_found", &[]),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DiffOp {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Myers' O(ND) line diff. Returns the edit script from `a` to `b` as indexes
+/// into the respective slices.
+fn diff_lines(a: &[&str], b: &[&str]) -> Vec<DiffOp> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    'search: for d in 0..=max as isize {
+        trace.push(v.clone());
+        let mut k = -d;
+        while k <= d {
+            let idx = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+            k += 2;
+        }
+    }
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let idx = (k + offset) as usize;
+        let prev_k = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[(prev_k + offset) as usize];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            ops.push(DiffOp::Equal((x - 1) as usize, (y - 1) as usize));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                ops.push(DiffOp::Insert((y - 1) as usize));
+            } else {
+                ops.push(DiffOp::Delete((x - 1) as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.reverse();
+    ops
+}
+
+/// Renders a unified diff (3 lines of context) of two texts; empty when equal.
+fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    const CONTEXT: usize = 3;
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&a, &b);
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    let mut i = 0;
+    while i < changes.len() {
+        let start = changes[i].saturating_sub(CONTEXT);
+        let mut end = changes[i];
+        while i + 1 < changes.len() && changes[i + 1] <= end + 2 * CONTEXT + 1 {
+            i += 1;
+            end = changes[i];
+        }
+        let end = (end + CONTEXT + 1).min(ops.len());
+        let hunk = &ops[start..end];
+        let old_start = hunk
+            .iter()
+            .find_map(|op| match op {
+                DiffOp::Equal(x, _) | DiffOp::Delete(x) => Some(*x),
+                DiffOp::Insert(_) => None,
+            })
+            .unwrap_or_else(|| old_position(&ops[..start]));
+        let new_start = hunk
+            .iter()
+            .find_map(|op| match op {
+                DiffOp::Equal(_, y) | DiffOp::Insert(y) => Some(*y),
+                DiffOp::Delete(_) => None,
+            })
+            .unwrap_or_else(|| new_position(&ops[..start]));
+        let old_len = hunk.iter().filter(|op| !matches!(op, DiffOp::Insert(_))).count();
+        let new_len = hunk.iter().filter(|op| !matches!(op, DiffOp::Delete(_))).count();
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            if old_len == 0 { old_start } else { old_start + 1 },
+            old_len,
+            if new_len == 0 { new_start } else { new_start + 1 },
+            new_len
+        ));
+        for op in hunk {
+            match op {
+                DiffOp::Equal(x, _) => out.push_str(&format!(" {}\n", a[*x])),
+                DiffOp::Delete(x) => out.push_str(&format!("-{}\n", a[*x])),
+                DiffOp::Insert(y) => out.push_str(&format!("+{}\n", b[*y])),
+            }
+        }
+        i += 1;
+    }
+    out
+}
+
+fn old_position(ops: &[DiffOp]) -> usize {
+    ops.iter().filter(|op| !matches!(op, DiffOp::Insert(_))).count()
+}
+
+fn new_position(ops: &[DiffOp]) -> usize {
+    ops.iter().filter(|op| !matches!(op, DiffOp::Delete(_))).count()
+}
+
+#[derive(Debug, Serialize)]
+struct FieldChange {
+    field: &'static str,
+    old: String,
+    new: String,
+}
+
+/// Compares two parsed versions of a task field by field. File-derived values
+/// (id, folder) are ignored so versions saved before a rename still compare.
+fn diff_tasks(old: &Task, new: &Task, old_label: &str, new_label: &str) -> serde_json::Value {
+    let estimate = |task: &Task| task.estimate.map(|e| e.to_string()).unwrap_or_default();
+    let pairs: [(&'static str, String, String); 9] = [
+        ("title", old.title.clone(), new.title.clone()),
+        ("creator", old.creator.clone(), new.creator.clone()),
+        ("assigned_to", old.assigned_to.clone(), new.assigned_to.clone()),
+        ("created_at", old.created_at.to_string(), new.created_at.to_string()),
+        ("updated_at", old.updated_at.to_string(), new.updated_at.to_string()),
+        ("status", old.status.clone(), new.status.clone()),
+        ("color", old.color.clone().unwrap_or_default(), new.color.clone().unwrap_or_default()),
+        ("icon", old.icon.clone().unwrap_or_default(), new.icon.clone().unwrap_or_default()),
+        ("estimate", estimate(old), estimate(new)),
+    ];
+    let fields: Vec<FieldChange> = pairs
+        .into_iter()
+        .filter(|(_, a, b)| a != b)
+        .map(|(field, old, new)| FieldChange { field, old, new })
+        .collect();
+    let added: Vec<&String> = new.tags.iter().filter(|t| !old.tags.contains(t)).collect();
+    let removed: Vec<&String> = old.tags.iter().filter(|t| !new.tags.contains(t)).collect();
+    let unified = unified_diff(&old.description, &new.description, old_label, new_label);
+    serde_json::json!({
+        "fields": fields,
+        "tags": { "added": added, "removed": removed },
+        "description": { "changed": !unified.is_empty(), "unified": unified },
+    })
+}
+
+fn handle_export(root: &Path, cfg: &BoardConfig, opts: &BoardOptions, id: &str, query: &HashMap<String, String>) -> Response<std::io::Cursor<Vec<u8>>> {
+    let Some((path, folder)) = find_task_path(root, id, cfg, opts) else {
+        return task_not_found();
+    };
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) => return respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+    };
+    match query.get("format").map(String::as_str) {
+        None | Some("md") | Some("markdown") => Response::from_string(content)
+            .with_header(Header::from_bytes("Content-Type", "text/markdown; charset=utf-8").unwrap())
+            .with_header(
+                Header::from_bytes("Content-Disposition", content_disposition(&format!("{}.md", id)).as_bytes()).unwrap(),
+            ),
+        Some("json") => match parse_task(&path, &folder, cfg.status_mode, &opts.base_path) {
+            Ok(task) => {
+                let mut payload = serde_json::json!(task);
+                payload["content"] = serde_json::Value::String(content);
+                respond_json(StatusCode(200), &payload.to_string())
+            }
+            Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+        },
+        Some(other) => respond_json(
+            StatusCode(400),
+            &serde_json::json!({"error": format!("unsupported format {:?}", other)}).to_string(),
+        ),
+    }
+}
+
+/// The theme's headline, else the board's folder name.
+fn board_title(root: &Path) -> String {
+    read_theme(root)
+        .ok()
+        .and_then(|theme| theme.headline)
+        .unwrap_or_else(|| board_name(root))
+}
+
+/// A column's heading in the board document, with `_No tasks._` when `empty`.
+fn column_markdown(column: &BoardColumn, empty: bool) -> String {
+    let mut out = format!("\n## {}\n\n", column.title);
+    match column.wip_limit {
+        Some(0) => out.push_str(&message("report.column_frozen", &[("column", &column.id)])),
+        Some(limit) => out.push_str(&message("report.column_wip", &[("column", &column.id), ("limit", &limit.to_string())])),
+        None => out.push_str(&message("report.column", &[("column", &column.id)])),
+    }
+    out.push('\n');
+    if empty {
+        out.push_str(&format!("\n{}\n", message("report.no_tasks", &[])));
+    }
+    out
+}
+
+/// One task of the board document, between its `<!-- task -->` markers.
+fn task_markdown(column: &str, task: &Task) -> String {
+    let rendered = render_task(task);
+    let (header, description) = rendered.split_once("\n\n").unwrap_or((rendered.as_str(), ""));
+    let mut out = format!("\n### {}\n\n<!-- task: {}/{}.md -->\n\n", task.title, column, task.id);
+    for line in header.lines() {
+        if let Some((key, value)) = line.split_once(": ").filter(|(_, value)| !value.is_empty()) {
+            out.push_str(&format!("{}\n: {}\n", key, value));
+        }
+    }
+    let description = description.trim_end();
+    if !description.is_empty() {
+        out.push_str(&format!("\n{}\n", description));
+    }
+    out.push_str("\n<!-- /task -->\n");
+    out
+}
+
+/// The task files of each column, in board order and id order within a
+/// column, for writing the board document one task at a time. Files that
+/// cannot be parsed are left out here, as listings leave them out, so that
+/// writing only fails on files that changed in between.
+fn board_markdown_files(
+    root: &Path,
+    config: &BoardConfig,
+    opts: &BoardOptions,
+    cache: Option<&TaskCache>,
+) -> io::Result<Vec<(BoardColumn, Vec<PathBuf>)>> {
+    let ignored = read_ignore_file(root);
+    let mut columns = Vec::new();
+    for column in &config.columns {
+        let dir = root.join(&column.id);
+        let mut files = Vec::new();
+        if !is_internal_dir(&column.id, &ignored) && dir.exists() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if task_file_id(&path, opts.follow_symlinks).is_some() && board_markdown_task(&path, &column.id, config.status_mode, cache, opts).is_ok() {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort_by(|a, b| task_file_id(a, opts.follow_symlinks).cmp(&task_file_id(b, opts.follow_symlinks)));
+        columns.push((column.clone(), files));
+    }
+    Ok(columns)
+}
+
+fn board_markdown_task(path: &Path, folder: &str, mode: StatusMode, cache: Option<&TaskCache>, opts: &BoardOptions) -> io::Result<Task> {
+    match cache {
+        Some(cache) => cached_parse_task(cache, path, folder, mode, opts, fs::metadata(path).ok()),
+        None => parse_task(path, folder, mode, &opts.base_path),
+    }
+}
+
+/// Writes the whole board as one Markdown document: an H1 with the board
+/// title, an H2 per column in board order and an H3 per task. Each task's
+/// non-empty header fields form a definition list followed by its
+/// description, and the task sits between `<!-- task: folder/id.md -->` and
+/// `<!-- /task -->` markers so the document can be split back into files.
+/// Tasks are read one at a time from the files `board_markdown_files`
+/// listed, so memory stays flat however large the board is.
+fn write_board_markdown(
+    out: &mut impl Write,
+    title: &str,
+    mode: StatusMode,
+    opts: &BoardOptions,
+    columns: &[(BoardColumn, Vec<PathBuf>)],
+    cache: Option<&TaskCache>,
+) -> io::Result<()> {
+    out.write_all(format!("# {}\n", title).as_bytes())?;
+    for (column, files) in columns {
+        out.write_all(column_markdown(column, files.is_empty()).as_bytes())?;
+        for path in files {
+            let task = board_markdown_task(path, &column.id, mode, cache, opts)?;
+            out.write_all(task_markdown(&column.id, &task).as_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// HTTP/1.1 chunked transfer encoding over a raw connection. Unlike
+/// tiny_http's encoder it only writes the closing chunk in `finish`, so a
+/// body that fails half way ends without one and the client sees it cut off.
+struct ChunkedWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> ChunkedWriter<W> {
+    fn finish(mut self) -> io::Result<()> {
+        self.inner.write_all(b"0\r\n\r\n")?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Write for ChunkedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !buf.is_empty() {
+            self.inner.write_all(format!("{:x}\r\n", buf.len()).as_bytes())?;
+            self.inner.write_all(buf)?;
+            self.inner.write_all(b"\r\n")?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// `GET /api/export.md`: the board document, streamed as it is written. The
+/// column folders are listed before anything is sent, so a board that cannot
+/// be read still gets a JSON error. A task that fails after the 200 went out
+/// is logged and the connection closes without the final chunk.
+fn stream_board_markdown(
+    request: tiny_http::Request,
+    root: &Path,
+    cache: &TaskCache,
+    opts: &BoardOptions,
+    shape: ApiShape,
+    url: &str,
+) {
+    let base_path = opts.base_path.as_str();
+    let listed = refresh_config(root).and_then(|cfg| {
+        let files = board_markdown_files(root, &cfg, opts, Some(cache)).map_err(|err| err.to_string())?;
+        Ok((cfg, files))
+    });
+    let (cfg, files) = match listed {
+        Ok(listed) => listed,
+        Err(msg) => {
+            eprintln!("GET /api/export.md -> 500: {}", msg);
+            let response = respond_json(StatusCode(500), &serde_json::json!({ "error": msg }).to_string());
+            let _ = request.respond(shape_response(response, shape, url, base_path));
+            return;
+        }
+    };
+    let mut head = String::from("HTTP/1.1 200 OK\r\nContent-Type: text/markdown; charset=utf-8\r\n");
+    head.push_str(&format!("Content-Disposition: {}\r\n", content_disposition(&format!("{}.md", board_name(root)))));
+    if shape == ApiShape::Legacy {
+        for header in legacy_headers(url, base_path) {
+            head.push_str(&format!("{}: {}\r\n", header.field, header.value));
+        }
+    }
+    head.push_str("Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n");
+    let mut writer = request.into_writer();
+    if writer.write_all(head.as_bytes()).is_err() {
+        return;
+    }
+    let mut body = io::BufWriter::new(ChunkedWriter { inner: writer });
+    let written = write_board_markdown(&mut body, &board_title(root), cfg.status_mode, opts, &files, Some(cache));
+    let finished = written.and_then(|_| body.into_inner().map_err(|err| err.into_error())?.finish());
+    if let Err(err) = finished {
+        eprintln!("GET /api/export.md stopped after the response started: {}", err);
+    }
+}
+
+/// Which tasks sat in which column at one point in time. Titles are kept so
+/// removed tasks can still be named; descriptions are not.
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    name: String,
+    taken_at: String,
+    columns: BTreeMap<String, Vec<SnapshotTask>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotTask {
+    id: String,
+    title: String,
+    updated_at: String,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct NewSnapshot {
+    name: Option<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+struct SnapshotEntry {
+    id: String,
+    title: String,
+    column: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+struct SnapshotMove {
+    id: String,
+    title: String,
+    from: String,
+    to: String,
+}
+
+/// `GET /api/snapshots/{name}/diff`: the live board compared with a
+/// snapshot. Moved tasks are not listed again under `edited`.
+#[derive(Debug, Serialize)]
+struct SnapshotDiff {
+    name: String,
+    taken_at: String,
+    added: Vec<SnapshotEntry>,
+    removed: Vec<SnapshotEntry>,
+    moved: Vec<SnapshotMove>,
+    edited: Vec<SnapshotEntry>,
+}
+
+fn snapshot_path(root: &Path, name: &str) -> PathBuf {
+    root.join(SNAPSHOTS_DIR).join(format!("{}.json", name))
+}
+
+/// Records the board under `name`, replacing an older snapshot of that name,
+/// and prunes the oldest files beyond `SNAPSHOT_LIMIT`.
+fn take_snapshot(
+    root: &Path,
+    config: &BoardConfig,
+    folders: &HashMap<String, Vec<Task>>,
+    name: &str,
+) -> io::Result<Snapshot> {
+    let columns = config
+        .columns
+        .iter()
+        .map(|column| {
+            let mut tasks: Vec<SnapshotTask> = folders
+                .get(&column.id)
+                .map(|tasks| {
+                    tasks
+                        .iter()
+                        .map(|t| SnapshotTask { id: t.id.clone(), title: t.title.clone(), updated_at: t.updated_at.to_string() })
+                        .collect()
+                })
+                .unwrap_or_default();
+            tasks.sort_by(|a, b| a.id.cmp(&b.id));
+            (column.id.clone(), tasks)
+        })
+        .collect();
+    let snapshot = Snapshot { name: name.to_string(), taken_at: now_iso(), columns };
+    let path = snapshot_path(root, name);
+    fs::create_dir_all(root.join(SNAPSHOTS_DIR))?;
+    fs::write(&path, serde_json::to_string(&snapshot).map_err(io::Error::other)?)?;
+    prune_snapshots(root, SNAPSHOT_LIMIT);
+    Ok(snapshot)
+}
+
+fn prune_snapshots(root: &Path, limit: usize) {
+    let Ok(entries) = fs::read_dir(root.join(SNAPSHOTS_DIR)) else {
+        return;
+    };
+    let mut files: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .map(|path| (fs::metadata(&path).and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH), path))
+        .collect();
+    files.sort();
+    let excess = files.len().saturating_sub(limit);
+    for (_, path) in files.into_iter().take(excess) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Names and times of the stored snapshots, newest first.
+fn list_snapshots(root: &Path) -> Vec<serde_json::Value> {
+    let mut out: Vec<(String, String)> = fs::read_dir(root.join(SNAPSHOTS_DIR))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+                .filter_map(|contents| serde_json::from_str::<Snapshot>(&contents).ok())
+                .map(|snapshot| (snapshot.taken_at, snapshot.name))
+                .collect()
+        })
+        .unwrap_or_default();
+    out.sort_by(|a, b| b.cmp(a));
+    out.into_iter()
+        .map(|(taken_at, name)| serde_json::json!({ "name": name, "taken_at": taken_at }))
+        .collect()
+}
+
+fn read_snapshot(root: &Path, name: &str) -> io::Result<Snapshot> {
+    let contents = fs::read_to_string(snapshot_path(root, name))?;
+    serde_json::from_str(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn diff_snapshot(snapshot: &Snapshot, config: &BoardConfig, folders: &HashMap<String, Vec<Task>>) -> SnapshotDiff {
+    let before: HashMap<&str, (&str, &SnapshotTask)> = snapshot
+        .columns
+        .iter()
+        .flat_map(|(column, tasks)| tasks.iter().map(move |t| (t.id.as_str(), (column.as_str(), t))))
+        .collect();
+    let mut diff = SnapshotDiff {
+        name: snapshot.name.clone(),
+        taken_at: snapshot.taken_at.clone(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        moved: Vec::new(),
+        edited: Vec::new(),
+    };
+    let mut live = HashSet::new();
+    for column in &config.columns {
+        let mut tasks: Vec<&Task> = folders.get(&column.id).map(|tasks| tasks.iter().collect()).unwrap_or_default();
+        tasks.sort_by(|a, b| a.id.cmp(&b.id));
+        for task in tasks {
+            live.insert(task.id.as_str());
+            let entry = SnapshotEntry { id: task.id.clone(), title: task.title.clone(), column: column.id.clone() };
+            match before.get(task.id.as_str()) {
+                None => diff.added.push(entry),
+                Some((from, _)) if *from != column.id => diff.moved.push(SnapshotMove {
+                    id: entry.id,
+                    title: entry.title,
+                    from: from.to_string(),
+                    to: entry.column,
+                }),
+                Some((_, old)) if task.updated_at.at() > parse_timestamp(&old.updated_at) => diff.edited.push(entry),
+                Some(_) => {}
+            }
+        }
+    }
+    for (column, tasks) in &snapshot.columns {
+        for task in tasks.iter().filter(|t| !live.contains(t.id.as_str())) {
+            diff.removed.push(SnapshotEntry { id: task.id.clone(), title: task.title.clone(), column: column.clone() });
+        }
+    }
+    diff
+}
+
+/// A standup summary of `diff` with column titles, ready to paste.
+fn render_snapshot_markdown(diff: &SnapshotDiff, config: &BoardConfig) -> String {
+    let title = |id: &str| {
+        config
+            .columns
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| c.title.clone())
+            .unwrap_or_else(|| id.to_string())
+    };
+    let mut out = format!("# {}\n", message("report.since", &[("name", &diff.name), ("taken_at", &diff.taken_at)]));
+    let sections = [("report.added", &diff.added), ("report.edited", &diff.edited), ("report.removed", &diff.removed)];
+    if !diff.moved.is_empty() {
+        out.push_str(&format!("\n## {}\n\n", message("report.moved", &[])));
+        for task in &diff.moved {
+            out.push_str(&format!("- {}: {} → {}\n", task.title, title(&task.from), title(&task.to)));
+        }
+    }
+    for (heading, entries) in sections {
+        if !entries.is_empty() {
+            out.push_str(&format!("\n## {}\n\n", message(heading, &[])));
+            for task in entries {
+                out.push_str(&format!("- {} ({})\n", task.title, title(&task.column)));
+            }
+        }
+    }
+    if diff.added.is_empty() && diff.removed.is_empty() && diff.moved.is_empty() && diff.edited.is_empty() {
+        out.push_str(&format!("\n{}\n", message("report.no_changes", &[])));
+    }
+    out
+}
+
+/// `export`: writes the Markdown snapshot to `output`, or stdout.
+fn export_board(root: &Path, opts: &BoardOptions, output: Option<&Path>) -> io::Result<()> {
+    let config = read_config(root)?;
+    let files = board_markdown_files(root, &config, opts, None)?;
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(fs::File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut out = io::BufWriter::new(&mut out);
+    write_board_markdown(&mut out, &board_title(root), config.status_mode, opts, &files, None)?;
+    out.flush()
+}
+
+/// Dialect of the documents `GET /api/schema` serves.
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// A string property that must match `pattern`.
+fn string_matching(pattern: &str) -> serde_json::Value {
+    serde_json::json!({ "type": "string", "pattern": pattern })
+}
+
+/// An array of strings that pass `item`.
+fn string_list(item: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({ "type": "array", "items": item })
+}
+
+fn object_schema(properties: serde_json::Value, required: &[&str]) -> serde_json::Value {
+    serde_json::json!({ "type": "object", "properties": properties, "required": required })
+}
+
+/// `status` on create and update: in free mode one of the board's
+/// `statuses` (or empty) when it lists any.
+fn status_schema(cfg: &BoardConfig) -> serde_json::Value {
+    match cfg.status_mode {
+        StatusMode::Free if !cfg.statuses.is_empty() => {
+            let mut allowed = cfg.statuses.clone();
+            allowed.push(String::new());
+            serde_json::json!({ "type": "string", "enum": allowed })
+        }
+        _ => serde_json::json!({ "type": "string" }),
+    }
+}
+
+/// `extra` on create and update: declared fields by type, other names as
+/// `is_field_name` allows. Enum values are listed as declared; the server
+/// also takes them in another case.
+fn extra_schema(cfg: &BoardConfig) -> serde_json::Value {
+    let clear = [serde_json::json!({ "type": "null" }), serde_json::json!({ "const": "" })];
+    let declared: serde_json::Map<String, serde_json::Value> = cfg
+        .fields
+        .iter()
+        .map(|field| {
+            let value = match field.kind {
+                FieldKind::Text => serde_json::json!({ "type": ["string", "number", "boolean"] }),
+                FieldKind::Number => serde_json::json!({
+                    "anyOf": [{ "type": "number" }, string_matching(r"^\s*-?[0-9]+(\.[0-9]+)?\s*$")],
+                }),
+                FieldKind::Enum => serde_json::json!({ "enum": field.values }),
+            };
+            let mut any_of = vec![value];
+            any_of.extend(clear.iter().cloned());
+            (field.name.clone(), serde_json::json!({ "anyOf": any_of }))
+        })
+        .collect();
+    serde_json::json!({
+        "type": "object",
+        "properties": declared,
+        "propertyNames": { "pattern": "^[a-z][a-z0-9_-]*$", "not": { "enum": TASK_HEADERS } },
+        "additionalProperties": { "type": ["string", "number", "boolean", "null"] },
+    })
+}
+
+/// Properties `NewTask` and `UpdateTask` share, checked as `build_new_task`
+/// and the update handler check them.
+fn task_field_schemas(cfg: &BoardConfig) -> serde_json::Map<String, serde_json::Value> {
+    let text = serde_json::json!({ "type": "string" });
+    let properties = serde_json::json!({
+        "title": text,
+        "description": text,
+        "creator": text,
+        "assigned_to": text,
+        "tags": string_list(text.clone()),
+        "status": status_schema(cfg),
+        "color": string_matching(r"^\s*(#([0-9a-fA-F]{3,4}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8}))?\s*$"),
+        "icon": text,
+        "due": string_matching(r"^\s*([0-9]{4}-[0-9]{2}-[0-9]{2})?\s*$"),
+        "estimate": { "type": "number", "minimum": 0 },
+        "extra": extra_schema(cfg),
+    });
+    match properties {
+        serde_json::Value::Object(map) => map,
+        _ => unreachable!("json! of an object literal"),
+    }
+}
+
+/// JSON Schema documents for the bodies of task creates, updates and moves
+/// and of board updates, for the board as configured now: column ids,
+/// statuses and declared fields become enums and property schemas. The
+/// fingerprint changes whenever any of them does.
+fn request_schemas(cfg: &BoardConfig, settings: &Settings) -> serde_json::Value {
+    let text = serde_json::json!({ "type": "string" });
+    let column_ids: Vec<&str> = cfg.columns.iter().map(|c| c.id.as_str()).collect();
+    let open_ids: Vec<&str> = cfg.columns.iter().filter(|c| !is_frozen(c)).map(|c| c.id.as_str()).collect();
+    let collisions = ["suffix", "error", "overwrite", "skip"];
+    let list_item = string_matching(r"^[^,\s]+$");
+    let date_or_empty = string_matching(r"^\s*([0-9]{4}-[0-9]{2}-[0-9]{2})?\s*$");
+
+    let mut new_task = task_field_schemas(cfg);
+    new_task.insert(
+        "id".to_string(),
+        serde_json::json!({
+            "type": "string",
+            "pattern": r"^\s*([a-z0-9-]([a-z0-9.-]*[a-z0-9-])?)?\s*$",
+            "not": { "pattern": r"\.\." },
+        }),
+    );
+    new_task.insert(
+        "created_at".to_string(),
+        serde_json::json!({ "type": "string", "description": format!("One of: {}", TIMESTAMP_FORMATS.join(", ")) }),
+    );
+    new_task.insert("folder".to_string(), serde_json::json!({ "type": "string", "enum": open_ids }));
+    new_task.insert("collision".to_string(), serde_json::json!({ "type": "string", "enum": collisions }));
+    let update_task = task_field_schemas(cfg);
+
+    let mut sort_keys: Vec<serde_json::Value> = COLUMN_SORT_KEYS.iter().map(|k| serde_json::json!(k)).collect();
+    sort_keys.push(serde_json::Value::Null);
+    let column = object_schema(
+        serde_json::json!({
+            "id": string_matching("^[a-z0-9_-]+$"),
+            "title": text,
+            "wip_limit": { "type": ["integer", "null"], "minimum": 0 },
+            "default_tags": string_list(list_item.clone()),
+            "default_assignee": { "type": ["string", "null"], "pattern": r"^\S*$" },
+            "done": { "type": "boolean" },
+            "template": { "type": ["string", "null"] },
+            "sort": { "enum": sort_keys },
+            "sort_dir": { "enum": ["asc", "desc", null] },
+            "auto_assign": string_list(list_item.clone()),
+            "locked": { "type": "boolean" },
+            "max_age": { "type": ["string", "null"], "pattern": "^[0-9]+[dh]$" },
+        }),
+        &["id", "title"],
+    );
+    let mut columns = serde_json::json!({ "type": "array", "items": column, "minItems": 1 });
+    if settings.board.max_columns > 0 {
+        columns["maxItems"] = serde_json::json!(settings.board.max_columns);
+    }
+    let field = object_schema(
+        serde_json::json!({
+            "name": { "type": "string", "pattern": "^[a-z][a-z0-9_-]*$", "not": { "enum": TASK_HEADERS } },
+            "type": { "enum": ["text", "number", "enum"] },
+            "values": string_list(string_matching(r"^[^,)\n]+$")),
+        }),
+        &["name", "type"],
+    );
+    let rule = object_schema(
+        serde_json::json!({
+            "column": text,
+            "kind": { "enum": ["requires-tag", "denies-tag"] },
+            "tag": list_item,
+        }),
+        &["column", "kind", "tag"],
+    );
+    let board_update = object_schema(
+        serde_json::json!({
+            "columns": columns,
+            "status_mode": { "enum": ["column", "free"] },
+            "statuses": string_list(text.clone()),
+            "default_column": text,
+            "max_tasks": { "type": "integer", "minimum": 0 },
+            "unique_titles": { "enum": ["off", "warn", "enforce"] },
+            "collision": { "enum": collisions },
+            "slug_stopwords": { "type": "boolean" },
+            "slug_stopwords_extra": string_list(text.clone()),
+            "fields": { "type": "array", "items": field },
+            "private_fields": string_list(text.clone()),
+            "rules": { "type": "array", "items": rule },
+            "sprint_start": date_or_empty,
+            "sprint_end": date_or_empty,
+        }),
+        &["columns"],
+    );
+    let move_task = object_schema(
+        serde_json::json!({
+            "folder": { "type": "string", "enum": column_ids },
+            "status": status_schema(cfg),
+            "position": { "type": "integer", "minimum": 0 },
+        }),
+        &["folder"],
+    );
+
+    // Like `parse_body`, only `--strict-api` refuses unknown fields, and only
+    // at the top level.
+    let document = |title: &str, schema: serde_json::Value| {
+        let mut schema = schema;
+        schema["$schema"] = serde_json::json!(JSON_SCHEMA_DIALECT);
+        schema["title"] = serde_json::json!(title);
+        schema["additionalProperties"] = serde_json::json!(!settings.strict_api);
+        schema
+    };
+    let schemas = serde_json::json!({
+        "NewTask": document("NewTask", object_schema(serde_json::Value::Object(new_task), &["title"])),
+        "UpdateTask": document("UpdateTask", object_schema(serde_json::Value::Object(update_task), &[])),
+        "MoveTask": document("MoveTask", move_task),
+        "BoardUpdate": document("BoardUpdate", board_update),
+    });
+    let fingerprint = format!("{:016x}", content_hash(&schemas.to_string()));
+    serde_json::json!({ "fingerprint": fingerprint, "schemas": schemas })
+}
+
+/// Format version of the board packs this server writes; newer packs are
+/// refused rather than half understood.
+const BOARD_PACK_VERSION: u64 = 1;
+/// Sections a board pack may have next to its `kanban_board_pack` version.
+const BOARD_PACK_SECTIONS: [&str; 5] = ["board", "theme", "templates", "users", "views"];
+
+/// A board's setup without its tasks, as `GET /api/boardpack` exports it:
+/// the board settings (columns, fields and routing rules), the theme, task
+/// templates by id, team members and saved views. Only `board` is required.
+struct BoardPack {
+    board: BoardUpdate,
+    theme: Option<ThemeFile>,
+    templates: BTreeMap<String, String>,
+    users: Vec<String>,
+    views: Vec<SavedView>,
+}
+
+/// Why one section of a pack cannot be applied.
+#[derive(Debug, Serialize, PartialEq)]
+struct PackError {
+    section: String,
+    error: String,
+}
+
+fn pack_error(section: &str, error: impl Into<String>) -> PackError {
+    PackError { section: section.to_string(), error: error.into() }
+}
+
+/// What applying a pack changed.
+#[derive(Debug, Default, Serialize)]
+struct PackReport {
+    columns_added: Vec<String>,
+    columns_updated: Vec<String>,
+    theme: bool,
+    templates: usize,
+    users_added: usize,
+    views: usize,
+}
+
+/// The pack of the board at `root`. The theme is only included when the
+/// board has a theme file, so applying the pack keeps another board's own.
+fn export_board_pack(root: &Path) -> Result<serde_json::Value, String> {
+    let config = read_config(root).map_err(|err| err.to_string())?;
+    let mut board = serde_json::json!(config);
+    if let Some(board) = board.as_object_mut() {
+        board.remove("modified_at");
+        board.remove("modified_by");
+    }
+    let mut pack = serde_json::json!({
+        "kanban_board_pack": BOARD_PACK_VERSION,
+        "board": board,
+        "templates": read_task_templates(root),
+        "users": read_users_file(root),
+        "views": read_views(root),
+    });
+    if !theme_files(root).is_empty() {
+        let theme = read_theme(root)?;
+        pack["theme"] = serde_json::json!(ThemeFile {
+            headline: theme.headline,
+            colors: theme.colors,
+            palette: Some(theme.palette),
+            font_family: Some(theme.font_family),
+            font_size: Some(theme.font_size),
+            card_radius: Some(theme.card_radius),
+            density: Some(theme.density),
+            column_colors: theme.column_colors,
+        });
+    }
+    Ok(pack)
+}
+
+/// The files in `.kanban-templates/` by template id.
+fn read_task_templates(root: &Path) -> BTreeMap<String, String> {
+    let mut templates = BTreeMap::new();
+    for entry in fs::read_dir(root.join(TASK_TEMPLATES_DIR)).into_iter().flatten().flatten() {
+        let path = entry.path();
+        let Some(id) = path.file_name().and_then(|n| n.to_str()).and_then(|n| n.strip_suffix(".md")) else {
+            continue;
+        };
+        if let (true, Ok(text)) = (is_valid_id(id), fs::read_to_string(&path)) {
+            templates.insert(id.to_string(), text);
+        }
+    }
+    templates
+}
+
+/// Reads a pack, checking every section on its own so all problems are
+/// reported at once. Unknown fields are errors, not ignored: a misspelt
+/// key in a hand-edited pack would otherwise silently do nothing.
+fn parse_board_pack(text: &str) -> Result<BoardPack, Vec<PackError>> {
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|err| vec![pack_error("pack", err.to_string())])?;
+    let Some(object) = value.as_object() else {
+        return Err(vec![pack_error("pack", "expected a JSON object")]);
+    };
+    let mut errors = Vec::new();
+    match object.get("kanban_board_pack").map(|v| v.as_u64()) {
+        None => errors.push(pack_error("kanban_board_pack", "missing format version; is this a board pack?")),
+        Some(Some(version)) if (1..=BOARD_PACK_VERSION).contains(&version) => {}
+        Some(Some(version)) if version > BOARD_PACK_VERSION => errors.push(pack_error(
+            "kanban_board_pack",
+            format!("format version {} is newer than this server supports ({})", version, BOARD_PACK_VERSION),
+        )),
+        Some(_) => errors.push(pack_error("kanban_board_pack", "expected a format version number from 1")),
+    }
+    for key in object.keys().filter(|key| *key != "kanban_board_pack" && !BOARD_PACK_SECTIONS.contains(&key.as_str())) {
+        errors.push(pack_error(key, format!("unknown section (expected {})", BOARD_PACK_SECTIONS.join(", "))));
+    }
+    let section = |name: &str, errors: &mut Vec<PackError>| -> Option<serde_json::Value> {
+        let value = object.get(name)?.clone();
+        let mut unknown = Vec::new();
+        match name {
+            "board" => {
+                unknown = unknown_fields(&value, accepted_fields::<BoardUpdate>(), "");
+                for (i, column) in value["columns"].as_array().into_iter().flatten().enumerate() {
+                    unknown.extend(unknown_fields(column, accepted_fields::<BoardColumn>(), &format!("columns[{}].", i)));
+                }
+            }
+            "views" => {
+                for (i, view) in value.as_array().into_iter().flatten().enumerate() {
+                    unknown.extend(unknown_fields(view, accepted_fields::<SavedView>(), &format!("[{}].", i)));
+                }
+            }
+            _ => {}
+        }
+        if !unknown.is_empty() {
+            errors.push(pack_error(name, format!("unknown fields: {}", unknown.join(", "))));
+            return None;
+        }
+        Some(value)
+    };
+    let board = match section("board", &mut errors) {
+        Some(value) => serde_json::from_value::<BoardUpdate>(value).map_err(|err| errors.push(pack_error("board", err.to_string()))).ok(),
+        None if !object.contains_key("board") => {
+            errors.push(pack_error("board", "missing; a pack needs at least the board's columns"));
+            None
+        }
+        None => None,
+    };
+    let theme = section("theme", &mut errors).and_then(|value| {
+        let checked = serde_json::from_value::<ThemeFile>(value.clone())
+            .map_err(|err| err.to_string())
+            .and_then(|file| apply_theme_file(&mut ThemeSettings::default(), file));
+        match checked {
+            Ok(()) => serde_json::from_value::<ThemeFile>(value).ok(),
+            Err(msg) => {
+                errors.push(pack_error("theme", msg));
+                None
+            }
+        }
+    });
+    let templates = section("templates", &mut errors)
+        .and_then(|value| serde_json::from_value::<BTreeMap<String, String>>(value).map_err(|err| errors.push(pack_error("templates", err.to_string()))).ok())
+        .unwrap_or_default();
+    for id in templates.keys().filter(|id| !is_valid_id(id)) {
+        errors.push(pack_error("templates", format!("invalid template id: {:?}", id)));
+    }
+    let users = section("users", &mut errors)
+        .and_then(|value| serde_json::from_value::<Vec<String>>(value).map_err(|err| errors.push(pack_error("users", err.to_string()))).ok())
+        .unwrap_or_default();
+    for user in users.iter().filter(|user| user.trim().is_empty() || user.trim().contains(char::is_whitespace)) {
+        errors.push(pack_error("users", format!("invalid user name: {:?}", user)));
+    }
+    let views = section("views", &mut errors)
+        .and_then(|value| serde_json::from_value::<Vec<SavedView>>(value).map_err(|err| errors.push(pack_error("views", err.to_string()))).ok())
+        .unwrap_or_default();
+    for (i, view) in views.iter().enumerate() {
+        if !is_view_name(&view.name) {
+            errors.push(pack_error("views", format!("invalid view name: {:?}", view.name)));
+        } else if views[..i].iter().any(|other| other.name == view.name) {
+            errors.push(pack_error("views", format!("view {} is listed twice", view.name)));
+        }
+    }
+    match board {
+        Some(board) if errors.is_empty() => Ok(BoardPack { board, theme, templates, users, views }),
+        _ => Err(errors),
+    }
+}
+
+/// The board config `pack` turns the board at `root` into, or what is
+/// wrong with it. Columns merge by id: a column the board already has takes
+/// the pack's title and attributes in place, new columns are appended in
+/// pack order, and columns the pack leaves out are kept with their tasks.
+/// Other board settings follow `PUT /api/board`: those the pack includes
+/// replace the board's. A root without a board file starts from the pack.
+fn plan_board_pack(root: &Path, pack: &BoardPack, opts: &BoardOptions) -> Result<BoardConfig, Vec<PackError>> {
+    let old = read_config(root).unwrap_or_default();
+    let mut columns = old.columns.clone();
+    for column in &pack.board.columns {
+        match columns.iter_mut().find(|c| c.id == column.id) {
+            Some(existing) => *existing = column.clone(),
+            None => columns.push(column.clone()),
+        }
+    }
+    let update = BoardUpdate { columns, ..pack.board.clone() };
+    let config = updated_config(&old, update);
+    let mut errors = Vec::new();
+    if let Err(msg) = validate_board(&config, opts.max_columns) {
+        errors.push(pack_error("board", msg));
+    }
+    if let Some(file) = &pack.theme {
+        let mut theme = ThemeSettings::default();
+        let file = ThemeFile { column_colors: file.column_colors.clone(), ..Default::default() };
+        if apply_theme_file(&mut theme, file).is_ok() {
+            let unknown = unknown_theme_columns(&theme, &config);
+            if !unknown.is_empty() {
+                errors.push(pack_error("theme", format!("column_colors names unknown columns: {}", unknown.join(", "))));
+            }
+        }
+    }
+    for column in &config.columns {
+        let template = column.template.as_deref().filter(|id| !pack.templates.contains_key(*id));
+        if let Some(id) = template.filter(|id| !root.join(TASK_TEMPLATES_DIR).join(format!("{}.md", id)).is_file()) {
+            errors.push(pack_error("templates", format!("column {} uses template {}, which neither the pack nor the board has", column.id, id)));
+        }
+    }
+    if errors.is_empty() {
+        Ok(config)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Writes a planned pack: templates, users and views are merged into the
+/// board's (the pack wins where both have the same id or name), the theme
+/// replaces `.kanban-theme.json`, then the board file is saved. Tasks are
+/// never touched.
+fn write_board_pack(root: &Path, pack: BoardPack, mut config: BoardConfig, actor: &str) -> Result<PackReport, String> {
+    let old = read_config(root).map(|old| old.columns).unwrap_or_default();
+    let mut report = PackReport {
+        theme: pack.theme.is_some(),
+        templates: pack.templates.len(),
+        views: pack.views.len(),
+        ..Default::default()
+    };
+    for column in &config.columns {
+        match old.iter().any(|c| c.id == column.id) {
+            true if pack.board.columns.iter().any(|c| c.id == column.id) => report.columns_updated.push(column.id.clone()),
+            true => {}
+            false => report.columns_added.push(column.id.clone()),
+        }
+    }
+    if !pack.templates.is_empty() {
+        let dir = root.join(TASK_TEMPLATES_DIR);
+        fs::create_dir_all(&dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+        for (id, text) in &pack.templates {
+            let path = dir.join(format!("{}.md", id));
+            fs::write(&path, text).map_err(|err| format!("{}: {}", path.display(), err))?;
+        }
+    }
+    let mut users = read_users_file(root);
+    let added: Vec<String> = pack.users.iter().map(|u| u.trim().to_string()).filter(|u| !users.contains(u)).collect();
+    if !added.is_empty() {
+        report.users_added = added.len();
+        users.extend(added);
+        let path = root.join(USERS_FILE);
+        fs::write(&path, users.join("\n") + "\n").map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    if !pack.views.is_empty() {
+        let mut views = read_views(root);
+        views.retain(|view| !pack.views.iter().any(|new| new.name == view.name));
+        views.extend(pack.views);
+        write_views(root, &views).map_err(|err| format!("{}: {}", root.join(VIEWS_FILE).display(), err))?;
+    }
+    if let Some(theme) = &pack.theme {
+        let contents = serde_json::to_string_pretty(theme).map_err(|err| err.to_string())?;
+        fs::write(theme_json_path(root), contents + "\n").map_err(|err| format!("{}: {}", THEME_JSON_FILE, err))?;
+    }
+    apply_board_config(root, &mut config, actor)?;
+    Ok(report)
+}
+
+/// `init --from-pack`: applies a pack file to the board at `root`, creating
+/// the directory when it is missing. Returns the exit code.
+fn init_from_pack(root: &Path, file: &Path, opts: &BoardOptions) -> i32 {
+    let text = match fs::read_to_string(file) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("Cannot read {}: {}", file.display(), err);
+            return 1;
+        }
+    };
+    let planned = parse_board_pack(&text).and_then(|pack| plan_board_pack(root, &pack, opts).map(|config| (pack, config)));
+    let (pack, config) = match planned {
+        Ok(planned) => planned,
+        Err(errors) => {
+            eprintln!("{} is not a valid board pack; nothing was changed:", file.display());
+            for error in errors {
+                eprintln!("  {}: {}", error.section, error.error);
+            }
+            return 1;
+        }
+    };
+    if let Err(err) = fs::create_dir_all(root) {
+        eprintln!("Cannot create board root {}: {}", root.display(), err);
+        return 1;
+    }
+    match write_board_pack(root, pack, config, "init") {
+        Ok(report) => {
+            println!("Applied {} to {}", file.display(), root.display());
+            println!("Columns:     {} added, {} updated", report.columns_added.len(), report.columns_updated.len());
+            println!("Theme:       {}", if report.theme { "replaced .kanban-theme.json" } else { "unchanged" });
+            println!("Templates:   {}", report.templates);
+            println!("Users:       {} added", report.users_added);
+            println!("Views:       {}", report.views);
+            0
+        }
+        Err(err) => {
+            eprintln!("Applying {} failed: {}", file.display(), err);
+            1
+        }
+    }
+}
+
+/// Header a merged task carries: the source board and the task's id there,
+/// so running the same merge again skips it.
+const MERGE_MARKER_FIELD: &str = "merged_from";
+/// Reports of `merge` runs, one JSON file each. A dot-directory, so never
+/// a column.
+const MERGES_DIR: &str = ".kanban-merges";
+
+/// Options of the `merge` command.
+#[derive(Debug, Default, Clone)]
+struct MergeOptions {
+    from: Option<PathBuf>,
+    into: Option<PathBuf>,
+    /// `from=to` pairs: source columns whose tasks go to another column.
+    column_map: Vec<(String, String)>,
+    /// Put in front of a source id that is taken, instead of appending
+    /// `-2`, `-3`, ...
+    prefix: Option<String>,
+}
+
+/// Parses a `--column-map` value: `from=to` pairs, comma-separated.
+fn parse_column_map(value: &str) -> Result<Vec<(String, String)>, String> {
+    split_list(value)
+        .into_iter()
+        .map(|pair| match pair.split_once('=') {
+            Some((from, to)) if !from.trim().is_empty() && !to.trim().is_empty() => {
+                Ok((from.trim().to_string(), to.trim().to_string()))
+            }
+            _ => Err(format!("Invalid value for --column-map: {} (use from=to)", pair)),
+        })
+        .collect()
+}
+
+/// How a merge places the source board's columns.
+struct ColumnPlan {
+    /// Target column of each source column.
+    targets: HashMap<String, String>,
+    /// Columns the target board gains, with the source column's settings.
+    created: Vec<BoardColumn>,
+    /// One report entry per source column.
+    decisions: Vec<serde_json::Value>,
+}
+
+/// Plans the columns of a merge. A mapped column keeps its target; an
+/// unmapped one goes to the target column with the same id, which is
+/// created when missing.
+fn plan_merge_columns(from: &BoardConfig, into: &BoardConfig, column_map: &[(String, String)]) -> Result<ColumnPlan, String> {
+    if let Some((source, _)) = column_map.iter().find(|(source, _)| !from.columns.iter().any(|c| &c.id == source)) {
+        return Err(format!("--column-map names {}, which the source board does not have", source));
+    }
+    let mut targets = HashMap::new();
+    let mut created: Vec<BoardColumn> = Vec::new();
+    let mut decisions = Vec::new();
+    for column in &from.columns {
+        let mapped = column_map.iter().find(|(source, _)| *source == column.id).map(|(_, target)| target);
+        let target = mapped.unwrap_or(&column.id).clone();
+        let decision = if mapped.is_some() {
+            "mapped"
+        } else if into.columns.iter().any(|c| c.id == target) {
+            "matched"
+        } else {
+            created.push(column.clone());
+            "created"
+        };
+        decisions.push(serde_json::json!({ "kind": "column", "source": column.id, "target": target, "decision": decision }));
+        targets.insert(column.id.clone(), target);
+    }
+    let known = |id: &String| into.columns.iter().chain(&created).any(|c| &c.id == id);
+    if let Some((source, target)) = column_map.iter().find(|(_, target)| !known(target)) {
+        return Err(format!("--column-map maps {} to {}, which the target board does not have", source, target));
+    }
+    Ok(ColumnPlan { targets, created, decisions })
+}
+
+/// The id a source task gets on the target: its own, or on a clash
+/// `prefix` + id when a prefix is set, else id-2, id-3, ... Either way the
+/// smallest free suffix settles a remaining clash.
+fn merged_task_id(id: &str, prefix: Option<&str>, taken: &HashSet<String>) -> String {
+    if !taken.contains(&id.to_ascii_lowercase()) {
+        return id.to_string();
+    }
+    match prefix {
+        Some(prefix) => unique_slug(&format!("{}{}", prefix, id), taken),
+        None => unique_slug(id, taken),
+    }
+}
+
+/// `merge --from <a> --into <b>`: copies board `a` into board `b` without
+/// changing `a`. Columns are mapped or created first, users are added, then
+/// every task is copied with a `MERGE_MARKER_FIELD` header. The task file
+/// is written last, after its attachments, so a run that stopped halfway
+/// is finished by running it again: tasks whose marker `b` already has are
+/// skipped. Every decision goes to a report in `MERGES_DIR`. Returns the
+/// exit code.
+fn merge_boards(options: &MergeOptions, opts: &BoardOptions) -> i32 {
+    match run_merge(options, opts) {
+        Ok((report, path)) => {
+            let count = |kind: &str, decision: &str| {
+                report["decisions"]
+                    .as_array()
+                    .map_or(0, |all| all.iter().filter(|d| d["kind"] == kind && d["decision"] == decision).count())
+            };
+            println!("Merged {} into {}", report["from"].as_str().unwrap_or_default(), report["into"].as_str().unwrap_or_default());
+            println!(
+                "Columns:     {} created, {} mapped, {} matched",
+                count("column", "created"),
+                count("column", "mapped"),
+                count("column", "matched")
+            );
+            println!(
+                "Tasks:       {} copied ({} renamed), {} already merged, {} failed",
+                count("task", "copied") + count("task", "renamed"),
+                count("task", "renamed"),
+                count("task", "already_merged"),
+                count("task", "failed")
+            );
+            println!("Users:       {} added", count("user", "added"));
+            println!("Report:      {}", path.display());
+            i32::from(count("task", "failed") > 0)
+        }
+        Err(msg) => {
+            eprintln!("{}", msg);
+            1
+        }
+    }
+}
+
+fn run_merge(options: &MergeOptions, opts: &BoardOptions) -> Result<(serde_json::Value, PathBuf), String> {
+    let resolve = |path: &Option<PathBuf>| {
+        let path = path.as_deref().unwrap_or(Path::new(""));
+        fs::canonicalize(path).map_err(|err| format!("Cannot resolve board root {}: {}", path.display(), err))
+    };
+    let (from, into) = (resolve(&options.from)?, resolve(&options.into)?);
+    if from == into {
+        return Err(format!("--from and --into are the same board: {}", from.display()));
+    }
+    for root in [&from, &into] {
+        if let Some((pid, _)) = running_server(&root.join(PID_FILE)) {
+            return Err(format!("A server (pid {}) is running on {}; stop it before merging", pid, root.display()));
+        }
+    }
+    let read = |root: &Path| read_config(root).map_err(|err| format!("{}: {}", config_path(root).display(), err));
+    let (from_cfg, mut into_cfg) = (read(&from)?, read(&into)?);
+    let ColumnPlan { targets, created, mut decisions } = plan_merge_columns(&from_cfg, &into_cfg, &options.column_map)?;
+    let mut skipped = Vec::new();
+    let source = load_tasks(&from, &from_cfg, opts, None, true, &mut skipped).map_err(|err| format!("{}: {}", from.display(), err))?;
+    let merged: HashMap<String, (String, String)> = load_all_tasks(&into, &into_cfg, opts, None)
+        .map_err(|err| format!("{}: {}", into.display(), err))?
+        .into_values()
+        .flatten()
+        .filter_map(|task| Some((task.extra.get(MERGE_MARKER_FIELD)?.as_str()?.to_string(), (task.folder, task.id))))
+        .collect();
+    let marker = |id: &str| format!("{}#{}", from.display(), id);
+    let mut taken = existing_ids(&into, &into_cfg);
+    let adding = source.values().flatten().filter(|task| !merged.contains_key(&marker(&task.id))).count();
+    check_capacity(&into_cfg, taken.len(), adding)?;
+
+    if !created.is_empty() {
+        into_cfg.columns.extend(created);
+        validate_board(&into_cfg, opts.max_columns)?;
+        apply_board_config(&into, &mut into_cfg, "merge")?;
+    }
+    let mut users = read_users_file(&into);
+    let added: Vec<String> = read_users_file(&from).into_iter().filter(|user| !users.contains(user)).collect();
+    if !added.is_empty() {
+        decisions.extend(added.iter().map(|name| serde_json::json!({ "kind": "user", "name": name, "decision": "added" })));
+        users.extend(added);
+        let path = into.join(USERS_FILE);
+        fs::write(&path, users.join("\n") + "\n").map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+
+    for warning in skipped {
+        decisions.push(serde_json::json!({ "kind": "task", "source": warning.task, "decision": "failed", "reason": warning.message }));
+    }
+    for column in &from_cfg.columns {
+        let mut tasks = source.get(&column.id).cloned().unwrap_or_default();
+        tasks.sort_by(|a, b| a.id.cmp(&b.id));
+        for mut task in tasks {
+            let source_file = format!("{}/{}.md", column.id, task.id);
+            let marker = marker(&task.id);
+            if let Some((folder, id)) = merged.get(&marker) {
+                decisions.push(serde_json::json!({
+                    "kind": "task",
+                    "source": source_file,
+                    "target": format!("{}/{}.md", folder, id),
+                    "decision": "already_merged",
+                }));
+                continue;
+            }
+            let folder = targets[&column.id].clone();
+            let id = merged_task_id(&task.id, options.prefix.as_deref(), &taken);
+            let decision = if id == task.id { "copied" } else { "renamed" };
+            let old_id = std::mem::replace(&mut task.id, id.clone());
+            task.folder = folder.clone();
+            match into_cfg.status_mode {
+                StatusMode::Column => task.status = folder.clone(),
+                StatusMode::Free => {
+                    if validate_status(&into_cfg, &task.status).is_err() {
+                        task.status = String::new();
+                    }
+                }
+            }
+            update_completion(&into_cfg, &mut task);
+            task.extra.insert(MERGE_MARKER_FIELD.to_string(), serde_json::json!(marker));
+            let written = checked_task_path(&into, &folder, &id).and_then(|path| {
+                let attachments = attachments_dir(&from, &old_id);
+                if attachments.is_dir() {
+                    copy_files(&attachments, &attachments_dir(&into, &id)).map_err(|err| err.to_string())?;
+                }
+                write_task(&path, &mut task).map_err(|err| err.to_string())
+            });
+            let target = format!("{}/{}.md", folder, id);
+            match written {
+                Ok(()) => {
+                    taken.insert(id.to_ascii_lowercase());
+                    record_task_activity(&into, &Hooks::default(), "cli", "task_created", &id, serde_json::json!({ "column": folder, MERGE_MARKER_FIELD: marker }));
+                    decisions.push(serde_json::json!({ "kind": "task", "source": source_file, "target": target, "decision": decision }));
+                }
+                Err(msg) => {
+                    decisions.push(serde_json::json!({ "kind": "task", "source": source_file, "target": target, "decision": "failed", "reason": msg }));
+                }
+            }
+        }
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let report = serde_json::json!({
+        "from": from.display().to_string(),
+        "into": into.display().to_string(),
+        "at": now_iso(),
+        "decisions": decisions,
+    });
+    let dir = into.join(MERGES_DIR);
+    let stamp = history_stamp(now);
+    let mut path = dir.join(format!("{}.json", stamp));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.json", stamp, n));
+        n += 1;
+    }
+    fs::create_dir_all(&dir)
+        .and_then(|()| fs::write(&path, serde_json::to_string_pretty(&report).unwrap_or_default() + "\n"))
+        .map_err(|err| format!("{}: {}", path.display(), err))?;
+    Ok((report, path))
+}
+
+/// Options of the `publish` command.
+#[derive(Debug, Default, Clone)]
+struct PublishOptions {
+    out: Option<PathBuf>,
+    /// Add the tasks in `_archive/` as an extra, last column.
+    include_archive: bool,
+    /// Add the tasks in `.trash/` as an extra, last column.
+    include_trash: bool,
+}
+
+#[derive(Debug)]
+struct PublishReport {
+    out: PathBuf,
+    tasks: usize,
+    files: usize,
+    bytes: u64,
+}
+
+/// Makes the web UI read `data.json`, `board.json` and `theme.json` instead
+/// of the API, and hide everything that would change the board.
+const PUBLISH_CONFIG_JS: &str = "\
+window.KANBAN_STATIC = {
+  \"api/tasks\": \"data.json\",
+  \"api/board\": \"board.json\",
+  \"api/ui\": \"board.json\",
+  \"api/theme\": \"theme.json\",
+};
+";
+
+/// Writes a static, read-only copy of the board to `options.out`: the JSON
+/// the UI would fetch (`data.json` is `GET /api/tasks`, `board.json` is
+/// `GET /api/board`, `theme.json` is `GET /api/theme`), the web assets and
+/// a `config.js` that points the UI at those files. Files are overwritten,
+/// so running it again refreshes the copy.
+fn publish_board(root: &Path, opts: &BoardOptions, options: &PublishOptions) -> Result<PublishReport, String> {
+    let out = options.out.as_deref().ok_or("publish needs --out <dir>")?;
+    let mut config = read_config(root).map_err(|err| format!("{}: {}", root.join(CONFIG_FILE).display(), err))?;
+    // The nearest existing ancestor decides, so an `--out` that does not
+    // exist yet is checked too.
+    let resolved = std::path::absolute(out)
+        .ok()
+        .and_then(|out| out.ancestors().find_map(|dir| fs::canonicalize(dir).ok()));
+    if let (Ok(root), Some(resolved)) = (fs::canonicalize(root), resolved) {
+        if resolved.starts_with(&root) {
+            return Err(format!("--out must be outside the board directory: {}", out.display()));
+        }
+    }
+    let mut folders = load_all_tasks(root, &config, opts, None).map_err(|err| err.to_string())?;
+    let extra = [(options.include_archive, "_archive", "Archive"), (options.include_trash, ".trash", "Trash")];
+    for (_, id, title) in extra.into_iter().filter(|(include, _, _)| *include) {
+        let tasks = scan_column(root, id, config.status_mode, opts, None).map_err(|err| err.to_string())?;
+        config.columns.push(BoardColumn { id: id.to_string(), title: title.to_string(), ..Default::default() });
+        folders.insert(id.to_string(), tasks);
+    }
+    let tasks = folders.values().map(Vec::len).sum();
+    let data = tasks_payload(root, &config, opts, folders, &TaskFilter::default(), &Page::default(), 0, None);
+    let board = with_warnings(serde_json::json!({ "board": config }), lint_columns(&config.columns));
+    let theme = serde_json::json!({ "theme": read_theme(root).unwrap_or_default() });
+    let index = INDEX_HTML.replacen(
+        "<script src=\"app.js\"></script>",
+        "<script src=\"config.js\"></script>\n    <script src=\"app.js\"></script>",
+        1,
+    );
+    let files = [
+        ("data.json", data.to_string()),
+        ("board.json", board.to_string()),
+        ("theme.json", theme.to_string()),
+        ("views.json", serde_json::json!({ "views": read_views(root) }).to_string()),
+        ("index.html", index),
+        ("app.js", APP_JS.to_string()),
+        ("styles.css", STYLES_CSS.to_string()),
+        ("config.js", PUBLISH_CONFIG_JS.to_string()),
+    ];
+    fs::create_dir_all(out).map_err(|err| format!("{}: {}", out.display(), err))?;
+    let mut bytes = 0;
+    for (name, contents) in &files {
+        let path = out.join(name);
+        fs::write(&path, contents).map_err(|err| format!("{}: {}", path.display(), err))?;
+        bytes += contents.len() as u64;
+    }
+    Ok(PublishReport { out: out.to_path_buf(), tasks, files: files.len(), bytes })
+}
+
+/// A byte count for humans: `812 B`, `14.2 KB`, `3.1 MB`.
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+fn attachments_dir(root: &Path, id: &str) -> PathBuf {
+    root.join(ATTACHMENTS_DIR).join(id)
+}
+
+fn attachment_url(base_path: &str, id: &str, name: &str) -> String {
+    format!("{}/api/tasks/{}/attachments/{}", base_path, id, name)
+}
+
+/// Parses the `attachments:` header (`name:size, name:size`); their URLs
+/// start with `base_path`.
+fn parse_attachments(id: &str, value: &str, base_path: &str) -> Vec<Attachment> {
+    value
+        .split(',')
+        .filter_map(|item| {
+            let (name, size) = item.trim().rsplit_once(':')?;
+            Some(Attachment {
+                name: name.to_string(),
+                size: size.parse().ok()?,
+                url: attachment_url(base_path, id, name),
+            })
+        })
+        .collect()
+}
+
+/// Reduces an uploaded file name to `[A-Za-z0-9._-]`, dropping any directory
+/// part, so it can never escape the task's attachment folder.
+fn sanitize_filename(raw: &str) -> Option<String> {
+    let base = raw.rsplit(['/', '\\']).next().unwrap_or("");
+    let mut out = String::new();
+    for ch in base.chars() {
+        if ch.is_ascii_alphanumeric() || ch == '.' || ch == '-' || ch == '_' {
+            out.push(ch);
+        } else if ch.is_whitespace() && !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    let out = out.trim_start_matches('.').to_string();
+    if out.is_empty() || out.len() > 128 {
+        return None;
+    }
+    Some(out)
+}
+
+fn attachment_mime(name: &str) -> Option<&'static str> {
+    let ext = name.rsplit_once('.')?.1.to_ascii_lowercase();
+    ATTACHMENT_TYPES
+        .iter()
+        .find(|(allowed, _)| *allowed == ext)
+        .map(|(_, mime)| *mime)
+}
+
+fn remove_attachments(root: &Path, id: &str) {
+    let dir = attachments_dir(root, id);
+    if dir.exists() {
+        if let Err(err) = fs::remove_dir_all(&dir) {
+            eprintln!("Failed to remove attachments of {}: {}", id, err);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_attachments(
+    root: &Path,
+    cfg: &BoardConfig,
+    opts: &BoardOptions,
+    id: &str,
+    rest: &[&str],
+    method: &Method,
+    query: &HashMap<String, String>,
+    upload: &[u8],
+    history_limit: usize,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let Some((path, folder)) = find_task_path(root, id, cfg, opts) else {
+        return task_not_found();
+    };
+    let mut task = match parse_task(&path, &folder, cfg.status_mode, &opts.base_path) {
+        Ok(task) => task,
+        Err(err) => return respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+    };
+    match (method, rest) {
+        (Method::Get, []) => respond_json(
+            StatusCode(200),
+            &serde_json::json!({ "id": id, "attachments": task.attachments }).to_string(),
+        ),
+        (Method::Post, []) => {
+            let Some(name) = query.get("filename").and_then(|raw| sanitize_filename(raw)) else {
+                return respond_json(StatusCode(400), &serde_json::json!({"error": "query parameter filename is required"}).to_string());
+            };
+            if attachment_mime(&name).is_none() {
+                return respond_json(
+                    StatusCode(415),
+                    &serde_json::json!({"error": format!("file type not allowed: {}", name)}).to_string(),
+                );
+            }
+            if upload.is_empty() {
+                return respond_json(StatusCode(400), &serde_json::json!({"error": "empty upload"}).to_string());
+            }
+            if upload.len() > ATTACHMENT_MAX_BYTES {
+                return respond_json(
+                    StatusCode(413),
+                    &serde_json::json!({"error": format!("attachments are limited to {} bytes", ATTACHMENT_MAX_BYTES)}).to_string(),
+                );
+            }
+            let dir = attachments_dir(root, id);
+            let target = dir.join(&name);
+            let written = fs::create_dir_all(&dir).and_then(|_| {
+                if !resolves_within(&dir, &root.join(ATTACHMENTS_DIR)) {
+                    return Err(io::Error::other("attachment folder resolves outside the board"));
+                }
+                // Replace a symlink instead of writing through it.
+                if fs::symlink_metadata(&target).is_ok_and(|m| m.file_type().is_symlink()) {
+                    fs::remove_file(&target)?;
+                }
+                fs::write(&target, upload)
+            });
+            if let Err(err) = written {
+                return respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string());
+            }
+            record_history(root, id, &path, history_limit);
+            task.attachments.retain(|a| a.name != name);
+            task.attachments.push(Attachment {
+                url: attachment_url(&opts.base_path, id, &name),
+                name,
+                size: upload.len() as u64,
+            });
+            touch_task(&mut task);
+            match write_task(&path, &mut task) {
+                Ok(_) => respond_json(StatusCode(201), &serde_json::json!(task).to_string()),
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            }
+        }
+        (Method::Delete, [name]) => {
+            if !task.attachments.iter().any(|a| a.name == *name) {
+                return respond_json(StatusCode(404), &serde_json::json!({"error": "attachment not found"}).to_string());
+            }
+            if sanitize_filename(name).as_deref() == Some(name) {
+                let _ = fs::remove_file(attachments_dir(root, id).join(name));
+            }
+            record_history(root, id, &path, history_limit);
+            task.attachments.retain(|a| a.name != *name);
+            touch_task(&mut task);
+            match write_task(&path, &mut task) {
+                Ok(_) => respond_json(StatusCode(204), ""),
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            }
+        }
+        _ => respond_error(404, "not_found", &[]),
+    }
+}
+
+/// `GET /api/tasks/{id}/attachments/{name}` is answered outside the JSON
+/// router so the file is streamed from disk instead of buffered.
+fn serve_attachment(request: tiny_http::Request, root: &Path, id: &str, name: &str, opts: &BoardOptions) {
+    let not_found = |what: &str| respond_json(StatusCode(404), &serde_json::json!({ "error": format!("{} not found", what) }).to_string());
+    let response = match refresh_config(root) {
+        Ok(cfg) if find_task_path(root, id, &cfg, opts).is_none() => not_found("task"),
+        Ok(_) => {
+            let file = attachments_dir(root, id).join(name);
+            match (sanitize_filename(name).filter(|n| n == name), attachment_mime(name)) {
+                (Some(_), Some(mime)) if file.is_file() && resolves_within(&file, &root.join(ATTACHMENTS_DIR)) => {
+                    return respond_file(request, &file, mime)
+                }
+                _ => not_found("attachment"),
+            }
+        }
+        Err(msg) => respond_json(StatusCode(500), &serde_json::json!({ "error": msg }).to_string()),
+    };
+    let _ = request.respond(response);
+}
+
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    Full,
+    /// Inclusive first and last byte.
+    Slice(u64, u64),
+    Unsatisfiable,
+}
+
+/// Interprets a `Range` header for a body of `len` bytes. Only a single
+/// `bytes=` range is honored; anything else falls back to the full body.
+fn parse_range(header: Option<&str>, len: u64) -> ByteRange {
+    let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+    let range = if start.is_empty() {
+        match end.parse::<u64>() {
+            Ok(0) => return ByteRange::Unsatisfiable,
+            Ok(suffix) => (len.saturating_sub(suffix), len.saturating_sub(1)),
+            Err(_) => return ByteRange::Full,
+        }
+    } else {
+        let Ok(start) = start.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+        let end = match end {
+            "" => len.saturating_sub(1),
+            value => match value.parse::<u64>() {
+                Ok(end) if end >= start => end.min(len.saturating_sub(1)),
+                _ => return ByteRange::Full,
+            },
+        };
+        (start, end)
+    };
+    if len == 0 || range.0 >= len {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Slice(range.0, range.1)
+}
+
+/// Streams a file, honoring `Range` with 206 / 416 responses.
+fn respond_file(request: tiny_http::Request, path: &Path, mime: &str) {
+    let opened = fs::File::open(path).and_then(|file| {
+        let len = file.metadata()?.len();
+        Ok((file, len))
+    });
+    let (mut file, len) = match opened {
+        Ok(opened) => opened,
+        Err(err) => {
+            let _ = request.respond(respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()));
+            return;
+        }
+    };
+    let mut headers = vec![
+        Header::from_bytes("Content-Type", mime).unwrap(),
+        Header::from_bytes("X-Content-Type-Options", "nosniff").unwrap(),
+        Header::from_bytes("Accept-Ranges", "bytes").unwrap(),
+    ];
+    let (status, start, count) = match parse_range(header_value(&request, "Range").as_deref(), len) {
+        ByteRange::Full => (200, 0, len),
+        ByteRange::Slice(first, last) => {
+            headers.push(Header::from_bytes("Content-Range", format!("bytes {}-{}/{}", first, last, len)).unwrap());
+            (206, first, last - first + 1)
+        }
+        ByteRange::Unsatisfiable => {
+            let response = respond_json(StatusCode(416), &serde_json::json!({"error": "range not satisfiable"}).to_string())
+                .with_header(Header::from_bytes("Content-Range", format!("bytes */{}", len)).unwrap());
+            let _ = request.respond(response);
+            return;
+        }
+    };
+    if start > 0 {
+        if let Err(err) = file.seek(SeekFrom::Start(start)) {
+            let _ = request.respond(respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()));
+            return;
+        }
+    }
+    let body = Read::take(file, count);
+    let _ = request.respond(Response::new(StatusCode(status), headers, body, Some(count as usize), None));
+}
+
+fn handle_diff(root: &Path, cfg: &BoardConfig, opts: &BoardOptions, id: &str, query: &HashMap<String, String>) -> Response<std::io::Cursor<Vec<u8>>> {
+    let current = find_task_path(root, id, cfg, opts);
+    if current.is_none() && !history_dir(root, id).exists() {
+        return task_not_found();
+    }
+    let Some(from) = query.get("from").filter(|v| !v.is_empty()) else {
+        return respond_json(StatusCode(400), &serde_json::json!({"error": "query parameter from is required"}).to_string());
+    };
+    let to = query.get("to").filter(|v| !v.is_empty()).map(String::as_str).unwrap_or("current");
+    let available = || {
+        list_versions(root, id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|v| v.timestamp)
+            .collect::<Vec<_>>()
+    };
+    let load = |which: &str| -> Option<Task> {
+        if which == "current" {
+            let (path, folder) = current.clone()?;
+            return parse_task(&path, &folder, cfg.status_mode, &opts.base_path).ok();
+        }
+        let path = version_path(root, id, which)?;
+        let folder = current.as_ref().map(|(_, f)| f.as_str()).unwrap_or("");
+        parse_task(&path, folder, cfg.status_mode, &opts.base_path).ok().map(|mut task| {
+            task.id = id.to_string();
+            task
+        })
+    };
+    let (Some(old), Some(new)) = (load(from), load(to)) else {
+        return respond_json(
+            StatusCode(404),
+            &serde_json::json!({"error": "version not found", "available": available()}).to_string(),
+        );
+    };
+    let mut payload = diff_tasks(&old, &new, from, to);
+    payload["id"] = serde_json::json!(id);
+    payload["from"] = serde_json::json!(from);
+    payload["to"] = serde_json::json!(to);
+    respond_json(StatusCode(200), &payload.to_string())
+}
+
+const JOURNAL_CAPACITY: usize = 100;
+/// The journal on disk, so undo survives a restart and `recover` can finish
+/// what a crash interrupted. A dot-directory, so never a column.
+const JOURNAL_DIR: &str = ".kanban-journal";
+const JOURNAL_FILE: &str = "journal.jsonl";
+/// The journal file before the last rotation; older ones are dropped.
+const JOURNAL_ROTATED_FILE: &str = "journal.1.jsonl";
+/// `journal.jsonl` is rotated once it grows past this.
+const JOURNAL_FILE_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+/// One side of a journaled mutation: the task file as it existed at
+/// `folder/id.md` with its full content. The content is left out of API
+/// listings but kept in the journal file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileState {
+    folder: String,
+    id: String,
+    #[serde(skip_serializing)]
+    content: String,
+}
+
+/// A reversible mutation. `before` is what existed prior to the change and
+/// `after` what it left behind; undoing writes `before` back and removes
+/// `after`, which is itself recorded as a new entry (so undo can be undone).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntry {
+    id: u64,
+    at: String,
+    action: String,
+    before: Option<FileState>,
+    after: Option<FileState>,
+}
+
+struct Journal {
+    next_id: AtomicU64,
+    entries: Mutex<VecDeque<JournalEntry>>,
+    /// The board's `JOURNAL_DIR`; `None` keeps the journal in memory.
+    dir: Option<PathBuf>,
+    /// Serializes appends to the journal file.
+    file_lock: Mutex<()>,
+}
+
+/// How far a journaled mutation got. An entry is written `pending` before
+/// its files change and followed by a `done` line once they have; `recover`
+/// ends the ones that stayed pending with `done` or `aborted`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JournalState {
+    Pending,
+    Done,
+    Aborted,
+}
+
+fn journal_line(entry: &JournalEntry, state: JournalState) -> serde_json::Value {
+    let side = |file: &Option<FileState>| {
+        file.as_ref().map(|file| serde_json::json!({ "folder": file.folder, "id": file.id, "content": file.content }))
+    };
+    serde_json::json!({
+        "id": entry.id,
+        "at": entry.at,
+        "action": entry.action,
+        "state": state,
+        "before": side(&entry.before),
+        "after": side(&entry.after),
+    })
+}
+
+/// Appends one line to the journal file and fsyncs it, so the line is on
+/// disk before the caller goes on. A file past `JOURNAL_FILE_MAX_BYTES` is
+/// rotated first.
+fn append_journal(journal: &Journal, line: &serde_json::Value) -> io::Result<()> {
+    let Some(dir) = &journal.dir else {
+        return Ok(());
+    };
+    let _guard = journal.file_lock.lock().unwrap();
+    fs::create_dir_all(dir)?;
+    let path = dir.join(JOURNAL_FILE);
+    if fs::metadata(&path).is_ok_and(|meta| meta.len() > JOURNAL_FILE_MAX_BYTES) {
+        fs::rename(&path, dir.join(JOURNAL_ROTATED_FILE))?;
+    }
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(&path)?;
+    file.write_all(format!("{}\n", line).as_bytes())?;
+    file.sync_data()
+}
+
+/// Every entry in `dir`, oldest first, with the last state recorded for it.
+/// A line holds a whole entry with its state, or only the `id` and the
+/// state it reached. Unreadable lines are skipped.
+fn read_journal(dir: &Path) -> Vec<(JournalEntry, JournalState)> {
+    let mut entries: Vec<(JournalEntry, JournalState)> = Vec::new();
+    for name in [JOURNAL_ROTATED_FILE, JOURNAL_FILE] {
+        let file = fs::File::open(dir.join(name)).ok().map(io::BufReader::new);
+        for line in file.into_iter().flat_map(|file| file.lines().map_while(Result::ok)) {
+            let Ok(line) = serde_json::from_str::<serde_json::Value>(&line) else {
+                continue;
+            };
+            let Ok(state) = JournalState::deserialize(&line["state"]) else {
+                continue;
+            };
+            if line.get("action").is_some() {
+                if let Ok(entry) = JournalEntry::deserialize(&line) {
+                    entries.push((entry, state));
+                }
+            } else if let Some((_, last)) = entries.iter_mut().rev().find(|(entry, _)| Some(entry.id) == line["id"].as_u64()) {
+                *last = state;
+            }
+        }
+    }
+    entries
+}
+
+/// The journal of the board at `root`, holding the newest completed entries
+/// from disk so undo works across restarts.
+fn open_journal(root: &Path) -> Journal {
+    let dir = root.join(JOURNAL_DIR);
+    let stored = read_journal(&dir);
+    let pending = stored.iter().filter(|(_, state)| *state == JournalState::Pending).count();
+    if pending > 0 {
+        eprintln!(
+            "Warning: {} journaled change(s) never completed; run `kanban-server recover --target {}` to finish or roll them back",
+            pending,
+            root.display()
+        );
+    }
+    let next_id = stored.iter().map(|(entry, _)| entry.id).max().unwrap_or(0) + 1;
+    let mut entries: VecDeque<JournalEntry> =
+        stored.into_iter().filter(|(_, state)| *state == JournalState::Done).map(|(entry, _)| entry).collect();
+    while entries.len() > JOURNAL_CAPACITY {
+        entries.pop_front();
+    }
+    Journal {
+        next_id: AtomicU64::new(next_id),
+        entries: Mutex::new(entries),
+        dir: Some(dir),
+        file_lock: Mutex::new(()),
+    }
+}
+
+fn file_state(folder: &str, id: &str, content: String) -> FileState {
+    FileState {
+        folder: folder.to_string(),
+        id: id.to_string(),
+        content,
+    }
+}
+
+fn new_journal_entry(journal: &Journal, action: &str, before: Option<FileState>, after: Option<FileState>) -> JournalEntry {
+    JournalEntry {
+        id: journal.next_id.fetch_add(1, Ordering::SeqCst),
+        at: now_iso(),
+        action: action.to_string(),
+        before,
+        after,
+    }
+}
+
+fn push_journal_entry(journal: &Journal, entry: JournalEntry) {
+    let mut entries = journal.entries.lock().unwrap();
+    entries.push_back(entry);
+    while entries.len() > JOURNAL_CAPACITY {
+        entries.pop_front();
+    }
+}
+
+/// Journals a mutation whose single file write has already happened (task
+/// creation), as `done`. A journal that cannot be written is only logged.
+fn journal_record(
+    journal: &Journal,
+    action: &str,
+    before: Option<FileState>,
+    after: Option<FileState>,
+) -> u64 {
+    let entry = new_journal_entry(journal, action, before, after);
+    let id = entry.id;
+    if let Err(err) = append_journal(journal, &journal_line(&entry, JournalState::Done)) {
+        eprintln!("Could not write {}: {}", JOURNAL_DIR, err);
+    }
+    push_journal_entry(journal, entry);
+    id
+}
+
+/// Runs the file changes of a mutation with its journal entry fsynced first
+/// as `pending`, then marked `done` once `apply` succeeded. When `apply`
+/// fails the entry stays pending for `recover`, since some of its changes
+/// may have happened; when the journal cannot be written nothing is
+/// changed.
+fn journaled<T>(
+    journal: &Journal,
+    action: &str,
+    before: Option<FileState>,
+    after: Option<FileState>,
+    apply: impl FnOnce() -> io::Result<T>,
+) -> io::Result<(T, u64)> {
+    let entry = new_journal_entry(journal, action, before, after);
+    append_journal(journal, &journal_line(&entry, JournalState::Pending))?;
+    expect_journaled(journal, &entry);
+    let value = apply()?;
+    let id = entry.id;
+    if let Err(err) = append_journal(journal, &serde_json::json!({ "id": id, "state": JournalState::Done })) {
+        eprintln!("Could not write {}: {}", JOURNAL_DIR, err);
+    }
+    push_journal_entry(journal, entry);
+    Ok((value, id))
+}
+
+/// Announces the file changes of a journaled mutation to `--watch`: the
+/// `after` file gets its content and a `before` file elsewhere goes away.
+fn expect_journaled(journal: &Journal, entry: &JournalEntry) {
+    let Some(root) = journal.dir.as_deref().and_then(Path::parent) else {
+        return;
+    };
+    let after = entry.after.as_ref().map(|after| task_path(root, &after.folder, &after.id));
+    if let Some(before) = &entry.before {
+        let path = task_path(root, &before.folder, &before.id);
+        if after.as_ref() != Some(&path) {
+            expect_change(&path, None);
+        }
+    }
+    if let (Some(path), Some(state)) = (&after, &entry.after) {
+        expect_change(path, Some(state.content.as_bytes()));
+    }
+}
+
+/// Reverts a journal entry (the newest when `entry_id` is None). Fails with
+/// 409 when the files no longer look like the mutation left them, e.g. the
+/// task was edited again or its id has since been reused.
+fn undo_entry(
+    root: &Path,
+    cfg: &BoardConfig,
+    opts: &BoardOptions,
+    journal: &Journal,
+    entry_id: Option<u64>,
+) -> Result<(JournalEntry, u64), (u16, String)> {
+    let entry = {
+        let entries = journal.entries.lock().unwrap();
+        match entry_id {
+            Some(id) => entries.iter().find(|e| e.id == id).cloned(),
+            None => entries.back().cloned(),
+        }
+    }
+    .ok_or((404, "nothing to undo".to_string()))?;
+    if let Some(after) = &entry.after {
+        let path = task_path(root, &after.folder, &after.id);
+        match fs::read_to_string(&path) {
+            Ok(current) if current == after.content => {}
+            Ok(_) => return Err((409, format!("task {} changed since this operation", after.id))),
+            Err(_) => return Err((409, format!("task {} no longer exists", after.id))),
+        }
+    }
+    if let Some(before) = &entry.before {
+        let reused = find_task_path(root, &before.id, cfg, opts).is_some_and(|(_, folder)| {
+            !entry
+                .after
+                .as_ref()
+                .is_some_and(|after| after.id == before.id && after.folder == folder)
+        });
+        if reused {
+            return Err((409, format!("task id {} has since been reused", before.id)));
+        }
+        if !cfg.columns.iter().any(|c| c.id == before.folder) {
+            return Err((409, format!("column {} no longer exists", before.folder)));
+        }
+    }
+    let revert = || {
+        if let Some(after) = &entry.after {
+            fs::remove_file(task_path(root, &after.folder, &after.id))?;
+        }
+        if let Some(before) = &entry.before {
+            let path = task_path(root, &before.folder, &before.id);
+            if let Err(err) = fs::write(&path, &before.content) {
+                if let Some(after) = &entry.after {
+                    let _ = fs::write(task_path(root, &after.folder, &after.id), &after.content);
+                }
+                return Err(err);
+            }
+        }
+        Ok(())
+    };
+    let action = format!("undo {}", entry.action);
+    let ((), new_id) = journaled(journal, &action, entry.after.clone(), entry.before.clone(), revert)
+        .map_err(|err| (500, err.to_string()))?;
+    Ok((entry, new_id))
+}
+
+/// What `recover` makes of a journal entry that stayed pending.
+#[derive(Debug, PartialEq)]
+enum Recovery {
+    /// Every change reached disk; only the `done` line is missing.
+    Applied,
+    /// No change reached disk; the entry is dropped.
+    NotStarted,
+    /// Some changes reached disk; the rest are made from the entry.
+    Partial,
+    /// This file holds content the entry does not know, e.g. it was edited
+    /// after the crash. Left alone.
+    Conflict(PathBuf),
+}
+
+fn classify_pending(root: &Path, entry: &JournalEntry) -> Recovery {
+    let path = |file: &FileState| task_path(root, &file.folder, &file.id);
+    let before = entry.before.as_ref().map(|file| (path(file), file.content.as_str()));
+    let after = entry.after.as_ref().map(|file| (path(file), file.content.as_str()));
+    let known: Vec<&str> = before.iter().chain(&after).map(|(_, content)| *content).collect();
+    for (path, _) in before.iter().chain(&after) {
+        if let Ok(current) = fs::read_to_string(path) {
+            if !known.contains(&current.as_str()) {
+                return Recovery::Conflict(path.clone());
+            }
+        }
+    }
+    let holds = |(path, content): &(PathBuf, &str)| fs::read_to_string(path).is_ok_and(|current| current == *content);
+    let same_path = matches!((&before, &after), (Some((b, _)), Some((a, _))) if a == b);
+    let applied = after.as_ref().is_none_or(holds) && before.as_ref().is_none_or(|(path, _)| same_path || !path.exists());
+    let untouched = before.as_ref().is_none_or(holds) && after.as_ref().is_none_or(|(path, _)| same_path || !path.exists());
+    if applied {
+        Recovery::Applied
+    } else if untouched {
+        Recovery::NotStarted
+    } else {
+        Recovery::Partial
+    }
+}
+
+/// Makes the rest of a partly applied entry's changes: writes `after` and
+/// removes `before` when it lived elsewhere.
+fn finish_pending(root: &Path, entry: &JournalEntry) -> io::Result<()> {
+    let after_path = entry.after.as_ref().map(|after| task_path(root, &after.folder, &after.id));
+    if let (Some(after), Some(path)) = (&entry.after, &after_path) {
+        fs::write(path, &after.content)?;
+    }
+    if let Some(before) = &entry.before {
+        let path = task_path(root, &before.folder, &before.id);
+        if after_path.as_ref() != Some(&path) && path.exists() {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// `recover`: lists the journal entries that never recorded completion and,
+/// with `apply`, ends each one. An entry that changed some files is
+/// finished, one that changed none is rolled back (dropped); conflicts are
+/// left for a person to sort out. Returns the exit code: 1 when anything is
+/// left unresolved.
+fn recover(root: &Path, apply: bool) -> i32 {
+    let journal = Journal {
+        next_id: AtomicU64::new(0),
+        entries: Mutex::new(VecDeque::new()),
+        dir: Some(root.join(JOURNAL_DIR)),
+        file_lock: Mutex::new(()),
+    };
+    let pending: Vec<JournalEntry> = read_journal(&root.join(JOURNAL_DIR))
+        .into_iter()
+        .filter(|(_, state)| *state == JournalState::Pending)
+        .map(|(entry, _)| entry)
+        .collect();
+    if pending.is_empty() {
+        println!("No unfinished changes in {}", root.join(JOURNAL_DIR).display());
+        return 0;
+    }
+    let mut unresolved = 0;
+    for entry in &pending {
+        let describe = |file: &Option<FileState>| {
+            file.as_ref().map(|file| format!("{}/{}.md", file.folder, file.id)).unwrap_or_else(|| "-".to_string())
+        };
+        let label = format!("#{} {} {} -> {}", entry.id, entry.action, describe(&entry.before), describe(&entry.after));
+        let recovery = classify_pending(root, entry);
+        let (found, state) = match &recovery {
+            Recovery::Applied => ("fully applied", JournalState::Done),
+            Recovery::NotStarted => ("never applied", JournalState::Aborted),
+            Recovery::Partial => ("partly applied", JournalState::Done),
+            Recovery::Conflict(path) => {
+                println!("{}: {} was changed since; left alone", label, path.display());
+                unresolved += 1;
+                continue;
+            }
+        };
+        let action = if state == JournalState::Done { "finish" } else { "roll back" };
+        if !apply {
+            println!("{}: {}, would {}", label, found, action);
+            unresolved += 1;
+            continue;
+        }
+        let mut result = if recovery == Recovery::Partial { finish_pending(root, entry) } else { Ok(()) };
+        // A finished title change also carries history, attachments and
+        // the alias over, which the crash may have cut off.
+        if let (Some(before), Some(after)) = (&entry.before, &entry.after) {
+            if state == JournalState::Done && before.id != after.id {
+                result = result.and_then(|()| finish_rename(root, &before.id, &after.id));
+            }
+        }
+        match result.and_then(|()| append_journal(&journal, &serde_json::json!({ "id": entry.id, "state": state }))) {
+            Ok(()) => println!("{}: {}, {}", label, found, if state == JournalState::Done { "finished" } else { "rolled back" }),
+            Err(err) => {
+                println!("{}: {}, could not {}: {}", label, found, action, err);
+                unresolved += 1;
+            }
+        }
+    }
+    if unresolved > 0 && !apply {
+        println!("Run again with --apply to resolve them.");
+    }
+    i32::from(unresolved > 0)
+}
+
+/// `--debug-journal` files are rotated once they grow past this; the
+/// current file and `DEBUG_JOURNAL_ROTATIONS` older ones are kept.
+const DEBUG_JOURNAL_MAX_BYTES: u64 = 8 * 1024 * 1024;
+const DEBUG_JOURNAL_ROTATIONS: usize = 4;
+/// Request bodies are cut to this many bytes in the debug journal.
+const DEBUG_JOURNAL_BODY_MAX_BYTES: usize = 64 * 1024;
+/// Headers whose values never reach the debug journal, besides any whose
+/// name mentions a token or a secret.
+const DEBUG_JOURNAL_REDACTED_HEADERS: [&str; 4] = ["authorization", "proxy-authorization", "cookie", "x-api-key"];
+
+/// The `--debug-journal`: every request that may change the board, kept to
+/// work out later what happened to a task. A request gets a `request` line,
+/// flushed before it is handled, and a `done` line once it is answered, so
+/// one a crash cut off still shows up. Journaled requests run one at a time,
+/// which lets each `done` line name exactly the files its request changed.
+struct DebugJournal {
+    path: PathBuf,
+    root: PathBuf,
+    max_bytes: u64,
+    state: Mutex<DebugJournalState>,
+}
+
+struct DebugJournalState {
+    file: io::BufWriter<fs::File>,
+    size: u64,
+    seq: u64,
+    /// The board's files and their content fingerprints as of the last
+    /// journaled request; outside edits in between are taken in before the
+    /// next one starts, so they are not blamed on it.
+    stamps: FileStamps,
+    fingerprints: BTreeMap<PathBuf, u64>,
+}
+
+/// A journaled request between its `request` and its `done` line. Holds
+/// the journal until it is finished.
+struct DebugRecord<'a> {
+    journal: &'a DebugJournal,
+    state: std::sync::MutexGuard<'a, DebugJournalState>,
+    seq: u64,
+    started: Instant,
+    activity_len: u64,
+}
+
+/// `path` with `.n` appended: where the `n`th rotation of a debug journal
+/// goes.
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// The files of the debug journal at `path`, oldest first.
+fn debug_journal_files(path: &Path) -> Vec<PathBuf> {
+    (1..=DEBUG_JOURNAL_ROTATIONS).rev().map(|n| rotated_path(path, n)).chain([path.to_path_buf()]).collect()
+}
+
+fn redacted_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    DEBUG_JOURNAL_REDACTED_HEADERS.contains(&name.as_str()) || name.contains("token") || name.contains("secret")
+}
+
+/// Activity log entries appended since the log was `from` bytes long.
+fn activity_since(root: &Path, from: u64) -> Vec<serde_json::Value> {
+    let Ok(mut file) = fs::File::open(root.join(ACTIVITY_FILE)) else {
+        return Vec::new();
+    };
+    if file.seek(SeekFrom::Start(from)).is_err() {
+        return Vec::new();
+    }
+    io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+impl DebugJournal {
+    /// Opens the journal at `path` for the board at `root`. Numbering goes
+    /// on from the newest entry kept, and a line a crash left half-written
+    /// is ended first so it cannot swallow the next one.
+    fn open(path: &Path, root: &Path, max_bytes: u64) -> io::Result<DebugJournal> {
+        let path = absolute_path(path);
+        let dir = path.parent().unwrap_or(Path::new("/"));
+        fs::create_dir_all(dir)?;
+        let path = fs::canonicalize(dir)?.join(path.file_name().unwrap_or_default());
+        let seq = debug_journal_files(&path)
+            .into_iter()
+            .filter_map(|file| fs::File::open(file).ok())
+            .flat_map(|file| io::BufReader::new(file).lines().map_while(Result::ok))
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(&line).ok()?["seq"].as_u64())
+            .max()
+            .unwrap_or(0);
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        let mut size = file.metadata()?.len();
+        if size > 0 {
+            let mut last = [0u8];
+            let mut reader = fs::File::open(&path)?;
+            reader.seek(SeekFrom::End(-1))?;
+            reader.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                file.write_all(b"\n")?;
+                size += 1;
+            }
+        }
+        let journal = DebugJournal {
+            path,
+            root: root.to_path_buf(),
+            max_bytes,
+            state: Mutex::new(DebugJournalState {
+                file: io::BufWriter::new(file),
+                size,
+                seq,
+                stamps: FileStamps::new(),
+                fingerprints: BTreeMap::new(),
+            }),
+        };
+        journal.take_in_changes(&mut journal.state.lock().unwrap());
+        Ok(journal)
+    }
+
+    /// The board files `--watch` would look at, without the journal's own.
+    fn board_stamps(&self) -> FileStamps {
+        let excludes: Vec<String> = WATCH_DEFAULT_EXCLUDES.iter().map(|name| name.to_string()).collect();
+        let own = debug_journal_files(&self.path);
+        let mut stamps = stamp_files(&self.root, &excludes);
+        stamps.retain(|path, _| !own.contains(path));
+        stamps
+    }
+
+    /// Restamps the board and fingerprints the files changed since the last
+    /// look.
+    fn take_in_changes(&self, state: &mut DebugJournalState) {
+        let stamps = self.board_stamps();
+        for path in changed_files(&state.stamps, &stamps) {
+            match fs::read(&path) {
+                Ok(bytes) => state.fingerprints.insert(path, content_hash(&bytes[..])),
+                Err(_) => state.fingerprints.remove(&path),
+            };
+        }
+        state.stamps = stamps;
+    }
+
+    /// Appends one line and flushes it. Journaling is best effort: a
+    /// failure is logged and the request goes on.
+    fn write_line(&self, state: &mut DebugJournalState, line: &serde_json::Value) {
+        let text = format!("{}\n", line);
+        let written = (|| {
+            if state.size > 0 && state.size + text.len() as u64 > self.max_bytes {
+                self.rotate(state)?;
+            }
+            state.file.write_all(text.as_bytes())?;
+            state.file.flush()
+        })();
+        match written {
+            Ok(()) => state.size += text.len() as u64,
+            Err(err) => eprintln!("Could not write {}: {}", self.path.display(), err),
+        }
+    }
+
+    /// Shifts every file one rotation back, dropping the oldest, and starts
+    /// a new current file.
+    fn rotate(&self, state: &mut DebugJournalState) -> io::Result<()> {
+        state.file.flush()?;
+        for n in (1..DEBUG_JOURNAL_ROTATIONS).rev() {
+            let from = rotated_path(&self.path, n);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        state.file = io::BufWriter::new(fs::OpenOptions::new().create(true).append(true).open(&self.path)?);
+        state.size = 0;
+        Ok(())
+    }
+
+    /// Writes the `request` line of a request about to be handled, with
+    /// sensitive header values redacted and the body cut to
+    /// `DEBUG_JOURNAL_BODY_MAX_BYTES`.
+    fn begin(&self, method: &Method, url: &str, client: Option<IpAddr>, headers: &[Header], body: &[u8]) -> DebugRecord<'_> {
+        let mut state = self.state.lock().unwrap();
+        self.take_in_changes(&mut state);
+        state.seq += 1;
+        let seq = state.seq;
+        let headers: serde_json::Map<String, serde_json::Value> = headers
+            .iter()
+            .map(|header| {
+                let name = header.field.as_str().as_str();
+                let value = if redacted_header(name) { "[redacted]" } else { header.value.as_str() };
+                (name.to_string(), serde_json::json!(value))
+            })
+            .collect();
+        let shown = &body[..body.len().min(DEBUG_JOURNAL_BODY_MAX_BYTES)];
+        let mut line = serde_json::json!({
+            "seq": seq,
+            "phase": "request",
+            "at": now_iso(),
+            "method": method.to_string(),
+            "url": url,
+            "client": client,
+            "headers": headers,
+            "body": String::from_utf8_lossy(shown),
+            "body_bytes": body.len(),
+        });
+        if shown.len() < body.len() {
+            line["body_truncated"] = serde_json::json!(true);
+        }
+        self.write_line(&mut state, &line);
+        let activity_len = fs::metadata(self.root.join(ACTIVITY_FILE)).map(|meta| meta.len()).unwrap_or(0);
+        DebugRecord { journal: self, state, seq, started: Instant::now(), activity_len }
+    }
+}
+
+impl DebugRecord<'_> {
+    /// Writes the `done` line: the status, the activity entries the request
+    /// wrote (the action it resolved to) and every board file it added,
+    /// changed or removed, with content fingerprints before and after
+    /// (`null` where the file did not exist).
+    fn finish(mut self, status: u16) {
+        let journal = self.journal;
+        let stamps = journal.board_stamps();
+        let mut files = Vec::new();
+        for path in changed_files(&self.state.stamps, &stamps) {
+            let before = self.state.fingerprints.get(&path).copied();
+            let after = fs::read(&path).ok().map(|bytes| content_hash(&bytes[..]));
+            match after {
+                Some(fingerprint) => self.state.fingerprints.insert(path.clone(), fingerprint),
+                None => self.state.fingerprints.remove(&path),
+            };
+            let fingerprint = |value: Option<u64>| value.map(|value| format!("{:016x}", value));
+            files.push(serde_json::json!({
+                "path": path.strip_prefix(&journal.root).unwrap_or(&path).to_string_lossy().replace('\\', "/"),
+                "before": fingerprint(before),
+                "after": fingerprint(after),
+            }));
+        }
+        self.state.stamps = stamps;
+        let line = serde_json::json!({
+            "seq": self.seq,
+            "phase": "done",
+            "at": now_iso(),
+            "status": status,
+            "ms": self.started.elapsed().as_millis() as u64,
+            "activity": activity_since(&journal.root, self.activity_len),
+            "files": files,
+        });
+        journal.write_line(&mut self.state, &line);
+    }
+}
+
+/// Whether a debug journal line concerns task `id`: its URL or body names
+/// the task, its activity does, or it changed one of the task's files.
+fn debug_entry_names_task(entry: &serde_json::Value, id: &str) -> bool {
+    let in_url = entry["url"]
+        .as_str()
+        .is_some_and(|url| url.split(['?', '#']).next().unwrap_or("").split('/').any(|segment| segment == id));
+    let in_body = entry["body"]
+        .as_str()
+        .and_then(|body| serde_json::from_str::<serde_json::Value>(body).ok())
+        .is_some_and(|body| body["id"] == id);
+    let in_activity = entry["activity"].as_array().is_some_and(|activity| {
+        activity
+            .iter()
+            .any(|item| item["task"] == id || item["tasks"].as_array().is_some_and(|tasks| tasks.iter().any(|task| task == id)))
+    });
+    let in_files = entry["files"].as_array().is_some_and(|files| {
+        files
+            .iter()
+            .any(|file| file["path"].as_str().and_then(|path| Path::new(path).file_stem()).and_then(|stem| stem.to_str()) == Some(id))
+    });
+    in_url || in_body || in_activity || in_files
+}
+
+/// `journal grep --task <id>`: copies both lines of every journaled request
+/// concerning task `id` to `out`, oldest first across rotations, and
+/// returns how many requests matched. Unreadable lines are skipped.
+fn grep_debug_journal(path: &Path, id: &str, out: &mut impl Write) -> io::Result<usize> {
+    let lines = || {
+        debug_journal_files(path)
+            .into_iter()
+            .filter_map(|file| fs::File::open(file).ok())
+            .flat_map(|file| io::BufReader::new(file).lines().map_while(Result::ok))
+            .filter_map(|line| Some((serde_json::from_str::<serde_json::Value>(&line).ok()?, line)))
+    };
+    let matched: HashSet<u64> = lines()
+        .filter(|(entry, _)| debug_entry_names_task(entry, id))
+        .filter_map(|(entry, _)| entry["seq"].as_u64())
+        .collect();
+    for (entry, line) in lines() {
+        if entry["seq"].as_u64().is_some_and(|seq| matched.contains(&seq)) {
+            writeln!(out, "{}", line)?;
+        }
+    }
+    Ok(matched.len())
+}
+
+/// How long an editing lock lasts unless its holder posts it again.
+const LOCK_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// An advisory editing lock. Locks live in memory only.
+#[derive(Debug, Clone, Serialize)]
+struct TaskLock {
+    locked_by: String,
+    locked_until: String,
+    #[serde(skip)]
+    expires: OffsetDateTime,
+}
+
+#[derive(Default)]
+struct TaskLocks {
+    entries: Mutex<HashMap<String, TaskLock>>,
+}
+
+/// The unexpired lock on `id`, dropping an expired one.
+fn active_lock(locks: &TaskLocks, id: &str, now: OffsetDateTime) -> Option<TaskLock> {
+    let mut entries = locks.entries.lock().unwrap();
+    match entries.get(id) {
+        Some(lock) if lock.expires > now => Some(lock.clone()),
+        Some(_) => {
+            entries.remove(id);
+            None
+        }
+        None => None,
+    }
+}
+
+/// The lock on `id` when someone other than `user` holds it.
+fn foreign_lock(locks: &TaskLocks, id: &str, user: &str, now: OffsetDateTime) -> Option<TaskLock> {
+    active_lock(locks, id, now).filter(|lock| lock.locked_by != user)
+}
+
+/// Takes or refreshes `user`'s lock on `id`; fails with the current lock
+/// when another user holds it.
+fn acquire_lock(locks: &TaskLocks, id: &str, user: &str, now: OffsetDateTime) -> Result<TaskLock, TaskLock> {
+    if let Some(held) = foreign_lock(locks, id, user, now) {
+        return Err(held);
+    }
+    let expires = now + LOCK_TTL;
+    let lock = TaskLock {
+        locked_by: user.to_string(),
+        locked_until: format_timestamp(expires),
+        expires,
+    };
+    locks.entries.lock().unwrap().insert(id.to_string(), lock.clone());
+    Ok(lock)
+}
+
+/// Fills in `locked_by`/`locked_until` for locked tasks.
+fn set_locks<'a>(tasks: impl IntoIterator<Item = &'a mut Task>, locks: &TaskLocks, now: OffsetDateTime) {
+    for task in tasks {
+        if let Some(lock) = active_lock(locks, &task.id, now) {
+            task.locked_by = Some(lock.locked_by);
+            task.locked_until = Some(lock.locked_until);
+        }
+    }
+}
+
+fn locked_response(lock: &TaskLock) -> Response<std::io::Cursor<Vec<u8>>> {
+    respond_json(
+        StatusCode(423),
+        &serde_json::json!({
+            "error": format!("Task is being edited by {} (add ?override=true to proceed anyway)", lock.locked_by),
+            "locked_by": lock.locked_by,
+            "locked_until": lock.locked_until,
+        })
+        .to_string(),
+    )
+}
+
+/// `POST`/`DELETE /api/tasks/{id}/lock`. Taking a lock needs an
+/// `X-Kanban-User` header; `force` lets another user take over or release it.
+#[allow(clippy::too_many_arguments)]
+fn handle_lock(
+    root: &Path,
+    cfg: &BoardConfig,
+    opts: &BoardOptions,
+    locks: &TaskLocks,
+    events: &EventBus,
+    id: &str,
+    method: &Method,
+    user: Option<&str>,
+    force: bool,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    if find_task_path(root, id, cfg, opts).is_none() {
+        return task_not_found();
+    }
+    let now = OffsetDateTime::now_utc();
+    match (method, user) {
+        (Method::Post, None) => {
+            respond_json(StatusCode(400), &serde_json::json!({ "error": "X-Kanban-User header required" }).to_string())
+        }
+        (Method::Post, Some(user)) => {
+            if force {
+                locks.entries.lock().unwrap().remove(id);
+            }
+            match acquire_lock(locks, id, user, now) {
+                Ok(lock) => {
+                    publish_event(events, "task_locked", serde_json::json!({ "id": id, "locked_by": lock.locked_by, "locked_until": lock.locked_until }));
+                    respond_json(StatusCode(200), &serde_json::json!(lock).to_string())
+                }
+                Err(held) => locked_response(&held),
+            }
+        }
+        (Method::Delete, user) => {
+            let user = user.unwrap_or("server");
+            match (active_lock(locks, id, now), force) {
+                (Some(held), false) if held.locked_by != user => locked_response(&held),
+                (Some(_), _) => {
+                    locks.entries.lock().unwrap().remove(id);
+                    publish_event(events, "task_unlocked", serde_json::json!({ "id": id, "by": user }));
+                    respond_json(StatusCode(204), "")
+                }
+                (None, _) => respond_json(StatusCode(204), ""),
+            }
+        }
+        _ => respond_json(StatusCode(405), &serde_json::json!({ "error": "method not allowed" }).to_string()),
+    }
+}
+
+fn handle_undo(
+    root: &Path,
+    journal: &Journal,
+    entry_id: Option<u64>,
+    update_state: &Arc<UpdateState>,
+    opts: &BoardOptions,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let cfg = match refresh_config(root) {
+        Ok(cfg) => cfg,
+        Err(msg) => return respond_json(StatusCode(500), &serde_json::json!({ "error": msg }).to_string()),
+    };
+    match undo_entry(root, &cfg, opts, journal, entry_id) {
+        Ok((undone, journal_id)) => {
+            notify_update(update_state);
+            respond_json(
+                StatusCode(200),
+                &serde_json::json!({ "undone": undone, "journal_id": journal_id }).to_string(),
+            )
+        }
+        Err((status, msg)) => respond_json(StatusCode(status), &serde_json::json!({ "error": msg }).to_string()),
+    }
+}
+
+/// Scans every column folder. With a `cache`, files whose size and mtime did
+/// not change are not re-parsed, and entries for vanished files are dropped.
+fn load_all_tasks(
+    root: &Path,
+    config: &BoardConfig,
+    opts: &BoardOptions,
+    cache: Option<&TaskCache>,
+) -> io::Result<HashMap<String, Vec<Task>>> {
+    load_tasks(root, config, opts, cache, true, &mut Vec::new())
+}
+
+/// `load_all_tasks` for a listing: on a `--manifest` server, descriptions
+/// not held in memory are left empty unless `filter` searches them, and
+/// `tasks_payload` reads them for the tasks it returns. Also returns an
+/// `unreadable_task` warning per task file that could not be read.
+fn load_listing_tasks(
+    root: &Path,
+    config: &BoardConfig,
+    opts: &BoardOptions,
+    cache: &TaskCache,
+    filter: &TaskFilter,
+) -> io::Result<(Folders, Vec<Warning>)> {
+    let mut skipped = Vec::new();
+    let folders = load_tasks(root, config, opts, Some(cache), filter.q.is_some(), &mut skipped)?;
+    Ok((folders, skipped))
+}
+
+fn load_tasks(
+    root: &Path,
+    config: &BoardConfig,
+    opts: &BoardOptions,
+    cache: Option<&TaskCache>,
+    descriptions: bool,
+    skipped: &mut Vec<Warning>,
+) -> io::Result<HashMap<String, Vec<Task>>> {
+    if let Some(cache) = cache.filter(|cache| cache.manifest.is_some()) {
+        return manifest_tasks(root, config, opts, cache, descriptions);
+    }
+    let mut out: HashMap<String, Vec<Task>> = HashMap::new();
+    let ignored = read_ignore_file(root);
+    for column in &config.columns {
+        let mut tasks = if is_internal_dir(&column.id, &ignored) {
+            Vec::new()
+        } else {
+            scan_column_reporting(root, &column.id, config.status_mode, opts, cache, skipped)?
+        };
+        for task in &mut tasks {
+            type_fields(&config.fields, task);
+        }
+        out.insert(column.id.clone(), tasks);
+    }
+    if let Some(cache) = cache {
+        let mut entries = cache.entries.lock().unwrap();
+        entries.retain(|_, cached| {
+            out.get(&cached.task.folder)
+                .map(|tasks| tasks.iter().any(|t| t.id == cached.task.id))
+                .unwrap_or(false)
+        });
+    }
+    Ok(out)
+}
+
+fn scan_column(root: &Path, folder: &str, mode: StatusMode, opts: &BoardOptions, cache: Option<&TaskCache>) -> io::Result<Vec<Task>> {
+    scan_column_reporting(root, folder, mode, opts, cache, &mut Vec::new())
+}
+
+/// `scan_column`, adding an `unreadable_task` warning to `skipped` for each
+/// task file it had to leave out.
+fn scan_column_reporting(
+    root: &Path,
+    folder: &str,
+    mode: StatusMode,
+    opts: &BoardOptions,
+    cache: Option<&TaskCache>,
+    skipped: &mut Vec<Warning>,
+) -> io::Result<Vec<Task>> {
+    let mut tasks = Vec::new();
+    let dir = root.join(folder);
+    if !dir.exists() {
+        return Ok(tasks);
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let Some(id) = task_file_id(&path, opts.follow_symlinks) else {
+            continue;
+        };
+        let parsed = match cache {
+            // fs::metadata follows a symlinked task file, so edits to its
+            // target invalidate the cache.
+            Some(cache) => cached_parse_task(cache, &path, folder, mode, opts, fs::metadata(&path).ok()),
+            None => parse_task(&path, folder, mode, &opts.base_path),
+        };
+        match parsed {
+            Ok(task) => tasks.push(task),
+            Err(err) => {
+                let message = format!("{}/{}.md could not be read and was left out: {}", folder, id, err);
+                skipped.push(Warning::new("unreadable_task", message).task(id).column(folder));
+            }
+        }
+    }
+    Ok(tasks)
+}
+
+/// Parsed tasks keyed by file path. An entry is valid while the file's size and
+/// modification time match what was recorded when it was parsed.
+struct TaskCache {
+    entries: Mutex<HashMap<PathBuf, CachedTask>>,
+    /// Set when the server runs with `--manifest`.
+    manifest: Option<ManifestState>,
+}
+
+struct CachedTask {
+    modified: Option<SystemTime>,
+    len: u64,
+    mode: StatusMode,
+    task: Task,
+}
+
+fn cached_parse_task(
+    cache: &TaskCache,
+    path: &Path,
+    folder: &str,
+    mode: StatusMode,
+    opts: &BoardOptions,
+    metadata: Option<fs::Metadata>,
+) -> io::Result<Task> {
+    let (modified, len) = match &metadata {
+        Some(meta) => (meta.modified().ok(), meta.len()),
+        None => (None, 0),
+    };
+    if modified.is_some() {
+        let entries = cache.entries.lock().unwrap();
+        if let Some(cached) = entries.get(path) {
+            if cached.modified == modified && cached.len == len && cached.mode == mode && cached.task.folder == folder {
+                return Ok(cached.task.clone());
+            }
+        }
+    }
+    let task = parse_task(path, folder, mode, &opts.base_path)?;
+    cache.entries.lock().unwrap().insert(
+        path.to_path_buf(),
+        CachedTask {
+            modified,
+            len,
+            mode,
+            task: task.clone(),
+        },
+    );
+    Ok(task)
+}
+
+/// Parsed task headers of the whole board in one file, so listings on a
+/// network share need not read every task file (`--manifest`).
+const MANIFEST_FILE: &str = ".kanban-manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+
+/// How often a `--manifest` server checks every task file's size and
+/// modification time for changes made outside the server.
+const MANIFEST_VERIFY_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    status_mode: StatusMode,
+    /// The column folders the entries cover.
+    folders: Vec<String>,
+    /// Keyed by the task file's path below the root, `folder/id.md`.
+    tasks: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManifestEntry {
+    /// Nanoseconds since the Unix epoch.
+    modified: Option<u64>,
+    len: u64,
+    description_hash: String,
+    /// The parsed task, without its description.
+    task: Task,
+}
+
+struct ManifestState {
+    sync: Mutex<ManifestSync>,
+    /// Bumped by every change the server makes; a new version makes the
+    /// next listing check the files.
+    updates: Arc<UpdateState>,
+}
+
+struct ManifestSync {
+    manifest: Manifest,
+    version: u64,
+    verified_at: Option<Instant>,
+}
+
+impl ManifestState {
+    fn open(root: &Path, updates: Arc<UpdateState>) -> ManifestState {
+        let sync = ManifestSync { manifest: read_manifest(root), version: 0, verified_at: None };
+        ManifestState { sync: Mutex::new(sync), updates }
+    }
+}
+
+fn read_manifest(root: &Path) -> Manifest {
+    fs::read_to_string(root.join(MANIFEST_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str::<Manifest>(&text).ok())
+        .filter(|manifest| manifest.version == MANIFEST_VERSION)
+        .unwrap_or_default()
+}
+
+fn write_manifest(root: &Path, manifest: &Manifest) -> io::Result<()> {
+    let staged = root.join(format!("{}.tmp", MANIFEST_FILE));
+    fs::write(&staged, serde_json::to_string(manifest).unwrap_or_default())?;
+    fs::rename(&staged, root.join(MANIFEST_FILE))
+}
+
+fn description_hash(description: &str) -> String {
+    format!("{:016x}", content_hash(description))
+}
+
+fn nanos_since_epoch(time: SystemTime) -> Option<u64> {
+    time.duration_since(SystemTime::UNIX_EPOCH).ok().and_then(|d| u64::try_from(d.as_nanos()).ok())
+}
+
+/// The folders a manifest for `config` covers: every column but internal ones.
+fn manifest_folders(root: &Path, config: &BoardConfig) -> Vec<String> {
+    let ignored = read_ignore_file(root);
+    let mut folders: Vec<String> =
+        config.columns.iter().filter(|c| !is_internal_dir(&c.id, &ignored)).map(|c| c.id.clone()).collect();
+    folders.sort();
+    folders
+}
+
+/// Brings `manifest` in line with the task files: re-parses files whose size
+/// or modification time differ from their entry and drops entries of files
+/// and columns that are gone. Returns whether anything changed.
+fn sync_manifest(root: &Path, config: &BoardConfig, opts: &BoardOptions, manifest: &mut Manifest, cache: Option<&TaskCache>) -> io::Result<bool> {
+    let folders = manifest_folders(root, config);
+    let mut changed = manifest.version != MANIFEST_VERSION || manifest.status_mode != config.status_mode;
+    if changed {
+        *manifest = Manifest { version: MANIFEST_VERSION, status_mode: config.status_mode, ..Default::default() };
+    }
+    let mut seen = HashSet::new();
+    for folder in &folders {
+        let dir = root.join(folder);
+        if !dir.exists() {
+            continue;
+        }
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Some(id) = task_file_id(&path, opts.follow_symlinks) else {
+                continue;
+            };
+            let Ok(meta) = fs::metadata(&path) else {
+                continue;
+            };
+            let key = format!("{}/{}.md", folder, id);
+            let (modified, len) = (meta.modified().ok().and_then(nanos_since_epoch), meta.len());
+            let fresh = manifest.tasks.get(&key).is_some_and(|e| modified.is_some() && e.modified == modified && e.len == len);
+            if !fresh {
+                let Some(entry) = manifest_entry(&path, folder, config, opts, cache, meta) else {
+                    continue;
+                };
+                manifest.tasks.insert(key.clone(), entry);
+                changed = true;
+            }
+            seen.insert(key);
+        }
+    }
+    let before = manifest.tasks.len();
+    manifest.tasks.retain(|key, _| seen.contains(key));
+    changed |= manifest.tasks.len() != before || manifest.folders != folders;
+    manifest.folders = folders;
+    Ok(changed)
+}
+
+/// The manifest entry for the task file at `path`, None when it cannot be parsed.
+fn manifest_entry(path: &Path, folder: &str, config: &BoardConfig, opts: &BoardOptions, cache: Option<&TaskCache>, meta: fs::Metadata) -> Option<ManifestEntry> {
+    let (modified, len) = (meta.modified().ok().and_then(nanos_since_epoch), meta.len());
+    let parsed = match cache {
+        Some(cache) => cached_parse_task(cache, path, folder, config.status_mode, opts, Some(meta)),
+        None => parse_task(path, folder, config.status_mode, &opts.base_path),
+    };
+    let mut task = parsed.ok()?;
+    let description_hash = description_hash(&std::mem::take(&mut task.description));
+    Some(ManifestEntry { modified, len, description_hash, task })
+}
+
+/// `doctor`'s check of the manifest: how many entries differ from the task
+/// files, or None when the board has no manifest.
+fn manifest_drift(root: &Path, config: &BoardConfig, opts: &BoardOptions) -> Option<usize> {
+    let stored = fs::read_to_string(root.join(MANIFEST_FILE)).ok()?;
+    let stored = serde_json::from_str::<Manifest>(&stored).unwrap_or_default();
+    let mut fresh = Manifest::default();
+    if sync_manifest(root, config, opts, &mut fresh, None).is_err() {
+        return Some(stored.tasks.len().max(1));
+    }
+    if stored.version != fresh.version || stored.status_mode != fresh.status_mode {
+        return Some(fresh.tasks.len().max(1));
+    }
+    let keys: HashSet<&String> = stored.tasks.keys().chain(fresh.tasks.keys()).collect();
+    let same = |key: &String| match (stored.tasks.get(key), fresh.tasks.get(key)) {
+        (Some(a), Some(b)) => serde_json::json!(a) == serde_json::json!(b),
+        _ => false,
+    };
+    Some(keys.into_iter().filter(|key| !same(key)).count())
+}
+
+/// Writes a manifest built from scratch; returns the number of tasks in it.
+fn rebuild_manifest(root: &Path, config: &BoardConfig, opts: &BoardOptions) -> io::Result<usize> {
+    let mut manifest = Manifest::default();
+    sync_manifest(root, config, opts, &mut manifest, None)?;
+    write_manifest(root, &manifest)?;
+    Ok(manifest.tasks.len())
+}
+
+/// Checks a `--manifest` server's manifest against the task files when the
+/// server changed a task since the last check, when `force` is set or when
+/// the board's columns changed, and writes it back if anything differed.
+fn refresh_manifest(root: &Path, config: &BoardConfig, opts: &BoardOptions, cache: &TaskCache, force: bool) -> io::Result<()> {
+    let Some(state) = &cache.manifest else {
+        return Ok(());
+    };
+    let mut sync = state.sync.lock().unwrap();
+    let version = state.updates.version.load(Ordering::SeqCst);
+    let stale = force
+        || sync.version != version
+        || sync.verified_at.is_none()
+        || sync.manifest.status_mode != config.status_mode
+        || sync.manifest.folders != manifest_folders(root, config);
+    if !stale {
+        return Ok(());
+    }
+    if sync_manifest(root, config, opts, &mut sync.manifest, Some(cache))? {
+        write_manifest(root, &sync.manifest)?;
+    }
+    sync.version = version;
+    sync.verified_at = Some(Instant::now());
+    let paths: HashSet<PathBuf> = sync.manifest.tasks.keys().map(|key| root.join(key)).collect();
+    cache.entries.lock().unwrap().retain(|path, _| paths.contains(path));
+    Ok(())
+}
+
+/// Writes the manifest entries of the task files a server change just wrote
+/// or removed, given as `(folder, id)`, so the next listing need not check
+/// every file. Called after `notify_update`: when that change is the only
+/// one since the last check, the manifest counts as checked again; otherwise
+/// the next listing checks the files as before.
+fn record_manifest_change(root: &Path, config: &BoardConfig, opts: &BoardOptions, cache: &TaskCache, files: &[(&str, &str)]) {
+    let Some(state) = &cache.manifest else {
+        return;
+    };
+    let mut sync = state.sync.lock().unwrap();
+    let version = state.updates.version.load(Ordering::SeqCst);
+    if sync.verified_at.is_none()
+        || sync.version + 1 != version
+        || sync.manifest.status_mode != config.status_mode
+        || sync.manifest.folders != manifest_folders(root, config)
+    {
+        return;
+    }
+    for (folder, id) in files {
+        if !sync.manifest.folders.iter().any(|f| f == folder) {
+            continue;
+        }
+        let key = format!("{}/{}.md", folder, id);
+        let path = root.join(&key);
+        match fs::metadata(&path).ok().and_then(|meta| manifest_entry(&path, folder, config, opts, Some(cache), meta)) {
+            Some(entry) => {
+                sync.manifest.tasks.insert(key, entry);
+            }
+            None => {
+                sync.manifest.tasks.remove(&key);
+                cache.entries.lock().unwrap().remove(&path);
+            }
+        }
+    }
+    match write_manifest(root, &sync.manifest) {
+        Ok(()) => sync.version = version,
+        Err(err) => eprintln!("Warning: could not update {}: {}", MANIFEST_FILE, err),
+    }
+}
+
+/// `load_tasks` on a `--manifest` server: headers come from the manifest,
+/// descriptions from memory or, when `descriptions` is set, from the files.
+fn manifest_tasks(
+    root: &Path,
+    config: &BoardConfig,
+    opts: &BoardOptions,
+    cache: &TaskCache,
+    descriptions: bool,
+) -> io::Result<HashMap<String, Vec<Task>>> {
+    refresh_manifest(root, config, opts, cache, false)?;
+    let mut out: HashMap<String, Vec<Task>> = config.columns.iter().map(|c| (c.id.clone(), Vec::new())).collect();
+    let entries: Vec<ManifestEntry> = match &cache.manifest {
+        Some(state) => state.sync.lock().unwrap().manifest.tasks.values().cloned().collect(),
+        None => Vec::new(),
+    };
+    for entry in entries {
+        let Some(tasks) = out.get_mut(&entry.task.folder) else {
+            continue;
+        };
+        let mut task = entry.task.clone();
+        fill_description(root, config.status_mode, opts, cache, &entry, &mut task, descriptions);
+        type_fields(&config.fields, &mut task);
+        tasks.push(task);
+    }
+    Ok(out)
+}
+
+/// Sets the description of a task loaded from `entry`: from the in-memory
+/// cache when it holds the same file, else from the file when `read` is set.
+fn fill_description(root: &Path, mode: StatusMode, opts: &BoardOptions, cache: &TaskCache, entry: &ManifestEntry, task: &mut Task, read: bool) {
+    if entry.description_hash == description_hash("") {
+        return;
+    }
+    let path = root.join(&task.folder).join(format!("{}.md", task.id));
+    {
+        let entries = cache.entries.lock().unwrap();
+        if let Some(cached) = entries.get(&path) {
+            if cached.modified.and_then(nanos_since_epoch) == entry.modified && cached.len == entry.len {
+                task.description = cached.task.description.clone();
+                return;
+            }
+        }
+    }
+    if read {
+        if let Ok(parsed) = cached_parse_task(cache, &path, &task.folder, mode, opts, fs::metadata(&path).ok()) {
+            task.description = parsed.description;
+        }
+    }
+}
+
+/// Reads the descriptions `load_listing_tasks` left out, for the tasks a
+/// listing returns.
+fn fill_descriptions(root: &Path, mode: StatusMode, opts: &BoardOptions, cache: &TaskCache, tasks: &mut [Task]) {
+    let Some(state) = &cache.manifest else {
+        return;
+    };
+    for task in tasks.iter_mut().filter(|task| task.description.is_empty()) {
+        let key = format!("{}/{}.md", task.folder, task.id);
+        let entry = state.sync.lock().unwrap().manifest.tasks.get(&key).cloned();
+        if let Some(entry) = entry {
+            fill_description(root, mode, opts, cache, &entry, task, true);
+        }
+    }
+}
+
+fn parse_timestamp(value: &str) -> Option<OffsetDateTime> {
+    OffsetDateTime::parse(value.trim(), &Rfc3339).ok()
+}
+
+const STATS_TOP_TAGS: usize = 10;
+
+#[derive(Debug, Serialize)]
+struct NamedCount {
+    name: String,
+    count: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct ColumnCount {
+    id: String,
+    title: String,
+    count: usize,
+    done: bool,
+}
+
+#[derive(Debug, Serialize, Default)]
+struct RecentCounts {
+    last_7_days: usize,
+    last_30_days: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct UntouchedTask {
+    id: String,
+    title: String,
+    folder: String,
+    updated_at: String,
+}
+
+/// `GET /api/users/:name/activity`: what one actor did in the last `days`.
+#[derive(Debug, Serialize, Default)]
+struct UserActivity {
+    user: String,
+    days: i64,
+    since: String,
+    created: usize,
+    moved: usize,
+    /// Moves per `from`→`to` pair, most frequent first.
+    moves: Vec<MoveCount>,
+    /// Tasks that entered a done column (moved or created there).
+    completed: usize,
+    edited: usize,
+    /// Open tasks currently assigned to the user.
+    assigned: Vec<AssignedTask>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+struct MoveCount {
+    from: String,
+    to: String,
+    count: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct AssignedTask {
+    id: String,
+    title: String,
+    column: String,
+}
+
+const ACTIVITY_MAX_DAYS: i64 = 366;
+
+/// Folds activity log lines for `user` since `since`, one line at a time.
+/// Entries without an actor count as "anonymous"; unreadable lines are
+/// skipped.
+fn summarize_activity(log: impl BufRead, config: &BoardConfig, user: &str, since: OffsetDateTime) -> UserActivity {
+    let mut summary = UserActivity { user: user.to_string(), since: format_timestamp(since), ..Default::default() };
+    let mut moves: HashMap<(String, String), usize> = HashMap::new();
+    for line in log.lines().map_while(Result::ok) {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let actor = entry["actor"].as_str().filter(|a| !a.is_empty()).unwrap_or("anonymous");
+        let recent = entry["at"].as_str().and_then(parse_timestamp).is_some_and(|at| at >= since);
+        if !recent || !actor.eq_ignore_ascii_case(user) {
+            continue;
+        }
+        let field = |key: &str| entry[key].as_str().unwrap_or_default().to_string();
+        match entry["action"].as_str() {
+            Some("task_created") => {
+                summary.created += 1;
+                if is_done_column(config, &field("column")) {
+                    summary.completed += 1;
+                }
+            }
+            Some("task_moved") => {
+                summary.moved += 1;
+                if is_done_column(config, &field("to")) && !is_done_column(config, &field("from")) {
+                    summary.completed += 1;
+                }
+                *moves.entry((field("from"), field("to"))).or_default() += 1;
+            }
+            Some("task_edited") => summary.edited += 1,
+            _ => {}
+        }
+    }
+    summary.moves = moves.into_iter().map(|((from, to), count)| MoveCount { from, to, count }).collect();
+    summary.moves.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| (&a.from, &a.to).cmp(&(&b.from, &b.to))));
+    summary
+}
+
+const HEATMAP_DEFAULT_DAYS: i64 = 180;
+
+/// Where a heatmap day's counts come from: the activity log, or, for days
+/// before the log starts, the tasks' `created_at`/`completed_at` headers
+/// (which know nothing about moves, deleted tasks or earlier completions).
+#[derive(Debug, Default, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum HeatmapSource {
+    #[default]
+    Log,
+    Headers,
+}
+
+#[derive(Debug, Default, Serialize, Clone, PartialEq)]
+struct DayActivity {
+    created: usize,
+    moved: usize,
+    completed: usize,
+    source: HeatmapSource,
+}
+
+#[derive(Debug, Serialize)]
+struct Heatmap {
+    from: String,
+    to: String,
+    timezone: String,
+    /// First day with a log entry; earlier days use the header fallback.
+    log_since: Option<String>,
+    log_days: usize,
+    header_days: usize,
+    /// Every day of the window, oldest first, zeros included.
+    days: BTreeMap<String, DayActivity>,
+}
+
+/// `GET /api/stats/heatmap`: per-day created/moved/completed counts for the
+/// `days` ending `today`, with dates taken in `tz`.
+fn activity_heatmap(
+    log: Option<impl BufRead>,
+    config: &BoardConfig,
+    folders: &HashMap<String, Vec<Task>>,
+    today: Date,
+    tz: UtcOffset,
+    days: i64,
+) -> Heatmap {
+    let start = today - time::Duration::days(days - 1);
+    let day_of = |stamp: &str| parse_timestamp(stamp).map(|at| at.to_offset(tz).date());
+    let mut logged: HashMap<Date, DayActivity> = HashMap::new();
+    let mut log_since: Option<Date> = None;
+    for line in log.into_iter().flat_map(|log| log.lines().map_while(Result::ok)) {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let Some(day) = entry["at"].as_str().and_then(day_of) else {
+            continue;
+        };
+        log_since = Some(log_since.map_or(day, |since| since.min(day)));
+        if day < start || day > today {
+            continue;
+        }
+        let field = |key: &str| entry[key].as_str().unwrap_or_default().to_string();
+        let counts = logged.entry(day).or_default();
+        match entry["action"].as_str() {
+            Some("task_created") => {
+                counts.created += 1;
+                if is_done_column(config, &field("column")) {
+                    counts.completed += 1;
+                }
+            }
+            Some("task_moved") => {
+                counts.moved += 1;
+                if is_done_column(config, &field("to")) && !is_done_column(config, &field("from")) {
+                    counts.completed += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut from_headers: HashMap<Date, DayActivity> = HashMap::new();
+    for task in folders.values().flatten() {
+        if let Some(day) = day_of(&task.created_at) {
+            from_headers.entry(day).or_default().created += 1;
+        }
+        if let Some(day) = task.completed_at.as_deref().and_then(day_of) {
+            from_headers.entry(day).or_default().completed += 1;
+        }
+    }
+    let mut heatmap = Heatmap {
+        from: start.to_string(),
+        to: today.to_string(),
+        timezone: format_utc_offset(tz),
+        log_since: log_since.map(|day| day.to_string()),
+        log_days: 0,
+        header_days: 0,
+        days: BTreeMap::new(),
+    };
+    let mut day = start;
+    while day <= today {
+        let counts = if log_since.is_some_and(|since| day >= since) {
+            heatmap.log_days += 1;
+            logged.remove(&day).unwrap_or_default()
+        } else {
+            heatmap.header_days += 1;
+            DayActivity { source: HeatmapSource::Headers, ..from_headers.remove(&day).unwrap_or_default() }
+        };
+        heatmap.days.insert(day.to_string(), counts);
+        day = day.next_day().expect("date in range");
+    }
+    heatmap
+}
+
+/// Weeks `GET /api/stats/forecast` looks back by default, and the range
+/// `weeks` accepts.
+const FORECAST_DEFAULT_WEEKS: i64 = 8;
+const FORECAST_MAX_WEEKS: i64 = 52;
+/// Fewer fully logged weeks than this give no ETA; also the smallest
+/// `weeks` accepted.
+const FORECAST_MIN_WEEKS: usize = 3;
+
+#[derive(Debug, Serialize, PartialEq)]
+struct WeekOutflow {
+    from: String,
+    to: String,
+    /// Tasks moved out of the column or deleted from it.
+    outflow: usize,
+    /// False when the activity log starts after `from`; such weeks are
+    /// listed but left out of the averages.
+    logged: bool,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+struct ForecastEta {
+    weeks: f64,
+    date: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Forecast {
+    column: String,
+    count: usize,
+    weeks: i64,
+    timezone: String,
+    log_since: Option<String>,
+    /// Oldest week first; the last one ends today.
+    weekly: Vec<WeekOutflow>,
+    enough_data: bool,
+    /// Why there is no ETA when `enough_data` is false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    average_per_week: Option<f64>,
+    stddev: Option<f64>,
+    /// At the average pace, and at one standard deviation above and below
+    /// it. `pessimistic` is null when that pace is zero.
+    eta: Option<ForecastEta>,
+    optimistic: Option<ForecastEta>,
+    pessimistic: Option<ForecastEta>,
+}
+
+/// `GET /api/stats/forecast`: how fast `column` has drained over the last
+/// `weeks` (from the activity log) and when its `count` tasks would be
+/// gone at that pace. Weeks end on `today`; dates are taken in `tz`.
+fn throughput_forecast(log: Option<impl BufRead>, column: &str, count: usize, today: Date, tz: UtcOffset, weeks: i64) -> Forecast {
+    let start = today - time::Duration::days(weeks * 7 - 1);
+    let mut outflow = vec![0; weeks as usize];
+    let mut log_since: Option<Date> = None;
+    for line in log.into_iter().flat_map(|log| log.lines().map_while(Result::ok)) {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let Some(day) = entry["at"].as_str().and_then(parse_timestamp).map(|at| at.to_offset(tz).date()) else {
+            continue;
+        };
+        log_since = Some(log_since.map_or(day, |since| since.min(day)));
+        if day < start || day > today {
+            continue;
+        }
+        let left = match entry["action"].as_str() {
+            Some("task_moved") => entry["from"] == column && entry["to"] != column,
+            Some("task_deleted") => entry["column"] == column,
+            _ => false,
+        };
+        if left {
+            outflow[((day - start).whole_days() / 7) as usize] += 1;
+        }
+    }
+    let weekly: Vec<WeekOutflow> = outflow
+        .iter()
+        .enumerate()
+        .map(|(i, &outflow)| {
+            let from = start + time::Duration::days(i as i64 * 7);
+            WeekOutflow {
+                from: from.to_string(),
+                to: (from + time::Duration::days(6)).to_string(),
+                outflow,
+                logged: log_since.is_some_and(|since| since <= from),
+            }
+        })
+        .collect();
+    let mut forecast = Forecast {
+        column: column.to_string(),
+        count,
+        weeks,
+        timezone: format_utc_offset(tz),
+        log_since: log_since.map(|day| day.to_string()),
+        weekly,
+        enough_data: false,
+        reason: None,
+        average_per_week: None,
+        stddev: None,
+        eta: None,
+        optimistic: None,
+        pessimistic: None,
+    };
+    let samples: Vec<f64> = forecast.weekly.iter().filter(|w| w.logged).map(|w| w.outflow as f64).collect();
+    if samples.len() < FORECAST_MIN_WEEKS {
+        forecast.reason = Some(format!(
+            "not enough data: {} fully logged week(s), at least {} needed",
+            samples.len(),
+            FORECAST_MIN_WEEKS
+        ));
+        return forecast;
+    }
+    let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+    let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (samples.len() - 1) as f64;
+    let stddev = variance.sqrt();
+    forecast.average_per_week = Some(mean);
+    forecast.stddev = Some(stddev);
+    if mean == 0.0 {
+        forecast.reason = Some(format!("not enough data: nothing left {} in the last {} weeks", column, samples.len()));
+        return forecast;
+    }
+    let eta = |rate: f64| {
+        (rate > 0.0).then(|| {
+            let weeks = count as f64 / rate;
+            let date = today + time::Duration::days((weeks * 7.0).ceil() as i64);
+            ForecastEta { weeks: (weeks * 10.0).round() / 10.0, date: date.to_string() }
+        })
+    };
+    forecast.enough_data = true;
+    forecast.eta = eta(mean);
+    forecast.optimistic = eta(mean + stddev);
+    forecast.pessimistic = eta(mean - stddev);
+    forecast
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+struct BurnupDay {
+    date: String,
+    /// Tasks (or points) in the sprint by the end of the day; null for days
+    /// after today, like the other two.
+    scope: Option<f64>,
+    completed: Option<f64>,
+    /// Scope created that day after the sprint began.
+    added: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct Burnup {
+    sprint_start: String,
+    sprint_end: String,
+    timezone: String,
+    /// `points` when any task has an estimate, else `tasks`.
+    unit: &'static str,
+    /// Tasks without an estimate; they count 0 points.
+    unestimated: usize,
+    log_since: Option<String>,
+    days: Vec<BurnupDay>,
+    /// False when scope changes cannot all be told from the task headers
+    /// and the activity log; `warnings` says what is missing.
+    scope_reliable: bool,
+    warnings: Vec<String>,
+}
+
+/// `GET /api/stats/burnup`: for each day from `start` to `end`, the sprint's
+/// scope (tasks on the board created by then, less those completed before
+/// the sprint) and how much of it was completed, in estimate points when
+/// any task has one. Tasks that left the board are not in `folders`, so
+/// `log` is only read to report them. Dates are taken in `tz`.
+fn burnup(
+    config: &BoardConfig,
+    folders: &HashMap<String, Vec<Task>>,
+    (start, end): (Date, Date),
+    log: Option<impl BufRead>,
+    today: Date,
+    tz: UtcOffset,
+) -> Burnup {
+    let day_of = |raw: Option<&str>| raw.and_then(parse_timestamp).map(|at| at.to_offset(tz).date());
+    let tasks: Vec<&Task> = config
+        .columns
+        .iter()
+        .flat_map(|c| folders.get(&c.id).into_iter().flatten())
+        .filter(|t| day_of(t.completed_at.as_deref()).is_none_or(|done| done >= start) || !is_done_column(config, &t.folder))
+        .collect();
+    let points = tasks.iter().any(|t| t.estimate.is_some());
+    let weight = |t: &Task| if points { t.estimate.unwrap_or(0.0) } else { 1.0 };
+    let completed_on = |t: &Task| day_of(t.completed_at.as_deref()).filter(|_| is_done_column(config, &t.folder));
+    let mut warnings = Vec::new();
+    let undated = tasks.iter().filter(|t| day_of(Some(&t.created_at)).is_none()).count();
+    if undated > 0 {
+        warnings.push(format!("{} task(s) have no created_at and count as scope from the first day", undated));
+    }
+    let unstamped = tasks.iter().filter(|t| is_done_column(config, &t.folder) && completed_on(t).is_none()).count();
+    if unstamped > 0 {
+        warnings.push(format!("{} done task(s) have no completed_at and are not counted as completed", unstamped));
+    }
+    let mut log_since: Option<Date> = None;
+    let mut removed = 0;
+    for line in log.into_iter().flat_map(|log| log.lines().map_while(Result::ok)) {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let Some(day) = day_of(entry["at"].as_str()) else {
+            continue;
+        };
+        log_since = Some(log_since.map_or(day, |since| since.min(day)));
+        let left = match entry["action"].as_str() {
+            Some("task_deleted") => true,
+            Some("task_transferred") => entry["to_board"].is_string(),
+            Some("task_moved") => entry["to"].as_str().is_some_and(|to| !config.columns.iter().any(|c| c.id == to)),
+            _ => false,
+        };
+        if left && day >= start && day <= end {
+            removed += 1;
+        }
+    }
+    match log_since {
+        None => warnings.push("there is no activity log, so tasks removed during the sprint cannot be detected".to_string()),
+        Some(since) if since > start => warnings.push(format!(
+            "the activity log starts on {}, after sprint_start; tasks removed before then cannot be detected",
+            since
+        )),
+        _ => {}
+    }
+    if removed > 0 {
+        warnings.push(format!("{} task(s) left the board during the sprint and are missing from the scope", removed));
+    }
+    let round = |value: f64| (value * 100.0).round() / 100.0;
+    let mut days = Vec::new();
+    let mut day = start;
+    loop {
+        let known = day <= today;
+        let created = |t: &&&Task| day_of(Some(&t.created_at)).is_none_or(|created| created <= day);
+        let scope: f64 = tasks.iter().filter(created).map(|t| weight(t)).sum();
+        let completed: f64 = tasks.iter().filter(|t| completed_on(t).is_some_and(|done| done <= day)).map(|t| weight(t)).sum();
+        let added: f64 =
+            tasks.iter().filter(|t| day > start && day_of(Some(&t.created_at)) == Some(day)).map(|t| weight(t)).sum();
+        days.push(BurnupDay {
+            date: day.to_string(),
+            scope: known.then_some(round(scope)),
+            completed: known.then_some(round(completed)),
+            added: known.then_some(round(added)),
+        });
+        match day.next_day().filter(|next| *next <= end) {
+            Some(next) => day = next,
+            None => break,
+        }
+    }
+    Burnup {
+        sprint_start: start.to_string(),
+        sprint_end: end.to_string(),
+        timezone: format_utc_offset(tz),
+        unit: if points { "points" } else { "tasks" },
+        unestimated: if points { tasks.iter().filter(|t| t.estimate.is_none()).count() } else { 0 },
+        log_since: log_since.map(|day| day.to_string()),
+        days,
+        scope_reliable: warnings.is_empty(),
+        warnings,
+    }
+}
+
+fn user_activity(
+    root: &Path,
+    config: &BoardConfig,
+    folders: &HashMap<String, Vec<Task>>,
+    user: &str,
+    days: i64,
+) -> UserActivity {
+    let since = OffsetDateTime::now_utc() - time::Duration::days(days);
+    let mut summary = match fs::File::open(root.join(ACTIVITY_FILE)) {
+        Ok(file) => summarize_activity(io::BufReader::new(file), config, user, since),
+        Err(_) => UserActivity { user: user.to_string(), since: format_timestamp(since), ..Default::default() },
+    };
+    summary.days = days;
+    for column in config.columns.iter().filter(|c| !is_done_column(config, &c.id)) {
+        for task in folders.get(&column.id).into_iter().flatten() {
+            if task.assigned_to.eq_ignore_ascii_case(user) {
+                summary.assigned.push(AssignedTask {
+                    id: task.id.clone(),
+                    title: task.title.clone(),
+                    column: column.id.clone(),
+                });
+            }
+        }
+    }
+    summary
+}
+
+#[derive(Debug, Serialize)]
+struct BoardStats {
+    generated_at: String,
+    total: usize,
+    columns: Vec<ColumnCount>,
+    assignees: Vec<NamedCount>,
+    unassigned: usize,
+    tags: Vec<NamedCount>,
+    created: RecentCounts,
+    completed: RecentCounts,
+    overdue: usize,
+    oldest_untouched: Option<UntouchedTask>,
+    usage: BoardUsage,
+}
+
+/// Sorts by count (descending), then name, so the output is stable.
+fn sorted_counts(counts: HashMap<String, usize>) -> Vec<NamedCount> {
+    let mut list: Vec<NamedCount> = counts
+        .into_iter()
+        .map(|(name, count)| NamedCount { name, count })
+        .collect();
+    list.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    list
+}
+
+fn count_recent(counts: &mut RecentCounts, stamp: Option<&str>, now: OffsetDateTime) {
+    let Some(at) = stamp.and_then(parse_timestamp) else {
+        return;
+    };
+    let age = now - at;
+    if age <= time::Duration::days(7) {
+        counts.last_7_days += 1;
+    }
+    if age <= time::Duration::days(30) {
+        counts.last_30_days += 1;
+    }
+}
+
+fn read_users_file(root: &Path) -> Vec<String> {
+    fs::read_to_string(root.join(USERS_FILE))
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Workload {
+    assignee: String,
+    open: usize,
+    /// Sum of the open tasks' estimates; tasks without one add nothing.
+    estimate: f64,
+    unestimated: usize,
+    overdue: usize,
+    /// Open tasks per column, in board order (`?by_column=true`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    columns: Option<Vec<NamedCount>>,
+}
+
+#[derive(Debug, Serialize)]
+struct WorkloadReport {
+    generated_at: String,
+    assignees: Vec<Workload>,
+    unassigned: Workload,
+}
+
+/// `GET /api/stats/workload`: open (non-done) tasks per assignee, matched
+/// case-insensitively. Everyone in `users` appears, with zeros if idle.
+/// Sorted by open count (descending), then name.
+fn workload(
+    config: &BoardConfig,
+    folders: &HashMap<String, Vec<Task>>,
+    users: &[String],
+    now: OffsetDateTime,
+    tz: UtcOffset,
+    by_column: bool,
+) -> WorkloadReport {
+    let today = now.to_offset(tz).date();
+    let empty = |assignee: &str| Workload {
+        assignee: assignee.to_string(),
+        columns: by_column.then(Vec::new),
+        ..Default::default()
+    };
+    let mut people: HashMap<String, Workload> = HashMap::new();
+    for user in users {
+        people.entry(user.to_lowercase()).or_insert_with(|| empty(user));
+    }
+    let mut unassigned = empty("");
+    for column in config.columns.iter().filter(|c| !is_done_column(config, &c.id)) {
+        for task in folders.get(&column.id).map(Vec::as_slice).unwrap_or_default() {
+            let assignee = task.assigned_to.trim();
+            let entry = if assignee.is_empty() {
+                &mut unassigned
+            } else {
+                people.entry(assignee.to_lowercase()).or_insert_with(|| empty(assignee))
+            };
+            entry.open += 1;
+            match task.estimate {
+                Some(estimate) => entry.estimate += estimate,
+                None => entry.unestimated += 1,
+            }
+            if task.due.as_deref().and_then(parse_plain_date).is_some_and(|due| due < today) {
+                entry.overdue += 1;
+            }
+            if let Some(columns) = entry.columns.as_mut() {
+                match columns.last_mut() {
+                    Some(last) if last.name == column.id => last.count += 1,
+                    _ => columns.push(NamedCount { name: column.id.clone(), count: 1 }),
+                }
+            }
+        }
+    }
+    let mut assignees: Vec<Workload> = people.into_values().collect();
+    assignees.sort_by(|a, b| {
+        b.open
+            .cmp(&a.open)
+            .then_with(|| a.assignee.to_lowercase().cmp(&b.assignee.to_lowercase()))
+    });
+    WorkloadReport {
+        generated_at: now.format(&Rfc3339).unwrap_or_default(),
+        assignees,
+        unassigned,
+    }
+}
+
+/// Dashboard summary. Overdue means a due date before today (in `tz`) on a
+/// task outside the done columns; the oldest untouched task likewise ignores
+/// finished work.
+fn board_stats(
+    config: &BoardConfig,
+    folders: &HashMap<String, Vec<Task>>,
+    now: OffsetDateTime,
+    tz: UtcOffset,
+) -> BoardStats {
+    let today = now.to_offset(tz).date();
+    let mut stats = BoardStats {
+        generated_at: now.format(&Rfc3339).unwrap_or_default(),
+        total: 0,
+        columns: Vec::new(),
+        assignees: Vec::new(),
+        unassigned: 0,
+        tags: Vec::new(),
+        created: RecentCounts::default(),
+        completed: RecentCounts::default(),
+        overdue: 0,
+        oldest_untouched: None,
+        usage: BoardUsage::default(),
+    };
+    let mut assignees: HashMap<String, usize> = HashMap::new();
+    let mut tags: HashMap<String, usize> = HashMap::new();
+    let mut oldest: Option<(OffsetDateTime, &Task)> = None;
+    for column in &config.columns {
+        let tasks = folders.get(&column.id).map(Vec::as_slice).unwrap_or_default();
+        let done = is_done_column(config, &column.id);
+        stats.total += tasks.len();
+        stats.columns.push(ColumnCount {
+            id: column.id.clone(),
+            title: column.title.clone(),
+            count: tasks.len(),
+            done,
+        });
+        for task in tasks {
+            let assignee = task.assigned_to.trim();
+            if assignee.is_empty() {
+                stats.unassigned += 1;
+            } else {
+                *assignees.entry(assignee.to_string()).or_default() += 1;
+            }
+            for tag in &task.tags {
+                *tags.entry(tag.to_lowercase()).or_default() += 1;
+            }
+            count_recent(&mut stats.created, Some(&task.created_at), now);
+            count_recent(&mut stats.completed, task.completed_at.as_deref(), now);
+            if done {
+                continue;
+            }
+            if task
+                .due
+                .as_deref()
+                .and_then(parse_plain_date)
+                .is_some_and(|due| due < today)
+            {
+                stats.overdue += 1;
+            }
+            if let Some(updated) = task.updated_at.at() {
+                if oldest.is_none_or(|(at, _)| updated < at) {
+                    oldest = Some((updated, task));
+                }
+            }
+        }
+    }
+    stats.assignees = sorted_counts(assignees);
+    stats.tags = sorted_counts(tags);
+    stats.tags.truncate(STATS_TOP_TAGS);
+    stats.oldest_untouched = oldest.map(|(_, task)| UntouchedTask {
+        id: task.id.clone(),
+        title: task.title.clone(),
+        folder: task.folder.clone(),
+        updated_at: task.updated_at.to_string(),
+    });
+    stats
+}
+
+#[derive(Debug, Serialize)]
+struct SearchSnippet {
+    field: &'static str,
+    line: String,
+    matches: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchHit {
+    score: f64,
+    column: String,
+    column_title: String,
+    snippet: Option<SearchSnippet>,
+    task: Task,
+}
+
+const SEARCH_DEFAULT_LIMIT: usize = 20;
+const SEARCH_MAX_LIMIT: usize = 200;
+
+fn fold_chars(text: &str) -> Vec<char> {
+    text.chars()
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+        .collect()
+}
+
+/// Character offsets `[start, end)` of every case-insensitive occurrence of
+/// `word` in `line`.
+fn find_matches(line: &str, word: &str) -> Vec<(usize, usize)> {
+    let hay = fold_chars(line);
+    let needle = fold_chars(word);
+    let mut out = Vec::new();
+    if needle.is_empty() || needle.len() > hay.len() {
+        return out;
+    }
+    let mut i = 0;
+    while i + needle.len() <= hay.len() {
+        if hay[i..i + needle.len()] == needle[..] {
+            out.push((i, i + needle.len()));
+            i += needle.len();
+        } else {
+            i += 1;
+        }
+    }
+    out
+}
+
+fn contains_folded(text: &str, word: &str) -> bool {
+    !find_matches(text, word).is_empty()
+}
+
+/// Scores a task against every query word (all words must match somewhere).
+/// Title hits weigh most, description hits least; recent edits get a small
+/// bonus that fades over 90 days so ties favor active work.
+fn score_task(task: &Task, words: &[String], now: OffsetDateTime) -> Option<f64> {
+    let mut score = 0.0;
+    for word in words {
+        let mut word_score = 0.0;
+        if contains_folded(&task.title, word) {
+            word_score += 10.0;
+        }
+        if contains_folded(&task.id, word) {
+            word_score += 6.0;
+        }
+        if task.tags.iter().any(|t| contains_folded(t, word)) {
+            word_score += 5.0;
+        }
+        if contains_folded(&task.assigned_to, word) {
+            word_score += 4.0;
+        }
+        if contains_folded(&task.description, word) {
+            word_score += 2.0;
+        }
+        if word_score == 0.0 {
+            return None;
+        }
+        score += word_score;
+    }
+    if let Some(updated) = task.updated_at.at() {
+        let days = (now - updated).whole_days().max(0) as f64;
+        score += (1.0 - days / 90.0).max(0.0) * 2.0;
+    }
+    Some(score)
+}
+
+fn search_snippet(task: &Task, words: &[String]) -> Option<SearchSnippet> {
+    let candidates = std::iter::once(("title", task.title.as_str()))
+        .chain(task.description.lines().map(|line| ("description", line)));
+    for (field, line) in candidates {
+        let mut matches: Vec<(usize, usize)> = words
+            .iter()
+            .flat_map(|word| find_matches(line, word))
+            .collect();
+        if !matches.is_empty() {
+            matches.sort();
+            return Some(SearchSnippet {
+                field,
+                line: line.to_string(),
+                matches,
+            });
+        }
+    }
+    None
+}
+
+/// Outcome of a search: total hit count, the hits up to `limit`, and how many
+/// tasks were dropped by date filters because they lack the timestamp.
+struct SearchResults {
+    total: usize,
+    hits: Vec<SearchHit>,
+    excluded_missing_dates: usize,
+}
+
+fn search_tasks(
+    config: &BoardConfig,
+    folders: HashMap<String, Vec<Task>>,
+    query: &str,
+    filter: &TaskFilter,
+    limit: usize,
+) -> SearchResults {
+    let words: Vec<String> = query.split_whitespace().map(|w| w.to_lowercase()).collect();
+    let now = OffsetDateTime::now_utc();
+    let mut hits = Vec::new();
+    let mut excluded_missing_dates = 0;
+    for column in &config.columns {
+        for task in folders.get(&column.id).cloned().unwrap_or_default() {
+            match filter_task(filter, &task) {
+                FilterOutcome::Match => {}
+                FilterOutcome::Reject => continue,
+                FilterOutcome::MissingDate => {
+                    excluded_missing_dates += 1;
+                    continue;
+                }
+            }
+            if let Some(score) = score_task(&task, &words, now) {
+                hits.push(SearchHit {
+                    score,
+                    column: column.id.clone(),
+                    column_title: column.title.clone(),
+                    snippet: search_snippet(&task, &words),
+                    task,
+                });
+            }
+        }
+    }
+    hits.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| b.task.updated_at.cmp(&a.task.updated_at))
+            .then_with(|| a.task.id.cmp(&b.task.id))
+    });
+    let total = hits.len();
+    hits.truncate(limit);
+    SearchResults {
+        total,
+        hits,
+        excluded_missing_dates,
+    }
+}
+
+fn respond_asset(path: &str) -> Option<Response<std::io::Cursor<Vec<u8>>>> {
+    let (body, content_type) = match path {
+        "/" | "/index.html" => (INDEX_HTML, "text/html"),
+        "/app.js" => (APP_JS, "application/javascript"),
+        "/styles.css" => (STYLES_CSS, "text/css"),
+        _ => return None,
+    };
+    Some(
+        Response::from_string(body)
+            .with_header(Header::from_bytes("Content-Type", content_type).unwrap()),
+    )
+}
+
+/// The board's own copy of `name`: `.kanban-assets/<name>`, else
+/// `<root>/<name>`. Files that are not regular, too large or resolve outside
+/// the root through a symlink are passed over.
+fn board_asset(root: &Path, name: &str) -> Option<Vec<u8>> {
+    [root.join(ASSETS_DIR).join(name), root.join(name)]
+        .into_iter()
+        .filter(|path| path.is_file() && resolves_within(path, root))
+        .filter(|path| fs::metadata(path).is_ok_and(|meta| meta.len() <= BOARD_ASSET_MAX_BYTES))
+        .find_map(|path| fs::read(path).ok())
+}
+
+/// The icon behind every favicon path: the board's SVG or PNG, the format
+/// in the path first (PNG for `/favicon.ico`), else the bundled SVG.
+fn board_favicon(root: &Path, path: &str) -> (Vec<u8>, &'static str) {
+    let mut formats = [("favicon.png", "image/png"), ("favicon.svg", "image/svg+xml")];
+    if path == "/favicon.svg" {
+        formats.reverse();
+    }
+    formats
+        .iter()
+        .find_map(|(name, mime)| board_asset(root, name).map(|body| (body, *mime)))
+        .unwrap_or_else(|| (FAVICON_SVG.as_bytes().to_vec(), "image/svg+xml"))
+}
+
+/// The board's `manifest.json` as is, else one naming the board after its
+/// title and pointing at its favicon.
+fn board_manifest(root: &Path) -> Vec<u8> {
+    if let Some(body) = board_asset(root, "manifest.json") {
+        return body;
+    }
+    let (_, mime) = board_favicon(root, "/favicon.ico");
+    let icon = if mime == "image/png" { "favicon.png" } else { "favicon.svg" };
+    let title = board_title(root);
+    serde_json::json!({
+        "name": title,
+        "short_name": title,
+        "start_url": ".",
+        "display": "standalone",
+        "icons": [{ "src": icon, "type": mime, "sizes": "any" }],
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// Favicon and manifest, per board, with an ETag so a browser revalidates
+/// cheaply once `BOARD_ASSET_MAX_AGE` has passed. A board SVG can never run
+/// script: the policy header forbids it even when opened directly.
+fn respond_board_asset(root: &Path, path: &str, if_none_match: Option<&str>) -> Option<Response<std::io::Cursor<Vec<u8>>>> {
+    let (body, content_type) = match path {
+        "/favicon.ico" | "/favicon.png" | "/favicon.svg" => board_favicon(root, path),
+        "/manifest.json" | "/manifest.webmanifest" => (board_manifest(root), "application/manifest+json"),
+        _ => return None,
+    };
+    let etag = format!("\"{:016x}\"", content_hash(&body[..]));
+    let response = if if_none_match == Some(etag.as_str()) {
+        Response::from_data(Vec::new()).with_status_code(StatusCode(304))
+    } else {
+        Response::from_data(body).with_header(Header::from_bytes("Content-Type", content_type).unwrap())
+    };
+    let cache = format!("public, max-age={}", BOARD_ASSET_MAX_AGE);
+    Some(
+        response
+            .with_header(Header::from_bytes("ETag", etag.as_bytes()).unwrap())
+            .with_header(Header::from_bytes("Cache-Control", cache.as_bytes()).unwrap())
+            .with_header(Header::from_bytes("Content-Security-Policy", "default-src 'none'; style-src 'unsafe-inline'").unwrap())
+            .with_header(Header::from_bytes("X-Content-Type-Options", "nosniff").unwrap()),
+    )
+}
+
+fn respond_json(status: StatusCode, body: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_string(body)
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap())
+}
+
+/// `{"error": text, "code": code, "message": text}` with the texts from
+/// `MESSAGES`: `error` in English as legacy clients always got it, `message`
+/// in the request's language. Callers may add details before responding.
+fn error_payload(code: &str, params: &[(&str, &str)]) -> serde_json::Value {
+    debug_assert!(ERROR_CODES.contains(&code), "{} is not in ERROR_CODES", code);
+    serde_json::json!({ "error": message_in(DEFAULT_LANG, code, params), "code": code, "message": message(code, params) })
+}
+
+fn respond_error(status: u16, code: &str, params: &[(&str, &str)]) -> Response<std::io::Cursor<Vec<u8>>> {
+    respond_json(StatusCode(status), &error_payload(code, params).to_string())
+}
+
+fn respond_text(status: StatusCode, body: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_string(body).with_status_code(status)
+}
+
+/// Records the field names serde hands to `deserialize_struct`, then stops.
+struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> serde::Deserializer<'de> for FieldNames<'_> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(serde::de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: serde::de::Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(serde::de::Error::custom("fields read"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit
+        unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// The JSON fields a request struct accepts, straight from its `Deserialize`
+/// impl so the list cannot drift from the struct.
+fn accepted_fields<T: serde::de::DeserializeOwned>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// Keys of the object `value` that are not in `accepted`, each prefixed with
+/// `prefix` (e.g. `tasks[2].`).
+fn unknown_fields(value: &serde_json::Value, accepted: &[&str], prefix: &str) -> Vec<String> {
+    value
+        .as_object()
+        .map(|object| {
+            object
+                .keys()
+                .filter(|key| !accepted.contains(&key.as_str()))
+                .map(|key| format!("{}{}", prefix, key))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The 400 body for unknown fields (with `--strict-api`) or for a body that
+/// failed to parse after unknown fields were dropped.
+fn unknown_fields_error(message: String, unknown: &[String], accepted: &[&str]) -> serde_json::Value {
+    serde_json::json!({ "error": message, "unknown_fields": unknown, "accepted_fields": accepted })
+}
+
+/// Parses a JSON request body into `T`. Unknown top-level fields are
+/// rejected when `strict` (`--strict-api`) and otherwise returned so the
+/// response can list them as `ignored_fields`. Errors are ready-made 400
+/// bodies.
+fn parse_body<T: serde::de::DeserializeOwned>(body: &str, strict: bool) -> Result<(T, Vec<String>), serde_json::Value> {
+    let value: serde_json::Value =
+        serde_json::from_str(body).map_err(|err| serde_json::json!({ "error": err.to_string() }))?;
+    let accepted = accepted_fields::<T>();
+    let unknown = unknown_fields(&value, accepted, "");
+    check_unknown_fields(&unknown, accepted, strict)?;
+    match serde_json::from_value(value) {
+        Ok(parsed) => Ok((parsed, unknown)),
+        Err(err) if unknown.is_empty() => Err(serde_json::json!({ "error": err.to_string() })),
+        Err(err) => Err(unknown_fields_error(format!("{} (ignored unknown fields: {})", err, unknown.join(", ")), &unknown, accepted)),
+    }
+}
+
+fn check_unknown_fields(unknown: &[String], accepted: &[&str], strict: bool) -> Result<(), serde_json::Value> {
+    if strict && !unknown.is_empty() {
+        return Err(unknown_fields_error(format!("Unknown fields: {}", unknown.join(", ")), unknown, accepted));
+    }
+    Ok(())
+}
+
+/// Something a client should know about an otherwise successful request.
+/// Responses list them in a top-level `warnings` array (`with_warnings`).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+struct Warning {
+    code: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    task: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<String>,
+}
+
+impl Warning {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Warning {
+            code,
+            message: message.into(),
+            task: None,
+            column: None,
+        }
+    }
+
+    fn task(mut self, id: &str) -> Self {
+        self.task = Some(id.to_string());
+        self
+    }
+
+    fn column(mut self, id: &str) -> Self {
+        self.column = Some(id.to_string());
+        self
+    }
+}
+
+/// Appends `warnings` to a JSON object response's `warnings` array, adding
+/// the array when there is something to report.
+fn with_warnings(mut payload: serde_json::Value, warnings: impl IntoIterator<Item = impl Serialize>) -> serde_json::Value {
+    let mut warnings = warnings.into_iter().peekable();
+    if let (Some(object), true) = (payload.as_object_mut(), warnings.peek().is_some()) {
+        let list = object.entry("warnings").or_insert_with(|| serde_json::json!([]));
+        if let Some(list) = list.as_array_mut() {
+            list.extend(warnings.map(|w| serde_json::json!(w)));
+        }
+    }
+    payload
+}
+
+/// Adds `ignored_fields`, and an `ignored_field` warning for each, to a JSON
+/// object response when any were ignored.
+fn with_ignored_fields(mut payload: serde_json::Value, ignored: &[String]) -> serde_json::Value {
+    if let (Some(object), false) = (payload.as_object_mut(), ignored.is_empty()) {
+        object.insert("ignored_fields".to_string(), serde_json::json!(ignored));
+    }
+    with_warnings(
+        payload,
+        ignored.iter().map(|field| Warning::new("ignored_field", format!("unknown field {:?} was ignored", field))),
+    )
+}
+
+/// `GET /api/ui`: UI defaults plus what this server supports, so the frontend
+/// does not have to probe endpoints.
+#[derive(Debug, Serialize)]
+struct Capabilities {
+    show_task_editor: bool,
+    show_board_editor: bool,
+    api_version: u32,
+    api_versions: ApiVersions,
+    ui: UiOptions,
+    board: BoardInfo,
+    timezone: String,
+    features: Features,
+    limits: Limits,
+    slugs: SlugRules,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiVersions {
+    /// Route prefixes such as `v1` in `/api/v1/...`.
+    supported: Vec<&'static str>,
+    /// `Sunset` of the unprefixed, deprecated `/api/...` routes.
+    legacy_sunset: &'static str,
+}
+
+/// How the server derives ids from titles, so clients can preview them.
+#[derive(Debug, Serialize)]
+struct SlugRules {
+    stopwords_enabled: bool,
+    lang: String,
+    /// Dropped words, lowercase; empty when stop-words are off.
+    stopwords: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct BoardInfo {
+    name: String,
+    /// Canonical absolute path of the board root, as printed at startup.
+    root: String,
+    /// Absolute URL of the board as the client reached it.
+    url: String,
+    status_mode: StatusMode,
+    statuses: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Features {
+    read_only: bool,
+    auth_required: bool,
+    stable_ids: bool,
+    multi_board: bool,
+    archive: bool,
+    sse: bool,
+    search: bool,
+    stats: bool,
+    attachments: bool,
+    undo: bool,
+    history: bool,
+    strict_api: bool,
+    /// A board read-me exists (`GET /api/readme`).
+    readme: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Limits {
+    max_body_bytes: usize,
+    attachment_max_bytes: usize,
+    search_max_results: usize,
+    history_versions: usize,
+    undo_entries: usize,
+    rate_limit_per_minute: Option<u32>,
+    /// None when listings are not capped.
+    max_tasks_per_response: Option<usize>,
+    /// None when the column count is not capped.
+    max_columns: Option<usize>,
+}
+
+/// The served board's name: its folder name.
+fn board_name(root: &Path) -> String {
+    fs::canonicalize(root)
+        .unwrap_or_else(|_| root.to_path_buf())
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn capabilities(settings: &Settings, root: &Path, config: &BoardConfig, peer: &Peer) -> Capabilities {
+    Capabilities {
+        show_task_editor: settings.ui.show_task_editor,
+        show_board_editor: settings.ui.show_board_editor,
+        api_version: API_VERSION,
+        api_versions: ApiVersions { supported: API_VERSIONS.to_vec(), legacy_sunset: LEGACY_API_SUNSET },
+        ui: settings.ui,
+        board: BoardInfo {
+            name: board_name(root),
+            root: root.display().to_string(),
+            url: format!(
+                "{}://{}{}/",
+                peer.proto,
+                peer.host.as_deref().unwrap_or("localhost"),
+                settings.board.base_path
+            ),
+            status_mode: config.status_mode,
+            statuses: config.statuses.clone(),
+        },
+        timezone: format_utc_offset(settings.timezone),
+        features: Features {
+            read_only: false,
+            auth_required: false,
+            stable_ids: false,
+            multi_board: false,
+            archive: false,
+            sse: true,
+            search: true,
+            stats: true,
+            attachments: true,
+            undo: true,
+            history: settings.history_limit > 0,
+            strict_api: settings.strict_api,
+            readme: readme_path(root).is_some(),
+        },
+        limits: Limits {
+            max_body_bytes: ATTACHMENT_MAX_BYTES,
+            attachment_max_bytes: ATTACHMENT_MAX_BYTES,
+            search_max_results: SEARCH_MAX_LIMIT,
+            history_versions: settings.history_limit,
+            undo_entries: JOURNAL_CAPACITY,
+            rate_limit_per_minute: None,
+            max_tasks_per_response: Some(settings.max_tasks_per_response).filter(|max| *max > 0),
+            max_columns: Some(settings.board.max_columns).filter(|max| *max > 0),
+        },
+        slugs: SlugRules {
+            stopwords_enabled: config.slug_stopwords,
+            lang: stopword_lang(&settings.board.lang).to_string(),
+            stopwords: slug_stopwords(config, &settings.board.lang),
+        },
+    }
+}
+
+fn main() -> io::Result<()> {
+    let settings = match parse_args() {
+        Ok(v) => v,
+        Err(msg) if std::env::args().any(|arg| arg == "--startup-json") => {
+            eprintln!("{}", serde_json::json!({ "error": msg }));
+            std::process::exit(1);
+        }
+        Err(msg) => {
+            eprintln!("{}\n", msg);
+            print_help();
+            std::process::exit(1);
+        }
+    };
+    let quiet = settings.quiet || settings.startup_json;
+    let port = settings.port;
+    let yes = settings.yes;
+    REQUEST_LANG.with(|cell| cell.set(message_lang(&settings.board.lang)));
+    let root = settings.target.clone().unwrap_or_else(|| "./kanban_data".to_string());
+    if settings.status {
+        let pidfile = settings.pidfile.clone().unwrap_or_else(|| absolute_path(Path::new(&root)).join(PID_FILE));
+        std::process::exit(server_status(&pidfile));
+    }
+    if let Some(command) = settings.service {
+        std::process::exit(service_command(command, &absolute_path(Path::new(&root))));
+    }
+    if settings.merge {
+        std::process::exit(merge_boards(&settings.merge_options, &settings.board));
+    }
+    if let (true, Some(path), Some(task)) = (settings.journal_grep, &settings.debug_journal, &settings.journal_task) {
+        if !debug_journal_files(path).iter().any(|file| file.exists()) {
+            eprintln!("No debug journal at {}", path.display());
+            std::process::exit(1);
+        }
+        match grep_debug_journal(path, task, &mut io::stdout().lock()) {
+            Ok(0) => {
+                eprintln!("No journaled request concerns {}", task);
+                std::process::exit(1);
+            }
+            Ok(_) => return Ok(()),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+    let root_path = match canonical_root(&root) {
+        Ok(path) => path,
+        Err(err) => startup_failed(&settings, &format!("Cannot resolve board root {}: {}", root, err)),
+    };
+    if let Some(reason) = unusual_root_reason(&root_path).filter(|_| !settings.allow_unusual_root) {
+        startup_failed(
+            &settings,
+            &format!(
+                "Refusing to use {} as the board root: it is {} (pass --allow-unusual-root to use it anyway)",
+                root_path.display(),
+                reason
+            ),
+        );
+    }
+    let defaults = board_defaults(&settings);
+    let bind = format!("0.0.0.0:{}", port);
+    if settings.validate_only || settings.doctor {
+        let scan = if settings.doctor {
+            if root_path.is_dir() {
+                let _ = CASE_INSENSITIVE_FS.set(probe_case_insensitive(&root_path));
+            }
+            doctor(&root_path, yes, settings.backfill, settings.reindex, settings.relocate_foreign, &settings.board)
+        } else {
+            scan_board(&root_path, yes, &settings.board)
+        };
+        print_summary(&scan, &bind);
+        std::process::exit(if scan.servable() { 0 } else { 1 });
+    }
+    if settings.recover {
+        std::process::exit(recover(&root_path, settings.apply));
+    }
+    if let Some(file) = &settings.from_pack {
+        std::process::exit(init_from_pack(&root_path, file, &settings.board));
+    }
+    if settings.export {
+        if let Err(err) = export_board(&root_path, &settings.board, settings.output.as_deref()) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if let Some(file) = &settings.import {
+        let options = CsvImportOptions { history_limit: settings.history_limit, ..settings.csv.clone() };
+        std::process::exit(import_csv_file(&root_path, file, &settings.board, &options));
+    }
+    if settings.publish {
+        match publish_board(&root_path, &settings.board, &settings.publish_options) {
+            Ok(report) => {
+                println!(
+                    "Published {} task(s) to {} ({} files, {})",
+                    report.tasks,
+                    report.out.display(),
+                    report.files,
+                    format_size(report.bytes)
+                );
+                return Ok(());
+            }
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+    if settings.dry_run {
+        if let Err(err) = dry_run(&root_path, &defaults, &settings.board) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    let pidfile = settings.pidfile.clone().or_else(|| settings.daemonize.then(|| root_path.join(PID_FILE)));
+    if let Some((pid, _)) = pidfile.as_deref().and_then(running_server) {
+        startup_failed(
+            &settings,
+            &format!(
+                "Another server (pid {}) is running with pidfile {}; stop it first or pass another --pidfile",
+                pid,
+                pidfile.as_deref().unwrap_or(Path::new("")).display()
+            ),
+        );
+    }
+    if let Err(msg) = setup_board(&settings, &root_path, &defaults) {
+        startup_failed(&settings, &msg);
+    }
+    let _ = CASE_INSENSITIVE_FS.set(probe_case_insensitive(&root_path));
+    if let Some(path) = &settings.debug_journal {
+        eprintln!("WARNING: --debug-journal records every request that changes the board, bodies included, in {}.", path.display());
+        eprintln!("         It holds task contents and whatever else clients send; turn it off once you are done debugging.");
+    }
+
+    if !quiet {
+        print_summary(&scan_board(&root_path, yes, &settings.board), &listen_label(&settings, &bind));
+    }
+    if settings.reindex {
+        match read_config(&root_path).and_then(|cfg| rebuild_manifest(&root_path, &cfg, &settings.board)) {
+            Ok(count) if !quiet => println!("Manifest:    rebuilt with {} task(s)", count),
+            Ok(_) => {}
+            Err(err) => startup_failed(&settings, &format!("Could not rebuild {}: {}", MANIFEST_FILE, err)),
+        }
+    }
+    if let (true, Some(pidfile)) = (settings.daemonize, &pidfile) {
+        let log = settings.log_file.clone().unwrap_or_else(|| root_path.join(SERVER_LOG_FILE));
+        let line = match daemonize(pidfile, &log) {
+            Ok(line) => line,
+            Err(msg) => startup_failed(&settings, &msg),
+        };
+        if settings.startup_json {
+            println!("{}", line);
+        } else if !quiet {
+            let started: serde_json::Value = serde_json::from_str(&line).unwrap_or_default();
+            match started["url"].as_str().or(started["socket"].as_str()) {
+                Some(at) => println!("Kanban server running in the background on {} (pid {})", at, started["pid"]),
+                None => println!("Kanban server running in the background (pid {})", started["pid"]),
+            }
+            println!("Pidfile:     {}", pidfile.display());
+            println!("Log:         {}", log.display());
+        }
+        return Ok(());
+    }
+    let handle = match start_server(&bind, root_path.clone(), settings.clone()) {
+        Ok(handle) => handle,
+        Err(err) => startup_failed(&settings, &err.to_string()),
+    };
+    let url = handle.addr.map(|addr| format!("http://localhost:{}{}/", addr.port(), settings.board.base_path));
+    let line = startup_line(&bind, &root_path, handle.addr, handle.socket.as_deref(), url.as_deref());
+    if settings.startup_json {
+        println!("{}", line);
+    }
+    if let Some(pidfile) = &pidfile {
+        if let Err(err) = write_pidfile(pidfile, &line) {
+            startup_failed(&settings, &format!("Could not write pidfile {}: {}", pidfile.display(), err));
+        }
+    }
+    #[cfg(unix)]
+    if handle.socket.is_some() || pidfile.is_some() {
+        remove_on_signal(handle.socket.iter().chain(&pidfile).cloned().collect());
+    }
+    if let Some(socket) = &handle.socket {
+        if !quiet {
+            println!("Kanban server listening on unix socket {}", socket.display());
+        }
+    }
+    let Some(url) = url else {
+        if settings.open_browser && !quiet {
+            println!("Not opening a browser: the server only listens on a unix socket (add --tcp)");
+        }
+        handle.wait();
+        remove_pidfile(pidfile.as_deref());
+        return Ok(());
+    };
+    if !quiet {
+        println!("Kanban server running on {}", url);
+    }
+    if settings.open_browser {
+        let marker = browser_marker_path(&root_path);
+        let already_opened = settings.open_browser_once && marker.exists();
+        if !already_opened {
+            if let Err(err) = open_browser_url(&url) {
+                startup_error(&settings, &format!("Failed to open browser: {}", err));
+            } else if settings.open_browser_once {
+                let _ = fs::write(marker, url.as_bytes());
+            }
+        }
+    }
+    handle.wait();
+    remove_pidfile(pidfile.as_deref());
+    Ok(())
+}
+
+/// The board file `setup_board` writes when there is none yet: the columns
+/// of `--template`, titled in `--lang`.
+fn board_defaults(settings: &Settings) -> BoardConfig {
+    BoardConfig {
+        columns: template_columns(&settings.template, &settings.board.lang).unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+/// Prints a startup error on stderr: one JSON object `{"error": ...}` with
+/// `--startup-json`, the plain text otherwise.
+fn startup_error(settings: &Settings, msg: &str) {
+    if settings.startup_json {
+        eprintln!("{}", serde_json::json!({ "error": msg }));
+    } else {
+        eprintln!("{}", msg);
+    }
+}
+
+fn startup_failed(settings: &Settings, msg: &str) -> ! {
+    startup_error(settings, msg);
+    std::process::exit(1);
+}
+
+/// The one line `--startup-json` prints once the server listens. `port`
+/// and `url` are null when only a unix socket is served; `socket` is only
+/// present with `--unix-socket`.
+fn startup_line(
+    bind: &str,
+    root: &Path,
+    addr: Option<std::net::SocketAddr>,
+    socket: Option<&Path>,
+    url: Option<&str>,
+) -> String {
+    let host = bind.rsplit_once(':').map_or(bind, |(host, _)| host);
+    let mut line = serde_json::json!({
+        "port": addr.map(|addr| addr.port()),
+        "bind": host,
+        "root": root.display().to_string(),
+        "pid": std::process::id(),
+        "url": url,
+    });
+    if let Some(socket) = socket {
+        line["socket"] = serde_json::json!(socket.display().to_string());
+    }
+    line.to_string()
+}
+
+/// Creates or checks everything the server needs before it binds: the root
+/// folder, the board file, an optional default theme and pending migrations.
+/// May prompt on a terminal unless `settings.yes` is set.
+fn setup_board(settings: &Settings, root_path: &Path, defaults: &BoardConfig) -> Result<(), String> {
+    let quiet = settings.quiet || settings.startup_json;
+    if ensure_root(root_path, settings.create_root)? && !quiet {
+        println!("Created board root {}", absolute_path(root_path).display());
+    }
+    check_format_version(root_path)?;
+    if let Some(format) = settings.write_default_theme {
+        let path = match format {
+            ThemeFormat::Conf => theme_path(root_path),
+            ThemeFormat::Json => theme_json_path(root_path),
+        };
+        match write_default_theme(root_path, format) {
+            Ok(_) if quiet => {}
+            Ok(true) => println!("Created default theme file at {}", path.display()),
+            Ok(false) => println!("Theme file already exists at {}", path.display()),
+            Err(err) => return Err(format!("Failed to write theme: {}", err)),
+        }
+    }
+    if !is_known_lang(&settings.board.lang) && !quiet {
+        eprintln!(
+            "Warning: no column titles for language '{}', using English",
+            settings.board.lang
+        );
+    }
+    ensure_config(root_path, settings.yes, defaults).map_err(|err| err.to_string())?;
+    let config = prepare_board(root_path, settings.yes, &settings.board)?;
+    for line in run_migrations(root_path, &config, &settings.board, false)?.into_iter().filter(|_| !quiet) {
+        println!("Ran {}", line);
+    }
+    Ok(())
+}
+
+/// State shared by every request thread.
+struct AppState {
+    root_path: PathBuf,
+    settings: Arc<Settings>,
+    update_state: Arc<UpdateState>,
+    task_cache: Arc<TaskCache>,
+    journal: Arc<Journal>,
+    locks: Arc<TaskLocks>,
+    events: Arc<EventBus>,
+    theme_cache: Arc<ThemeCache>,
+    hooks: Arc<Hooks>,
+    debug_journal: Option<DebugJournal>,
+}
+
+/// A server started by `start_server`.
+struct ServerHandle {
+    /// The bound TCP address; its port is the real one when port 0 was
+    /// asked for. None when only a unix socket is served.
+    addr: Option<std::net::SocketAddr>,
+    /// The unix socket file, removed once the request loops end.
+    socket: Option<PathBuf>,
+    servers: Vec<Arc<Server>>,
+    stopped: Arc<AtomicBool>,
+    threads: Vec<std::thread::JoinHandle<()>>,
+}
+
+impl ServerHandle {
+    /// Blocks until the request loops end.
+    fn wait(self) {
+        for thread in self.threads {
+            let _ = thread.join();
+        }
+        if let Some(socket) = &self.socket {
+            let _ = fs::remove_file(socket);
+        }
+    }
+
+    /// Stops accepting requests and waits for the request loops to end.
+    /// Requests already being handled finish on their own threads. Used
+    /// when the Windows service is stopped; elsewhere the binary runs until
+    /// killed, so only the tests call this.
+    #[cfg_attr(not(any(test, windows)), allow(dead_code))]
+    fn shutdown(self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        for server in &self.servers {
+            server.unblock();
+        }
+        self.wait();
+    }
+}
+
+/// Binds a unix domain socket that only its owner may use. A socket file
+/// left behind by a server that is gone is replaced; a socket another
+/// process listens on, or a file that is no socket, is not.
+#[cfg(unix)]
+fn bind_unix_socket(path: &Path) -> io::Result<Server> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+    if let Ok(meta) = fs::symlink_metadata(path) {
+        if !meta.file_type().is_socket() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} exists and is not a socket", path.display())));
+        }
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, format!("{} is in use by another process", path.display())));
+        }
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Server::from_listener(listener, None).map_err(io::Error::other)
+}
+
+#[cfg(not(unix))]
+fn bind_unix_socket(_path: &Path) -> io::Result<Server> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "--unix-socket is only supported on Unix-like systems"))
+}
+
+/// Set by SIGINT or SIGTERM once `remove_socket_on_signal` is armed.
+#[cfg(unix)]
+static TERMINATING: AtomicBool = AtomicBool::new(false);
+
+/// Removes the unix socket file and the pidfile before the process exits
+/// on SIGINT or SIGTERM, which would otherwise leave them behind.
+#[cfg(unix)]
+fn remove_on_signal(paths: Vec<PathBuf>) {
+    extern "C" {
+        fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+    }
+    extern "C" fn on_signal(_: i32) {
+        TERMINATING.store(true, Ordering::SeqCst);
+    }
+    const SIGINT: i32 = 2;
+    const SIGTERM: i32 = 15;
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    unsafe {
+        signal(SIGINT, on_signal);
+        signal(SIGTERM, on_signal);
+    }
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_millis(100));
+        if TERMINATING.load(Ordering::SeqCst) {
+            for path in &paths {
+                let _ = fs::remove_file(path);
+            }
+            std::process::exit(0);
+        }
+    });
+}
+
+/// Where `--daemonize` records the server unless `--pidfile` is given.
+const PID_FILE: &str = ".kanban-server.pid";
+/// Where `--daemonize` sends the server's output unless `--log-file` is given.
+const SERVER_LOG_FILE: &str = ".kanban-server.log";
+/// How long `--daemonize` waits for the background server to listen.
+const DAEMON_START_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Records a listening server in `path`: its `--startup-json` line, so
+/// `status` learns the pid and port. Written atomically, as `daemonize`
+/// polls for it.
+fn write_pidfile(path: &Path, line: &str) -> io::Result<()> {
+    let staged = path.with_extension("pid.tmp");
+    fs::write(&staged, format!("{}\n", line))?;
+    fs::rename(&staged, path)
+}
+
+fn remove_pidfile(path: Option<&Path>) {
+    if let Some(path) = path {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// The line a pidfile holds, if it is one.
+fn read_pidfile(path: &Path) -> Option<serde_json::Value> {
+    let line: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    line["pid"].is_u64().then_some(line)
+}
+
+/// The pid and line of the server `path` names, while that server runs.
+/// On Unix the process is looked up; elsewhere its port has to answer.
+fn running_server(path: &Path) -> Option<(u32, serde_json::Value)> {
+    let line = read_pidfile(path)?;
+    let pid = u32::try_from(line["pid"].as_u64()?).ok()?;
+    #[cfg(unix)]
+    let running = process_alive(pid);
+    #[cfg(not(unix))]
+    let running = line["port"].as_u64().and_then(|port| u16::try_from(port).ok()).is_some_and(|port| {
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+        std::net::TcpStream::connect_timeout(&addr, Duration::from_secs(1)).is_ok()
+    });
+    running.then_some((pid, line))
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    extern "C" {
+        fn kill(pid: i32, signum: i32) -> i32;
+    }
+    const EPERM: i32 = 1;
+    let Ok(pid) = i32::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks whether the process exists.
+    let found = unsafe { kill(pid, 0) } == 0;
+    found || io::Error::last_os_error().raw_os_error() == Some(EPERM)
+}
+
+/// `status`: prints whether the server recorded in `pidfile` runs and where
+/// it listens. Returns the exit code, 0 when it runs and 3 when it does not,
+/// as init scripts expect.
+fn server_status(pidfile: &Path) -> i32 {
+    if !pidfile.exists() {
+        println!("Not running: no pidfile at {}", pidfile.display());
+        return 3;
+    }
+    let Some(line) = read_pidfile(pidfile) else {
+        eprintln!("{} is not a kanban-server pidfile", pidfile.display());
+        return 1;
+    };
+    let Some((pid, line)) = running_server(pidfile) else {
+        println!("Not running: pid {} from {} is gone (stale pidfile)", line["pid"], pidfile.display());
+        return 3;
+    };
+    match (line["port"].as_u64(), line["socket"].as_str()) {
+        (Some(port), _) => println!("Running: pid {} on port {} ({})", pid, port, line["url"].as_str().unwrap_or("")),
+        (None, Some(socket)) => println!("Running: pid {} on unix socket {}", pid, socket),
+        (None, None) => println!("Running: pid {}", pid),
+    }
+    0
+}
+
+/// `--daemonize`: starts this binary again with the same arguments but
+/// without the flag, detached from the terminal, stdin closed and its output
+/// appended to `log`. Returns the background server's startup line once it
+/// wrote `pidfile`, or why it did not start.
+fn daemonize(pidfile: &Path, log: &Path) -> Result<String, String> {
+    let mut args = Vec::new();
+    let mut given = std::env::args().skip(1);
+    while let Some(arg) = given.next() {
+        match arg.as_str() {
+            "--daemonize" => {}
+            "--pidfile" | "--log-file" => {
+                given.next();
+            }
+            _ => args.push(arg),
+        }
+    }
+    let exe = std::env::current_exe().map_err(|err| format!("Cannot find the server binary: {}", err))?;
+    let out = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log)
+        .map_err(|err| format!("Cannot open log file {}: {}", log.display(), err))?;
+    let err = out.try_clone().map_err(|err| format!("Cannot open log file {}: {}", log.display(), err))?;
+    let mut command = Command::new(exe);
+    command
+        .args(&args)
+        .arg("--pidfile")
+        .arg(pidfile)
+        .env_remove("KANBAN_DAEMONIZE")
+        .env_remove("KANBAN_LOG_FILE")
+        .stdin(Stdio::null())
+        .stdout(out)
+        .stderr(err);
+    detach(&mut command);
+    let _ = fs::remove_file(pidfile);
+    let mut child = command.spawn().map_err(|err| format!("Cannot start the background server: {}", err))?;
+    let started = Instant::now();
+    loop {
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(format!("The background server stopped ({}); see {}", status, log.display()));
+        }
+        if let Some(line) = read_pidfile(pidfile).filter(|line| line["pid"] == child.id()) {
+            return Ok(line.to_string());
+        }
+        if started.elapsed() > DAEMON_START_TIMEOUT {
+            return Err(format!(
+                "The background server (pid {}) did not start listening within {}s; see {}",
+                child.id(),
+                DAEMON_START_TIMEOUT.as_secs(),
+                log.display()
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Puts the background server in a session of its own, so closing the
+/// terminal does not stop it.
+#[cfg(unix)]
+fn detach(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    extern "C" {
+        fn setsid() -> i32;
+    }
+    // SAFETY: setsid is async-signal-safe and touches no memory of ours.
+    unsafe {
+        command.pre_exec(|| {
+            setsid();
+            Ok(())
+        });
+    }
+}
+
+/// Starts the background server without a console of its own, in a process
+/// group that does not get the terminal's Ctrl+C.
+#[cfg(windows)]
+fn detach(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const DETACHED_PROCESS: u32 = 0x0000_0008;
+    const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+    command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn detach(_: &mut Command) {}
+
+/// Name of the Windows service `service install` registers.
+#[cfg(windows)]
+const SERVICE_NAME: &str = "kanban-server";
+
+/// A `service` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ServiceCommand {
+    /// Register a service that starts with the machine and serves the board
+    /// with the other options given.
+    Install,
+    /// Stop and remove that service.
+    Uninstall,
+    /// Serve as the service; only the service manager starts this.
+    Run,
+}
+
+/// The command line the installed service starts with: the one given,
+/// without `service install`, with the board root and the pidfile and log
+/// paths made absolute (services start in the system folder), and `--yes`,
+/// as nobody answers prompts.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn service_arguments(given: &[String], root: &Path) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut given = given.iter();
+    while let Some(arg) = given.next() {
+        match arg.as_str() {
+            "service" | "-t" | "--target" => {
+                given.next();
+            }
+            "--pidfile" | "--log-file" => {
+                if let Some(path) = given.next() {
+                    args.push(arg.clone());
+                    args.push(absolute_path(Path::new(path)).display().to_string());
+                }
+            }
+            _ => args.push(arg.clone()),
+        }
+    }
+    args.extend(["--target".to_string(), root.display().to_string(), "--yes".to_string()]);
+    args.extend(["service".to_string(), "run".to_string()]);
+    args
+}
+
+/// `service install|uninstall|run`: manages the Windows service that serves
+/// the board at `root`. Returns the exit code.
+#[cfg(windows)]
+fn service_command(command: ServiceCommand, root: &Path) -> i32 {
+    let result = match command {
+        ServiceCommand::Install => install_service(root),
+        ServiceCommand::Uninstall => uninstall_service(),
+        ServiceCommand::Run => windows_service::service_dispatcher::start(SERVICE_NAME, ffi_service_main).map_err(|err| {
+            format!("Cannot run as a service: {} (the service manager starts it: sc start {})", service_error(err), SERVICE_NAME)
+        }),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            1
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn service_command(_: ServiceCommand, _: &Path) -> i32 {
+    eprintln!("The service commands are only available on Windows; use --daemonize or a service manager such as systemd");
+    1
+}
+
+/// The message of a service manager error, with the system's reason.
+#[cfg(windows)]
+fn service_error(err: windows_service::Error) -> String {
+    match err {
+        windows_service::Error::Winapi(err) => err.to_string(),
+        err => err.to_string(),
+    }
+}
+
+/// Registers `SERVICE_NAME` to start with the machine, running this binary
+/// with `service_arguments`.
+#[cfg(windows)]
+fn install_service(root: &Path) -> Result<(), String> {
+    use windows_service::service::{ServiceAccess, ServiceErrorControl, ServiceInfo, ServiceStartType, ServiceType};
+    use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    let access = ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE;
+    let manager = ServiceManager::local_computer(None::<&str>, access)
+        .map_err(|err| format!("Cannot open the service manager (run as administrator): {}", service_error(err)))?;
+    let given: Vec<String> = std::env::args().skip(1).collect();
+    let info = ServiceInfo {
+        name: SERVICE_NAME.into(),
+        display_name: "Kanban server".into(),
+        service_type: ServiceType::OWN_PROCESS,
+        start_type: ServiceStartType::AutoStart,
+        error_control: ServiceErrorControl::Normal,
+        executable_path: std::env::current_exe().map_err(|err| format!("Cannot find the server binary: {}", err))?,
+        launch_arguments: service_arguments(&given, root).into_iter().map(Into::into).collect(),
+        dependencies: Vec::new(),
+        account_name: None,
+        account_password: None,
+    };
+    let service = manager
+        .create_service(&info, ServiceAccess::CHANGE_CONFIG)
+        .map_err(|err| format!("Cannot install service {}: {}", SERVICE_NAME, service_error(err)))?;
+    let _ = service.set_description(format!("Serves the kanban board at {}", root.display()));
+    let vars: Vec<(String, String)> = std::env::vars().filter(|(key, _)| key.starts_with("KANBAN_")).collect();
+    set_service_environment(&vars).map_err(|err| format!("Cannot pass the KANBAN_* variables to service {}: {}", SERVICE_NAME, err))?;
+    println!("Installed service {} for {}; it starts with the machine, or now with: sc start {}", SERVICE_NAME, root.display(), SERVICE_NAME);
+    Ok(())
+}
+
+/// Gives the service `vars`, as a service does not inherit the environment
+/// of whoever installed it: the service manager reads them from the
+/// `Environment` value of the service's registry key.
+#[cfg(windows)]
+fn set_service_environment(vars: &[(String, String)]) -> io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::System::Registry::{RegSetKeyValueW, HKEY_LOCAL_MACHINE, REG_MULTI_SZ};
+    if vars.is_empty() {
+        return Ok(());
+    }
+    let wide = |text: &str| std::ffi::OsStr::new(text).encode_wide().chain([0]).collect::<Vec<u16>>();
+    let key = wide(&format!("SYSTEM\\CurrentControlSet\\Services\\{}", SERVICE_NAME));
+    let name = wide("Environment");
+    let mut data: Vec<u16> = vars.iter().flat_map(|(key, value)| wide(&format!("{}={}", key, value))).collect();
+    data.push(0);
+    let size = u32::try_from(data.len() * 2).map_err(io::Error::other)?;
+    // SAFETY: the key and name are NUL-terminated and `data` holds `size`
+    // bytes; all outlive the call.
+    let status = unsafe { RegSetKeyValueW(HKEY_LOCAL_MACHINE, key.as_ptr(), name.as_ptr(), REG_MULTI_SZ, data.as_ptr().cast(), size) };
+    match status {
+        0 => Ok(()),
+        code => Err(io::Error::from_raw_os_error(code as i32)),
+    }
+}
+
+/// Stops `SERVICE_NAME` if it runs and removes it.
+#[cfg(windows)]
+fn uninstall_service() -> Result<(), String> {
+    use windows_service::service::{ServiceAccess, ServiceState};
+    use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)
+        .map_err(|err| format!("Cannot open the service manager (run as administrator): {}", service_error(err)))?;
+    let access = ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE;
+    let service = manager
+        .open_service(SERVICE_NAME, access)
+        .map_err(|err| format!("Cannot open service {}: {}", SERVICE_NAME, service_error(err)))?;
+    let state = service.query_status().map_err(service_error)?.current_state;
+    if state != ServiceState::Stopped {
+        service.stop().map_err(|err| format!("Cannot stop service {}: {}", SERVICE_NAME, service_error(err)))?;
+    }
+    service.delete().map_err(|err| format!("Cannot remove service {}: {}", SERVICE_NAME, service_error(err)))?;
+    println!("Removed service {}", SERVICE_NAME);
+    Ok(())
+}
+
+#[cfg(windows)]
+windows_service::define_windows_service!(ffi_service_main, service_main);
+
+/// Runs on a thread of the service manager's once `service run` started
+/// the dispatcher. The dispatcher hands over no state, so the command line
+/// is parsed again. Serves until the service is stopped; startup errors go
+/// to `--log-file`, as a service has no console.
+#[cfg(windows)]
+fn service_main(_: Vec<std::ffi::OsString>) {
+    use windows_service::service::{ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus, ServiceType};
+    use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
+    let Ok(settings) = parse_args() else {
+        return;
+    };
+    let (stop, stop_requested) = std::sync::mpsc::channel();
+    let handler = move |control| match control {
+        ServiceControl::Stop | ServiceControl::Shutdown => {
+            let _ = stop.send(());
+            ServiceControlHandlerResult::NoError
+        }
+        ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+        _ => ServiceControlHandlerResult::NotImplemented,
+    };
+    let Ok(status) = service_control_handler::register(SERVICE_NAME, handler) else {
+        return;
+    };
+    let report = |state, controls_accepted, exit_code| {
+        let _ = status.set_service_status(ServiceStatus {
+            service_type: ServiceType::OWN_PROCESS,
+            current_state: state,
+            controls_accepted,
+            exit_code,
+            checkpoint: 0,
+            wait_hint: DAEMON_START_TIMEOUT,
+            process_id: None,
+        });
+    };
+    report(ServiceState::StartPending, ServiceControlAccept::empty(), ServiceExitCode::Win32(0));
+    let exit_code = match start_service_server(&settings) {
+        Ok((handle, pidfile)) => {
+            report(ServiceState::Running, ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN, ServiceExitCode::Win32(0));
+            let _ = stop_requested.recv();
+            report(ServiceState::StopPending, ServiceControlAccept::empty(), ServiceExitCode::Win32(0));
+            handle.shutdown();
+            remove_pidfile(pidfile.as_deref());
+            ServiceExitCode::Win32(0)
+        }
+        Err(msg) => {
+            let root = absolute_path(Path::new(settings.target.as_deref().unwrap_or("./kanban_data")));
+            let log = settings.log_file.clone().unwrap_or_else(|| root.join(SERVER_LOG_FILE));
+            if let Ok(mut file) = fs::OpenOptions::new().create(true).append(true).open(log) {
+                let _ = writeln!(file, "{}", msg);
+            }
+            ServiceExitCode::ServiceSpecific(1)
+        }
+    };
+    report(ServiceState::Stopped, ServiceControlAccept::empty(), exit_code);
+}
+
+/// Sets up the board and starts serving it for `service_main`, recording
+/// the server in `--pidfile` if given. Returns the server and that pidfile.
+#[cfg(windows)]
+fn start_service_server(settings: &Settings) -> Result<(ServerHandle, Option<PathBuf>), String> {
+    let root = settings.target.clone().unwrap_or_else(|| "./kanban_data".to_string());
+    let root_path = canonical_root(&root).map_err(|err| format!("Cannot resolve board root {}: {}", root, err))?;
+    setup_board(settings, &root_path, &board_defaults(settings))?;
+    let _ = CASE_INSENSITIVE_FS.set(probe_case_insensitive(&root_path));
+    let bind = format!("0.0.0.0:{}", settings.port);
+    let handle = start_server(&bind, root_path.clone(), settings.clone()).map_err(|err| err.to_string())?;
+    if let Some(pidfile) = &settings.pidfile {
+        let url = handle.addr.map(|addr| format!("http://localhost:{}{}/", addr.port(), settings.board.base_path));
+        let line = startup_line(&bind, &root_path, handle.addr, None, url.as_deref());
+        write_pidfile(pidfile, &line).map_err(|err| format!("Could not write pidfile {}: {}", pidfile.display(), err))?;
+    }
+    Ok((handle, settings.pidfile.clone()))
+}
+
+/// What the startup summary shows as `Bind`.
+fn listen_label(settings: &Settings, bind: &str) -> String {
+    match &settings.unix_socket {
+        None => bind.to_string(),
+        Some(path) if settings.tcp => format!("{} and unix:{}", bind, path.display()),
+        Some(path) => format!("unix:{}", path.display()),
+    }
+}
+
+/// Binds `bind` (port 0 picks a free port) and, with `--unix-socket`, the
+/// socket, or only the socket unless `--tcp` is set. Serves the board at
+/// `root_path` from background threads. Run `setup_board` first.
+fn start_server(bind: &str, root_path: PathBuf, settings: Settings) -> io::Result<ServerHandle> {
+    let mut servers = Vec::new();
+    let mut addr = None;
+    if settings.unix_socket.is_none() || settings.tcp {
+        let server = Server::http(bind).map_err(io::Error::other)?;
+        addr = Some(
+            server
+                .server_addr()
+                .to_ip()
+                .ok_or_else(|| io::Error::other("server is not listening on an IP address"))?,
+        );
+        servers.push(Arc::new(server));
+    }
+    if let Some(path) = &settings.unix_socket {
+        servers.push(Arc::new(bind_unix_socket(path)?));
+    }
+    let socket = settings.unix_socket.clone();
+    let update_state = Arc::new(UpdateState {
+        version: AtomicU64::new(1),
+        lock: Mutex::new(()),
+        cvar: Condvar::new(),
+    });
+    let task_cache = Arc::new(TaskCache {
+        entries: Mutex::new(HashMap::new()),
+        manifest: settings.manifest.then(|| ManifestState::open(&root_path, update_state.clone())),
+    });
+    let events = Arc::new(EventBus {
+        log: Mutex::new(EventLog::default()),
+        cvar: Condvar::new(),
+    });
+    let theme_cache = Arc::new(ThemeCache {
+        state: Mutex::new(ThemeState::default()),
+    });
+    let stopped = Arc::new(AtomicBool::new(false));
+    let webhook = settings.webhook.as_ref().map(|url| {
+        Arc::new(Webhook {
+            url: url.clone(),
+            root: root_path.clone(),
+            batch_window: Duration::from_millis(settings.webhook_batch_window_ms),
+            retry_for: Duration::from_secs(settings.webhook_retry_hours * 3600),
+            queue: Mutex::new(WebhookQueue::default()),
+            cvar: Condvar::new(),
+        })
+    });
+    if let Some(webhook) = &webhook {
+        let webhook = webhook.clone();
+        let stopped = stopped.clone();
+        std::thread::spawn(move || run_webhook(&webhook, &stopped));
+    }
+    let hooks = Arc::new(Hooks {
+        hook: settings.hook.as_ref().map(|command| Hook {
+            command: command.clone(),
+            events: settings.hook_events.clone(),
+            root: root_path.clone(),
+        }),
+        webhook,
+    });
+    current_theme(&root_path, &theme_cache, &events);
+    {
+        let root_path = root_path.clone();
+        let theme_cache = theme_cache.clone();
+        let events = events.clone();
+        let stopped = stopped.clone();
+        std::thread::spawn(move || {
+            while !stopped.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_secs(2));
+                current_theme(&root_path, &theme_cache, &events);
+            }
+        });
+    }
+    {
+        let root_path = root_path.clone();
+        let opts = settings.board.clone();
+        let task_cache = task_cache.clone();
+        let events = events.clone();
+        let hooks = hooks.clone();
+        let stopped = stopped.clone();
+        std::thread::spawn(move || {
+            // Breaches found by the first scan crossed their threshold while
+            // the server was down; only later crossings are announced.
+            let mut seen = HashSet::new();
+            let mut first = true;
+            while !stopped.load(Ordering::SeqCst) {
+                let cfg = read_config(&root_path).ok().filter(|cfg| cfg.columns.iter().any(|c| c.max_age.is_some()));
+                if let Some(cfg) = cfg {
+                    if let Ok(folders) = load_all_tasks(&root_path, &cfg, &opts, Some(&task_cache)) {
+                        let violations = board_violations(&root_path, &cfg, &folders, OffsetDateTime::now_utc());
+                        for violation in new_age_violations(violations, &mut seen) {
+                            if !first {
+                                announce_violation(&root_path, &hooks, &events, &violation);
+                            }
+                        }
+                    }
+                }
+                first = false;
+                std::thread::sleep(VIOLATION_SCAN_INTERVAL);
+            }
+        });
+    }
+    if task_cache.manifest.is_some() {
+        let root_path = root_path.clone();
+        let opts = settings.board.clone();
+        let task_cache = task_cache.clone();
+        let stopped = stopped.clone();
+        std::thread::spawn(move || {
+            while !stopped.load(Ordering::SeqCst) {
+                std::thread::sleep(MANIFEST_VERIFY_INTERVAL);
+                if let Ok(cfg) = read_config(&root_path) {
+                    if let Err(err) = refresh_manifest(&root_path, &cfg, &opts, &task_cache, true) {
+                        eprintln!("Warning: could not update {}: {}", MANIFEST_FILE, err);
+                    }
+                }
+            }
+        });
+    }
+    if settings.watch {
+        WATCHING.store(true, Ordering::SeqCst);
+        let root_path = root_path.clone();
+        let update_state = update_state.clone();
+        let events = events.clone();
+        let stopped = stopped.clone();
+        let excludes: Vec<String> =
+            WATCH_DEFAULT_EXCLUDES.iter().map(|name| name.to_string()).chain(settings.watch_excludes.clone()).collect();
+        let interval = Duration::from_millis(settings.watch_interval_ms);
+        std::thread::spawn(move || run_watcher(&root_path, &excludes, interval, &update_state, &events, &stopped));
+    }
+    let journal = Arc::new(open_journal(&root_path));
+    let debug_journal = match &settings.debug_journal {
+        Some(path) => Some(DebugJournal::open(path, &root_path, DEBUG_JOURNAL_MAX_BYTES)?),
+        None => None,
+    };
+    let state = Arc::new(AppState {
+        root_path,
+        settings: Arc::new(settings),
+        update_state,
+        task_cache,
+        journal,
+        locks: Arc::new(TaskLocks::default()),
+        events,
+        theme_cache,
+        hooks,
+        debug_journal,
+    });
+    let threads = servers
+        .iter()
+        .map(|server| {
+            let (server, state) = (server.clone(), state.clone());
+            std::thread::spawn(move || serve(&server, state))
+        })
+        .collect();
+    Ok(ServerHandle { addr, socket, servers, stopped, threads })
+}
+
+/// The request loop: one thread per request until the server is unblocked.
+fn serve(server: &Server, state: Arc<AppState>) {
+    for request in server.incoming_requests() {
+        let state = state.clone();
+        std::thread::spawn(move || handle_request(request, &state));
+    }
+}
+
+fn handle_request(mut request: tiny_http::Request, state: &AppState) {
+    let root_path = state.root_path.clone();
+    let update_state = state.update_state.clone();
+    let task_cache = state.task_cache.clone();
+    let settings = state.settings.clone();
+    let journal = state.journal.clone();
+    let locks = state.locks.clone();
+    let events = state.events.clone();
+    let theme_cache = state.theme_cache.clone();
+    let hooks = state.hooks.clone();
+    let method = request.method().clone();
+    let peer = resolve_peer(&request, &settings.trusted_proxies);
+    let actor = request_actor(&request);
+    let admin = is_admin(&request, &settings);
+    let (shape, url) = match route_url(request.url(), &settings.board.base_path) {
+        Ok(url) => api_shape(&url),
+        Err(Some(location)) => {
+            let response = respond_text(StatusCode(308), "")
+                .with_header(Header::from_bytes("Location", location.as_bytes()).unwrap());
+            let _ = request.respond(response);
+            return;
+        }
+        Err(None) => {
+            let _ = request.respond(respond_text(StatusCode(404), "Not Found"));
+            return;
+        }
+    };
+    let target = match parse_target(&url) {
+        Ok(target) => target,
+        Err(msg) => {
+            let _ = request.respond(respond_json(StatusCode(400), &serde_json::json!({ "error": msg }).to_string()));
+            return;
+        }
+    };
+    let path_only = target.path.as_str();
+    let lang = resolve_request_lang(target.query.get("lang").map(String::as_str), header_value(&request, "Accept-Language").as_deref());
+    REQUEST_LANG.with(|cell| cell.set(lang.or_else(|| message_lang(&settings.board.lang))));
+
+    if method == Method::Get && path_only == "/api/events" {
+        stream_events(request, &events);
+        return;
+    }
+    if method == Method::Get {
+        let download = path_only
+            .strip_prefix("/api/tasks/")
+            .and_then(|rest| rest.split_once("/attachments/"))
+            .filter(|(_, name)| !name.is_empty() && !name.contains('/'));
+        if let Some((raw_id, name)) = download {
+            let id = normalize_id(raw_id);
+            if is_valid_id(&id) {
+                serve_attachment(request, &root_path, &id, name, &settings.board);
+                return;
+            }
+        }
+    }
+
+    if path_only.starts_with("/api/") && !root_path.is_dir() {
+        // Never recreate a vanished root mid-request; an empty board
+        // would silently replace whatever was mounted there.
+        let response = respond_error(503, "root_missing", &[]);
+        let _ = request.respond(shape_response(response, shape, &url, &settings.board.base_path));
+        return;
+    }
+    if path_only.starts_with("/api/")
+        && read_config(&root_path).is_err()
+        && (method != Method::Get || last_good_config(&root_path).is_none())
+    {
+        // Only startup may create or prompt for a board file; reads
+        // keep using the last good config meanwhile.
+        let response = respond_error(503, "config_missing", &[]);
+        let _ = request.respond(shape_response(response, shape, &url, &settings.board.base_path));
+        return;
+    }
+    if method == Method::Get && path_only == "/api/export.md" {
+        stream_board_markdown(request, &root_path, &task_cache, &settings.board, shape, &url);
+        return;
+    }
+    if path_only.starts_with("/api/") {
+        let mut raw_body = Vec::new();
+        let _ = Read::take(request.as_reader(), ATTACHMENT_MAX_BYTES as u64 + 1).read_to_end(&mut raw_body);
+        let body = String::from_utf8_lossy(&raw_body).into_owned();
+        let record = state
+            .debug_journal
+            .as_ref()
+            .filter(|_| !matches!(method, Method::Get | Method::Head | Method::Options))
+            .map(|journal| journal.begin(&method, &url, peer.ip, request.headers(), &raw_body));
+
+        let response = match (&method, path_only) {
+            (Method::Get, "/api/updates") => {
+                let since = parse_since(&target.query);
+                let guard = update_state.lock.lock().unwrap();
+                let current = update_state.version.load(Ordering::SeqCst);
+                let mut changed = current > since;
+                if !changed {
+                    let _ = update_state
+                        .cvar
+                        .wait_timeout(guard, Duration::from_secs(25))
+                        .unwrap();
+                    let latest = update_state.version.load(Ordering::SeqCst);
+                    changed = latest > since;
+                }
+                let latest = update_state.version.load(Ordering::SeqCst);
+                respond_json(
+                    StatusCode(200),
+                    &serde_json::json!({ "version": latest, "changed": changed }).to_string(),
+                )
+            }
+            (Method::Get, "/api/board") => match refresh_config(&root_path) {
+                Ok(cfg) => {
+                    let payload = with_warnings(serde_json::json!({ "board": cfg }), lint_columns(&cfg.columns));
+                    respond_json(StatusCode(200), &payload.to_string())
+                }
+            Err(msg) => respond_json(
+                StatusCode(500),
+                &serde_json::json!({"error": msg}).to_string(),
+            ),
+        },
+        (Method::Put, "/api/board") => match refresh_config(&root_path) {
+            Ok(old_cfg) => {
+                let strict = target.query.get("strict").is_some_and(|v| v == "true");
+                match parse_body::<BoardUpdate>(&body, settings.strict_api) {
+                    Ok((update, ignored)) => {
+                        let mut new_config = updated_config(&old_cfg, update);
+                        let warnings = load_all_tasks(&root_path, &new_config, &settings.board, Some(&task_cache))
+                            .map(|folders| {
+                                let counts: HashMap<String, usize> =
+                                    folders.iter().map(|(id, tasks)| (id.clone(), tasks.len())).collect();
+                                wip_limit_warnings(&old_cfg, &new_config, &counts)
+                            })
+                            .unwrap_or_default();
+                        if let Err(msg) = validate_board(&new_config, settings.board.max_columns) {
+                            respond_json(
+                                StatusCode(400),
+                                &serde_json::json!({ "error": msg }).to_string(),
+                            )
+                        } else if strict && !warnings.is_empty() {
+                            respond_json(
+                                StatusCode(409),
+                                &serde_json::json!({
+                                    "error": "WIP limits below current task counts",
+                                    "warnings": warnings,
+                                })
+                                .to_string(),
+                            )
+                        } else {
+                            match apply_board_config(&root_path, &mut new_config, &actor) {
+                                Ok(_) => match refresh_config(&root_path) {
+                                    Ok(cfg) => {
+                                        notify_update(&update_state);
+                                        let payload = with_warnings(serde_json::json!({ "board": cfg }), warnings);
+                                        respond_json(StatusCode(200), &with_ignored_fields(payload, &ignored).to_string())
+                                    }
+                                    Err(msg) => respond_json(
+                                        StatusCode(500),
+                                        &serde_json::json!({"error": msg}).to_string(),
+                                    ),
+                                },
+                                Err(msg) => respond_json(
+                                    StatusCode(500),
+                                    &serde_json::json!({ "error": msg }).to_string(),
+                                ),
+                            }
+                        }
+                    }
+                    Err(error) => respond_json(StatusCode(400), &error.to_string()),
+                }
+            }
+            Err(msg) => respond_json(
+                StatusCode(500),
+                &serde_json::json!({"error": msg}).to_string(),
+            ),
+        },
+        (Method::Get, "/api/board/foreign-files") => match refresh_config(&root_path) {
+            Ok(cfg) => respond_json(StatusCode(200), &foreign_files_report(&root_path, &cfg).to_string()),
+            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+        },
+        (Method::Get, "/api/board/pending-changes") | (Method::Post, "/api/board/pending-changes") => {
+            let proposed = if method == Method::Post {
+                parse_body::<BoardUpdate>(&body, settings.strict_api)
+                    .map_err(|error| (400, error))
+                    .and_then(|(update, _)| {
+                        validate_columns(&update.columns, settings.board.max_columns).map_err(|msg| (400, serde_json::json!({ "error": msg })))?;
+                        Ok(BoardConfig { columns: update.columns, ..Default::default() })
+                    })
+            } else {
+                read_config(&root_path).map_err(|err| (500, serde_json::json!({ "error": err.to_string() })))
+            };
+            match proposed.and_then(|cfg| {
+                plan_reconcile(&root_path, &cfg).map_err(|err| (500, serde_json::json!({ "error": err.to_string() })))
+            }) {
+                Ok(plan) => respond_json(
+                    StatusCode(200),
+                    &serde_json::json!({ "changes": !plan.is_empty(), "plan": plan }).to_string(),
+                ),
+                Err((status, error)) => respond_json(StatusCode(status), &error.to_string()),
+            }
+        }
+        (Method::Post, "/api/board/columns/reorder") => match refresh_config(&root_path) {
+            Ok(cfg) => match parse_body::<ColumnReorder>(&body, settings.strict_api) {
+                Ok((reorder, ignored)) => match reorder_columns(&cfg.columns, &reorder.columns) {
+                    Ok(columns) => {
+                        let mut new_config = BoardConfig { columns, ..cfg };
+                        match apply_board_config(&root_path, &mut new_config, &actor) {
+                            Ok(_) => {
+                                notify_update(&update_state);
+                                let payload = with_ignored_fields(serde_json::json!({ "board": new_config }), &ignored);
+                                respond_json(StatusCode(200), &payload.to_string())
+                            }
+                            Err(msg) => respond_json(
+                                StatusCode(500),
+                                &serde_json::json!({ "error": msg }).to_string(),
+                            ),
+                        }
+                    }
+                    Err(msg) => respond_json(StatusCode(400), &serde_json::json!({ "error": msg }).to_string()),
+                },
+                Err(error) => respond_json(StatusCode(400), &error.to_string()),
+            },
+            Err(msg) => respond_json(
+                StatusCode(500),
+                &serde_json::json!({"error": msg}).to_string(),
+            ),
+        },
+        (Method::Get, "/api/violations") => match refresh_config(&root_path) {
+            Ok(cfg) => match load_all_tasks(&root_path, &cfg, &settings.board, Some(&task_cache)) {
+                Ok(folders) => {
+                    let violations = board_violations(&root_path, &cfg, &folders, OffsetDateTime::now_utc());
+                    respond_json(
+                        StatusCode(200),
+                        &serde_json::json!({ "count": violations.len(), "violations": violations }).to_string(),
+                    )
+                }
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            },
+            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+        },
+        (Method::Get, "/api/ui") => respond_json(
+            StatusCode(200),
+            &serde_json::json!(capabilities(
+                &settings,
+                &root_path,
+                &read_config(&root_path).unwrap_or_default(),
+                &peer
+            ))
+            .to_string(),
+        ),
+        (Method::Get, "/api/readme") => match readme_path(&root_path).map(fs::read_to_string) {
+            None => respond_error(404, "readme_not_found", &[]),
+            Some(Err(err)) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            Some(Ok(text)) if target.query.get("render").is_some_and(|v| v == "html") => Response::from_string(render_markdown_html(&text))
+                .with_header(Header::from_bytes("Content-Type", "text/html; charset=utf-8").unwrap()),
+            Some(Ok(text)) => Response::from_string(text)
+                .with_header(Header::from_bytes("Content-Type", "text/markdown; charset=utf-8").unwrap()),
+        },
+        (Method::Put, "/api/readme") if settings.admin_token.is_some() && !admin => respond_error(401, "admin_required", &[]),
+        (Method::Put, "/api/readme") => match write_readme(&root_path, &body) {
+            Ok(()) => respond_json(StatusCode(200), &serde_json::json!({ "readme": readme_path(&root_path).is_some() }).to_string()),
+            Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+        },
+        (Method::Get, "/api/boardpack") => match export_board_pack(&root_path) {
+            Ok(pack) => respond_json(StatusCode(200), &pack.to_string()).with_header(
+                Header::from_bytes("Content-Disposition", content_disposition(&format!("{}.kanban-pack.json", board_name(&root_path))).as_bytes())
+                    .unwrap(),
+            ),
+            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+        },
+        (Method::Post, "/api/boardpack") if settings.admin_token.is_some() && !admin => respond_error(401, "admin_required", &[]),
+        (Method::Post, "/api/boardpack") => {
+            let planned = parse_board_pack(&body).and_then(|pack| plan_board_pack(&root_path, &pack, &settings.board).map(|config| (pack, config)));
+            match planned {
+                Ok((pack, config)) => match write_board_pack(&root_path, pack, config, &actor) {
+                    Ok(report) => {
+                        notify_update(&update_state);
+                        let _ = current_theme(&root_path, &theme_cache, &events);
+                        respond_json(StatusCode(200), &serde_json::json!({ "applied": report }).to_string())
+                    }
+                    Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+                },
+                Err(errors) => respond_json(
+                    StatusCode(400),
+                    &serde_json::json!({ "error": "Invalid board pack; nothing was changed", "sections": errors }).to_string(),
+                ),
+            }
+        }
+        (Method::Get, "/api/schema") => match refresh_config(&root_path) {
+            Ok(cfg) => {
+                let schemas = request_schemas(&cfg, &settings);
+                let etag = format!("\"{}\"", schemas["fingerprint"].as_str().unwrap_or_default());
+                let response = if header_value(&request, "If-None-Match").as_deref() == Some(etag.as_str()) {
+                    Response::from_string("").with_status_code(StatusCode(304))
+                } else {
+                    respond_json(StatusCode(200), &schemas.to_string())
+                };
+                response.with_header(Header::from_bytes("ETag", etag.as_bytes()).unwrap())
+            }
+            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+        },
+        (Method::Get, "/api/theme") => {
+            let (theme, etag) = current_theme(&root_path, &theme_cache, &events);
+            if header_value(&request, "If-None-Match").as_deref() == Some(etag.as_str()) {
+                Response::from_string("")
+                    .with_status_code(StatusCode(304))
+                    .with_header(Header::from_bytes("ETag", etag.as_bytes()).unwrap())
+            } else {
+                respond_json(StatusCode(200), &serde_json::json!({ "theme": theme }).to_string())
+                    .with_header(Header::from_bytes("ETag", etag.as_bytes()).unwrap())
+            }
+        }
+        (Method::Put, "/api/theme") => match refresh_config(&root_path) {
+            Ok(cfg) => match save_theme_file(&root_path, &cfg, &body) {
+                Ok(()) => {
+                    let (theme, etag) = current_theme(&root_path, &theme_cache, &events);
+                    respond_json(StatusCode(200), &serde_json::json!({ "theme": theme }).to_string())
+                        .with_header(Header::from_bytes("ETag", etag.as_bytes()).unwrap())
+                }
+                Err((code, msg)) => respond_json(
+                    StatusCode(code),
+                    &serde_json::json!({"error": msg}).to_string(),
+                ),
+            },
+            Err(msg) => respond_json(
+                StatusCode(500),
+                &serde_json::json!({"error": msg}).to_string(),
+            ),
+        },
+        (Method::Get, "/api/search") => match refresh_config(&root_path) {
+            Ok(cfg) => {
+                let query = &target.query;
+                let q = query.get("q").map(|v| v.trim().to_string()).unwrap_or_default();
+                let limit = match query.get("limit").map(|v| v.parse::<usize>()) {
+                    None => Ok(SEARCH_DEFAULT_LIMIT),
+                    Some(Ok(n)) if n > 0 => Ok(n.min(SEARCH_MAX_LIMIT)),
+                    Some(_) => Err("limit must be a positive integer"),
+                };
+                let filter = parse_task_filter(query, settings.timezone).map(|mut f| {
+                    f.q = None;
+                    f
+                });
+                if q.is_empty() {
+                    respond_json(StatusCode(400), &serde_json::json!({"error": "query parameter q is required"}).to_string())
+                } else if let Err(msg) = limit {
+                    respond_json(StatusCode(400), &serde_json::json!({"error": msg}).to_string())
+                } else if let Err(msg) = &filter {
+                    respond_json(StatusCode(400), &serde_json::json!({"error": msg}).to_string())
+                } else {
+                    let filter = filter.unwrap_or_default();
+                    match load_all_tasks(&root_path, &cfg, &settings.board, Some(&task_cache)) {
+                        Ok(folders) => {
+                            let results = search_tasks(&cfg, folders, &q, &filter, limit.unwrap_or(SEARCH_DEFAULT_LIMIT));
+                            let payload = serde_json::json!({
+                                "query": q,
+                                "total": results.total,
+                                "results": results.hits,
+                                "filter": filter,
+                                "excluded_missing_dates": results.excluded_missing_dates,
+                            });
+                            respond_json(StatusCode(200), &payload.to_string())
+                        }
+                        Err(err) => respond_json(
+                            StatusCode(500),
+                            &serde_json::json!({"error": err.to_string()}).to_string(),
+                        ),
+                    }
+                }
+            }
+            Err(msg) => respond_json(
+                StatusCode(500),
+                &serde_json::json!({"error": msg}).to_string(),
+            ),
+        },
+        (Method::Get, p) if p.starts_with("/api/mentions/") => {
+            let user = percent_decode(p.trim_start_matches("/api/mentions/"));
+            let user = user.trim().trim_start_matches('@').to_lowercase();
+            if user.is_empty() || user.contains('/') {
+                respond_json(StatusCode(400), &serde_json::json!({"error": "invalid user"}).to_string())
+            } else {
+                match refresh_config(&root_path)
+                    .and_then(|cfg| load_all_tasks(&root_path, &cfg, &settings.board, Some(&task_cache)).map_err(|err| err.to_string()))
+                {
+                    Ok(folders) => {
+                        let mut tasks: Vec<Task> = folders
+                            .into_values()
+                            .flatten()
+                            .filter(|task| task.mentions.contains(&user))
+                            .collect();
+                        tasks.sort_by(|a, b| b.updated_at.cmp(&a.updated_at).then_with(|| a.id.cmp(&b.id)));
+                        respond_json(
+                            StatusCode(200),
+                            &serde_json::json!({ "user": user, "tasks": tasks }).to_string(),
+                        )
+                    }
+                    Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+                }
+            }
+        }
+        (Method::Get, "/api/health") => match refresh_config(&root_path) {
+            Ok(cfg) => respond_json(
+                StatusCode(200),
+                &serde_json::json!({ "status": "ok", "usage": board_usage(&root_path, &cfg, &settings.board) }).to_string(),
+            ),
+            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+        },
+        (Method::Get, p) if p.starts_with("/api/templates/") => match target.segments.as_slice() {
+            [_, _, id] if is_valid_id(id) => match refresh_config(&root_path) {
+                Ok(cfg) => match read_task_template(&root_path, id, cfg.status_mode, &settings.board) {
+                    Ok(template) => respond_json(
+                        StatusCode(200),
+                        &serde_json::json!({ "id": id, "tags": template.tags, "description": template.description }).to_string(),
+                    ),
+                    Err(_) => respond_json(StatusCode(404), &serde_json::json!({"error": "template not found"}).to_string()),
+                },
+                Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+            },
+            _ => respond_json(StatusCode(400), &serde_json::json!({"error": "invalid template id"}).to_string()),
+        },
+        (Method::Get, "/api/snapshots") => {
+            respond_json(StatusCode(200), &serde_json::json!({ "snapshots": list_snapshots(&root_path) }).to_string())
+        }
+        (Method::Post, "/api/snapshots") => {
+            let parsed = if body.trim().is_empty() { Ok((NewSnapshot::default(), Vec::new())) } else { parse_body::<NewSnapshot>(&body, settings.strict_api) };
+            match parsed {
+                Ok((request, ignored)) => {
+                    let name = request.name.map(|n| n.trim().to_lowercase()).unwrap_or_else(|| now_iso()[..10].to_string());
+                    if !is_valid_id(&name) {
+                        respond_json(
+                            StatusCode(400),
+                            &serde_json::json!({"error": format!("invalid snapshot name: {:?} (use a-z, 0-9, '-' and '.')", name)}).to_string(),
+                        )
+                    } else {
+                        match refresh_config(&root_path) {
+                            Ok(cfg) => match load_all_tasks(&root_path, &cfg, &settings.board, Some(&task_cache))
+                                .and_then(|folders| take_snapshot(&root_path, &cfg, &folders, &name))
+                            {
+                                Ok(snapshot) => {
+                                    let payload = serde_json::json!({ "name": snapshot.name, "taken_at": snapshot.taken_at });
+                                    respond_json(StatusCode(201), &with_ignored_fields(payload, &ignored).to_string())
+                                }
+                                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                            },
+                            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+                        }
+                    }
+                }
+                Err(error) => respond_json(StatusCode(400), &error.to_string()),
+            }
+        }
+        (Method::Get, p) if p.starts_with("/api/snapshots/") => match target.segments.as_slice() {
+            [_, _, name, action] if action == "diff" && is_valid_id(name) => match read_snapshot(&root_path, name) {
+                Ok(snapshot) => match refresh_config(&root_path) {
+                    Ok(cfg) => match load_all_tasks(&root_path, &cfg, &settings.board, Some(&task_cache)) {
+                        Ok(folders) => {
+                            let diff = diff_snapshot(&snapshot, &cfg, &folders);
+                            if header_value(&request, "Accept").is_some_and(|accept| accept.contains("text/markdown")) {
+                                Response::from_string(render_snapshot_markdown(&diff, &cfg))
+                                    .with_header(Header::from_bytes("Content-Type", "text/markdown; charset=utf-8").unwrap())
+                            } else {
+                                respond_json(StatusCode(200), &serde_json::json!(diff).to_string())
+                            }
+                        }
+                        Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                    },
+                    Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+                },
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    respond_error(404, "snapshot_not_found", &[])
+                }
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            },
+            _ => respond_error(404, "not_found", &[]),
+        },
+        (Method::Get, "/api/stats/heatmap") => {
+            let days = match target.query.get("days") {
+                None => Ok(HEATMAP_DEFAULT_DAYS),
+                Some(raw) => raw
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|d| (1..=ACTIVITY_MAX_DAYS).contains(d))
+                    .ok_or_else(|| format!("Invalid days: {} (1 to {})", raw, ACTIVITY_MAX_DAYS)),
+            };
+            match (days, refresh_config(&root_path)) {
+                (Err(msg), _) => respond_json(StatusCode(400), &serde_json::json!({"error": msg}).to_string()),
+                (_, Err(msg)) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+                (Ok(days), Ok(cfg)) => match load_all_tasks(&root_path, &cfg, &settings.board, Some(&task_cache)) {
+                    Ok(folders) => {
+                        let log = fs::File::open(root_path.join(ACTIVITY_FILE)).ok().map(io::BufReader::new);
+                        let today = OffsetDateTime::now_utc().to_offset(settings.timezone).date();
+                        let heatmap = activity_heatmap(log, &cfg, &folders, today, settings.timezone, days);
+                        respond_json(StatusCode(200), &serde_json::json!(heatmap).to_string())
+                    }
+                    Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                },
+            }
+        }
+        (Method::Get, "/api/stats/forecast") => {
+            let weeks = match target.query.get("weeks") {
+                None => Ok(FORECAST_DEFAULT_WEEKS),
+                Some(raw) => raw
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|w| (FORECAST_MIN_WEEKS as i64..=FORECAST_MAX_WEEKS).contains(w))
+                    .ok_or_else(|| format!("Invalid weeks: {} ({} to {})", raw, FORECAST_MIN_WEEKS, FORECAST_MAX_WEEKS)),
+            };
+            match (weeks, refresh_config(&root_path)) {
+                (Err(msg), _) => respond_json(StatusCode(400), &serde_json::json!({"error": msg}).to_string()),
+                (_, Err(msg)) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+                (Ok(weeks), Ok(cfg)) => match target.query.get("column").filter(|id| cfg.columns.iter().any(|c| c.id == **id)) {
+                    None => respond_json(
+                        StatusCode(400),
+                        &serde_json::json!({"error": "column must name a board column"}).to_string(),
+                    ),
+                    Some(column) => match scan_column(&root_path, column, cfg.status_mode, &settings.board, Some(&task_cache)) {
+                        Ok(tasks) => {
+                            let log = fs::File::open(root_path.join(ACTIVITY_FILE)).ok().map(io::BufReader::new);
+                            let today = OffsetDateTime::now_utc().to_offset(settings.timezone).date();
+                            let forecast = throughput_forecast(log, column, tasks.len(), today, settings.timezone, weeks);
+                            respond_json(StatusCode(200), &serde_json::json!(forecast).to_string())
+                        }
+                        Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                    },
+                },
+            }
+        }
+        (Method::Get, "/api/stats/burnup") => match refresh_config(&root_path).map(|cfg| (sprint_window(&cfg), cfg)) {
+            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+            Ok((Ok(None), _)) => respond_json(
+                StatusCode(404),
+                &serde_json::json!({"error": "the board has no sprint; set sprint_start and sprint_end"}).to_string(),
+            ),
+            Ok((Err(msg), _)) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+            Ok((Ok(Some(window)), cfg)) => match load_all_tasks(&root_path, &cfg, &settings.board, Some(&task_cache)) {
+                Ok(folders) => {
+                    let log = fs::File::open(root_path.join(ACTIVITY_FILE)).ok().map(io::BufReader::new);
+                    let today = OffsetDateTime::now_utc().to_offset(settings.timezone).date();
+                    let report = burnup(&cfg, &folders, window, log, today, settings.timezone);
+                    respond_json(StatusCode(200), &serde_json::json!(report).to_string())
+                }
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            },
+        },
+        (Method::Get, "/api/stats/workload") => match refresh_config(&root_path) {
+            Ok(cfg) => match load_all_tasks(&root_path, &cfg, &settings.board, Some(&task_cache)) {
+                Ok(folders) => {
+                    let by_column = target.query.get("by_column").is_some_and(|v| v == "true");
+                    let report = workload(&cfg, &folders, &read_users_file(&root_path), OffsetDateTime::now_utc(), settings.timezone, by_column);
+                    respond_json(StatusCode(200), &serde_json::json!(report).to_string())
+                }
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            },
+            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+        },
+        (Method::Get, "/api/stats") => match refresh_config(&root_path) {
+            Ok(cfg) => match load_all_tasks(&root_path, &cfg, &settings.board, Some(&task_cache)) {
+                Ok(folders) => {
+                    let mut stats = board_stats(&cfg, &folders, OffsetDateTime::now_utc(), settings.timezone);
+                    stats.usage = board_usage(&root_path, &cfg, &settings.board);
+                    respond_json(StatusCode(200), &serde_json::json!(stats).to_string())
+                }
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            },
+            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+        },
+        (Method::Get, p) if p.starts_with("/api/shared/") => match refresh_config(&root_path) {
+            Ok(cfg) => handle_shared(&root_path, &cfg, &target.segments, &target.query, &settings, || {
+                current_theme(&root_path, &theme_cache, &events).0
+            }),
+            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+        },
+        (_, p) if (p == "/api/shares" || p.starts_with("/api/shares/")) && settings.admin_token.is_some() && !admin => {
+            respond_error(401, "admin_required", &[])
+        }
+        (Method::Get, "/api/shares") => {
+            let now = OffsetDateTime::now_utc();
+            let shares: Vec<Share> = read_shares(&root_path).into_iter().filter(|s| !share_expired(s, now)).collect();
+            respond_json(StatusCode(200), &serde_json::json!({ "shares": shares }).to_string())
+        }
+        (Method::Post, "/api/shares") => match parse_body::<NewShare>(&body, settings.strict_api) {
+            Ok((request, ignored)) => match create_share(&root_path, request, &actor, settings.timezone) {
+                Ok(share) => {
+                    let mut payload = serde_json::json!(share);
+                    payload["url"] = serde_json::json!(format!("{}/api/shared/{}/tasks", settings.board.base_path, share.token));
+                    respond_json(StatusCode(201), &with_ignored_fields(payload, &ignored).to_string())
+                }
+                Err((status, msg)) => respond_json(StatusCode(status), &serde_json::json!({"error": msg}).to_string()),
+            },
+            Err(error) => respond_json(StatusCode(400), &error.to_string()),
+        },
+        (Method::Delete, p) if p.starts_with("/api/shares/") => match target.segments.as_slice() {
+            [_, _, token] => match revoke_share(&root_path, token) {
+                Ok(true) => respond_json(StatusCode(204), ""),
+                Ok(false) => respond_json(StatusCode(404), &code_error("share_not_found", &[]).to_string()),
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            },
+            _ => respond_error(404, "not_found", &[]),
+        },
+        (_, p) if p.starts_with("/api/webhooks/") && settings.admin_token.is_some() && !admin => {
+            respond_error(401, "admin_required", &[])
+        }
+        (Method::Get, "/api/webhooks/deliveries") => {
+            match target.query.get("status").map(|status| DeliveryStatus::parse(status)).transpose() {
+                Ok(status) => {
+                    let mut deliveries: Vec<Delivery> =
+                        read_deliveries(&root_path).into_iter().filter(|d| status.is_none_or(|s| d.status == s)).collect();
+                    deliveries.reverse();
+                    respond_json(StatusCode(200), &serde_json::json!({ "deliveries": deliveries }).to_string())
+                }
+                Err(msg) => respond_json(StatusCode(400), &serde_json::json!({"error": msg}).to_string()),
+            }
+        }
+        (Method::Post, p) if p.starts_with("/api/webhooks/deliveries/") => match target.segments.as_slice() {
+            [_, _, _, id, action] if action == "redeliver" => match &settings.webhook {
+                None => respond_json(
+                    StatusCode(409),
+                    &serde_json::json!({"error": "No webhook configured; start the server with --webhook"}).to_string(),
+                ),
+                Some(url) => match read_deliveries(&root_path).into_iter().find(|d| d.id == *id) {
+                    Some(delivery) => {
+                        let retry_for = Duration::from_secs(settings.webhook_retry_hours * 3600);
+                        let delivery = attempt_delivery(&root_path, url, delivery, retry_for);
+                        respond_json(StatusCode(200), &serde_json::json!(delivery).to_string())
+                    }
+                    None => respond_error(404, "delivery_not_found", &[]),
+                },
+            },
+            _ => respond_error(404, "not_found", &[]),
+        },
+        (Method::Get, "/api/subscriptions") => {
+            let user = target.query.get("user").map(|u| u.trim()).unwrap_or("");
+            let subscriptions: Vec<Subscription> = read_subscriptions(&root_path)
+                .into_iter()
+                .filter(|s| user.is_empty() || s.user.eq_ignore_ascii_case(user))
+                .collect();
+            respond_json(StatusCode(200), &serde_json::json!({ "subscriptions": subscriptions }).to_string())
+        }
+        (Method::Post, "/api/subscriptions") => match (parse_body::<NewSubscription>(&body, settings.strict_api), refresh_config(&root_path)) {
+            (Err(error), _) => respond_json(StatusCode(400), &error.to_string()),
+            (_, Err(msg)) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+            (Ok((request, ignored)), Ok(cfg)) => match create_subscription(&root_path, &cfg, request) {
+                Ok(subscription) => {
+                    let payload = serde_json::json!(subscription);
+                    respond_json(StatusCode(201), &with_ignored_fields(payload, &ignored).to_string())
+                }
+                Err(msg) => respond_json(StatusCode(400), &serde_json::json!({"error": msg}).to_string()),
+            },
+        },
+        (Method::Delete, p) if p.starts_with("/api/subscriptions/") => match target.segments.as_slice() {
+            [_, _, id] => match delete_subscription(&root_path, id) {
+                Ok(true) => respond_json(StatusCode(204), ""),
+                Ok(false) => respond_error(404, "subscription_not_found", &[]),
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            },
+            _ => respond_error(404, "not_found", &[]),
+        },
+        (Method::Get, "/api/views") => {
+            let owner = target.query.get("owner").map(|o| o.trim()).unwrap_or("");
+            let views: Vec<SavedView> =
+                read_views(&root_path).into_iter().filter(|v| owner.is_empty() || v.owner.eq_ignore_ascii_case(owner)).collect();
+            respond_json(StatusCode(200), &serde_json::json!({ "views": views }).to_string())
+        }
+        (Method::Post, "/api/views") => match (parse_body::<NewView>(&body, settings.strict_api), refresh_config(&root_path)) {
+            (Err(error), _) => respond_json(StatusCode(400), &error.to_string()),
+            (_, Err(msg)) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+            (Ok((request, ignored)), Ok(cfg)) => match create_view(&root_path, &cfg, request, settings.timezone) {
+                Ok(view) => respond_json(StatusCode(201), &with_ignored_fields(serde_json::json!(view), &ignored).to_string()),
+                Err((status, msg)) => respond_json(StatusCode(status), &serde_json::json!({"error": msg}).to_string()),
+            },
+        },
+        (Method::Get, p) if p.starts_with("/api/views/") && p.ends_with("/tasks") => match (target.segments.as_slice(), refresh_config(&root_path)) {
+            (_, Err(msg)) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+            ([_, _, name, _], Ok(cfg)) => match read_views(&root_path).into_iter().find(|v| v.name == *name) {
+                None => respond_error(404, "view_not_found", &[]),
+                Some(view) => match view_listing(&cfg, &view, &target.query, settings.timezone) {
+                    Err(msg) => respond_json(StatusCode(400), &serde_json::json!({"error": msg}).to_string()),
+                    Ok((board, filter, page)) => match load_listing_tasks(&root_path, &board, &settings.board, &task_cache, &filter) {
+                        Ok((folders, skipped)) => {
+                            let mut payload =
+                                tasks_payload(&root_path, &board, &settings.board, folders, &filter, &page, settings.max_tasks_per_response, Some(&task_cache));
+                            payload["view"] = serde_json::json!(view);
+                            respond_json(StatusCode(200), &with_warnings(payload, skipped).to_string())
+                        }
+                        Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                    },
+                },
+            },
+            _ => respond_error(404, "not_found", &[]),
+        },
+        (Method::Delete, p) if p.starts_with("/api/views/") => match target.segments.as_slice() {
+            [_, _, name] => match delete_view(&root_path, name) {
+                Ok(true) => respond_json(StatusCode(204), ""),
+                Ok(false) => respond_error(404, "view_not_found", &[]),
+                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+            },
+            _ => respond_error(404, "not_found", &[]),
+        },
+        (Method::Get, p) if p.starts_with("/api/users/") && p.ends_with("/feed.atom") => match (target.segments.as_slice(), refresh_config(&root_path)) {
+            ([_, _, user, _], Ok(cfg)) if !user.trim().is_empty() => Response::from_string(user_feed(&root_path, &cfg, user.trim()))
+                .with_header(Header::from_bytes("Content-Type", "application/atom+xml; charset=utf-8").unwrap()),
+            (_, Err(msg)) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+            _ => respond_error(404, "not_found", &[]),
+        },
+        (Method::Post, p) if p.starts_with("/api/users/") && p.ends_with("/handover") => match (target.segments.as_slice(), refresh_config(&root_path)) {
+            ([_, _, user, _], Ok(cfg)) if !user.trim().is_empty() => {
+                handle_handover(&root_path, &cfg, user.trim(), &body, &settings, &hooks, &actor, &journal, &update_state, &events)
+            }
+            (_, Err(msg)) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+            _ => respond_error(404, "not_found", &[]),
+        },
+        (Method::Get, p) if p.starts_with("/api/users/") && p.ends_with("/activity") => {
+            let user = match target.segments.as_slice() {
+                [_, _, user, _] => user.as_str(),
+                _ => "",
+            };
+            let days = match target.query.get("days") {
+                None => Ok(14),
+                Some(raw) => raw
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|d| (1..=ACTIVITY_MAX_DAYS).contains(d))
+                    .ok_or_else(|| format!("Invalid days: {} (1 to {})", raw, ACTIVITY_MAX_DAYS)),
+            };
+            match (days, refresh_config(&root_path)) {
+                (Err(msg), _) => respond_json(StatusCode(400), &serde_json::json!({"error": msg}).to_string()),
+                (_, Err(msg)) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+                (Ok(_), Ok(_)) if user.trim().is_empty() => {
+                    respond_error(404, "not_found", &[])
+                }
+                (Ok(days), Ok(cfg)) => match load_all_tasks(&root_path, &cfg, &settings.board, Some(&task_cache)) {
+                    Ok(folders) => respond_json(
+                        StatusCode(200),
+                        &serde_json::json!(user_activity(&root_path, &cfg, &folders, user.trim(), days)).to_string(),
+                    ),
+                    Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                },
+            }
+        }
+        (Method::Patch, p) if p.starts_with("/api/columns/") => match (target.segments.as_slice(), refresh_config(&root_path)) {
+            ([_, _, column_id], Ok(cfg)) => match (cfg.columns.iter().position(|c| &c.id == column_id), parse_body::<ColumnPatch>(&body, settings.strict_api)) {
+                (None, _) => respond_json(StatusCode(404), &serde_json::json!({"error": "column not found"}).to_string()),
+                (Some(_), Err(error)) => respond_json(StatusCode(400), &error.to_string()),
+                (Some(index), Ok((patch, ignored))) => {
+                    let mut new_config = cfg;
+                    if let Some(locked) = patch.locked {
+                        new_config.columns[index].locked = locked;
+                    }
+                    match apply_board_config(&root_path, &mut new_config, &actor) {
+                        Ok(_) => {
+                            notify_update(&update_state);
+                            let payload = serde_json::json!({ "column": new_config.columns[index] });
+                            respond_json(StatusCode(200), &with_ignored_fields(payload, &ignored).to_string())
+                        }
+                        Err(msg) => respond_json(StatusCode(500), &serde_json::json!({ "error": msg }).to_string()),
+                    }
+                }
+            },
+            (_, Err(msg)) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+            _ => respond_error(404, "not_found", &[]),
+        },
+        (Method::Get, p) if p.starts_with("/api/columns/") => {
+            let rest = &p["/api/columns/".len()..];
+            match rest.split_once('/') {
+                Some((column_id, "tasks")) => match refresh_config(&root_path) {
+                    Ok(cfg) => match (
+                        cfg.columns.iter().find(|c| c.id == column_id),
+                        parse_listing_query(&target.query, settings.timezone),
+                    ) {
+                        (None, _) => respond_json(StatusCode(404), &serde_json::json!({"error": "column not found"}).to_string()),
+                        (Some(_), Err(msg)) => respond_json(StatusCode(400), &serde_json::json!({"error": msg}).to_string()),
+                        (Some(column), Ok((filter, page))) => {
+                            let mut skipped = Vec::new();
+                            match scan_column_reporting(&root_path, &column.id, cfg.status_mode, &settings.board, Some(&task_cache), &mut skipped) {
+                                Ok(mut tasks) => {
+                                    for task in &mut tasks {
+                                        type_fields(&cfg.fields, task);
+                                    }
+                                    set_locks(&mut tasks, &locks, OffsetDateTime::now_utc());
+                                    let payload =
+                                        column_tasks_payload(&root_path, column, &cfg.fields, tasks, &filter, &page, settings.max_tasks_per_response);
+                                    respond_json(StatusCode(200), &with_warnings(payload, skipped).to_string())
+                                }
+                                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                            }
+                        }
+                    },
+                    Err(msg) => respond_json(StatusCode(500), &serde_json::json!({"error": msg}).to_string()),
+                },
+                _ => respond_error(404, "not_found", &[]),
+            }
+        }
+        (Method::Get, "/api/tasks") => match refresh_config(&root_path) {
+            Ok(cfg) => match parse_listing_query(&target.query, settings.timezone) {
+                Err(msg) => respond_json(
+                    StatusCode(400),
+                    &serde_json::json!({"error": msg}).to_string(),
+                ),
+                Ok((filter, page)) => match load_listing_tasks(&root_path, &cfg, &settings.board, &task_cache, &filter) {
+                    Ok((mut folders, skipped)) => {
+                        set_locks(folders.values_mut().flatten(), &locks, OffsetDateTime::now_utc());
+                        let payload =
+                            tasks_payload(&root_path, &cfg, &settings.board, folders, &filter, &page, settings.max_tasks_per_response, Some(&task_cache));
+                        respond_json(StatusCode(200), &with_warnings(payload, skipped).to_string())
+                    }
+                    Err(err) => respond_json(
+                        StatusCode(500),
+                        &serde_json::json!({"error": err.to_string()}).to_string(),
+                    ),
+                },
+            },
+            Err(msg) => respond_json(
+                StatusCode(500),
+                &serde_json::json!({"error": msg}).to_string(),
+            ),
+        },
+        (Method::Post, "/api/tasks") => {
+            match refresh_config(&root_path) {
+                Ok(cfg) => {
+                    match parse_body::<NewTask>(&body, settings.strict_api) {
+                        Ok((new_task, ignored)) => {
+                            let similar = match cfg.unique_titles {
+                                UniqueTitles::Off => Vec::new(),
+                                _ => load_all_tasks(&root_path, &cfg, &settings.board, Some(&task_cache))
+                                    .map(|folders| similar_tasks(&folders, &new_task.title))
+                                    .unwrap_or_default(),
+                            };
+                            let force = target.query.get("force").is_some_and(|v| v == "true");
+                            let conflicts: Vec<&str> = similar.iter().filter(|s| s.exact).map(|s| s.id.as_str()).collect();
+                            if cfg.unique_titles == UniqueTitles::Enforce && !force && !conflicts.is_empty() {
+                                respond_json(
+                                    StatusCode(409),
+                                    &serde_json::json!({
+                                        "error": "A task with this title already exists (add ?force=true to create it anyway)",
+                                        "conflicts": conflicts,
+                                        "similar_tasks": similar,
+                                    })
+                                    .to_string(),
+                                )
+                            } else {
+                                match create_task(&root_path, &cfg, &settings.board, new_task, admin, rules_forced(&target.query, &settings, admin), settings.history_limit) {
+                                    Ok((task, applied, replaced)) => {
+                                        record_task_activity(&root_path, &hooks, &actor, "task_created", &task.id, created_activity(&task, &applied, replaced.as_ref()));
+                                        record_new_mentions(&root_path, &hooks, &actor, &task, &[]);
+                                        // An overwritten task may have lived in another column.
+                                        let replaced_file = replaced.as_ref().map(|r| (r.folder.clone(), r.id.clone()));
+                                        journal_record(&journal, "create", replaced, Some(file_state(&task.folder, &task.id, render_task(&task))));
+                                        notify_update(&update_state);
+                                        let mut written = vec![(task.folder.as_str(), task.id.as_str())];
+                                        written.extend(replaced_file.as_ref().map(|(folder, id)| (folder.as_str(), id.as_str())));
+                                        record_manifest_change(&root_path, &cfg, &settings.board, &task_cache, &written);
+                                        let mut payload = task_with_defaults(&task, &applied);
+                                        if !similar.is_empty() {
+                                            payload["similar_tasks"] = serde_json::json!(similar);
+                                        }
+                                        let payload = with_warnings(payload, similar.iter().map(similar_title_warning));
+                                        respond_json(StatusCode(201), &with_ignored_fields(payload, &ignored).to_string())
+                                    }
+                                    Err((507, msg)) => board_full(&msg),
+                                    Err((409, id)) => id_conflict(&id),
+                                    Err((422, rule)) => rule_broken(&rule),
+                                    Err((status, msg)) => respond_json(StatusCode(status), &serde_json::json!({ "error": msg }).to_string()),
+                                }
+                            }
+                        }
+                        Err(error) => respond_json(StatusCode(400), &error.to_string()),
+                    }
+                }
+                Err(msg) => respond_json(
+                    StatusCode(500),
+                    &serde_json::json!({ "error": msg }).to_string(),
+                ),
+            }
+        }
+        (Method::Post, "/api/import/csv") => match refresh_config(&root_path) {
+            Ok(cfg) => match CsvImportOptions::from_query(&target.query).map_err(|msg| (400, msg)).and_then(|options| {
+                let force_rules = rules_forced(&target.query, &settings, admin);
+                let options = CsvImportOptions { admin, force_rules, history_limit: settings.history_limit, ..options };
+                import_csv(&root_path, &cfg, &settings.board, &body, &options)
+            }) {
+                Ok(report) => {
+                    for ((task, applied), replaced) in report.tasks.iter().zip(&report.applied).zip(&report.replaced) {
+                        record_task_activity(&root_path, &hooks, &actor, "task_created", &task.id, created_activity(task, applied, replaced.as_ref()));
+                        record_new_mentions(&root_path, &hooks, &actor, task, &[]);
+                        journal_record(&journal, "create", replaced.clone(), Some(file_state(&task.folder, &task.id, render_task(task))));
+                    }
+                    if report.created > 0 {
+                        notify_update(&update_state);
+                    }
+                    let status = if report.failed == 0 { 201 } else { 207 };
+                    respond_json(StatusCode(status), &serde_json::json!(report).to_string())
+                }
+                Err((507, msg)) => board_full(&msg),
+                Err((status, msg)) => respond_json(StatusCode(status), &serde_json::json!({ "error": msg }).to_string()),
+            },
+            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({ "error": msg }).to_string()),
+        },
+        (Method::Get, "/api/tasks/ready") => match refresh_config(&root_path) {
+            Ok(cfg) => match target.query.get("column").filter(|id| cfg.columns.iter().any(|c| &c.id == *id)) {
+                None => respond_json(StatusCode(400), &serde_json::json!({ "error": "column must name a column of the board" }).to_string()),
+                Some(column) => match load_all_tasks(&root_path, &cfg, &settings.board, Some(&task_cache)) {
+                    Ok(folders) => {
+                        let archived: HashSet<String> = scan_column(&root_path, "_archive", cfg.status_mode, &settings.board, None)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|task| task.id)
+                            .collect();
+                        let (listing, warnings) = ready_listing(&cfg, column, &folders, &archived);
+                        respond_json(StatusCode(200), &with_warnings(serde_json::json!(listing), warnings).to_string())
+                    }
+                    Err(err) => respond_json(StatusCode(500), &serde_json::json!({ "error": err.to_string() }).to_string()),
+                },
+            },
+            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({ "error": msg }).to_string()),
+        },
+        (Method::Post, "/api/tasks/batch") => match refresh_config(&root_path) {
+            Ok(cfg) => match target.query.get("collision").map(|v| Collision::parse(v)).transpose() {
+                Ok(collision) => {
+                    let force_rules = rules_forced(&target.query, &settings, admin);
+                    handle_batch_create(&root_path, &cfg, &settings.board, &body, &journal, &update_state, &hooks, &actor, admin, force_rules, collision, settings.history_limit, settings.strict_api)
+                }
+                Err(msg) => respond_json(StatusCode(400), &serde_json::json!({ "error": msg }).to_string()),
+            },
+            Err(msg) => respond_json(StatusCode(500), &serde_json::json!({ "error": msg }).to_string()),
+        },
+        (Method::Get, "/api/undo") => {
+            let entries: Vec<JournalEntry> = journal.entries.lock().unwrap().iter().rev().cloned().collect();
+            respond_json(StatusCode(200), &serde_json::json!({ "entries": entries }).to_string())
+        }
+        (Method::Post, "/api/undo") => handle_undo(&root_path, &journal, None, &update_state, &settings.board),
+        (Method::Post, _) if matches!(target.segments.as_slice(), [api, boards, _, tasks, _, transfer]
+            if api == "api" && boards == "boards" && tasks == "tasks" && transfer == "transfer") =>
+        {
+            let id = normalize_id(&target.segments[4]);
+            if !is_valid_id(&id) {
+                respond_json(StatusCode(400), &serde_json::json!({ "error": format!("invalid id: {:?}", id) }).to_string())
+            } else {
+                match refresh_config(&root_path) {
+                    Ok(cfg) => handle_transfer(&root_path, &cfg, &target.segments[2], &id, &body, &settings, &hooks, &actor, admin, &update_state),
+                    Err(msg) => respond_json(StatusCode(500), &serde_json::json!({ "error": msg }).to_string()),
+                }
+            }
+        }
+        _ => {
+            if let Some(rest) = path_only.strip_prefix("/api/undo/") {
+                match rest.parse::<u64>() {
+                    Ok(entry_id) if method == Method::Post => {
+                        handle_undo(&root_path, &journal, Some(entry_id), &update_state, &settings.board)
+                    }
+                    Ok(_) => respond_json(StatusCode(405), &serde_json::json!({"error": "method not allowed"}).to_string()),
+                    Err(_) => respond_json(StatusCode(400), &serde_json::json!({"error": "invalid journal id"}).to_string()),
+                }
+            } else if let Some(id) = path_only.strip_prefix("/api/tasks/") {
+                let parts: Vec<&str> = id.split('/').collect();
+                let requested_id = normalize_id(parts.first().copied().unwrap_or(""));
+                let canonical_id = refresh_config(&root_path)
+                    .ok()
+                    .and_then(|cfg| resolve_alias(&root_path, &requested_id, &cfg, &settings.board));
+                let normalized_id = canonical_id.clone().unwrap_or(requested_id);
+                let id_part = normalized_id.as_str();
+                let edits = (parts.len() == 1 && (method == Method::Put || method == Method::Delete)) || (parts.len() == 2 && parts[1] == "move" && method == Method::Post);
+                let force = target.query.get("override").is_some_and(|v| v == "true");
+                let held = if edits && !force { foreign_lock(&locks, id_part, &actor, OffsetDateTime::now_utc()) } else { None };
+                let mut route = parts.clone();
+                route[0] = id_part;
+                let checked = refresh_config(&root_path)
+                    .map_err(|msg| respond_json(StatusCode(500), &serde_json::json!({ "error": msg }).to_string()))
+                    .and_then(|cfg| check_task_route(&root_path, &cfg, &settings.board, &route, &method));
+                let response = if let Err(response) = checked {
+                    response
+                } else if parts.len() >= 2 && parts[1] == "attachments" {
+                    match refresh_config(&root_path) {
+                        Ok(cfg) => {
+                            let response = handle_attachments(
+                                &root_path,
+                                &cfg,
+                                &settings.board,
+                                id_part,
+                                &parts[2..],
+                                &method,
+                                &target.query,
+                                &raw_body,
+                                settings.history_limit,
+                            );
+                            if method != Method::Get && response.status_code().0 < 300 {
+                                notify_update(&update_state);
+                            }
+                            response
+                        }
+                        Err(msg) => respond_json(
+                            StatusCode(500),
+                            &serde_json::json!({ "error": msg }).to_string(),
+                        ),
+                    }
+                } else if parts.len() == 2 && parts[1] == "export" && method == Method::Get {
+                    match refresh_config(&root_path) {
+                        Ok(cfg) => handle_export(&root_path, &cfg, &settings.board, id_part, &target.query),
+                        Err(msg) => respond_json(
+                            StatusCode(500),
+                            &serde_json::json!({ "error": msg }).to_string(),
+                        ),
+                    }
+                } else if parts.len() == 2 && parts[1] == "diff" && method == Method::Get {
+                    match refresh_config(&root_path) {
+                        Ok(cfg) => handle_diff(&root_path, &cfg, &settings.board, id_part, &target.query),
+                        Err(msg) => respond_json(
+                            StatusCode(500),
+                            &serde_json::json!({ "error": msg }).to_string(),
+                        ),
+                    }
+                } else if parts.len() >= 2 && parts[1] == "versions" {
+                    match refresh_config(&root_path) {
+                        Ok(cfg) => {
+                            let response = handle_versions(&root_path, &cfg, &settings.board, id_part, &parts[2..], &method, settings.history_limit, &journal);
+                            if method == Method::Post && response.status_code() == StatusCode(200) {
+                                notify_update(&update_state);
+                            }
+                            response
+                        }
+                        Err(msg) => respond_json(
+                            StatusCode(500),
+                            &serde_json::json!({ "error": msg }).to_string(),
+                        ),
+                    }
+                } else if let Some(lock) = held {
+                    locked_response(&lock)
+                } else if parts.len() == 2 && parts[1] == "lock" {
+                    match refresh_config(&root_path) {
+                        Ok(cfg) => {
+                            let user = header_value(&request, "X-Kanban-User").map(|_| actor.as_str());
+                            handle_lock(&root_path, &cfg, &settings.board, &locks, &events, id_part, &method, user, force)
+                        }
+                        Err(msg) => respond_json(
+                            StatusCode(500),
+                            &serde_json::json!({ "error": msg }).to_string(),
+                        ),
+                    }
+                } else if parts.len() == 2 && parts[1] == "move" && method == Method::Post {
+                    match refresh_config(&root_path) {
+                        Ok(cfg) => {
+                            match parse_body::<MoveTask>(&body, settings.strict_api) {
+                                Ok((move_req, ignored)) => {
+                                    let status_check = match (cfg.status_mode, move_req.status.as_deref()) {
+                                        (StatusMode::Free, Some(status)) => validate_status(&cfg, status),
+                                        _ => Ok(()),
+                                    };
+                                    if !cfg.columns.iter().any(|c| c.id == move_req.folder) {
+                                        respond_json(StatusCode(400), &serde_json::json!({"error": "invalid folder"}).to_string())
+                                    } else if let Err(msg) = status_check {
+                                        respond_json(StatusCode(400), &serde_json::json!({ "error": msg }).to_string())
+                                    } else if let Some((path, current_folder)) =
+                                        find_task_path(&root_path, id_part, &cfg, &settings.board)
+                                    {
+                                        match parse_task(&path, &current_folder, cfg.status_mode, &settings.board.base_path) {
+                                            Ok(mut task) => {
+                                                type_fields(&cfg.fields, &mut task);
+                                                // A task already in the frozen column may still be "moved" to it.
+                                                let frozen = cfg
+                                                    .columns
+                                                    .iter()
+                                                    .find(|c| c.id == move_req.folder && is_frozen(c))
+                                                    .filter(|_| current_folder != move_req.folder);
+                                                let locked = locked_column(&cfg, &[&current_folder, &move_req.folder], admin)
+                                                    .filter(|_| current_folder != move_req.folder);
+                                                let rule = broken_rule(&cfg, &move_req.folder, &task, rules_forced(&target.query, &settings, admin))
+                                                    .filter(|_| current_folder != move_req.folder);
+                                                match checked_task_path(&root_path, &move_req.folder, id_part) {
+                                                    Err(msg) => respond_json(StatusCode(400), &serde_json::json!({ "error": msg }).to_string()),
+                                                    Ok(_) if locked.is_some() => locked.map(column_locked).unwrap(),
+                                                    Ok(_) if frozen.is_some() => {
+                                                        respond_error(400, "column_frozen", &[("column", &move_req.folder)])
+                                                    }
+                                                    Ok(_) if rule.is_some() => rule.map(|rule| rule_broken(&rule.spec())).unwrap(),
+                                                    // Moving within its column only changes the task's position.
+                                                    Ok(target_path) if target_path.exists() && target_path != path => {
+                                                        respond_json(StatusCode(409), &serde_json::json!({"error": "target file exists"}).to_string())
+                                                    }
+                                                    Ok(target_path) => {
+                                                        let applied = if current_folder == move_req.folder {
+                                                            AppliedDefaults::default()
+                                                        } else {
+                                                            match cfg.columns.iter().find(|c| c.id == move_req.folder) {
+                                                                Some(column) => {
+                                                                    let mut applied = apply_column_defaults(column, &mut task);
+                                                                    apply_auto_assign(&root_path, column, &mut task, &mut applied);
+                                                                    applied
+                                                                }
+                                                                None => AppliedDefaults::default(),
+                                                            }
+                                                        };
+                                                        task.folder = move_req.folder.clone();
+                                                        match cfg.status_mode {
+                                                            StatusMode::Column => task.status = move_req.folder.clone(),
+                                                            StatusMode::Free => {
+                                                                if let Some(status) = move_req.status {
+                                                                    task.status = status;
+                                                                }
+                                                            }
+                                                        }
+                                                        touch_task(&mut task);
+                                                        if current_folder != move_req.folder {
+                                                            task.column_since = Some(task.updated_at.clone());
+                                                        }
+                                                        update_completion(&cfg, &mut task);
+                                                        let before = fs::read_to_string(&path).unwrap_or_default();
+                                                        record_history(&root_path, id_part, &path, settings.history_limit);
+                                                        let before = file_state(&current_folder, id_part, before);
+                                                        let after = file_state(&move_req.folder, id_part, render_task(&task));
+                                                        let applied_move = journaled(&journal, "move", Some(before), Some(after), || {
+                                                            fs::rename(&path, &target_path)?;
+                                                            write_task(&target_path, &mut task)
+                                                        });
+                                                        if let Err(err) = applied_move {
+                                                            respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string())
+                                                        } else {
+                                                            let (from_position, to_position) =
+                                                                move_in_order(&root_path, id_part, &current_folder, &move_req.folder, move_req.position)
+                                                                    .unwrap_or_else(|err| {
+                                                                        eprintln!("Failed to save the manual order for {}: {}", id_part, err);
+                                                                        (None, None)
+                                                                    });
+                                                            // The log only notes positions of ordered columns.
+                                                            let with_positions = |mut extra: serde_json::Value| {
+                                                                for (key, position) in [("from_position", from_position), ("to_position", to_position)] {
+                                                                    if let Some(position) = position {
+                                                                        extra[key] = serde_json::json!(position);
+                                                                    }
+                                                                }
+                                                                extra
+                                                            };
+                                                            if current_folder == move_req.folder {
+                                                                record_task_activity(&root_path, &hooks, &actor, "task_edited", id_part, with_positions(serde_json::json!({})));
+                                                            } else {
+                                                                let moved = with_positions(serde_json::json!({ "from": current_folder, "to": move_req.folder }));
+                                                                record_task_activity(&root_path, &hooks, &actor, "task_moved", id_part, entry_activity(moved, &applied));
+                                                            }
+                                                            publish_event(
+                                                                &events,
+                                                                "task_moved",
+                                                                serde_json::json!({
+                                                                    "id": id_part,
+                                                                    "from": current_folder,
+                                                                    "to": move_req.folder,
+                                                                    "from_position": from_position,
+                                                                    "to_position": to_position,
+                                                                }),
+                                                            );
+                                                            notify_update(&update_state);
+                                                            record_manifest_change(&root_path, &cfg, &settings.board, &task_cache, &[(&current_folder, id_part), (&move_req.folder, id_part)]);
+                                                            let payload = with_ignored_fields(task_with_defaults(&task, &applied), &ignored);
+                                                            respond_json(StatusCode(200), &payload.to_string())
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                            Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                                        }
+                                    } else {
+                                        task_not_found()
+                                    }
+                                }
+                                Err(error) => respond_json(StatusCode(400), &error.to_string()),
+                            }
+                        }
+                        Err(msg) => respond_json(
+                            StatusCode(500),
+                            &serde_json::json!({ "error": msg }).to_string(),
+                        ),
+                    }
+                } else if parts.len() == 1 && method == Method::Put {
+                    match refresh_config(&root_path) {
+                        Ok(cfg) => {
+                            match parse_body::<UpdateTask>(&body, settings.strict_api) {
+                                Ok((update, ignored)) => if let Some(Err(msg)) = update.color.as_deref().map(normalize_color) {
+                                    respond_json(StatusCode(400), &serde_json::json!({ "error": msg }).to_string())
+                                } else if let Some(Err(msg)) = update.due.as_deref().map(normalize_due) {
+                                    respond_json(StatusCode(400), &serde_json::json!({ "error": msg }).to_string())
+                                } else if let Some(Err(msg)) = update.estimate.map(normalize_estimate) {
+                                    respond_json(StatusCode(400), &serde_json::json!({ "error": msg }).to_string())
+                                } else if let Some(Err(msg)) = update
+                                    .status
+                                    .as_deref()
+                                    .filter(|_| cfg.status_mode == StatusMode::Free)
+                                    .map(|status| validate_status(&cfg, status))
+                                {
+                                    respond_json(StatusCode(400), &serde_json::json!({ "error": msg }).to_string())
+                                } else if let Some(Err(msg)) = update.extra.clone().map(|extra| normalize_extra(&cfg.fields, extra)) {
+                                    respond_json(StatusCode(400), &serde_json::json!({ "error": msg }).to_string())
+                                } else {
+                                    if let Some((path, folder)) =
+                                        find_task_path(&root_path, id_part, &cfg, &settings.board)
+                                    {
+                                        match parse_task(&path, &folder, cfg.status_mode, &settings.board.base_path) {
+                                            Ok(mut task) => {
+                                                type_fields(&cfg.fields, &mut task);
+                                                let before = fs::read_to_string(&path).unwrap_or_default();
+                                                let mentioned = task.mentions.clone();
+                                                record_history(&root_path, id_part, &path, settings.history_limit);
+                                                let mut rename_error: Option<Response<std::io::Cursor<Vec<u8>>>> = None;
+                                                let mut renamed_from: Option<String> = None;
+                                                // Held until the write so that no new task takes the id meanwhile.
+                                                let _guard = update.title.is_some().then(|| CREATE_LOCK.lock().unwrap());
+                                                if let Some(title) = update.title {
+                                                    let new_slug = task_slug(&cfg, &settings.board.lang, &title);
+                                                    if new_slug != task.id {
+                                                        let final_slug = unique_slug(&new_slug, &existing_ids(&root_path, &cfg));
+                                                        match checked_task_path(&root_path, &folder, &final_slug) {
+                                                            Err(msg) => {
+                                                                rename_error = Some(respond_json(StatusCode(400), &serde_json::json!({ "error": msg }).to_string()));
+                                                            }
+                                                            Ok(_) => {
+                                                                for attachment in &mut task.attachments {
+                                                                    attachment.url = attachment_url(&settings.board.base_path, &final_slug, &attachment.name);
+                                                                }
+                                                                renamed_from = Some(std::mem::replace(&mut task.id, final_slug));
+                                                            }
+                                                        }
+                                                    }
+                                                    task.title = title;
+                                                }
+                                                if let Some(resp) = rename_error {
+                                                    resp
+                                                } else {
+                                                    if let Some(desc) = update.description {
+                                                        task.mentions = extract_mentions(&desc);
+                                                        task.description = desc;
+                                                    }
+                                                    if let Some(creator) = update.creator {
+                                                        task.creator = creator;
+                                                    }
+                                                    if let Some(assigned_to) = update.assigned_to {
+                                                        task.assigned_to = assigned_to;
+                                                    }
+                                                    if let Some(tags) = update.tags {
+                                                        task.tags = tags;
+                                                    }
+                                                    if let Some(color) = update.color.as_deref() {
+                                                        task.color = normalize_color(color).unwrap_or_default();
+                                                    }
+                                                    if let Some(icon) = update.icon.as_deref() {
+                                                        task.icon = normalize_icon(icon);
+                                                    }
+                                                    if let Some(due) = update.due.as_deref() {
+                                                        task.due = normalize_due(due).unwrap_or_default();
+                                                    }
+                                                    if let Some(estimate) = update.estimate {
+                                                        task.estimate = normalize_estimate(estimate).unwrap_or_default();
+                                                    }
+                                                    if let Some(Ok(extra)) = update.extra.map(|extra| normalize_extra(&cfg.fields, extra)) {
+                                                        merge_extra(&mut task, extra);
+                                                    }
+                                                    if cfg.status_mode == StatusMode::Free {
+                                                        if let Some(status) = update.status {
+                                                            task.status = status;
+                                                        }
+                                                    }
+                                                    touch_task(&mut task);
+                                                    let final_path = task_path(&root_path, &folder, &task.id);
+                                                    let before = file_state(&folder, id_part, before);
+                                                    let after = file_state(&folder, &task.id, render_task(&task));
+                                                    let applied = journaled(&journal, "update", Some(before), Some(after), || {
+                                                        let Some(old_id) = &renamed_from else {
+                                                            return write_task(&final_path, &mut task);
+                                                        };
+                                                        fs::rename(&path, &final_path)?;
+                                                        write_task(&final_path, &mut task)?;
+                                                        finish_rename(&root_path, old_id, &task.id)
+                                                    });
+                                                    match applied {
+                                                        Ok(_) => {
+                                                            record_task_activity(&root_path, &hooks, &actor, "task_edited", &task.id, serde_json::json!({}));
+                                                            record_new_mentions(&root_path, &hooks, &actor, &task, &mentioned);
+                                                            notify_update(&update_state);
+                                                            record_manifest_change(&root_path, &cfg, &settings.board, &task_cache, &[(&folder, id_part), (&folder, &task.id)]);
+                                                            respond_json(StatusCode(200), &with_ignored_fields(serde_json::json!(task), &ignored).to_string())
+                                                        }
+                                                        Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                                                    }
+                                                }
+                                            }
+                                            Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                                        }
+                                    } else {
+                                        task_not_found()
+                                    }
+                                }
+                                Err(error) => respond_json(StatusCode(400), &error.to_string()),
+                            }
+                        }
+                        Err(msg) => respond_json(
+                            StatusCode(500),
+                            &serde_json::json!({ "error": msg }).to_string(),
+                        ),
+                    }
+                } else if parts.len() == 1 && method == Method::Get {
+                    let raw = target.query.get("raw").is_some_and(|v| v == "true");
+                    match refresh_config(&root_path) {
+                        Ok(cfg) => match find_task_path(&root_path, id_part, &cfg, &settings.board) {
+                            // The file exactly as stored, front matter included.
+                            Some((path, _)) if raw => match fs::read_to_string(&path) {
+                                Ok(content) => Response::from_string(content)
+                                    .with_header(Header::from_bytes("Content-Type", "text/plain; charset=utf-8").unwrap()),
+                                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                            },
+                            Some((path, folder)) => match parse_task(&path, &folder, cfg.status_mode, &settings.board.base_path) {
+                                Ok(mut task) => {
+                                    type_fields(&cfg.fields, &mut task);
+                                    let now = OffsetDateTime::now_utc();
+                                    set_locks(std::iter::once(&mut task), &locks, now);
+                                    set_days_in_column(std::slice::from_mut(&mut task), now);
+                                    respond_json(StatusCode(200), &serde_json::json!(task).to_string())
+                                }
+                                Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                            },
+                            None => task_not_found(),
+                        },
+                        Err(msg) => respond_json(
+                            StatusCode(500),
+                            &serde_json::json!({ "error": msg }).to_string(),
+                        ),
+                    }
+                } else if parts.len() == 1 && method == Method::Delete {
+                    match refresh_config(&root_path) {
+                        Ok(cfg) => {
+                            if let Some((path, folder)) =
+                                find_task_path(&root_path, id_part, &cfg, &settings.board)
+                            {
+                                let before = fs::read_to_string(&path).unwrap_or_default();
+                                record_history(&root_path, id_part, &path, settings.history_limit);
+                                match journaled(&journal, "delete", Some(file_state(&folder, id_part, before)), None, || fs::remove_file(&path)) {
+                                    Ok(_) => {
+                                        record_task_activity(&root_path, &hooks, &actor, "task_deleted", id_part, serde_json::json!({ "column": folder }));
+                                        remove_attachments(&root_path, id_part);
+                                        locks.entries.lock().unwrap().remove(id_part);
+                                        let _ = prune_aliases(&root_path, &cfg, &settings.board);
+                                        notify_update(&update_state);
+                                        record_manifest_change(&root_path, &cfg, &settings.board, &task_cache, &[(&folder, id_part)]);
+                                        respond_json(StatusCode(204), "")
+                                    }
+                                    Err(err) => respond_json(StatusCode(500), &serde_json::json!({"error": err.to_string()}).to_string()),
+                                }
+                            } else {
+                                task_not_found()
+                            }
+                        }
+                        Err(msg) => respond_json(
+                            StatusCode(500),
+                            &serde_json::json!({ "error": msg }).to_string(),
+                        ),
+                    }
+                } else {
+                    respond_error(404, "not_found", &[])
+                };
+                match canonical_id {
+                    // Requested through an alias left by an earlier rename.
+                    Some(id) => response.with_header(Header::from_bytes("Kanban-Canonical-Id", id.as_bytes()).unwrap()),
+                    None => response,
+                }
+            } else {
+                respond_error(404, "not_found", &[])
+            }
+        }
+    };
+
+        if let Some(record) = record {
+            record.finish(response.status_code().0);
+        }
+        if response.status_code().0 >= 500 {
+            let client = peer.ip.map(|ip| ip.to_string()).unwrap_or_else(|| "-".to_string());
+            eprintln!("{} {} {} -> {}", client, method, path_only, response.status_code().0);
+        }
+        let _ = request.respond(shape_response(response, shape, &url, &settings.board.base_path));
+        return;
+    }
+
+    if let Some(response) = respond_asset(path_only) {
+        let _ = request.respond(response);
+    } else if let Some(response) = respond_board_asset(&root_path, path_only, header_value(&request, "If-None-Match").as_deref()) {
+        let _ = request.respond(response);
+    } else {
+        let response = respond_text(StatusCode(404), "Not Found");
+        let _ = request.respond(response);
+    }
+}
+
+/// Boards served over real HTTP for the end-to-end tests in `tests`.
+#[cfg(test)]
+mod test_support {
+    use super::*;
+    use std::net::TcpStream;
+
+    /// A default board in a `TempDir`, served on an ephemeral port. Dropping
+    /// it stops the server and removes the board.
+    pub struct TestServer {
+        pub root: PathBuf,
+        handle: Option<ServerHandle>,
+        _dir: TempDir,
+    }
+
+    /// An empty directory under the system temp dir, named after the test
+    /// and the process. Dropping it removes the directory.
+    pub struct TempDir(PathBuf);
+
+    impl TempDir {
+        pub fn new(name: &str) -> TempDir {
+            let path = std::env::temp_dir().join(format!("kanban-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl std::ops::Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl AsRef<Path> for TempDir {
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    pub struct TestResponse {
+        pub status: u16,
+        pub headers: Vec<(String, String)>,
+        pub body: String,
+    }
+
+    impl TestResponse {
+        pub fn json(&self) -> serde_json::Value {
+            serde_json::from_str(&self.body).unwrap_or_else(|err| panic!("{}: {}", err, self.body))
+        }
+
+        pub fn header(&self, name: &str) -> Option<&str> {
+            self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+        }
+    }
+
+    impl TestServer {
+        pub fn start(name: &str) -> TestServer {
+            TestServer::start_with(name, &[])
+        }
+
+        /// Like `start`, with extra command-line flags.
+        pub fn start_with(name: &str, args: &[&str]) -> TestServer {
+            let dir = TempDir::new(&format!("http-{}", name));
+            let root = dir.to_path_buf();
+            let args = ["--yes", "--create-root"].iter().chain(args).map(|arg| arg.to_string());
+            let settings = parse_arg_list(args).unwrap();
+            let defaults = BoardConfig {
+                columns: template_columns(DEFAULT_TEMPLATE, DEFAULT_LANG).unwrap_or_default(),
+                ..Default::default()
+            };
+            setup_board(&settings, &root, &defaults).unwrap();
+            let handle = start_server("127.0.0.1:0", root.clone(), settings).unwrap();
+            TestServer { root, handle: Some(handle), _dir: dir }
+        }
+
+        /// Sends one request with `Connection: close` and reads the whole
+        /// response.
+        pub fn request(&self, method: &str, path: &str, body: Option<serde_json::Value>) -> TestResponse {
+            self.request_with_headers(method, path, body, &[])
+        }
+
+        pub fn request_with_headers(
+            &self,
+            method: &str,
+            path: &str,
+            body: Option<serde_json::Value>,
+            headers: &[(&str, &str)],
+        ) -> TestResponse {
+            self.request_raw(method, path, &body.map(|b| b.to_string()).unwrap_or_default(), headers)
+        }
+
+        /// Sends `body` as is, for endpoints that take no JSON.
+        pub fn request_raw(&self, method: &str, path: &str, body: &str, headers: &[(&str, &str)]) -> TestResponse {
+            let addr = self.handle.as_ref().unwrap().addr.unwrap();
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+            let head = format!(
+                "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}\r\n",
+                method,
+                path,
+                addr,
+                body.len(),
+                headers.iter().map(|(key, value)| format!("{}: {}\r\n", key, value)).collect::<String>()
+            );
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(body.as_bytes()).unwrap();
+            let mut raw = String::new();
+            stream.read_to_string(&mut raw).unwrap();
+            let (head, body) = raw.split_once("\r\n\r\n").unwrap_or((&raw, ""));
+            let mut lines = head.lines();
+            let status = lines
+                .next()
+                .and_then(|line| line.split_whitespace().nth(1))
+                .and_then(|code| code.parse().ok())
+                .unwrap_or_else(|| panic!("bad status line: {}", head));
+            let headers = lines
+                .filter_map(|line| line.split_once(':'))
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .collect();
+            TestResponse { status, headers, body: body.to_string() }
+        }
+
+        pub fn get(&self, path: &str) -> TestResponse {
+            self.request("GET", path, None)
+        }
+
+        /// Opens `GET /api/events`; reading starts after the response head
+        /// and gives up on a line after 100ms.
+        pub fn events(&self) -> io::BufReader<TcpStream> {
+            let addr = self.handle.as_ref().unwrap().addr.unwrap();
+            let mut stream = TcpStream::connect(addr).unwrap();
+            stream.set_read_timeout(Some(Duration::from_millis(100))).unwrap();
+            write!(stream, "GET /api/events HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", addr).unwrap();
+            let mut reader = io::BufReader::new(stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 && line != "\r\n" {
+                line.clear();
+            }
+            reader
+        }
+    }
+
+    impl Drop for TestServer {
+        fn drop(&mut self) {
+            if let Some(handle) = self.handle.take() {
+                handle.shutdown();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(id: &str) -> BoardColumn {
+        BoardColumn {
+            id: id.to_string(),
+            title: id.to_uppercase(),
+            ..Default::default()
+        }
+    }
+
+    fn task(id: &str, folder: &str, updated_at: &str) -> Task {
+        Task {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: String::new(),
+            creator: String::new(),
+            assigned_to: String::new(),
+            created_at: Timestamp::read(updated_at),
+            updated_at: Timestamp::read(updated_at),
+            status: folder.to_string(),
+            tags: Vec::new(),
+            folder: folder.to_string(),
+            color: None,
+            icon: None,
+            mentions: Vec::new(),
+            due: None,
+            estimate: None,
+            completed_at: None,
+            column_since: None,
+            days_in_column: None,
+            locked_by: None,
+            locked_until: None,
+            attachments: Vec::new(),
+            extra: serde_json::Map::new(),
+            file: None,
+        }
+    }
+
+    #[test]
+    fn stats_ignore_done_columns_for_overdue_and_oldest() {
+        let mut shipped = column("shipped");
+        shipped.done = true;
+        let config = BoardConfig {
+            columns: vec![column("todo"), column("done"), shipped],
+            ..Default::default()
+        };
+        let mut late = task("late", "todo", "2026-01-10T00:00:00Z");
+        late.due = Some("2026-01-31".to_string());
+        let mut finished = task("finished", "shipped", "2025-12-01T00:00:00Z");
+        finished.due = Some("2026-01-01".to_string());
+        finished.completed_at = Some(Timestamp::read("2026-01-30T00:00:00Z"));
+        let folders = HashMap::from([
+            ("todo".to_string(), vec![late, task("fresh", "todo", "2026-01-30T00:00:00Z")]),
+            ("done".to_string(), vec![task("old", "done", "2025-06-01T00:00:00Z")]),
+            ("shipped".to_string(), vec![finished]),
+        ]);
+        let now = parse_timestamp("2026-02-01T12:00:00Z").unwrap();
+        let stats = board_stats(&config, &folders, now, UtcOffset::UTC);
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.overdue, 1);
+        assert_eq!(stats.completed.last_7_days, 1);
+        assert_eq!(stats.created.last_7_days, 1);
+        assert_eq!(stats.oldest_untouched.map(|t| t.id), Some("old".to_string()));
+    }
+
+    #[test]
+    fn content_disposition_encodes_non_ascii_names() {
+        assert_eq!(
+            content_disposition("plan.md"),
+            "attachment; filename=\"plan.md\"; filename*=UTF-8''plan.md"
+        );
+        assert_eq!(
+            content_disposition("café \"x\".md"),
+            "attachment; filename=\"caf_ _x_.md\"; filename*=UTF-8''caf%C3%A9%20%22x%22.md"
+        );
+    }
+
+    #[test]
+    fn attachment_names_cannot_escape_their_folder() {
+        assert_eq!(sanitize_filename("../../etc/passwd").as_deref(), Some("passwd"));
+        assert_eq!(sanitize_filename("..\\..\\boot.ini").as_deref(), Some("boot.ini"));
+        assert_eq!(sanitize_filename("my shot (1).png").as_deref(), Some("my_shot_1.png"));
+        assert_eq!(sanitize_filename(".."), None);
+        assert_eq!(sanitize_filename("dir/"), None);
+    }
+
+    #[test]
+    fn every_template_is_a_valid_board_in_every_language() {
+        for (template, _) in BOARD_TEMPLATES {
+            for (lang, _) in COLUMN_TITLES {
+                let columns = template_columns(template, lang).unwrap();
+                assert_eq!(validate_columns(&columns, DEFAULT_MAX_COLUMNS), Ok(()), "{} / {}", template, lang);
+                let parsed = parse_config_contents(&render_config(&BoardConfig { columns: columns.clone(), ..Default::default() }));
+                assert_eq!(parsed.len(), columns.len(), "{} / {}", template, lang);
+                for (column, reparsed) in columns.iter().zip(&parsed) {
+                    assert_eq!(column.id, reparsed.id);
+                    assert_eq!(column.title, reparsed.title);
+                    assert_eq!(column.wip_limit, reparsed.wip_limit);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_language_falls_back_to_english_titles() {
+        let columns = template_columns("default", "xx").unwrap();
+        assert_eq!(columns[2].title, "In Progress");
+        assert!(template_columns("kanban2000", "en").is_none());
+    }
+
+    fn keys(value: &serde_json::Value) -> Vec<&str> {
+        let mut keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn capabilities_schema_is_stable() {
+        let settings = Settings {
+            timezone: UtcOffset::from_hms(-5, -30, 0).unwrap(),
+            ..Default::default()
+        };
+        let peer = Peer {
+            ip: None,
+            proto: "https".to_string(),
+            host: Some("tools.example.com".to_string()),
+        };
+        let config = BoardConfig { columns: vec![column("todo")], ..Default::default() };
+        let doc = serde_json::json!(capabilities(&settings, Path::new("/srv/team-board"), &config, &peer));
+        assert_eq!(
+            keys(&doc),
+            vec!["api_version", "api_versions", "board", "features", "limits", "show_board_editor", "show_task_editor", "slugs", "timezone", "ui"]
+        );
+        assert_eq!(keys(&doc["slugs"]), vec!["lang", "stopwords", "stopwords_enabled"]);
+        assert_eq!(keys(&doc["board"]), vec!["name", "root", "status_mode", "statuses", "url"]);
+        assert_eq!(doc["board"]["status_mode"], "column");
+        assert_eq!(
+            keys(&doc["features"]),
+            vec![
+                "archive", "attachments", "auth_required", "history", "multi_board", "read_only", "readme", "search",
+                "sse", "stable_ids", "stats", "strict_api", "undo"
+            ]
+        );
+        assert_eq!(
+            keys(&doc["limits"]),
+            vec![
+                "attachment_max_bytes", "history_versions", "max_body_bytes", "max_columns", "max_tasks_per_response",
+                "rate_limit_per_minute", "search_max_results", "undo_entries"
+            ]
+        );
+        assert_eq!(keys(&doc["ui"]), vec!["show_board_editor", "show_task_editor"]);
+        assert!(doc["features"].as_object().unwrap().values().all(|v| v.is_boolean()));
+        assert_eq!(doc["show_task_editor"], true);
+        assert_eq!(doc["timezone"], "-05:30");
+        assert_eq!(doc["board"]["name"], "team-board");
+    }
+
+    #[test]
+    fn base_path_is_stripped_before_routing() {
+        assert_eq!(route_url("/api/tasks?tag=x", ""), Ok("/api/tasks?tag=x".to_string()));
+        assert_eq!(route_url("/kanban/api/tasks", "/kanban"), Ok("/api/tasks".to_string()));
+        assert_eq!(route_url("/kanban/", "/kanban"), Ok("/".to_string()));
+        assert_eq!(route_url("/kanban", "/kanban"), Err(Some("/kanban/".to_string())));
+        assert_eq!(route_url("/kanbanx/app.js", "/kanban"), Err(None));
+        assert_eq!(route_url("/api/tasks", "/kanban"), Err(None));
+        assert_eq!(normalize_base_path("kanban/").as_deref(), Ok("/kanban"));
+        assert_eq!(normalize_base_path("/").as_deref(), Ok(""));
+    }
+
+    #[test]
+    fn forwarded_for_is_only_honored_from_trusted_proxies() {
+        let trusted = vec![parse_cidr("10.0.0.0/8").unwrap(), parse_cidr("::1").unwrap()];
+        let proxy: IpAddr = "10.1.2.3".parse().unwrap();
+        let outsider: IpAddr = "203.0.113.9".parse().unwrap();
+        assert_eq!(forwarded_client(outsider, Some("1.2.3.4"), &trusted), outsider);
+        assert_eq!(
+            forwarded_client(proxy, Some("1.2.3.4, 198.51.100.7, 10.0.0.5"), &trusted),
+            "198.51.100.7".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(forwarded_client(proxy, None, &trusted), proxy);
+        assert!(parse_cidr("10.0.0.0/33").is_none());
+        assert!(parse_cidr("0.0.0.0/0").unwrap().contains(outsider));
+    }
+
+    #[test]
+    fn config_round_trip_preserves_column_order() {
+        let config = BoardConfig {
+            columns: vec![column("zeta"), column("alpha"), column("mid")],
+            ..Default::default()
+        };
+        let parsed = parse_config_contents(&render_config(&config));
+        let ids: Vec<&str> = parsed.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["zeta", "alpha", "mid"]);
+    }
+
+    #[test]
+    fn reorder_keeps_fields_and_follows_order() {
+        let mut doing = column("doing");
+        doing.wip_limit = Some(3);
+        doing.default_tags = vec!["active".to_string()];
+        let columns = vec![column("todo"), doing, column("done")];
+        let order: Vec<String> = ["done", "doing", "todo"].iter().map(|s| s.to_string()).collect();
+        let reordered = reorder_columns(&columns, &order).unwrap();
+        let ids: Vec<&str> = reordered.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["done", "doing", "todo"]);
+        assert_eq!(reordered[1].wip_limit, Some(3));
+        assert_eq!(reordered[1].default_tags, vec!["active"]);
+    }
+
+    #[test]
+    fn reorder_rejects_incomplete_or_unknown_ids() {
+        let columns = vec![column("todo"), column("done")];
+        let ids = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(reorder_columns(&columns, &ids(&["todo"])).is_err());
+        assert!(reorder_columns(&columns, &ids(&["todo", "todo"])).is_err());
+        assert!(reorder_columns(&columns, &ids(&["todo", "later"])).is_err());
+    }
+
+    #[test]
+    fn mentions_at_line_start_and_after_punctuation() {
+        let text = "@alice please review\n(cc @Bob), thanks @carol.\n\"@dave\" and @erin!";
+        assert_eq!(
+            extract_mentions(text),
+            vec!["alice", "bob", "carol", "dave", "erin"]
+        );
+    }
+
+    #[test]
+    fn mentions_skip_emails_and_code() {
+        let text = "mail alice@example.com\n`@bob` inline\n```\n@carol in fence\n```\nafter @dave";
+        assert_eq!(extract_mentions(text), vec!["dave"]);
+    }
+
+    #[test]
+    fn mentions_are_deduplicated_and_keep_inner_punctuation() {
+        let text = "@first.last and @first.last. and @First.Last-";
+        assert_eq!(extract_mentions(text), vec!["first.last"]);
+    }
+
+    #[test]
+    fn unified_diff_marks_changed_lines_with_context() {
+        let old = "one\ntwo\nthree\nfour";
+        let new = "one\n2\nthree\nfour\nfive";
+        let diff = unified_diff(old, new, "a", "b");
+        assert_eq!(
+            diff,
+            "--- a\n+++ b\n@@ -1,4 +1,5 @@\n one\n-two\n+2\n three\n four\n+five\n"
+        );
+        assert!(unified_diff(old, old, "a", "b").is_empty());
+    }
+
+    #[test]
+    fn diff_lines_handles_empty_sides() {
+        assert_eq!(diff_lines(&[], &["x"]), vec![DiffOp::Insert(0)]);
+        assert_eq!(diff_lines(&["x"], &[]), vec![DiffOp::Delete(0)]);
+        assert!(diff_lines(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn lone_at_sign_is_not_a_mention() {
+        assert!(extract_mentions("meet @ noon, @- @.").is_empty());
+    }
+
+    #[test]
+    fn unique_slug_takes_the_smallest_free_suffix() {
+        let mut taken = HashSet::new();
+        assert_eq!(unique_slug("fix-login", &taken), "fix-login");
+        taken.extend(["fix-login".to_string(), "fix-login-3".to_string()]);
+        assert_eq!(unique_slug("fix-login", &taken), "fix-login-2");
+    }
+
+    #[test]
+    fn same_title_creates_list_each_folder_once() {
+        let root = test_support::TempDir::new("slug-test");
+        fs::create_dir_all(root.join("backlog")).unwrap();
+        let cfg = BoardConfig { columns: vec![column("backlog")], ..Default::default() };
+        ID_LISTINGS.with(|n| n.set(0));
+        for i in 1..=500 {
+            let new_task: NewTask = serde_json::from_value(serde_json::json!({ "title": "Standup notes" })).unwrap();
+            let (task, _, _) = create_task(&root, &cfg, &BoardOptions::default(), new_task, false, false, DEFAULT_HISTORY_LIMIT).unwrap();
+            let expected = if i == 1 { "standup-notes".to_string() } else { format!("standup-notes-{}", i) };
+            assert_eq!(task.id, expected);
+        }
+        assert_eq!(ID_LISTINGS.with(|n| n.get()), 500);
+        assert_eq!(fs::read_dir(root.join("backlog")).unwrap().count(), 500);
+    }
+
+    #[test]
+    fn pasted_file_names_normalize_to_addressable_ids() {
+        assert_eq!(normalize_id("Fix-Login.md"), "fix-login");
+        assert_eq!(normalize_id("v1.2-release"), "v1.2-release");
+        assert!(is_valid_id("v1.2-release"));
+        for bad in ["", "..", "a..b", ".hidden", "a/b", "a\\b", "A"] {
+            assert!(!is_valid_id(bad), "{:?} should be rejected", bad);
+        }
+        for title in ["v1.2 release", "Done. Next...", "...", "e.g. this", "x_y.z"] {
+            assert!(is_valid_id(&slugify(title)), "slug of {:?}", title);
+        }
+        assert_eq!(slugify("v1.2 release"), "v1.2-release");
+        assert_eq!(slugify("Done. Next..."), "done-next");
+    }
+
+    #[test]
+    fn slug_stopwords_are_opt_in_and_keep_the_last_word() {
+        let mut config = BoardConfig { columns: vec![column("todo")], ..Default::default() };
+        assert_eq!(task_slug(&config, DEFAULT_LANG, "Fix the bug in the login form"), "fix-the-bug-in-the-login-form");
+        apply_board_settings(&mut config, "slug_stopwords = true\nslug_stopwords_extra = Bug, please\n").unwrap();
+        assert_eq!(slugify_without("Fix the bug in the login form", &slug_stopwords(&config, "en")), "fix-login-form");
+        assert_eq!(slugify_without("Die Suche in der Liste", &slug_stopwords(&config, "de")), "suche-liste");
+        assert_eq!(slug_stopwords(&config, "fr"), slug_stopwords(&config, "en"));
+        assert_eq!(slugify_without("The end of the", &slug_stopwords(&config, "en")), "end");
+        assert_eq!(slugify_without("Of the", &slug_stopwords(&config, "en")), "the");
+        assert!(render_config(&config).starts_with("slug_stopwords = true\nslug_stopwords_extra = bug, please\n"));
+        assert!(apply_board_settings(&mut config, "slug_stopwords = yes\n").is_err());
+    }
+
+    #[test]
+    fn range_headers_resolve_to_inclusive_slices() {
+        assert_eq!(parse_range(None, 100), ByteRange::Full);
+        assert_eq!(parse_range(Some("bytes=0-9"), 100), ByteRange::Slice(0, 9));
+        assert_eq!(parse_range(Some("bytes=90-"), 100), ByteRange::Slice(90, 99));
+        assert_eq!(parse_range(Some("bytes=-10"), 100), ByteRange::Slice(90, 99));
+        assert_eq!(parse_range(Some("bytes=50-500"), 100), ByteRange::Slice(50, 99));
+        assert_eq!(parse_range(Some("bytes=-500"), 100), ByteRange::Slice(0, 99));
+        assert_eq!(parse_range(Some("bytes=100-"), 100), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=-0"), 100), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range(Some("bytes=0-1,5-6"), 100), ByteRange::Full);
+        assert_eq!(parse_range(Some("bytes=9-2"), 100), ByteRange::Full);
+        assert_eq!(parse_range(Some("items=0-1"), 100), ByteRange::Full);
+    }
+
+    #[test]
+    fn wip_warnings_follow_limit_changes() {
+        let limited = |limit: Option<u32>| BoardConfig {
+            columns: vec![BoardColumn { wip_limit: limit, ..column("doing") }],
+            ..Default::default()
+        };
+        let counts = HashMap::from([("doing".to_string(), 4)]);
+
+        let shrink = wip_limit_warnings(&limited(Some(5)), &limited(Some(3)), &counts);
+        assert_eq!(shrink.len(), 1);
+        assert_eq!((shrink[0].column.as_str(), shrink[0].wip_limit, shrink[0].count), ("doing", 3, 4));
+        assert_eq!(shrink[0].code, "wip_limit_exceeded");
+
+        assert!(wip_limit_warnings(&limited(Some(3)), &limited(Some(6)), &counts).is_empty());
+        assert!(wip_limit_warnings(&limited(Some(3)), &limited(None), &counts).is_empty());
+        assert!(wip_limit_warnings(&limited(Some(3)), &limited(Some(0)), &counts).is_empty());
+        // Unchanged limits are not reported again.
+        assert!(wip_limit_warnings(&limited(Some(3)), &limited(Some(3)), &counts).is_empty());
+    }
+
+    #[test]
+    fn board_settings_round_trip_and_leave_columns_alone() {
+        let mut config = BoardConfig {
+            columns: vec![column("todo"), column("done")],
+            status_mode: StatusMode::Free,
+            statuses: vec!["blocked".to_string(), "waiting-review".to_string()],
+            default_column: Some("done".to_string()),
+            ..Default::default()
+        };
+        let contents = render_config(&config);
+        assert!(contents.starts_with("status_mode = free\nstatuses = blocked, waiting-review\ndefault_column = done\n"));
+        assert_eq!(parse_config_contents(&contents).len(), 2);
+
+        let mut parsed = BoardConfig { columns: parse_config_contents(&contents), ..Default::default() };
+        apply_board_settings(&mut parsed, &contents).unwrap();
+        assert_eq!(parsed.status_mode, StatusMode::Free);
+        assert_eq!(parsed.statuses, config.statuses);
+        assert_eq!(default_column(&parsed), "done");
+        assert!(validate_board(&parsed, DEFAULT_MAX_COLUMNS).is_ok());
+        parsed.columns.pop();
+        assert!(validate_board(&parsed, DEFAULT_MAX_COLUMNS).is_err());
+        assert_eq!(default_column(&parsed), "todo");
+
+        assert!(validate_status(&config, "blocked").is_ok());
+        assert!(validate_status(&config, "").is_ok());
+        assert!(validate_status(&config, "stuck").is_err());
+        config.statuses.clear();
+        assert!(validate_status(&config, "stuck").is_ok());
+        assert!(apply_board_settings(&mut config, "status_mode = loose").is_err());
+
+        // Rendered as is, such a status would add a column line.
+        for status in ["x\nevil: Evil", "a,b", " "] {
+            let injected = BoardConfig { statuses: vec!["ok".to_string(), status.to_string()], ..parsed.clone() };
+            assert!(validate_board(&injected, DEFAULT_MAX_COLUMNS).unwrap_err().contains("Invalid status"), "{:?}", status);
+        }
+
+        let sprint = FieldDef { name: "sprint".to_string(), kind: FieldKind::Text, values: Vec::new() };
+        let private = |keys: &[&str]| BoardConfig {
+            columns: vec![column("todo")],
+            fields: vec![sprint.clone()],
+            private_fields: keys.iter().map(|k| k.to_string()).collect(),
+            ..Default::default()
+        };
+        assert!(validate_board(&private(&["creator", "sprint"]), DEFAULT_MAX_COLUMNS).is_ok());
+        for key in ["a\nevil3: Evil", "creator, sprint", "points"] {
+            assert!(validate_board(&private(&[key]), DEFAULT_MAX_COLUMNS).unwrap_err().contains("Invalid private field"), "{:?}", key);
+        }
+        let stopwords = |words: &[&str]| BoardConfig {
+            columns: vec![column("todo")],
+            slug_stopwords_extra: words.iter().map(|w| w.to_string()).collect(),
+            ..Default::default()
+        };
+        assert!(validate_board(&stopwords(&["und", "oder"]), DEFAULT_MAX_COLUMNS).is_ok());
+        for word in ["a\nevil: Evil", "a,b", "two words"] {
+            assert!(validate_board(&stopwords(&[word]), DEFAULT_MAX_COLUMNS).unwrap_err().contains("Invalid stop-word"), "{:?}", word);
+        }
+    }
+
+    #[test]
+    fn updated_at_never_goes_backwards() {
+        let mut future = task("a", "todo", "2999-01-01T00:00:00Z");
+        touch_task(&mut future);
+        assert_eq!(&*future.updated_at, "2999-01-01T00:00:01Z");
+
+        let mut past = task("b", "todo", "2020-01-01T00:00:00Z");
+        touch_task(&mut past);
+        assert!(past.updated_at.at().unwrap() > parse_timestamp("2020-01-01T00:00:00Z").unwrap());
+
+        let mut unset = task("c", "todo", "");
+        touch_task(&mut unset);
+        assert!(unset.updated_at.at().is_some());
+    }
+
+    #[test]
+    fn header_timestamps_are_normalized_to_utc() {
+        for raw in ["2024-06-01T16:00:00+02:00", "2024-06-01 14:00", "2024-06-01 14:00:00", "1717250400"] {
+            assert_eq!(&*Timestamp::read(raw), "2024-06-01T14:00:00Z", "{}", raw);
+        }
+        assert_eq!(&*Timestamp::read(" 2024-06-01 "), "2024-06-01T00:00:00Z");
+        for raw in ["yesterday", "2024-06-01 14", "2024-06-01 25:00", "2024-13-01", "-5"] {
+            let kept = Timestamp::read(raw);
+            assert_eq!((&*kept, kept.at()), (raw, None));
+            let err = Timestamp::parse("created_at", raw).unwrap_err();
+            assert!(err.contains("created_at") && err.contains("YYYY-MM-DD HH:MM[:SS]"), "{}", err);
+        }
+        let stamp: Timestamp = serde_json::from_value(serde_json::json!("2024-06-01 14:00")).unwrap();
+        assert_eq!(serde_json::to_value(&stamp).unwrap(), serde_json::json!("2024-06-01T14:00:00Z"));
+    }
+
+    #[test]
+    fn pages_are_sorted_and_capped_across_columns() {
+        let make = |n: usize| -> Vec<Task> {
+            (0..n).map(|i| task(&format!("t{:02}", i), "todo", &format!("2026-01-{:02}T00:00:00Z", i + 1))).collect()
+        };
+        let todo = column("todo");
+        let mut tasks = make(10);
+        let mut meta = column_meta(&todo, 10, 10);
+        paginate(&mut tasks, &todo, &Page { limit: Some(3), offset: 2, ..Default::default() }, &[], &[], &mut None, &mut meta);
+        let ids: Vec<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["t07", "t06", "t05"]);
+        assert_eq!((meta.offset, meta.returned, meta.truncated), (2, 3, false));
+
+        let mut budget = Some(4);
+        let mut first = make(3);
+        let mut meta = column_meta(&todo, 3, 3);
+        paginate(&mut first, &todo, &Page::default(), &[], &[], &mut budget, &mut meta);
+        assert!(!meta.truncated);
+        let mut second = make(3);
+        let mut meta = column_meta(&todo, 3, 3);
+        paginate(&mut second, &todo, &Page::default(), &[], &[], &mut budget, &mut meta);
+        assert_eq!((second.len(), meta.returned, meta.truncated, budget), (1, 1, true, Some(0)));
+
+        let mut past_end = make(2);
+        let mut meta = column_meta(&todo, 2, 2);
+        paginate(&mut past_end, &todo, &Page { limit: None, offset: 5, ..Default::default() }, &[], &[], &mut None, &mut meta);
+        assert!(past_end.is_empty() && !meta.truncated);
+    }
+
+    #[test]
+    fn task_file_uses_forward_slashes_and_fields_keep_id() {
+        let dir = test_support::TempDir::new("file");
+        fs::create_dir_all(dir.join("backlog")).unwrap();
+        let mut t = task("a.b", "backlog", "2026-01-01T00:00:00Z");
+        write_task(&dir.join("backlog").join("a.b.md"), &mut t).unwrap();
+        let file = t.file.clone().unwrap();
+        assert_eq!(file.path, "backlog/a.b.md");
+        assert!(file.size > 0 && file.modified.is_some());
+
+        let fields = vec!["title".to_string()];
+        let projected = project_tasks(std::slice::from_ref(&t), Some(&fields));
+        assert_eq!(keys(&projected[0]), vec!["id", "title"]);
+    }
+
+    #[test]
+    fn reserved_device_names_get_a_suffix() {
+        let table = [
+            ("con", "con-task"),
+            ("AUX", "AUX-task"),
+            ("nul.log", "nul-task.log"),
+            ("com1", "com1-task"),
+            ("lpt9.2", "lpt9-task.2"),
+            ("com10", "com10"),
+            ("console", "console"),
+            ("my-aux", "my-aux"),
+        ];
+        for (stem, expected) in table {
+            assert_eq!(avoid_reserved_name(stem), expected, "{:?}", stem);
+        }
+        assert_eq!(portable_stem("notes. ."), "notes");
+        assert!(!is_valid_id("notes."));
+    }
+
+    #[test]
+    fn overlong_task_paths_are_refused() {
+        let root = Path::new("/boards/team");
+        assert!(checked_task_path(root, "backlog", "short").is_ok());
+        let err = checked_task_path(root, "backlog", &"x".repeat(MAX_PATH_LEN)).unwrap_err();
+        assert!(err.contains("260 character limit"), "{}", err);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_columns_are_followed_and_linked_files_skipped() {
+        use std::os::unix::fs::symlink;
+        let base = test_support::TempDir::new("links");
+        let root = base.join("board");
+        let elsewhere = base.join("disk");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        fs::create_dir_all(&elsewhere).unwrap();
+        let mut t = task("far", "done", "2026-01-01T00:00:00Z");
+        write_task(&elsewhere.join("far.md"), &mut t).unwrap();
+        fs::write(base.join("vault.md"), "title: Vault\n\nnotes").unwrap();
+
+        // Link to a directory: a column living on another disk.
+        symlink(&elsewhere, root.join("done")).unwrap();
+        let done = scan_column(&root, "done", StatusMode::Column, &BoardOptions::default(), None).unwrap();
+        assert_eq!(done.len(), 1);
+
+        // Link to a file: skipped unless following is enabled.
+        let linked = root.join("todo").join("vault.md");
+        symlink(base.join("vault.md"), &linked).unwrap();
+        assert!(link_skip_reason(&linked, false).is_some());
+        assert_eq!(link_skip_reason(&linked, true), None);
+        assert!(scan_column(&root, "todo", StatusMode::Column, &BoardOptions::default(), None).unwrap().is_empty());
+        assert!(!resolves_within(&linked, &root));
+
+        // Dangling link: skipped either way and reported by the scan.
+        let dangling = root.join("todo").join("gone.md");
+        symlink(base.join("missing.md"), &dangling).unwrap();
+        assert_eq!(link_skip_reason(&dangling, true), Some("dangling symlink"));
+        let (count, skipped, _) = scan_column_files(&root, "todo", false, &BoardOptions::default());
+        assert_eq!(count, 0);
+        assert_eq!(skipped.len(), 2);
+
+        // Removing a linked folder drops the link, not the files behind it.
+        remove_folder(&root.join("done")).unwrap();
+        assert!(!root.join("done").exists());
+        assert!(elsewhere.join("far.md").is_file());
+    }
+
+    #[test]
+    fn case_clashes_are_reported_and_renamed() {
+        let names: Vec<String> = ["Readme-task.md", "readme-task.md", "Fix.md", "other.md"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        assert_eq!(case_clashes(&names), vec![vec!["Readme-task.md".to_string(), "readme-task.md".to_string()]]);
+        let renames = rename_proposals(&names);
+        assert_eq!(renames["Readme-task.md"], "readme-task-2.md");
+        assert_eq!(renames["Fix.md"], "fix.md");
+        assert!(!renames.contains_key("other.md"));
+
+        // Linux keeps both files; the flag only changes the wording.
+        let root = test_support::TempDir::new("case");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        fs::write(root.join("todo").join("Readme-task.md"), "title: A\n").unwrap();
+        fs::write(root.join("todo").join("readme-task.md"), "title: B\n").unwrap();
+        for insensitive in [false, true] {
+            let (_, warnings, _) = scan_column_files(&root, "todo", insensitive, &BoardOptions::default());
+            assert!(warnings.iter().any(|w| w.contains("todo/Readme-task.md and todo/readme-task.md")));
+            assert!(warnings.iter().any(|w| w.contains("rename it to readme-task-2.md")));
+            assert_eq!(warnings.iter().any(|w| w.contains("one file on this file system")), insensitive);
+        }
+        assert!(exact_file_exists(&root.join("todo").join("readme-task.md"), true));
+        assert!(!exact_file_exists(&root.join("todo").join("README-TASK.md"), true));
+    }
+
+    #[test]
+    fn board_writes_are_stamped_and_logged() {
+        let root = test_support::TempDir::new("audit");
+        let mut todo = column("todo");
+        todo.title = "Todo".to_string();
+        let mut config = BoardConfig { columns: vec![todo.clone(), column("done")], ..Default::default() };
+        write_config(&root, &mut config, "server").unwrap();
+
+        todo.title = "To do".to_string();
+        todo.wip_limit = Some(3);
+        let mut config = BoardConfig { columns: vec![todo, column("review")], ..Default::default() };
+        write_config(&root, &mut config, "alice").unwrap();
+
+        let saved = read_config(&root).unwrap();
+        assert_eq!(saved.modified_by.as_deref(), Some("alice"));
+        assert_eq!(saved.modified_at, config.modified_at);
+        assert_eq!(saved.columns.len(), 2);
+
+        let log = fs::read_to_string(root.join(ACTIVITY_FILE)).unwrap();
+        let last: serde_json::Value = serde_json::from_str(log.lines().last().unwrap()).unwrap();
+        assert_eq!((last["action"].as_str(), last["actor"].as_str()), (Some("board_changed"), Some("alice")));
+        let changes: Vec<&str> = last["columns"].as_array().unwrap().iter().map(|c| c["change"].as_str().unwrap()).collect();
+        assert_eq!(changes, vec!["removed", "renamed", "limit_changed", "added"]);
+    }
+
+    #[test]
+    fn max_tasks_caps_creates_and_reports_usage() {
+        let root = test_support::TempDir::new("cap");
+        fs::create_dir_all(root.join("backlog")).unwrap();
+        let cfg = BoardConfig { columns: vec![column("backlog")], max_tasks: Some(2), ..Default::default() };
+        let new_task = || serde_json::from_value::<NewTask>(serde_json::json!({ "title": "Sync" })).unwrap();
+        create_task(&root, &cfg, &BoardOptions::default(), new_task(), false, false, DEFAULT_HISTORY_LIMIT).unwrap();
+        create_task(&root, &cfg, &BoardOptions::default(), new_task(), false, false, DEFAULT_HISTORY_LIMIT).unwrap();
+        let (status, msg) = create_task(&root, &cfg, &BoardOptions::default(), new_task(), false, false, DEFAULT_HISTORY_LIMIT).unwrap_err();
+        assert_eq!(status, 507, "{}", msg);
+
+        let usage = board_usage(&root, &cfg, &BoardOptions::default());
+        assert_eq!((usage.tasks, usage.percent_of_cap), (2, Some(100.0)));
+        assert!(usage.bytes > 0);
+        assert!(check_capacity(&cfg, 1, 1).is_ok());
+        assert!(check_capacity(&BoardConfig::default(), 10_000, 1).is_ok());
+    }
+
+    #[test]
+    fn user_activity_folds_the_log_per_actor() {
+        let mut done = column("done");
+        done.done = true;
+        let cfg = BoardConfig { columns: vec![column("todo"), column("doing"), done], ..Default::default() };
+        let log = [
+            r#"{"at":"2026-03-01T10:00:00Z","actor":"Alice","action":"task_created","task":"a","column":"todo"}"#,
+            r#"{"at":"2026-03-02T10:00:00Z","actor":"alice","action":"task_moved","task":"a","from":"todo","to":"doing"}"#,
+            r#"{"at":"2026-03-03T10:00:00Z","actor":"alice","action":"task_moved","task":"a","from":"doing","to":"done"}"#,
+            r#"{"at":"2026-03-03T11:00:00Z","actor":"alice","action":"task_moved","task":"b","from":"todo","to":"doing"}"#,
+            r#"{"at":"2026-03-03T12:00:00Z","actor":"alice","action":"task_edited","task":"b"}"#,
+            r#"{"at":"2026-01-01T00:00:00Z","actor":"alice","action":"task_edited","task":"old"}"#,
+            r#"{"at":"2026-03-04T00:00:00Z","actor":"bob","action":"task_created","task":"c","column":"todo"}"#,
+            r#"{"at":"2026-03-04T00:00:00Z","action":"task_created","task":"d","column":"done"}"#,
+            "not json",
+        ]
+        .join("\n");
+        let since = parse_timestamp("2026-02-15T00:00:00Z").unwrap();
+        let alice = summarize_activity(io::Cursor::new(log.as_str()), &cfg, "alice", since);
+        assert_eq!((alice.created, alice.moved, alice.completed, alice.edited), (1, 3, 1, 1));
+        assert_eq!(alice.moves[0], MoveCount { from: "todo".to_string(), to: "doing".to_string(), count: 2 });
+
+        let anonymous = summarize_activity(io::Cursor::new(log.as_str()), &cfg, "anonymous", since);
+        assert_eq!((anonymous.created, anonymous.completed), (1, 1));
+        let nobody = summarize_activity(io::Cursor::new(log.as_str()), &cfg, "carol", since);
+        assert_eq!(nobody.created + nobody.moved + nobody.edited, 0);
+    }
+
+    #[test]
+    fn column_since_round_trips_and_ages() {
+        let mut t = task("a", "todo", "2026-03-01T00:00:00Z");
+        t.column_since = Some(Timestamp::read("2026-03-01T12:00:00Z"));
+        let rendered = render_task(&t);
+        assert!(rendered.contains("column_since: 2026-03-01T12:00:00Z\n"));
+
+        let mut tasks = vec![t, task("b", "todo", "2026-03-01T00:00:00Z")];
+        set_days_in_column(&mut tasks, parse_timestamp("2026-03-04T11:00:00Z").unwrap());
+        assert_eq!((tasks[0].days_in_column, tasks[1].days_in_column), (Some(2), None));
+        let json = serde_json::json!(tasks[0]);
+        assert_eq!(json["days_in_column"], 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hooks_get_the_event_on_stdin_and_env_and_report_failures() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = test_support::TempDir::new("hook");
+        let script = dir.join("hook.sh");
+        let out = dir.join("out.txt");
+        fs::write(
+            &script,
+            format!("#!/bin/sh\necho \"$KANBAN_EVENT $KANBAN_TASK_ID $KANBAN_FROM>$KANBAN_TO\" > {0}\ncat >> {0}\necho \"$1\" >&2\n[ -z \"$1\" ]\n", out.display()),
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let entry = serde_json::json!({ "action": "task_moved", "task": "a", "from": "todo", "to": "done" });
+        let event = hook_event(entry["action"].as_str().unwrap()).unwrap();
+        run_hook_command(&script, &hook_env(event, &entry), &entry.to_string(), Duration::from_secs(5)).unwrap();
+        let written = fs::read_to_string(&out).unwrap();
+        assert!(written.starts_with("move a todo>done\n{\"action\":\"task_moved\""), "{}", written);
+
+        let created = serde_json::json!({ "action": "task_created", "task": "b", "column": "todo" });
+        assert_eq!(hook_env("create", &created)[2..], [("KANBAN_FROM", String::new()), ("KANBAN_TO", "todo".to_string())]);
+        assert_eq!(hook_event("task_commented"), None);
+
+        let wrapper = dir.join("fail.sh");
+        fs::write(&wrapper, format!("#!/bin/sh\nexec {} 'led board offline'\n", script.display())).unwrap();
+        fs::set_permissions(&wrapper, fs::Permissions::from_mode(0o755)).unwrap();
+        let err = run_hook_command(&wrapper, &[], "{}", Duration::from_secs(5)).unwrap_err();
+        assert!(err.ends_with(": led board offline"), "{}", err);
+
+        let slow = dir.join("slow.sh");
+        fs::write(&slow, "#!/bin/sh\nsleep 5\n").unwrap();
+        fs::set_permissions(&slow, fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(run_hook_command(&slow, &[], "", Duration::from_millis(200)).unwrap_err(), "timed out after 200ms");
+    }
+
+    #[test]
+    fn watch_skips_excluded_files_and_expected_writes() {
+        let root = test_support::TempDir::new("watch");
+        for dir in ["todo", "_attachments/a", ".history/a", "todo/notes"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in ["todo/a.md", "todo/notes/n.txt", "_attachments/a/x.png", ".history/a/1.md", ".kanban-activity.jsonl", ".workspace-kanban.tmp"] {
+            fs::write(root.join(file), "x").unwrap();
+        }
+        let excludes: Vec<String> = WATCH_DEFAULT_EXCLUDES.iter().map(|name| name.to_string()).collect();
+        let before = stamp_files(&root, &excludes);
+        assert_eq!(before.keys().cloned().collect::<Vec<_>>(), vec![root.join("todo/a.md"), root.join("todo/notes/n.txt")]);
+
+        let now = Instant::now();
+        let mut expected = BTreeMap::new();
+        for (name, content) in [("a.md", "server"), ("b.md", "server"), ("c.md", "outside")] {
+            fs::write(root.join("todo").join(name), content).unwrap();
+        }
+        expected.insert(root.join("todo/a.md"), (Some(content_hash("server".as_bytes())), now));
+        expected.insert(root.join("todo/b.md"), (Some(content_hash("server, in full".as_bytes())), now));
+        expected.insert(root.join("todo/c.md"), (Some(content_hash("outside".as_bytes())), now - WATCH_EXPECT_TTL));
+        fs::remove_file(root.join("todo/notes/n.txt")).unwrap();
+        let changed = changed_files(&before, &stamp_files(&root, &excludes));
+        assert_eq!(changed.len(), 4);
+        // a.md holds what the server wrote, b.md is still being written, and
+        // the expectation for c.md has expired.
+        let (external, pending) = sort_changes(&mut expected, changed, now);
+        assert_eq!(external, vec![root.join("todo/c.md"), root.join("todo/notes/n.txt")]);
+        assert_eq!(pending, vec![root.join("todo/b.md")]);
+        assert!(!expected.contains_key(&root.join("todo/c.md")));
+    }
+
+    #[test]
+    fn watch_scan_reports_a_torn_outside_write_once() {
+        let file = PathBuf::from("/board/todo/a.md");
+        let other = PathBuf::from("/board/todo/b.md");
+        let at = |secs| Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+        let mut known = FileStamps::from([(file.clone(), (10, at(1))), (other.clone(), (3, at(1)))]);
+        let mut settling = Settling::new();
+        let mut expected = BTreeMap::new();
+        let mut scan = |current: &[(&PathBuf, u64, u64)]| {
+            let current = current.iter().map(|(path, len, secs)| ((*path).clone(), (*len, at(*secs)))).collect();
+            watch_scan(&mut known, current, &mut settling, &mut expected, Instant::now())
+        };
+        // Truncated, then filled, then left alone; b.md is removed.
+        assert!(scan(&[(&file, 0, 2), (&other, 3, 1)]).is_empty());
+        assert!(scan(&[(&file, 15, 3)]).is_empty());
+        assert_eq!(scan(&[(&file, 15, 3)]), vec![file.clone(), other.clone()]);
+        assert!(scan(&[(&file, 15, 3)]).is_empty());
+        // A write that reverts before it settles is never reported.
+        assert!(scan(&[(&file, 0, 4)]).is_empty());
+        assert!(scan(&[(&file, 15, 3)]).is_empty());
+        assert!(scan(&[(&file, 15, 3)]).is_empty());
+    }
+
+    #[test]
+    fn webhook_urls_batches_and_backoff() {
+        let url = WebhookUrl::parse("http://hooks.local:8080/kanban?x=1").unwrap();
+        assert_eq!((url.host.as_str(), url.port, url.path.as_str()), ("hooks.local", 8080, "/kanban?x=1"));
+        assert_eq!(WebhookUrl::parse("http://[::1]").unwrap().host, "::1");
+        assert!(WebhookUrl::parse("https://hooks.local/").is_err());
+        assert!(WebhookUrl::parse("http://:80/").is_err());
+
+        let start = Instant::now();
+        let mut queue = WebhookQueue { events: vec![serde_json::json!(1), serde_json::json!(2)], since: Some(start) };
+        let window = Duration::from_millis(500);
+        assert!(take_batches(&mut queue, window, start + Duration::from_millis(100)).is_empty());
+        assert_eq!(take_batches(&mut queue, window, start + window).len(), 1);
+        assert!(queue.events.is_empty() && queue.since.is_none());
+        queue = WebhookQueue { events: vec![serde_json::json!(1), serde_json::json!(2)], since: Some(start) };
+        assert_eq!(take_batches(&mut queue, Duration::ZERO, start).len(), 2);
+
+        let now = parse_timestamp("2026-05-01T10:00:00Z").unwrap();
+        let day = Duration::from_secs(24 * 3600);
+        assert_eq!(next_attempt("2026-05-01T10:00:00Z", 1, now, day).as_deref(), Some("2026-05-01T10:00:30Z"));
+        assert_eq!(next_attempt("2026-05-01T10:00:00Z", 3, now, day).as_deref(), Some("2026-05-01T10:02:00Z"));
+        assert_eq!(next_attempt("2026-05-01T10:00:00Z", 30, now, day).as_deref(), Some("2026-05-01T11:00:00Z"));
+        assert_eq!(next_attempt("2026-04-30T10:30:00Z", 30, now, day), None);
+        assert_eq!(next_attempt("2026-05-01T10:00:00Z", 1, now, Duration::ZERO), None);
+    }
+
+    #[test]
+    fn column_count_is_capped_and_sprawl_is_linted() {
+        let many: Vec<BoardColumn> = (0..=DEFAULT_MAX_COLUMNS).map(|i| column(&format!("c{}", i))).collect();
+        assert!(validate_columns(&many[..DEFAULT_MAX_COLUMNS], DEFAULT_MAX_COLUMNS).is_ok());
+        assert_eq!(
+            validate_columns(&many, DEFAULT_MAX_COLUMNS).unwrap_err(),
+            "Board has 51 columns, more than the maximum of 50 (raise it with --max-columns)"
+        );
+
+        let mut columns = vec![column("in-progress"), column("in_progress"), column("todo")];
+        columns[2].title = "In-Progress ".to_string();
+        let lint: Vec<(&str, String)> = lint_columns(&columns).into_iter().map(|w| (w.code, w.message)).collect();
+        assert_eq!(
+            lint,
+            vec![
+                ("lookalike_column_ids", "column ids in-progress and in_progress differ only in '-' and '_'".to_string()),
+                ("duplicate_column_title", "columns in-progress and todo have the same title \"In-Progress\"".to_string()),
+            ]
+        );
+        let mut sprawl = many[..LINT_UNLIMITED_COLUMNS + 1].to_vec();
+        assert_eq!(lint_columns(&sprawl).len(), 1);
+        sprawl[0].wip_limit = Some(3);
+        assert!(lint_columns(&sprawl).is_empty());
+    }
+
+    #[test]
+    fn editing_locks_expire_and_block_only_other_users() {
+        let locks = TaskLocks::default();
+        let now = parse_timestamp("2026-03-01T10:00:00Z").unwrap();
+        let lock = acquire_lock(&locks, "a", "alice", now).unwrap();
+        assert_eq!(lock.locked_until, "2026-03-01T10:05:00Z");
+        assert!(foreign_lock(&locks, "a", "alice", now).is_none());
+        assert_eq!(acquire_lock(&locks, "a", "bob", now).unwrap_err().locked_by, "alice");
+        let later = now + Duration::from_secs(4 * 60);
+        assert_eq!(acquire_lock(&locks, "a", "alice", later).unwrap().locked_until, "2026-03-01T10:09:00Z");
+
+        let mut tasks = vec![task("a", "todo", "2026-03-01T00:00:00Z"), task("b", "todo", "2026-03-01T00:00:00Z")];
+        set_locks(&mut tasks, &locks, later);
+        assert_eq!(serde_json::json!(tasks[0])["locked_by"], "alice");
+        assert!(serde_json::json!(tasks[1]).get("locked_by").is_none());
+
+        let expired = later + LOCK_TTL;
+        assert!(foreign_lock(&locks, "a", "bob", expired).is_none());
+        assert!(locks.entries.lock().unwrap().is_empty());
+        assert!(acquire_lock(&locks, "a", "bob", expired).is_ok());
+    }
+
+    #[test]
+    fn custom_fields_are_typed_validated_filtered_and_sorted() {
+        let mut config = BoardConfig { columns: vec![column("todo")], ..Default::default() };
+        apply_board_settings(&mut config, "field severity: enum(low,med,high)\nfield points: number\nfield customer: text\n").unwrap();
+        assert_eq!(config.fields.len(), 3);
+        assert!(render_config(&config).contains("field severity: enum(low,med,high)\nfield points: number\n"));
+        assert!(apply_board_settings(&mut BoardConfig::default(), "field title: text\n").is_err());
+        assert!(apply_board_settings(&mut BoardConfig::default(), "field size: enum()\n").is_err());
+        assert!(apply_board_settings(&mut BoardConfig::default(), "field size: date\n").is_err());
+
+        let root = test_support::TempDir::new("fields");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        let path = root.join("todo").join("a.md");
+        fs::write(&path, "title: A\nsprint: 12b\npoints: 3\nseverity: HIGH\n\nBody\n").unwrap();
+        let mut a = parse_task(&path, "todo", StatusMode::Column, "").unwrap();
+        assert_eq!(a.extra["points"], "3");
+        type_fields(&config.fields, &mut a);
+        assert_eq!(serde_json::json!(a)["extra"], serde_json::json!({ "sprint": "12b", "points": 3, "severity": "high" }));
+        assert!(render_task(&a).contains("sprint: 12b\npoints: 3\nseverity: high\n"));
+
+        let bad = serde_json::json!({ "severity": "urgent" }).as_object().unwrap().clone();
+        assert_eq!(normalize_extra(&config.fields, bad).unwrap_err(), "Invalid severity: urgent (allowed: low, med, high)");
+        let bad = serde_json::json!({ "points": "many" }).as_object().unwrap().clone();
+        assert!(normalize_extra(&config.fields, bad).is_err());
+        let update = serde_json::json!({ "points": 5, "sprint": null }).as_object().unwrap().clone();
+        merge_extra(&mut a, normalize_extra(&config.fields, update).unwrap());
+        assert_eq!(serde_json::json!(a.extra), serde_json::json!({ "points": 5, "severity": "high" }));
+
+        let mut b = task("b", "todo", "2026-01-02T00:00:00Z");
+        b.extra.insert("severity".to_string(), serde_json::json!("low"));
+        let c = task("c", "todo", "2026-01-03T00:00:00Z");
+        let query = HashMap::from([("field.severity".to_string(), "High".to_string())]);
+        let filter = parse_task_filter(&query, UtcOffset::UTC).unwrap();
+        assert_eq!(filter_task(&filter, &a), FilterOutcome::Match);
+        assert_eq!(filter_task(&filter, &b), FilterOutcome::Reject);
+
+        for (sort, expected) in [("field.severity", ["b", "a", "c"]), ("-field.severity", ["a", "b", "c"])] {
+            let page = parse_page(&HashMap::from([("sort".to_string(), sort.to_string())])).unwrap();
+            let mut tasks = vec![c.clone(), a.clone(), b.clone()];
+            let mut meta = column_meta(&column("todo"), 3, 3);
+            paginate(&mut tasks, &column("todo"), &page, &config.fields, &[], &mut None, &mut meta);
+            assert_eq!(tasks.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), expected, "{}", sort);
+        }
+        assert!(parse_page(&HashMap::from([("sort".to_string(), "title".to_string())])).is_err());
+    }
+
+    #[test]
+    fn request_targets_ignore_queries_fragments_and_trailing_slashes() {
+        let endpoints = [
+            "/",
+            "/app.js",
+            "/api/board",
+            "/api/board/pending-changes",
+            "/api/board/foreign-files",
+            "/api/board/columns/reorder",
+            "/api/tasks",
+            "/api/tasks/batch",
+            "/api/tasks/fix-login/move",
+            "/api/tasks/fix-login/attachments/shot.png",
+            "/api/columns/todo/tasks",
+            "/api/search",
+            "/api/stats",
+            "/api/stats/workload",
+            "/api/stats/heatmap",
+            "/api/stats/forecast",
+            "/api/stats/burnup",
+            "/api/health",
+            "/api/ui",
+            "/api/readme",
+            "/api/updates",
+            "/api/undo",
+            "/api/users/alice/activity",
+        ];
+        for endpoint in endpoints {
+            for url in [
+                endpoint.to_string(),
+                format!("{}?v=123", endpoint),
+                format!("{}/?", endpoint.trim_end_matches('/')),
+                format!("{}#top", endpoint),
+                format!("{}?since=4&x=1#frag", endpoint),
+            ] {
+                assert_eq!(parse_target(&url).unwrap().path, endpoint, "{}", url);
+            }
+        }
+
+        let target = parse_target("/api/users/J%C3%BCrgen%20K+/activity?days=7&q=a+b%26c#x").unwrap();
+        assert_eq!(target.segments, vec!["api", "users", "Jürgen K+", "activity"]);
+        assert_eq!(target.query.get("days").map(String::as_str), Some("7"));
+        assert_eq!(target.query.get("q").map(String::as_str), Some("a b&c"));
+        assert_eq!(parse_since(&parse_target("/api/updates?since=42").unwrap().query), 42);
+
+        for bad in ["/api/tasks/a%2Fb", "/api/tasks/..", "/api/tasks/%2E%2E/move", "/api/tasks/a%5Cb"] {
+            assert!(parse_target(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn internal_dirs_cover_tooling_and_the_ignore_file() {
+        let none: Vec<String> = Vec::new();
+        for name in [".git", ".hg", ".svn", ".history", ".trash", ".kanban-templates", "_attachments", "_archive", "node_modules", "target"] {
+            assert!(is_internal_dir(name, &none), "{}", name);
+        }
+        for name in ["backlog", "done", "targets", "archive", "_notes"] {
+            assert!(!is_internal_dir(name, &none), "{}", name);
+        }
+
+        let root = test_support::TempDir::new("ignore");
+        for dir in ["todo", "vendor", ".hg", "node_modules", "notes", "scratch"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("notes").join("a.md"), "title: A\n").unwrap();
+        fs::write(root.join("scratch").join("b.md"), "title: B\n").unwrap();
+        fs::write(root.join(IGNORE_FILE), "# tooling\nvendor/\n\nscratch\n").unwrap();
+        assert_eq!(read_ignore_file(&root), vec!["vendor", "scratch"]);
+
+        let cfg = BoardConfig { columns: vec![column("todo"), column("scratch")], ..Default::default() };
+        let plan = plan_reconcile(&root, &cfg).unwrap();
+        assert!(plan.remove_empty_folders.is_empty());
+        let orphans: Vec<&str> = plan.orphan_folders.iter().map(|o| o.folder.as_str()).collect();
+        assert_eq!(orphans, vec!["notes"]);
+        assert!(load_all_tasks(&root, &cfg, &BoardOptions::default(), None).unwrap()["scratch"].is_empty());
+    }
+
+    #[test]
+    fn missing_config_at_request_time_fails_fast_or_uses_the_last_good_one() {
+        let root = test_support::TempDir::new("noconfig");
+        let (tx, rx) = std::sync::mpsc::channel();
+        let probe = root.to_path_buf();
+        std::thread::spawn(move || tx.send(refresh_config(&probe)).unwrap());
+        let result = rx.recv_timeout(Duration::from_secs(2)).expect("refresh_config must not wait for input");
+        assert_eq!(result.unwrap_err(), CONFIG_MISSING);
+        assert!(!config_path(&root).exists());
+
+        fs::write(config_path(&root), "todo: Todo\n").unwrap();
+        assert_eq!(refresh_config(&root).unwrap().columns.len(), 1);
+        fs::remove_file(config_path(&root)).unwrap();
+        assert_eq!(refresh_config(&root).unwrap().columns[0].id, "todo");
+    }
+
+    #[test]
+    fn transfer_moves_the_task_and_its_attachments_without_leaving_a_copy() {
+        let base = test_support::TempDir::new("transfer");
+        let (src, dest) = (base.join("work"), base.join("team"));
+        for dir in [src.join("todo"), dest.join("backlog")] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(src.join("todo").join("fix.md"), "title: Fix\nstatus: todo\n").unwrap();
+        fs::write(dest.join("backlog").join("fix.md"), "title: Other fix\nstatus: backlog\n").unwrap();
+        fs::create_dir_all(attachments_dir(&src, "fix")).unwrap();
+        fs::write(attachments_dir(&src, "fix").join("log.txt"), "trace").unwrap();
+        let src_cfg = BoardConfig { columns: vec![column("todo")], ..Default::default() };
+        let dest_cfg = BoardConfig { columns: vec![column("backlog")], ..Default::default() };
+
+        let (task, from) = transfer_task(&src, &src_cfg, &BoardOptions::default(), "fix", &dest, &dest_cfg, None, 0).unwrap();
+        assert_eq!((task.id.as_str(), task.folder.as_str(), task.status.as_str(), from.as_str()), ("fix-2", "backlog", "backlog", "todo"));
+        assert!(dest.join("backlog").join("fix-2.md").exists());
+        assert_eq!(fs::read_to_string(attachments_dir(&dest, "fix-2").join("log.txt")).unwrap(), "trace");
+        assert!(fs::read_dir(src.join("todo")).unwrap().next().is_none());
+        assert!(!attachments_dir(&src, "fix").exists());
+        assert_eq!(transfer_task(&src, &src_cfg, &BoardOptions::default(), "fix", &dest, &dest_cfg, None, 0).unwrap_err().0, 404);
+
+        let full = BoardConfig { max_tasks: Some(2), ..dest_cfg.clone() };
+        fs::write(src.join("todo").join("more.md"), "title: More\n").unwrap();
+        assert_eq!(transfer_task(&src, &src_cfg, &BoardOptions::default(), "more", &dest, &full, None, 0).unwrap_err().0, 507);
+        assert!(src.join("todo").join("more.md").exists());
+    }
+
+    #[test]
+    fn migrations_run_once_in_order_and_refuse_newer_boards() {
+        assert_eq!(MIGRATIONS.last().map(|m| m.version), Some(FORMAT_VERSION));
+        assert!(MIGRATIONS.windows(2).all(|pair| pair[0].version + 1 == pair[1].version));
+
+        let root = test_support::TempDir::new("migrate");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        let old = "title: Old\nstatus: todo\nupdated_at: 2024-01-02T03:04:05Z\n";
+        fs::write(root.join("todo").join("old.md"), old).unwrap();
+        let cfg = BoardConfig { columns: vec![column("todo")], ..Default::default() };
+
+        assert_eq!(check_format_version(&root), Ok(0));
+        assert_eq!(migrate_column_since(&root, &cfg, &BoardOptions::default(), true).unwrap(), vec!["todo/old.md"]);
+        assert_eq!(run_migrations(&root, &cfg, &BoardOptions::default(), true).unwrap().len(), 1);
+        assert_eq!(fs::read_to_string(root.join("todo").join("old.md")).unwrap(), old);
+        assert!(!root.join(FORMAT_VERSION_FILE).exists());
+
+        assert_eq!(run_migrations(&root, &cfg, &BoardOptions::default(), false).unwrap().len(), 1);
+        let task = parse_task(&root.join("todo").join("old.md"), "todo", cfg.status_mode, "").unwrap();
+        assert_eq!(task.column_since.as_deref(), Some("2024-01-02T03:04:05Z"));
+        assert_eq!(check_format_version(&root), Ok(FORMAT_VERSION));
+        assert!(run_migrations(&root, &cfg, &BoardOptions::default(), false).unwrap().is_empty());
+        assert!(migrate_column_since(&root, &cfg, &BoardOptions::default(), false).unwrap().is_empty());
+
+        fs::write(root.join(FORMAT_VERSION_FILE), format!("{}\n", FORMAT_VERSION + 1)).unwrap();
+        assert!(run_migrations(&root, &cfg, &BoardOptions::default(), false).unwrap_err().contains("newer than this server supports"));
+    }
+
+    #[test]
+    fn workload_counts_open_tasks_per_assignee_including_idle_users() {
+        let config = BoardConfig { columns: vec![column("todo"), column("doing"), column("done")], ..Default::default() };
+        let assigned = |id: &str, folder: &str, who: &str, estimate: Option<f64>| {
+            let mut t = task(id, folder, "2026-01-10T00:00:00Z");
+            t.assigned_to = who.to_string();
+            t.estimate = estimate;
+            t
+        };
+        let mut late = assigned("late", "doing", "ana", Some(3.0));
+        late.due = Some("2026-01-31".to_string());
+        let folders = HashMap::from([
+            ("todo".to_string(), vec![assigned("a", "todo", "Ana", Some(2.0)), assigned("b", "todo", "", None)]),
+            ("doing".to_string(), vec![late, assigned("c", "doing", "bo", None)]),
+            ("done".to_string(), vec![assigned("d", "done", "bo", Some(8.0))]),
+        ]);
+        let now = parse_timestamp("2026-02-01T12:00:00Z").unwrap();
+        let users = vec!["Cy".to_string(), "ANA".to_string()];
+        let report = workload(&config, &folders, &users, now, UtcOffset::UTC, true);
+        let rows: Vec<(&str, usize, f64, usize, usize)> = report
+            .assignees
+            .iter()
+            .map(|w| (w.assignee.as_str(), w.open, w.estimate, w.unestimated, w.overdue))
+            .collect();
+        assert_eq!(rows, vec![("ANA", 2, 5.0, 0, 1), ("bo", 1, 0.0, 1, 0), ("Cy", 0, 0.0, 0, 0)]);
+        let columns: Vec<(&str, usize)> = report.assignees[0].columns.as_ref().unwrap().iter().map(|c| (c.name.as_str(), c.count)).collect();
+        assert_eq!(columns, vec![("todo", 1), ("doing", 1)]);
+        assert_eq!((report.unassigned.open, report.unassigned.unestimated), (1, 1));
+        assert!(workload(&config, &folders, &[], now, UtcOffset::UTC, false).assignees[0].columns.is_none());
+        assert_eq!(normalize_estimate(0.0), Ok(None));
+        assert!(normalize_estimate(-1.0).is_err());
+    }
+
+    #[test]
+    fn failed_board_edits_remove_new_folders_and_keep_the_old_config() {
+        // Runs as root in CI, where read-only modes are not enforced, so the
+        // failures come from paths that cannot be a directory or a file.
+        let root = test_support::TempDir::new("board-rollback");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        fs::write(config_path(&root), "todo: Todo\n").unwrap();
+        fs::write(root.join("blocked"), "not a folder").unwrap();
+
+        let mut config = BoardConfig { columns: vec![column("todo"), column("fresh"), column("blocked")], ..Default::default() };
+        let err = apply_board_config(&root, &mut config, "alice").unwrap_err();
+        assert!(err.contains("blocked"), "{}", err);
+        assert!(!root.join("fresh").exists());
+        assert_eq!(fs::read_to_string(config_path(&root)).unwrap(), "todo: Todo\n");
+
+        fs::create_dir(root.join(format!("{}.tmp", CONFIG_FILE))).unwrap();
+        let mut config = BoardConfig { columns: vec![column("todo"), column("fresh")], ..Default::default() };
+        let err = apply_board_config(&root, &mut config, "alice").unwrap_err();
+        assert!(err.contains(CONFIG_FILE), "{}", err);
+        assert!(!root.join("fresh").exists());
+        assert_eq!(fs::read_to_string(config_path(&root)).unwrap(), "todo: Todo\n");
+
+        fs::remove_dir(root.join(format!("{}.tmp", CONFIG_FILE))).unwrap();
+        apply_board_config(&root, &mut config, "alice").unwrap();
+        assert!(root.join("fresh").is_dir());
+        assert_eq!(read_config(&root).unwrap().columns.len(), 2);
+    }
+
+    #[test]
+    fn board_markdown_lists_columns_in_order_with_delimited_tasks() {
+        let mut todo = column("todo");
+        todo.title = "To do".to_string();
+        todo.wip_limit = Some(3);
+        let config = BoardConfig { columns: vec![todo, column("done")], ..Default::default() };
+        let mut fix = task("fix", "todo", "2026-01-10T00:00:00Z");
+        fix.title = "Fix login".to_string();
+        fix.description = "## Steps\n\nRetry twice.".to_string();
+        let root = test_support::TempDir::new("markdown");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        fs::write(root.join("todo/fix.md"), render_task(&fix)).unwrap();
+        fs::write(root.join("todo/broken.md"), [0xff, 0xfe]).unwrap();
+
+        let files = board_markdown_files(&root, &config, &BoardOptions::default(), None).unwrap();
+        let mut markdown = Vec::new();
+        write_board_markdown(&mut markdown, "Team", config.status_mode, &BoardOptions::default(), &files, None).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+        assert!(markdown.starts_with("# Team\n\n## To do\n\nColumn `todo`, WIP limit 3\n\n### Fix login\n\n<!-- task: todo/fix.md -->\n\n"));
+        assert!(markdown.contains("\ntitle\n: Fix login\n"));
+        assert!(markdown.ends_with("## DONE\n\nColumn `done`\n\n_No tasks._\n"));
+
+        let start = markdown.find("<!-- task: todo/fix.md -->").unwrap();
+        let end = markdown.find("<!-- /task -->").unwrap();
+        let body = &markdown[start..end];
+        assert!(body.contains("\n## Steps\n\nRetry twice.\n"));
+        assert_eq!(body.lines().filter(|line| line.starts_with(": ")).count(), render_task(&fix).split("\n\n").next().unwrap().lines().filter(|line| !line.ends_with(": ")).count());
+    }
+
+    struct CountingAllocator;
+
+    // Per thread, so tests running in parallel do not count each other.
+    thread_local! {
+        static LIVE_BYTES: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+        static PEAK_BYTES: std::cell::Cell<isize> = const { std::cell::Cell::new(0) };
+    }
+
+    unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+            let ptr = std::alloc::System.alloc(layout);
+            if !ptr.is_null() {
+                let _ = LIVE_BYTES.try_with(|live| {
+                    live.set(live.get() + layout.size() as isize);
+                    let _ = PEAK_BYTES.try_with(|peak| peak.set(peak.get().max(live.get())));
+                });
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+            std::alloc::System.dealloc(ptr, layout);
+            let _ = LIVE_BYTES.try_with(|live| live.set(live.get() - layout.size() as isize));
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    /// Most bytes this thread held at once while `run` ran, beyond what it
+    /// held before.
+    fn peak_allocation(run: impl FnOnce()) -> isize {
+        let start = LIVE_BYTES.with(|live| live.get());
+        PEAK_BYTES.with(|peak| peak.set(start));
+        run();
+        PEAK_BYTES.with(|peak| peak.get()) - start
+    }
+
+    #[test]
+    fn board_markdown_export_memory_stays_flat_as_the_board_grows() {
+        let config = BoardConfig { columns: vec![column("todo"), column("done")], ..Default::default() };
+        let export_peak = |tasks: usize| {
+            let root = test_support::TempDir::new(&format!("export-{}", tasks));
+            for folder in ["todo", "done"] {
+                fs::create_dir_all(root.join(folder)).unwrap();
+            }
+            for i in 0..tasks {
+                let folder = if i % 2 == 0 { "todo" } else { "done" };
+                let mut t = task(&format!("task-{:05}", i), folder, "2026-01-10T00:00:00Z");
+                t.description = "Steps to reproduce and expected behaviour. ".repeat(50);
+                fs::write(root.join(folder).join(format!("{}.md", t.id)), render_task(&t)).unwrap();
+            }
+            let mut written = 0;
+            let peak = peak_allocation(|| {
+                let files = board_markdown_files(&root, &config, &BoardOptions::default(), None).unwrap();
+                let mut out = CountingSink(0);
+                write_board_markdown(&mut io::BufWriter::new(&mut out), "Team", config.status_mode, &BoardOptions::default(), &files, None).unwrap();
+                written = out.0;
+            });
+            (peak, written)
+        };
+        struct CountingSink(usize);
+        impl Write for CountingSink {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0 += buf.len();
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let (small, _) = export_peak(20);
+        let (large, written) = export_peak(4000);
+        assert!(written > 8_000_000, "{}", written);
+        // Only the file list grows with the board, by about 100 bytes a task.
+        assert!(large < small + 1_000_000 && large < written as isize / 10, "small {} large {} written {}", small, large, written);
+    }
+
+    #[test]
+    fn heatmap_fills_every_day_and_falls_back_to_headers_before_the_log() {
+        let config = BoardConfig { columns: vec![column("todo"), column("done")], ..Default::default() };
+        let mut old = task("old", "done", "2026-01-02T00:00:00Z");
+        old.created_at = Timestamp::read("2026-01-01T10:00:00Z");
+        old.completed_at = Some(Timestamp::read("2026-01-02T23:30:00Z"));
+        let mut new = task("new", "todo", "2026-01-04T00:00:00Z");
+        new.created_at = Timestamp::read("2026-01-04T09:00:00Z");
+        let folders = HashMap::from([("done".to_string(), vec![old]), ("todo".to_string(), vec![new])]);
+        let log = concat!(
+            "{\"at\":\"2026-01-04T09:00:00Z\",\"action\":\"task_created\",\"task\":\"new\",\"column\":\"todo\"}\n",
+            "not json\n",
+            "{\"at\":\"2026-01-05T08:00:00Z\",\"action\":\"task_moved\",\"task\":\"new\",\"from\":\"todo\",\"to\":\"done\"}\n",
+        );
+        let today = Date::from_calendar_date(2026, Month::January, 5).unwrap();
+        let tz = UtcOffset::from_hms(1, 0, 0).unwrap();
+        let heatmap = activity_heatmap(Some(log.as_bytes()), &config, &folders, today, tz, 5);
+
+        assert_eq!((heatmap.from.as_str(), heatmap.log_since.as_deref()), ("2026-01-01", Some("2026-01-04")));
+        assert_eq!((heatmap.header_days, heatmap.log_days, heatmap.days.len()), (3, 2, 5));
+        let day = |date: &str| {
+            let d = &heatmap.days[date];
+            (d.created, d.moved, d.completed, d.source)
+        };
+        assert_eq!(day("2026-01-01"), (1, 0, 0, HeatmapSource::Headers));
+        assert_eq!(day("2026-01-02"), (0, 0, 0, HeatmapSource::Headers));
+        assert_eq!(day("2026-01-03"), (0, 0, 1, HeatmapSource::Headers));
+        assert_eq!(day("2026-01-04"), (1, 0, 0, HeatmapSource::Log));
+        assert_eq!(day("2026-01-05"), (0, 1, 1, HeatmapSource::Log));
+
+        let without_log = activity_heatmap(None::<&[u8]>, &config, &folders, today, tz, 5);
+        assert_eq!((without_log.header_days, without_log.log_since), (5, None));
+        assert_eq!(without_log.days["2026-01-04"].created, 1);
+    }
+
+    #[test]
+    fn column_templates_seed_new_tasks_but_not_moved_ones() {
+        let root = test_support::TempDir::new("task-template");
+        fs::create_dir_all(root.join(TASK_TEMPLATES_DIR)).unwrap();
+        fs::write(
+            root.join(TASK_TEMPLATES_DIR).join("incident.md"),
+            "title: Incident\ntags: incident, ops\n\n## Impact\n\n## Timeline\n\n## Action items\n",
+        )
+        .unwrap();
+        let incidents = parse_config_line("incidents: Incidents default_tags=ops template=incident").unwrap();
+        assert_eq!(incidents.template.as_deref(), Some("incident"));
+        let cfg = BoardConfig { columns: vec![column("todo"), incidents], ..Default::default() };
+        assert!(render_config(&cfg).contains("incidents: Incidents default_tags=ops template=incident\n"));
+
+        let request = |description: Option<&str>| {
+            serde_json::from_value::<NewTask>(serde_json::json!({ "title": "Outage", "folder": "incidents", "description": description })).unwrap()
+        };
+        let (seeded, applied) = build_new_task(&root, &cfg, &BoardOptions::default(), request(None), &HashSet::new()).unwrap();
+        assert!(seeded.description.starts_with("## Impact"));
+        assert_eq!(seeded.tags, vec!["ops", "incident"]);
+        assert_eq!((applied.tags, applied.template.as_deref()), (vec!["ops".to_string(), "incident".to_string()], Some("incident")));
+
+        let (given, applied) = build_new_task(&root, &cfg, &BoardOptions::default(), request(Some("Already known")), &HashSet::new()).unwrap();
+        assert_eq!((given.description.as_str(), applied.template), ("Already known", None));
+
+        let mut moved = task("moved", "todo", "2026-01-01T00:00:00Z");
+        moved.description = "Keep me".to_string();
+        apply_column_defaults(&cfg.columns[1], &mut moved);
+        assert_eq!(moved.description, "Keep me");
+
+        let broken = BoardConfig { columns: vec![BoardColumn { template: Some("../x".to_string()), ..column("todo") }], ..Default::default() };
+        assert!(validate_columns(&broken.columns, DEFAULT_MAX_COLUMNS).is_err());
+    }
+
+    #[test]
+    fn csv_parsing_handles_quotes_crlf_and_blank_lines() {
+        let rows = parse_csv("\u{feff}a,\"b, c\",\"say \"\"hi\"\"\"\r\n\r\n1,\"two\nlines\",\n").unwrap();
+        assert_eq!(rows, vec![vec!["a", "b, c", "say \"hi\""], vec!["1", "two\nlines", ""]]);
+        assert!(parse_csv("a,\"open").is_err());
+        let header = vec!["Name".to_string(), "Lane".to_string()];
+        let mapping = parse_csv_mapping("title:name, column:Lane", Some(&header)).unwrap();
+        assert_eq!((mapping["title"], mapping["column"]), (0, 1));
+        assert!(parse_csv_mapping("title:Name", None).is_err());
+        assert!(parse_csv_mapping("tags:2", None).is_err());
+        assert!(parse_csv_mapping("title:1,points:2", None).is_err());
+    }
+
+    #[test]
+    fn csv_import_creates_skips_and_fails_rows() {
+        let root = test_support::TempDir::new("csv");
+        for dir in ["todo", "doing"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let cfg = BoardConfig { columns: vec![column("todo"), column("doing")], ..Default::default() };
+        let csv = "Title,Who,Lane,Tags\nShip it,ana,doing,a; b\n,bo,todo,\nShip it,,Nowhere,\n";
+        let lenient = CsvImportOptions { map: "title:Title,assigned_to:Who,column:Lane,tags:4".to_string(), headers: true, strict: false, ..Default::default() };
+        let report = import_csv(&root, &cfg, &BoardOptions::default(), csv, &lenient).unwrap();
+        assert_eq!((report.created, report.skipped, report.failed), (2, 1, 0));
+        assert_eq!(report.tasks[0].folder, "doing");
+        assert_eq!(report.tasks[0].tags, vec!["a", "b"]);
+        assert_eq!((report.tasks[1].id.as_str(), report.tasks[1].folder.as_str()), ("ship-it-2", "todo"));
+        assert_eq!(report.results[1]["skipped"], "empty title");
+
+        let strict = CsvImportOptions { strict: true, ..lenient };
+        let report = import_csv(&root, &cfg, &BoardOptions::default(), csv, &strict).unwrap();
+        assert_eq!((report.created, report.failed), (1, 1));
+        assert_eq!(report.results[2]["error"], "Unknown column: Nowhere");
+
+        let full = BoardConfig { max_tasks: Some(3), ..cfg.clone() };
+        assert_eq!(import_csv(&root, &full, &BoardOptions::default(), csv, &strict).unwrap_err().0, 507);
+        assert_eq!(import_csv(&root, &cfg, &BoardOptions::default(), csv, &CsvImportOptions::default()).unwrap_err().0, 400);
+    }
+
+    #[test]
+    fn csv_import_folds_line_breaks_in_header_cells() {
+        let root = test_support::TempDir::new("csv-lines");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        let cfg = BoardConfig { columns: vec![column("todo"), column("done")], ..Default::default() };
+        let csv = "Inj,\"bob\ncolumn_since: x\r\nstatus: done\",\"a;\nb\",\"multi\nline\",\"Steps:\n1. run\"\n";
+        let options = CsvImportOptions { map: "title:4,assigned_to:2,tags:3,description:5".to_string(), ..Default::default() };
+        let report = import_csv(&root, &cfg, &BoardOptions::default(), csv, &options).unwrap();
+        assert_eq!(report.created, 1, "{:?}", report.results);
+        let file = fs::read_to_string(root.join("todo").join("multi-line.md")).unwrap();
+        let (header, body) = file.split_once("\n\n").unwrap();
+        let lines: Vec<_> = header.lines().filter(|line| !line.contains("_at: ") && !line.starts_with("column_since: ")).collect();
+        assert_eq!(lines, ["creator: ", "assigned_to: bob column_since: x status: done", "status: todo", "tags: a, b", "title: multi line"]);
+        assert_eq!(header.lines().filter(|line| line.starts_with("column_since: ")).count(), 1, "{}", file);
+        assert_eq!(header.lines().filter(|line| line.starts_with("status: ")).count(), 1, "{}", file);
+        assert_eq!(body.trim_end(), "Steps:\n1. run");
+    }
+
+    #[test]
+    fn similar_titles_match_after_normalizing_and_by_word_prefix() {
+        let titled = |id: &str, title: &str| {
+            let mut t = task(id, "todo", "2026-01-01T00:00:00Z");
+            t.title = title.to_string();
+            t
+        };
+        let folders = HashMap::from([(
+            "todo".to_string(),
+            vec![
+                titled("ci", "Fix  flaky CI"),
+                titled("ci-mac", "Fix flaky CI on macOS"),
+                titled("fixture", "Fixture cleanup"),
+                titled("other", "Write docs"),
+            ],
+        )]);
+        let found: Vec<(String, bool)> = similar_tasks(&folders, " fix flaky ci ").into_iter().map(|s| (s.id, s.exact)).collect();
+        assert_eq!(found, vec![("ci".to_string(), true), ("ci-mac".to_string(), false)]);
+        assert!(similar_tasks(&folders, "Fix").is_empty());
+        assert!(similar_tasks(&folders, "").is_empty());
+
+        let mut config = BoardConfig { columns: vec![column("todo")], ..Default::default() };
+        apply_board_settings(&mut config, "unique_titles = enforce\n").unwrap();
+        assert_eq!(config.unique_titles, UniqueTitles::Enforce);
+        assert!(render_config(&config).starts_with("unique_titles = enforce\n"));
+        assert!(apply_board_settings(&mut config, "unique_titles = strict\n").is_err());
+    }
+
+    #[test]
+    fn json_theme_overrides_the_conf_file_and_is_validated() {
+        let root = test_support::TempDir::new("theme");
+        fs::write(theme_path(&root), "headline=Conf\ncolor.accent=#111111\nfont_size=14px\ncolumn_color.todo=#AABBCC\n").unwrap();
+        fs::write(
+            theme_json_path(&root),
+            r##"{"colors": {"ink": "#222222"}, "card_radius": "0.5rem", "density": "compact", "column_colors": {"done": "#27AE60"}}"##,
+        )
+        .unwrap();
+        let theme = read_theme(&root).unwrap();
+        assert_eq!(theme.headline.as_deref(), Some("Conf"));
+        assert_eq!(theme.colors.len(), 2);
+        assert_eq!((theme.font_size.as_str(), theme.card_radius.as_str()), ("14px", "0.5rem"));
+        assert_eq!(theme.density, Density::Compact);
+        assert_eq!(theme.column_colors["todo"], "#aabbcc");
+        assert_eq!(theme.column_colors["done"], "#27ae60");
+
+        let config = BoardConfig { columns: vec![column("todo"), column("done")], ..Default::default() };
+        assert!(save_theme_file(&root, &config, r##"{"font_size": "large"}"##).is_err_and(|(code, _)| code == 400));
+        assert!(save_theme_file(&root, &config, r##"{"density": "cozy"}"##).is_err());
+        assert!(save_theme_file(&root, &config, r##"{"fontsize": "12px"}"##).is_err());
+        let (code, msg) = save_theme_file(&root, &config, r##"{"column_colors": {"archive": "#000000"}}"##).unwrap_err();
+        assert_eq!((code, msg.as_str()), (400, "column_colors names unknown columns: archive"));
+        save_theme_file(&root, &config, r##"{"font_family": "Inter, sans-serif"}"##).unwrap();
+        let theme = read_theme(&root).unwrap();
+        assert_eq!(theme.font_family, "Inter, sans-serif");
+        assert_eq!(theme.density, Density::Comfortable);
+    }
+
+    #[test]
+    fn task_routes_resolve_in_a_fixed_order() {
+        let root = test_support::TempDir::new("routes");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        fs::write(task_path(&root, "todo", "fix"), "title: Fix\n").unwrap();
+        fs::create_dir_all(history_dir(&root, "gone")).unwrap();
+        let config = BoardConfig { columns: vec![column("todo"), column("done")], ..Default::default() };
+        let cases = [
+            (Method::Put, "Bad_Id", 400, None, ""),
+            (Method::Get, "bad_id/archive", 400, None, ""),
+            (Method::Post, "fix/archive", 404, Some("unknown_action"), ""),
+            (Method::Post, "missing/archive", 404, Some("unknown_action"), ""),
+            (Method::Get, "fix/versions/1/restore/now", 404, Some("unknown_action"), ""),
+            (Method::Post, "fix", 405, Some("method_not_allowed"), "GET, PUT, DELETE"),
+            (Method::Post, "missing", 405, Some("method_not_allowed"), "GET, PUT, DELETE"),
+            (Method::Get, "missing", 404, Some("task_not_found"), ""),
+            (Method::Get, "fix", 200, None, ""),
+            (Method::Get, "fix/move", 405, Some("method_not_allowed"), "POST"),
+            (Method::Put, "fix/lock", 405, Some("method_not_allowed"), "POST, DELETE"),
+            (Method::Post, "fix/export", 405, Some("method_not_allowed"), "GET"),
+            (Method::Put, "missing", 404, Some("task_not_found"), ""),
+            (Method::Post, "missing/move", 404, Some("task_not_found"), ""),
+            (Method::Get, "missing/versions", 404, Some("task_not_found"), ""),
+            (Method::Delete, "gone", 404, Some("task_not_found"), ""),
+            (Method::Get, "gone/versions", 200, None, ""),
+            (Method::Get, "gone/diff", 200, None, ""),
+            (Method::Put, "fix", 200, None, ""),
+            (Method::Delete, "fix/attachments/a.png", 200, None, ""),
+            (Method::Post, "fix/versions/1/restore", 200, None, ""),
+        ];
+        for (method, path, status, error, allow) in cases {
+            let parts: Vec<&str> = path.split('/').collect();
+            let (code, body, allowed) = match check_task_route(&root, &config, &BoardOptions::default(), &parts, &method) {
+                Ok(()) => (200, serde_json::Value::Null, None),
+                Err(response) => {
+                    let code = response.status_code().0;
+                    let allowed = response
+                        .headers()
+                        .iter()
+                        .find(|h| h.field.equiv("Allow"))
+                        .map(|h| h.value.to_string());
+                    let mut body = String::new();
+                    response.into_reader().read_to_string(&mut body).unwrap();
+                    (code, serde_json::from_str(&body).unwrap(), allowed)
+                }
+            };
+            assert_eq!(code, status, "{} {}", method, path);
+            if let Some(error) = error {
+                assert_eq!(body["error"], error, "{} {}", method, path);
+            }
+            assert_eq!(allowed.unwrap_or_default(), allow, "{} {}", method, path);
+        }
+    }
+
+    #[test]
+    fn snapshot_diff_lists_added_removed_moved_and_edited_tasks() {
+        let root = test_support::TempDir::new("snapshots");
+        let mut todo = column("todo");
+        todo.title = "To Do".to_string();
+        let config = BoardConfig { columns: vec![todo, column("done")], ..Default::default() };
+        let old = "2026-01-01T09:00:00Z";
+        let before = HashMap::from([
+            ("todo".to_string(), vec![task("stay", "todo", old), task("edit", "todo", old), task("go", "todo", old)]),
+            ("done".to_string(), vec![task("drop", "done", old)]),
+        ]);
+        for name in ["a", "b", "c"] {
+            take_snapshot(&root, &config, &before, name).unwrap();
+        }
+        prune_snapshots(&root, 2);
+        assert_eq!(list_snapshots(&root).len(), 2);
+        let snapshot = read_snapshot(&root, "c").unwrap();
+
+        let new = "2026-01-02T09:00:00Z";
+        let after = HashMap::from([
+            ("todo".to_string(), vec![task("stay", "todo", old), task("edit", "todo", new), task("new", "todo", new)]),
+            ("done".to_string(), vec![task("go", "done", new)]),
+        ]);
+        let diff = diff_snapshot(&snapshot, &config, &after);
+        let ids = |entries: &[SnapshotEntry]| entries.iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&diff.added), vec!["new"]);
+        assert_eq!(ids(&diff.edited), vec!["edit"]);
+        assert_eq!(ids(&diff.removed), vec!["drop"]);
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!((diff.moved[0].from.as_str(), diff.moved[0].to.as_str()), ("todo", "done"));
+        let markdown = render_snapshot_markdown(&diff, &config);
+        assert!(markdown.contains("## Moved\n\n- go: To Do → DONE\n"));
+        assert!(markdown.contains("## Removed\n\n- drop (DONE)\n"));
+    }
+
+    #[test]
+    fn request_bodies_report_unknown_fields() {
+        assert_eq!(accepted_fields::<MoveTask>(), ["folder", "status", "position"]);
+        assert!(accepted_fields::<NewTask>().contains(&"assigned_to"));
+
+        let (update, ignored) = parse_body::<UpdateTask>(r#"{"title": "New", "asigned_to": "ana"}"#, false).unwrap();
+        assert_eq!(update.title.as_deref(), Some("New"));
+        assert_eq!(ignored, vec!["asigned_to"]);
+
+        let error = parse_body::<NewTask>(r#"{"name": "Fix login"}"#, false).unwrap_err();
+        assert_eq!(error["error"], "missing field `title` (ignored unknown fields: name)");
+        assert_eq!(error["unknown_fields"], serde_json::json!(["name"]));
+        assert!(error["accepted_fields"].as_array().unwrap().contains(&serde_json::json!("title")));
+
+        let payload = with_ignored_fields(serde_json::json!({ "id": "a" }), &ignored);
+        assert_eq!(payload["ignored_fields"], serde_json::json!(["asigned_to"]));
+        assert!(with_ignored_fields(serde_json::json!({}), &[]).get("ignored_fields").is_none());
+    }
+
+    #[test]
+    fn publish_writes_a_static_copy_and_can_run_again() {
+        let base = test_support::TempDir::new("publish");
+        let root = base.join("board");
+        let out = base.join("site");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        fs::create_dir_all(root.join("_archive")).unwrap();
+        let mut config = BoardConfig { columns: vec![column("todo")], ..Default::default() };
+        write_config(&root, &mut config, "server").unwrap();
+        fs::write(task_path(&root, "todo", "fix"), "title: Fix\n").unwrap();
+        fs::write(task_path(&root, "_archive", "old"), "title: Old\n").unwrap();
+
+        let mut options = PublishOptions { out: Some(out.clone()), ..Default::default() };
+        let report = publish_board(&root, &BoardOptions::default(), &options).unwrap();
+        assert_eq!((report.tasks, report.files), (1, 8));
+        assert_eq!(fs::read_to_string(out.join("views.json")).unwrap(), r#"{"views":[]}"#);
+        let data: serde_json::Value = serde_json::from_str(&fs::read_to_string(out.join("data.json")).unwrap()).unwrap();
+        assert_eq!(data["folders"]["todo"][0]["id"], "fix");
+        assert!(data["folders"].get("_archive").is_none());
+        assert!(fs::read_to_string(out.join("index.html")).unwrap().contains("<script src=\"config.js\"></script>"));
+
+        options.include_archive = true;
+        let report = publish_board(&root, &BoardOptions::default(), &options).unwrap();
+        assert_eq!(report.tasks, 2);
+        let board: serde_json::Value = serde_json::from_str(&fs::read_to_string(out.join("board.json")).unwrap()).unwrap();
+        assert_eq!(board["board"]["columns"][1]["id"], "_archive");
+
+        options.out = Some(root.join(".site"));
+        assert!(publish_board(&root, &BoardOptions::default(), &options).is_err());
+        assert!(!root.join(".site").exists());
+    }
+
+    #[test]
+    fn column_sort_policy_orders_listings_unless_the_request_sorts() {
+        let done = parse_config_line("done: Done sort=due dir=DESC").unwrap();
+        assert_eq!((done.sort.as_deref(), done.sort_dir.as_deref()), (Some("due"), Some("desc")));
+        let cfg = BoardConfig { columns: vec![column("todo"), done.clone()], ..Default::default() };
+        assert!(render_config(&cfg).contains("done: Done sort=due dir=desc\n"));
+        assert!(validate_columns(&cfg.columns, DEFAULT_MAX_COLUMNS).is_ok());
+        let bad = parse_config_line("todo: Todo sort=prio").unwrap();
+        assert!(validate_columns(&[bad], DEFAULT_MAX_COLUMNS).unwrap_err().contains("Invalid sort for column todo: prio"));
+        let bad = parse_config_line("todo: Todo sort=due dir=down").unwrap();
+        assert!(validate_columns(&[bad], DEFAULT_MAX_COLUMNS).is_err());
+
+        let mut a = task("a", "done", "2026-01-03T00:00:00Z");
+        a.due = Some("2026-02-01".to_string());
+        let mut b = task("b", "done", "2026-01-02T00:00:00Z");
+        b.due = Some("2026-03-01".to_string());
+        let mut c = task("c", "done", "2026-01-01T00:00:00Z");
+        c.extra.insert("severity".to_string(), serde_json::json!(1));
+        let ids = |page: &Page, column: &BoardColumn| {
+            let mut tasks = vec![c.clone(), a.clone(), b.clone()];
+            let mut meta = column_meta(column, 3, 3);
+            paginate(&mut tasks, column, page, &[], &[], &mut None, &mut meta);
+            tasks.into_iter().map(|t| t.id).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&Page::default(), &done), vec!["b", "a", "c"]);
+        assert_eq!(ids(&Page::default(), &column("todo")), vec!["a", "b", "c"]);
+        let by_field = parse_page(&HashMap::from([("sort".to_string(), "field.severity".to_string())])).unwrap();
+        assert_eq!(ids(&by_field, &done), vec!["c", "a", "b"]);
+    }
+
+    #[test]
+    fn http_task_lifecycle_end_to_end() {
+        let server = test_support::TestServer::start("lifecycle");
+        let created = server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "Write docs", "status": "backlog" })));
+        assert_eq!(created.status, 201, "{}", created.body);
+        let id = created.json()["id"].as_str().unwrap().to_string();
+        assert_eq!(id, "write-docs");
+
+        let listed = server.get("/api/tasks").json();
+        assert_eq!(listed["folders"]["backlog"][0]["title"], "Write docs");
+
+        let renamed = server.request("PUT", &format!("/api/tasks/{}", id), Some(serde_json::json!({ "title": "Write the docs" })));
+        assert_eq!(renamed.status, 200, "{}", renamed.body);
+        // Renaming re-slugs the id; the old id keeps resolving through an alias.
+        assert_eq!(renamed.json()["id"], "write-the-docs");
+        let old = server.get(&format!("/api/tasks/{}", id));
+        assert_eq!((old.status, old.header("Kanban-Canonical-Id")), (200, Some("write-the-docs")));
+        assert_eq!(old.json()["title"], "Write the docs");
+        let old_id = id;
+        let id = "write-the-docs".to_string();
+
+        let moved = server.request("POST", &format!("/api/tasks/{}/move", id), Some(serde_json::json!({ "folder": "planned" })));
+        assert_eq!(moved.status, 200, "{}", moved.body);
+        assert_eq!(server.get("/api/tasks").json()["folders"]["planned"][0]["id"], id.as_str());
+
+        fs::write(server.root.join("done").join(format!("{}.md", id)), "title: Stray copy\n").unwrap();
+        let conflict = server.request("POST", &format!("/api/tasks/{}/move", id), Some(serde_json::json!({ "folder": "done" })));
+        assert_eq!(conflict.status, 409, "{}", conflict.body);
+        fs::remove_file(server.root.join("done").join(format!("{}.md", id))).unwrap();
+
+        let moved_back = server.request("POST", &format!("/api/tasks/{}/move", old_id), Some(serde_json::json!({ "folder": "backlog" })));
+        assert_eq!((moved_back.status, moved_back.header("Kanban-Canonical-Id")), (200, Some(id.as_str())));
+        assert!(server.get(&format!("/api/tasks/{}", id)).header("Kanban-Canonical-Id").is_none());
+
+        assert_eq!(server.request("DELETE", &format!("/api/tasks/{}", id), None).status, 204);
+        assert_eq!(server.get(&format!("/api/tasks/{}", old_id)).status, 404);
+        assert!(!server.root.join(ALIASES_FILE).exists());
+        let gone = server.request("PUT", &format!("/api/tasks/{}", id), Some(serde_json::json!({ "title": "Again" })));
+        assert_eq!((gone.status, gone.json()["error"].as_str()), (404, Some("task_not_found")));
+        assert_eq!(server.request("DELETE", &format!("/api/tasks/{}", id), None).status, 404);
+    }
+
+    #[test]
+    fn http_board_validation_and_static_files() {
+        let server = test_support::TestServer::start("board");
+        let put_board = |columns: serde_json::Value| server.request("PUT", "/api/board", Some(serde_json::json!({ "columns": columns })));
+        assert_eq!(put_board(serde_json::json!([])).status, 400);
+        let duplicate = put_board(serde_json::json!([{ "id": "todo", "title": "A" }, { "id": "todo", "title": "B" }]));
+        assert_eq!(duplicate.status, 400);
+        assert!(duplicate.json()["error"].as_str().unwrap().contains("Duplicate column id"));
+        assert_eq!(put_board(serde_json::json!([{ "id": "Bad Id", "title": "A" }])).status, 400);
+        assert_eq!(server.request("PUT", "/api/board", Some(serde_json::json!({ "columns": "todo" }))).status, 400);
+        let columns = server.get("/api/board").json()["board"]["columns"].clone();
+        let config = fs::read_to_string(config_path(&server.root)).unwrap();
+        let injected = serde_json::json!({ "columns": columns, "statuses": ["ok", "x\nevil: Evil"] });
+        assert_eq!(server.request("PUT", "/api/board", Some(injected)).status, 400);
+        for key in ["a\nevil: Evil", "creator,evil", "nope"] {
+            let injected = serde_json::json!({ "columns": columns, "private_fields": [key] });
+            assert_eq!(server.request("PUT", "/api/board", Some(injected)).status, 400, "{:?}", key);
+        }
+        let injected = serde_json::json!({ "columns": columns, "slug_stopwords_extra": ["the\nevil2: Evil"] });
+        assert_eq!(server.request("PUT", "/api/board", Some(injected)).status, 400);
+        assert_eq!(fs::read_to_string(config_path(&server.root)).unwrap(), config);
+        assert!(!server.root.join("evil").exists());
+        let board = server.get("/api/board").json();
+        assert_eq!(board["board"]["columns"].as_array().unwrap().len(), 4);
+
+        let index = server.get("/");
+        assert_eq!(index.status, 200);
+        assert!(index.header("Content-Type").is_some_and(|v| v.starts_with("text/html")));
+        assert_eq!(server.get("/app.js").status, 200);
+        assert_eq!(server.get("/missing.js").status, 404);
+        assert_eq!(server.get("/nested/app.js").status, 404);
+    }
+
+    #[test]
+    fn http_task_listing_counts_columns_for_the_active_filter() {
+        let server = test_support::TestServer::start("column-counts");
+        let mut columns = server.get("/api/board").json()["board"]["columns"].clone();
+        columns[2]["wip_limit"] = serde_json::json!(1);
+        assert_eq!(server.request("PUT", "/api/board", Some(serde_json::json!({ "columns": columns }))).status, 200);
+        for (title, tags, status) in [("Login form", vec!["ui"], "in_progress"), ("Schema", vec![], "in_progress"), ("Icons", vec!["ui"], "backlog")] {
+            let body = serde_json::json!({ "title": title, "tags": tags, "status": status });
+            assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        }
+
+        let listed = server.get("/api/tasks?tag=UI").json();
+        assert_eq!(listed["filter"], serde_json::json!({ "tag": "UI" }));
+        assert_eq!(listed["filtered"], true);
+        let doing = &listed["columns"][2];
+        assert_eq!(doing["id"], "in_progress");
+        assert_eq!((doing["count"].clone(), doing["total"].clone(), doing["hidden"].clone()), (1.into(), 2.into(), 1.into()));
+        // The WIP state compares the whole column, not what the filter shows.
+        assert_eq!((doing["wip_limit"].clone(), doing["over_limit"].clone()), (1.into(), true.into()));
+        assert_eq!(listed["folders"]["in_progress"].as_array().unwrap().len(), 1);
+        assert_eq!(listed["totals"], serde_json::json!({ "count": 2, "total": 3, "hidden": 1, "over_limit_columns": 1 }));
+
+        let all = server.get("/api/tasks").json();
+        assert_eq!(all["filtered"], false);
+        assert_eq!(all["totals"]["hidden"], 0);
+        assert_eq!(all["columns"][2]["count"], 2);
+    }
+
+    #[test]
+    fn http_column_defaults_fill_tags_and_assignee_on_entry() {
+        let server = test_support::TestServer::start("column-defaults");
+        let columns = |qa: serde_json::Value| {
+            let board = serde_json::json!({ "columns": [{ "id": "backlog", "title": "Backlog" }, qa] });
+            assert_eq!(server.request("PUT", "/api/board", Some(board)).status, 200);
+        };
+        columns(serde_json::json!({ "id": "qa", "title": "QA", "default_tags": ["needs-test"], "default_assignee": "rita" }));
+
+        let body = serde_json::json!({ "title": "Login form", "tags": ["ui", "needs-test"], "status": "qa" });
+        let created = server.request("POST", "/api/tasks", Some(body)).json();
+        assert_eq!((created["tags"].clone(), created["assigned_to"].clone()), (serde_json::json!(["ui", "needs-test"]), "rita".into()));
+        assert_eq!(created["applied_defaults"], serde_json::json!({ "assigned_to": "rita" }));
+
+        let body = serde_json::json!({ "title": "Schema", "status": "backlog", "assigned_to": "bob" });
+        assert!(server.request("POST", "/api/tasks", Some(body)).json().get("applied_defaults").is_none());
+        let moved = server.request("POST", "/api/tasks/schema/move", Some(serde_json::json!({ "folder": "qa" }))).json();
+        assert_eq!((moved["tags"].clone(), moved["assigned_to"].clone()), (serde_json::json!(["needs-test"]), "bob".into()));
+        assert_eq!(moved["applied_defaults"], serde_json::json!({ "tags": ["needs-test"] }));
+
+        // Dropping the defaults leaves the tasks that already got them alone.
+        columns(serde_json::json!({ "id": "qa", "title": "QA" }));
+        let schema = server.get("/api/tasks/schema").json();
+        assert_eq!((schema["tags"].clone(), schema["assigned_to"].clone()), (serde_json::json!(["needs-test"]), "bob".into()));
+    }
+
+    #[test]
+    fn http_task_colors_and_icons_are_validated_and_persisted() {
+        let server = test_support::TestServer::start("color-icon");
+        let body = serde_json::json!({ "title": "Launch", "color": "#FF7A18", "icon": "🚀🔥" });
+        let created = server.request("POST", "/api/tasks", Some(body));
+        assert_eq!(created.status, 201, "{}", created.body);
+        assert_eq!((created.json()["color"].clone(), created.json()["icon"].clone()), ("#ff7a18".into(), "🚀".into()));
+        let file = fs::read_to_string(server.root.join("backlog").join("launch.md")).unwrap();
+        assert!(file.contains("color: #ff7a18\nicon: 🚀\n"), "{}", file);
+
+        assert_eq!(server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "Red", "color": "red" }))).status, 400);
+        assert_eq!(server.request("PUT", "/api/tasks/launch", Some(serde_json::json!({ "color": "#12345" }))).status, 400);
+        let cleared = server.request("PUT", "/api/tasks/launch", Some(serde_json::json!({ "color": "" }))).json();
+        assert!(cleared.get("color").is_none(), "{}", cleared);
+        assert_eq!(cleared["icon"], "🚀");
+        let plain = server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "Plain" }))).json();
+        assert!(plain.get("color").is_none() && plain.get("icon").is_none());
+
+        fs::write(server.root.join(THEME_FILE), "color.palette.1=#ff7a18\ncolor.palette.2=#2F80ED\n").unwrap();
+        assert_eq!(server.get("/api/theme").json()["theme"]["palette"], serde_json::json!(["#ff7a18", "#2f80ed"]));
+    }
+
+    #[test]
+    fn http_search_ranks_title_hits_first_and_needs_a_query() {
+        let server = test_support::TestServer::start("search");
+        for (title, description) in [("Docs", "Explain the login flow"), ("Fix login crash", "The app crashes\nwhen login fails")] {
+            let body = serde_json::json!({ "title": title, "description": description });
+            assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        }
+
+        let found = server.get("/api/search?q=LOGIN").json();
+        assert_eq!(found["total"], 2);
+        let ids: Vec<_> = found["results"].as_array().unwrap().iter().map(|hit| hit["task"]["id"].clone()).collect();
+        assert_eq!(ids, ["fix-login-crash", "docs"]);
+        assert_eq!(found["results"][0]["column"], "backlog");
+        assert_eq!(found["results"][0]["snippet"], serde_json::json!({ "field": "title", "line": "Fix login crash", "matches": [[4, 9]] }));
+        assert_eq!(found["results"][1]["snippet"]["line"], "Explain the login flow");
+
+        // Every word has to match somewhere in the task.
+        let both = server.get("/api/search?q=login+fails").json();
+        assert_eq!((both["total"].clone(), both["results"][0]["task"]["id"].clone()), (1.into(), "fix-login-crash".into()));
+        let limited = server.get("/api/search?q=login&limit=1").json();
+        assert_eq!((limited["total"].clone(), limited["results"].as_array().unwrap().len()), (2.into(), 1));
+        assert_eq!(server.get("/api/search?q=%20").status, 400);
+        assert_eq!(server.get("/api/search").status, 400);
+    }
+
+    #[test]
+    fn http_date_filters_use_the_timezone_and_count_undated_tasks() {
+        let server = test_support::TestServer::start_with("date-filters", &["--timezone=+02:00"]);
+        let backlog = server.root.join("backlog");
+        fs::write(backlog.join("old.md"), "title: Old\ncreated_at: 2026-01-01T10:00:00Z\nupdated_at: 2026-01-02T10:00:00Z\n").unwrap();
+        fs::write(backlog.join("late.md"), "title: Late\ncreated_at: 2026-01-10T23:30:00Z\nupdated_at: 2026-01-10T23:30:00Z\n").unwrap();
+        fs::write(backlog.join("bare.md"), "title: Bare\n").unwrap();
+        let listed = |query: &str| {
+            let listed = server.get(&format!("/api/tasks?{}", query)).json();
+            let ids: Vec<String> = listed["folders"]["backlog"].as_array().unwrap().iter().map(|t| t["id"].as_str().unwrap().to_string()).collect();
+            (ids, listed["excluded_missing_dates"].clone())
+        };
+
+        // 23:30 UTC on the 10th is already the 11th at +02:00.
+        assert_eq!(listed("created_after=2026-01-11"), (vec!["late".to_string()], 1.into()));
+        assert_eq!(listed("created_before=2026-01-10"), (vec!["old".to_string()], 1.into()));
+        assert_eq!(listed("created_after=2026-01-01T00:00:00Z&updated_before=2026-01-05").0, ["old"]);
+        assert_eq!(listed("").1, 0);
+        let filter = server.get("/api/tasks?created_after=2026-01-11").json()["filter"].clone();
+        assert_eq!(filter, serde_json::json!({ "created_after": "2026-01-11T00:00:00+02:00" }));
+
+        let bad = server.get("/api/tasks?updated_after=2026-13-01");
+        assert_eq!(bad.status, 400);
+        assert!(bad.body.contains("updated_after"), "{}", bad.body);
+        let searched = server.get("/api/search?q=late&updated_before=2026-01-05").json();
+        assert_eq!((searched["total"].clone(), searched["excluded_missing_dates"].clone()), (0.into(), 1.into()));
+        assert_eq!(server.get("/api/search?q=late&created_after=2026-01-11").json()["total"], 1);
+    }
+
+    #[test]
+    fn http_versions_are_capped_listed_and_restored() {
+        let server = test_support::TestServer::start_with("versions", &["--history-limit=2"]);
+        let body = serde_json::json!({ "title": "Spec", "description": "first" });
+        assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        for description in ["second", "third", "fourth"] {
+            std::thread::sleep(Duration::from_millis(5));
+            let update = server.request("PUT", "/api/tasks/spec", Some(serde_json::json!({ "description": description })));
+            assert_eq!(update.status, 200, "{}", update.body);
+        }
+
+        let versions = server.get("/api/tasks/spec/versions").json()["versions"].clone();
+        assert_eq!(versions.as_array().unwrap().len(), 2, "{}", versions);
+        let oldest = versions[1]["timestamp"].as_str().unwrap().to_string();
+        let raw = server.get(&format!("/api/tasks/spec/versions/{}", oldest));
+        assert!(raw.body.trim_end().ends_with("\n\nsecond"), "{}", raw.body);
+        assert_eq!(server.get("/api/tasks/spec/versions/20200101T000000000Z").status, 404);
+
+        std::thread::sleep(Duration::from_millis(5));
+        let restored = server.request("POST", &format!("/api/tasks/spec/versions/{}/restore", oldest), None);
+        assert_eq!(restored.status, 200, "{}", restored.body);
+        assert_eq!(server.get("/api/tasks/spec").json()["description"], "second");
+        // The restore saved the file it replaced, pushing the oldest version out.
+        let versions = server.get("/api/tasks/spec/versions").json()["versions"].clone();
+        let newest = versions[0]["timestamp"].as_str().unwrap();
+        assert!(server.get(&format!("/api/tasks/spec/versions/{}", newest)).body.trim_end().ends_with("\n\nfourth"));
+        assert!(server.get("/api/tasks").json()["folders"].get(HISTORY_DIR).is_none());
+    }
+
+    #[test]
+    fn http_undo_reverts_moves_and_deletes_and_refuses_reused_ids() {
+        let server = test_support::TestServer::start("undo");
+        let folder = |id: &str| server.get(&format!("/api/tasks/{}", id)).json()["folder"].clone();
+        assert_eq!(server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "Ship" }))).status, 201);
+        assert_eq!(server.request("POST", "/api/tasks/ship/move", Some(serde_json::json!({ "folder": "done" }))).status, 200);
+
+        let undone = server.request("POST", "/api/undo", None);
+        assert_eq!(undone.status, 200, "{}", undone.body);
+        assert_eq!(undone.json()["undone"]["action"], "move");
+        assert_eq!(folder("ship"), "backlog");
+        // Undoing the undo moves the task forward again.
+        assert_eq!(server.request("POST", "/api/undo", None).json()["undone"]["action"], "undo move");
+        assert_eq!(folder("ship"), "done");
+        assert_eq!(server.request("DELETE", "/api/tasks/ship", None).status, 204);
+        assert_eq!(server.request("POST", "/api/undo", None).json()["undone"]["action"], "delete");
+        assert_eq!(folder("ship"), "done");
+
+        assert_eq!(server.request("DELETE", "/api/tasks/ship", None).status, 204);
+        let deleted = server.get("/api/undo").json()["entries"][0].clone();
+        assert_eq!(deleted["action"], "delete");
+        let body = serde_json::json!({ "title": "Ship", "status": "planned" });
+        assert_eq!(server.request("POST", "/api/tasks", Some(body)).json()["id"], "ship");
+        let reused = server.request("POST", &format!("/api/undo/{}", deleted["id"]), None);
+        assert_eq!(reused.status, 409, "{}", reused.body);
+        assert_eq!(folder("ship"), "planned");
+        assert_eq!(server.request("POST", "/api/undo/9999", None).status, 404);
+    }
+
+    #[test]
+    fn http_theme_changes_are_pushed_and_a_broken_edit_keeps_the_last_theme() {
+        let server = test_support::TestServer::start("theme-reload");
+        fs::write(server.root.join(THEME_FILE), "headline = Ops\n").unwrap();
+        let first = server.get("/api/theme");
+        let etag = first.header("ETag").unwrap().to_string();
+        assert_eq!(first.json()["theme"]["headline"], "Ops");
+        let cached = server.request_with_headers("GET", "/api/theme", None, &[("If-None-Match", &etag)]);
+        assert_eq!((cached.status, cached.body.as_str()), (304, ""));
+
+        let mut events = server.events();
+        fs::write(server.root.join(THEME_FILE), "headline = Ops Board\n").unwrap();
+        let changed = server.get("/api/theme");
+        assert_eq!(changed.json()["theme"]["headline"], "Ops Board");
+        assert_ne!(changed.header("ETag").unwrap(), etag);
+        let mut line = String::new();
+        let started = Instant::now();
+        while line != "event: theme_changed\n" && started.elapsed() < Duration::from_secs(10) {
+            line.clear();
+            let _ = events.read_line(&mut line);
+        }
+        line.clear();
+        events.read_line(&mut line).unwrap();
+        let event: serde_json::Value = serde_json::from_str(line.trim_start_matches("data: ")).unwrap();
+        assert_eq!(event["theme"]["headline"], "Ops Board");
+
+        fs::write(server.root.join(THEME_FILE), "headline = Ops Board\nthis line has no equals sign\n").unwrap();
+        assert_eq!(server.get("/api/theme").json()["theme"]["headline"], "Ops Board");
+    }
+
+    #[test]
+    fn http_pending_changes_and_dry_run_report_without_touching_the_board() {
+        let server = test_support::TestServer::start("pending-changes");
+        fs::create_dir_all(server.root.join("old")).unwrap();
+        fs::write(server.root.join("old").join("x.md"), "title: X\n").unwrap();
+        let pending = server.get("/api/board/pending-changes").json();
+        assert_eq!(pending["changes"], true);
+        assert_eq!(pending["plan"]["orphan_folders"], serde_json::json!([{ "folder": "old", "tasks": 1, "foreign_files": 0 }]));
+
+        let proposed = serde_json::json!({ "columns": [{ "id": "backlog", "title": "Backlog" }, { "id": "review", "title": "Review" }] });
+        let plan = server.request("POST", "/api/board/pending-changes", Some(proposed)).json()["plan"].clone();
+        assert_eq!(plan["create_folders"], serde_json::json!(["review"]));
+        assert_eq!(plan["remove_empty_folders"], serde_json::json!(["done", "in_progress", "planned"]));
+        assert!(!server.root.join("review").exists());
+        assert!(server.root.join("done").is_dir());
+
+        let root = test_support::TempDir::new("dry-run");
+        fs::create_dir_all(root.join("old")).unwrap();
+        fs::write(root.join("old").join("x.md"), "title: X\n").unwrap();
+        let defaults = BoardConfig { columns: vec![column("todo"), column("done")], ..Default::default() };
+        dry_run(&root, &defaults, &BoardOptions::default()).unwrap();
+        let mut left: Vec<_> = fs::read_dir(&root).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        left.sort();
+        assert_eq!(left, ["old"]);
+        let plan = plan_reconcile(&root, &defaults).unwrap();
+        assert_eq!((plan.create_folders, plan.orphan_folders.len()), (vec!["todo".to_string(), "done".to_string()], 1));
+    }
+
+    #[test]
+    fn startup_summary_counts_columns_and_fails_boards_that_cannot_be_served() {
+        let server = test_support::TestServer::start("summary");
+        assert_eq!(server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "Ship", "status": "planned" }))).status, 201);
+        fs::write(server.root.join("backlog").join("broken.md"), [0xff, 0xfe]).unwrap();
+        fs::create_dir_all(server.root.join("old")).unwrap();
+        fs::write(server.root.join("old").join("x.md"), "title: X\n").unwrap();
+
+        let scan = scan_board(&server.root, false, &BoardOptions::default());
+        assert!(scan.servable(), "{:?}", scan.errors);
+        assert!(scan.root.is_absolute());
+        let counts: Vec<_> = scan.columns.iter().map(|c| (c.id.as_str(), c.tasks)).collect();
+        assert_eq!(counts, [("backlog", 0), ("planned", 1), ("in_progress", 0), ("done", 0)]);
+        assert!(scan.warnings.iter().any(|w| w == "skipped unreadable file backlog/broken.md"), "{:?}", scan.warnings);
+        assert!(scan.warnings.iter().any(|w| w.starts_with("folder old/ has 1 task(s)")), "{:?}", scan.warnings);
+        assert_eq!(scan.theme_files, Vec::<&str>::new());
+
+        fs::remove_file(config_path(&server.root)).unwrap();
+        assert!(!scan_board(&server.root, false, &BoardOptions::default()).servable());
+        let file = server.root.join("old").join("x.md");
+        assert!(scan_board(&file, false, &BoardOptions::default()).errors[0].ends_with("is a file, not a directory"));
+        assert!(!scan_board(&server.root.join("nowhere"), false, &BoardOptions::default()).servable());
+    }
+
+    #[test]
+    fn http_column_listing_returns_one_column_with_its_metadata() {
+        let server = test_support::TestServer::start("column-listing");
+        for (title, tag) in [("Alpha", "ui"), ("Beta", "api"), ("Gamma", "ui")] {
+            let body = serde_json::json!({ "title": title, "tags": [tag], "status": "in_progress" });
+            assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        }
+        assert_eq!(server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "Elsewhere" }))).status, 201);
+
+        let listed = server.get("/api/columns/in_progress/tasks?tag=ui&fields=id,title").json();
+        assert_eq!(listed["tasks"], serde_json::json!([{ "id": "gamma", "title": "Gamma" }, { "id": "alpha", "title": "Alpha" }]));
+        let column = &listed["column"];
+        assert_eq!((column["title"].clone(), column["count"].clone(), column["total"].clone()), ("In Progress".into(), 2.into(), 3.into()));
+        assert_eq!(listed["filter"], serde_json::json!({ "tag": "ui" }));
+
+        let page = server.get("/api/columns/in_progress/tasks?limit=1&offset=1&fields=id").json();
+        assert_eq!((page["tasks"].clone(), page["column"]["returned"].clone()), (serde_json::json!([{ "id": "beta" }]), 1.into()));
+        assert_eq!(server.get("/api/columns/nope/tasks").status, 404);
+        assert_eq!(server.get("/api/columns/in_progress/tasks?sort=title").status, 400);
+    }
+
+    #[test]
+    fn http_vanished_root_answers_503_and_is_not_recreated() {
+        let server = test_support::TestServer::start("root-missing");
+        assert_eq!(server.get("/api/tasks").status, 200);
+        fs::remove_dir_all(&server.root).unwrap();
+        let missing = server.get("/api/tasks");
+        assert_eq!((missing.status, missing.json()["error"].as_str()), (503, Some(ROOT_MISSING)));
+        assert_eq!(server.get("/api/v1/board").json()["error"]["code"], "root_missing");
+        assert_eq!(server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "Lost" }))).status, 503);
+        assert!(!server.root.exists());
+
+        let dir = test_support::TempDir::new("root-kinds");
+        let file = dir.join("board.txt");
+        fs::write(&file, "").unwrap();
+        assert!(ensure_root(&file, true).unwrap_err().ends_with("is a file, not a directory"));
+        assert_eq!(ensure_root(&dir, false), Ok(false));
+        assert_eq!(ensure_root(&dir.join("new").join("board"), true), Ok(true));
+        assert!(dir.join("new").join("board").is_dir());
+    }
+
+    #[test]
+    fn http_default_column_takes_new_tasks_and_guards_its_column() {
+        let server = test_support::TestServer::start("default-column");
+        let columns = server.get("/api/board").json()["board"]["columns"].clone();
+        let put_board = |body: serde_json::Value| server.request("PUT", "/api/board", Some(body));
+        assert_eq!(put_board(serde_json::json!({ "columns": columns, "default_column": "nope" })).status, 400);
+        assert_eq!(put_board(serde_json::json!({ "columns": columns, "default_column": "planned" })).status, 200);
+        assert_eq!(server.get("/api/board").json()["board"]["default_column"], "planned");
+        assert!(fs::read_to_string(config_path(&server.root)).unwrap().contains("default_column = planned\n"));
+
+        let created = server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "Quick capture" }))).json();
+        assert_eq!(created["folder"], "planned");
+        let body = serde_json::json!({ "title": "Placed", "status": "done" });
+        assert_eq!(server.request("POST", "/api/tasks", Some(body)).json()["folder"], "done");
+
+        let without_planned: Vec<_> = columns.as_array().unwrap().iter().filter(|c| c["id"] != "planned").cloned().collect();
+        let refused = put_board(serde_json::json!({ "columns": without_planned }));
+        assert_eq!(refused.status, 400);
+        assert!(refused.body.contains("planned"), "{}", refused.body);
+        let moved = server.request("POST", "/api/tasks/quick-capture/move", Some(serde_json::json!({ "folder": "backlog" })));
+        assert_eq!(moved.status, 200);
+        assert_eq!(put_board(serde_json::json!({ "columns": without_planned, "default_column": "" })).status, 200);
+        assert_eq!(server.get("/api/board").json()["board"]["default_column"], serde_json::Value::Null);
+        let created = server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "Later" }))).json();
+        assert_eq!(created["folder"], "backlog");
+    }
+
+    #[test]
+    fn aliases_follow_rename_chains_and_prune_deleted_targets() {
+        let root = test_support::TempDir::new("aliases");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        let config = BoardConfig { columns: vec![column("todo")], ..Default::default() };
+        fs::write(root.join("todo").join("c.md"), "title: C\n").unwrap();
+        fs::write(root.join("todo").join("x.md"), "title: X\n").unwrap();
+        for (old, new) in [("a", "b"), ("b", "c"), ("x", "gone"), ("loop", "loop2"), ("loop2", "loop")] {
+            record_alias(&root, old, new).unwrap();
+        }
+        assert_eq!(resolve_alias(&root, "a", &config, &BoardOptions::default()).as_deref(), Some("c"));
+        assert_eq!(resolve_alias(&root, "c", &config, &BoardOptions::default()), None);
+        assert_eq!(resolve_alias(&root, "x", &config, &BoardOptions::default()), None, "a live task wins over its alias");
+        assert_eq!(resolve_alias(&root, "loop", &config, &BoardOptions::default()), None);
+
+        prune_aliases(&root, &config, &BoardOptions::default()).unwrap();
+        assert_eq!(fs::read_to_string(root.join(ALIASES_FILE)).unwrap(), "a -> c\nb -> c\n");
+        fs::remove_file(root.join("todo").join("c.md")).unwrap();
+        prune_aliases(&root, &config, &BoardOptions::default()).unwrap();
+        assert!(!root.join(ALIASES_FILE).exists());
+    }
+
+    #[test]
+    fn auto_assign_rotates_through_the_column_and_survives_restarts() {
+        let root = test_support::TempDir::new("auto-assign");
+        let triage = parse_config_line("triage: Triage auto_assign=alice,bob,carol").unwrap();
+        assert_eq!(triage.auto_assign, vec!["alice", "bob", "carol"]);
+        let cfg = BoardConfig { columns: vec![triage.clone()], ..Default::default() };
+        assert!(render_config(&cfg).contains("triage: Triage auto_assign=alice,bob,carol\n"));
+        let mut both = triage.clone();
+        both.default_assignee = Some("dave".to_string());
+        assert!(validate_columns(&[both], DEFAULT_MAX_COLUMNS).is_err());
+
+        let mut picks = Vec::new();
+        for i in 0..4 {
+            let mut task = task(&format!("t{}", i), "triage", "2026-01-01T00:00:00Z");
+            let mut applied = AppliedDefaults::default();
+            apply_auto_assign(&root, &triage, &mut task, &mut applied);
+            assert!(applied.auto_assigned);
+            assert_eq!(entry_activity(serde_json::json!({ "column": "triage" }), &applied)["auto_assigned"], task.assigned_to.as_str());
+            picks.push(task.assigned_to);
+        }
+        assert_eq!(picks, vec!["alice", "bob", "carol", "alice"]);
+
+        let mut assigned = task("x", "triage", "2026-01-01T00:00:00Z");
+        assigned.assigned_to = "erin".to_string();
+        let mut applied = AppliedDefaults::default();
+        apply_auto_assign(&root, &triage, &mut assigned, &mut applied);
+        assert_eq!((assigned.assigned_to.as_str(), applied.auto_assigned), ("erin", false));
+        assert_eq!(entry_activity(serde_json::json!({}), &applied), serde_json::json!({}));
+        // The cursor lives on disk, so the next pick after a restart is bob.
+        assert_eq!(fs::read_to_string(root.join(ROTATION_FILE)).unwrap(), r#"{"triage":1}"#);
+    }
+
+    #[test]
+    fn forecast_averages_weekly_outflow_and_flags_short_history() {
+        let today = Date::from_calendar_date(2026, Month::March, 28).unwrap();
+        let entry = |at: &str, action: &str, extra: &str| format!(r#"{{"at":"{}T10:00:00Z","action":"{}",{}}}"#, at, action, extra);
+        let moved = |at: &str| entry(at, "task_moved", r#""from":"backlog","to":"doing""#);
+        let log = [
+            entry("2026-02-20", "task_created", r#""column":"backlog""#),
+            moved("2026-03-08"),
+            moved("2026-03-09"),
+            moved("2026-03-14"),
+            entry("2026-03-15", "task_deleted", r#""column":"backlog""#),
+            entry("2026-03-16", "task_moved", r#""from":"doing","to":"backlog""#),
+            moved("2026-03-22"),
+            moved("2026-03-27"),
+        ]
+        .join("\n");
+        let forecast = throughput_forecast(Some(log.as_bytes()), "backlog", 6, today, UtcOffset::UTC, 4);
+        let outflow: Vec<usize> = forecast.weekly.iter().map(|w| w.outflow).collect();
+        assert_eq!(outflow, vec![0, 3, 1, 2]);
+        assert_eq!((forecast.weekly[0].from.as_str(), forecast.weekly[3].to.as_str()), ("2026-03-01", "2026-03-28"));
+        assert!(forecast.enough_data);
+        assert_eq!(forecast.average_per_week, Some(1.5));
+        let eta = forecast.eta.unwrap();
+        assert_eq!((eta.weeks, eta.date.as_str()), (4.0, "2026-04-25"));
+        assert!(forecast.optimistic.unwrap().weeks < 4.0);
+        assert!(forecast.pessimistic.unwrap().weeks > 4.0);
+
+        // A log that started ten days ago covers only one full week.
+        let short = throughput_forecast(Some(moved("2026-03-18").as_bytes()), "backlog", 6, today, UtcOffset::UTC, 4);
+        assert!(!short.enough_data && short.eta.is_none() && short.average_per_week.is_none());
+        assert!(short.reason.unwrap().starts_with("not enough data"));
+        assert_eq!(short.weekly.iter().filter(|w| w.logged).count(), 1);
+        assert!(!throughput_forecast(None::<&[u8]>, "backlog", 6, today, UtcOffset::UTC, 4).enough_data);
+    }
+
+    #[test]
+    fn burnup_counts_scope_and_completion_per_sprint_day() {
+        let config = BoardConfig { columns: vec![column("todo"), column("done")], ..Default::default() };
+        let dated = |id: &str, folder: &str, created: &str, completed: Option<&str>| Task {
+            created_at: Timestamp::read(&format!("{}T09:00:00Z", created)),
+            completed_at: completed.map(|day| Timestamp::read(&format!("{}T17:00:00Z", day))),
+            ..task(id, folder, "2026-03-01T00:00:00Z")
+        };
+        let mut folders = HashMap::from([
+            ("todo".to_string(), vec![dated("a", "todo", "2026-02-20", None), dated("c", "todo", "2026-03-03", None)]),
+            (
+                "done".to_string(),
+                vec![dated("b", "done", "2026-02-25", Some("2026-03-03")), dated("old", "done", "2026-02-01", Some("2026-02-27"))],
+            ),
+        ]);
+        let day = |d: u8| Date::from_calendar_date(2026, Month::March, d).unwrap();
+        let log = [r#"{"at":"2026-02-01T10:00:00Z","action":"task_created"}"#, r#"{"at":"2026-03-03T10:00:00Z","action":"task_deleted"}"#].join("\n");
+
+        let report = burnup(&config, &folders, (day(2), day(6)), Some(log.as_bytes()), day(4), UtcOffset::UTC);
+        let series: Vec<(Option<f64>, Option<f64>, Option<f64>)> = report.days.iter().map(|d| (d.scope, d.completed, d.added)).collect();
+        assert_eq!(
+            series,
+            vec![
+                (Some(2.0), Some(0.0), Some(0.0)),
+                (Some(3.0), Some(1.0), Some(1.0)),
+                (Some(3.0), Some(1.0), Some(0.0)),
+                (None, None, None),
+                (None, None, None),
+            ]
+        );
+        assert_eq!((report.unit, report.days[4].date.as_str()), ("tasks", "2026-03-06"));
+        assert!(!report.scope_reliable);
+        assert!(report.warnings[0].contains("1 task(s) left the board"));
+
+        folders.get_mut("todo").unwrap()[0].estimate = Some(3.0);
+        folders.get_mut("done").unwrap()[0].estimate = Some(2.0);
+        let points = burnup(&config, &folders, (day(2), day(6)), log.lines().next().map(str::as_bytes), day(4), UtcOffset::UTC);
+        assert_eq!((points.unit, points.unestimated), ("points", 1));
+        assert_eq!((points.days[1].scope, points.days[1].completed), (Some(5.0), Some(2.0)));
+        assert!(points.scope_reliable, "{:?}", points.warnings);
+        let unlogged = burnup(&config, &folders, (day(2), day(6)), None::<&[u8]>, day(4), UtcOffset::UTC);
+        assert!(!unlogged.scope_reliable && unlogged.warnings[0].contains("no activity log"));
+
+        let settings = |text: &str| {
+            let mut config = BoardConfig::default();
+            apply_board_settings(&mut config, text).map(|_| sprint_window(&config).unwrap())
+        };
+        assert_eq!(settings("sprint_start = 2026-03-02\nsprint_end = 2026-03-15\n"), Ok(Some((day(2), day(15)))));
+        assert!(settings("sprint_start = 2026-03-02\n").is_err());
+        assert!(settings("sprint_start = 2026-03-02\nsprint_end = 2026-03-01\n").is_err());
+        assert!(settings("sprint_start = march\nsprint_end = 2026-03-01\n").is_err());
+        assert_eq!(sprint_date(" 2026-3-2 "), Some("2026-03-02".to_string()));
+    }
+
+    #[test]
+    fn board_roots_resolve_to_one_canonical_path() {
+        let dir = test_support::TempDir::new("root");
+        let base = fs::canonicalize(&dir).unwrap();
+        fs::create_dir_all(base.join("boards").join("team")).unwrap();
+        let team = base.join("boards").join("team");
+        let shown = |path: &Path| path.display().to_string();
+        assert_eq!(canonical_root(&format!("{}/", shown(&team))).unwrap(), team);
+        assert_eq!(canonical_root(&format!("{}/./../team//", shown(&team))).unwrap(), team);
+        // Not created yet: the missing tail is kept below the resolved parent.
+        assert_eq!(canonical_root(&format!("{}/new/", shown(&team))).unwrap(), team.join("new"));
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&team, base.join("link")).unwrap();
+            assert_eq!(canonical_root(&shown(&base.join("link"))).unwrap(), team);
+            // `..` after a symlink leaves the link's target, as the shell's `cd -P` does.
+            assert_eq!(canonical_root(&format!("{}/../team", shown(&base.join("link")))).unwrap(), team);
+        }
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(canonical_root("kanban_data").unwrap(), fs::canonicalize(&cwd).unwrap().join("kanban_data"));
+        if let Some(home) = home_dir().and_then(|home| fs::canonicalize(home).ok()) {
+            assert_eq!(canonical_root("~").unwrap(), home);
+            assert_eq!(canonical_root("~/boards").unwrap(), home.join("boards"));
+            assert_eq!(unusual_root_reason(&home), Some("the home directory"));
+        }
+
+        assert_eq!(unusual_root_reason(&canonical_root("/").unwrap()), Some("the file system root"));
+        let exe_dir = fs::canonicalize(std::env::current_exe().unwrap().parent().unwrap()).unwrap();
+        assert_eq!(unusual_root_reason(&exe_dir), Some("the directory of the server binary"));
+        assert_eq!(unusual_root_reason(&team), None);
+    }
+
+    #[test]
+    fn wip_in_a_title_is_kept_and_wip_zero_freezes_a_column() {
+        let lab = parse_config_line("lab: WIP=experiments playground").unwrap();
+        assert_eq!((lab.title.as_str(), lab.wip_limit), ("WIP=experiments playground", None));
+        let lab = parse_config_line("lab: wip=experiments playground wip=2").unwrap();
+        assert_eq!((lab.title.as_str(), lab.wip_limit), ("wip=experiments playground", Some(2)));
+        let qa = parse_config_line("qa: QA wip=3 default_tags=needs-test").unwrap();
+        assert_eq!((qa.title.as_str(), qa.wip_limit, qa.default_tags.len()), ("QA", Some(3), 1));
+        let frozen = parse_config_line("legacy: Legacy wip=0").unwrap();
+        assert_eq!(frozen.wip_limit, Some(0));
+
+        let cfg = BoardConfig { columns: vec![column("todo"), lab, frozen], ..Default::default() };
+        let rendered = render_config(&cfg);
+        assert!(rendered.contains("lab: wip=experiments playground wip=2\n"));
+        assert!(rendered.contains("legacy: Legacy wip=0\n"));
+        let reparsed: Vec<BoardColumn> = rendered.lines().filter_map(parse_config_line).collect();
+        assert_eq!(
+            reparsed.iter().map(|c| (c.title.as_str(), c.wip_limit)).collect::<Vec<_>>(),
+            vec![("TODO", None), ("wip=experiments playground", Some(2)), ("Legacy", Some(0))]
+        );
+
+        let legacy = &cfg.columns[2];
+        let meta = column_meta(legacy, 4, 4);
+        assert!(meta.frozen && !meta.over_limit);
+        assert_eq!(meta.wip_limit, Some(0));
+        let request = serde_json::from_value::<NewTask>(serde_json::json!({ "title": "More", "folder": "legacy" })).unwrap();
+        let err = build_new_task(Path::new("/nonexistent"), &cfg, &BoardOptions::default(), request, &HashSet::new()).unwrap_err();
+        assert_eq!(err, "Column legacy is frozen (wip=0) and accepts no new tasks");
+
+        // Titles that would not read back as written are refused.
+        let titled = |title: &str| {
+            let column = BoardColumn { title: title.to_string(), ..column("release") };
+            validate_columns(&[column], DEFAULT_MAX_COLUMNS)
+        };
+        assert!(titled("Limits wip=2 review").is_ok());
+        assert!(titled("Release wip=0 locked=true").unwrap_err().contains("read as an attribute"));
+        assert!(titled("Release locked=true").is_err());
+        assert!(titled("Release\nevil: Evil").unwrap_err().contains("line breaks"));
+        let server = test_support::TestServer::start("column-titles");
+        let columns = serde_json::json!([{ "id": "todo", "title": "To do" }, { "id": "release", "title": "Release wip=0 locked=true" }]);
+        assert_eq!(server.request("PUT", "/api/board", Some(serde_json::json!({ "columns": columns }))).status, 400);
+        let columns = serde_json::json!([{ "id": "todo", "title": "To do\nevil: Evil" }]);
+        assert_eq!(server.request("PUT", "/api/board", Some(serde_json::json!({ "columns": columns }))).status, 400);
+        assert!(!server.root.join("evil").exists());
+    }
+
+    #[test]
+    fn http_handover_reassigns_open_tasks_once() {
+        let server = test_support::TestServer::start("handover");
+        for (title, column, assignee) in [("One", "backlog", "alice"), ("Two", "in_progress", "Alice"), ("Three", "done", "alice"), ("Four", "planned", "carol")] {
+            let body = serde_json::json!({ "title": title, "status": column, "assigned_to": assignee });
+            assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        }
+        let handover = |body: serde_json::Value| server.request("POST", "/api/users/alice/handover", Some(body));
+
+        let preview = handover(serde_json::json!({ "to": "bob", "dry_run": true }));
+        assert_eq!(preview.status, 200, "{}", preview.body);
+        let mut ids: Vec<String> = serde_json::from_value(preview.json()["tasks"].clone()).unwrap();
+        ids.sort();
+        assert_eq!(ids, ["one", "two"]);
+        assert_eq!(server.get("/api/tasks/one").json()["assigned_to"], "alice");
+
+        assert_eq!(handover(serde_json::json!({ "to": "bob", "columns": ["nope"] })).status, 400);
+        assert_eq!(handover(serde_json::json!({ "to": "ALICE" })).status, 400);
+        fs::write(server.root.join(USERS_FILE), "alice\nbob\n").unwrap();
+        let unknown = handover(serde_json::json!({ "to": "dave" }));
+        assert!(unknown.json()["error"].as_str().unwrap().contains("Unknown user"), "{}", unknown.body);
+
+        let done = handover(serde_json::json!({ "to": "bob", "columns": ["backlog", "done"] }));
+        assert_eq!(done.status, 200, "{}", done.body);
+        assert_eq!(done.json()["tasks"], serde_json::json!(["one", "three"]));
+        assert_eq!(server.get("/api/tasks/one").json()["assigned_to"], "bob");
+        assert_eq!(server.get("/api/tasks/two").json()["assigned_to"], "Alice");
+        let log = fs::read_to_string(server.root.join(ACTIVITY_FILE)).unwrap();
+        assert_eq!(log.matches("\"task_reassigned\"").count(), 2);
+        assert!(!log.contains("tasks_handed_over"));
+    }
+
+    #[test]
+    fn http_delete_respects_editing_locks() {
+        let server = test_support::TestServer::start("delete-lock");
+        assert_eq!(server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "One" }))).status, 201);
+        let lock = server.request_with_headers("POST", "/api/tasks/one/lock", None, &[("X-Kanban-User", "alice")]);
+        assert_eq!(lock.status, 200, "{}", lock.body);
+
+        let refused = server.request_with_headers("DELETE", "/api/tasks/one", None, &[("X-Kanban-User", "bob")]);
+        assert_eq!(refused.status, 423, "{}", refused.body);
+        assert_eq!(refused.json()["locked_by"], "alice");
+        assert_eq!(server.get("/api/tasks/one").status, 200);
+
+        let deleted = server.request_with_headers("DELETE", "/api/tasks/one", None, &[("X-Kanban-User", "alice")]);
+        assert_eq!(deleted.status, 204, "{}", deleted.body);
+        // A task recreated under the same id does not inherit the old lock.
+        assert_eq!(server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "One" }))).status, 201);
+        assert!(server.get("/api/tasks/one").json().get("locked_by").is_none());
+
+        server.request_with_headers("POST", "/api/tasks/one/lock", None, &[("X-Kanban-User", "alice")]);
+        let forced = server.request_with_headers("DELETE", "/api/tasks/one?override=true", None, &[("X-Kanban-User", "bob")]);
+        assert_eq!(forced.status, 204, "{}", forced.body);
+    }
+
+    #[test]
+    fn http_get_task_returns_json_or_raw_markdown() {
+        let server = test_support::TestServer::start("get-task");
+        let body = serde_json::json!({ "title": "One", "description": "Body text" });
+        assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+
+        let task = server.get("/api/tasks/one");
+        assert_eq!(task.status, 200, "{}", task.body);
+        assert_eq!(task.json()["title"], "One");
+        assert_eq!(task.json()["description"], "Body text");
+        assert_eq!(task.json()["days_in_column"], 0);
+        assert!(task.json().get("locked_by").is_none());
+
+        let lock = server.request_with_headers("POST", "/api/tasks/one/lock", None, &[("X-Kanban-User", "alice")]);
+        assert_eq!(lock.status, 200, "{}", lock.body);
+        let locked = server.get("/api/tasks/one").json();
+        assert_eq!(locked["locked_by"], "alice");
+        assert_eq!(locked["locked_until"], lock.json()["locked_until"]);
+        assert_eq!(locked["locked_until"], server.get("/api/tasks").json()["folders"]["backlog"][0]["locked_until"]);
+
+        let raw = server.get("/api/tasks/one?raw=true");
+        assert_eq!(raw.status, 200);
+        assert_eq!(raw.header("Content-Type"), Some("text/plain; charset=utf-8"));
+        let (path, _) = find_task_path(&server.root, "one", &refresh_config(&server.root).unwrap(), &BoardOptions::default()).unwrap();
+        assert_eq!(raw.body, fs::read_to_string(path).unwrap());
+
+        let missing = server.get("/api/tasks/nope?raw=true");
+        assert_eq!((missing.status, missing.json()), (404, serde_json::json!({ "error": "task_not_found" })));
+        assert_eq!(server.get("/api/tasks/..%2Fetc").status, 400);
+    }
+
+    #[test]
+    fn http_legacy_and_v1_routes_share_handlers_but_not_shapes() {
+        let server = test_support::TestServer::start("versions");
+        let created = server.request("POST", "/api/v1/tasks", Some(serde_json::json!({ "title": "Ship it", "status": "planned" })));
+        assert_eq!(created.status, 201, "{}", created.body);
+        assert!(created.header("Deprecation").is_none());
+
+        let legacy = server.get("/api/tasks?fields=id");
+        assert_eq!(legacy.header("Deprecation"), Some("true"));
+        assert_eq!(legacy.header("Sunset"), Some(LEGACY_API_SUNSET));
+        assert_eq!(legacy.header("Link"), Some("</api/v1/tasks?fields=id>; rel=\"successor-version\""));
+        let legacy = legacy.json();
+        assert_eq!(legacy["folders"]["planned"], serde_json::json!([{ "id": "ship-it" }]));
+        assert!(legacy["columns"][1].get("tasks").is_none());
+
+        let v1 = server.get("/api/v1/tasks?fields=id").json();
+        assert!(v1.get("folders").is_none());
+        assert_eq!(v1["columns"][1]["id"], "planned");
+        assert_eq!(v1["columns"][1]["tasks"], serde_json::json!([{ "id": "ship-it", "position": 0 }]));
+        assert_eq!(v1["columns"][0]["tasks"], serde_json::json!([]));
+
+        let missing = server.get("/api/tasks/nope");
+        assert_eq!((missing.status, missing.json()), (404, serde_json::json!({ "error": "task_not_found" })));
+        let missing = server.get("/api/v1/tasks/nope");
+        assert_eq!(missing.status, 404);
+        assert_eq!(missing.json(), serde_json::json!({ "error": { "code": "task_not_found", "message": "Task not found" } }));
+        let wrong = server.request("POST", "/api/v1/tasks/ship-it", None);
+        assert_eq!((wrong.status, wrong.header("Allow")), (405, Some("GET, PUT, DELETE")));
+        assert_eq!(wrong.json()["error"]["allowed"], serde_json::json!(["GET", "PUT", "DELETE"]));
+        let invalid = server.request("POST", "/api/v1/tasks", Some(serde_json::json!({ "title": 5 }))).json();
+        assert_eq!(invalid["error"]["code"], "bad_request");
+
+        let ui = server.get("/api/v1/ui").json();
+        assert_eq!(ui["api_versions"]["supported"], serde_json::json!(["v1"]));
+        assert_eq!(server.get("/api/v1").status, 404);
+        assert!(server.get("/").header("Deprecation").is_none());
+    }
+
+    #[test]
+    fn http_locked_columns_refuse_adding_and_removing_tasks() {
+        let server = test_support::TestServer::start_with("locked", &["--admin-token", "s3cret"]);
+        let admin = [("Authorization", "Bearer s3cret")];
+        let create = |column: &str, headers: &[(&str, &str)]| {
+            let body = serde_json::json!({ "title": format!("Task in {}", column), "status": column });
+            server.request_with_headers("POST", "/api/tasks", Some(body), headers)
+        };
+        assert_eq!(create("planned", &[]).status, 201);
+
+        let patched = server.request("PATCH", "/api/columns/done", Some(serde_json::json!({ "locked": true })));
+        assert_eq!(patched.status, 200, "{}", patched.body);
+        assert_eq!(patched.json()["column"]["locked"], true);
+        assert!(fs::read_to_string(server.root.join(CONFIG_FILE)).unwrap().contains("done: Done locked=true"));
+        let board = server.get("/api/board").json();
+        assert_eq!(board["board"]["columns"][3]["locked"], true);
+        let columns = server.get("/api/tasks").json()["columns"].clone();
+        assert_eq!((columns[2]["locked"].clone(), columns[3]["locked"].clone()), (serde_json::json!(false), serde_json::json!(true)));
+
+        let refused = create("done", &[]);
+        assert_eq!(refused.status, 423, "{}", refused.body);
+        assert!(refused.json()["error"].as_str().unwrap().contains("Column done is locked"));
+        let move_in = serde_json::json!({ "folder": "done" });
+        assert_eq!(server.request("POST", "/api/tasks/task-in-planned/move", Some(move_in.clone())).status, 423);
+        let wrong_token = [("Authorization", "Bearer nope")];
+        assert_eq!(server.request_with_headers("POST", "/api/tasks/task-in-planned/move", Some(move_in.clone()), &wrong_token).status, 423);
+        assert_eq!(server.request_with_headers("POST", "/api/tasks/task-in-planned/move", Some(move_in), &admin).status, 200);
+
+        let edited = server.request("PUT", "/api/tasks/task-in-planned", Some(serde_json::json!({ "description": "Release notes" })));
+        assert_eq!(edited.status, 200, "{}", edited.body);
+        let move_out = serde_json::json!({ "folder": "backlog" });
+        assert_eq!(server.request("POST", "/api/tasks/task-in-planned/move", Some(move_out)).status, 423);
+        let batch = server.request("POST", "/api/tasks/batch", Some(serde_json::json!([{ "title": "Late fix", "status": "done" }])));
+        assert!(batch.json()["results"][0]["error"].as_str().unwrap().contains("locked"), "{}", batch.body);
+
+        assert_eq!(server.request("PATCH", "/api/columns/done", Some(serde_json::json!({ "locked": false }))).status, 200);
+        assert_eq!(create("done", &[]).status, 201);
+        assert_eq!(server.request("PATCH", "/api/columns/nope", Some(serde_json::json!({ "locked": true }))).status, 404);
+    }
+
+    #[test]
+    fn share_tokens_are_long_random_hex() {
+        let (first, second) = (share_token().unwrap(), share_token().unwrap());
+        assert_eq!(first.len(), 64);
+        assert!(first.chars().all(|c| c.is_ascii_hexdigit()), "{}", first);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn http_shares_expose_only_the_filtered_tasks() {
+        let server = test_support::TestServer::start_with("shares", &["--admin-token", "tok"]);
+        let admin = [("Authorization", "Bearer tok")];
+        for (title, tag) in [("Acme logo", "client-acme"), ("Payroll", "internal")] {
+            let body = serde_json::json!({ "title": title, "tags": [tag], "creator": "alice", "status": "backlog" });
+            assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        }
+        let columns = server.get("/api/board").json()["board"]["columns"].clone();
+        let board = serde_json::json!({ "columns": columns, "private_fields": ["creator"] });
+        assert_eq!(server.request("PUT", "/api/board", Some(board)).status, 200);
+
+        let share = |body: serde_json::Value, headers: &[(&str, &str)]| server.request_with_headers("POST", "/api/shares", Some(body), headers);
+        let acme = serde_json::json!({ "filter": { "tag": "client-acme" }, "expires_in_days": 30 });
+        assert_eq!(share(acme.clone(), &[]).status, 401);
+        assert_eq!(share(serde_json::json!({ "filter": { "limit": "5" } }), &admin).status, 400);
+        assert_eq!(share(serde_json::json!({ "filter": {} }), &admin).status, 400);
+        let created = share(acme, &admin);
+        assert_eq!(created.status, 201, "{}", created.body);
+        let token = created.json()["token"].as_str().unwrap().to_string();
+        assert_eq!(token.len(), 2 * SHARE_TOKEN_BYTES);
+        assert_eq!(created.json()["url"], format!("/api/shared/{}/tasks", token));
+
+        let listed = server.get(&format!("/api/shared/{}/tasks", token)).json();
+        let backlog = listed["folders"]["backlog"].as_array().unwrap();
+        assert_eq!(backlog.len(), 1);
+        assert_eq!(backlog[0]["id"], "acme-logo");
+        assert!(backlog[0].get("creator").is_none());
+        assert_eq!((listed["columns"][0]["total"].clone(), listed["totals"]["total"].clone()), (serde_json::json!(1), serde_json::json!(1)));
+        assert_eq!(listed["board"]["private_fields"], serde_json::json!([]));
+        assert!(listed["board"]["modified_by"].is_null());
+
+        assert_eq!(server.get(&format!("/api/shared/{}/tasks/payroll", token)).status, 404);
+        assert!(server.get(&format!("/api/shared/{}/tasks/acme-logo", token)).json().get("creator").is_none());
+        assert_eq!(server.get(&format!("/api/shared/{}/search?q=payroll", token)).json()["total"], 0);
+        assert_eq!(server.get(&format!("/api/shared/{}/search?q=logo", token)).json()["total"], 1);
+        assert_eq!(server.get(&format!("/api/shared/{}/theme", token)).status, 200);
+        assert_eq!(server.get("/api/shared/0123456789abcdef0123456789abcdef/tasks").status, 404);
+
+        let mut shares = read_shares(&server.root);
+        let listed = server.request_with_headers("GET", "/api/shares", None, &admin).json();
+        assert_eq!(listed["shares"][0]["token"], token.as_str());
+        shares[0].expires_at = "2020-01-01T00:00:00Z".to_string();
+        write_shares(&server.root, &shares).unwrap();
+        assert_eq!(server.get(&format!("/api/shared/{}/tasks", token)).status, 410);
+        assert_eq!(server.request_with_headers("DELETE", &format!("/api/shares/{}", token), None, &admin).status, 204);
+        assert_eq!(server.get(&format!("/api/shared/{}/tasks", token)).status, 404);
+        assert_eq!(server.request_with_headers("DELETE", &format!("/api/shares/{}", token), None, &admin).status, 404);
+    }
+
+    #[test]
+    fn every_error_code_has_an_english_message() {
+        let english = MESSAGES.iter().find(|(lang, _)| *lang == DEFAULT_LANG).map(|(_, texts)| *texts).unwrap();
+        for code in ERROR_CODES {
+            assert!(english.iter().any(|(key, _)| *key == code), "no English message for {}", code);
+        }
+        for (lang, texts) in MESSAGES {
+            for (key, _) in texts {
+                assert!(english.iter().any(|(k, _)| k == key), "{} has {} but English does not", lang, key);
+            }
+        }
+    }
+
+    #[test]
+    fn request_language_prefers_query_then_accept_language() {
+        assert_eq!(resolve_request_lang(Some("de"), Some("en")), Some("de"));
+        assert_eq!(resolve_request_lang(Some("xx"), Some("fr;q=0.9, de-AT;q=0.8, en;q=0.5")), Some("de"));
+        assert_eq!(resolve_request_lang(None, Some("en;q=0.2, de")), Some("de"));
+        assert_eq!(resolve_request_lang(None, Some("de;q=0")), None);
+        assert_eq!(resolve_request_lang(None, None), None);
+    }
+
+    #[test]
+    fn http_errors_and_reports_follow_the_request_language() {
+        let server = test_support::TestServer::start("localized-errors");
+        let german = [("Accept-Language", "de-DE,de;q=0.9,en;q=0.8")];
+        let localized = server.request_with_headers("GET", "/api/v1/tasks/nope", None, &german);
+        assert_eq!(localized.status, 404);
+        assert_eq!(localized.json(), serde_json::json!({ "error": { "code": "task_not_found", "message": "Aufgabe nicht gefunden" } }));
+        // Legacy error bodies stay as they were.
+        let legacy = server.request_with_headers("GET", "/api/snapshots/none/diff", None, &german);
+        assert_eq!(legacy.json(), serde_json::json!({ "error": "snapshot not found" }));
+        let queried = server.get("/api/v1/snapshots/none/diff?lang=de").json();
+        assert_eq!(queried["error"]["code"], "snapshot_not_found");
+        assert_eq!(queried["error"]["message"], "Snapshot nicht gefunden");
+        let fallback = server.request_with_headers("GET", "/api/v1/snapshots/none/diff", None, &[("Accept-Language", "ja")]).json();
+        assert_eq!(fallback, serde_json::json!({ "error": { "code": "snapshot_not_found", "message": "snapshot not found" } }));
+
+        let report = server.request_with_headers("GET", "/api/export.md", None, &[("Accept-Language", "de")]);
+        assert!(report.body.contains("_Keine Aufgaben._"), "{}", report.body);
+        assert!(!server.get("/api/export.md").body.contains("Keine"));
+    }
+
+    #[test]
+    fn http_board_and_api_flags_apply_to_the_started_server() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+        let dir = test_support::TempDir::new("flags");
+        let script = dir.join("hook.sh");
+        let out = dir.join("out.txt");
+        fs::write(&script, format!("#!/bin/sh\necho \"$KANBAN_EVENT $KANBAN_TASK_ID\" >> {}\n", out.display())).unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let hook = script.to_str().unwrap();
+        let flags = ["--strict-api", "--base-path", "/kanban", "--max-columns=4", "--lang=de", "--follow-symlinks", "--hook", hook];
+        let server = test_support::TestServer::start_with("flags", &flags);
+
+        assert_eq!(server.get("/api/ui").status, 404);
+        let ui = server.get("/kanban/api/ui").json();
+        assert_eq!(ui["limits"]["max_columns"], 4);
+        let listing = server.get("/kanban/api/tasks");
+        assert_eq!(listing.header("Link"), Some("</kanban/api/v1/tasks>; rel=\"successor-version\""));
+        let missing = server.get("/kanban/api/v1/tasks/nope").json();
+        assert_eq!(missing["error"]["message"], "Aufgabe nicht gefunden");
+
+        let unknown = server.request("POST", "/kanban/api/tasks", Some(serde_json::json!({ "title": "Ship", "colour": "red" })));
+        assert_eq!(unknown.status, 400);
+        let created = server.request("POST", "/kanban/api/tasks", Some(serde_json::json!({ "title": "Ship" })));
+        assert_eq!(created.status, 201, "{}", created.body);
+        let mut logged = String::new();
+        for _ in 0..100 {
+            logged = fs::read_to_string(&out).unwrap_or_default();
+            if !logged.is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(logged, "create ship\n");
+
+        fs::write(dir.join("far.md"), "title: Far\n\nnotes").unwrap();
+        symlink(dir.join("far.md"), server.root.join("backlog").join("far.md")).unwrap();
+        assert_eq!(server.get("/kanban/api/tasks/far").status, 200);
+
+        let columns: Vec<_> = (1..=5).map(|n| serde_json::json!({ "id": format!("c{}", n), "title": format!("C{}", n) })).collect();
+        let too_many = server.request("PUT", "/kanban/api/board", Some(serde_json::json!({ "columns": columns })));
+        assert_eq!(too_many.status, 400);
+        assert!(too_many.body.contains("maximum of 4"), "{}", too_many.body);
+    }
+
+    #[test]
+    fn http_watch_reports_one_event_for_outside_edits_only() {
+        let server = test_support::TestServer::start_with("watch", &["--watch", "--watch-interval-ms=20"]);
+        let mut events = server.events();
+        let csv: String = (1..=500).map(|n| format!("Imported {}\n", n)).collect();
+        let imported = server.request_raw("POST", "/api/import/csv?map=title:1", &csv, &[("Content-Type", "text/csv")]);
+        assert_eq!(imported.status, 201, "{}", imported.body);
+        let column = read_config(&server.root).unwrap().columns[0].id.clone();
+        fs::write(server.root.join(&column).join("by-hand.md"), "title: By hand\n").unwrap();
+
+        // Events arrive in order: a repeated report of by-hand.md would come
+        // before the one for the sentinel written after the first report.
+        let mut reported = Vec::new();
+        let started = Instant::now();
+        let mut line = String::new();
+        while started.elapsed() < Duration::from_secs(30) && reported.len() < 2 {
+            line.clear();
+            if events.read_line(&mut line).is_err() || line != "event: files_changed\n" {
+                continue;
+            }
+            line.clear();
+            events.read_line(&mut line).unwrap();
+            reported.push(serde_json::from_str::<serde_json::Value>(line.trim_start_matches("data: ")).unwrap());
+            if reported.len() == 1 {
+                fs::write(server.root.join(&column).join("sentinel.md"), "title: Sentinel\n").unwrap();
+            }
+        }
+        assert_eq!(
+            reported,
+            vec![
+                serde_json::json!({ "paths": [format!("{}/by-hand.md", column)] }),
+                serde_json::json!({ "paths": [format!("{}/sentinel.md", column)] }),
+            ]
+        );
+    }
+
+    #[test]
+    fn http_webhook_deliveries_are_batched_logged_and_redelivered() {
+        use std::net::TcpListener;
+        use std::sync::mpsc;
+        // A receiver that is down for the first delivery and back for the
+        // redelivery.
+        let receiver = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", receiver.local_addr().unwrap());
+        let (sent, received) = mpsc::channel();
+        std::thread::spawn(move || {
+            for (stream, status) in receiver.incoming().zip(["503 Service Unavailable", "204 No Content"]) {
+                let mut stream = stream.unwrap();
+                let mut reader = io::BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((_, value)) = line.to_ascii_lowercase().split_once("content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                stream.write_all(format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status).as_bytes()).unwrap();
+                sent.send(serde_json::from_slice::<serde_json::Value>(&body).unwrap()).unwrap();
+            }
+        });
+        let server = test_support::TestServer::start_with("webhook", &["--webhook", &url, "--webhook-batch-window-ms=500"]);
+        let subscribed = server.request("POST", "/api/subscriptions", Some(serde_json::json!({ "user": "bob" })));
+        assert_eq!(subscribed.status, 201, "{}", subscribed.body);
+        for title in ["One", "Two", "Three"] {
+            assert_eq!(server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": title }))).status, 201);
+        }
+        let first = received.recv_timeout(Duration::from_secs(10)).unwrap();
+        let events: Vec<&str> = first["events"].as_array().unwrap().iter().map(|e| e["task"].as_str().unwrap()).collect();
+        assert_eq!(events, ["one", "two", "three"]);
+        assert_eq!(first["events"][0]["event"], "create");
+        // The same entry the hook gets, matched subscriptions included.
+        assert_eq!(first["events"][0]["subscriptions"][0]["user"], "bob");
+
+        let mut failed = serde_json::Value::Null;
+        for _ in 0..50 {
+            failed = server.get("/api/webhooks/deliveries?status=failed").json();
+            if !failed["deliveries"].as_array().unwrap().is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let delivery = &failed["deliveries"][0];
+        assert_eq!((delivery["id"].clone(), delivery["attempts"].clone()), (first["delivery"].clone(), serde_json::json!(1)));
+        assert_eq!(delivery["error"], "HTTP 503");
+        assert!(delivery["next_attempt_at"].is_string());
+        assert_eq!(server.get("/api/webhooks/deliveries?status=lost").status, 400);
+
+        let id = delivery["id"].as_str().unwrap();
+        let redelivered = server.request("POST", &format!("/api/webhooks/deliveries/{}/redeliver", id), None).json();
+        assert_eq!((redelivered["status"].as_str(), redelivered["attempts"].as_u64()), (Some("delivered"), Some(2)));
+        assert_eq!(received.recv_timeout(Duration::from_secs(10)).unwrap()["delivery"], id);
+        assert!(server.get("/api/webhooks/deliveries?status=failed").json()["deliveries"].as_array().unwrap().is_empty());
+        assert_eq!(server.request("POST", "/api/webhooks/deliveries/nope/redeliver", None).status, 404);
+        assert!(fs::read_to_string(server.root.join(WEBHOOK_LOG_FILE)).unwrap().contains("\"delivered\""));
+    }
+
+    #[test]
+    fn http_subscriptions_feed_only_matching_activity() {
+        let server = test_support::TestServer::start("subscriptions");
+        let subscribe = |body: serde_json::Value| server.request("POST", "/api/subscriptions", Some(body));
+        assert_eq!(subscribe(serde_json::json!({ "user": "alice", "columns": ["blocked"] })).status, 400);
+        assert_eq!(subscribe(serde_json::json!({ "user": "alice", "events": ["task_renamed"] })).status, 400);
+        let created = subscribe(serde_json::json!({ "user": "alice", "columns": ["done"], "events": ["task_moved", "task_created"] }));
+        assert_eq!(created.status, 201, "{}", created.body);
+        let id = created.json()["id"].as_str().unwrap().to_string();
+        assert_eq!(subscribe(serde_json::json!({ "user": "bob" })).status, 201);
+
+        let listed = server.get("/api/subscriptions?user=Alice").json();
+        assert_eq!(listed["subscriptions"].as_array().unwrap().len(), 1);
+        assert_eq!(listed["subscriptions"][0]["columns"], serde_json::json!(["done"]));
+
+        for title in ["Ship", "Other"] {
+            assert_eq!(server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": title, "status": "backlog" }))).status, 201);
+        }
+        assert_eq!(server.request("POST", "/api/tasks/ship/move", Some(serde_json::json!({ "folder": "done" }))).status, 200);
+        assert_eq!(server.request("POST", "/api/tasks/other/move", Some(serde_json::json!({ "folder": "in_progress" }))).status, 200);
+
+        let feed = server.get("/api/users/alice/feed.atom");
+        assert_eq!(feed.header("Content-Type"), Some("application/atom+xml; charset=utf-8"));
+        assert_eq!(feed.body.matches("<entry>").count(), 1, "{}", feed.body);
+        assert!(feed.body.contains("<title>ship moved from Backlog to Done</title>"), "{}", feed.body);
+        assert_eq!(server.get("/api/users/bob/feed.atom").body.matches("<entry>").count(), 4);
+
+        assert_eq!(server.request("DELETE", &format!("/api/subscriptions/{}", id), None).status, 204);
+        assert_eq!(server.request("DELETE", &format!("/api/subscriptions/{}", id), None).status, 404);
+        assert!(!server.get("/api/users/alice/feed.atom").body.contains("<entry>"));
+    }
+
+    #[test]
+    fn http_new_mentions_are_logged_once_per_user() {
+        let server = test_support::TestServer::start("mentions");
+        let created = server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "Ship", "description": "ask @alice" })));
+        assert_eq!(created.status, 201, "{}", created.body);
+        let edit = |description: &str| server.request("PUT", "/api/tasks/ship", Some(serde_json::json!({ "description": description }))).status;
+        assert_eq!(edit("ask @alice and @bob"), 200);
+        assert_eq!(edit("ask @bob"), 200);
+
+        let mentioned: Vec<String> = fs::read_to_string(server.root.join(ACTIVITY_FILE))
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .filter(|entry| entry["action"] == "task_mentioned")
+            .map(|entry| format!("{}:{}", entry["task"].as_str().unwrap(), entry["user"].as_str().unwrap()))
+            .collect();
+        assert_eq!(mentioned, ["ship:alice", "ship:bob"]);
+        assert_eq!(hook_event("task_mentioned"), Some("mention"));
+    }
+
+    #[test]
+    fn settle_id_applies_each_collision_strategy() {
+        let mut cfg = BoardConfig { columns: vec![column("todo")], ..Default::default() };
+        let taken: HashSet<String> = ["fix-login".to_string()].into();
+        let request = |id: Option<&str>, collision| NewTask {
+            id: id.map(str::to_string),
+            title: "Fix login".to_string(),
+            collision,
+            ..Default::default()
+        };
+        let settle = |cfg: &BoardConfig, task: NewTask, fallback| {
+            settle_id(cfg, &BoardOptions::default(), &task, fallback, &taken).map(|s| (s.id, s.strategy, s.outcome))
+        };
+        assert_eq!(settle(&cfg, request(None, None), None), Ok(("fix-login-2".to_string(), Collision::Suffix, CollisionOutcome::Suffixed)));
+        assert_eq!(settle(&cfg, request(Some("fix-login"), None), None), Err((409, "fix-login".to_string())));
+        assert_eq!(settle(&cfg, request(Some("fresh"), None), None), Ok(("fresh".to_string(), Collision::Error, CollisionOutcome::Created)));
+        assert_eq!(
+            settle(&cfg, request(None, Some(Collision::Overwrite)), Some(Collision::Skip)),
+            Ok(("fix-login".to_string(), Collision::Overwrite, CollisionOutcome::Overwritten))
+        );
+        assert_eq!(settle(&cfg, request(None, None), Some(Collision::Skip)), Ok(("fix-login".to_string(), Collision::Skip, CollisionOutcome::Skipped)));
+        cfg.collision = Some(Collision::Error);
+        assert_eq!(settle(&cfg, request(None, None), None), Err((409, "fix-login".to_string())));
+        assert_eq!(settle(&cfg, request(Some("Bad Id"), None), None).unwrap_err().0, 400);
+
+        apply_board_settings(&mut cfg, "collision = overwrite\n").unwrap();
+        assert_eq!(cfg.collision, Some(Collision::Overwrite));
+        assert!(render_config(&cfg).contains("collision = overwrite\n"));
+        assert!(apply_board_settings(&mut cfg, "collision = rename\n").is_err());
+    }
+
+    #[test]
+    fn http_collision_strategies_on_create_batch_and_import() {
+        let server = test_support::TestServer::start("collisions");
+        let create = |body: serde_json::Value| server.request("POST", "/api/tasks", Some(body));
+        assert_eq!(create(serde_json::json!({ "title": "Fix login", "status": "backlog", "description": "old" })).status, 201);
+
+        let taken = create(serde_json::json!({ "id": "fix-login", "title": "Other" }));
+        assert_eq!(taken.status, 409);
+        assert_eq!(taken.json()["id"], "fix-login");
+        let taken = server.request("POST", "/api/v1/tasks", Some(serde_json::json!({ "id": "fix-login", "title": "Other" })));
+        assert_eq!((taken.json()["error"]["code"].as_str(), taken.json()["error"]["id"].as_str()), (Some("id_conflict"), Some("fix-login")));
+        assert_eq!(create(serde_json::json!({ "id": "fix-login", "title": "Other", "collision": "skip" })).status, 409);
+        assert_eq!(create(serde_json::json!({ "title": "Fix login" })).json()["id"], "fix-login-2");
+
+        let batch = |collision: &str, items: serde_json::Value| {
+            server.request("POST", &format!("/api/tasks/batch?collision={}", collision), Some(items))
+        };
+        let suffixed = batch("suffix", serde_json::json!([{ "title": "Fix login" }]));
+        assert_eq!(suffixed.status, 201, "{}", suffixed.body);
+        assert_eq!(suffixed.json()["results"][0]["task"]["id"], "fix-login-3");
+        assert_eq!((suffixed.json()["results"][0]["collision"].as_str(), suffixed.json()["results"][0]["outcome"].as_str()), (Some("suffix"), Some("suffixed")));
+
+        let refused = batch("error", serde_json::json!([{ "title": "Fix login" }, { "title": "New one" }]));
+        assert_eq!(refused.status, 207, "{}", refused.body);
+        assert_eq!(refused.json()["results"][0]["outcome"], "conflict");
+        assert_eq!(refused.json()["results"][0]["id"], "fix-login");
+        assert_eq!(refused.json()["results"][1]["outcome"], "created");
+
+        let skipped = batch("skip", serde_json::json!([{ "title": "Fix login" }]));
+        assert_eq!(skipped.status, 201, "{}", skipped.body);
+        assert_eq!((skipped.json()["created"].as_u64(), skipped.json()["skipped"].as_u64()), (Some(0), Some(1)));
+        assert_eq!(skipped.json()["results"][0]["outcome"], "skipped");
+        assert_eq!(batch("bogus", serde_json::json!([{ "title": "X" }])).status, 400);
+
+        let overwritten = batch("overwrite", serde_json::json!([{ "title": "Fix login", "status": "done", "description": "new" }, { "title": "Fix login" }]));
+        assert_eq!(overwritten.status, 207, "{}", overwritten.body);
+        assert_eq!(overwritten.json()["results"][0]["outcome"], "overwritten");
+        assert_eq!(overwritten.json()["results"][1]["outcome"], "conflict");
+        let task = server.get("/api/tasks/fix-login").json();
+        assert_eq!((task["folder"].as_str(), task["description"].as_str()), (Some("done"), Some("new")));
+        assert!(!server.root.join("backlog/fix-login.md").exists());
+        assert_eq!(fs::read_dir(history_dir(&server.root, "fix-login")).unwrap().count(), 1);
+        let log = fs::read_to_string(server.root.join(ACTIVITY_FILE)).unwrap();
+        assert!(log.contains("\"overwritten\":\"backlog\""), "{}", log);
+
+        assert_eq!(server.request("POST", "/api/import/csv?map=title:1&collision=rename", None).status, 400);
+    }
+
+    #[test]
+    fn csv_import_skips_or_overwrites_taken_ids() {
+        let root = test_support::TempDir::new("csv-collision");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        let cfg = BoardConfig { columns: vec![column("todo")], ..Default::default() };
+        let base = CsvImportOptions { map: "title:1,description:2".to_string(), history_limit: 5, ..Default::default() };
+        assert_eq!(import_csv(&root, &cfg, &BoardOptions::default(), "Ship it,old\n", &base).unwrap().created, 1);
+
+        let skip = CsvImportOptions { collision: Some(Collision::Skip), ..base.clone() };
+        let report = import_csv(&root, &cfg, &BoardOptions::default(), "Ship it,new\nBrand new,\n", &skip).unwrap();
+        assert_eq!((report.created, report.skipped), (1, 1));
+        assert_eq!((report.results[0]["outcome"].as_str(), report.results[0]["id"].as_str()), (Some("skipped"), Some("ship-it")));
+
+        let overwrite = CsvImportOptions { collision: Some(Collision::Overwrite), ..base };
+        let report = import_csv(&root, &cfg, &BoardOptions::default(), "Ship it,new\n", &overwrite).unwrap();
+        assert_eq!(report.results[0]["outcome"], "overwritten");
+        assert_eq!(report.replaced[0].as_ref().map(|r| r.content.contains("old")), Some(true));
+        assert!(fs::read_to_string(root.join("todo/ship-it.md")).unwrap().contains("new"));
+        assert_eq!(fs::read_dir(history_dir(&root, "ship-it")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn http_board_favicon_and_manifest_fall_back_to_defaults() {
+        let server = test_support::TestServer::start("board-assets");
+        let icon = server.get("/favicon.ico");
+        assert_eq!(icon.status, 200);
+        assert_eq!(icon.header("Content-Type"), Some("image/svg+xml"));
+        assert_eq!(icon.body, FAVICON_SVG);
+        assert_eq!(icon.header("Cache-Control"), Some("public, max-age=300"));
+
+        fs::write(server.root.join(THEME_FILE), "headline = Ops Board\n").unwrap();
+        let manifest = server.get("/manifest.json");
+        assert_eq!(manifest.header("Content-Type"), Some("application/manifest+json"));
+        assert_eq!(manifest.json()["name"], "Ops Board");
+        assert_eq!(manifest.json()["icons"][0]["src"], "favicon.svg");
+
+        // The assets folder wins over the root and is never a column.
+        fs::create_dir_all(server.root.join(ASSETS_DIR)).unwrap();
+        fs::write(server.root.join("favicon.png"), "root png").unwrap();
+        fs::write(server.root.join(ASSETS_DIR).join("favicon.png"), "assets png").unwrap();
+        let icon = server.get("/favicon.ico");
+        assert_eq!((icon.header("Content-Type"), icon.body.as_str()), (Some("image/png"), "assets png"));
+        let etag = icon.header("ETag").unwrap().to_string();
+        let cached = server.request_with_headers("GET", "/favicon.ico", None, &[("If-None-Match", &etag)]);
+        assert_eq!((cached.status, cached.body.as_str()), (304, ""));
+        assert_eq!(server.get("/manifest.json").json()["icons"][0]["type"], "image/png");
+        let board = server.get("/api/board").json();
+        assert!(board["board"]["columns"].as_array().unwrap().iter().all(|c| c["id"] != ASSETS_DIR));
+
+        fs::write(server.root.join(ASSETS_DIR).join("manifest.json"), "{\"name\":\"Custom\"}").unwrap();
+        assert_eq!(server.get("/manifest.webmanifest").json(), serde_json::json!({"name": "Custom"}));
+
+        #[cfg(unix)]
+        {
+            let dir = test_support::TempDir::new("outside-icon");
+            let outside = dir.join("favicon.svg");
+            fs::write(&outside, "<svg>outside</svg>").unwrap();
+            std::os::unix::fs::symlink(&outside, server.root.join(ASSETS_DIR).join("favicon.svg")).unwrap();
+            assert_eq!(server.get("/favicon.svg").body, "assets png");
+        }
+    }
+
+    #[test]
+    fn max_age_accepts_days_and_hours_only() {
+        assert_eq!(parse_max_age("5d"), Ok(time::Duration::days(5)));
+        assert_eq!(parse_max_age("36h"), Ok(time::Duration::hours(36)));
+        for nonsense in ["5", "0d", "-1d", "+5d", "1.5d", "5w", "d", "", "99999d", "99999999999999999999h"] {
+            assert!(parse_max_age(nonsense).is_err(), "{}", nonsense);
+        }
+        let review = parse_config_line("review: Review wip=3 max_age=5D").unwrap();
+        assert_eq!(review.max_age.as_deref(), Some("5d"));
+        assert!(render_config(&BoardConfig { columns: vec![review], ..Default::default() }).contains("review: Review wip=3 max_age=5d\n"));
+        let mut bad = column("review");
+        bad.max_age = Some("soon".to_string());
+        assert!(validate_columns(&[bad], DEFAULT_MAX_COLUMNS).unwrap_err().contains("Invalid max_age for column review"));
+    }
+
+    #[test]
+    fn violations_cover_wip_age_and_locked_columns() {
+        let root = test_support::TempDir::new("violations");
+        let log = [
+            r#"{"at":"2024-05-01T09:00:00Z","action":"task_moved","task":"old","from":"todo","to":"review"}"#,
+            r#"{"at":"2024-05-01T09:00:00Z","action":"board_changed","columns":[{"change":"lock_changed","column":"done","locked":true}]}"#,
+        ];
+        fs::write(root.join(ACTIVITY_FILE), log.join("\n")).unwrap();
+        let mut review = column("review");
+        review.wip_limit = Some(1);
+        review.max_age = Some("5d".to_string());
+        let mut done = column("done");
+        done.locked = true;
+        let cfg = BoardConfig { columns: vec![review.clone(), done], ..Default::default() };
+        // `old` has no column_since; the log says it entered review on May 1.
+        let old = task("old", "review", "2024-05-09T00:00:00Z");
+        let mut fresh = task("fresh", "review", "2024-05-09T00:00:00Z");
+        fresh.column_since = Some(Timestamp::read("2024-05-09T00:00:00Z"));
+        let mut sneaked = task("sneaked", "done", "2024-05-03T00:00:00Z");
+        sneaked.column_since = Some(Timestamp::read("2024-05-03T00:00:00Z"));
+        let folders = HashMap::from([
+            ("review".to_string(), vec![old.clone(), fresh.clone()]),
+            ("done".to_string(), vec![sneaked]),
+        ]);
+        let now = parse_timestamp("2024-05-10T09:00:00Z").unwrap();
+        let violations = board_violations(&root, &cfg, &folders, now);
+        assert_eq!(
+            violations,
+            vec![
+                Violation::Wip { column: "review".into(), wip_limit: 1, count: 2, tasks: vec!["old".into(), "fresh".into()] },
+                Violation::MaxAge {
+                    column: "review".into(),
+                    max_age: "5d".into(),
+                    task: "old".into(),
+                    since: "2024-05-01T09:00:00Z".into(),
+                    age_hours: 216,
+                },
+                Violation::Locked {
+                    column: "done".into(),
+                    task: "sneaked".into(),
+                    since: "2024-05-03T00:00:00Z".into(),
+                    locked_at: "2024-05-01T09:00:00Z".into(),
+                },
+            ]
+        );
+        assert_eq!(over_age_tasks(&root, &review, &[old, fresh], now), vec!["old"]);
+
+        // Only crossings not seen by the previous scan are announced.
+        let mut seen = HashSet::new();
+        assert_eq!(new_age_violations(board_violations(&root, &cfg, &folders, now), &mut seen).len(), 1);
+        assert!(new_age_violations(board_violations(&root, &cfg, &folders, now), &mut seen).is_empty());
+    }
+
+    #[test]
+    fn http_violations_and_column_meta_flag_old_tasks() {
+        let server = test_support::TestServer::start("violations");
+        let mut board = server.get("/api/board").json()["board"].clone();
+        board["columns"][0]["max_age"] = serde_json::json!("36h");
+        let first = board["columns"][0]["id"].as_str().unwrap().to_string();
+        assert_eq!(server.request("PUT", "/api/board", Some(board.clone())).status, 200);
+        let id = server.request("POST", "/api/tasks", Some(serde_json::json!({"title": "Stale"}))).json()["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(server.get("/api/violations").json(), serde_json::json!({"count": 0, "violations": []}));
+
+        let path = server.root.join(&first).join(format!("{}.md", id));
+        let contents = fs::read_to_string(&path).unwrap();
+        let since = contents.lines().find(|l| l.starts_with("column_since:")).unwrap().to_string();
+        fs::write(&path, contents.replace(&since, "column_since: 2024-01-01T00:00:00Z")).unwrap();
+        let violations = server.get("/api/violations").json();
+        assert_eq!(violations["count"], 1);
+        assert_eq!(violations["violations"][0]["policy"], "max_age");
+        assert_eq!(violations["violations"][0]["task"], id.as_str());
+        let meta = &server.get("/api/tasks").json()["columns"][0];
+        assert_eq!((meta["max_age"].as_str(), meta["over_age"].clone()), (Some("36h"), serde_json::json!([id])));
+
+        board["columns"][0]["max_age"] = serde_json::json!("a while");
+        let refused = server.request("PUT", "/api/board", Some(board));
+        assert_eq!(refused.status, 400);
+        assert!(refused.json()["error"].as_str().unwrap().contains("max_age"));
+    }
+
+    #[test]
+    fn journal_survives_restarts_and_recover_ends_pending_entries() {
+        let root = test_support::TempDir::new("journal");
+        for folder in ["todo", "doing"] {
+            fs::create_dir_all(root.join(folder)).unwrap();
+        }
+        let cfg = BoardConfig { columns: vec![column("todo"), column("doing")], ..Default::default() };
+        let journal = open_journal(&root);
+        fs::write(task_path(&root, "todo", "a"), "title: A\n").unwrap();
+        journal_record(&journal, "create", None, Some(file_state("todo", "a", "title: A\n".to_string())));
+        let edit = || fs::write(task_path(&root, "todo", "a"), "title: A2\n");
+        let before = file_state("todo", "a", "title: A\n".to_string());
+        let (_, edit_id) = journaled(&journal, "update", Some(before), Some(file_state("todo", "a", "title: A2\n".to_string())), edit).unwrap();
+
+        // A move that crashed after the rename, before the header rewrite.
+        let before = file_state("todo", "a", "title: A2\n".to_string());
+        let after = file_state("doing", "a", "title: A2\nstatus: doing\n".to_string());
+        let crashed = journaled(&journal, "move", Some(before), Some(after), || {
+            fs::rename(task_path(&root, "todo", "a"), task_path(&root, "doing", "a"))?;
+            Err::<(), _>(io::Error::other("disk full"))
+        });
+        assert!(crashed.is_err());
+        // A delete that never got to remove its file.
+        fs::write(task_path(&root, "doing", "b"), "title: B\n").unwrap();
+        let gone = journaled(&journal, "delete", Some(file_state("doing", "b", "title: B\n".to_string())), None, || {
+            Err::<(), _>(io::Error::other("crash"))
+        });
+        assert!(gone.is_err());
+
+        let reopened = open_journal(&root);
+        let ids: Vec<u64> = reopened.entries.lock().unwrap().iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![edit_id - 1, edit_id]);
+        assert_eq!(reopened.next_id.load(Ordering::SeqCst), edit_id + 3);
+        let pending: Vec<JournalEntry> = read_journal(&root.join(JOURNAL_DIR))
+            .into_iter()
+            .filter(|(_, state)| *state == JournalState::Pending)
+            .map(|(entry, _)| entry)
+            .collect();
+        assert_eq!(classify_pending(&root, &pending[0]), Recovery::Partial);
+        assert_eq!(classify_pending(&root, &pending[1]), Recovery::NotStarted);
+
+        assert_eq!(recover(&root, false), 1);
+        assert_eq!(fs::read_to_string(task_path(&root, "doing", "a")).unwrap(), "title: A2\n");
+        assert_eq!(recover(&root, true), 0);
+        assert_eq!(fs::read_to_string(task_path(&root, "doing", "a")).unwrap(), "title: A2\nstatus: doing\n");
+        assert_eq!(recover(&root, false), 0);
+        assert!(task_path(&root, "doing", "b").exists());
+
+        // The finished move is the newest entry after a restart, and undo
+        // takes the task back.
+        let reopened = open_journal(&root);
+        let (undone, _) = undo_entry(&root, &cfg, &BoardOptions::default(), &reopened, None).unwrap();
+        assert_eq!(undone.action, "move");
+        assert_eq!(fs::read_to_string(task_path(&root, "todo", "a")).unwrap(), "title: A2\n");
+        assert!(!task_path(&root, "doing", "a").exists());
+
+        fs::write(task_path(&root, "todo", "a"), "edited by hand\n").unwrap();
+        let entry = JournalEntry {
+            id: 99,
+            at: now_iso(),
+            action: "update".to_string(),
+            before: Some(file_state("todo", "a", "title: A2\n".to_string())),
+            after: Some(file_state("todo", "a", "title: A3\n".to_string())),
+        };
+        assert_eq!(classify_pending(&root, &entry), Recovery::Conflict(task_path(&root, "todo", "a")));
+    }
+
+    #[test]
+    fn recover_finishes_a_title_change_cut_short() {
+        let root = test_support::TempDir::new("journal-rename");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        fs::create_dir_all(attachments_dir(&root, "old")).unwrap();
+        fs::write(attachments_dir(&root, "old").join("a.txt"), "x").unwrap();
+        fs::write(task_path(&root, "todo", "old"), "title: Old\n").unwrap();
+        let journal = open_journal(&root);
+        let before = file_state("todo", "old", "title: Old\n".to_string());
+        let after = file_state("todo", "new", "title: New\n".to_string());
+        let crashed = journaled(&journal, "update", Some(before), Some(after), || {
+            fs::rename(task_path(&root, "todo", "old"), task_path(&root, "todo", "new"))?;
+            Err::<(), _>(io::Error::other("crash"))
+        });
+        assert!(crashed.is_err());
+
+        assert_eq!(recover(&root, true), 0);
+        assert_eq!(fs::read_to_string(task_path(&root, "todo", "new")).unwrap(), "title: New\n");
+        assert!(!task_path(&root, "todo", "old").exists());
+        assert!(attachments_dir(&root, "new").join("a.txt").exists());
+        assert!(!attachments_dir(&root, "old").exists());
+        assert_eq!(read_aliases(&root).get("old").map(String::as_str), Some("new"));
+    }
+
+    #[test]
+    fn http_debug_journal_records_mutations_and_greps_one_task() {
+        let dir = test_support::TempDir::new("debug-journal");
+        let journal = dir.join("requests.jsonl");
+        let server = test_support::TestServer::start_with("debug-journal", &["--debug-journal", journal.to_str().unwrap()]);
+        fs::write(config_path(&server.root), "todo: To do\ndoing: Doing\n").unwrap();
+        for title in ["Ship it", "Other"] {
+            let body = serde_json::json!({ "title": title, "folder": "todo" });
+            assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        }
+        let admin = [("Authorization", "Bearer s3cret"), ("X-Kanban-User", "alice")];
+        let edited = server.request_with_headers("PUT", "/api/tasks/ship-it", Some(serde_json::json!({ "description": "Soon" })), &admin);
+        assert_eq!(edited.status, 200);
+        assert_eq!(server.request("POST", "/api/tasks/ship-it/move", Some(serde_json::json!({ "folder": "doing" }))).status, 200);
+        assert_eq!(server.get("/api/tasks").status, 200);
+
+        let lines: Vec<serde_json::Value> =
+            fs::read_to_string(&journal).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        let phases: Vec<(u64, &str)> = lines.iter().map(|line| (line["seq"].as_u64().unwrap(), line["phase"].as_str().unwrap())).collect();
+        assert_eq!(phases, [(1, "request"), (1, "done"), (2, "request"), (2, "done"), (3, "request"), (3, "done"), (4, "request"), (4, "done")]);
+        // The board file was rewritten before the first request; that is not
+        // put down to it.
+        assert_eq!(lines[1]["files"], serde_json::json!([{ "path": "todo/ship-it.md", "before": null, "after": lines[1]["files"][0]["after"] }]));
+        assert_eq!(lines[1]["activity"][0]["action"], "task_created");
+        assert_eq!(lines[4]["headers"]["Authorization"], "[redacted]");
+        assert_eq!(lines[4]["headers"]["X-Kanban-User"], "alice");
+        assert_eq!(lines[4]["body"], r#"{"description":"Soon"}"#);
+        assert_eq!(lines[5]["files"][0]["before"], lines[1]["files"][0]["after"]);
+        assert_ne!(lines[5]["files"][0]["after"], lines[5]["files"][0]["before"]);
+        let moved: Vec<(&str, bool, bool)> = lines[7]["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|file| (file["path"].as_str().unwrap(), file["before"].is_null(), file["after"].is_null()))
+            .collect();
+        assert_eq!(moved, [("doing/ship-it.md", true, false), ("todo/ship-it.md", false, true)]);
+
+        let mut out = Vec::new();
+        assert_eq!(grep_debug_journal(&journal, "ship-it", &mut out).unwrap(), 3);
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 6);
+        assert!(!out.contains("Other"));
+    }
+
+    #[test]
+    fn debug_journal_rotates_and_ends_a_torn_line() {
+        let parse = |args: &[&str]| parse_settings(args.iter().map(|a| a.to_string()), |_| None);
+        assert!(parse(&["journal", "grep", "--debug-journal", "requests.jsonl", "--task", "a"]).unwrap().journal_grep);
+        assert!(parse(&["journal", "grep", "--task", "a"]).is_err());
+        assert!(parse(&["journal", "tail", "--debug-journal", "requests.jsonl"]).is_err());
+        assert!(parse(&["doctor", "--debug-journal", "requests.jsonl"]).is_err());
+        assert!(parse(&["--task", "a"]).is_err());
+
+        let root = test_support::TempDir::new("debug-rotation");
+        fs::create_dir_all(root.join("todo")).unwrap();
+        let path = root.join("journal").join("requests.jsonl");
+        let journal = DebugJournal::open(&path, &root, 600).unwrap();
+        for n in 0..12 {
+            let id = if n % 3 == 0 { "kept" } else { "noise" };
+            let record = journal.begin(&Method::Post, &format!("/api/tasks/{}", id), None, &[], b"{}");
+            fs::write(root.join("todo").join(format!("{}.md", id)), format!("title: {}\n", n)).unwrap();
+            record.finish(200);
+        }
+        drop(journal);
+        let files = debug_journal_files(&path);
+        assert!(files.iter().all(|file| file.exists()), "{:?}", files);
+        assert!(files.iter().all(|file| fs::metadata(file).unwrap().len() <= 600));
+
+        let mut out = Vec::new();
+        let kept = grep_debug_journal(&path, "kept", &mut out).unwrap();
+        assert!((1..4).contains(&kept), "{}", kept);
+        let newest = String::from_utf8(out).unwrap().lines().last().map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["seq"].clone());
+        assert_eq!(newest, Some(serde_json::json!(10)));
+
+        let mut torn = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        torn.write_all(br#"{"seq":13,"phase":"requ"#).unwrap();
+        let journal = DebugJournal::open(&path, &root, 600).unwrap();
+        journal.begin(&Method::Delete, "/api/tasks/kept", None, &[], b"").finish(204);
+        let text: String = debug_journal_files(&path).iter().map(|file| fs::read_to_string(file).unwrap()).collect();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[lines.len() - 3], r#"{"seq":13,"phase":"requ"#);
+        let last: serde_json::Value = serde_json::from_str(lines[lines.len() - 1]).unwrap();
+        assert_eq!((last["seq"].as_u64(), last["phase"].as_str()), (Some(13), Some("done")));
+    }
+
+    #[test]
+    fn http_views_store_filters_and_list_through_the_task_engine() {
+        let server = test_support::TestServer::start("views");
+        let columns: Vec<String> = server.get("/api/board").json()["board"]["columns"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["id"].as_str().unwrap().to_string())
+            .collect();
+        for (title, status, priority) in [("Low bug", &columns[0], "low"), ("High bug", &columns[0], "high"), ("Elsewhere", &columns[1], "high")] {
+            let body = serde_json::json!({ "title": title, "tags": ["bug"], "assigned_to": "alice", "status": status, "priority": priority });
+            assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        }
+        let view = serde_json::json!({
+            "name": "my-bugs",
+            "owner": "alice",
+            "filter": { "tag": "bug", "assigned_to": "alice", "columns": [columns[0]] },
+            "sort": "priority",
+        });
+        let created = server.request("POST", "/api/views", Some(view.clone()));
+        assert_eq!(created.status, 201, "{}", created.body);
+        assert_eq!(created.json()["filter"]["columns"], serde_json::json!([columns[0]]));
+        assert_eq!(server.request("POST", "/api/views", Some(view.clone())).status, 409);
+        let mut bad = view.clone();
+        bad["name"] = serde_json::json!("My Bugs");
+        assert_eq!(server.request("POST", "/api/views", Some(bad)).status, 400);
+        let mut bad = view.clone();
+        bad["name"] = serde_json::json!("other");
+        bad["filter"]["columns"] = serde_json::json!(["nowhere"]);
+        assert_eq!(server.request("POST", "/api/views", Some(bad)).status, 400);
+
+        let listed = server.get("/api/views/my-bugs/tasks").json();
+        assert_eq!(listed["view"]["name"], "my-bugs");
+        let ids: Vec<&str> = listed["folders"][&columns[0]].as_array().unwrap().iter().map(|t| t["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["high-bug", "low-bug"]);
+        assert!(listed["folders"].get(&columns[1]).is_none());
+        assert_eq!(server.get("/api/views/my-bugs/tasks?limit=1").json()["folders"][&columns[0]].as_array().unwrap().len(), 1);
+
+        assert_eq!(server.get("/api/views?owner=ALICE").json()["views"][0]["name"], "my-bugs");
+        assert_eq!(server.get("/api/views?owner=bob").json()["views"], serde_json::json!([]));
+        assert_eq!(server.request("DELETE", "/api/views/my-bugs", None).status, 204);
+        assert_eq!(server.request("DELETE", "/api/v1/views/my-bugs", None).json()["error"]["code"], "view_not_found");
+        assert_eq!(server.get("/api/views/my-bugs/tasks").status, 404);
+    }
+
+    #[test]
+    fn http_manifest_serves_listings_and_tracks_changes() {
+        let server = test_support::TestServer::start_with("manifest", &["--manifest"]);
+        let column = server.get("/api/board").json()["board"]["columns"][0]["id"].as_str().unwrap().to_string();
+        let body = serde_json::json!({ "title": "Fix login", "status": column, "description": "Steps to reproduce" });
+        assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        let description = |query: &str| server.get(&format!("/api/tasks{}", query)).json()["folders"][&column][0]["description"].clone();
+        assert_eq!(description(""), "Steps to reproduce");
+        assert!(description("?fields=title").is_null());
+
+        let stored = || serde_json::from_str::<serde_json::Value>(&fs::read_to_string(server.root.join(MANIFEST_FILE)).unwrap()).unwrap()["tasks"].clone();
+        let entry = &stored()[format!("{}/fix-login.md", column)];
+        assert_eq!(entry["task"]["description"], "");
+        assert_eq!(entry["description_hash"], description_hash("Steps to reproduce"));
+
+        // Writes update their own entries before any listing runs.
+        let edited = server.request("PUT", "/api/tasks/fix-login", Some(serde_json::json!({ "description": "Fixed" })));
+        assert_eq!(edited.status, 200);
+        assert_eq!(stored()[format!("{}/fix-login.md", column)]["description_hash"], description_hash("Fixed"));
+        assert_eq!(description(""), "Fixed");
+        assert_eq!(description("?q=fixed"), "Fixed");
+        let other = server.get("/api/board").json()["board"]["columns"][1]["id"].as_str().unwrap().to_string();
+        let body = serde_json::json!({ "title": "Scratch", "status": column });
+        assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        assert_eq!(stored()[format!("{}/scratch.md", column)]["task"]["title"], "Scratch");
+        let moved = server.request("POST", "/api/tasks/scratch/move", Some(serde_json::json!({ "folder": other })));
+        assert_eq!(moved.status, 200, "{}", moved.body);
+        assert!(stored().get(format!("{}/scratch.md", column)).is_none());
+        assert_eq!(stored()[format!("{}/scratch.md", other)]["task"]["folder"], other.as_str());
+        assert_eq!(server.request("DELETE", "/api/tasks/scratch", None).status, 204);
+        assert!(stored().get(format!("{}/scratch.md", other)).is_none());
+
+        let config = read_config(&server.root).unwrap();
+        assert_eq!(manifest_drift(&server.root, &config, &BoardOptions::default()), Some(0));
+        let path = server.root.join(&column).join("fix-login.md");
+        fs::write(&path, fs::read_to_string(&path).unwrap() + "\nEdited elsewhere").unwrap();
+        assert_eq!(manifest_drift(&server.root, &config, &BoardOptions::default()), Some(1));
+        assert_eq!(rebuild_manifest(&server.root, &config, &BoardOptions::default()).unwrap(), 1);
+        assert_eq!(manifest_drift(&server.root, &config, &BoardOptions::default()), Some(0));
+        fs::remove_file(server.root.join(MANIFEST_FILE)).unwrap();
+        assert_eq!(manifest_drift(&server.root, &config, &BoardOptions::default()), None);
+        assert!(parse_arg_list(["--reindex".to_string()]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_serves_the_api_and_replaces_only_stale_sockets() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::os::unix::net::{UnixListener, UnixStream};
+        let dir = test_support::TempDir::new("unix-socket");
+        let socket = dir.join("kanban.sock");
+        drop(UnixListener::bind(&socket).unwrap());
+        let path = socket.to_str().unwrap();
+        let server = test_support::TestServer::start_with("unix-socket", &["--unix-socket", path, "--tcp"]);
+        assert_eq!(fs::metadata(&socket).unwrap().permissions().mode() & 0o777, 0o600);
+
+        let mut stream = UnixStream::connect(&socket).unwrap();
+        stream.write_all(b"GET /api/board HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.contains("\"columns\""));
+        assert_eq!(server.get("/api/board").status, 200);
+
+        let settings = parse_arg_list(["--unix-socket".to_string(), path.to_string()]).unwrap();
+        let taken = start_server("127.0.0.1:0", server.root.clone(), settings).err().unwrap();
+        assert_eq!(taken.kind(), io::ErrorKind::AddrInUse);
+        drop(server);
+        assert!(!socket.exists());
+
+        fs::write(&socket, "not a socket").unwrap();
+        assert!(bind_unix_socket(&socket).is_err());
+        assert!(parse_arg_list(["--tcp".to_string()]).is_err());
+    }
+
+    #[test]
+    fn daemon_options_and_pidfiles() {
+        let parse = |args: &[&str]| parse_settings(args.iter().map(|a| a.to_string()), |_| None);
+        let settings = parse(&["--daemonize", "--pidfile", "/run/kanban.pid", "--log-file", "/var/log/kanban.log"]).unwrap();
+        assert!(settings.daemonize);
+        assert_eq!(settings.pidfile, Some(PathBuf::from("/run/kanban.pid")));
+        let err = parse(&["--daemonize", "--open-browser=true"]).unwrap_err();
+        assert!(err.contains("--open-browser"), "{}", err);
+        assert!(parse(&["--daemonize", "--open-browser=false"]).is_ok());
+        assert!(parse(&["doctor", "--daemonize"]).is_err());
+        assert!(parse(&["--log-file", "server.log"]).is_err());
+        assert!(parse(&["export", "--pidfile", "kanban.pid"]).is_err());
+        assert!(parse(&["status", "--pidfile", "kanban.pid"]).unwrap().status);
+
+        let dir = test_support::TempDir::new("pidfile");
+        let pidfile = dir.join(PID_FILE);
+        assert_eq!(server_status(&pidfile), 3);
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 8787));
+        write_pidfile(&pidfile, &startup_line("0.0.0.0:8787", &dir, Some(addr), None, Some("http://localhost:8787/"))).unwrap();
+        let (pid, line) = running_server(&pidfile).unwrap();
+        assert_eq!(pid, std::process::id());
+        assert_eq!(line["port"], 8787);
+        fs::write(&pidfile, format!("{{\"pid\": {}, \"port\": 8787}}", u32::MAX - 1)).unwrap();
+        assert!(running_server(&pidfile).is_none());
+        assert_eq!(server_status(&pidfile), 3);
+        fs::write(&pidfile, "1234").unwrap();
+        assert_eq!(server_status(&pidfile), 1);
+    }
+
+    #[test]
+    fn service_commands_keep_the_serving_options() {
+        let parse = |args: &[&str]| parse_settings(args.iter().map(|a| a.to_string()), |_| None);
+        assert_eq!(parse(&["service", "install", "--watch"]).unwrap().service, Some(ServiceCommand::Install));
+        assert_eq!(parse(&["service", "uninstall"]).unwrap().service, Some(ServiceCommand::Uninstall));
+        assert!(parse(&["service", "run", "--log-file", "kanban.log"]).is_ok());
+        assert!(parse(&["service"]).is_err());
+        assert!(parse(&["service", "start"]).unwrap_err().contains("install, uninstall, run"));
+        assert!(parse(&["service", "install", "--daemonize"]).is_err());
+        assert!(parse(&["service", "install", "--open-browser=true"]).is_err());
+        assert!(parse(&["service", "install", "doctor"]).is_err());
+        assert!(parse(&["service", "uninstall", "--pidfile", "kanban.pid"]).is_err());
+
+        let given: Vec<String> = ["-t", "board", "service", "install", "--watch", "--pidfile", "kanban.pid"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let root = Path::new("/srv/board");
+        let args = service_arguments(&given, root);
+        let pidfile = absolute_path(Path::new("kanban.pid")).display().to_string();
+        assert_eq!(args, ["--watch", "--pidfile", &pidfile, "--target", "/srv/board", "--yes", "service", "run"]);
+        let again = parse(&args.iter().map(String::as_str).collect::<Vec<_>>()).unwrap();
+        assert_eq!((again.service, again.target.as_deref(), again.yes), (Some(ServiceCommand::Run), Some("/srv/board"), true));
+    }
+
+    #[test]
+    fn http_warnings_from_different_producers_share_one_array() {
+        let server = test_support::TestServer::start("warnings");
+        let mut columns = server.get("/api/board").json()["board"]["columns"].clone();
+        let column = columns[0]["id"].as_str().unwrap().to_string();
+        columns[0]["wip_limit"] = serde_json::json!(1);
+        let board = server.request("PUT", "/api/board", Some(serde_json::json!({ "columns": columns, "unique_titles": "warn" })));
+        assert_eq!(board.status, 200, "{}", board.body);
+        assert!(board.json().get("warnings").is_none());
+
+        let body = serde_json::json!({ "title": "Fix login", "status": column });
+        assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        let body = serde_json::json!({ "title": "Fix login", "status": column, "colour": "red" });
+        let created = server.request("POST", "/api/tasks", Some(body)).json();
+        let codes = |payload: &serde_json::Value| -> Vec<String> {
+            payload["warnings"].as_array().unwrap().iter().map(|w| w["code"].as_str().unwrap().to_string()).collect()
+        };
+        assert_eq!(codes(&created), ["similar_title", "ignored_field"]);
+        assert_eq!(created["warnings"][0]["task"], "fix-login");
+        assert_eq!(created["ignored_fields"], serde_json::json!(["colour"]));
+
+        fs::write(server.root.join(&column).join("broken.md"), [0xff, 0xfe, 0x00]).unwrap();
+        let listed = server.get("/api/tasks").json();
+        assert_eq!(codes(&listed), ["wip_limit_exceeded", "unreadable_task"]);
+        assert_eq!(listed["warnings"][0]["column"], column.as_str());
+        assert_eq!((&listed["warnings"][1]["task"], &listed["warnings"][1]["column"]), (&serde_json::json!("broken"), &serde_json::json!(column)));
+        let listed = server.get(&format!("/api/columns/{}/tasks", column)).json();
+        assert_eq!(codes(&listed), ["wip_limit_exceeded", "unreadable_task"]);
+
+        // Board updates keep the limit and count next to the common fields.
+        fs::remove_file(server.root.join(&column).join("broken.md")).unwrap();
+        let body = serde_json::json!({ "title": "Third", "status": column });
+        assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        columns[0]["wip_limit"] = serde_json::json!(2);
+        let board = server.request("PUT", "/api/board", Some(serde_json::json!({ "columns": columns }))).json();
+        let warning = &board["warnings"][0];
+        assert_eq!((warning["code"].as_str(), warning["column"].as_str()), (Some("wip_limit_exceeded"), Some(column.as_str())));
+        assert_eq!((warning["wip_limit"].as_u64(), warning["count"].as_u64()), (Some(2), Some(3)));
+    }
+
+    #[test]
+    fn http_moves_keep_a_manual_order_per_column() {
+        let server = test_support::TestServer::start("manual-order");
+        let columns = server.get("/api/board").json()["board"]["columns"].clone();
+        let (todo, doing) = (columns[0]["id"].as_str().unwrap().to_string(), columns[1]["id"].as_str().unwrap().to_string());
+        for title in ["Alpha", "Beta", "Gamma"] {
+            assert_eq!(server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": title, "status": todo }))).status, 201);
+        }
+        let move_to = |id: &str, folder: &str, position: Option<usize>| {
+            let body = serde_json::json!({ "folder": folder, "position": position });
+            assert_eq!(server.request("POST", &format!("/api/tasks/{}/move", id), Some(body)).status, 200);
+        };
+        let listed = |column: &str| -> Vec<String> {
+            let folders = server.get("/api/tasks").json()["folders"].clone();
+            folders[column].as_array().unwrap().iter().map(|t| t["id"].as_str().unwrap().to_string()).collect()
+        };
+        move_to("alpha", &doing, Some(0));
+        move_to("beta", &doing, Some(0));
+        move_to("gamma", &doing, None);
+        assert_eq!(listed(&doing), ["beta", "alpha", "gamma"]);
+        move_to("beta", &doing, Some(9));
+        assert_eq!(listed(&doing), ["alpha", "gamma", "beta"]);
+        move_to("gamma", &todo, None);
+        assert_eq!(listed(&doing), ["alpha", "beta"]);
+        let order: serde_json::Value = serde_json::from_str(&fs::read_to_string(server.root.join(ORDER_FILE)).unwrap()).unwrap();
+        assert_eq!(order, serde_json::json!({ doing.as_str(): ["alpha", "beta"] }));
+
+        let log = fs::read_to_string(server.root.join(ACTIVITY_FILE)).unwrap();
+        let last: serde_json::Value = serde_json::from_str(log.lines().last().unwrap()).unwrap();
+        assert_eq!((&last["action"], &last["from_position"], last.get("to_position")), (&serde_json::json!("task_moved"), &serde_json::json!(1), None));
+
+        fs::remove_file(server.root.join(&doing).join("alpha.md")).unwrap();
+        let warnings = doctor(&server.root, false, false, false, false, &BoardOptions::default()).warnings;
+        assert!(warnings.iter().any(|w| w.contains("lists alpha for")), "{:?}", warnings);
+    }
+
+    #[test]
+    fn http_create_checks_timestamps_and_doctor_reports_unreadable_ones() {
+        let server = test_support::TestServer::start("timestamps");
+        let bad = server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "Old", "created_at": "last week" })));
+        assert_eq!(bad.status, 400);
+        assert!(bad.body.contains("accepted formats") && bad.body.contains("Unix epoch seconds"), "{}", bad.body);
+
+        let created = server.request("POST", "/api/tasks", Some(serde_json::json!({ "title": "Old", "created_at": "1717250400" })));
+        assert_eq!(created.status, 201, "{}", created.body);
+        let task: serde_json::Value = serde_json::from_str(&created.body).unwrap();
+        assert_eq!(task["created_at"], "2024-06-01T14:00:00Z");
+        let path = server.root.join(task["folder"].as_str().unwrap()).join("old.md");
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("created_at: 2024-06-01T14:00:00Z\n"), "{}", text);
+
+        fs::write(&path, text.replace("created_at: 2024-06-01T14:00:00Z", "created_at: 2024-06-01 14:00")).unwrap();
+        assert_eq!(server.get("/api/tasks").body.matches("2024-06-01T14:00:00Z").count(), 1);
+        assert!(doctor(&server.root, false, false, false, false, &BoardOptions::default()).warnings.iter().all(|w| !w.contains("unreadable")));
+        fs::write(&path, text.replace("created_at: 2024-06-01T14:00:00Z", "created_at: soon")).unwrap();
+        let warnings = doctor(&server.root, false, false, false, false, &BoardOptions::default()).warnings;
+        assert!(warnings.iter().any(|w| w.contains("old.md has an unreadable created_at \"soon\"")), "{:?}", warnings);
+    }
+
+    #[test]
+    fn env_variables_fill_options_the_command_line_leaves_out() {
+        let vars = HashMap::from([
+            ("KANBAN_ROOT", "/srv/board"),
+            ("KANBAN_YES", "on"),
+            ("KANBAN_SHOW_BOARD_EDITOR", "true"),
+            ("KANBAN_HISTORY_LIMIT", "5"),
+            ("KANBAN_TRUSTED_PROXY", "10.0.0.0/8, 192.168.1.1"),
+            ("KANBAN_LANG", "de"),
+            ("KANBAN_PORT", "9000"),
+            ("KANBAN_STRICT_API", ""),
+        ]);
+        let parse = |args: &[&str], vars: &HashMap<&str, &str>| {
+            parse_settings(args.iter().map(|a| a.to_string()), |name| vars.get(name).map(|v| v.to_string()))
+        };
+        let settings = parse(&["-t", "./here", "--lang=fr"], &vars).unwrap();
+        assert_eq!(settings.target.as_deref(), Some("./here"));
+        assert_eq!(settings.board.lang, "fr");
+        assert!(settings.yes && settings.ui.show_board_editor && !settings.strict_api);
+        assert_eq!((settings.history_limit, settings.port, settings.trusted_proxies.len()), (5, 9000, 2));
+        assert_eq!(parse(&[], &vars).unwrap().target.as_deref(), Some("/srv/board"));
+        // The command line replaces a list variable rather than adding to it.
+        assert_eq!(parse(&["--trusted-proxy", "127.0.0.1"], &vars).unwrap().trusted_proxies.len(), 1);
+
+        for (var, value, error) in [
+            ("KANBAN_YES", "maybe", "Invalid boolean for KANBAN_YES: maybe"),
+            ("KANBAN_HISTORY_LIMIT", "lots", "KANBAN_HISTORY_LIMIT: Invalid value for --history-limit: lots"),
+            ("KANBAN_PORT", "99999", "KANBAN_PORT: Invalid port: 99999"),
+        ] {
+            assert_eq!(parse(&[], &HashMap::from([(var, value)])).unwrap_err(), error);
+        }
+        assert!(env_help().contains("KANBAN_ADMIN_TOKEN               --admin-token\n"));
+    }
+
+    #[test]
+    fn http_routing_rules_keep_tasks_out_of_columns() {
+        let server = test_support::TestServer::start_with("rules", &["--admin-token", "s3cret"]);
+        let admin = [("Authorization", "Bearer s3cret")];
+        let columns = server.get("/api/board").json()["board"]["columns"].clone();
+        let rules = serde_json::json!([
+            { "column": "done", "kind": "requires-tag", "tag": "ready" },
+            { "column": "backlog", "kind": "denies-tag", "tag": "bug" },
+        ]);
+        let unknown = serde_json::json!({ "columns": columns, "rules": [{ "column": "nope", "kind": "denies-tag", "tag": "bug" }] });
+        assert_eq!(server.request("PUT", "/api/board", Some(unknown)).status, 400);
+        let board = serde_json::json!({ "columns": columns, "rules": rules });
+        assert_eq!(server.request("PUT", "/api/board", Some(board)).status, 200);
+        assert_eq!(server.get("/api/board").json()["board"]["rules"], rules);
+        let config = fs::read_to_string(server.root.join(CONFIG_FILE)).unwrap();
+        assert!(config.contains("rule done requires-tag ready\nrule backlog denies-tag bug\n"), "{}", config);
+
+        let create = |title: &str, tags: &[&str], status: &str, query: &str| {
+            let body = serde_json::json!({ "title": title, "tags": tags, "status": status });
+            server.request("POST", &format!("/api/tasks{}", query), Some(body))
+        };
+        let refused = create("Crash on save", &["Bug"], "backlog", "");
+        assert_eq!(refused.status, 422, "{}", refused.body);
+        assert_eq!(refused.json()["rule"], "backlog denies-tag bug");
+        let body = serde_json::json!({ "title": "Crash on save", "tags": ["Bug"], "status": "backlog" });
+        assert_eq!(server.request("POST", "/api/v1/tasks", Some(body)).json()["error"]["code"], "rule_broken");
+        assert_eq!(create("Crash on save", &["bug"], "planned", "").status, 201);
+        assert_eq!(create("Docs", &[], "backlog", "").status, 201);
+
+        let move_to = |id: &str, folder: &str, query: &str, headers: &[(&str, &str)]| {
+            let path = format!("/api/tasks/{}/move{}", id, query);
+            server.request_with_headers("POST", &path, Some(serde_json::json!({ "folder": folder })), headers).status
+        };
+        assert_eq!(move_to("crash-on-save", "backlog", "", &[]), 422);
+        assert_eq!(move_to("docs", "done", "?force=true", &[]), 422);
+        assert_eq!(move_to("docs", "done", "?force=true", &admin), 200);
+        assert_eq!(move_to("docs", "done", "", &[]), 200, "moves within a column skip the rules");
+
+        let batch = server.request("POST", "/api/tasks/batch", Some(serde_json::json!([{ "title": "Ship it", "status": "done" }])));
+        assert!(batch.json()["results"][0]["error"].as_str().unwrap().contains("done requires-tag ready"), "{}", batch.body);
+        assert_eq!(create("Ship it", &["ready"], "done", "").status, 201);
+
+        let mut parsed = BoardConfig { columns: vec![column("todo")], ..Default::default() };
+        assert!(apply_board_settings(&mut parsed, "rule todo requires tag ready").is_err());
+        apply_board_settings(&mut parsed, "rule todo denies-tag wip").unwrap();
+        assert!(render_config(&parsed).lines().any(|line| line == "rule todo denies-tag wip"));
+    }
+
+    #[test]
+    fn http_csv_import_checks_routing_rules_per_row() {
+        let server = test_support::TestServer::start_with("csv-rules", &["--admin-token", "s3cret"]);
+        let columns = server.get("/api/board").json()["board"]["columns"].clone();
+        let rules = serde_json::json!([{ "column": "backlog", "kind": "denies-tag", "tag": "bug" }]);
+        let board = serde_json::json!({ "columns": columns, "rules": rules });
+        assert_eq!(server.request("PUT", "/api/board", Some(board)).status, 200);
+
+        let csv = "Crash on save,Bug,backlog\nDocs,,backlog\n";
+        let import = |query: &str, headers: &[(&str, &str)]| {
+            let path = format!("/api/import/csv?map=title:1,tags:2,column:3{}", query);
+            let headers: Vec<_> = [("Content-Type", "text/csv")].iter().chain(headers).copied().collect();
+            server.request_raw("POST", &path, csv, &headers)
+        };
+        let imported = import("", &[]);
+        assert_eq!(imported.status, 207, "{}", imported.body);
+        let report = imported.json();
+        assert_eq!((report["created"].clone(), report["failed"].clone()), (serde_json::json!(1), serde_json::json!(1)));
+        assert_eq!(report["results"][0]["row"], 1);
+        assert!(report["results"][0]["error"].as_str().unwrap().contains("backlog denies-tag bug"), "{}", imported.body);
+        assert!(!server.root.join("backlog").join("crash-on-save.md").exists());
+
+        assert_eq!(import("&force=true&collision=skip", &[]).status, 207, "only admins may force");
+        let forced = import("&force=true&collision=skip", &[("Authorization", "Bearer s3cret")]);
+        assert_eq!(forced.status, 201, "{}", forced.body);
+        assert!(server.root.join("backlog").join("crash-on-save.md").exists());
+    }
+
+    #[test]
+    fn foreign_files_are_listed_kept_and_relocated() {
+        let server = test_support::TestServer::start("foreign");
+        let body = serde_json::json!({ "title": "Draw mockup", "status": "planned" });
+        assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        fs::write(server.root.join("planned").join("mockup.png"), [0u8; 12]).unwrap();
+        fs::write(server.root.join("planned").join("notes.txt"), "ask design").unwrap();
+        fs::write(server.root.join("done").join("old.txt"), "x").unwrap();
+
+        let report = server.get("/api/board/foreign-files").json();
+        assert_eq!((report["count"].clone(), report["bytes"].clone()), (serde_json::json!(3), serde_json::json!(23)));
+        assert_eq!(report["columns"][0]["column"], "planned");
+        assert_eq!(report["columns"][0]["files"][0], serde_json::json!({ "name": "mockup.png", "bytes": 12 }));
+        assert_eq!(report["columns"][1]["files"][0]["name"], "old.txt");
+        assert_eq!(server.get("/api/tasks").json()["folders"]["planned"].as_array().unwrap().len(), 1);
+
+        let columns: Vec<serde_json::Value> = server.get("/api/board").json()["board"]["columns"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|c| c["id"] != "done")
+            .cloned()
+            .collect();
+        assert_eq!(server.request("PUT", "/api/board", Some(serde_json::json!({ "columns": columns }))).status, 200);
+        assert_eq!(server.get("/api/board/foreign-files").json()["count"], 2);
+        assert!(server.root.join("done").join("old.txt").is_file(), "folders with foreign files are never removed automatically");
+        let cfg = read_config(&server.root).unwrap();
+        let plan = plan_reconcile(&server.root, &cfg).unwrap();
+        assert!(plan.remove_empty_folders.is_empty() && plan.orphan_folders.is_empty());
+        assert_eq!((plan.kept_folders[0].folder.as_str(), plan.kept_folders[0].foreign_files), ("done", 1));
+
+        let warnings = doctor(&server.root, false, false, false, false, &BoardOptions::default()).warnings;
+        assert!(warnings.iter().any(|w| w.starts_with("2 file(s) in column folders are not tasks")), "{:?}", warnings);
+        assert!(warnings.iter().any(|w| w.starts_with("folder done/ is not in")), "{:?}", warnings);
+        fs::create_dir_all(server.root.join(ATTACHMENTS_DIR).join(LOOSE_FILES_DIR).join("planned")).unwrap();
+        fs::write(server.root.join(ATTACHMENTS_DIR).join(LOOSE_FILES_DIR).join("planned").join("notes.txt"), "older").unwrap();
+        let warnings = doctor(&server.root, false, false, false, true, &BoardOptions::default()).warnings;
+        assert!(warnings.contains(&"moved planned/mockup.png to _attachments/_loose/planned/mockup.png".to_string()), "{:?}", warnings);
+        assert!(warnings.iter().any(|w| w.starts_with("could not move planned/notes.txt")), "{:?}", warnings);
+        assert!(!warnings.iter().any(|w| w.contains("belongs to no task")), "{:?}", warnings);
+        assert_eq!(fs::read_to_string(server.root.join("planned").join("notes.txt")).unwrap(), "ask design");
+        assert_eq!(server.get("/api/board/foreign-files").json()["count"], 1);
+    }
+
+    #[test]
+    fn http_board_readme_is_served_raw_or_rendered() {
+        let server = test_support::TestServer::start_with("readme", &["--admin-token", "s3cret"]);
+        let admin = [("Authorization", "Bearer s3cret")];
+        assert_eq!(server.get("/api/v1/readme").json()["error"]["code"], "readme_not_found");
+        assert_eq!(server.get("/api/ui").json()["features"]["readme"], false);
+
+        let text = "# Conventions\n\nTag **bugs** with `bug`.\n<script>alert(1)</script>\n\n- [Guide](https://example.com/dod)\n- [bad](javascript:void)\n";
+        assert_eq!(server.request_raw("PUT", "/api/readme", text, &[]).status, 401);
+        let saved = server.request_raw("PUT", "/api/readme", text, &admin);
+        assert_eq!(saved.status, 200, "{}", saved.body);
+        assert_eq!(fs::read_to_string(server.root.join("BOARD.md")).unwrap(), text);
+        assert_eq!(server.get("/api/ui").json()["features"]["readme"], true);
+        let raw = server.get("/api/readme");
+        assert_eq!((raw.body.as_str(), raw.header("Content-Type")), (text, Some("text/markdown; charset=utf-8")));
+        let html = server.get("/api/readme?render=html").body;
+        assert_eq!(
+            html,
+            "<h1>Conventions</h1>\n<p>Tag <strong>bugs</strong> with <code>bug</code>. &lt;script&gt;alert(1)&lt;/script&gt;</p>\n\
+             <ul>\n<li><a href=\"https://example.com/dod\">Guide</a></li>\n<li>bad</li>\n</ul>\n"
+        );
+        assert!(server.get("/api/tasks").json()["folders"].as_object().unwrap().values().all(|tasks| tasks.as_array().unwrap().is_empty()));
+
+        fs::rename(server.root.join("BOARD.md"), server.root.join(".kanban-readme.md")).unwrap();
+        assert_eq!(server.request_raw("PUT", "/api/readme", "Be nice.", &admin).status, 200);
+        assert_eq!(fs::read_to_string(server.root.join(".kanban-readme.md")).unwrap(), "Be nice.");
+        assert!(!server.root.join("BOARD.md").exists());
+        assert_eq!(server.request_raw("PUT", "/api/readme", "", &admin).json()["readme"], false);
+        assert_eq!(server.get("/api/readme").status, 404);
+
+        assert_eq!(render_markdown_html("1. a *b*\n2. c**\n```\n<b>\n```"), "<ol>\n<li>a <em>b</em></li>\n<li>c**</li>\n</ol>\n<pre><code>&lt;b&gt;\n</code></pre>\n");
+    }
+
+    #[test]
+    fn http_schema_matches_what_the_server_accepts() {
+        let server = test_support::TestServer::start("schema");
+        fs::write(config_path(&server.root), "todo: To do\nparked: Parked wip=0\nfield severity: enum(low,high)\nfield points: number\n").unwrap();
+        let served = server.get("/api/schema");
+        assert_eq!(served.status, 200, "{}", served.body);
+        let etag = served.header("ETag").unwrap().to_string();
+        assert_eq!(server.request_with_headers("GET", "/api/schema", None, &[("If-None-Match", &etag)]).status, 304);
+        let schemas = served.json()["schemas"].clone();
+        let validator = |name: &str| jsonschema::validator_for(&schemas[name]).unwrap();
+
+        let good = serde_json::json!({ "title": "Crash", "folder": "todo", "due": "2026-11-01", "extra": { "severity": "high", "points": 3 } });
+        assert!(validator("NewTask").is_valid(&good));
+        assert_eq!(server.request("POST", "/api/tasks", Some(good)).status, 201);
+        let bad = [
+            serde_json::json!({ "title": "A", "extra": { "severity": "urgent" } }),
+            serde_json::json!({ "title": "B", "extra": { "points": "many" } }),
+            serde_json::json!({ "title": "C", "id": "Bad Id" }),
+            serde_json::json!({ "title": "D", "color": "red" }),
+            serde_json::json!({ "title": "E", "estimate": -1 }),
+            serde_json::json!({ "title": "F", "due": "tomorrow" }),
+            serde_json::json!({ "title": "G", "folder": "parked" }),
+        ];
+        for body in bad {
+            assert!(!validator("NewTask").is_valid(&body), "{}", body);
+            assert_eq!(server.request("POST", "/api/tasks", Some(body.clone())).status, 400, "{}", body);
+        }
+        assert!(validator("UpdateTask").is_valid(&serde_json::json!({ "extra": { "severity": null } })));
+        assert!(!validator("UpdateTask").is_valid(&serde_json::json!({ "extra": { "status": "x" } })));
+        let away = serde_json::json!({ "folder": "nowhere" });
+        assert!(!validator("MoveTask").is_valid(&away));
+        assert_eq!(server.request("POST", "/api/tasks/crash/move", Some(away)).status, 400);
+
+        let columns = serde_json::json!([{ "id": "todo", "title": "To do" }, { "id": "parked", "title": "Parked", "wip_limit": 0 }]);
+        let broken = serde_json::json!({ "columns": [{ "id": "Bad Id", "title": "Bad" }] });
+        assert!(!validator("BoardUpdate").is_valid(&broken));
+        assert_eq!(server.request("PUT", "/api/board", Some(broken)).status, 400);
+        let update = serde_json::json!({ "columns": columns, "fields": [{ "name": "team", "type": "enum", "values": ["ops"] }] });
+        assert!(validator("BoardUpdate").is_valid(&update));
+        assert_eq!(server.request("PUT", "/api/board", Some(update)).status, 200);
+
+        let changed = server.get("/api/schema");
+        assert_ne!(changed.header("ETag"), Some(etag.as_str()));
+        let extra = &changed.json()["schemas"]["NewTask"]["properties"]["extra"]["properties"];
+        assert!(extra.get("team").is_some() && extra.get("severity").is_none(), "{}", extra);
+    }
+
+    #[test]
+    fn http_listings_number_tasks_densely_and_repeat_byte_for_byte() {
+        let server = test_support::TestServer::start("positions");
+        fs::write(config_path(&server.root), "todo: To do\ndoing: Doing\n").unwrap();
+        for (title, tags) in [("Alpha", "ui"), ("Beta", "api"), ("Gamma", "ui"), ("Delta", "ui")] {
+            let body = serde_json::json!({ "title": title, "folder": "todo", "tags": [tags] });
+            assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        }
+        let moved = server.request("POST", "/api/tasks/gamma/move", Some(serde_json::json!({ "folder": "todo", "position": 0 })));
+        assert_eq!(moved.status, 200);
+        let placed = |tasks: &serde_json::Value| {
+            tasks.as_array().unwrap().iter().map(|t| (t["id"].as_str().unwrap().to_string(), t["position"].as_u64().unwrap())).collect::<Vec<_>>()
+        };
+
+        let listing = server.get("/api/v1/tasks");
+        let todo = placed(&listing.json()["columns"][0]["tasks"]);
+        assert_eq!(todo[0], ("gamma".to_string(), 0));
+        assert_eq!(todo.iter().map(|(_, p)| *p).collect::<Vec<_>>(), [0, 1, 2, 3]);
+        assert_eq!(server.get("/api/v1/tasks").body, listing.body);
+        assert_eq!(placed(&server.get("/api/v1/columns/todo/tasks").json()["tasks"]), todo);
+
+        let paged = server.get("/api/v1/tasks?offset=1&limit=2&fields=title").json();
+        assert_eq!(placed(&paged["columns"][0]["tasks"]), todo[1..3]);
+        let filtered = placed(&server.get("/api/v1/tasks?tag=ui").json()["columns"][0]["tasks"]);
+        assert_eq!(filtered.iter().map(|(_, p)| *p).collect::<Vec<_>>(), [0, 1, 2]);
+        // The unprefixed routes keep their payload.
+        assert!(server.get("/api/tasks").json()["folders"]["todo"][0].get("position").is_none());
+        assert!(server.get("/api/columns/todo/tasks").json()["tasks"][0].get("position").is_none());
+        assert!(render_task(&parse_task(&server.root.join("todo/gamma.md"), "todo", StatusMode::Column, "").unwrap()).find("position").is_none());
+    }
+
+    #[test]
+    fn http_ready_listing_splits_a_column_by_unfinished_blockers() {
+        let server = test_support::TestServer::start("ready");
+        fs::write(config_path(&server.root), "planned: Planned\ndoing: Doing\nshipped: Shipped done=true\nfield priority: enum(high,low)\n").unwrap();
+        let create = |id: &str, folder: &str, extra: serde_json::Value| {
+            let body = serde_json::json!({ "id": id, "title": id, "folder": folder, "extra": extra });
+            assert_eq!(server.request("POST", "/api/tasks", Some(body)).status, 201);
+        };
+        create("api", "doing", serde_json::json!({}));
+        create("schema", "shipped", serde_json::json!({}));
+        create("old", "planned", serde_json::json!({ "priority": "low" }));
+        create("urgent", "planned", serde_json::json!({ "priority": "high", "blocked_by": "schema" }));
+        create("ui", "planned", serde_json::json!({ "priority": "high", "blocked_by": "api, schema" }));
+        create("docs", "planned", serde_json::json!({ "blocked_by": "ui gone" }));
+        fs::create_dir_all(server.root.join("_archive")).unwrap();
+        let archived = fs::read_to_string(server.root.join("shipped/schema.md")).unwrap().replace("schema", "legacy");
+        fs::write(server.root.join("_archive/legacy.md"), archived).unwrap();
+        create("port", "planned", serde_json::json!({ "blocked_by": "legacy" }));
+
+        let listing = server.get("/api/tasks/ready?column=planned").json();
+        let ids = |list: &serde_json::Value| list.as_array().unwrap().iter().map(|t| t["id"].as_str().unwrap().to_string()).collect::<Vec<_>>();
+        assert_eq!(ids(&listing["ready"]), ["urgent", "old", "port"]);
+        assert_eq!(ids(&listing["blocked"]), ["ui", "docs"]);
+        assert_eq!(listing["counts"], serde_json::json!({ "ready": 3, "blocked": 2 }));
+        assert_eq!(listing["blocked"][0]["waiting_on"], serde_json::json!([{ "id": "api", "title": "api", "column": "doing" }]));
+        assert_eq!(listing["blocked"][1]["waiting_on"][0]["id"], "ui");
+        assert_eq!(listing["warnings"][0]["code"], "dangling_blocker");
+        assert_eq!(listing["warnings"][0]["task"], "docs");
+
+        let moved = server.request("POST", "/api/tasks/api/move", Some(serde_json::json!({ "folder": "shipped" })));
+        assert_eq!(moved.status, 200);
+        assert_eq!(server.get("/api/tasks/ready?column=planned").json()["counts"], serde_json::json!({ "ready": 4, "blocked": 1 }));
+        assert_eq!(server.get("/api/tasks/ready?column=nowhere").status, 400);
+        assert_eq!(server.get("/api/tasks/ready").status, 400);
+    }
+
+    #[test]
+    fn http_board_packs_round_trip_and_merge_columns() {
+        let source = test_support::TestServer::start("pack-source");
+        fs::create_dir_all(source.root.join(TASK_TEMPLATES_DIR)).unwrap();
+        let template = "---\ntitle: Incident\ntags: bug\n---\nSteps to reproduce:\n";
+        fs::write(source.root.join(TASK_TEMPLATES_DIR).join("incident.md"), template).unwrap();
+        fs::write(config_path(&source.root), "backlog: Ideas wip=5\nreview: Review template=incident\nrule review requires-tag ready\n").unwrap();
+        fs::write(source.root.join(USERS_FILE), "alice\n").unwrap();
+        fs::write(theme_json_path(&source.root), r##"{"headline": "Ops", "column_colors": {"review": "#ff0000"}}"##).unwrap();
+        let view = serde_json::json!({ "name": "mine", "owner": "alice", "filter": { "assigned_to": "alice" } });
+        assert_eq!(source.request("POST", "/api/views", Some(view)).status, 201);
+
+        let exported = source.get("/api/boardpack");
+        assert_eq!(exported.status, 200, "{}", exported.body);
+        assert!(exported.header("Content-Disposition").unwrap().contains(".kanban-pack.json"));
+        let pack = exported.json();
+        assert_eq!(pack["kanban_board_pack"], BOARD_PACK_VERSION);
+        assert!(pack["board"].get("modified_at").is_none());
+        assert_eq!(pack["board"]["rules"][0]["column"], "review");
+        assert_eq!(pack["templates"]["incident"], template);
+        assert_eq!(pack["users"], serde_json::json!(["alice"]));
+        assert_eq!(pack["theme"]["headline"], "Ops");
+
+        let target = test_support::TestServer::start("pack-target");
+        let task = serde_json::json!({ "title": "Ship it", "status": "done" });
+        assert_eq!(target.request("POST", "/api/tasks", Some(task)).status, 201);
+        let mut broken = pack.clone();
+        broken["kanban_board_pack"] = serde_json::json!(2);
+        broken["board"]["columns"][0]["wip"] = serde_json::json!(3);
+        broken["theme"]["density"] = serde_json::json!("cozy");
+        broken["tasks"] = serde_json::json!([]);
+        let refused = target.request("POST", "/api/boardpack", Some(broken));
+        assert_eq!(refused.status, 400, "{}", refused.body);
+        let sections: Vec<String> = refused.json()["sections"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| format!("{}: {}", e["section"].as_str().unwrap(), e["error"].as_str().unwrap()))
+            .collect();
+        assert_eq!(sections.len(), 4, "{:?}", sections);
+        assert!(sections[0].starts_with("kanban_board_pack: format version 2 is newer"), "{:?}", sections);
+        assert_eq!(sections[1], "tasks: unknown section (expected board, theme, templates, users, views)");
+        assert_eq!(sections[2], "board: unknown fields: columns[0].wip");
+        assert!(sections[3].starts_with("theme: "), "{:?}", sections);
+        assert!(!target.root.join("review").exists());
+
+        let applied = target.request("POST", "/api/boardpack", Some(pack.clone()));
+        assert_eq!(applied.status, 200, "{}", applied.body);
+        assert_eq!(applied.json()["applied"]["columns_added"], serde_json::json!(["review"]));
+        assert_eq!(applied.json()["applied"]["columns_updated"], serde_json::json!(["backlog"]));
+        let board = target.get("/api/board").json()["board"].clone();
+        let ids: Vec<&str> = board["columns"].as_array().unwrap().iter().map(|c| c["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["backlog", "planned", "in_progress", "done", "review"]);
+        assert_eq!((board["columns"][0]["title"].as_str(), board["columns"][0]["wip_limit"].as_u64()), (Some("Ideas"), Some(5)));
+        assert_eq!(target.get("/api/tasks").json()["folders"]["done"][0]["title"], "Ship it");
+        assert_eq!(fs::read_to_string(target.root.join(TASK_TEMPLATES_DIR).join("incident.md")).unwrap(), template);
+        assert_eq!(read_users_file(&target.root), ["alice"]);
+        assert_eq!(read_views(&target.root)[0].name, "mine");
+        assert_eq!(target.get("/api/theme").json()["theme"]["headline"], "Ops");
+
+        let file = target.root.join("ops.kanban-pack.json");
+        fs::write(&file, pack.to_string()).unwrap();
+        let fresh = target.root.join("fresh");
+        assert_eq!(init_from_pack(&fresh, &file, &BoardOptions::default()), 0);
+        let ids: Vec<String> = read_config(&fresh).unwrap().columns.into_iter().map(|c| c.id).collect();
+        assert_eq!(ids, ["backlog", "review"]);
+        assert!(fresh.join("review").is_dir());
+        fs::write(&file, r#"{"kanban_board_pack": 1, "board": {"columns": []}}"#).unwrap();
+        assert_eq!(init_from_pack(&target.root.join("empty"), &file, &BoardOptions::default()), 1);
+        assert!(!target.root.join("empty").exists());
+    }
+
+    #[test]
+    fn merge_copies_a_board_once_and_leaves_the_source_alone() {
+        let base = test_support::TempDir::new("merge");
+        let (a, b) = (base.join("team-a"), base.join("team-b"));
+        for (root, config, users) in [
+            (&a, "todo: To do\ndoing: Doing\nreview: Review\n", "alice\ncarol\n"),
+            (&b, "todo: To do\nin_progress: In progress\n", "alice\nbob\n"),
+        ] {
+            fs::create_dir_all(root).unwrap();
+            fs::write(config_path(root), config).unwrap();
+            fs::write(root.join(USERS_FILE), users).unwrap();
+            for column in read_config(root).unwrap().columns {
+                fs::create_dir_all(root.join(&column.id)).unwrap();
+            }
+        }
+        fs::write(task_path(&a, "todo", "fix-login"), "title: Fix login\nassigned_to: carol\n\nSteps\n").unwrap();
+        fs::write(task_path(&a, "doing", "api"), "title: API\n").unwrap();
+        fs::write(task_path(&a, "review", "docs"), "title: Docs\n").unwrap();
+        fs::create_dir_all(attachments_dir(&a, "fix-login")).unwrap();
+        fs::write(attachments_dir(&a, "fix-login").join("trace.txt"), "boom").unwrap();
+        fs::write(task_path(&b, "in_progress", "api"), "title: Their API\n").unwrap();
+        let source_files = |root: &Path| {
+            stamp_files(root, &[]).into_keys().map(|path| (fs::read(&path).unwrap(), path)).collect::<Vec<_>>()
+        };
+        let before = source_files(&a);
+
+        let options = MergeOptions {
+            from: Some(a.clone()),
+            into: Some(b.clone()),
+            column_map: parse_column_map("doing=in_progress").unwrap(),
+            prefix: Some("team-a-".to_string()),
+        };
+        assert_eq!(merge_boards(&options, &BoardOptions::default()), 0);
+        let columns: Vec<String> = read_config(&b).unwrap().columns.into_iter().map(|c| c.id).collect();
+        assert_eq!(columns, ["todo", "in_progress", "review"]);
+        let renamed = parse_task(&task_path(&b, "in_progress", "team-a-api"), "in_progress", StatusMode::Column, "").unwrap();
+        assert_eq!((renamed.title.as_str(), renamed.status.as_str()), ("API", "in_progress"));
+        let marker = format!("{}#api", fs::canonicalize(&a).unwrap().display());
+        assert_eq!(renamed.extra[MERGE_MARKER_FIELD], marker.as_str());
+        assert_eq!(fs::read_to_string(task_path(&b, "in_progress", "api")).unwrap(), "title: Their API\n");
+        assert!(task_path(&b, "review", "docs").is_file());
+        assert_eq!(fs::read_to_string(attachments_dir(&b, "fix-login").join("trace.txt")).unwrap(), "boom");
+        assert_eq!(read_users_file(&b), ["alice", "bob", "carol"]);
+        assert_eq!(source_files(&a), before);
+
+        // Running it again copies nothing and reports why.
+        assert_eq!(merge_boards(&options, &BoardOptions::default()), 0);
+        assert_eq!(existing_ids(&b, &read_config(&b).unwrap()).len(), 4);
+        let reports: Vec<serde_json::Value> = fs::read_dir(b.join(MERGES_DIR))
+            .unwrap()
+            .map(|entry| serde_json::from_str(&fs::read_to_string(entry.unwrap().path()).unwrap()).unwrap())
+            .collect();
+        assert_eq!(reports.len(), 2);
+        let decisions = |report: &serde_json::Value, kind: &str| -> Vec<String> {
+            report["decisions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|d| d["kind"] == kind)
+                .map(|d| d["decision"].as_str().unwrap().to_string())
+                .collect()
+        };
+        let mut runs: Vec<Vec<String>> = reports.iter().map(|report| decisions(report, "task")).collect();
+        runs.sort();
+        assert_eq!(runs, [vec!["already_merged"; 3], vec!["copied", "renamed", "copied"]]);
+        assert!(reports.iter().any(|report| decisions(report, "column") == ["matched", "mapped", "created"]));
+
+        let bad_map = MergeOptions { column_map: parse_column_map("doing=nowhere").unwrap(), ..options.clone() };
+        assert!(run_merge(&bad_map, &BoardOptions::default()).unwrap_err().contains("nowhere"));
+        assert!(parse_column_map("doing").is_err());
+        let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 8787));
+        write_pidfile(&a.join(PID_FILE), &startup_line("0.0.0.0:8787", &a, Some(addr), None, None)).unwrap();
+        assert!(run_merge(&options, &BoardOptions::default()).unwrap_err().contains("stop it before merging"));
+    }
+}
@@ -0,0 +1,62 @@
+//! Starts the built binary with `--daemonize` and checks it on with `status`.
+#![cfg(unix)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{Duration, Instant};
+
+fn board_dir(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("kanban-daemon-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    root
+}
+
+fn status(pidfile: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_kanban-server")).arg("status").arg("--pidfile").arg(pidfile).output().unwrap()
+}
+
+#[test]
+fn daemonized_server_reports_status_and_cleans_up_on_sigterm() {
+    let root = board_dir("serve");
+    let pidfile = root.join("server.pid");
+    let output = Command::new(env!("CARGO_BIN_EXE_kanban-server"))
+        .args(["--yes", "--create-root", "--daemonize", "--startup-json", "--target"])
+        .arg(&root)
+        .arg("--pidfile")
+        .arg(&pidfile)
+        .env("KANBAN_PORT", "0")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let started: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let pid = started["pid"].as_u64().unwrap();
+    let port = started["port"].as_u64().unwrap();
+    assert_eq!(fs::read_to_string(&pidfile).unwrap().trim(), started.to_string());
+    assert!(root.join(".kanban-server.log").exists());
+
+    let running = status(&pidfile);
+    assert_eq!(running.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&running.stdout);
+    assert!(stdout.contains(&format!("pid {} on port {}", pid, port)), "{}", stdout);
+
+    let again = Command::new(env!("CARGO_BIN_EXE_kanban-server"))
+        .args(["--yes", "--daemonize", "--target"])
+        .arg(&root)
+        .arg("--pidfile")
+        .arg(&pidfile)
+        .env("KANBAN_PORT", "0")
+        .output()
+        .unwrap();
+    assert_eq!(again.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&again.stderr).contains("is running"));
+
+    assert!(Command::new("kill").arg(pid.to_string()).status().unwrap().success());
+    let started = Instant::now();
+    while pidfile.exists() && started.elapsed() < Duration::from_secs(5) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert!(!pidfile.exists(), "SIGTERM removes the pidfile");
+    assert_eq!(status(&pidfile).status.code(), Some(3));
+    let _ = fs::remove_dir_all(&root);
+}
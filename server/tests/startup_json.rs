@@ -0,0 +1,76 @@
+//! Runs the built binary the way a script would and reads what it prints.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+fn board_dir(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("kanban-startup-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    root
+}
+
+#[test]
+fn startup_json_prints_one_line_then_serves() {
+    let root = board_dir("json");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_kanban-server"))
+        .args(["--yes", "--create-root", "--startup-json", "--target"])
+        .arg(&root)
+        .env("KANBAN_PORT", "0")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    let startup: serde_json::Value = serde_json::from_str(&line).unwrap_or_else(|_| panic!("not JSON: {:?}", line));
+
+    let port = startup["port"].as_u64().unwrap();
+    assert!(port > 0);
+    assert_eq!(startup["bind"], "0.0.0.0");
+    assert_eq!(startup["root"], fs::canonicalize(&root).unwrap().display().to_string());
+    assert_eq!(startup["pid"], child.id());
+    assert_eq!(startup["url"], format!("http://localhost:{}/", port));
+    let keys: Vec<&str> = startup.as_object().unwrap().keys().map(String::as_str).collect();
+    assert_eq!(keys, ["port", "bind", "root", "pid", "url"]);
+
+    let mut stream = TcpStream::connect(("127.0.0.1", port as u16)).unwrap();
+    stream.write_all(b"GET /api/board HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    let mut rest = String::new();
+    stdout.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "", "nothing but the JSON line goes to stdout");
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn startup_json_reports_errors_as_one_json_object() {
+    let root = board_dir("missing");
+    let output = Command::new(env!("CARGO_BIN_EXE_kanban-server"))
+        .args(["--yes", "--startup-json", "--target"])
+        .arg(&root)
+        .env("KANBAN_PORT", "0")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr.lines().count(), 1, "{}", stderr);
+    let error: serde_json::Value = serde_json::from_str(&stderr).unwrap();
+    assert!(error["error"].as_str().unwrap().contains("does not exist"), "{}", stderr);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_kanban-server"))
+        .args(["--startup-json", "--history-limit=lots"])
+        .output()
+        .unwrap();
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert!(error["error"].as_str().unwrap().contains("--history-limit"));
+}
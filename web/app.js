@@ -19,14 +19,35 @@ const toggleTaskEditorButton = document.getElementById("toggle-task-editor");
 const headline = document.getElementById("headline");
 const toast = document.getElementById("toast");
 
+// Set by the config.js of a `kanban-server publish` copy: API paths mapped
+// to static JSON files. Such a copy is read-only.
+const STATIC_FILES = window.KANBAN_STATIC || null;
+if (STATIC_FILES) {
+  document.body.classList.add("read-only");
+}
+
 async function api(path, options = {}) {
+  if (STATIC_FILES) {
+    const file = STATIC_FILES[path.split("?")[0]];
+    if (!file || (options.method && options.method !== "GET")) {
+      throw new Error("This board is a read-only copy");
+    }
+    path = file;
+  }
   const res = await fetch(path, {
     headers: { "Content-Type": "application/json" },
     ...options,
   });
   if (!res.ok) {
     const text = await res.text();
-    throw new Error(text || "Request failed");
+    let message = text;
+    try {
+      const body = JSON.parse(text);
+      message = body.message || body.error || text;
+    } catch (err) {
+      // Not JSON; show the body as is.
+    }
+    throw new Error(message || "Request failed");
   }
   if (res.status === 204) return null;
   return res.json();
@@ -41,12 +62,22 @@ function renderTask(task) {
   const card = template.content.firstElementChild.cloneNode(true);
   card.dataset.id = task.id;
   card.dataset.folder = task.folder;
-  card.querySelector(".card-title").textContent = task.title || task.id;
+  card.querySelector(".card-title").textContent = task.icon
+    ? `${task.icon} ${task.title || task.id}`
+    : task.title || task.id;
+  if (task.color) {
+    card.style.setProperty("--card-color", task.color);
+    card.classList.add("has-color");
+  }
   card.querySelector(".card-description").textContent = task.description || "";
   const creator = card.querySelector("[data-meta='creator']");
   creator.textContent = formatMeta("creator", task.creator);
   const assigned = card.querySelector("[data-meta='assigned_to']");
   assigned.textContent = formatMeta("assigned", task.assigned_to);
+  card.querySelector("[data-meta='due']").textContent = formatMeta("due", task.due);
+  if (STATUS_MODE === "free") {
+    card.querySelector("[data-meta='status']").textContent = formatMeta("status", task.status);
+  }
   card.querySelector("[data-meta='updated_at']").textContent = task.updated_at ? `updated: ${task.updated_at}` : "";
   card.querySelector("[data-meta='id']").textContent = task.id;
 
@@ -58,6 +89,17 @@ function renderTask(task) {
     tagsWrap.appendChild(span);
   });
 
+  const attachmentsWrap = card.querySelector(".card-attachments");
+  (task.attachments || []).forEach((attachment) => {
+    const link = document.createElement("a");
+    link.href = attachment.url;
+    link.target = "_blank";
+    link.rel = "noopener";
+    link.textContent = `📎 ${attachment.name}`;
+    attachmentsWrap.appendChild(link);
+  });
+
+  card.draggable = !STATIC_FILES;
   card.addEventListener("dragstart", (event) => {
     event.dataTransfer.setData("text/plain", task.id);
   });
@@ -67,6 +109,10 @@ function renderTask(task) {
     form.creator.value = task.creator || "";
     form.assigned_to.value = task.assigned_to || "";
     form.tags.value = (task.tags || []).join(", ");
+    form.color.value = task.color || "";
+    form.icon.value = task.icon || "";
+    form.due.value = task.due || "";
+    form.status.value = STATUS_MODE === "free" ? task.status || "" : "";
     form.description.value = task.description || "";
     editingTaskId = task.id;
     submitButton.textContent = "Update task";
@@ -83,42 +129,81 @@ function renderTask(task) {
     form.scrollIntoView({ behavior: "smooth", block: "start" });
   });
 
+  card.querySelector("[data-action='attach']").addEventListener("click", () => {
+    const picker = document.createElement("input");
+    picker.type = "file";
+    picker.addEventListener("change", async () => {
+      const file = picker.files[0];
+      if (!file) return;
+      try {
+        await api(`api/tasks/${task.id}/attachments?filename=${encodeURIComponent(file.name)}`, {
+          method: "POST",
+          headers: { "Content-Type": file.type || "application/octet-stream" },
+          body: file,
+        });
+        await loadTasks();
+      } catch (err) {
+        alert(`Upload failed: ${err.message}`);
+      }
+    });
+    picker.click();
+  });
+
+  card.querySelector("[data-action='export']").addEventListener("click", () => {
+    window.location.href = `api/tasks/${encodeURIComponent(task.id)}/export`;
+  });
+
   card.querySelector("[data-action='delete']").addEventListener("click", async () => {
     if (!confirm(`Delete ${task.title}?`)) return;
-    await api(`/api/tasks/${task.id}`, { method: "DELETE" });
+    await api(`api/tasks/${task.id}`, { method: "DELETE" });
     await loadTasks();
   });
 
   return card;
 }
 
+function sortBadge(column) {
+  if (!column.sort || column.sort === "manual") return "";
+  const arrow = column.sort_dir === "desc" ? "↓" : "↑";
+  return `<span class="sort-policy" title="Sorted by ${column.sort} (${column.sort_dir || "asc"})">${arrow} ${column.sort}</span>`;
+}
+
+function lockBadge(column) {
+  if (!column.locked) return "";
+  return `<span class="lock-badge" title="Locked: tasks cannot be added or removed">locked</span>`;
+}
+
 function renderBoard(columns) {
   board.innerHTML = "";
   columns.forEach((column) => {
     const section = document.createElement("section");
     section.className = "column";
     section.dataset.folder = column.id;
-    if (column.wip_limit) {
+    if (column.wip_limit != null) {
       section.dataset.wip = String(column.wip_limit);
     }
     section.innerHTML = `
       <header>
         <h3>${column.title}</h3>
+        ${lockBadge(column)}
+        ${sortBadge(column)}
         <span class="count" data-count="${column.id}">0</span>
       </header>
       <div class="column-body" data-dropzone="${column.id}"></div>
     `;
     board.appendChild(section);
   });
+  applyColumnColors();
 }
 
 function renderBoardEditor(columns) {
   boardRows.innerHTML = "";
   columns.forEach((column) => {
     const row = boardRowTemplate.content.firstElementChild.cloneNode(true);
+    row.dataset.column = JSON.stringify(column);
     row.querySelector("input[name='id']").value = column.id;
     row.querySelector("input[name='title']").value = column.title;
-    row.querySelector("input[name='wip_limit']").value = column.wip_limit || 0;
+    row.querySelector("input[name='wip_limit']").value = column.wip_limit ?? "";
     row.querySelector("[data-action='move-up']").addEventListener("click", () => {
       moveRow(row, "up");
     });
@@ -191,6 +276,28 @@ function writeUiPreference(key, value) {
   localStorage.setItem(key, value ? "true" : "false");
 }
 
+// "column": status mirrors the folder. "free": status is its own field,
+// optionally limited to the board's `statuses` list.
+let STATUS_MODE = "column";
+
+async function loadStatusMode() {
+  try {
+    const data = await api("api/ui");
+    STATUS_MODE = (data.board && data.board.status_mode) || "column";
+    const field = document.querySelector(".free-status");
+    field.hidden = STATUS_MODE !== "free";
+    const options = document.getElementById("status-options");
+    options.innerHTML = "";
+    ((data.board && data.board.statuses) || []).forEach((status) => {
+      const option = document.createElement("option");
+      option.value = status;
+      options.appendChild(option);
+    });
+  } catch (err) {
+    console.warn("Failed to load status mode", err);
+  }
+}
+
 async function loadUiDefaults() {
   try {
     const taskPref = readUiPreference("kanban.showTaskEditor");
@@ -206,7 +313,7 @@ async function loadUiDefaults() {
     });
       return;
     }
-    const data = await api("/api/ui");
+    const data = await api("api/ui");
     setEditorVisibility(taskEditor, data.show_task_editor, toggleTaskEditorButton, {
       show: "Show task editor",
       hide: "Hide task editor",
@@ -222,23 +329,61 @@ async function loadUiDefaults() {
   }
 }
 
+let appliedThemeColors = [];
+let themeColumnColors = {};
+
+function applyColumnColors() {
+  board.querySelectorAll(".column").forEach((section) => {
+    const color = themeColumnColors[section.dataset.folder];
+    if (color) {
+      section.style.setProperty("--column-color", color);
+      section.classList.add("has-color");
+    } else {
+      section.style.removeProperty("--column-color");
+      section.classList.remove("has-color");
+    }
+  });
+}
+
+function applyTheme(theme) {
+  if (theme.headline) {
+    headline.textContent = theme.headline;
+    document.title = theme.headline;
+  }
+  const colors = theme.colors || {};
+  appliedThemeColors.forEach((name) => document.documentElement.style.removeProperty(name));
+  appliedThemeColors = Object.entries(colors).map(([key, value]) => {
+    const name = `--${key.replace(/_/g, "-")}`;
+    document.documentElement.style.setProperty(name, value);
+    return name;
+  });
+  const root = document.documentElement.style;
+  if (theme.font_family) root.setProperty("--font-family", theme.font_family);
+  if (theme.font_size) root.setProperty("--font-size", theme.font_size);
+  if (theme.card_radius) root.setProperty("--card-radius", theme.card_radius);
+  document.body.dataset.density = theme.density || "comfortable";
+  themeColumnColors = theme.column_colors || {};
+  applyColumnColors();
+}
+
 async function loadThemeSettings() {
   try {
-    const data = await api("/api/theme");
-    const theme = data.theme || {};
-    if (theme.headline) {
-      headline.textContent = theme.headline;
-      document.title = theme.headline;
-    }
-    const colors = theme.colors || {};
-    Object.entries(colors).forEach(([key, value]) => {
-      document.documentElement.style.setProperty(`--${key.replace(/_/g, "-")}`, value);
-    });
+    const data = await api("api/theme");
+    applyTheme(data.theme || {});
   } catch (err) {
     console.warn("Failed to load theme settings", err);
   }
 }
 
+function listenForEvents() {
+  if (!window.EventSource) return;
+  const source = new EventSource("api/events");
+  source.addEventListener("theme_changed", (event) => {
+    const data = JSON.parse(event.data);
+    applyTheme(data.theme || {});
+  });
+}
+
 function getCardRects() {
   const rects = new Map();
   document.querySelectorAll(".card").forEach((card) => {
@@ -274,7 +419,7 @@ function animateCards(previousRects) {
 
 async function loadTasks() {
   const previousRects = getCardRects();
-  const data = await api("/api/tasks");
+  const data = await api("api/tasks");
   const snapshot = JSON.stringify(data.folders || {});
   const boardSnapshot = JSON.stringify(data.board || {});
   const boardChanged = boardSnapshot !== lastBoardSnapshot;
@@ -288,25 +433,33 @@ async function loadTasks() {
   }
   if (!boardChanged && snapshot === lastSnapshot) return;
   lastSnapshot = snapshot;
+  const meta = new Map((data.columns || []).map((c) => [c.id, c]));
   FOLDERS.forEach((folder) => {
     const column = board.querySelector(`[data-dropzone='${folder}']`);
     column.innerHTML = "";
     const tasks = (data.folders && data.folders[folder]) || [];
-    tasks.forEach((task) => column.appendChild(renderTask(task)));
+    const info = meta.get(folder) || { total: tasks.length, wip_limit: null, over_limit: false };
+    const overAge = new Set(info.over_age || []);
+    tasks.forEach((task) => {
+      const card = renderTask(task);
+      if (overAge.has(task.id)) {
+        card.classList.add("over-age");
+        card.title = `In this column longer than ${info.max_age}`;
+      }
+      column.appendChild(card);
+    });
     const count = document.querySelector(`[data-count='${folder}']`);
     const section = board.querySelector(`[data-folder='${folder}']`);
     if (section) {
-      const limit = Number(section.dataset.wip || 0);
-      if (limit > 0 && tasks.length > limit) {
-        section.classList.add("wip-over");
-      } else {
-        section.classList.remove("wip-over");
-      }
-      if (count) {
-        count.textContent = limit > 0 ? `${tasks.length}/${limit}` : `${tasks.length}`;
+      section.classList.toggle("wip-over", Boolean(info.over_limit));
+      section.classList.toggle("locked", Boolean(info.locked));
+    }
+    if (count) {
+      count.textContent = info.wip_limit != null ? `${info.total}/${info.wip_limit}` : `${info.total}`;
+      count.title = info.frozen ? "Frozen: accepts no new tasks" : "";
+      if (info.truncated) {
+        count.textContent += ` (showing ${info.returned})`;
       }
-    } else if (count) {
-      count.textContent = `${tasks.length}`;
     }
   });
   animateCards(previousRects);
@@ -327,7 +480,7 @@ function showToast(message) {
 
 async function listenForUpdates() {
   try {
-    const data = await api(`/api/updates?since=${updateVersion}`);
+    const data = await api(`api/updates?since=${updateVersion}`);
     if (data && typeof data.version === "number") {
       if (data.changed) {
         await loadTasks();
@@ -356,7 +509,7 @@ function setupDropzones() {
       const id = event.dataTransfer.getData("text/plain");
       if (!id) return;
       const folder = zone.dataset.dropzone;
-      await api(`/api/tasks/${id}/move`, {
+      await api(`api/tasks/${id}/move`, {
         method: "POST",
         body: JSON.stringify({ folder }),
       });
@@ -384,31 +537,41 @@ form.addEventListener("submit", async (event) => {
       .split(",")
       .map((t) => t.trim())
       .filter(Boolean),
-    status: FOLDERS[0] || "backlog",
+    color: formData.get("color") || "",
+    icon: formData.get("icon") || "",
+    due: formData.get("due") || "",
   };
+  // New tasks go to the board's default column (see default_column).
+  if (STATUS_MODE === "free") {
+    payload.status = formData.get("status") || "";
+  }
   if (editingTaskId) {
-    await api(`/api/tasks/${editingTaskId}`, { method: "PUT", body: JSON.stringify(payload) });
+    await api(`api/tasks/${editingTaskId}`, { method: "PUT", body: JSON.stringify(payload) });
     editingTaskId = null;
     submitButton.textContent = "Add to backlog";
   } else {
-    await api("/api/tasks", { method: "POST", body: JSON.stringify(payload) });
+    await api("api/tasks", { method: "POST", body: JSON.stringify(payload) });
   }
   form.reset();
   await loadTasks();
 });
 
-loadTasks().catch((err) => {
-  console.error(err);
-  alert("Failed to load tasks. Is the backend running?");
-});
+loadStatusMode()
+  .then(loadTasks)
+  .catch((err) => {
+    console.error(err);
+    alert("Failed to load tasks. Is the backend running?");
+  });
 
 loadUiDefaults();
 loadThemeSettings();
-listenForUpdates();
-
-setInterval(() => {
-  loadTasks().catch((err) => console.warn("Auto-refresh failed", err));
-}, AUTO_REFRESH_MS);
+if (!STATIC_FILES) {
+  listenForUpdates();
+  listenForEvents();
+  setInterval(() => {
+    loadTasks().catch((err) => console.warn("Auto-refresh failed", err));
+  }, AUTO_REFRESH_MS);
+}
 
 addColumnButton.addEventListener("click", () => {
   const row = boardRowTemplate.content.firstElementChild.cloneNode(true);
@@ -468,10 +631,13 @@ boardForm.addEventListener("submit", async (event) => {
       const title = row.querySelector("input[name='title']").value.trim();
       const wipRaw = row.querySelector("input[name='wip_limit']").value.trim();
       const wipLimit = Number.parseInt(wipRaw, 10);
+      const loaded = row.dataset.column ? JSON.parse(row.dataset.column) : {};
       return {
+        ...loaded,
         id,
         title: title || id,
-        wip_limit: Number.isFinite(wipLimit) && wipLimit > 0 ? wipLimit : 0,
+        // Empty means no limit; 0 freezes the column.
+        wip_limit: Number.isFinite(wipLimit) && wipLimit >= 0 ? wipLimit : null,
       };
     })
     .filter((col) => col.id.length > 0);
@@ -481,10 +647,27 @@ boardForm.addEventListener("submit", async (event) => {
     return;
   }
 
-  await api("/api/board", {
+  const pending = await api("api/board/pending-changes", {
+    method: "POST",
+    body: JSON.stringify({ columns }),
+  });
+  const plan = pending.plan || {};
+  const warnings = [];
+  (plan.remove_empty_folders || []).forEach((folder) => warnings.push(`remove empty folder "${folder}"`));
+  (plan.orphan_folders || []).forEach((orphan) =>
+    warnings.push(`leave ${orphan.tasks} task(s) in "${orphan.folder}" without a column (resolved on the server console)`)
+  );
+  if (warnings.length > 0 && !confirm(`Saving will:\n- ${warnings.join("\n- ")}\n\nContinue?`)) {
+    return;
+  }
+
+  const saved = await api("api/board", {
     method: "PUT",
     body: JSON.stringify({ columns }),
   });
+  if (saved.warnings && saved.warnings.length > 0) {
+    alert(saved.warnings.map((warning) => warning.message).join("\n"));
+  }
   await loadTasks();
 });
 